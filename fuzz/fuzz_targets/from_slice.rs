@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = rmp_serde::from_slice::<rmpv::Value>(data);
+    let _ = rmp_serde::from_slice::<Vec<(String, Vec<u8>)>>(data);
+
+    let mut de = rmp_serde::Deserializer::from_read_ref(data);
+    let _ = de.skip_value();
+});
@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = rmpv::decode::read_value(&mut &data[..]);
+    let borrowed = rmpv::decode::read_value_ref(&mut &data[..]);
+
+    // Anything the validator accepts must be decodable, unless it's nested too deep for rmpv.
+    if let (Ok(_), Err(err)) = (rmp::validate(data), &borrowed) {
+        assert!(matches!(err, rmpv::decode::Error::DepthLimitExceeded), "{err:?}");
+    }
+});
@@ -1,35 +1,69 @@
-#![feature(test)]
-
-extern crate test;
-
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 use serde::{Deserialize, Serialize};
 
-use test::Bencher;
+fn bench_strings(c: &mut Criterion) {
+    let mut group = c.benchmark_group("strings");
+    for size in [1000, 5000, 10000] {
+        let vec: Vec<String> = ::std::iter::repeat("abcdefghijklmnopqrstuvwxyz".into())
+            .take(size)
+            .collect();
 
-#[bench]
-fn bench_strings_1000(bencher: &mut Bencher) {
-    bench_strings(bencher, 1000);
-}
+        let mut buf = Vec::new();
+        vec.serialize(&mut rmp_serde::Serializer::new(&mut buf)).unwrap();
 
-#[bench]
-fn bench_strings_5000(bencher: &mut Bencher) {
-    bench_strings(bencher, 5000);
+        group.bench_with_input(BenchmarkId::from_parameter(size), &buf, |b, buf| {
+            b.iter(|| {
+                <Vec<String>>::deserialize(&mut rmp_serde::Deserializer::new(&buf[..])).unwrap();
+            });
+        });
+    }
+    group.finish();
 }
 
-#[bench]
-fn bench_strings_10000(bencher: &mut Bencher) {
-    bench_strings(bencher, 10000);
+fn bench_long_strings(c: &mut Criterion) {
+    let buf = long_strings();
+
+    let mut group = c.benchmark_group("long_strings");
+    group.throughput(Throughput::Bytes(buf.len() as u64));
+    group.bench_function("from_slice", |b| {
+        b.iter(|| {
+            <Vec<&str>>::deserialize(&mut rmp_serde::Deserializer::from_read_ref(&buf[..])).unwrap();
+        });
+    });
+    group.bench_function("from_read", |b| {
+        b.iter(|| {
+            <Vec<String>>::deserialize(&mut rmp_serde::Deserializer::new(&buf[..])).unwrap();
+        });
+    });
+    group.finish();
 }
 
-fn bench_strings(bencher: &mut Bencher, size: usize) {
-    let vec: Vec<String> = ::std::iter::repeat("abcdefghijklmnopqrstuvwxyz".into())
-        .take(size)
-        .collect();
+fn bench_long_bytes_from_read(c: &mut Criterion) {
+    let vec: Vec<serde_bytes::ByteBuf> = (0..100).map(|i| vec![i as u8; 64 * 1024].into()).collect();
 
     let mut buf = Vec::new();
     vec.serialize(&mut rmp_serde::Serializer::new(&mut buf)).unwrap();
 
-    bencher.iter(|| {
-        <Vec<String>>::deserialize(&mut rmp_serde::Deserializer::new(&buf[..])).unwrap();
+    let mut group = c.benchmark_group("long_bytes");
+    group.throughput(Throughput::Bytes(buf.len() as u64));
+    group.bench_function("from_read", |b| {
+        b.iter(|| {
+            <Vec<serde_bytes::ByteBuf>>::deserialize(&mut rmp_serde::Deserializer::new(&buf[..])).unwrap();
+        });
     });
+    group.finish();
 }
+
+/// Mixed ASCII and multi-byte text, long enough for validation to dominate decoding.
+fn long_strings() -> Vec<u8> {
+    let vec: Vec<String> = ::std::iter::repeat("MessagePack ist ein effizientes Binärformat — バイナリ形式. ".repeat(1000))
+        .take(100)
+        .collect();
+
+    let mut buf = Vec::new();
+    vec.serialize(&mut rmp_serde::Serializer::new(&mut buf)).unwrap();
+    buf
+}
+
+criterion_group!(benches, bench_strings, bench_long_strings, bench_long_bytes_from_read);
+criterion_main!(benches);
@@ -1,22 +1,22 @@
 /// Hacky serializer that only allows `u8`
 
-use std::fmt;
+use core::fmt;
 use serde::ser::Impossible;
 use serde::Serialize;
 
 pub(crate) struct OnlyBytes;
 pub(crate) struct Nope;
 
-impl std::error::Error for Nope {
+impl serde::ser::StdError for Nope {
 }
 
-impl std::fmt::Display for Nope {
+impl core::fmt::Display for Nope {
     fn fmt(&self, _: &mut fmt::Formatter<'_>) -> fmt::Result {
         Ok(())
     }
 }
 
-impl std::fmt::Debug for Nope {
+impl core::fmt::Debug for Nope {
     fn fmt(&self, _: &mut fmt::Formatter<'_>) -> fmt::Result {
         Ok(())
     }
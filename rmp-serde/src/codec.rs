@@ -0,0 +1,173 @@
+//! A [`tokio_util::codec`] implementation for streams of MessagePack values.
+//!
+//! [`RmpCodec`] splits a byte stream into consecutive MessagePack values, without any extra
+//! length prefix, and serializes or deserializes each of them as `T`. While a frame is
+//! incomplete, the length declared by its headers is remembered, so that a large value isn't
+//! rescanned every time a few more bytes arrive.
+//!
+//! This module is available with the `tokio-util` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use bytes::BytesMut;
+//! use serde::{Deserialize, Serialize};
+//! use tokio_util::codec::{Decoder, Encoder};
+//! use rmp_serde::codec::RmpCodec;
+//!
+//! #[derive(Debug, PartialEq, Deserialize, Serialize)]
+//! struct Msg {
+//!     id: u32,
+//!     body: String,
+//! }
+//!
+//! let mut codec = RmpCodec::<Msg>::default();
+//! let mut buf = BytesMut::new();
+//! codec.encode(Msg { id: 42, body: "le message".into() }, &mut buf).unwrap();
+//!
+//! // Only part of the frame has arrived.
+//! let mut rest = buf.split_off(5);
+//! assert_eq!(None, codec.decode(&mut buf).unwrap());
+//!
+//! buf.unsplit(rest);
+//! let msg = codec.decode(&mut buf).unwrap();
+//! assert_eq!(Some(Msg { id: 42, body: "le message".into() }), msg);
+//! assert!(buf.is_empty());
+//! ```
+
+use std::fmt::{self, Debug, Formatter};
+use std::marker::PhantomData;
+
+use ::bytes::{BufMut, BytesMut};
+use rmp::{ValidationError, MAX_VALIDATION_DEPTH};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::partial::frame_hint;
+use crate::{decode, encode};
+
+/// The default limit of the encoded length of a single frame, 8 MiB.
+pub const DEFAULT_MAX_FRAME_LEN: usize = 8 * 1024 * 1024;
+
+/// A codec that encodes and decodes values of type `T` as consecutive MessagePack values.
+///
+/// Values are encoded like [`to_vec`](crate::to_vec), i.e. structs are serialized as arrays.
+/// Frames whose encoded length exceeds the limit set with [`RmpCodec::with_max_frame_len`] are
+/// rejected with [`decode::Error::LengthLimitExceeded`] as soon as their headers are read.
+///
+/// A frame that fails to deserialize as `T` is still consumed, so decoding may go on with the
+/// next one. Malformed data, on the other hand, leaves the stream unusable.
+pub struct RmpCodec<T> {
+    /// Lower bound of the length of the frame at the start of the buffer.
+    needed: usize,
+    max_frame_len: usize,
+    _marker: PhantomData<fn(T) -> T>,
+}
+
+impl<T> RmpCodec<T> {
+    /// Creates a new codec with the default frame length limit.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        RmpCodec {
+            needed: 1,
+            max_frame_len: DEFAULT_MAX_FRAME_LEN,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Sets the maximum encoded length of a single frame, in bytes.
+    ///
+    /// The default is [`DEFAULT_MAX_FRAME_LEN`].
+    #[inline]
+    #[must_use]
+    pub fn with_max_frame_len(mut self, max_frame_len: usize) -> Self {
+        self.max_frame_len = max_frame_len;
+        self
+    }
+
+    /// Returns the maximum encoded length of a single frame, in bytes.
+    #[inline]
+    #[must_use]
+    pub fn max_frame_len(&self) -> usize {
+        self.max_frame_len
+    }
+
+    #[cold]
+    fn too_long(len: usize) -> decode::Error {
+        decode::Error::LengthLimitExceeded(u32::try_from(len).unwrap_or(u32::MAX))
+    }
+}
+
+impl<T> Default for RmpCodec<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for RmpCodec<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        RmpCodec {
+            needed: self.needed,
+            max_frame_len: self.max_frame_len,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Debug for RmpCodec<T> {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("RmpCodec")
+            .field("needed", &self.needed)
+            .field("max_frame_len", &self.max_frame_len)
+            .finish()
+    }
+}
+
+impl<T: DeserializeOwned> Decoder for RmpCodec<T> {
+    type Item = T;
+    type Error = decode::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<T>, decode::Error> {
+        if src.len() < self.needed {
+            src.reserve(self.needed - src.len());
+            return Ok(None);
+        }
+
+        let max_len = u32::try_from(self.max_frame_len).unwrap_or(u32::MAX);
+        let len = match rmp::validate_with_limits(src, MAX_VALIDATION_DEPTH, max_len) {
+            Ok(len) => len,
+            Err(ValidationError::Truncated) => {
+                self.needed = frame_hint(src).max(src.len() + 1);
+                if self.needed > self.max_frame_len {
+                    return Err(Self::too_long(self.needed));
+                }
+                src.reserve(self.needed - src.len());
+                return Ok(None);
+            }
+            Err(ValidationError::DepthLimitExceeded { .. }) => return Err(decode::Error::DepthLimitExceeded),
+            Err(ValidationError::LengthLimitExceeded { len, .. }) => {
+                return Err(decode::Error::LengthLimitExceeded(len))
+            }
+            Err(err) => return Err(decode::Error::Syntax(err.to_string())),
+        };
+        if len > self.max_frame_len {
+            return Err(Self::too_long(len));
+        }
+
+        self.needed = 1;
+        let frame = src.split_to(len);
+        crate::from_slice(&frame).map(Some)
+    }
+}
+
+impl<T: Serialize> Encoder<T> for RmpCodec<T> {
+    type Error = encode::Error;
+
+    fn encode(&mut self, item: T, dst: &mut BytesMut) -> Result<(), encode::Error> {
+        crate::to_writer(&mut dst.writer(), &item)
+    }
+}
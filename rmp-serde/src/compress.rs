@@ -0,0 +1,106 @@
+//! Serialization of compressed MessagePack, with zstd or LZ4 frames.
+//!
+//! Values are serialized straight into the compressor and deserialized straight from the
+//! decompressor, which both buffer internally, so no intermediate MessagePack buffer is needed.
+//! The helpers take care of finishing the compressed frame when writing, and of reading it to
+//! the end when reading, so that truncated or corrupted frames, and trailing data after the
+//! value, are reported as errors.
+//!
+//! The zstd helpers are available with the `zstd` feature, the LZ4 ones with the `lz4` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! # #[cfg(feature = "zstd")] {
+//! let buf = rmp_serde::compress::to_vec_zstd(&vec!["hello"; 100], 3).unwrap();
+//! assert!(buf.len() < 100);
+//!
+//! let val: Vec<String> = rmp_serde::compress::from_slice_zstd(&buf).unwrap();
+//! assert_eq!(vec!["hello"; 100], val);
+//! # }
+//! ```
+
+use std::io::{self, Read};
+
+use rmp::encode::ValueWriteError;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::{decode, encode, Deserializer, Serializer};
+
+/// Serializes the given data structure as MessagePack compressed into a zstd frame.
+///
+/// Structs are serialized as arrays, like [`to_vec`](crate::to_vec). The compression `level`
+/// ranges from 1 to 22, with 0 selecting zstd's default.
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to fail.
+#[cfg(feature = "zstd")]
+pub fn to_vec_zstd<T>(val: &T, level: i32) -> Result<Vec<u8>, encode::Error>
+where
+    T: Serialize + ?Sized,
+{
+    let mut wr = zstd::stream::Encoder::new(Vec::new(), level).map_err(write_error)?;
+    val.serialize(&mut Serializer::new(&mut wr))?;
+    wr.finish().map_err(write_error)
+}
+
+/// Deserializes an instance of type `T` from MessagePack compressed into a zstd frame.
+///
+/// # Errors
+///
+/// Fails if the frame is invalid or truncated, if the data doesn't match the structure expected
+/// by `T`, or with [`decode::Error::TrailingBytes`] if the frame contains more than one value.
+#[cfg(feature = "zstd")]
+pub fn from_slice_zstd<T>(buf: &[u8]) -> Result<T, decode::Error>
+where
+    T: DeserializeOwned,
+{
+    let rd = zstd::stream::Decoder::with_buffer(buf).map_err(decode::Error::InvalidDataRead)?;
+    from_read_to_end(rd)
+}
+
+/// Serializes the given data structure as MessagePack compressed into an LZ4 frame.
+///
+/// Structs are serialized as arrays, like [`to_vec`](crate::to_vec).
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to fail.
+#[cfg(feature = "lz4")]
+pub fn to_vec_lz4<T>(val: &T) -> Result<Vec<u8>, encode::Error>
+where
+    T: Serialize + ?Sized,
+{
+    let mut wr = lz4_flex::frame::FrameEncoder::new(Vec::new());
+    val.serialize(&mut Serializer::new(&mut wr))?;
+    wr.finish().map_err(|err| write_error(err.into()))
+}
+
+/// Deserializes an instance of type `T` from MessagePack compressed into an LZ4 frame.
+///
+/// # Errors
+///
+/// Fails if the frame is invalid or truncated, if the data doesn't match the structure expected
+/// by `T`, or with [`decode::Error::TrailingBytes`] if the frame contains more than one value.
+#[cfg(feature = "lz4")]
+pub fn from_slice_lz4<T>(buf: &[u8]) -> Result<T, decode::Error>
+where
+    T: DeserializeOwned,
+{
+    from_read_to_end(lz4_flex::frame::FrameDecoder::new(buf))
+}
+
+fn from_read_to_end<R: Read, T: DeserializeOwned>(rd: R) -> Result<T, decode::Error> {
+    let mut de = Deserializer::new(rd);
+    let val = T::deserialize(&mut de)?;
+    // Reading to the end makes the decompressor verify the end of the frame and its checksum.
+    de.end()?;
+    Ok(val)
+}
+
+#[cold]
+fn write_error(err: io::Error) -> encode::Error {
+    encode::Error::InvalidValueWrite(ValueWriteError::InvalidDataWrite(err))
+}
@@ -1,5 +1,8 @@
 //! Change MessagePack behavior with configuration wrappers.
 
+use alloc::vec::Vec;
+use core::fmt;
+
 /// Represents configuration that dicatates what the serializer does.
 ///
 /// Implemented as an empty trait depending on a hidden trait in order to allow changing the
@@ -9,7 +12,7 @@ pub trait SerializerConfig: sealed::SerializerConfig {}
 impl<T: sealed::SerializerConfig> SerializerConfig for T {}
 
 pub(crate) mod sealed {
-    use crate::config::BytesMode;
+    use crate::config::{BytesMode, CompatMode, FieldTable, FlattenMode, FloatMode, MapHook, OptionMode, VariantMode};
 
     /// This is the inner trait - the real `SerializerConfig`.
     ///
@@ -23,6 +26,36 @@ pub(crate) mod sealed {
         /// String struct fields
         fn is_named(&self) -> bool;
         fn bytes(&self) -> BytesMode;
+
+        /// Sort map entries by their encoded keys
+        fn is_canonical(&self) -> bool;
+
+        /// How to write structs with flattened fields in tuple mode
+        fn flatten(&self) -> FlattenMode;
+
+        /// Write integers with the width of their Rust type instead of the smallest one
+        fn is_fixed_int_width(&self) -> bool;
+
+        /// Reject values without a canonical encoding, and write the rest canonically
+        fn is_strict_canonical(&self) -> bool;
+
+        /// How to write `f64` values
+        fn floats(&self) -> FloatMode;
+
+        /// How to write `Some` values
+        fn options(&self) -> OptionMode;
+
+        /// How to identify enum variants
+        fn variants(&self) -> VariantMode;
+
+        /// Which version's layout to write
+        fn compat(&self) -> CompatMode;
+
+        /// Reorders or removes map entries before they are written
+        fn map_hook(&self) -> Option<MapHook>;
+
+        /// Field-name tables of structs written with field indices as keys
+        fn field_table(&self) -> Option<FieldTable>;
     }
 }
 
@@ -31,39 +64,261 @@ pub(crate) struct RuntimeConfig {
     pub(crate) is_human_readable: bool,
     pub(crate) is_named: bool,
     pub(crate) bytes: BytesMode,
+    pub(crate) is_canonical: bool,
+    pub(crate) flatten: FlattenMode,
+    pub(crate) is_fixed_int_width: bool,
+    pub(crate) is_strict_canonical: bool,
+    pub(crate) floats: FloatMode,
+    pub(crate) options: OptionMode,
+    pub(crate) variants: VariantMode,
+    pub(crate) compat: CompatMode,
+    pub(crate) map_hook: Option<MapHook>,
+    pub(crate) field_table: Option<FieldTable>,
 }
 
 /// When to encode `[u8]` as `bytes` rather than a sequence
 /// of integers. Serde without `serde_bytes` has trouble
 /// using `bytes`, and this is hack to force it. It may
 /// break some data types.
+///
+/// The deserializer accepts both encodings for `Vec<u8>` and
+/// other sequences, regardless of this mode.
 #[non_exhaustive]
 #[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
 pub enum BytesMode {
     /// Use bytes only when Serde requires it
     /// (typically only when `serde_bytes` is used)
-    #[default]
+    ///
+    /// This was the default of earlier versions, which wrote
+    /// `Vec<u8>` and `Box<[u8]>` as arrays of integers. Use it to
+    /// keep writing data that older readers expect.
     Normal,
-    /// Use bytes for slices, `Vec`, and a few other types that
-    /// use `Iterator` in Serde.
+    /// Use bytes for slices, `Vec`, `Box<[u8]>` and a few other
+    /// types that use `Iterator` in Serde.
     ///
     /// This may break some implementations of `Deserialize`.
     ///
     /// This does not include fixed-length arrays.
+    #[default]
     ForceIterables,
     /// Use bytes for everything that looks like a container of `u8`.
     /// This breaks some implementations of `Deserialize`.
     ForceAll,
 }
 
+/// How to encode structs that contain `#[serde(flatten)]` fields while structs are
+/// written as tuples.
+///
+/// Serde can't know the number of fields of such structs up front, so it always
+/// serializes them as maps of unknown length, with field names, no matter how other
+/// structs are encoded. This makes the layout differ from the compact tuple one.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum FlattenMode {
+    /// Write such structs as maps, leaving the encoding of their field values unchanged.
+    #[default]
+    Map,
+    /// Write such structs as maps and switch the whole subtree below them to
+    /// struct-as-map encoding, so that nested structs carry field names too.
+    NamedSubtree,
+    /// Fail with `Error::FlattenedStruct` instead of writing a map.
+    ///
+    /// Maps collected from iterators are written as usual. Note that this still rejects
+    /// hand-written `Serialize` impls calling `serialize_map(None)`, since the serializer
+    /// can't tell those apart from flattened structs.
+    Error,
+}
+
+/// How to encode `f64` values.
+///
+/// Both modes write floats bit-exactly, so NaN payloads and the sign of zero survive a
+/// round trip. `f32` values are always written as `float 32`.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum FloatMode {
+    /// Always write `f64` values as `float 64`.
+    #[default]
+    Exact,
+    /// Write `f64` values as `float 32` if this is lossless, i.e. if converting the value to
+    /// `f32` and back yields the same bits, and as `float 64` otherwise.
+    ///
+    /// This saves 4 bytes for values such as `1.5` or `-0.0`, but the receiver must accept
+    /// `float 32` where it expects a double.
+    Compact,
+}
+
+/// How to encode `Option` values.
+///
+/// By default `None` is written as nil and `Some(v)` as `v`, so `Some(None)` and `None` of an
+/// `Option<Option<T>>` are both nil and can't be told apart.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum OptionMode {
+    /// Write `None` as nil and `Some(v)` as `v`.
+    #[default]
+    Nil,
+    /// Write `None` as nil and `Some(v)` as a 1-element array `[v]`, so nested options
+    /// round-trip: `Some(None)` is `[nil]`.
+    ///
+    /// Data written this way must be read by a `Deserializer` with the same mode, which also
+    /// accepts options written in the default mode, unless `v` itself is a 1-element array.
+    Array,
+}
+
+/// How enum variants are identified when serialized.
+///
+/// Deserialization accepts both, as serde's derived implementations recognize variants by name
+/// and by index.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum VariantMode {
+    /// Write the name of the variant, as a string.
+    #[default]
+    Name,
+    /// Write the index of the variant in declaration order, as an integer.
+    ///
+    /// This matches protocols that encode enums as plain discriminants, e.g. C enums, as long as
+    /// the discriminants are consecutive and start at zero. Reordering variants changes the
+    /// encoding.
+    Index,
+}
+
+/// Compatibility with the wire format of earlier versions of this crate.
+///
+/// Data written by rmp-serde 0.8 lays enums out differently from the current format. The
+/// legacy mode writes and reads that layout, so such data can be read, and more of it written,
+/// without a migration. Strings and byte buffers are unaffected, as they are controlled by
+/// [`BytesMode`] and [`StrBinMode`].
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum CompatMode {
+    /// The current format: enum variants are written as `{variant: data}` maps, or as the bare
+    /// variant for unit variants.
+    #[default]
+    Current,
+    /// The format of rmp-serde 0.8: every enum value is written as a 2-element array of the
+    /// variant index and an array of its data, e.g. `[0, []]` for a unit variant and `[1, [42]]`
+    /// for a newtype variant.
+    ///
+    /// A `Deserializer` in this mode reads enums in both formats.
+    V08,
+}
+
+/// What to do with str values that aren't valid UTF-8.
+///
+/// The MessagePack spec requires str values to be UTF-8, but some producers write arbitrary bytes
+/// with str markers.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum Utf8Mode {
+    /// Give the raw bytes to the target type instead, and fail with `Error::Utf8Error` if it
+    /// doesn't accept bytes. This lets byte buffers and [`Raw`](crate::Raw) keep invalid data.
+    #[default]
+    Bytes,
+    /// Always fail with `Error::Utf8Error`.
+    Strict,
+    /// Replace invalid sequences with `U+FFFD REPLACEMENT CHARACTER` and give the target type
+    /// a string.
+    Lossy,
+}
+
+/// The entries of a map, each a pair of its encoded key and encoded value.
+pub type MapEntries<'a> = Vec<(&'a [u8], &'a [u8])>;
+
+/// A function that reorders or removes the entries of a map before it is written.
+///
+/// See [`Serializer::with_map_hook`](crate::Serializer::with_map_hook).
+#[derive(Copy, Clone)]
+pub struct MapHook(pub fn(&mut MapEntries<'_>));
+
+impl fmt::Debug for MapHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("MapHook")
+    }
+}
+
+/// A function that returns the field-name table of a struct, given the struct's name.
+///
+/// The position of a name in the table is the index that identifies the field, so tables should
+/// only ever be appended to. Only structs are looked up, struct variants of enums are written
+/// and read as usual. See
+/// [`Serializer::with_field_table`](crate::Serializer::with_field_table).
+#[derive(Copy, Clone)]
+pub struct FieldTable(pub fn(&str) -> Option<&'static [&'static str]>);
+
+impl fmt::Debug for FieldTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("FieldTable")
+    }
+}
+
+/// How `deserialize_any` should handle MessagePack ext values.
+///
+/// This only applies when the target type doesn't ask for an ext explicitly, e.g. when
+/// deserializing into a generic value type. Types such as [`Ext`](crate::Ext) always get the
+/// ext value, no matter which mode is selected.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum ExtMode {
+    /// Visit ext values as a newtype struct wrapping an `(i8, bytes)` tuple.
+    #[default]
+    Newtype,
+    /// Visit ext values as an `(i8, bytes)` tuple.
+    Tuple,
+    /// Skip over the ext data and visit a unit (`nil`) instead.
+    Skip,
+    /// Fail with `Error::TypeMismatch`.
+    Error,
+}
+
+/// Whether MessagePack str and bin values may be used interchangeably.
+///
+/// Producers following the old MessagePack spec, such as Python's `msgpack` with
+/// `use_bin_type=False`, write binary data with str markers.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum StrBinMode {
+    /// Str values are rejected by byte sequences such as `Vec<u8>`. Bin values are given to
+    /// string types as bytes, which they accept only if those are valid UTF-8.
+    #[default]
+    Strict,
+    /// Additionally allow str values to be deserialized into byte sequences.
+    Lenient,
+    /// Like `Lenient`, but strings are also read from str and bin values with invalid UTF-8,
+    /// replacing invalid sequences with `U+FFFD REPLACEMENT CHARACTER`.
+    LenientLossy,
+}
+
 impl RuntimeConfig {
     pub(crate) fn new(other: impl sealed::SerializerConfig) -> Self {
         Self {
             is_human_readable: other.is_human_readable(),
             is_named: other.is_named(),
             bytes: other.bytes(),
+            is_canonical: other.is_canonical(),
+            flatten: other.flatten(),
+            is_fixed_int_width: other.is_fixed_int_width(),
+            is_strict_canonical: other.is_strict_canonical(),
+            floats: other.floats(),
+            options: other.options(),
+            variants: other.variants(),
+            compat: other.compat(),
+            map_hook: other.map_hook(),
+            field_table: other.field_table(),
         }
     }
+
+    /// Whether maps have to be buffered, so that their entries can be rearranged on `end()`.
+    #[inline]
+    pub(crate) fn buffers_maps(&self) -> bool {
+        self.is_canonical || self.map_hook.is_some()
+    }
+
+    /// Whether integers are written with the width of their Rust type.
+    #[inline]
+    pub(crate) fn writes_fixed_ints(&self) -> bool {
+        self.is_fixed_int_width && !self.is_strict_canonical
+    }
 }
 
 impl sealed::SerializerConfig for RuntimeConfig {
@@ -81,6 +336,56 @@ impl sealed::SerializerConfig for RuntimeConfig {
     fn bytes(&self) -> BytesMode {
         self.bytes
     }
+
+    #[inline]
+    fn is_canonical(&self) -> bool {
+        self.is_canonical
+    }
+
+    #[inline]
+    fn flatten(&self) -> FlattenMode {
+        self.flatten
+    }
+
+    #[inline]
+    fn is_fixed_int_width(&self) -> bool {
+        self.is_fixed_int_width
+    }
+
+    #[inline]
+    fn is_strict_canonical(&self) -> bool {
+        self.is_strict_canonical
+    }
+
+    #[inline]
+    fn floats(&self) -> FloatMode {
+        self.floats
+    }
+
+    #[inline]
+    fn options(&self) -> OptionMode {
+        self.options
+    }
+
+    #[inline]
+    fn variants(&self) -> VariantMode {
+        self.variants
+    }
+
+    #[inline]
+    fn compat(&self) -> CompatMode {
+        self.compat
+    }
+
+    #[inline]
+    fn map_hook(&self) -> Option<MapHook> {
+        self.map_hook
+    }
+
+    #[inline]
+    fn field_table(&self) -> Option<FieldTable> {
+        self.field_table
+    }
 }
 
 /// The default serializer/deserializer configuration.
@@ -109,6 +414,56 @@ impl sealed::SerializerConfig for DefaultConfig {
     fn bytes(&self) -> BytesMode {
         BytesMode::default()
     }
+
+    #[inline(always)]
+    fn is_canonical(&self) -> bool {
+        false
+    }
+
+    #[inline(always)]
+    fn flatten(&self) -> FlattenMode {
+        FlattenMode::default()
+    }
+
+    #[inline(always)]
+    fn is_fixed_int_width(&self) -> bool {
+        false
+    }
+
+    #[inline(always)]
+    fn is_strict_canonical(&self) -> bool {
+        false
+    }
+
+    #[inline(always)]
+    fn floats(&self) -> FloatMode {
+        FloatMode::default()
+    }
+
+    #[inline(always)]
+    fn options(&self) -> OptionMode {
+        OptionMode::default()
+    }
+
+    #[inline(always)]
+    fn variants(&self) -> VariantMode {
+        VariantMode::default()
+    }
+
+    #[inline(always)]
+    fn compat(&self) -> CompatMode {
+        CompatMode::default()
+    }
+
+    #[inline(always)]
+    fn map_hook(&self) -> Option<MapHook> {
+        None
+    }
+
+    #[inline(always)]
+    fn field_table(&self) -> Option<FieldTable> {
+        None
+    }
 }
 
 /// Config wrapper, that overrides struct serialization by packing as a map with field names.
@@ -146,6 +501,46 @@ where
     fn bytes(&self) -> BytesMode {
         self.0.bytes()
     }
+
+    fn is_canonical(&self) -> bool {
+        self.0.is_canonical()
+    }
+
+    fn flatten(&self) -> FlattenMode {
+        self.0.flatten()
+    }
+
+    fn is_fixed_int_width(&self) -> bool {
+        self.0.is_fixed_int_width()
+    }
+
+    fn is_strict_canonical(&self) -> bool {
+        self.0.is_strict_canonical()
+    }
+
+    fn floats(&self) -> FloatMode {
+        self.0.floats()
+    }
+
+    fn options(&self) -> OptionMode {
+        self.0.options()
+    }
+
+    fn variants(&self) -> VariantMode {
+        self.0.variants()
+    }
+
+    fn compat(&self) -> CompatMode {
+        self.0.compat()
+    }
+
+    fn map_hook(&self) -> Option<MapHook> {
+        self.0.map_hook()
+    }
+
+    fn field_table(&self) -> Option<FieldTable> {
+        self.0.field_table()
+    }
 }
 
 /// Config wrapper that overrides struct serlization by packing as a tuple without field
@@ -178,6 +573,46 @@ where
     fn bytes(&self) -> BytesMode {
         self.0.bytes()
     }
+
+    fn is_canonical(&self) -> bool {
+        self.0.is_canonical()
+    }
+
+    fn flatten(&self) -> FlattenMode {
+        self.0.flatten()
+    }
+
+    fn is_fixed_int_width(&self) -> bool {
+        self.0.is_fixed_int_width()
+    }
+
+    fn is_strict_canonical(&self) -> bool {
+        self.0.is_strict_canonical()
+    }
+
+    fn floats(&self) -> FloatMode {
+        self.0.floats()
+    }
+
+    fn options(&self) -> OptionMode {
+        self.0.options()
+    }
+
+    fn variants(&self) -> VariantMode {
+        self.0.variants()
+    }
+
+    fn compat(&self) -> CompatMode {
+        self.0.compat()
+    }
+
+    fn map_hook(&self) -> Option<MapHook> {
+        self.0.map_hook()
+    }
+
+    fn field_table(&self) -> Option<FieldTable> {
+        self.0.field_table()
+    }
 }
 
 /// Config wrapper that overrides `Serializer::is_human_readable` and
@@ -210,6 +645,46 @@ where
     fn bytes(&self) -> BytesMode {
         self.0.bytes()
     }
+
+    fn is_canonical(&self) -> bool {
+        self.0.is_canonical()
+    }
+
+    fn flatten(&self) -> FlattenMode {
+        self.0.flatten()
+    }
+
+    fn is_fixed_int_width(&self) -> bool {
+        self.0.is_fixed_int_width()
+    }
+
+    fn is_strict_canonical(&self) -> bool {
+        self.0.is_strict_canonical()
+    }
+
+    fn floats(&self) -> FloatMode {
+        self.0.floats()
+    }
+
+    fn options(&self) -> OptionMode {
+        self.0.options()
+    }
+
+    fn variants(&self) -> VariantMode {
+        self.0.variants()
+    }
+
+    fn compat(&self) -> CompatMode {
+        self.0.compat()
+    }
+
+    fn map_hook(&self) -> Option<MapHook> {
+        self.0.map_hook()
+    }
+
+    fn field_table(&self) -> Option<FieldTable> {
+        self.0.field_table()
+    }
 }
 
 /// Config wrapper that overrides `Serializer::is_human_readable` and
@@ -242,4 +717,127 @@ where
     fn bytes(&self) -> BytesMode {
         self.0.bytes()
     }
+
+    fn is_canonical(&self) -> bool {
+        self.0.is_canonical()
+    }
+
+    fn flatten(&self) -> FlattenMode {
+        self.0.flatten()
+    }
+
+    fn is_fixed_int_width(&self) -> bool {
+        self.0.is_fixed_int_width()
+    }
+
+    fn is_strict_canonical(&self) -> bool {
+        self.0.is_strict_canonical()
+    }
+
+    fn floats(&self) -> FloatMode {
+        self.0.floats()
+    }
+
+    fn options(&self) -> OptionMode {
+        self.0.options()
+    }
+
+    fn variants(&self) -> VariantMode {
+        self.0.variants()
+    }
+
+    fn compat(&self) -> CompatMode {
+        self.0.compat()
+    }
+
+    fn map_hook(&self) -> Option<MapHook> {
+        self.0.map_hook()
+    }
+
+    fn field_table(&self) -> Option<FieldTable> {
+        self.0.field_table()
+    }
+}
+
+/// Whether a map may contain the same key more than once.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum DuplicateKeyMode {
+    /// Pass every entry to the target type, which usually keeps the last value of a key, or
+    /// fails for struct fields.
+    #[default]
+    Allow,
+    /// Fail with `Error::DuplicateKey` when a key occurs twice within the same map.
+    ///
+    /// Keys are compared by the scalar value they are decoded from, so `1` written as a
+    /// positive fixint and as a `uint 16` are equal, while a str and a bin with the same bytes
+    /// are not. Keys that are arrays, maps or ext values are not compared.
+    Reject,
+}
+
+/// How map keys are converted to the key type they are deserialized into.
+///
+/// MessagePack maps can have keys of any type, and producers in other languages often mix
+/// integer and string keys, or write numbers as strings.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum KeyPolicy {
+    /// Keys are passed to the key type as they were encoded, so an integer key can't be
+    /// deserialized into a `String`.
+    #[default]
+    Strict,
+    /// Convert map keys between numbers and strings when the key type asks for the other.
+    ///
+    /// Integer, float and boolean keys are passed as their decimal text to key types that
+    /// deserialize a string, and string keys holding a number are parsed for key types that
+    /// deserialize a number. In addition, maps can be deserialized as sequences of `(key, value)`
+    /// pairs, which keeps keys of any type and their order, for example into a `Vec<(K, V)>`.
+    ///
+    /// Struct field names are not converted, since fields can also be identified by index.
+    Lenient,
+}
+
+/// What happens when a struct is decoded from an array with more elements than it has fields.
+///
+/// Structs serialized with [`to_vec`](crate::to_vec) are written as arrays of their field values,
+/// identified only by position.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum ExtraFieldsMode {
+    /// Fail with `Error::FieldCountMismatch`.
+    #[default]
+    Reject,
+    /// Skip the extra elements, which allows reading data written after new fields were
+    /// appended to the struct.
+    Ignore,
+}
+
+/// How numbers are converted when the target type is a different kind of number.
+///
+/// By default integers can be read into floats, but floats can't be read into integers. This
+/// can get in the way with data from encoders that write every number as a float, like those
+/// for JavaScript.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum NumericCoercion {
+    /// Only convert numbers that fit the target type, and don't convert floats to integers.
+    #[default]
+    Exact,
+    /// Convert floats to integer types, discarding the fractional part.
+    Truncate,
+    /// Convert floats to integer types, rounding half-way cases away from zero.
+    Round,
+}
+
+/// What happens when a number doesn't fit the range of the numeric type it is read into.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum NumericOverflow {
+    /// Fail with `Error::OutOfRange`.
+    #[default]
+    Error,
+    /// Clamp the number to the smallest or largest value of the type.
+    ///
+    /// NaN still fails when read into an integer type.
+    Saturate,
 }
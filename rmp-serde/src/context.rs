@@ -0,0 +1,147 @@
+//! Externally supplied context for `Deserialize` implementations, e.g. a schema registry.
+//!
+//! `Deserialize::deserialize` takes nothing but the deserializer, so implementations that need
+//! more, like a registry to look up the schema of an ext type in, usually resort to globals.
+//! A [`DeserializerWithContext`] makes a context available for the duration of a single
+//! deserialization instead, and any implementation running within it, at any depth, can get it
+//! with [`context`].
+//!
+//! The context is kept in a thread-local, so deserializing on the same thread is all that's
+//! needed, and contexts of nested deserializations shadow those of outer ones. For context that
+//! only a single type needs, consider a [`DeserializeSeed`] instead.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::collections::HashMap;
+//!
+//! use rmp_serde::context::{self, DeserializerWithContext};
+//! use serde::{Deserialize, Deserializer};
+//!
+//! struct Registry(HashMap<u8, &'static str>);
+//!
+//! #[derive(Debug, PartialEq)]
+//! struct Kind(&'static str);
+//!
+//! impl<'de> Deserialize<'de> for Kind {
+//!     fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+//!         let id = u8::deserialize(de)?;
+//!         let registry = context::context::<Registry>().expect("no registry");
+//!         match registry.0.get(&id) {
+//!             Some(&name) => Ok(Kind(name)),
+//!             None => Err(serde::de::Error::custom("unknown kind")),
+//!         }
+//!     }
+//! }
+//!
+//! let registry = Registry(HashMap::from([(1, "user"), (2, "group")]));
+//! let buf = rmp_serde::to_vec(&[2, 1]).unwrap();
+//!
+//! let mut de = rmp_serde::Deserializer::new(&buf[..]);
+//! let kinds: Vec<Kind> = DeserializerWithContext::new(&mut de, registry).deserialize().unwrap();
+//! assert_eq!(vec![Kind("group"), Kind("user")], kinds);
+//! ```
+
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+use serde::de::{Deserialize, DeserializeSeed, Deserializer};
+
+use crate::decode::Error;
+use crate::scope::{self, Entry, Scope};
+
+/// Returns the innermost context of type `C` of the deserializations in progress on this thread.
+///
+/// Returns `None` outside of [`DeserializerWithContext`], or if no context is of type `C`.
+#[must_use]
+pub fn context<C: 'static>() -> Option<Rc<C>> {
+    scope::find_map(|entry| match entry {
+        Entry::Context(ctx) => ctx.clone().downcast::<C>().ok(),
+        _ => None,
+    })
+}
+
+/// A deserializer paired with a context that is available through [`context`] while it
+/// deserializes.
+///
+/// `D` is usually `&mut` [`Deserializer`](crate::Deserializer), but can be any deserializer.
+#[derive(Debug)]
+pub struct DeserializerWithContext<D, C> {
+    de: D,
+    ctx: Rc<C>,
+}
+
+impl<D, C: 'static> DeserializerWithContext<D, C> {
+    /// Pairs the given deserializer with a context.
+    #[inline]
+    pub fn new(de: D, ctx: C) -> Self {
+        Self::with_shared(de, Rc::new(ctx))
+    }
+
+    /// Pairs the given deserializer with a context shared with other deserializations.
+    #[inline]
+    pub fn with_shared(de: D, ctx: Rc<C>) -> Self {
+        DeserializerWithContext { de, ctx }
+    }
+
+    /// Returns a reference to the context.
+    #[inline]
+    pub fn context(&self) -> &C {
+        &self.ctx
+    }
+
+    /// Returns a reference to the underlying deserializer.
+    #[inline]
+    pub fn get_ref(&self) -> &D {
+        &self.de
+    }
+
+    /// Returns the underlying deserializer and the context, consuming this wrapper.
+    #[inline]
+    pub fn into_parts(self) -> (D, Rc<C>) {
+        (self.de, self.ctx)
+    }
+
+    /// Deserializes an instance of type `T` with the context available.
+    ///
+    /// # Errors
+    ///
+    /// Fails like `T::deserialize` with the underlying deserializer.
+    #[inline]
+    pub fn deserialize<'de, T>(self) -> Result<T, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de>,
+    {
+        self.deserialize_seed(PhantomData)
+    }
+
+    /// Deserializes a value using the given seed, with the context available.
+    ///
+    /// # Errors
+    ///
+    /// Fails like `seed.deserialize` with the underlying deserializer.
+    pub fn deserialize_seed<'de, S>(self, seed: S) -> Result<S::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+        S: DeserializeSeed<'de>,
+    {
+        let _scope = Scope::enter(Entry::Context(self.ctx));
+        seed.deserialize(self.de)
+    }
+}
+
+/// Deserializes an instance of type `T` from a slice, with the given context available through
+/// [`context`].
+///
+/// # Errors
+///
+/// Fails like [`from_slice`](crate::from_slice).
+pub fn from_slice_with_context<'a, T, C>(input: &'a [u8], ctx: C) -> Result<T, Error>
+where
+    T: Deserialize<'a>,
+    C: 'static,
+{
+    let mut de = crate::Deserializer::from_read_ref(input);
+    DeserializerWithContext::new(&mut de, ctx).deserialize()
+}
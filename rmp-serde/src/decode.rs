@@ -1,30 +1,42 @@
 //! Generic MessagePack deserialization.
 
 use crate::config::sealed::SerializerConfig as _;
-use std::convert::TryInto;
-use std::error;
-use std::fmt::{self, Display, Formatter};
-use std::io::{self, Cursor, ErrorKind, Read};
-use std::marker::PhantomData;
-use std::num::TryFromIntError;
-use std::str::{self, Utf8Error};
-
-use byteorder::{self, ReadBytesExt};
+use crate::io::{self, ErrorKind, Read};
+use alloc::borrow::{Cow, ToOwned};
+use alloc::boxed::Box;
+use alloc::collections::BTreeSet;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::convert::TryInto;
+use core::fmt::{self, Display, Formatter};
+use core::marker::PhantomData;
+use core::num::TryFromIntError;
+use core::str::{self, Utf8Error};
 
 use serde;
-use serde::de::value::SeqDeserializer;
-use serde::de::{self, Deserialize, DeserializeOwned, DeserializeSeed, Unexpected, Visitor};
+use serde::de::value::BorrowedStrDeserializer;
+use serde::de::{self, Deserialize, DeserializeOwned, DeserializeSeed, StdError, Unexpected, Visitor};
 use serde::forward_to_deserialize_any;
 
 use rmp;
 use rmp::decode::{self, DecodeStringError, MarkerReadError, NumValueReadError, RmpRead, ValueReadError};
 use rmp::Marker;
 
-use crate::config::{BinaryConfig, DefaultConfig, HumanReadableConfig, SerializerConfig};
+use crate::config::{
+    BinaryConfig, CompatMode, DefaultConfig, DuplicateKeyMode, ExtMode, ExtraFieldsMode, FieldTable,
+    HumanReadableConfig, KeyPolicy, NumericCoercion, NumericOverflow, OptionMode, SerializerConfig, StrBinMode,
+    Utf8Mode,
+};
+use crate::raw_value::RAW_VALUE_STRUCT_NAME;
+#[cfg(feature = "std")]
+use crate::intern::{self, Interner, INTERNED_STRUCT_NAME};
+use crate::registry::ExtRegistry;
 use crate::MSGPACK_EXT_STRUCT_NAME;
 
 /// Enum representing errors that can occur while decoding MessagePack data.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     /// The enclosed I/O error occurred while trying to read a MessagePack
     /// marker.
@@ -38,6 +50,11 @@ pub enum Error {
     OutOfRange,
     /// A decoded array did not have the enclosed expected length.
     LengthMismatch(u32),
+    /// A struct with the given name and number of fields was decoded from an array with the
+    /// given length.
+    ///
+    /// Extra elements can be skipped with [`ExtraFieldsMode::Ignore`].
+    FieldCountMismatch(&'static str, usize, u32),
     /// An otherwise uncategorized error occurred. See the enclosed `String` for
     /// details.
     Uncategorized(String),
@@ -48,15 +65,141 @@ pub enum Error {
     Utf8Error(Utf8Error),
     /// The depth limit was exceeded.
     DepthLimitExceeded,
+    /// A string, binary, ext, array or map header had the enclosed length, which exceeds the
+    /// configured limit.
+    LengthLimitExceeded(u32),
+    /// Decoding would read more than the enclosed total number of bytes, which is the
+    /// configured limit.
+    ByteLimitExceeded(u64),
+    /// A map contained the enclosed key more than once.
+    ///
+    /// Only returned with [`DuplicateKeyMode::Reject`].
+    DuplicateKey(String),
+    /// The value was followed by the enclosed number of unconsumed bytes.
+    ///
+    /// Returned by [`from_slice_exact`] and [`Deserializer::end`].
+    TrailingBytes(u64),
+    /// The enclosed error occurred in the value starting at the given byte offset.
+    ///
+    /// Created by [`Error::at`].
+    AtOffset(u64, Box<Error>),
+}
+
+impl Error {
+    /// Returns the category of this error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmp_serde::ErrorKind;
+    ///
+    /// // Truncated `[1, 2]`.
+    /// let err = rmp_serde::from_slice::<(u8, u8)>(&[0x92, 0x01]).unwrap_err();
+    /// assert_eq!(ErrorKind::UnexpectedEof, err.kind());
+    ///
+    /// let err = rmp_serde::from_slice::<u8>(&[0xa1, 0x61]).unwrap_err();
+    /// assert_eq!(ErrorKind::TypeMismatch, err.kind());
+    /// ```
+    #[must_use]
+    pub fn kind(&self) -> crate::ErrorKind {
+        match *self {
+            Error::InvalidMarkerRead(ref err) |
+            Error::InvalidDataRead(ref err) if err.kind() == ErrorKind::UnexpectedEof => crate::ErrorKind::UnexpectedEof,
+            Error::InvalidMarkerRead(..) | Error::InvalidDataRead(..) => crate::ErrorKind::Io,
+            Error::TypeMismatch(..) |
+            Error::OutOfRange |
+            Error::LengthMismatch(..) |
+            Error::FieldCountMismatch(..) => crate::ErrorKind::TypeMismatch,
+            Error::Utf8Error(..) => crate::ErrorKind::Utf8,
+            Error::DepthLimitExceeded | Error::LengthLimitExceeded(..) | Error::ByteLimitExceeded(..) => {
+                crate::ErrorKind::LimitExceeded
+            }
+            Error::Uncategorized(..) | Error::Syntax(..) | Error::DuplicateKey(..) | Error::TrailingBytes(..) => {
+                crate::ErrorKind::Syntax
+            }
+            Error::AtOffset(_, ref err) => err.kind(),
+        }
+    }
+
+    /// Returns the I/O error of the underlying reader that caused this error, if any.
+    #[must_use]
+    pub fn io_error(&self) -> Option<&io::Error> {
+        match *self {
+            Error::InvalidMarkerRead(ref err) | Error::InvalidDataRead(ref err) => Some(err),
+            Error::AtOffset(_, ref err) => err.io_error(),
+            _ => None,
+        }
+    }
+
+    /// Attaches the byte offset where this error occurred, usually taken from
+    /// [`Deserializer::marker_position`].
+    ///
+    /// Errors that already carry an offset are returned unchanged. [`from_slice`], [`from_read`]
+    /// and [`from_slice_exact`] do this for every error; callers driving a [`Deserializer`]
+    /// themselves can use it to report where decoding failed the same way.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    /// use rmp_serde::Deserializer;
+    ///
+    /// // Encoded `[1, "two"]`.
+    /// let buf = [0x92, 0x01, 0xa3, 0x74, 0x77, 0x6f];
+    ///
+    /// let mut de = Deserializer::from_read_ref(&buf);
+    /// let err = <(u8, u8)>::deserialize(&mut de).map_err(|err| err.at(de.marker_position())).unwrap_err();
+    /// assert_eq!(Some(2), err.offset());
+    /// assert_eq!("wrong msgpack marker FixStr(3) at byte 2", err.to_string());
+    /// ```
+    #[cold]
+    #[must_use]
+    pub fn at(self, offset: u64) -> Self {
+        match self {
+            Error::AtOffset(..) => self,
+            err => Error::AtOffset(offset, Box::new(err)),
+        }
+    }
+
+    /// Returns the byte offset attached with [`Error::at`], if any.
+    #[must_use]
+    pub fn offset(&self) -> Option<u64> {
+        match *self {
+            Error::AtOffset(offset, _) => Some(offset),
+            _ => None,
+        }
+    }
+
+    /// Removes the offset attached with [`Error::at`], returning the underlying error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmp_serde::decode::Error;
+    ///
+    /// // Encoded `[1, "two"]`.
+    /// let buf = [0x92, 0x01, 0xa3, 0x74, 0x77, 0x6f];
+    ///
+    /// let err = rmp_serde::from_slice::<(u8, u8)>(&buf).unwrap_err();
+    /// assert_eq!(Some(2), err.offset());
+    /// assert!(matches!(err.into_inner(), Error::TypeMismatch(..)));
+    /// ```
+    #[must_use]
+    pub fn into_inner(self) -> Self {
+        match self {
+            Error::AtOffset(_, err) => *err,
+            err => err,
+        }
+    }
 }
 
 macro_rules! depth_count(
     ( $counter:expr, $expr:expr ) => {
         {
-            $counter -= 1;
-            if $counter == 0 {
+            if $counter <= 1 {
                 return Err(Error::DepthLimitExceeded)
             }
+            $counter -= 1;
             let res = $expr;
             $counter += 1;
             res
@@ -64,19 +207,25 @@ macro_rules! depth_count(
     }
 );
 
-impl error::Error for Error {
+impl StdError for Error {
     #[cold]
-    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
         match *self {
             Error::TypeMismatch(..) => None,
             Error::InvalidMarkerRead(ref err) => Some(err),
             Error::InvalidDataRead(ref err) => Some(err),
             Error::LengthMismatch(..) => None,
+            Error::FieldCountMismatch(..) => None,
             Error::OutOfRange => None,
             Error::Uncategorized(..) => None,
             Error::Syntax(..) => None,
             Error::Utf8Error(ref err) => Some(err),
             Error::DepthLimitExceeded => None,
+            Error::ByteLimitExceeded(..) => None,
+            Error::LengthLimitExceeded(..) => None,
+            Error::DuplicateKey(..) => None,
+            Error::TrailingBytes(..) => None,
+            Error::AtOffset(_, ref err) => Some(&**err),
         }
     }
 }
@@ -102,23 +251,39 @@ impl Display for Error {
                 fmt,
                 "array had incorrect length, expected {expected_length}"
             ),
+            Error::FieldCountMismatch(name, fields, len) => write!(
+                fmt,
+                "struct {name} has {fields} fields, but the array has {len} elements"
+            ),
             Error::Uncategorized(ref msg) => write!(fmt, "uncategorized error: {msg}"),
             Error::Syntax(ref msg) => fmt.write_str(msg),
             Error::Utf8Error(ref err) => write!(fmt, "string found to be invalid utf8: {err}"),
             Error::DepthLimitExceeded => fmt.write_str("depth limit exceeded"),
+            Error::LengthLimitExceeded(len) => write!(fmt, "length {len} exceeds the limit"),
+            Error::ByteLimitExceeded(max) => write!(fmt, "input exceeds the limit of {max} bytes"),
+            Error::DuplicateKey(ref key) => write!(fmt, "duplicate map key {key}"),
+            Error::TrailingBytes(len) => write!(fmt, "{len} trailing bytes after the value"),
+            Error::AtOffset(offset, ref err) => write!(fmt, "{err} at byte {offset}"),
         }
     }
 }
 
-impl From<MarkerReadError> for Error {
+impl From<MarkerReadError<io::Error>> for Error {
     #[cold]
-    fn from(err: MarkerReadError) -> Error {
+    fn from(err: MarkerReadError<io::Error>) -> Error {
         match err {
             MarkerReadError(err) => Error::InvalidMarkerRead(err),
         }
     }
 }
 
+impl From<io::Error> for Error {
+    #[cold]
+    fn from(err: io::Error) -> Error {
+        Error::InvalidDataRead(err)
+    }
+}
+
 impl From<Utf8Error> for Error {
     #[cold]
     fn from(err: Utf8Error) -> Error {
@@ -126,9 +291,9 @@ impl From<Utf8Error> for Error {
     }
 }
 
-impl From<ValueReadError> for Error {
+impl From<ValueReadError<io::Error>> for Error {
     #[cold]
-    fn from(err: ValueReadError) -> Error {
+    fn from(err: ValueReadError<io::Error>) -> Error {
         match err {
             ValueReadError::TypeMismatch(marker) => Error::TypeMismatch(marker),
             ValueReadError::InvalidMarkerRead(err) => Error::InvalidMarkerRead(err),
@@ -137,9 +302,9 @@ impl From<ValueReadError> for Error {
     }
 }
 
-impl From<NumValueReadError> for Error {
+impl From<NumValueReadError<io::Error>> for Error {
     #[cold]
-    fn from(err: NumValueReadError) -> Error {
+    fn from(err: NumValueReadError<io::Error>) -> Error {
         match err {
             NumValueReadError::TypeMismatch(marker) => Error::TypeMismatch(marker),
             NumValueReadError::InvalidMarkerRead(err) => Error::InvalidMarkerRead(err),
@@ -149,9 +314,9 @@ impl From<NumValueReadError> for Error {
     }
 }
 
-impl<'a> From<DecodeStringError<'a>> for Error {
+impl<'a> From<DecodeStringError<'a, io::Error>> for Error {
     #[cold]
-    fn from(err: DecodeStringError<'_>) -> Error {
+    fn from(err: DecodeStringError<'_, io::Error>) -> Error {
         match err {
             DecodeStringError::InvalidMarkerRead(err) => Error::InvalidMarkerRead(err),
             DecodeStringError::InvalidDataRead(err) => Error::InvalidDataRead(err),
@@ -181,26 +346,55 @@ pub struct Deserializer<R, C = DefaultConfig> {
     _config: PhantomData<C>,
     is_human_readable: bool,
     marker: Option<Marker>,
+    marker_pos: u64,
     depth: u16,
-}
-
-impl<R: Read, C> Deserializer<R, C> {
+    ext_mode: ExtMode,
+    ext_registry: Option<ExtRegistry>,
+    #[cfg(feature = "std")]
+    interner: Option<Interner>,
+    str_bin_mode: StrBinMode,
+    utf8_mode: Utf8Mode,
+    duplicate_keys: DuplicateKeyMode,
+    key_policy: KeyPolicy,
+    field_table: Option<FieldTable>,
+    option_mode: OptionMode,
+    compat: CompatMode,
+    extra_fields: ExtraFieldsMode,
+    numeric_coercion: NumericCoercion,
+    numeric_overflow: NumericOverflow,
+    max_len: u32,
+    max_bytes: u64,
+}
+
+impl<'de, R: ReadSlice<'de>, C> Deserializer<R, C> {
     #[inline]
-    fn take_or_read_marker(&mut self) -> Result<Marker, MarkerReadError> {
-        self.marker
-            .take()
-            .map_or_else(|| rmp::decode::read_marker(&mut self.rd), Ok)
+    fn take_or_read_marker(&mut self) -> Result<Marker, Error> {
+        match self.marker.take() {
+            Some(m) => Ok(m),
+            None => self.read_marker(),
+        }
     }
 
     #[inline]
-    fn peek_or_read_marker(&mut self) -> Result<Marker, MarkerReadError> {
+    fn peek_or_read_marker(&mut self) -> Result<Marker, Error> {
         if let Some(m) = self.marker {
             Ok(m)
         } else {
-            let m = rmp::decode::read_marker(&mut self.rd)?;
+            let m = self.read_marker()?;
             Ok(self.marker.insert(m).to_owned())
         }
     }
+
+    #[inline]
+    fn read_marker(&mut self) -> Result<Marker, Error> {
+        if let Some(pos) = self.rd.position() {
+            if pos >= self.max_bytes {
+                return Err(Error::ByteLimitExceeded(self.max_bytes));
+            }
+            self.marker_pos = pos;
+        }
+        Ok(rmp::decode::read_marker(&mut self.rd)?)
+    }
 }
 
 impl<R: Read> Deserializer<ReadReader<R>, DefaultConfig> {
@@ -213,7 +407,24 @@ impl<R: Read> Deserializer<ReadReader<R>, DefaultConfig> {
             is_human_readable: DefaultConfig.is_human_readable(),
             // Cached marker in case of deserializing optional values.
             marker: None,
+            marker_pos: 0,
             depth: 1024,
+            ext_mode: ExtMode::Newtype,
+            ext_registry: None,
+            #[cfg(feature = "std")]
+            interner: None,
+            str_bin_mode: StrBinMode::Strict,
+            utf8_mode: Utf8Mode::Bytes,
+            duplicate_keys: DuplicateKeyMode::Allow,
+            key_policy: KeyPolicy::Strict,
+            field_table: None,
+            option_mode: OptionMode::Nil,
+            compat: CompatMode::Current,
+            extra_fields: ExtraFieldsMode::Reject,
+            numeric_coercion: NumericCoercion::Exact,
+            numeric_overflow: NumericOverflow::Error,
+            max_len: u32::MAX,
+            max_bytes: u64::MAX,
         }
     }
 }
@@ -236,6 +447,76 @@ impl<R: Read, C> Deserializer<ReadReader<R>, C> {
     pub fn into_inner(self) -> R {
         self.rd.rd
     }
+
+    /// Returns the current position of this deserializer, i.e. how many bytes were read.
+    #[inline(always)]
+    pub fn position(&self) -> u64 {
+        self.rd.pos
+    }
+
+    /// Returns the offset of the last marker read, i.e. where the value being decoded starts.
+    ///
+    /// After a failed deserialization this is the offset of the value that caused the failure.
+    /// See [`Error::at`] to attach it to the returned error.
+    #[inline(always)]
+    pub fn marker_position(&self) -> u64 {
+        self.marker_pos
+    }
+
+    /// Returns the number of bytes of the values deserialized so far.
+    ///
+    /// Unlike [`position`](Self::position), this doesn't count a marker that was read ahead but
+    /// not consumed yet, so after each complete value it is the offset of the next one. Like the
+    /// position, it starts at zero again on [`reset`](Self::reset).
+    #[inline]
+    pub fn bytes_consumed(&self) -> u64 {
+        self.position() - u64::from(self.marker.is_some())
+    }
+
+    /// Replaces the underlying reader, returning the previous one.
+    ///
+    /// This allows reusing one deserializer for many messages: the configuration is kept, and
+    /// so is the internal buffer used for strings and binaries that can't be borrowed, so that
+    /// it doesn't have to be allocated again for every message. Any peeked marker is discarded
+    /// and the [`position`](Self::position) starts at zero again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    ///
+    /// let messages = [rmp_serde::to_vec("first").unwrap(), rmp_serde::to_vec("second").unwrap()];
+    ///
+    /// let mut de = rmp_serde::Deserializer::new(&messages[0][..]);
+    /// assert_eq!("first", String::deserialize(&mut de).unwrap());
+    ///
+    /// de.reset(&messages[1][..]);
+    /// assert_eq!("second", String::deserialize(&mut de).unwrap());
+    /// ```
+    pub fn reset(&mut self, rd: R) -> R {
+        self.marker = None;
+        self.marker_pos = 0;
+        self.rd.pos = 0;
+        core::mem::replace(&mut self.rd.rd, rd)
+    }
+
+    /// Checks that the whole input has been consumed.
+    ///
+    /// This reads the rest of the stream until EOF, so it blocks on streams that are kept open,
+    /// such as sockets.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TrailingBytes`] with the number of bytes that remained, or
+    /// `Error::InvalidDataRead` if reading them fails.
+    pub fn end(&mut self) -> Result<(), Error> {
+        let peeked = u64::from(self.marker.take().is_some());
+        let len = skip_to_end(&mut self.rd).map_err(Error::InvalidDataRead)?;
+        match peeked + len {
+            0 => Ok(()),
+            len => Err(Error::TrailingBytes(len)),
+        }
+    }
 }
 
 impl<R: Read, C: SerializerConfig> Deserializer<R, C> {
@@ -246,13 +527,36 @@ impl<R: Read, C: SerializerConfig> Deserializer<R, C> {
     /// versions of `rmp-serde`.
     #[inline]
     pub fn with_human_readable(self) -> Deserializer<R, HumanReadableConfig<C>> {
-        let Deserializer { rd, _config: _, is_human_readable: _, marker, depth } = self;
+        let Deserializer {
+            rd, _config: _, is_human_readable: _, marker, marker_pos, depth, ext_mode, ext_registry, str_bin_mode, utf8_mode,
+            duplicate_keys, key_policy, field_table, option_mode, compat, extra_fields, numeric_coercion,
+            numeric_overflow, max_len, max_bytes,
+            #[cfg(feature = "std")]
+            interner,
+        } = self;
         Deserializer {
             rd,
             is_human_readable: true,
             _config: PhantomData,
             marker,
+            marker_pos,
             depth,
+            ext_mode,
+            ext_registry,
+            #[cfg(feature = "std")]
+            interner,
+            str_bin_mode,
+            utf8_mode,
+            duplicate_keys,
+            key_policy,
+            field_table,
+            option_mode,
+            compat,
+            extra_fields,
+            numeric_coercion,
+            numeric_overflow,
+            max_len,
+            max_bytes,
         }
     }
 
@@ -263,22 +567,140 @@ impl<R: Read, C: SerializerConfig> Deserializer<R, C> {
     /// representation.
     #[inline]
     pub fn with_binary(self) -> Deserializer<R, BinaryConfig<C>> {
-        let Deserializer { rd, _config: _, is_human_readable: _, marker, depth } = self;
+        let Deserializer {
+            rd, _config: _, is_human_readable: _, marker, marker_pos, depth, ext_mode, ext_registry, str_bin_mode, utf8_mode,
+            duplicate_keys, key_policy, field_table, option_mode, compat, extra_fields, numeric_coercion,
+            numeric_overflow, max_len, max_bytes,
+            #[cfg(feature = "std")]
+            interner,
+        } = self;
         Deserializer {
             rd,
             is_human_readable: false,
             _config: PhantomData,
             marker,
+            marker_pos,
             depth,
+            ext_mode,
+            ext_registry,
+            #[cfg(feature = "std")]
+            interner,
+            str_bin_mode,
+            utf8_mode,
+            duplicate_keys,
+            key_policy,
+            field_table,
+            option_mode,
+            compat,
+            extra_fields,
+            numeric_coercion,
+            numeric_overflow,
+            max_len,
+            max_bytes,
+        }
+    }
+
+    /// Changes whether types are deserialized from human-readable representations
+    /// (`Deserializer::is_human_readable` will return the given value).
+    ///
+    /// Unlike [`with_human_readable`](Self::with_human_readable) and
+    /// [`with_binary`](Self::with_binary), this allows choosing the representation at runtime
+    /// without changing the type of the deserializer.
+    ///
+    /// ```rust
+    /// use serde::Deserializer;
+    ///
+    /// let mut de = rmp_serde::Deserializer::from_read_ref(&[0xc0]);
+    /// de.set_human_readable(true);
+    /// assert!((&mut de).is_human_readable());
+    /// ```
+    #[inline]
+    pub fn set_human_readable(&mut self, human_readable: bool) {
+        self.is_human_readable = human_readable;
+    }
+}
+
+impl<'s, R: Read> Deserializer<ReadScratchReader<'s, R>, DefaultConfig> {
+    /// Constructs a new `Deserializer` by consuming the given reader, reading strings and
+    /// binaries into the given scratch buffer instead of allocating.
+    ///
+    /// Strings and binaries are then given to the visitor as `&str` and `&[u8]` borrowed from
+    /// the buffer, which is enough for types such as `heapless::String<N>` or `[u8; N]`, so data
+    /// can be deserialized without using the heap. Deserializing a `String` or `Vec<u8>` still
+    /// allocates in the visitor. Data longer than the buffer fails with
+    /// [`Error::InvalidDataRead`] holding an I/O error of kind [`ErrorKind::OutOfMemory`],
+    /// except when it is skipped. [`RawValue`](crate::RawValue)s can't be captured.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde::{Deserialize, Serialize};
+    /// use rmp_serde::decode::Error;
+    ///
+    /// #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    /// enum Unit {
+    ///     Celsius,
+    ///     Fahrenheit,
+    /// }
+    ///
+    /// // Unit variants are encoded by name, which is read into the scratch buffer.
+    /// let buf = rmp_serde::to_vec(&(7, Unit::Fahrenheit)).unwrap();
+    ///
+    /// let mut scratch = [0; 16];
+    /// let mut de = rmp_serde::Deserializer::from_read_scratch(&buf[..], &mut scratch);
+    /// assert_eq!((7, Unit::Fahrenheit), Deserialize::deserialize(&mut de).unwrap());
+    ///
+    /// let mut scratch = [0; 4];
+    /// let mut de = rmp_serde::Deserializer::from_read_scratch(&buf[..], &mut scratch);
+    /// let res: Result<(u8, Unit), Error> = Deserialize::deserialize(&mut de);
+    /// assert!(matches!(res, Err(Error::InvalidDataRead(..))));
+    /// ```
+    #[inline]
+    pub fn from_read_scratch(rd: R, scratch: &'s mut [u8]) -> Self {
+        Deserializer {
+            rd: ReadScratchReader { rd, scratch, pos: 0 },
+            is_human_readable: DefaultConfig.is_human_readable(),
+            _config: PhantomData,
+            marker: None,
+            marker_pos: 0,
+            depth: 1024,
+            ext_mode: ExtMode::Newtype,
+            ext_registry: None,
+            #[cfg(feature = "std")]
+            interner: None,
+            str_bin_mode: StrBinMode::Strict,
+            utf8_mode: Utf8Mode::Bytes,
+            duplicate_keys: DuplicateKeyMode::Allow,
+            key_policy: KeyPolicy::Strict,
+            field_table: None,
+            option_mode: OptionMode::Nil,
+            compat: CompatMode::Current,
+            extra_fields: ExtraFieldsMode::Reject,
+            numeric_coercion: NumericCoercion::Exact,
+            numeric_overflow: NumericOverflow::Error,
+            max_len: u32::MAX,
+            max_bytes: u64::MAX,
         }
     }
 }
 
-impl<R: AsRef<[u8]>> Deserializer<ReadReader<Cursor<R>>> {
+impl<R: Read, C> Deserializer<ReadScratchReader<'_, R>, C> {
+    /// Gets a reference to the underlying reader in this decoder.
+    #[inline(always)]
+    pub fn get_ref(&self) -> &R {
+        &self.rd.rd
+    }
+
+    /// Consumes this deserializer returning the underlying reader.
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.rd.rd
+    }
+
     /// Returns the current position of this deserializer, i.e. how many bytes were read.
     #[inline(always)]
     pub fn position(&self) -> u64 {
-        self.rd.rd.position()
+        self.rd.pos
     }
 }
 
@@ -294,7 +716,24 @@ where
             is_human_readable: DefaultConfig.is_human_readable(),
             _config: PhantomData,
             marker: None,
+            marker_pos: 0,
             depth: 1024,
+            ext_mode: ExtMode::Newtype,
+            ext_registry: None,
+            #[cfg(feature = "std")]
+            interner: None,
+            str_bin_mode: StrBinMode::Strict,
+            utf8_mode: Utf8Mode::Bytes,
+            duplicate_keys: DuplicateKeyMode::Allow,
+            key_policy: KeyPolicy::Strict,
+            field_table: None,
+            option_mode: OptionMode::Nil,
+            compat: CompatMode::Current,
+            extra_fields: ExtraFieldsMode::Reject,
+            numeric_coercion: NumericCoercion::Exact,
+            numeric_overflow: NumericOverflow::Error,
+            max_len: u32::MAX,
+            max_bytes: u64::MAX,
         }
     }
 
@@ -306,96 +745,790 @@ where
     }
 }
 
+impl<'de, R, C> Deserializer<ReadRefReader<'de, R>, C>
+where
+    R: AsRef<[u8]> + ?Sized,
+{
+    /// Returns the current position of this deserializer, i.e. how many bytes were read.
+    #[inline(always)]
+    pub fn position(&self) -> u64 {
+        (self.rd.whole_slice.as_ref().len() - self.rd.buf.len()) as u64
+    }
+
+    /// Returns the offset of the last marker read, i.e. where the value being decoded starts.
+    ///
+    /// After a failed deserialization this is the offset of the value that caused the failure.
+    /// See [`Error::at`] to attach it to the returned error.
+    #[inline(always)]
+    pub fn marker_position(&self) -> u64 {
+        self.marker_pos
+    }
+
+    /// Returns the number of bytes of the values deserialized so far.
+    ///
+    /// Unlike [`position`](Self::position), this doesn't count a marker that was read ahead but
+    /// not consumed yet, so after each complete value it is the offset of the next one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    ///
+    /// // Encoded `"a"`, `[1, 2]`.
+    /// let buf = [0xa1, 0x61, 0x92, 0x01, 0x02];
+    ///
+    /// let mut de = rmp_serde::Deserializer::from_read_ref(&buf);
+    /// String::deserialize(&mut de).unwrap();
+    /// assert_eq!(2, de.bytes_consumed());
+    /// Vec::<u8>::deserialize(&mut de).unwrap();
+    /// assert_eq!(5, de.bytes_consumed());
+    /// ```
+    #[inline]
+    pub fn bytes_consumed(&self) -> u64 {
+        self.position() - u64::from(self.marker.is_some())
+    }
+
+    /// Checks that the whole input slice has been consumed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TrailingBytes`] with the number of bytes that remained.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    /// use rmp_serde::decode::Error;
+    ///
+    /// let buf = [0x2a, 0xc0];
+    ///
+    /// let mut de = rmp_serde::Deserializer::from_read_ref(&buf);
+    /// assert_eq!(42, u8::deserialize(&mut de).unwrap());
+    /// assert!(matches!(de.end(), Err(Error::TrailingBytes(1))));
+    /// ```
+    pub fn end(&mut self) -> Result<(), Error> {
+        let len = self.rd.buf.len() as u64 + u64::from(self.marker.take().is_some());
+        match len {
+            0 => Ok(()),
+            len => Err(Error::TrailingBytes(len)),
+        }
+    }
+}
+
 impl<'de, R: ReadSlice<'de>, C: SerializerConfig> Deserializer<R, C> {
     /// Changes the maximum nesting depth that is allowed
     #[inline(always)]
     pub fn set_max_depth(&mut self, depth: usize) {
         self.depth = depth.min(u16::MAX as _) as u16;
     }
-}
 
-#[inline(never)]
-fn read_i128_marker<'de, R: ReadSlice<'de>>(marker: Marker, rd: &mut R) -> Result<i128, Error> {
-    Ok(match marker {
-        Marker::FixPos(val) => val.into(),
-        Marker::FixNeg(val) => val.into(),
-        Marker::U8 => rd.read_data_u8()?.into(),
-        Marker::U16 => rd.read_data_u16()?.into(),
-        Marker::U32 => rd.read_data_u32()?.into(),
-        Marker::U64 => rd.read_data_u64()?.into(),
-        Marker::I8 => rd.read_data_i8()?.into(),
-        Marker::I16 => rd.read_data_i16()?.into(),
-        Marker::I32 => rd.read_data_i32()?.into(),
-        Marker::I64 => rd.read_data_i64()?.into(),
-        Marker::Bin8 => {
-            let len = read_u8(&mut *rd)?;
-            read_128_buf(rd, len)?
-        },
-        Marker::FixArray(len) => {
-            read_128_buf(rd, len)?
-        },
-        marker => return Err(Error::TypeMismatch(marker)),
-    })
-}
+    /// Consumes this deserializer and returns it with the maximum nesting depth changed.
+    ///
+    /// Arrays, maps, ext values and enum variants each count as one level of nesting. Once the
+    /// limit is reached, deserialization fails with [`Error::DepthLimitExceeded`] instead of
+    /// recursing further, which protects against stack overflows on untrusted input.
+    ///
+    /// The default limit is 1024. Values above `u16::MAX` are clamped.
+    ///
+    /// Values that aren't deserialized, i.e. those skipped with `deserialize_ignored_any` or
+    /// [`skip_value`](Self::skip_value) and those captured as [`RawValue`](crate::RawValue), are
+    /// walked iteratively instead, so they don't grow the stack and aren't subject to this limit.
+    /// Dynamic values of untrusted depth can be read that way into an `rmpv::ext::DeepValue`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    /// use rmp_serde::decode::Error;
+    ///
+    /// // Encoded `[[[]]]`.
+    /// let buf = [0x91, 0x91, 0x90];
+    ///
+    /// let mut de = rmp_serde::Deserializer::from_read_ref(&buf).with_max_depth(2);
+    /// let res: Result<Vec<Vec<Vec<u8>>>, Error> = Deserialize::deserialize(&mut de);
+    /// assert!(matches!(res, Err(Error::DepthLimitExceeded)));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.set_max_depth(depth);
+        self
+    }
 
-fn read_128_buf<'de, R: ReadSlice<'de>>(rd: &mut R, len: u8) -> Result<i128, Error> {
-    if len != 16 {
-        return Err(Error::LengthMismatch(16));
+    /// Changes the maximum length of strings, binaries, ext values, arrays and maps that is
+    /// allowed.
+    #[inline(always)]
+    pub fn set_max_len(&mut self, len: usize) {
+        self.max_len = len.min(u32::MAX as _) as u32;
     }
-    let buf = match read_bin_data(rd, 16)? {
-        Reference::Borrowed(buf) => buf,
-        Reference::Copied(buf) => buf,
-    };
-    Ok(i128::from_be_bytes(buf.try_into().map_err(|_| Error::LengthMismatch(16))?))
-}
 
-fn read_str_data<'de, V, R>(rd: &mut R, len: u32, visitor: V) -> Result<V::Value, Error>
-    where V: Visitor<'de>, R: ReadSlice<'de>
-{
-    match read_bin_data(rd, len)? {
-        Reference::Borrowed(buf) => {
-            match str::from_utf8(buf) {
-                Ok(s) => visitor.visit_borrowed_str(s),
-                Err(err) => {
-                    // Allow to unpack invalid UTF-8 bytes into a byte array.
-                    match visitor.visit_borrowed_bytes::<Error>(buf) {
-                        Ok(buf) => Ok(buf),
-                        Err(..) => Err(Error::Utf8Error(err)),
-                    }
-                }
-            }
+    /// Consumes this deserializer and returns it with the maximum length of strings, binaries,
+    /// ext values, arrays and maps changed.
+    ///
+    /// Headers declaring a longer value fail with [`Error::LengthLimitExceeded`] before anything
+    /// is read or allocated for it. Together with [`with_max_depth`](Self::with_max_depth), this
+    /// bounds the work done for each value of untrusted input. To bound the total size of the
+    /// input, use [`with_max_bytes`](Self::with_max_bytes).
+    ///
+    /// By default there is no limit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    /// use rmp_serde::decode::Error;
+    ///
+    /// // A `bin 32` header claiming 4 GiB of data.
+    /// let buf = [0xc6, 0xff, 0xff, 0xff, 0xff];
+    ///
+    /// let mut de = rmp_serde::Deserializer::new(&buf[..]).with_max_len(1024);
+    /// let res: Result<serde_bytes::ByteBuf, Error> = Deserialize::deserialize(&mut de);
+    /// assert!(matches!(res, Err(Error::LengthLimitExceeded(0xffff_ffff))));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_max_len(mut self, len: usize) -> Self {
+        self.set_max_len(len);
+        self
+    }
+
+    /// Changes the maximum total number of bytes this deserializer is allowed to read.
+    #[inline(always)]
+    pub fn set_max_bytes(&mut self, max: u64) {
+        self.max_bytes = max;
+    }
+
+    /// Consumes this deserializer and returns it with the maximum total number of bytes it is
+    /// allowed to read changed.
+    ///
+    /// The budget is shared by all values read, so unlike [`with_max_len`](Self::with_max_len)
+    /// it also bounds input made of many values which are each within the length limit. Headers
+    /// declaring more data or elements than the remaining budget fail with
+    /// [`Error::ByteLimitExceeded`] before anything is read or allocated for them.
+    ///
+    /// The budget is only enforced for readers which keep track of their position, see
+    /// [`ReadSlice::position`]. All readers of this crate do. By default there is no limit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    /// use rmp_serde::decode::Error;
+    ///
+    /// // Encoded `[1, 2, 3, 4]`, each element well within any length limit.
+    /// let buf = [0x94, 0x01, 0x02, 0x03, 0x04];
+    ///
+    /// let mut de = rmp_serde::Deserializer::new(&buf[..]).with_max_bytes(3);
+    /// let res: Result<Vec<u8>, Error> = Deserialize::deserialize(&mut de);
+    /// assert!(matches!(res, Err(Error::ByteLimitExceeded(3))));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_max_bytes(mut self, max: u64) -> Self {
+        self.set_max_bytes(max);
+        self
+    }
+
+    /// Checks the length of a header against the length limit and the remaining byte budget.
+    ///
+    /// Every element of an array or map takes at least one byte, so the budget bounds their
+    /// lengths as well.
+    #[inline]
+    fn check_len(&self, len: u32) -> Result<u32, Error> {
+        if len > self.max_len {
+            return Err(Error::LengthLimitExceeded(len));
         }
-        Reference::Copied(buf) => {
-            match str::from_utf8(buf) {
-                Ok(s) => visitor.visit_str(s),
-                Err(err) => {
-                    // Allow to unpack invalid UTF-8 bytes into a byte array.
-                    match visitor.visit_bytes::<Error>(buf) {
-                        Ok(buf) => Ok(buf),
-                        Err(..) => Err(Error::Utf8Error(err)),
-                    }
-                }
+        if let Some(pos) = self.rd.position() {
+            if u64::from(len) > self.max_bytes.saturating_sub(pos) {
+                return Err(Error::ByteLimitExceeded(self.max_bytes));
             }
         }
+        Ok(len)
     }
-}
 
-fn read_bin_data<'a, 'de, R: ReadSlice<'de>>(rd: &'a mut R, len: u32) -> Result<Reference<'de, 'a, [u8]>, Error> {
-    rd.read_slice(len as usize).map_err(Error::InvalidDataRead)
-}
+    /// Changes how ext values are handled when the target type accepts any value.
+    #[inline(always)]
+    pub fn set_ext_mode(&mut self, mode: ExtMode) {
+        self.ext_mode = mode;
+    }
 
-fn read_u8<R: Read>(rd: &mut R) -> Result<u8, Error> {
-    byteorder::ReadBytesExt::read_u8(rd).map_err(Error::InvalidDataRead)
-}
+    /// Consumes this deserializer and returns it with the given [`ExtMode`].
+    ///
+    /// This allows decoding data from producers that use ext types the target type doesn't
+    /// know about, e.g. into a `serde_json::Value` or another self-describing value type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    /// use rmp_serde::config::ExtMode;
+    ///
+    /// // Encoded `[1, <ext 5: [0xaa]>]`.
+    /// let buf = [0x92, 0x01, 0xd4, 0x05, 0xaa];
+    ///
+    /// let mut de = rmp_serde::Deserializer::from_read_ref(&buf).with_ext_mode(ExtMode::Skip);
+    /// let val: (u8, ()) = Deserialize::deserialize(&mut de).unwrap();
+    /// assert_eq!((1, ()), val);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_ext_mode(mut self, mode: ExtMode) -> Self {
+        self.set_ext_mode(mode);
+        self
+    }
+
+    /// Sets the registry of types that ext values are decoded into.
+    #[inline]
+    pub fn set_ext_registry(&mut self, registry: ExtRegistry) {
+        self.ext_registry = Some(registry);
+    }
+
+    /// Consumes this deserializer and returns it with the given [`ExtRegistry`].
+    ///
+    /// Ext values of registered types are then decoded into those types wherever the target type
+    /// doesn't ask for an ext explicitly, e.g. in `deserialize_any`. See [`ExtRegistry`] for an
+    /// example.
+    #[inline]
+    #[must_use]
+    pub fn with_ext_registry(mut self, registry: ExtRegistry) -> Self {
+        self.set_ext_registry(registry);
+        self
+    }
+
+    /// Sets the interner that shares the allocations of repeated strings.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn set_interner(&mut self, interner: Interner) {
+        self.interner = Some(interner);
+    }
+
+    /// Consumes this deserializer and returns it with the given [`Interner`].
+    ///
+    /// Strings deserialized into [`Interned`](crate::intern::Interned) values, or into `Arc<str>`
+    /// fields annotated with `#[serde(with = "rmp_serde::intern")]`, then share one allocation per
+    /// distinct string. Targets such as `String` own their data and are not affected. See the
+    /// [`intern`](crate::intern) module for an example.
+    #[cfg(feature = "std")]
+    #[inline]
+    #[must_use]
+    pub fn with_interner(mut self, interner: Interner) -> Self {
+        self.set_interner(interner);
+        self
+    }
+
+    /// Changes whether str and bin values may be used interchangeably.
+    #[inline(always)]
+    pub fn set_str_bin_mode(&mut self, mode: StrBinMode) {
+        self.str_bin_mode = mode;
+    }
+
+    /// Consumes this deserializer and returns it with the given [`StrBinMode`].
+    ///
+    /// This allows decoding data from producers that don't distinguish strings from binary data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    /// use rmp_serde::config::StrBinMode;
+    ///
+    /// // Encoded `["ab", <bin [0xff]>]`.
+    /// let buf = [0x92, 0xa2, 0x61, 0x62, 0xc4, 0x01, 0xff];
+    ///
+    /// let mut de = rmp_serde::Deserializer::from_read_ref(&buf).with_str_bin_mode(StrBinMode::LenientLossy);
+    /// let val: (Vec<u8>, String) = Deserialize::deserialize(&mut de).unwrap();
+    /// assert_eq!((b"ab".to_vec(), "\u{fffd}".to_owned()), val);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_str_bin_mode(mut self, mode: StrBinMode) -> Self {
+        self.set_str_bin_mode(mode);
+        self
+    }
+
+    /// Changes what happens to str values that aren't valid UTF-8.
+    #[inline(always)]
+    pub fn set_utf8_mode(&mut self, mode: Utf8Mode) {
+        self.utf8_mode = mode;
+    }
+
+    /// Consumes this deserializer and returns it with the given [`Utf8Mode`].
+    ///
+    /// This chooses the behavior for the whole input, instead of wrapping every affected field
+    /// in [`Raw`](crate::Raw). Bin values read as strings are not affected, see
+    /// [`StrBinMode::LenientLossy`] for those.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    /// use rmp_serde::config::Utf8Mode;
+    ///
+    /// // Encoded `["a\xff"]`.
+    /// let buf = [0x91, 0xa2, 0x61, 0xff];
+    ///
+    /// let mut de = rmp_serde::Deserializer::from_read_ref(&buf).with_utf8_mode(Utf8Mode::Lossy);
+    /// let val: Vec<String> = Deserialize::deserialize(&mut de).unwrap();
+    /// assert_eq!(vec!["a\u{fffd}".to_owned()], val);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_utf8_mode(mut self, mode: Utf8Mode) -> Self {
+        self.set_utf8_mode(mode);
+        self
+    }
+
+    /// Changes whether maps may contain the same key more than once.
+    #[inline(always)]
+    pub fn set_duplicate_keys(&mut self, mode: DuplicateKeyMode) {
+        self.duplicate_keys = mode;
+    }
+
+    /// Consumes this deserializer and returns it with the given [`DuplicateKeyMode`].
+    ///
+    /// By default a repeated key is passed to the target type like any other, and maps usually
+    /// keep the last value. Rejecting such input avoids ambiguities when the same message is
+    /// also read by other decoders, which may keep the first value instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use serde::Deserialize;
+    /// use rmp_serde::config::DuplicateKeyMode;
+    /// use rmp_serde::decode::Error;
+    ///
+    /// // Encoded `{"a": 1, "a": 2}`.
+    /// let buf = [0x82, 0xa1, 0x61, 0x01, 0xa1, 0x61, 0x02];
+    ///
+    /// let mut de = rmp_serde::Deserializer::from_read_ref(&buf).with_duplicate_keys(DuplicateKeyMode::Reject);
+    /// let res: Result<HashMap<String, u8>, Error> = Deserialize::deserialize(&mut de);
+    /// assert_eq!("duplicate map key \"a\"", res.unwrap_err().to_string());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_duplicate_keys(mut self, mode: DuplicateKeyMode) -> Self {
+        self.set_duplicate_keys(mode);
+        self
+    }
+
+    /// Changes how map keys are converted to the key type.
+    #[inline(always)]
+    pub fn set_key_policy(&mut self, policy: KeyPolicy) {
+        self.key_policy = policy;
+    }
+
+    /// Consumes this deserializer and returns it with the given [`KeyPolicy`].
+    ///
+    /// With [`KeyPolicy::Lenient`], maps written by other languages with a mix of integer and
+    /// string keys can be deserialized into maps with `String` keys, or into a `Vec<(K, V)>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::BTreeMap;
+    /// use serde::Deserialize;
+    /// use rmp_serde::KeyPolicy;
+    ///
+    /// // Encoded `{1: 2, "a": 3}`.
+    /// let buf = [0x82, 0x01, 0x02, 0xa1, 0x61, 0x03];
+    ///
+    /// let mut de = rmp_serde::Deserializer::from_read_ref(&buf).with_key_policy(KeyPolicy::Lenient);
+    /// let val: BTreeMap<String, u8> = Deserialize::deserialize(&mut de).unwrap();
+    /// assert_eq!(vec![("1", 2), ("a", 3)], val.iter().map(|(k, &v)| (k.as_str(), v)).collect::<Vec<_>>());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_key_policy(mut self, policy: KeyPolicy) -> Self {
+        self.set_key_policy(policy);
+        self
+    }
+
+    /// Sets the field-name tables of structs written with field indices as keys.
+    #[inline]
+    pub fn set_field_table(&mut self, table: fn(&str) -> Option<&'static [&'static str]>) {
+        self.field_table = Some(FieldTable(table));
+    }
+
+    /// Consumes this deserializer and returns it with the given field-name tables.
+    ///
+    /// Integer keys of maps read into structs that `table` knows are replaced by the field name
+    /// at that index of their table, so data written with
+    /// [`Serializer::with_field_table`](crate::Serializer::with_field_table) can be read back.
+    /// Keys that are already field names are read as usual. An index beyond the end of the table
+    /// fails with `Error::Syntax`. Struct variants of enums don't use the table, like on the
+    /// writing side.
+    #[inline]
+    #[must_use]
+    pub fn with_field_table(mut self, table: fn(&str) -> Option<&'static [&'static str]>) -> Self {
+        self.set_field_table(table);
+        self
+    }
+
+    /// Changes how `Option` values are read.
+    #[inline(always)]
+    pub fn set_option_mode(&mut self, mode: OptionMode) {
+        self.option_mode = mode;
+    }
+
+    /// Consumes this deserializer and returns it with the given [`OptionMode`].
+    ///
+    /// With [`OptionMode::Array`], a 1-element array is unwrapped when an `Option` is expected,
+    /// which reads options written by a `Serializer` in the same mode, including nested ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    /// use rmp_serde::config::OptionMode;
+    ///
+    /// // `Some(None)` and `None`.
+    /// let buf = [0x92, 0x91, 0xc0, 0xc0];
+    ///
+    /// let mut de = rmp_serde::Deserializer::from_read_ref(&buf).with_option_mode(OptionMode::Array);
+    /// let val: Vec<Option<Option<u8>>> = Deserialize::deserialize(&mut de).unwrap();
+    /// assert_eq!(vec![Some(None), None], val);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_option_mode(mut self, mode: OptionMode) -> Self {
+        self.set_option_mode(mode);
+        self
+    }
+
+    /// Changes which versions' wire formats are read.
+    #[inline(always)]
+    pub fn set_compat_mode(&mut self, mode: CompatMode) {
+        self.compat = mode;
+    }
+
+    /// Consumes this deserializer and returns it with the given [`CompatMode`].
+    ///
+    /// With [`CompatMode::V08`], enums written by rmp-serde 0.8 as `[index, [data...]]` arrays
+    /// are read, as well as enums in the current format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    /// use rmp_serde::config::CompatMode;
+    ///
+    /// #[derive(Debug, PartialEq, Deserialize)]
+    /// enum Event {
+    ///     Idle,
+    ///     Moved(u8),
+    /// }
+    ///
+    /// // `[Idle, Moved(42)]`, as written by rmp-serde 0.8.
+    /// let buf = [0x92, 0x92, 0x00, 0x90, 0x92, 0x01, 0x91, 0x2a];
+    ///
+    /// let mut de = rmp_serde::Deserializer::from_read_ref(&buf).with_compat_mode(CompatMode::V08);
+    /// let val: Vec<Event> = Deserialize::deserialize(&mut de).unwrap();
+    /// assert_eq!(vec![Event::Idle, Event::Moved(42)], val);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_compat_mode(mut self, mode: CompatMode) -> Self {
+        self.set_compat_mode(mode);
+        self
+    }
+
+    /// Changes what happens when a struct is read from an array with more elements than fields.
+    #[inline(always)]
+    pub fn set_extra_fields(&mut self, mode: ExtraFieldsMode) {
+        self.extra_fields = mode;
+    }
+
+    /// Consumes this deserializer and returns it with the given [`ExtraFieldsMode`].
+    ///
+    /// With [`ExtraFieldsMode::Ignore`], structs written as arrays by a newer version of the
+    /// program, which appended fields to them, can still be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    /// use rmp_serde::config::ExtraFieldsMode;
+    ///
+    /// #[derive(Debug, PartialEq, Deserialize)]
+    /// struct Point {
+    ///     x: u8,
+    ///     y: u8,
+    /// }
+    ///
+    /// // Encoded `[1, 2, 3]`, written by a `Point` that gained a `z` field.
+    /// let buf = [0x93, 0x01, 0x02, 0x03];
+    ///
+    /// let err = rmp_serde::from_slice::<Point>(&buf).unwrap_err();
+    /// assert_eq!("struct Point has 2 fields, but the array has 3 elements at byte 2", err.to_string());
+    ///
+    /// let mut de = rmp_serde::Deserializer::from_read_ref(&buf).with_extra_fields(ExtraFieldsMode::Ignore);
+    /// assert_eq!(Point { x: 1, y: 2 }, Point::deserialize(&mut de).unwrap());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_extra_fields(mut self, mode: ExtraFieldsMode) -> Self {
+        self.set_extra_fields(mode);
+        self
+    }
+
+    /// Changes how numbers are converted to numeric types of a different kind.
+    #[inline(always)]
+    pub fn set_numeric_coercion(&mut self, coercion: NumericCoercion) {
+        self.numeric_coercion = coercion;
+    }
+
+    /// Consumes this deserializer and returns it with the given [`NumericCoercion`].
+    ///
+    /// This allows reading floats into integer types, for example from encoders that write
+    /// every number as a float. Whether floats that don't fit are an error is controlled by
+    /// [`with_numeric_overflow`](Self::with_numeric_overflow).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    /// use rmp_serde::config::NumericCoercion;
+    ///
+    /// // Encoded `[1.0, 2.5]` as f64.
+    /// let buf = [0x92, 0xcb, 0x3f, 0xf0, 0, 0, 0, 0, 0, 0, 0xcb, 0x40, 0x04, 0, 0, 0, 0, 0, 0];
+    ///
+    /// assert!(rmp_serde::from_slice::<Vec<u32>>(&buf).is_err());
+    ///
+    /// let mut de = rmp_serde::Deserializer::from_read_ref(&buf).with_numeric_coercion(NumericCoercion::Round);
+    /// assert_eq!(vec![1, 3], Vec::<u32>::deserialize(&mut de).unwrap());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_numeric_coercion(mut self, coercion: NumericCoercion) -> Self {
+        self.set_numeric_coercion(coercion);
+        self
+    }
+
+    /// Changes what happens when a number doesn't fit the numeric type it is read into.
+    #[inline(always)]
+    pub fn set_numeric_overflow(&mut self, overflow: NumericOverflow) {
+        self.numeric_overflow = overflow;
+    }
+
+    /// Consumes this deserializer and returns it with the given [`NumericOverflow`].
+    ///
+    /// This applies to integers narrowed to a smaller integer type, floats converted to integer
+    /// types, and `f64` values read into `f32`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    /// use rmp_serde::config::NumericOverflow;
+    ///
+    /// // Encoded `[300, -1]`.
+    /// let buf = [0x92, 0xcd, 0x01, 0x2c, 0xff];
+    ///
+    /// let mut de = rmp_serde::Deserializer::from_read_ref(&buf).with_numeric_overflow(NumericOverflow::Saturate);
+    /// assert_eq!(vec![255, 0], Vec::<u8>::deserialize(&mut de).unwrap());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_numeric_overflow(mut self, overflow: NumericOverflow) -> Self {
+        self.set_numeric_overflow(overflow);
+        self
+    }
+
+    /// Consumes exactly one complete MessagePack value, including all nested values of arrays
+    /// and maps, without deserializing it.
+    ///
+    /// This allows skipping values whose type isn't known or can't be deserialized, and
+    /// continuing with the next one. The value is walked iteratively, so arbitrarily deep
+    /// nesting doesn't grow the stack and isn't limited by [`set_max_depth`](Self::set_max_depth).
+    /// Nothing is allocated, even for large strings and binaries read from an [`io::Read`].
+    ///
+    /// This is also how `deserialize_ignored_any` is implemented, e.g. for unknown struct fields.
+    ///
+    /// Note that a failed deserialization may stop in the middle of a value. The reader is then
+    /// not at a value boundary, and this function consumes whatever follows.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde::Deserialize;
+    ///
+    /// // Encoded `{"skipped": [1, 2]}`, `42`.
+    /// let buf = [0x81, 0xa7, 0x73, 0x6b, 0x69, 0x70, 0x70, 0x65, 0x64, 0x92, 0x01, 0x02, 0x2a];
+    ///
+    /// let mut de = rmp_serde::Deserializer::from_read_ref(&buf);
+    /// de.skip_value().unwrap();
+    /// assert_eq!(42, u8::deserialize(&mut de).unwrap());
+    /// ```
+    pub fn skip_value(&mut self) -> Result<(), Error> {
+        // Saturates rather than overflows on absurd lengths, which fail as truncated input anyway.
+        let mut remaining: u64 = 1;
+        while remaining > 0 {
+            remaining -= 1;
+            let len = match self.take_or_read_marker()? {
+                Marker::Null |
+                Marker::True |
+                Marker::False |
+                Marker::FixPos(_) |
+                Marker::FixNeg(_) => 0,
+                Marker::U8 | Marker::I8 => 1,
+                Marker::U16 | Marker::I16 => 2,
+                Marker::U32 | Marker::I32 | Marker::F32 => 4,
+                Marker::U64 | Marker::I64 | Marker::F64 => 8,
+                Marker::FixStr(len) => u32::from(len),
+                Marker::Str8 | Marker::Bin8 => u32::from(self.rd.read_data_u8()?),
+                Marker::Str16 | Marker::Bin16 => u32::from(self.rd.read_data_u16()?),
+                Marker::Str32 | Marker::Bin32 => self.rd.read_data_u32()?,
+                Marker::FixArray(len) => {
+                    remaining = remaining.saturating_add(u64::from(len));
+                    0
+                }
+                Marker::Array16 => {
+                    remaining = remaining.saturating_add(u64::from(self.rd.read_data_u16()?));
+                    0
+                }
+                Marker::Array32 => {
+                    remaining = remaining.saturating_add(u64::from(self.rd.read_data_u32()?));
+                    0
+                }
+                Marker::FixMap(len) => {
+                    remaining = remaining.saturating_add(2 * u64::from(len));
+                    0
+                }
+                Marker::Map16 => {
+                    remaining = remaining.saturating_add(2 * u64::from(self.rd.read_data_u16()?));
+                    0
+                }
+                Marker::Map32 => {
+                    remaining = remaining.saturating_add(2 * u64::from(self.rd.read_data_u32()?));
+                    0
+                }
+                marker @ (Marker::FixExt1 |
+                Marker::FixExt2 |
+                Marker::FixExt4 |
+                Marker::FixExt8 |
+                Marker::FixExt16 |
+                Marker::Ext8 |
+                Marker::Ext16 |
+                Marker::Ext32) => {
+                    let len = ext_len(&mut self.rd, marker)?;
+                    self.rd.read_data_i8()?;
+                    len
+                }
+                Marker::Reserved => return Err(Error::TypeMismatch(Marker::Reserved)),
+            };
+            self.rd.skip(len as usize).map_err(Error::InvalidDataRead)?;
+        }
+        Ok(())
+    }
+}
+
+#[inline(never)]
+fn read_i128_marker<'de, R: ReadSlice<'de>>(marker: Marker, rd: &mut R) -> Result<i128, Error> {
+    Ok(match marker {
+        Marker::FixPos(val) => val.into(),
+        Marker::FixNeg(val) => val.into(),
+        Marker::U8 => rd.read_data_u8()?.into(),
+        Marker::U16 => rd.read_data_u16()?.into(),
+        Marker::U32 => rd.read_data_u32()?.into(),
+        Marker::U64 => rd.read_data_u64()?.into(),
+        Marker::I8 => rd.read_data_i8()?.into(),
+        Marker::I16 => rd.read_data_i16()?.into(),
+        Marker::I32 => rd.read_data_i32()?.into(),
+        Marker::I64 => rd.read_data_i64()?.into(),
+        Marker::Bin8 => {
+            let len = read_u8(&mut *rd)?;
+            read_128_buf(rd, len)?
+        },
+        Marker::FixArray(len) => {
+            read_128_buf(rd, len)?
+        },
+        marker => return Err(Error::TypeMismatch(marker)),
+    })
+}
+
+fn read_128_buf<'de, R: ReadSlice<'de>>(rd: &mut R, len: u8) -> Result<i128, Error> {
+    if len != 16 {
+        return Err(Error::LengthMismatch(16));
+    }
+    let buf = match read_bin_data(rd, 16)? {
+        Reference::Borrowed(buf) => buf,
+        Reference::Copied(buf) => buf,
+    };
+    Ok(i128::from_be_bytes(buf.try_into().map_err(|_| Error::LengthMismatch(16))?))
+}
+
+/// Reads a string and hands it to the visitor, falling back to bytes as `mode` allows.
+///
+/// Unlike `rmpv`, which validates strings chunk by chunk while reading them from an I/O stream,
+/// the whole string is read before it's validated here: visitors take a single `&str`, so the
+/// data has to be buffered contiguously anyway, and validating it in one go is faster.
+fn read_str_data<'de, V, R>(rd: &mut R, len: u32, mode: Utf8Mode, visitor: V) -> Result<V::Value, Error>
+    where V: Visitor<'de>, R: ReadSlice<'de>
+{
+    match read_bin_data(rd, len)? {
+        Reference::Borrowed(buf) => {
+            match from_utf8(buf) {
+                Ok(s) => visitor.visit_borrowed_str(s),
+                Err(err) => match mode {
+                    // Allow to unpack invalid UTF-8 bytes into a byte array.
+                    Utf8Mode::Bytes => match visitor.visit_borrowed_bytes::<Error>(buf) {
+                        Ok(buf) => Ok(buf),
+                        Err(..) => Err(Error::Utf8Error(err)),
+                    },
+                    Utf8Mode::Strict => Err(Error::Utf8Error(err)),
+                    Utf8Mode::Lossy => visitor.visit_string(String::from_utf8_lossy(buf).into_owned()),
+                },
+            }
+        }
+        Reference::Copied(buf) => {
+            match from_utf8(buf) {
+                Ok(s) => visitor.visit_str(s),
+                Err(err) => match mode {
+                    // Allow to unpack invalid UTF-8 bytes into a byte array.
+                    Utf8Mode::Bytes => match visitor.visit_bytes::<Error>(buf) {
+                        Ok(buf) => Ok(buf),
+                        Err(..) => Err(Error::Utf8Error(err)),
+                    },
+                    Utf8Mode::Strict => Err(Error::Utf8Error(err)),
+                    Utf8Mode::Lossy => visitor.visit_string(String::from_utf8_lossy(buf).into_owned()),
+                },
+            }
+        }
+    }
+}
+
+/// Validates a string read from the input.
+#[cfg(not(feature = "simdutf8"))]
+#[inline]
+fn from_utf8(buf: &[u8]) -> Result<&str, Utf8Error> {
+    str::from_utf8(buf)
+}
+
+/// Validates a string read from the input with SIMD instructions.
+///
+/// Only the error path goes through `str::from_utf8`, to get a `Utf8Error` with the position of the
+/// invalid sequence.
+#[cfg(feature = "simdutf8")]
+#[inline]
+fn from_utf8(buf: &[u8]) -> Result<&str, Utf8Error> {
+    simdutf8::basic::from_utf8(buf).or_else(|_| str::from_utf8(buf))
+}
+
+fn read_bin_data<'a, 'de, R: ReadSlice<'de>>(rd: &'a mut R, len: u32) -> Result<Reference<'de, 'a, [u8]>, Error> {
+    rd.read_slice(len as usize).map_err(Error::InvalidDataRead)
+}
+
+fn read_u8<R: Read>(rd: &mut R) -> Result<u8, Error> {
+    Ok(rd.read_data_u8()?)
+}
 
 fn read_u16<R: Read>(rd: &mut R) -> Result<u16, Error> {
-    rd.read_u16::<byteorder::BigEndian>()
-        .map_err(Error::InvalidDataRead)
+    Ok(rd.read_data_u16()?)
 }
 
 fn read_u32<R: Read>(rd: &mut R) -> Result<u32, Error> {
-    rd.read_u32::<byteorder::BigEndian>()
-        .map_err(Error::InvalidDataRead)
+    Ok(rd.read_data_u32()?)
 }
 
 fn ext_len<R: Read>(rd: &mut R, marker: Marker) -> Result<u32, Error> {
@@ -424,15 +1557,18 @@ struct ExtDeserializer<'a, R, C> {
     rd: &'a mut R,
     _config: PhantomData<C>,
     len: u32,
+    /// The type id, if it has already been read.
+    tag: Option<i8>,
     state: ExtDeserializerState,
 }
 
 impl<'de, 'a, R: ReadSlice<'de> + 'a, C: SerializerConfig> ExtDeserializer<'a, R, C> {
-    fn new(d: &'a mut Deserializer<R, C>, len: u32) -> Self {
+    fn new(d: &'a mut Deserializer<R, C>, len: u32, tag: Option<i8>) -> Self {
         ExtDeserializer {
             rd: &mut d.rd,
             _config: d._config,
             len,
+            tag,
             state: ExtDeserializerState::New,
         }
     }
@@ -479,7 +1615,10 @@ impl<'de, 'a, R: ReadSlice<'de> + 'a, C: SerializerConfig> de::Deserializer<'de>
     {
         match self.state {
             ExtDeserializerState::New => {
-                let tag = self.rd.read_data_i8()?;
+                let tag = match self.tag.take() {
+                    Some(tag) => tag,
+                    None => self.rd.read_data_i8()?,
+                };
                 self.state = ExtDeserializerState::ReadTag;
                 visitor.visit_i8(tag)
             }
@@ -505,66 +1644,226 @@ impl<'de, 'a, R: ReadSlice<'de> + 'a, C: SerializerConfig> de::Deserializer<'de>
     }
 }
 
+/// A scalar value read by [`read_scalar`], not yet handed to a visitor.
+enum Scalar {
+    Unit,
+    Bool(bool),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+}
+
+/// A value marker with its length, as read by [`read_header`].
+enum Header {
+    Scalar(Scalar),
+    Str(u32),
+    Bin(u32),
+    Array(u32),
+    Map(u32),
+    Ext(u32),
+}
+
+/// Reads the data of a nil, boolean or numeric value.
+///
+/// This doesn't depend on the visitor, so it's compiled once per reader, rather than for every
+/// reader and visitor combination. The reader stays generic, so that reading from slices doesn't
+/// go through dynamic dispatch.
 #[inline(never)]
-fn any_num<'de, R: ReadSlice<'de>, V: Visitor<'de>>(rd: &mut R, visitor: V, marker: Marker) -> Result<V::Value, Error> {
-    match marker {
-        Marker::Null => visitor.visit_unit(),
-        Marker::True |
-        Marker::False => visitor.visit_bool(marker == Marker::True),
-        Marker::FixPos(val) => visitor.visit_u8(val),
-        Marker::FixNeg(val) => visitor.visit_i8(val),
-        Marker::U8 => visitor.visit_u8(rd.read_data_u8()?),
-        Marker::U16 => visitor.visit_u16(rd.read_data_u16()?),
-        Marker::U32 => visitor.visit_u32(rd.read_data_u32()?),
-        Marker::U64 => visitor.visit_u64(rd.read_data_u64()?),
-        Marker::I8 => visitor.visit_i8(rd.read_data_i8()?),
-        Marker::I16 => visitor.visit_i16(rd.read_data_i16()?),
-        Marker::I32 => visitor.visit_i32(rd.read_data_i32()?),
-        Marker::I64 => visitor.visit_i64(rd.read_data_i64()?),
-        Marker::F32 => visitor.visit_f32(rd.read_data_f32()?),
-        Marker::F64 => visitor.visit_f64(rd.read_data_f64()?),
-        other_marker => Err(Error::TypeMismatch(other_marker)),
+fn read_scalar<R: Read>(rd: &mut R, marker: Marker) -> Result<Scalar, Error> {
+    Ok(match marker {
+        Marker::Null => Scalar::Unit,
+        Marker::True => Scalar::Bool(true),
+        Marker::False => Scalar::Bool(false),
+        Marker::FixPos(val) => Scalar::U8(val),
+        Marker::FixNeg(val) => Scalar::I8(val),
+        Marker::U8 => Scalar::U8(rd.read_data_u8()?),
+        Marker::U16 => Scalar::U16(rd.read_data_u16()?),
+        Marker::U32 => Scalar::U32(rd.read_data_u32()?),
+        Marker::U64 => Scalar::U64(rd.read_data_u64()?),
+        Marker::I8 => Scalar::I8(rd.read_data_i8()?),
+        Marker::I16 => Scalar::I16(rd.read_data_i16()?),
+        Marker::I32 => Scalar::I32(rd.read_data_i32()?),
+        Marker::I64 => Scalar::I64(rd.read_data_i64()?),
+        Marker::F32 => Scalar::F32(rd.read_data_f32()?),
+        Marker::F64 => Scalar::F64(rd.read_data_f64()?),
+        other_marker => return Err(Error::TypeMismatch(other_marker)),
+    })
+}
+
+/// Reads whatever follows the marker up to the payload: scalar data, or the length of a
+/// str, bin, array, map or ext value.
+#[inline(never)]
+fn read_header<R: Read>(rd: &mut R, marker: Marker) -> Result<Header, Error> {
+    Ok(match marker {
+        Marker::FixStr(len) => Header::Str(len.into()),
+        Marker::Str8 => Header::Str(read_u8(rd)?.into()),
+        Marker::Str16 => Header::Str(read_u16(rd)?.into()),
+        Marker::Str32 => Header::Str(read_u32(rd)?),
+        Marker::Bin8 => Header::Bin(read_u8(rd)?.into()),
+        Marker::Bin16 => Header::Bin(read_u16(rd)?.into()),
+        Marker::Bin32 => Header::Bin(read_u32(rd)?),
+        Marker::FixArray(len) => Header::Array(len.into()),
+        Marker::Array16 => Header::Array(read_u16(rd)?.into()),
+        Marker::Array32 => Header::Array(read_u32(rd)?),
+        Marker::FixMap(len) => Header::Map(len.into()),
+        Marker::Map16 => Header::Map(read_u16(rd)?.into()),
+        Marker::Map32 => Header::Map(read_u32(rd)?),
+        Marker::FixExt1 |
+        Marker::FixExt2 |
+        Marker::FixExt4 |
+        Marker::FixExt8 |
+        Marker::FixExt16 |
+        Marker::Ext8 |
+        Marker::Ext16 |
+        Marker::Ext32 => Header::Ext(ext_len(rd, marker)?),
+        marker => Header::Scalar(read_scalar(rd, marker)?),
+    })
+}
+
+fn visit_scalar<'de, V: Visitor<'de>>(visitor: V, scalar: Scalar) -> Result<V::Value, Error> {
+    match scalar {
+        Scalar::Unit => visitor.visit_unit(),
+        Scalar::Bool(val) => visitor.visit_bool(val),
+        Scalar::U8(val) => visitor.visit_u8(val),
+        Scalar::U16(val) => visitor.visit_u16(val),
+        Scalar::U32(val) => visitor.visit_u32(val),
+        Scalar::U64(val) => visitor.visit_u64(val),
+        Scalar::I8(val) => visitor.visit_i8(val),
+        Scalar::I16(val) => visitor.visit_i16(val),
+        Scalar::I32(val) => visitor.visit_i32(val),
+        Scalar::I64(val) => visitor.visit_i64(val),
+        Scalar::F32(val) => visitor.visit_f32(val),
+        Scalar::F64(val) => visitor.visit_f64(val),
     }
 }
 
-impl<'de, R: ReadSlice<'de>, C: SerializerConfig> Deserializer<R, C> {
+fn any_num<'de, R: ReadSlice<'de>, V: Visitor<'de>>(rd: &mut R, visitor: V, marker: Marker) -> Result<V::Value, Error> {
+    visit_scalar(visitor, read_scalar(rd, marker)?)
+}
+
+impl<'de, R: ReadSlice<'de>, C: SerializerConfig> Deserializer<R, C> {
+    #[inline]
+    fn coerces_numbers(&self) -> bool {
+        self.numeric_coercion != NumericCoercion::Exact || self.numeric_overflow != NumericOverflow::Error
+    }
+
+    /// Reads a number into an integer type with the given range, converting it according to the
+    /// numeric coercion and overflow settings.
+    fn read_coerced_int<V: Visitor<'de>>(&mut self, visitor: V, min: i128, max: i128) -> Result<V::Value, Error> {
+        let marker = self.take_or_read_marker()?;
+        let scalar = read_scalar(&mut self.rd, marker)?;
+        let val = match scalar {
+            Scalar::U8(val) => i128::from(val),
+            Scalar::U16(val) => i128::from(val),
+            Scalar::U32(val) => i128::from(val),
+            Scalar::U64(val) => i128::from(val),
+            Scalar::I8(val) => i128::from(val),
+            Scalar::I16(val) => i128::from(val),
+            Scalar::I32(val) => i128::from(val),
+            Scalar::I64(val) => i128::from(val),
+            Scalar::F32(..) | Scalar::F64(..) if self.numeric_coercion == NumericCoercion::Exact => {
+                return visit_scalar(visitor, scalar);
+            }
+            Scalar::F32(val) => self.float_to_int(f64::from(val))?,
+            Scalar::F64(val) => self.float_to_int(val)?,
+            Scalar::Unit | Scalar::Bool(..) => return visit_scalar(visitor, scalar),
+        };
+        let val = if (min..=max).contains(&val) {
+            val
+        } else {
+            match self.numeric_overflow {
+                NumericOverflow::Error => return Err(Error::OutOfRange),
+                NumericOverflow::Saturate => val.clamp(min, max),
+            }
+        };
+        // The value fits the target type, which accepts any integer visit in its range.
+        if val < 0 {
+            visitor.visit_i64(val as i64)
+        } else {
+            visitor.visit_u64(val as u64)
+        }
+    }
+
+    fn float_to_int(&self, val: f64) -> Result<i128, Error> {
+        if val.is_nan() {
+            return Err(Error::OutOfRange);
+        }
+        // Truncates, and saturates at the bounds of `i128`, which are out of range of every target
+        // type. This avoids `f64::round`, which isn't available without `std`.
+        let int = val as i128;
+        let frac = val - int as f64;
+        Ok(match self.numeric_coercion {
+            NumericCoercion::Round if frac >= 0.5 => int.saturating_add(1),
+            NumericCoercion::Round if frac <= -0.5 => int.saturating_sub(1),
+            _ => int,
+        })
+    }
+
+    /// Reads a struct written as an array of its fields, reporting a mismatching number of them.
+    fn read_struct_array<V: Visitor<'de>>(&mut self, name: &'static str, fields: usize, visitor: V) -> Result<V::Value, Error> {
+        let marker = self.take_or_read_marker()?;
+        let len = match read_header(&mut self.rd, marker)? {
+            Header::Array(len) => self.check_len(len)?,
+            _ => return Err(Error::TypeMismatch(marker)),
+        };
+        let extra_fields = self.extra_fields;
+
+        depth_count!(self.depth, {
+            let mut seq = SeqAccess::new(self, len);
+            match visitor.visit_seq(&mut seq) {
+                // The visitor asked for more elements than there are, and missed a field.
+                Err(..) if seq.overrun => Err(Error::FieldCountMismatch(name, fields, len)),
+                Err(err) => Err(err),
+                Ok(res) if seq.left == 0 => Ok(res),
+                Ok(res) if extra_fields == ExtraFieldsMode::Ignore => {
+                    for _ in 0..seq.left {
+                        seq.de.skip_value()?;
+                    }
+                    Ok(res)
+                }
+                Ok(..) => Err(Error::FieldCountMismatch(name, fields, len)),
+            }
+        })
+    }
+
+    /// Visits a map whose header has been read, with integer keys replaced by the names at those
+    /// indices of `fields`, if given.
+    fn read_map<V>(&mut self, len: u32, fields: Option<&'static [&'static str]>, visitor: V) -> Result<V::Value, Error>
+        where V: Visitor<'de>
+    {
+        let len = self.check_len(len)?;
+
+        depth_count!(self.depth, {
+            let mut seq = MapAccess::new(self, len, fields);
+            let res = visitor.visit_map(&mut seq)?;
+            match seq.left {
+                0 => Ok(res),
+                excess => Err(Error::LengthMismatch(len - excess)),
+            }
+        })
+    }
+
     fn any_inner<V: Visitor<'de>>(&mut self, visitor: V, allow_bytes: bool) -> Result<V::Value, Error> {
         let marker = self.take_or_read_marker()?;
-        match marker {
-            Marker::Null |
-            Marker::True |
-            Marker::False |
-            Marker::FixPos(_) |
-            Marker::FixNeg(_) |
-            Marker::U8 |
-            Marker::U16 |
-            Marker::U32 |
-            Marker::U64 |
-            Marker::I8 |
-            Marker::I16 |
-            Marker::I32 |
-            Marker::I64 |
-            Marker::F32 |
-            Marker::F64 => any_num(&mut self.rd, visitor, marker),
-            Marker::FixStr(_) | Marker::Str8 | Marker::Str16 | Marker::Str32 => {
-                let len = match marker {
-                    Marker::FixStr(len) => Ok(len.into()),
-                    Marker::Str8 => read_u8(&mut self.rd).map(u32::from),
-                    Marker::Str16 => read_u16(&mut self.rd).map(u32::from),
-                    Marker::Str32 => read_u32(&mut self.rd).map(u32::from),
-                    _ => return Err(Error::TypeMismatch(Marker::Reserved)),
-                }?;
-                read_str_data(&mut self.rd, len, visitor)
+        match read_header(&mut self.rd, marker)? {
+            Header::Scalar(scalar) => visit_scalar(visitor, scalar),
+            Header::Str(len) => {
+                let len = self.check_len(len)?;
+                if !allow_bytes && self.str_bin_mode != StrBinMode::Strict {
+                    let (Reference::Borrowed(buf) | Reference::Copied(buf)) = read_bin_data(&mut self.rd, len)?;
+                    return visitor.visit_seq(BytesAccess { iter: buf.iter() });
+                }
+                read_str_data(&mut self.rd, len, self.utf8_mode, visitor)
             }
-            Marker::FixArray(_) |
-            Marker::Array16 |
-            Marker::Array32 => {
-                let len = match marker {
-                    Marker::FixArray(len) => len.into(),
-                    Marker::Array16 => read_u16(&mut self.rd)?.into(),
-                    Marker::Array32 => read_u32(&mut self.rd)?,
-                    _ => return Err(Error::TypeMismatch(Marker::Reserved)),
-                };
+            Header::Array(len) => {
+                let len = self.check_len(len)?;
 
                 depth_count!(self.depth, {
                     let mut seq = SeqAccess::new(self, len);
@@ -575,52 +1874,77 @@ impl<'de, R: ReadSlice<'de>, C: SerializerConfig> Deserializer<R, C> {
                     }
                 })
             }
-            Marker::FixMap(_) |
-            Marker::Map16 |
-            Marker::Map32 => {
-                let len = match marker {
-                    Marker::FixMap(len) => len.into(),
-                    Marker::Map16 => read_u16(&mut self.rd)?.into(),
-                    Marker::Map32 => read_u32(&mut self.rd)?,
-                    _ => return Err(Error::TypeMismatch(Marker::Reserved)),
-                };
-
-                depth_count!(self.depth, {
-                    let mut seq = MapAccess::new(self, len);
-                    let res = visitor.visit_map(&mut seq)?;
-                    match seq.left {
-                        0 => Ok(res),
-                        excess => Err(Error::LengthMismatch(len - excess)),
-                    }
-                })
-            }
-            Marker::Bin8 | Marker::Bin16 | Marker::Bin32 => {
-                let len = match marker {
-                    Marker::Bin8 => read_u8(&mut self.rd).map(u32::from),
-                    Marker::Bin16 => read_u16(&mut self.rd).map(u32::from),
-                    Marker::Bin32 => read_u32(&mut self.rd).map(u32::from),
-                    _ => return Err(Error::TypeMismatch(Marker::Reserved)),
-                }?;
+            Header::Map(len) => self.read_map(len, None, visitor),
+            Header::Bin(len) => {
+                let len = self.check_len(len)?;
                 match read_bin_data(&mut self.rd, len)? {
                     Reference::Borrowed(buf) if allow_bytes => visitor.visit_borrowed_bytes(buf),
                     Reference::Copied(buf) if allow_bytes => visitor.visit_bytes(buf),
                     Reference::Borrowed(buf) | Reference::Copied(buf) => {
-                        visitor.visit_seq(SeqDeserializer::new(buf.iter().copied()))
+                        visitor.visit_seq(BytesAccess { iter: buf.iter() })
                     },
                 }
             }
-            Marker::FixExt1 |
-            Marker::FixExt2 |
-            Marker::FixExt4 |
-            Marker::FixExt8 |
-            Marker::FixExt16 |
-            Marker::Ext8 |
-            Marker::Ext16 |
-            Marker::Ext32 => {
-                let len = ext_len(&mut self.rd, marker)?;
-                depth_count!(self.depth, visitor.visit_newtype_struct(ExtDeserializer::new(self, len)))
+            Header::Ext(len) => {
+                let len = self.check_len(len)?;
+                // The type id is read up front to look it up, and handed over with the data
+                // otherwise.
+                let tag = if self.ext_registry.is_some() {
+                    let ty = self.rd.read_data_i8()?;
+                    if let Some(convert) = self.ext_registry.as_ref().and_then(|registry| registry.get(ty)) {
+                        let data = match read_bin_data(&mut self.rd, len)? {
+                            Reference::Borrowed(buf) | Reference::Copied(buf) => buf,
+                        };
+                        let content = convert(data)?;
+                        return depth_count!(self.depth, de::Deserializer::deserialize_any(content, visitor));
+                    }
+                    Some(ty)
+                } else {
+                    None
+                };
+                match self.ext_mode {
+                    ExtMode::Newtype => {
+                        depth_count!(self.depth, visitor.visit_newtype_struct(ExtDeserializer::new(self, len, tag)))
+                    }
+                    ExtMode::Tuple => {
+                        depth_count!(self.depth, visitor.visit_seq(ExtDeserializer::new(self, len, tag)))
+                    }
+                    ExtMode::Skip => {
+                        if tag.is_none() {
+                            self.rd.read_data_i8()?;
+                        }
+                        read_bin_data(&mut self.rd, len)?;
+                        visitor.visit_unit()
+                    }
+                    ExtMode::Error => Err(Error::TypeMismatch(marker)),
+                }
+            }
+        }
+    }
+
+    /// Reads a string from a str or bin value, replacing invalid UTF-8 sequences.
+    fn read_str_lossy<V: Visitor<'de>>(&mut self, visitor: V) -> Result<V::Value, Error> {
+        let marker = self.take_or_read_marker()?;
+        let len = match marker {
+            Marker::FixStr(len) => u32::from(len),
+            Marker::Str8 | Marker::Bin8 => u32::from(read_u8(&mut self.rd)?),
+            Marker::Str16 | Marker::Bin16 => u32::from(read_u16(&mut self.rd)?),
+            Marker::Str32 | Marker::Bin32 => read_u32(&mut self.rd)?,
+            marker => {
+                self.marker = Some(marker);
+                return self.any_inner(visitor, true);
             }
-            Marker::Reserved => Err(Error::TypeMismatch(Marker::Reserved)),
+        };
+        let len = self.check_len(len)?;
+        match read_bin_data(&mut self.rd, len)? {
+            Reference::Borrowed(buf) => match String::from_utf8_lossy(buf) {
+                Cow::Borrowed(s) => visitor.visit_borrowed_str(s),
+                Cow::Owned(s) => visitor.visit_string(s),
+            },
+            Reference::Copied(buf) => match String::from_utf8_lossy(buf) {
+                Cow::Borrowed(s) => visitor.visit_str(s),
+                Cow::Owned(s) => visitor.visit_string(s),
+            },
         }
     }
 }
@@ -654,12 +1978,14 @@ impl<'de, 'a, R: ReadSlice<'de>, C: SerializerConfig> serde::Deserializer<'de> f
         // # Note
         //
         // Round trips of Options where `Option<t> = None` such as `Some(None)` will fail because
-        // they are just seriialized as `nil`. The serialization format has probably to be changed
-        // to solve this. But as serde_json behaves the same, I think it's not worth doing this.
+        // they are just seriialized as `nil`, unless `OptionMode::Array` is used on both ends.
         let marker = self.take_or_read_marker()?;
 
         if marker == Marker::Null {
             visitor.visit_none()
+        } else if marker == Marker::FixArray(1) && self.option_mode == OptionMode::Array {
+            // `Some(v)` written as `[v]`, the value follows.
+            visitor.visit_some(self)
         } else {
             // Keep the marker until `o`'s innermost type `t` is visited.
             self.marker = Some(marker);
@@ -671,6 +1997,16 @@ impl<'de, 'a, R: ReadSlice<'de>, C: SerializerConfig> serde::Deserializer<'de> f
         where V: Visitor<'de>
     {
         let marker = self.peek_or_read_marker()?;
+        if self.compat == CompatMode::V08 {
+            if let Ok(len) = rmp::decode::read_array_len_with_marker(&mut self.rd, marker) {
+                // Legacy enums are encoded as [idx, [v1,...,vN]].
+                self.marker = None;
+                return match len {
+                    2 => depth_count!(self.depth, visitor.visit_enum(LegacyVariantAccess::new(self))),
+                    n => Err(Error::LengthMismatch(n)),
+                };
+            }
+        }
         match rmp::decode::marker_to_len(&mut self.rd, marker) {
             Ok(len) => match len {
                 // Enums are either encoded as maps with a single K/V pair
@@ -678,7 +2014,7 @@ impl<'de, 'a, R: ReadSlice<'de>, C: SerializerConfig> serde::Deserializer<'de> f
                 // or as just the variant
                 1 => {
                     self.marker = None;
-                    visitor.visit_enum(VariantAccess::new(self))
+                    depth_count!(self.depth, visitor.visit_enum(VariantAccess::new(self)))
                 }
                 n => Err(Error::LengthMismatch(n)),
             },
@@ -694,9 +2030,29 @@ impl<'de, 'a, R: ReadSlice<'de>, C: SerializerConfig> serde::Deserializer<'de> f
             let marker = self.take_or_read_marker()?;
 
             let len = ext_len(&mut self.rd, marker)?;
-            let ext_de = ExtDeserializer::new(self, len);
+            let len = self.check_len(len)?;
+            let ext_de = ExtDeserializer::new(self, len, None);
             return visitor.visit_newtype_struct(ext_de);
         }
+        #[cfg(feature = "std")]
+        if name == INTERNED_STRUCT_NAME {
+            if let Some(interner) = self.interner.clone() {
+                let interned = de::Deserializer::deserialize_str(&mut *self, intern::InternVisitor(&interner))?;
+                return intern::visit_interned(interned, visitor);
+            }
+        }
+        if name == RAW_VALUE_STRUCT_NAME {
+            let peeked = self.marker.map(|marker| marker.to_u8());
+            self.rd.begin_capture(peeked);
+            let res = self.skip_value();
+            let raw = self.rd.end_capture();
+            res?;
+            return match raw {
+                Some(Reference::Borrowed(buf)) => visitor.visit_borrowed_bytes(buf),
+                Some(Reference::Copied(buf)) => visitor.visit_bytes(buf),
+                None => Err(Error::Uncategorized("the reader can't capture raw values".into())),
+            };
+        }
 
         visitor.visit_newtype_struct(self)
     }
@@ -729,11 +2085,33 @@ impl<'de, 'a, R: ReadSlice<'de>, C: SerializerConfig> serde::Deserializer<'de> f
     where
         V: Visitor<'de>,
     {
-        visitor.visit_u128(read_i128_marker(self.take_or_read_marker()?, &mut self.rd)? as u128)
+        let marker = self.take_or_read_marker()?;
+        // Byte representations hold the bits of a `u128`, so only plain integers can be negative.
+        let is_bytes = matches!(marker, Marker::Bin8 | Marker::FixArray(_));
+        let val = read_i128_marker(marker, &mut self.rd)?;
+        if val < 0 && !is_bytes {
+            return Err(Error::OutOfRange);
+        }
+        visitor.visit_u128(val as u128)
     }
 
     #[inline]
     fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        if self.key_policy == KeyPolicy::Lenient && self.peek_or_read_marker()?.is_map() {
+            let marker = self.take_or_read_marker()?;
+            if let Header::Map(len) = read_header(&mut self.rd, marker)? {
+                let len = self.check_len(len)?;
+
+                return depth_count!(self.depth, {
+                    let mut seq = EntrySeqAccess { de: &mut *self, left: len };
+                    let res = visitor.visit_seq(&mut seq)?;
+                    match seq.left {
+                        0 => Ok(res),
+                        excess => Err(Error::LengthMismatch(len - excess)),
+                    }
+                });
+            }
+        }
         self.any_inner(visitor, false)
     }
 
@@ -743,7 +2121,18 @@ impl<'de, 'a, R: ReadSlice<'de>, C: SerializerConfig> serde::Deserializer<'de> f
     }
 
     #[inline]
-    fn deserialize_struct<V>(self, _: &'static str, _: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+    fn deserialize_struct<V>(self, name: &'static str, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        let marker = self.peek_or_read_marker()?;
+        if marker.is_array() {
+            return self.read_struct_array(name, fields.len(), visitor);
+        }
+        if let Some(table) = self.field_table.and_then(|FieldTable(lookup)| lookup(name)) {
+            if marker.is_map() {
+                let marker = self.take_or_read_marker()?;
+                let len = decode::read_map_len_with_marker(&mut self.rd, marker)?;
+                return self.read_map(len, Some(table), visitor);
+            }
+        }
         self.any_inner(visitor, false)
     }
 
@@ -752,10 +2141,29 @@ impl<'de, 'a, R: ReadSlice<'de>, C: SerializerConfig> serde::Deserializer<'de> f
         self.any_inner(visitor, false)
     }
 
+    #[inline]
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        if self.str_bin_mode == StrBinMode::LenientLossy {
+            return self.read_str_lossy(visitor);
+        }
+        self.any_inner(visitor, true)
+    }
+
+    #[inline]
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        self.deserialize_str(visitor)
+    }
+
     forward_to_deserialize_any! {
         bytes byte_buf unit
-        map identifier str string char
-        ignored_any
+        map identifier char
+    }
+
+    // Unknown fields are skipped without decoding them, see `skip_value`.
+    #[inline]
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        self.skip_value()?;
+        visitor.visit_unit()
     }
 
     fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
@@ -764,48 +2172,80 @@ impl<'de, 'a, R: ReadSlice<'de>, C: SerializerConfig> serde::Deserializer<'de> f
     }
 
     fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        if self.coerces_numbers() {
+            return self.read_coerced_int(visitor, u8::MIN.into(), u8::MAX.into());
+        }
         let marker = self.take_or_read_marker()?;
         any_num(&mut self.rd, visitor, marker)
     }
 
     fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        if self.coerces_numbers() {
+            return self.read_coerced_int(visitor, i8::MIN.into(), i8::MAX.into());
+        }
         let marker = self.take_or_read_marker()?;
         any_num(&mut self.rd, visitor, marker)
     }
 
     fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        if self.coerces_numbers() {
+            return self.read_coerced_int(visitor, i16::MIN.into(), i16::MAX.into());
+        }
         let marker = self.take_or_read_marker()?;
         any_num(&mut self.rd, visitor, marker)
     }
 
     fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        if self.coerces_numbers() {
+            return self.read_coerced_int(visitor, u16::MIN.into(), u16::MAX.into());
+        }
         let marker = self.take_or_read_marker()?;
         any_num(&mut self.rd, visitor, marker)
     }
 
     fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        if self.coerces_numbers() {
+            return self.read_coerced_int(visitor, i32::MIN.into(), i32::MAX.into());
+        }
         let marker = self.take_or_read_marker()?;
         any_num(&mut self.rd, visitor, marker)
     }
 
     fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        if self.coerces_numbers() {
+            return self.read_coerced_int(visitor, u32::MIN.into(), u32::MAX.into());
+        }
         let marker = self.take_or_read_marker()?;
         any_num(&mut self.rd, visitor, marker)
     }
 
     fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        if self.coerces_numbers() {
+            return self.read_coerced_int(visitor, i64::MIN.into(), i64::MAX.into());
+        }
         let marker = self.take_or_read_marker()?;
         any_num(&mut self.rd, visitor, marker)
     }
 
     fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
+        if self.coerces_numbers() {
+            return self.read_coerced_int(visitor, u64::MIN.into(), u64::MAX.into());
+        }
         let marker = self.take_or_read_marker()?;
         any_num(&mut self.rd, visitor, marker)
     }
 
     fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
         let marker = self.take_or_read_marker()?;
-        any_num(&mut self.rd, visitor, marker)
+        match read_scalar(&mut self.rd, marker)? {
+            Scalar::F64(val) if self.coerces_numbers() && val.is_finite() && val.abs() > f64::from(f32::MAX) => {
+                match self.numeric_overflow {
+                    NumericOverflow::Error => Err(Error::OutOfRange),
+                    NumericOverflow::Saturate => visitor.visit_f32(f32::MAX.copysign(val as f32)),
+                }
+            }
+            scalar => visit_scalar(visitor, scalar),
+        }
     }
 
     fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, Self::Error> where V: Visitor<'de> {
@@ -817,12 +2257,14 @@ impl<'de, 'a, R: ReadSlice<'de>, C: SerializerConfig> serde::Deserializer<'de> f
 struct SeqAccess<'a, R, C> {
     de: &'a mut Deserializer<R, C>,
     left: u32,
+    // Whether an element was requested after the last one.
+    overrun: bool,
 }
 
 impl<'a, R: 'a, C> SeqAccess<'a, R, C> {
     #[inline]
     fn new(de: &'a mut Deserializer<R, C>, len: u32) -> Self {
-        SeqAccess { de, left: len }
+        SeqAccess { de, left: len, overrun: false }
     }
 }
 
@@ -837,6 +2279,7 @@ impl<'de, 'a, R: ReadSlice<'de> + 'a, C: SerializerConfig> de::SeqAccess<'de> fo
             self.left -= 1;
             Ok(Some(seed.deserialize(&mut *self.de)?))
         } else {
+            self.overrun = true;
             Ok(None)
         }
     }
@@ -847,15 +2290,67 @@ impl<'de, 'a, R: ReadSlice<'de> + 'a, C: SerializerConfig> de::SeqAccess<'de> fo
     }
 }
 
+/// Gives the bytes of a bin value, or of a str value read as bytes, to a visitor that asked for a
+/// sequence, like the one of `Vec<u8>`.
+///
+/// Each byte is passed straight to `visit_u8`, without reading markers or going through the
+/// `Deserializer`.
+struct BytesAccess<'a> {
+    iter: core::slice::Iter<'a, u8>,
+}
+
+impl<'de> de::SeqAccess<'de> for BytesAccess<'_> {
+    type Error = Error;
+
+    #[inline]
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+        where T: DeserializeSeed<'de>
+    {
+        match self.iter.next() {
+            Some(&byte) => seed.deserialize(ByteDeserializer(byte)).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+/// Deserializes a single byte of a [`BytesAccess`].
+struct ByteDeserializer(u8);
+
+impl<'de> serde::Deserializer<'de> for ByteDeserializer {
+    type Error = Error;
+
+    #[inline(always)]
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        visitor.visit_u8(self.0)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option
+        unit unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier
+        ignored_any
+    }
+}
+
 struct MapAccess<'a, R, C> {
     de: &'a mut Deserializer<R, C>,
     left: u32,
+    // Keys read so far, only tracked with `DuplicateKeyMode::Reject`.
+    seen: BTreeSet<SeenKey>,
+    // Field-name table of the struct being read, for integer keys.
+    fields: Option<&'static [&'static str]>,
 }
 
 impl<'a, R: 'a, C> MapAccess<'a, R, C> {
     #[inline]
-    fn new(de: &'a mut Deserializer<R, C>, len: u32) -> Self {
-        MapAccess { de, left: len }
+    fn new(de: &'a mut Deserializer<R, C>, len: u32, fields: Option<&'static [&'static str]>) -> Self {
+        MapAccess { de, left: len, seen: BTreeSet::new(), fields }
     }
 }
 
@@ -866,12 +2361,47 @@ impl<'de, 'a, R: ReadSlice<'de> + 'a, C: SerializerConfig> de::MapAccess<'de> fo
     fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
         where K: DeserializeSeed<'de>
     {
-        if self.left > 0 {
-            self.left -= 1;
-            seed.deserialize(&mut *self.de).map(Some)
+        if self.left == 0 {
+            return Ok(None);
+        }
+        self.left -= 1;
+        if let Some(fields) = self.fields {
+            let marker = self.de.peek_or_read_marker()?;
+            if let Marker::FixPos(..) | Marker::U8 | Marker::U16 | Marker::U32 | Marker::U64 = marker {
+                let idx = u64::deserialize(&mut *self.de)?;
+                let name = match idx.try_into().ok().and_then(|idx: usize| fields.get(idx)) {
+                    Some(name) => *name,
+                    None => return Err(Error::Syntax(format!("field index {idx} is not in the field table"))),
+                };
+                if self.de.duplicate_keys != DuplicateKeyMode::Allow {
+                    if let Some(key) = self.seen.replace(SeenKey::Str(name.into())) {
+                        return Err(Error::DuplicateKey(key.to_string()));
+                    }
+                }
+                return seed.deserialize(BorrowedStrDeserializer::new(name)).map(Some);
+            }
+        }
+        let lenient = self.de.key_policy == KeyPolicy::Lenient;
+        if self.de.duplicate_keys == DuplicateKeyMode::Allow {
+            return if lenient {
+                seed.deserialize(LenientKey { de: &mut *self.de }).map(Some)
+            } else {
+                seed.deserialize(&mut *self.de).map(Some)
+            };
+        }
+
+        let mut key = None;
+        let res = if lenient {
+            seed.deserialize(KeyDeserializer { de: LenientKey { de: &mut *self.de }, key: &mut key })?
         } else {
-            Ok(None)
+            seed.deserialize(KeyDeserializer { de: &mut *self.de, key: &mut key })?
+        };
+        if let Some(key) = key {
+            if let Some(key) = self.seen.replace(key) {
+                return Err(Error::DuplicateKey(key.to_string()));
+            }
         }
+        Ok(Some(res))
     }
 
     #[inline]
@@ -887,6 +2417,379 @@ impl<'de, 'a, R: ReadSlice<'de> + 'a, C: SerializerConfig> de::MapAccess<'de> fo
     }
 }
 
+/// A scalar map key, as seen by the visitor of the key type.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum SeenKey {
+    Unit,
+    Bool(bool),
+    Int(i128),
+    Float(u64),
+    Str(String),
+    Bytes(Vec<u8>),
+}
+
+impl Display for SeenKey {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            SeenKey::Unit => fmt.write_str("nil"),
+            SeenKey::Bool(val) => write!(fmt, "{val}"),
+            SeenKey::Int(val) => write!(fmt, "{val}"),
+            SeenKey::Float(bits) => write!(fmt, "{}", f64::from_bits(bits)),
+            SeenKey::Str(ref val) => write!(fmt, "{val:?}"),
+            SeenKey::Bytes(ref val) => write!(fmt, "{val:?}"),
+        }
+    }
+}
+
+/// Deserializes a map key, recording its value in `key` if it's a scalar.
+struct KeyDeserializer<'k, D> {
+    de: D,
+    key: &'k mut Option<SeenKey>,
+}
+
+macro_rules! forward_to_key_visitor {
+    ($($method:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            #[inline]
+            fn $method<V: Visitor<'de>>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Self::Error> {
+                self.de.$method($($arg,)* KeyVisitor { visitor, key: self.key })
+            }
+        )*
+    };
+}
+
+impl<'de, D: de::Deserializer<'de>> de::Deserializer<'de> for KeyDeserializer<'_, D> {
+    type Error = D::Error;
+
+    forward_to_key_visitor! {
+        deserialize_any();
+        deserialize_bool();
+        deserialize_i8();
+        deserialize_i16();
+        deserialize_i32();
+        deserialize_i64();
+        deserialize_i128();
+        deserialize_u8();
+        deserialize_u16();
+        deserialize_u32();
+        deserialize_u64();
+        deserialize_u128();
+        deserialize_f32();
+        deserialize_f64();
+        deserialize_char();
+        deserialize_str();
+        deserialize_string();
+        deserialize_bytes();
+        deserialize_byte_buf();
+        deserialize_option();
+        deserialize_unit();
+        deserialize_unit_struct(name: &'static str);
+        deserialize_newtype_struct(name: &'static str);
+        deserialize_seq();
+        deserialize_tuple(len: usize);
+        deserialize_tuple_struct(name: &'static str, len: usize);
+        deserialize_map();
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]);
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+        deserialize_identifier();
+        deserialize_ignored_any();
+    }
+
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        self.de.is_human_readable()
+    }
+}
+
+/// Passes everything to the wrapped visitor, recording scalar values in `key`.
+struct KeyVisitor<'k, V> {
+    visitor: V,
+    key: &'k mut Option<SeenKey>,
+}
+
+macro_rules! record_key {
+    ($($method:ident($v:ident: $ty:ty) => $key:expr;)*) => {
+        $(
+            #[inline]
+            fn $method<E: de::Error>(self, $v: $ty) -> Result<Self::Value, E> {
+                *self.key = Some($key);
+                self.visitor.$method($v)
+            }
+        )*
+    };
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for KeyVisitor<'_, V> {
+    type Value = V::Value;
+
+    fn expecting(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        self.visitor.expecting(fmt)
+    }
+
+    record_key! {
+        visit_bool(v: bool) => SeenKey::Bool(v);
+        visit_i8(v: i8) => SeenKey::Int(v.into());
+        visit_i16(v: i16) => SeenKey::Int(v.into());
+        visit_i32(v: i32) => SeenKey::Int(v.into());
+        visit_i64(v: i64) => SeenKey::Int(v.into());
+        visit_i128(v: i128) => SeenKey::Int(v);
+        visit_u8(v: u8) => SeenKey::Int(v.into());
+        visit_u16(v: u16) => SeenKey::Int(v.into());
+        visit_u32(v: u32) => SeenKey::Int(v.into());
+        visit_u64(v: u64) => SeenKey::Int(v.into());
+        visit_f32(v: f32) => SeenKey::Float(f64::from(v).to_bits());
+        visit_f64(v: f64) => SeenKey::Float(v.to_bits());
+        visit_char(v: char) => SeenKey::Str(v.to_string());
+        visit_str(v: &str) => SeenKey::Str(v.to_owned());
+        visit_borrowed_str(v: &'de str) => SeenKey::Str(v.to_owned());
+        visit_string(v: String) => SeenKey::Str(v.clone());
+        visit_bytes(v: &[u8]) => SeenKey::Bytes(v.to_vec());
+        visit_borrowed_bytes(v: &'de [u8]) => SeenKey::Bytes(v.to_vec());
+        visit_byte_buf(v: Vec<u8>) => SeenKey::Bytes(v.clone());
+    }
+
+    #[inline]
+    fn visit_u128<E: de::Error>(self, v: u128) -> Result<Self::Value, E> {
+        *self.key = i128::try_from(v).ok().map(SeenKey::Int);
+        self.visitor.visit_u128(v)
+    }
+
+    #[inline]
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        *self.key = Some(SeenKey::Unit);
+        self.visitor.visit_unit()
+    }
+
+    #[inline]
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        *self.key = Some(SeenKey::Unit);
+        self.visitor.visit_none()
+    }
+
+    #[inline]
+    fn visit_some<D: de::Deserializer<'de>>(self, de: D) -> Result<Self::Value, D::Error> {
+        self.visitor.visit_some(KeyDeserializer { de, key: self.key })
+    }
+
+    #[inline]
+    fn visit_newtype_struct<D: de::Deserializer<'de>>(self, de: D) -> Result<Self::Value, D::Error> {
+        self.visitor.visit_newtype_struct(de)
+    }
+
+    #[inline]
+    fn visit_seq<A: de::SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+        self.visitor.visit_seq(seq)
+    }
+
+    #[inline]
+    fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        self.visitor.visit_map(map)
+    }
+
+    #[inline]
+    fn visit_enum<A: de::EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+        self.visitor.visit_enum(data)
+    }
+}
+
+/// Deserializes a map key with `KeyPolicy::Lenient`, converting between numbers and strings
+/// when the key type asks for the other.
+struct LenientKey<'a, R, C> {
+    de: &'a mut Deserializer<R, C>,
+}
+
+impl<'de, R: ReadSlice<'de>, C: SerializerConfig> LenientKey<'_, R, C> {
+    fn stringify_key<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let marker = self.de.peek_or_read_marker()?;
+        if !(marker.is_int() || marker.is_float() || marker == Marker::True || marker == Marker::False) {
+            return de::Deserializer::deserialize_str(&mut *self.de, visitor);
+        }
+        self.de.marker = None;
+
+        let text = match read_scalar(&mut self.de.rd, marker)? {
+            Scalar::Unit => return Err(de::Error::invalid_type(Unexpected::Unit, &visitor)),
+            Scalar::Bool(v) => v.to_string(),
+            Scalar::U8(v) => v.to_string(),
+            Scalar::U16(v) => v.to_string(),
+            Scalar::U32(v) => v.to_string(),
+            Scalar::U64(v) => v.to_string(),
+            Scalar::I8(v) => v.to_string(),
+            Scalar::I16(v) => v.to_string(),
+            Scalar::I32(v) => v.to_string(),
+            Scalar::I64(v) => v.to_string(),
+            Scalar::F32(v) => v.to_string(),
+            Scalar::F64(v) => v.to_string(),
+        };
+        visitor.visit_string(text)
+    }
+
+    fn parse_key<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let text = String::deserialize(&mut *self.de)?;
+        if let Ok(v) = text.parse::<i64>() {
+            visitor.visit_i64(v)
+        } else if let Ok(v) = text.parse::<u64>() {
+            visitor.visit_u64(v)
+        } else if let Ok(v) = text.parse::<f64>() {
+            visitor.visit_f64(v)
+        } else {
+            Err(de::Error::invalid_value(Unexpected::Str(&text), &visitor))
+        }
+    }
+}
+
+macro_rules! forward_to_deserializer {
+    ($($method:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            #[inline]
+            fn $method<V: Visitor<'de>>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Self::Error> {
+                self.de.$method($($arg,)* visitor)
+            }
+        )*
+    };
+}
+
+macro_rules! parse_lenient_key {
+    ($($method:ident)*) => {
+        $(
+            #[inline]
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                if self.de.peek_or_read_marker()?.is_str() {
+                    return self.parse_key(visitor);
+                }
+                self.de.$method(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de, R: ReadSlice<'de>, C: SerializerConfig> de::Deserializer<'de> for LenientKey<'_, R, C> {
+    type Error = Error;
+
+    #[inline]
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.stringify_key(visitor)
+    }
+
+    #[inline]
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.stringify_key(visitor)
+    }
+
+    parse_lenient_key! {
+        deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64 deserialize_i128
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_u128
+        deserialize_f32 deserialize_f64
+    }
+
+    forward_to_deserializer! {
+        deserialize_any();
+        deserialize_bool();
+        deserialize_char();
+        deserialize_bytes();
+        deserialize_byte_buf();
+        deserialize_option();
+        deserialize_unit();
+        deserialize_unit_struct(name: &'static str);
+        deserialize_newtype_struct(name: &'static str);
+        deserialize_seq();
+        deserialize_tuple(len: usize);
+        deserialize_tuple_struct(name: &'static str, len: usize);
+        deserialize_map();
+        deserialize_struct(name: &'static str, fields: &'static [&'static str]);
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+        deserialize_identifier();
+        deserialize_ignored_any();
+    }
+
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        self.de.is_human_readable
+    }
+}
+
+/// Reads a map as a sequence of `(key, value)` pairs, with `KeyPolicy::Lenient`.
+struct EntrySeqAccess<'a, R, C> {
+    de: &'a mut Deserializer<R, C>,
+    left: u32,
+}
+
+impl<'de, R: ReadSlice<'de>, C: SerializerConfig> de::SeqAccess<'de> for EntrySeqAccess<'_, R, C> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+        where T: DeserializeSeed<'de>
+    {
+        if self.left == 0 {
+            return Ok(None);
+        }
+        self.left -= 1;
+        seed.deserialize(EntryDeserializer { de: &mut *self.de }).map(Some)
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> Option<usize> {
+        self.left.try_into().ok()
+    }
+}
+
+/// Deserializes a single map entry as a sequence of its key and value.
+struct EntryDeserializer<'a, R, C> {
+    de: &'a mut Deserializer<R, C>,
+}
+
+impl<'de, R: ReadSlice<'de>, C: SerializerConfig> de::Deserializer<'de> for EntryDeserializer<'_, R, C> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let mut entry = EntryAccess { de: self.de, left: 2 };
+        let res = visitor.visit_seq(&mut entry)?;
+        match entry.left {
+            0 => Ok(res),
+            excess => Err(Error::LengthMismatch(2 - excess)),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option
+        unit unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier ignored_any
+    }
+
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        self.de.is_human_readable
+    }
+}
+
+struct EntryAccess<'a, R, C> {
+    de: &'a mut Deserializer<R, C>,
+    left: u32,
+}
+
+impl<'de, R: ReadSlice<'de>, C: SerializerConfig> de::SeqAccess<'de> for EntryAccess<'_, R, C> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+        where T: DeserializeSeed<'de>
+    {
+        match self.left {
+            0 => Ok(None),
+            2 => {
+                self.left = 1;
+                seed.deserialize(LenientKey { de: &mut *self.de }).map(Some)
+            }
+            _ => {
+                self.left = 0;
+                seed.deserialize(&mut *self.de).map(Some)
+            }
+        }
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> Option<usize> {
+        self.left.try_into().ok()
+    }
+}
+
 struct UnitVariantAccess<'a, R: 'a, C> {
     de: &'a mut Deserializer<R, C>,
 }
@@ -1010,6 +2913,71 @@ impl<'de, 'a, R: ReadSlice<'de>, C: SerializerConfig> de::VariantAccess<'de> for
     }
 }
 
+/// Variant access of enums written in the layout of [`CompatMode::V08`], once the outer array
+/// header has been read.
+struct LegacyVariantAccess<'a, R, C> {
+    de: &'a mut Deserializer<R, C>,
+}
+
+impl<'a, R: 'a, C> LegacyVariantAccess<'a, R, C> {
+    pub fn new(de: &'a mut Deserializer<R, C>) -> Self {
+        LegacyVariantAccess { de }
+    }
+}
+
+impl<'de, 'a, R: ReadSlice<'de>, C: SerializerConfig> LegacyVariantAccess<'a, R, C> {
+    /// Reads the header of the data array, which must have `len` elements.
+    fn read_data_len(&mut self, len: u32) -> Result<(), Error> {
+        match decode::read_array_len(&mut self.de.rd)? {
+            n if n == len => Ok(()),
+            n => Err(Error::LengthMismatch(n)),
+        }
+    }
+}
+
+impl<'de, 'a, R: ReadSlice<'de>, C: SerializerConfig> de::EnumAccess<'de> for LegacyVariantAccess<'a, R, C> {
+    type Error = Error;
+    type Variant = Self;
+
+    #[inline]
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self), Error>
+        where V: de::DeserializeSeed<'de>,
+    {
+        Ok((seed.deserialize(&mut *self.de)?, self))
+    }
+}
+
+impl<'de, 'a, R: ReadSlice<'de>, C: SerializerConfig> de::VariantAccess<'de> for LegacyVariantAccess<'a, R, C> {
+    type Error = Error;
+
+    #[inline]
+    fn unit_variant(mut self) -> Result<(), Error> {
+        self.read_data_len(0)
+    }
+
+    #[inline]
+    fn newtype_variant_seed<T>(mut self, seed: T) -> Result<T::Value, Self::Error>
+        where T: DeserializeSeed<'de>
+    {
+        self.read_data_len(1)?;
+        seed.deserialize(self.de)
+    }
+
+    #[inline]
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, Error>
+        where V: Visitor<'de>
+    {
+        de::Deserializer::deserialize_tuple(self.de, len, visitor)
+    }
+
+    #[inline]
+    fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value, Error>
+        where V: Visitor<'de>
+    {
+        de::Deserializer::deserialize_tuple(self.de, fields.len(), visitor)
+    }
+}
+
 /// Unification of both borrowed and non-borrowed reference types.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Reference<'b, 'c, T: ?Sized + 'static> {
@@ -1021,17 +2989,117 @@ pub enum Reference<'b, 'c, T: ?Sized + 'static> {
 
 /// Extends the `Read` trait by allowing to read slices directly by borrowing bytes.
 ///
-/// Used to allow zero-copy reading.
+/// Used to allow zero-copy reading. Readers backed by a byte slice, like the one used by
+/// [`from_slice`], return [`Reference::Borrowed`] subslices of the input, so `&str` and `&[u8]`
+/// targets borrow from it directly and `[u8; N]` targets are filled straight from it, without any
+/// intermediate allocation. Other readers copy into an internal buffer that is reused between
+/// calls.
 pub trait ReadSlice<'de>: Read {
     /// Reads the exact number of bytes from the underlying byte-array.
     fn read_slice<'a>(&'a mut self, len: usize) -> Result<Reference<'de, 'a, [u8]>, io::Error>;
+
+    /// Returns how many bytes were read so far, if this reader keeps track of it.
+    ///
+    /// Used for [`Deserializer::marker_position`].
+    #[inline]
+    fn position(&self) -> Option<u64> {
+        None
+    }
+
+    /// Consumes the exact number of bytes without returning them.
+    #[inline]
+    fn skip(&mut self, len: usize) -> Result<(), io::Error> {
+        self.read_slice(len).map(drop)
+    }
+
+    /// Starts recording the consumed bytes, preceded by the already consumed marker `peeked`, if
+    /// any. This is how [`RawValue`](crate::RawValue)s capture the encoding of a value.
+    ///
+    /// Readers that don't support this ignore it and return `None` from
+    /// [`end_capture`](Self::end_capture).
+    #[inline]
+    fn begin_capture(&mut self, _peeked: Option<u8>) {}
+
+    /// Stops recording, returning the bytes consumed since
+    /// [`begin_capture`](Self::begin_capture).
+    #[inline]
+    fn end_capture<'a>(&'a mut self) -> Option<Reference<'de, 'a, [u8]>> {
+        None
+    }
+}
+
+/// Appends up to `len` bytes read from `rd` to `buf`, returning how many were read before EOF.
+#[cfg(feature = "std")]
+fn read_to_vec<R: Read>(rd: &mut R, len: usize, buf: &mut Vec<u8>) -> io::Result<usize> {
+    rd.by_ref().take(len as u64).read_to_end(buf)
+}
+
+#[cfg(not(feature = "std"))]
+fn read_to_vec<R: Read>(rd: &mut R, len: usize, buf: &mut Vec<u8>) -> io::Result<usize> {
+    let mut read = 0;
+    while read < len {
+        // Grows the buffer in bounded steps, as `len` isn't trusted.
+        let filled = buf.len();
+        buf.resize(filled + (len - read).min(MAX_PREALLOC), 0);
+        let res = rd.read(&mut buf[filled..]);
+        buf.truncate(filled + *res.as_ref().unwrap_or(&0));
+        match res? {
+            0 => break,
+            n => read += n,
+        }
+    }
+    Ok(read)
+}
+
+/// Reads and discards the rest of `rd`, returning how many bytes there were.
+#[cfg(feature = "std")]
+fn skip_to_end<R: Read>(rd: &mut R) -> io::Result<u64> {
+    std::io::copy(rd, &mut std::io::sink())
+}
+
+#[cfg(not(feature = "std"))]
+fn skip_to_end<R: Read>(rd: &mut R) -> io::Result<u64> {
+    let mut buf = [0; 256];
+    let mut len = 0;
+    loop {
+        match rd.read(&mut buf)? {
+            0 => return Ok(len),
+            n => len += n as u64,
+        }
+    }
+}
+
+/// Reads and discards up to `len` bytes of `rd`, returning how many were read before EOF.
+#[cfg(feature = "std")]
+fn skip_up_to<R: Read>(rd: &mut R, len: u64) -> io::Result<u64> {
+    std::io::copy(&mut rd.by_ref().take(len), &mut std::io::sink())
+}
+
+#[cfg(not(feature = "std"))]
+fn skip_up_to<R: Read>(rd: &mut R, len: u64) -> io::Result<u64> {
+    let mut buf = [0; 256];
+    let mut read = 0;
+    while read < len {
+        let chunk = (len - read).min(buf.len() as u64) as usize;
+        match rd.read(&mut buf[..chunk])? {
+            0 => break,
+            n => read += n as u64,
+        }
+    }
+    Ok(read)
 }
 
+/// The largest buffer that is allocated for a string or binary before its data is read.
+const MAX_PREALLOC: usize = 1 << 20;
+
 /// Owned reader wrapper.
 #[derive(Debug)]
 pub struct ReadReader<R: Read> {
     rd: R,
     buf: Vec<u8>,
+    pos: u64,
+    // Bytes consumed since `begin_capture`.
+    capture: Option<Vec<u8>>,
 }
 
 impl<R: Read> ReadReader<R> {
@@ -1040,6 +3108,8 @@ impl<R: Read> ReadReader<R> {
         ReadReader {
             rd,
             buf: Vec::with_capacity(128),
+            pos: 0,
+            capture: None,
         }
     }
 }
@@ -1048,24 +3118,157 @@ impl<'de, R: Read> ReadSlice<'de> for ReadReader<R> {
     #[inline]
     fn read_slice<'a>(&'a mut self, len: usize) -> Result<Reference<'de, 'a, [u8]>, io::Error> {
         self.buf.clear();
-        let read = self.rd.by_ref().take(len as u64).read_to_end(&mut self.buf)?;
+        // Reading into a buffer of the declared length avoids growing it in small steps, but
+        // the length isn't trusted for more than a bounded allocation up front.
+        self.buf.reserve(len.min(MAX_PREALLOC));
+        let read = read_to_vec(&mut self.rd, len, &mut self.buf)?;
+        self.pos += read as u64;
+        if let Some(capture) = &mut self.capture {
+            capture.extend_from_slice(&self.buf);
+        }
         if read != len {
             return Err(io::ErrorKind::UnexpectedEof.into());
         }
 
         Ok(Reference::Copied(&self.buf[..]))
     }
+
+    #[inline]
+    fn position(&self) -> Option<u64> {
+        Some(self.pos)
+    }
+
+    #[inline]
+    fn skip(&mut self, len: usize) -> Result<(), io::Error> {
+        // Unlike `read_slice`, this goes through a fixed-size stack buffer, so skipping large
+        // values doesn't grow `buf`. Captured bytes are kept, though.
+        let read = match &mut self.capture {
+            Some(capture) => read_to_vec(&mut self.rd, len, capture)? as u64,
+            None => skip_up_to(&mut self.rd, len as u64)?,
+        };
+        self.pos += read;
+        if read != len as u64 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn begin_capture(&mut self, peeked: Option<u8>) {
+        self.capture = Some(peeked.into_iter().collect());
+    }
+
+    #[inline]
+    fn end_capture<'a>(&'a mut self) -> Option<Reference<'de, 'a, [u8]>> {
+        self.buf = self.capture.take()?;
+        Some(Reference::Copied(&self.buf[..]))
+    }
 }
 
 impl<R: Read> Read for ReadReader<R> {
     #[inline]
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        self.rd.read(buf)
+        let n = self.rd.read(buf)?;
+        self.pos += n as u64;
+        if let Some(capture) = &mut self.capture {
+            capture.extend_from_slice(&buf[..n]);
+        }
+        Ok(n)
+    }
+
+    #[inline]
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        self.rd.read_exact(buf)?;
+        self.pos += buf.len() as u64;
+        if let Some(capture) = &mut self.capture {
+            capture.extend_from_slice(buf);
+        }
+        Ok(())
+    }
+}
+
+// With `std`, this comes from the blanket implementation for `io::Read`.
+#[cfg(not(feature = "std"))]
+impl<R: Read> RmpRead for ReadReader<R> {
+    type Error = io::Error;
+
+    #[inline]
+    fn read_exact_buf(&mut self, buf: &mut [u8]) -> Result<(), io::Error> {
+        self.read_exact(buf)
+    }
+}
+
+/// Reader wrapper copying strings and binaries into a caller-provided buffer.
+///
+/// Created by [`Deserializer::from_read_scratch`]. Data that doesn't fit the buffer fails with an
+/// I/O error of kind [`ErrorKind::OutOfMemory`], rather than being allocated.
+#[derive(Debug)]
+pub struct ReadScratchReader<'s, R: Read> {
+    rd: R,
+    scratch: &'s mut [u8],
+    pos: u64,
+}
+
+impl<'de, R: Read> ReadSlice<'de> for ReadScratchReader<'_, R> {
+    #[inline]
+    fn read_slice<'a>(&'a mut self, len: usize) -> Result<Reference<'de, 'a, [u8]>, io::Error> {
+        let capacity = self.scratch.len();
+        let buf = self.scratch.get_mut(..len).ok_or_else(|| scratch_overflow(len, capacity))?;
+        self.rd.read_exact(buf)?;
+        self.pos += len as u64;
+        Ok(Reference::Copied(buf))
+    }
+
+    #[inline]
+    fn skip(&mut self, len: usize) -> Result<(), io::Error> {
+        // Skipped data doesn't have to fit in the scratch buffer.
+        let read = skip_up_to(&mut self.rd, len as u64)?;
+        self.pos += read;
+        if read != len as u64 {
+            return Err(ErrorKind::UnexpectedEof.into());
+        }
+        Ok(())
+    }
+}
+
+/// The error for data that doesn't fit the scratch buffer of a [`ReadScratchReader`].
+#[cfg(feature = "std")]
+fn scratch_overflow(len: usize, capacity: usize) -> io::Error {
+    io::Error::new(
+        ErrorKind::OutOfMemory,
+        format!("{len} bytes don't fit in the scratch buffer of {capacity} bytes"),
+    )
+}
+
+#[cfg(not(feature = "std"))]
+fn scratch_overflow(_len: usize, _capacity: usize) -> io::Error {
+    ErrorKind::OutOfMemory.into()
+}
+
+impl<R: Read> Read for ReadScratchReader<'_, R> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.rd.read(buf)?;
+        self.pos += n as u64;
+        Ok(n)
     }
 
     #[inline]
     fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
-        self.rd.read_exact(buf)
+        self.rd.read_exact(buf)?;
+        self.pos += buf.len() as u64;
+        Ok(())
+    }
+}
+
+// With `std`, this comes from the blanket implementation for `io::Read`.
+#[cfg(not(feature = "std"))]
+impl<R: Read> RmpRead for ReadScratchReader<'_, R> {
+    type Error = io::Error;
+
+    #[inline]
+    fn read_exact_buf(&mut self, buf: &mut [u8]) -> Result<(), io::Error> {
+        self.read_exact(buf)
     }
 }
 
@@ -1074,6 +3277,8 @@ impl<R: Read> Read for ReadReader<R> {
 pub struct ReadRefReader<'a, R: ?Sized> {
     whole_slice: &'a R,
     buf: &'a [u8],
+    // Remaining length of `buf` at `begin_capture`.
+    capture_from: Option<usize>,
 }
 
 impl<'a, T> ReadRefReader<'a, T> {
@@ -1090,6 +3295,7 @@ impl<'a, T: AsRef<[u8]> + ?Sized> ReadRefReader<'a, T> {
         Self {
             whole_slice: rd,
             buf: rd.as_ref(),
+            capture_from: None,
         }
     }
 }
@@ -1097,12 +3303,32 @@ impl<'a, T: AsRef<[u8]> + ?Sized> ReadRefReader<'a, T> {
 impl<'a, T: AsRef<[u8]> + ?Sized> Read for ReadRefReader<'a, T> {
     #[inline]
     fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
-        self.buf.read(buf)
+        let len = buf.len().min(self.buf.len());
+        let (a, b) = self.buf.split_at(len);
+        buf[..len].copy_from_slice(a);
+        self.buf = b;
+        Ok(len)
     }
 
     #[inline]
     fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), io::Error> {
-        self.buf.read_exact(buf)
+        if buf.len() > self.buf.len() {
+            return Err(ErrorKind::UnexpectedEof.into());
+        }
+        let (a, b) = self.buf.split_at(buf.len());
+        buf.copy_from_slice(a);
+        self.buf = b;
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<'a, T: AsRef<[u8]> + ?Sized> RmpRead for ReadRefReader<'a, T> {
+    type Error = io::Error;
+
+    #[inline]
+    fn read_exact_buf(&mut self, buf: &mut [u8]) -> Result<(), io::Error> {
+        self.read_exact(buf)
     }
 }
 
@@ -1116,6 +3342,24 @@ impl<'de, T: AsRef<[u8]> + ?Sized> ReadSlice<'de> for ReadRefReader<'de, T> {
         self.buf = b;
         Ok(Reference::Borrowed(a))
     }
+
+    #[inline]
+    fn position(&self) -> Option<u64> {
+        Some((self.whole_slice.as_ref().len() - self.buf.len()) as u64)
+    }
+
+    #[inline]
+    fn begin_capture(&mut self, peeked: Option<u8>) {
+        // A peeked marker was consumed from this slice, right before the remaining part.
+        self.capture_from = Some(self.buf.len() + usize::from(peeked.is_some()));
+    }
+
+    #[inline]
+    fn end_capture<'a>(&'a mut self) -> Option<Reference<'de, 'a, [u8]>> {
+        let whole: &'de [u8] = self.whole_slice.as_ref();
+        let start = whole.len() - self.capture_from.take()?;
+        Some(Reference::Borrowed(&whole[start..whole.len() - self.buf.len()]))
+    }
 }
 
 #[test]
@@ -1129,6 +3373,131 @@ fn test_as_ref_reader() {
     assert_eq!(rd.read_slice(4).unwrap(), Reference::Borrowed(&[7, 8, 9, 10][..]));
 }
 
+/// Reader wrapper that counts the number of bytes read through it.
+#[derive(Debug)]
+struct CountingReader<R> {
+    rd: R,
+    count: u64,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.rd.read(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl<R: Read> RmpRead for CountingReader<R> {
+    type Error = io::Error;
+
+    #[inline]
+    fn read_exact_buf(&mut self, buf: &mut [u8]) -> Result<(), io::Error> {
+        self.read_exact(buf)
+    }
+}
+
+/// Iterator that deserializes a stream of consecutive top-level MessagePack values.
+///
+/// Values are read one after another until the underlying reader reaches EOF at a value
+/// boundary. A reader that ends in the middle of a value yields an error instead.
+///
+/// After the first error the iterator is exhausted. Use [`byte_offset`](Self::byte_offset) to find
+/// where the last successfully decoded value ended, and [`into_inner`](Self::into_inner) to get the
+/// reader back.
+///
+/// # Examples
+///
+/// ```
+/// use rmp_serde::decode::StreamDeserializer;
+///
+/// let mut buf = Vec::new();
+/// buf.extend(rmp_serde::to_vec(&(1, "one")).unwrap());
+/// buf.extend(rmp_serde::to_vec(&(2, "two")).unwrap());
+///
+/// let mut stream = StreamDeserializer::<_, (u8, String)>::new(&buf[..]);
+/// assert_eq!((1, "one".to_owned()), stream.next().unwrap().unwrap());
+/// assert_eq!(6, stream.byte_offset());
+/// assert_eq!((2, "two".to_owned()), stream.next().unwrap().unwrap());
+/// assert!(stream.next().is_none());
+/// ```
+#[derive(Debug)]
+pub struct StreamDeserializer<R: Read, T> {
+    de: Deserializer<ReadReader<CountingReader<R>>>,
+    offset: u64,
+    failed: bool,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<R: Read, T> StreamDeserializer<R, T> {
+    /// Constructs a new `StreamDeserializer` by consuming the given reader.
+    #[inline]
+    pub fn new(rd: R) -> Self {
+        StreamDeserializer {
+            de: Deserializer::new(CountingReader { rd, count: 0 }),
+            offset: 0,
+            failed: false,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of bytes consumed by the values successfully deserialized so far.
+    ///
+    /// If the last call to `next` failed, this is the offset where the failed value starts.
+    #[inline]
+    pub fn byte_offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Gets a reference to the underlying reader.
+    #[inline]
+    pub fn get_ref(&self) -> &R {
+        &self.de.get_ref().rd
+    }
+
+    /// Consumes this stream returning the underlying reader.
+    ///
+    /// If deserialization failed, the reader may be positioned anywhere after
+    /// [`byte_offset`](Self::byte_offset).
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.de.into_inner().rd
+    }
+}
+
+impl<R: Read, T: DeserializeOwned> Iterator for StreamDeserializer<R, T> {
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        // Clean EOF between two values terminates the stream.
+        match self.de.peek_or_read_marker() {
+            Ok(..) => {}
+            Err(Error::InvalidMarkerRead(ref err)) if err.kind() == ErrorKind::UnexpectedEof => return None,
+            Err(err) => {
+                self.failed = true;
+                return Some(Err(err));
+            }
+        }
+
+        match T::deserialize(&mut self.de) {
+            Ok(val) => {
+                self.offset = self.de.get_ref().count;
+                Some(Ok(val))
+            }
+            Err(err) => {
+                self.failed = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
 /// Deserialize an instance of type `T` from an I/O stream of MessagePack.
 ///
 /// # Errors
@@ -1136,12 +3505,23 @@ fn test_as_ref_reader() {
 /// This conversion can fail if the structure of the Value does not match the structure expected
 /// by `T`. It can also fail if the structure is correct but `T`'s implementation of `Deserialize`
 /// decides that something is wrong with the data, for example required struct fields are missing.
+/// The error carries the offset of the last marker read, see [`Error::offset`].
 #[inline]
 pub fn from_read<R, T>(rd: R) -> Result<T, Error>
 where R: Read,
       T: DeserializeOwned
 {
-    Deserialize::deserialize(&mut Deserializer::new(rd))
+    deserialize_at(&mut Deserializer::new(rd))
+}
+
+/// Deserializes a `T`, attaching the offset of the last marker read to any error.
+fn deserialize_at<'de, R, C, T>(de: &mut Deserializer<R, C>) -> Result<T, Error>
+where
+    R: ReadSlice<'de>,
+    C: SerializerConfig,
+    T: Deserialize<'de>,
+{
+    T::deserialize(&mut *de).map_err(|err| err.at(de.marker_pos))
 }
 
 /// Deserialize a temporary scope-bound instance of type `T` from a slice, with zero-copy if possible.
@@ -1154,6 +3534,7 @@ where R: Read,
 /// This conversion can fail if the structure of the Value does not match the structure expected
 /// by `T`. It can also fail if the structure is correct but `T`'s implementation of `Deserialize`
 /// decides that something is wrong with the data, for example required struct fields are missing.
+/// The error carries the offset of the last marker read, see [`Error::offset`].
 ///
 /// # Examples
 ///
@@ -1180,6 +3561,77 @@ where
     from_read_ref(input)
 }
 
+/// Deserialize an instance of type `T` from a slice, failing if any bytes remain after it.
+///
+/// Unlike [`from_slice`], which silently ignores anything following the first value, this
+/// ensures that the slice contains exactly one MessagePack value.
+///
+/// # Errors
+///
+/// In addition to the errors of [`from_slice`], this returns [`Error::TrailingBytes`] with the
+/// number of unconsumed bytes.
+///
+/// # Examples
+///
+/// ```
+/// use rmp_serde::decode::Error;
+///
+/// assert_eq!(42u8, rmp_serde::from_slice_exact(&[0x2a]).unwrap());
+///
+/// match rmp_serde::from_slice_exact::<u8>(&[0x2a, 0x2a]) {
+///     Err(Error::TrailingBytes(1)) => {}
+///     other => panic!("unexpected result: {other:?}"),
+/// }
+/// ```
+pub fn from_slice_exact<'a, T>(input: &'a [u8]) -> Result<T, Error>
+where
+    T: Deserialize<'a>,
+{
+    let mut de = Deserializer::from_read_ref(input);
+    let val = deserialize_at(&mut de)?;
+    de.end()?;
+    Ok(val)
+}
+
+/// Deserialize all consecutive top-level values of type `T` from a slice, such as written by
+/// [`to_vec_all`](crate::to_vec_all).
+///
+/// Values borrow from the slice where possible, like with [`from_slice`]. To read values one at a
+/// time from an I/O stream instead, use [`StreamDeserializer`].
+///
+/// # Errors
+///
+/// Fails with the first error of any value, including a value cut short by the end of the
+/// slice. The error is wrapped with [`Error::at`] to tell the offset of the last marker read
+/// before the failure, as given by [`Deserializer::marker_position`]. That is the marker at
+/// fault, which may be nested within the failing value rather than start it.
+///
+/// # Examples
+///
+/// ```
+/// let buf = rmp_serde::to_vec_all(&[(1, "one"), (2, "two")]).unwrap();
+/// let vals: Vec<(u8, &str)> = rmp_serde::from_slice_all(&buf).unwrap();
+/// assert_eq!(vec![(1, "one"), (2, "two")], vals);
+///
+/// // The string marker of the first value is at offset 2.
+/// let err = rmp_serde::from_slice_all::<(u8, u8)>(&buf).unwrap_err();
+/// assert_eq!(Some(2), err.offset());
+/// ```
+pub fn from_slice_all<'a, T>(input: &'a [u8]) -> Result<Vec<T>, Error>
+where
+    T: Deserialize<'a>,
+{
+    let mut de = Deserializer::from_read_ref(input);
+    let mut vals = Vec::new();
+    while de.position() < input.len() as u64 {
+        match Deserialize::deserialize(&mut de) {
+            Ok(val) => vals.push(val),
+            Err(err) => return Err(err.at(de.marker_position())),
+        }
+    }
+    Ok(vals)
+}
+
 #[inline]
 #[doc(hidden)]
 #[deprecated(note = "use from_slice")]
@@ -1189,5 +3641,5 @@ where
     T: Deserialize<'a>,
 {
     let mut de = Deserializer::from_read_ref(rd);
-    Deserialize::deserialize(&mut de)
+    deserialize_at(&mut de)
 }
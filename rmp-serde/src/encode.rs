@@ -1,33 +1,41 @@
 //! Serialize a Rust data structure into MessagePack data.
 
 use crate::bytes::OnlyBytes;
-use crate::config::BytesMode;
-use std::error;
-use std::fmt::{self, Display};
-use std::io::Write;
-use std::marker::PhantomData;
+use crate::config::{BytesMode, CompatMode, FieldTable, FlattenMode, FloatMode, MapEntries, MapHook, OptionMode, VariantMode};
+use crate::io::{self, Write};
+use alloc::collections::BTreeSet;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{self, Display};
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
+#[cfg(feature = "std")]
+use std::io::{BufWriter, IoSlice, Seek, SeekFrom};
 
 use serde;
 use serde::ser::{
     SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
-    SerializeTupleStruct, SerializeTupleVariant,
+    SerializeTupleStruct, SerializeTupleVariant, StdError,
 };
 use serde::Serialize;
 
+use rmp::decode::RmpRead;
 use rmp::encode::ValueWriteError;
 use rmp::{encode, Marker};
 
 use crate::config::{
     BinaryConfig, DefaultConfig, HumanReadableConfig, RuntimeConfig, SerializerConfig, StructMapConfig, StructTupleConfig
 };
-use crate::MSGPACK_EXT_STRUCT_NAME;
+use crate::raw_value::RAW_VALUE_STRUCT_NAME;
+use crate::{MSGPACK_EXT_STRUCT_NAME, MSGPACK_STR_STRUCT_NAME, NAMED_STRUCT_NAME};
 
 /// This type represents all possible errors that can occur when serializing or
 /// deserializing MessagePack data.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     /// Failed to write a MessagePack value.
-    InvalidValueWrite(ValueWriteError),
+    InvalidValueWrite(ValueWriteError<io::Error>),
     //TODO: This can be removed at some point
     /// Failed to serialize struct, sequence or map, because its length is unknown.
     UnknownLength,
@@ -35,18 +43,69 @@ pub enum Error {
     InvalidDataModel(&'static str),
     /// Depth limit exceeded
     DepthLimitExceeded,
+    /// Failed to serialize a struct with flattened fields, because structs are written as
+    /// tuples and `FlattenMode::Error` is set.
+    FlattenedStruct,
+    /// The value has no canonical encoding, e.g. a NaN float.
+    ///
+    /// Only returned by serializers with [`Serializer::with_strict_canonical`], and by those with
+    /// [`Serializer::with_canonical`] for [`RawValue`](crate::RawValue)s with unsorted maps.
+    NonCanonical(&'static str),
+    /// Failed to decode the input of [`reencode`](crate::reencode).
+    Decode(crate::decode::Error),
     /// Catchall for syntax error messages.
     Syntax(String),
 }
 
-impl error::Error for Error {
+impl Error {
+    /// Returns the category of this error.
+    ///
+    /// Write errors are reported as [`ErrorKind::UnexpectedEof`](crate::ErrorKind::UnexpectedEof)
+    /// or [`ErrorKind::Io`](crate::ErrorKind::Io), depending on the kind of their I/O error.
+    #[must_use]
+    pub fn kind(&self) -> crate::ErrorKind {
+        match *self {
+            Error::InvalidValueWrite(..) => match self.io_error() {
+                Some(err) if err.kind() == io::ErrorKind::UnexpectedEof => crate::ErrorKind::UnexpectedEof,
+                _ => crate::ErrorKind::Io,
+            },
+            Error::DepthLimitExceeded => crate::ErrorKind::LimitExceeded,
+            Error::Decode(ref err) => err.kind(),
+            Error::UnknownLength |
+            Error::InvalidDataModel(..) |
+            Error::FlattenedStruct |
+            Error::NonCanonical(..) |
+            Error::Syntax(..) => crate::ErrorKind::Syntax,
+        }
+    }
+
+    /// Returns the I/O error of the underlying writer that caused this error, if any.
+    #[must_use]
+    pub fn io_error(&self) -> Option<&io::Error> {
+        match *self {
+            Error::InvalidValueWrite(ValueWriteError::InvalidMarkerWrite(ref err) | ValueWriteError::InvalidDataWrite(ref err)) => {
+                Some(err)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl StdError for Error {
     #[cold]
-    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
         match *self {
+            #[cfg(feature = "std")]
             Error::InvalidValueWrite(ref err) => Some(err),
+            // `rmp` implements `Error` for its errors only with `std`.
+            #[cfg(not(feature = "std"))]
+            Error::InvalidValueWrite(..) => None,
             Error::UnknownLength => None,
             Error::InvalidDataModel(_) => None,
             Error::DepthLimitExceeded => None,
+            Error::FlattenedStruct => None,
+            Error::NonCanonical(..) => None,
+            Error::Decode(ref err) => Some(err),
             Error::Syntax(..) => None,
         }
     }
@@ -62,18 +121,30 @@ impl Display for Error {
             }
             Error::InvalidDataModel(r) => write!(f, "serialize data model is invalid: {r}"),
             Error::DepthLimitExceeded => f.write_str("depth limit exceeded"),
+            Error::FlattenedStruct => {
+                f.write_str("attempt to serialize struct with flattened fields as a tuple")
+            }
+            Error::NonCanonical(r) => write!(f, "value has no canonical encoding: {r}"),
+            Error::Decode(ref err) => write!(f, "failed to decode input: {err}"),
             Error::Syntax(ref msg) => f.write_str(msg),
         }
     }
 }
 
-impl From<ValueWriteError> for Error {
+impl From<ValueWriteError<io::Error>> for Error {
     #[cold]
-    fn from(err: ValueWriteError) -> Error {
+    fn from(err: ValueWriteError<io::Error>) -> Error {
         Error::InvalidValueWrite(err)
     }
 }
 
+impl From<io::Error> for Error {
+    #[cold]
+    fn from(err: io::Error) -> Error {
+        Error::InvalidValueWrite(ValueWriteError::InvalidDataWrite(err))
+    }
+}
+
 impl serde::ser::Error for Error {
     /// Raised when there is general error when deserializing a type.
     #[cold]
@@ -115,7 +186,7 @@ pub trait UnderlyingWrite {
 // TODO: Docs. Examples.
 #[derive(Debug)]
 pub struct Serializer<W, C = DefaultConfig> {
-    wr: W,
+    wr: CountingWriter<W>,
     depth: u16,
     config: RuntimeConfig,
     _back_compat_config: PhantomData<C>,
@@ -125,21 +196,104 @@ impl<W: Write, C> Serializer<W, C> {
     /// Gets a reference to the underlying writer.
     #[inline(always)]
     pub fn get_ref(&self) -> &W {
-        &self.wr
+        &self.wr.wr
     }
 
     /// Gets a mutable reference to the underlying writer.
     ///
-    /// It is inadvisable to directly write to the underlying writer.
+    /// It is inadvisable to directly write to the underlying writer. Use
+    /// [`serialize_array_header`](Self::serialize_array_header) and
+    /// [`serialize_map_header`](Self::serialize_map_header) to write containers whose elements
+    /// are serialized separately.
     #[inline(always)]
     pub fn get_mut(&mut self) -> &mut W {
-        &mut self.wr
+        &mut self.wr.wr
+    }
+
+    /// Writes the header of an array of `len` elements, which must then be serialized one by
+    /// one with this serializer.
+    ///
+    /// This allows building a message from a hand-written envelope and serde-serialized parts.
+    /// The header can only be written between top-level values, as the serializer is borrowed
+    /// while a value is being serialized, and it's counted in
+    /// [`bytes_written`](Self::bytes_written).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to the underlying writer fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde::Serialize;
+    ///
+    /// let mut se = rmp_serde::Serializer::new(Vec::new());
+    /// se.serialize_array_header(2).unwrap();
+    /// 1u8.serialize(&mut se).unwrap();
+    /// "body".serialize(&mut se).unwrap();
+    ///
+    /// let buf = se.into_inner();
+    /// assert_eq!((1, "body"), rmp_serde::from_slice::<(u8, &str)>(&buf).unwrap());
+    /// ```
+    #[inline]
+    pub fn serialize_array_header(&mut self, len: u32) -> Result<(), Error> {
+        encode::write_array_len(&mut self.wr, len)?;
+        Ok(())
+    }
+
+    /// Writes the header of a map of `len` entries, whose keys and values must then be
+    /// serialized alternately with this serializer.
+    ///
+    /// See [`serialize_array_header`](Self::serialize_array_header) for details.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if writing to the underlying writer fails.
+    #[inline]
+    pub fn serialize_map_header(&mut self, len: u32) -> Result<(), Error> {
+        encode::write_map_len(&mut self.wr, len)?;
+        Ok(())
     }
 
     /// Unwraps this `Serializer`, returning the underlying writer.
     #[inline(always)]
     pub fn into_inner(self) -> W {
-        self.wr
+        self.wr.wr
+    }
+
+    /// Returns the number of bytes written by this serializer since it was created or since the
+    /// last call to [`reset_bytes_written`](Self::reset_bytes_written).
+    ///
+    /// The count is kept by the serializer itself, so it works with any writer, not only
+    /// seekable ones. Bytes written directly to the underlying writer aren't counted. When
+    /// several values are serialized into one stream, resetting the count before each of them
+    /// gives their encoded lengths, e.g. to build an index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde::Serialize;
+    ///
+    /// let mut se = rmp_serde::Serializer::new(Vec::new());
+    /// let mut lens = Vec::new();
+    /// for val in ["a", "bcd"] {
+    ///     se.reset_bytes_written();
+    ///     val.serialize(&mut se).unwrap();
+    ///     lens.push(se.bytes_written());
+    /// }
+    ///
+    /// assert_eq!(vec![2, 4], lens);
+    /// assert_eq!(6, se.get_ref().len());
+    /// ```
+    #[inline]
+    pub fn bytes_written(&self) -> u64 {
+        self.wr.written
+    }
+
+    /// Resets the count of [`bytes_written`](Self::bytes_written) to zero.
+    #[inline]
+    pub fn reset_bytes_written(&mut self) {
+        self.wr.written = 0;
     }
 
     /// Changes the maximum nesting depth that is allowed.
@@ -163,7 +317,7 @@ impl<W: Write> Serializer<W, DefaultConfig> {
     #[inline]
     pub fn new(wr: W) -> Self {
         Serializer {
-            wr,
+            wr: CountingWriter::new(wr),
             depth: 1024,
             config: RuntimeConfig::new(DefaultConfig),
             _back_compat_config: PhantomData,
@@ -171,17 +325,97 @@ impl<W: Write> Serializer<W, DefaultConfig> {
     }
 }
 
+impl<W: Write, C: SerializerConfig> Serializer<W, C> {
+    /// Constructs a new `MessagePack` serializer with the given configuration, whose output will
+    /// be written to the writer specified.
+    ///
+    /// ```rust
+    /// use serde::Serialize;
+    /// use rmp_serde::config::{DefaultConfig, StructMapConfig};
+    ///
+    /// #[derive(Serialize)]
+    /// struct Point { x: u8 }
+    ///
+    /// let mut buf = Vec::new();
+    /// Point { x: 1 }.serialize(&mut rmp_serde::Serializer::with_config(&mut buf, StructMapConfig::new(DefaultConfig))).unwrap();
+    /// assert_eq!(vec![0x81, 0xa1, 0x78, 0x01], buf);
+    /// ```
+    #[inline]
+    pub fn with_config(wr: W, config: C) -> Self {
+        Serializer {
+            wr: CountingWriter::new(wr),
+            depth: 1024,
+            config: RuntimeConfig::new(config),
+            _back_compat_config: PhantomData,
+        }
+    }
+}
+
 impl<'a, W: Write + 'a, C> Serializer<W, C> {
     #[inline]
     fn compound(&'a mut self) -> Result<Compound<'a, W, C>, Error> {
-        Ok(Compound { se: self })
+        Ok(Compound { se: self, fields: None })
+    }
+
+    /// Writes the header of a struct, as a map if it has field names or indices as keys.
+    #[inline]
+    fn struct_compound(&'a mut self, len: usize, fields: Option<&'static [&'static str]>) -> Result<Compound<'a, W, C>, Error> {
+        if self.config.is_named || fields.is_some() {
+            encode::write_map_len(&mut self.wr, len as u32)?;
+        } else {
+            encode::write_array_len(&mut self.wr, len as u32)?;
+        }
+        Ok(Compound { se: self, fields })
     }
 }
 
 impl<'a, W: Write + 'a, C: SerializerConfig> Serializer<W, C> {
+    /// Writes the identifier of an enum variant, by name or by index.
+    #[inline]
+    fn serialize_variant(&mut self, idx: u32, variant: &'static str) -> Result<(), Error> {
+        match self.config.variants {
+            VariantMode::Name => serde::Serializer::serialize_str(self, variant),
+            VariantMode::Index => serde::Serializer::serialize_u32(self, idx),
+        }
+    }
+
+    /// Writes what precedes the data of a non-unit enum variant: the header of a single-entry map
+    /// and the variant as its key, or in [`CompatMode::V08`] a 2-element array and the variant
+    /// index.
+    #[inline]
+    fn serialize_variant_header(&mut self, idx: u32, variant: &'static str) -> Result<(), Error> {
+        match self.config.compat {
+            CompatMode::Current => {
+                encode::write_map_len(&mut self.wr, 1)?;
+                self.serialize_variant(idx, variant)
+            }
+            CompatMode::V08 => {
+                encode::write_array_len(&mut self.wr, 2)?;
+                serde::Serializer::serialize_u32(self, idx)
+            }
+        }
+    }
+
+    #[inline]
+    fn map_compound(&'a mut self, len: Option<usize>, is_flattened: bool) -> Result<MaybeUnknownLengthCompound<'a, W, C>, Error> {
+        if is_flattened && self.config.flatten == FlattenMode::Error {
+            return Err(Error::FlattenedStruct);
+        }
+
+        // Canonical and hooked maps are always buffered, so that entries can be rearranged on `end()`.
+        let len = if self.config.buffers_maps() { None } else { len };
+        let mut compound = self.maybe_unknown_len_compound(len.map(|len| len as u32), encode::write_map_len)?;
+        if let Some(buf) = compound.compound.as_mut() {
+            if is_flattened && buf.se.config.flatten == FlattenMode::NamedSubtree {
+                buf.se.config.is_named = true;
+            }
+        }
+        Ok(compound)
+    }
+
     #[inline]
     fn maybe_unknown_len_compound<F>(&'a mut self, len: Option<u32>, f: F) -> Result<MaybeUnknownLengthCompound<'a, W, C>, Error>
-    where F: Fn(&mut W, u32) -> Result<Marker, ValueWriteError>
+    where F: Fn(&mut CountingWriter<W>, u32) -> Result<Marker, ValueWriteError<io::Error>>
     {
         Ok(MaybeUnknownLengthCompound {
             compound: match len {
@@ -262,6 +496,25 @@ impl<W: Write, C> Serializer<W, C> {
         }
     }
 
+    /// Changes whether some types are serialized in human-readable representations
+    /// (`Serializer::is_human_readable` will return the given value).
+    ///
+    /// Unlike [`with_human_readable`](Self::with_human_readable) and
+    /// [`with_binary`](Self::with_binary), this allows choosing the representation at runtime
+    /// without changing the type of the serializer.
+    ///
+    /// ```rust
+    /// use serde::Serializer;
+    ///
+    /// let mut se = rmp_serde::Serializer::new(Vec::new());
+    /// se.set_human_readable(true);
+    /// assert!((&mut se).is_human_readable());
+    /// ```
+    #[inline]
+    pub fn set_human_readable(&mut self, human_readable: bool) {
+        self.config.is_human_readable = human_readable;
+    }
+
     /// Prefer encoding sequences of `u8` as bytes, rather than
     /// as a sequence of variable-size integers.
     ///
@@ -269,18 +522,326 @@ impl<W: Write, C> Serializer<W, C> {
     /// decodnig of some Serde types that happen to contain `[u8]`s,
     /// but don't implement Serde's `visit_bytes`.
     ///
+    /// By default ([`BytesMode::ForceIterables`]) `Vec<u8>`,
+    /// `&[u8]` and `Box<[u8]>` are written as bin values, which
+    /// the deserializer reads back into the same types. Use
+    /// [`BytesMode::Normal`] to write them as arrays, as earlier
+    /// versions did.
+    ///
     /// ```rust
     /// use serde::ser::Serialize;
     /// let mut msgpack_data = Vec::new();
     /// let mut serializer = rmp_serde::Serializer::new(&mut msgpack_data)
     ///     .with_bytes(rmp_serde::config::BytesMode::ForceAll);
     /// vec![255u8; 100].serialize(&mut serializer).unwrap();
+    ///
+    /// let mut msgpack_data = Vec::new();
+    /// vec![1u8, 2].serialize(&mut rmp_serde::Serializer::new(&mut msgpack_data)).unwrap();
+    /// assert_eq!(vec![0xc4, 0x02, 0x01, 0x02], msgpack_data);
+    ///
+    /// let mut msgpack_data = Vec::new();
+    /// let mut serializer = rmp_serde::Serializer::new(&mut msgpack_data)
+    ///     .with_bytes(rmp_serde::config::BytesMode::Normal);
+    /// vec![1u8, 2].serialize(&mut serializer).unwrap();
+    /// assert_eq!(vec![0x92, 0x01, 0x02], msgpack_data);
     /// ```
     #[inline]
     pub fn with_bytes(mut self, mode: BytesMode) -> Serializer<W, C> {
         self.config.bytes = mode;
         self
     }
+
+    /// Consumes this serializer returning the new one, which will write map entries sorted by
+    /// their encoded keys.
+    ///
+    /// This guarantees byte-identical output for logically equal maps regardless of their
+    /// iteration order, e.g. for `HashMap`, at the cost of buffering every map before it is
+    /// written. Entries with equal encoded keys keep their relative order.
+    ///
+    /// ```rust
+    /// use std::collections::HashMap;
+    /// use serde::ser::Serialize;
+    ///
+    /// let map: HashMap<&str, u8> = [("b", 2), ("a", 1)].into_iter().collect();
+    ///
+    /// let mut buf = Vec::new();
+    /// map.serialize(&mut rmp_serde::Serializer::new(&mut buf).with_canonical()).unwrap();
+    /// assert_eq!(vec![0x82, 0xa1, 0x61, 0x01, 0xa1, 0x62, 0x02], buf);
+    /// ```
+    #[inline]
+    pub fn with_canonical(mut self) -> Serializer<W, C> {
+        self.config.is_canonical = true;
+        self
+    }
+
+    /// Consumes this serializer returning the new one, which will write a canonical byte stream,
+    /// e.g. for hashing or signing, and fail on values that can't be written canonically.
+    ///
+    /// In addition to sorting map entries like [`with_canonical`](Self::with_canonical), this:
+    ///
+    /// - writes integers in the smallest representation, overriding
+    ///   [`with_fixed_int_width`](Self::with_fixed_int_width);
+    /// - fails with [`Error::NonCanonical`] on NaN and infinite floats;
+    /// - fails with [`Error::NonCanonical`] on ext values, except timestamps (type `-1`) in
+    ///   their smallest format.
+    ///
+    /// ```rust
+    /// use std::collections::HashMap;
+    /// use serde::ser::Serialize;
+    ///
+    /// let map: HashMap<&str, u64> = [("b", 2), ("a", 1)].into_iter().collect();
+    ///
+    /// let mut buf = Vec::new();
+    /// map.serialize(&mut rmp_serde::Serializer::new(&mut buf).with_strict_canonical()).unwrap();
+    /// assert_eq!(vec![0x82, 0xa1, 0x61, 0x01, 0xa1, 0x62, 0x02], buf);
+    ///
+    /// let mut se = rmp_serde::Serializer::new(Vec::new()).with_strict_canonical();
+    /// assert!(f64::NAN.serialize(&mut se).is_err());
+    /// ```
+    #[inline]
+    pub fn with_strict_canonical(mut self) -> Serializer<W, C> {
+        self.config.is_canonical = true;
+        self.config.is_strict_canonical = true;
+        self
+    }
+
+    /// Changes how structs with `#[serde(flatten)]` fields are written while structs are
+    /// serialized as tuples.
+    ///
+    /// By default such structs are silently written as maps with field names. See
+    /// [`FlattenMode`] for the alternatives.
+    ///
+    /// ```rust
+    /// use serde::Serialize;
+    /// use rmp_serde::config::FlattenMode;
+    ///
+    /// #[derive(Serialize)]
+    /// struct Inner {
+    ///     b: u8,
+    /// }
+    ///
+    /// #[derive(Serialize)]
+    /// struct Outer {
+    ///     a: u8,
+    ///     #[serde(flatten)]
+    ///     inner: Inner,
+    /// }
+    ///
+    /// let mut buf = Vec::new();
+    /// let mut se = rmp_serde::Serializer::new(&mut buf).with_flatten(FlattenMode::Error);
+    /// assert!(Outer { a: 1, inner: Inner { b: 2 } }.serialize(&mut se).is_err());
+    /// ```
+    #[inline]
+    pub fn with_flatten(mut self, mode: FlattenMode) -> Serializer<W, C> {
+        self.config.flatten = mode;
+        self
+    }
+
+    /// Consumes this serializer returning the new one, which will write integers with the
+    /// marker matching their Rust type, e.g. `u32` values always as `uint 32`.
+    ///
+    /// By default integers are written in the smallest representation that fits their value.
+    /// This is more compact, but some decoders expect fields with a fixed width.
+    ///
+    /// ```rust
+    /// use serde::Serialize;
+    ///
+    /// let mut buf = Vec::new();
+    /// 1u32.serialize(&mut rmp_serde::Serializer::new(&mut buf).with_fixed_int_width()).unwrap();
+    /// assert_eq!(vec![0xce, 0x00, 0x00, 0x00, 0x01], buf);
+    /// ```
+    #[inline]
+    pub fn with_fixed_int_width(mut self) -> Serializer<W, C> {
+        self.config.is_fixed_int_width = true;
+        self
+    }
+
+    /// Consumes this serializer returning the new one, which will collect the written bytes in
+    /// a buffer of the given capacity before passing them on to the underlying writer.
+    ///
+    /// Markers and values are written with many small writes, each of which is a system call
+    /// when writing to an unbuffered stream like `TcpStream`. The buffer is flushed when it's
+    /// full and when the serializer is dropped, ignoring errors. To handle them, flush it
+    /// explicitly through [`get_mut`](Self::get_mut), or get the writer back with
+    /// `into_inner().into_inner()`.
+    ///
+    /// ```rust
+    /// use std::io::Write;
+    /// use serde::Serialize;
+    ///
+    /// let val = vec![(1, "a"); 100];
+    ///
+    /// let mut buf = Vec::new();
+    /// let mut se = rmp_serde::Serializer::new(&mut buf).with_buffer(1024);
+    /// val.serialize(&mut se).unwrap();
+    /// se.get_mut().flush().unwrap();
+    /// drop(se);
+    ///
+    /// assert_eq!(rmp_serde::to_vec(&val).unwrap(), buf);
+    /// ```
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn with_buffer(self, capacity: usize) -> Serializer<BufWriter<W>, C> {
+        let Serializer { wr, depth, config, _back_compat_config } = self;
+        Serializer {
+            wr: CountingWriter { wr: BufWriter::with_capacity(capacity, wr.wr), written: wr.written },
+            depth,
+            config,
+            _back_compat_config,
+        }
+    }
+
+    /// Changes how `f64` values are written.
+    ///
+    /// By default they are always written as `float 64`. See [`FloatMode`] for the alternatives.
+    ///
+    /// ```rust
+    /// use serde::Serialize;
+    /// use rmp_serde::config::FloatMode;
+    ///
+    /// let mut buf = Vec::new();
+    /// 1.5f64.serialize(&mut rmp_serde::Serializer::new(&mut buf).with_floats(FloatMode::Compact)).unwrap();
+    /// assert_eq!(vec![0xca, 0x3f, 0xc0, 0x00, 0x00], buf);
+    /// ```
+    #[inline]
+    pub fn with_floats(mut self, mode: FloatMode) -> Serializer<W, C> {
+        self.config.floats = mode;
+        self
+    }
+
+    /// Changes how `Option` values are written.
+    ///
+    /// By default `Some(v)` is written as `v`, which loses nesting: `Some(None)` and `None` are
+    /// both nil. See [`OptionMode`] for the alternative.
+    ///
+    /// ```rust
+    /// use serde::Serialize;
+    /// use rmp_serde::config::OptionMode;
+    ///
+    /// let mut buf = Vec::new();
+    /// Some(None::<u8>).serialize(&mut rmp_serde::Serializer::new(&mut buf).with_option_mode(OptionMode::Array)).unwrap();
+    /// assert_eq!(vec![0x91, 0xc0], buf);
+    /// ```
+    #[inline]
+    pub fn with_option_mode(mut self, mode: OptionMode) -> Serializer<W, C> {
+        self.config.options = mode;
+        self
+    }
+
+    /// Changes how enum variants are identified.
+    ///
+    /// By default variants are written by name. See [`VariantMode`] for the alternative.
+    ///
+    /// ```rust
+    /// use serde::{Deserialize, Serialize};
+    /// use rmp_serde::config::VariantMode;
+    ///
+    /// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    /// enum Command {
+    ///     Stop,
+    ///     Start,
+    /// }
+    ///
+    /// let mut buf = Vec::new();
+    /// Command::Start.serialize(&mut rmp_serde::Serializer::new(&mut buf).with_variant_mode(VariantMode::Index)).unwrap();
+    /// assert_eq!(vec![0x01], buf);
+    /// assert_eq!(Command::Start, rmp_serde::from_slice(&buf).unwrap());
+    /// ```
+    #[inline]
+    pub fn with_variant_mode(mut self, mode: VariantMode) -> Serializer<W, C> {
+        self.config.variants = mode;
+        self
+    }
+
+    /// Changes which version's wire format is written, see [`CompatMode`].
+    ///
+    /// ```rust
+    /// use serde::Serialize;
+    /// use rmp_serde::config::CompatMode;
+    ///
+    /// #[derive(Serialize)]
+    /// enum Event {
+    ///     Idle,
+    ///     Moved(u8),
+    /// }
+    ///
+    /// let mut buf = Vec::new();
+    /// Event::Moved(42).serialize(&mut rmp_serde::Serializer::new(&mut buf).with_compat_mode(CompatMode::V08)).unwrap();
+    /// assert_eq!(vec![0x92, 0x01, 0x91, 0x2a], buf);
+    /// ```
+    #[inline]
+    pub fn with_compat_mode(mut self, mode: CompatMode) -> Serializer<W, C> {
+        self.config.compat = mode;
+        self
+    }
+
+    /// Consumes this serializer returning the new one, which will pass the entries of every map
+    /// to `hook` before writing them.
+    ///
+    /// The hook gets the encoded key and value of each entry, in the order they were
+    /// serialized, or sorted if [`with_canonical`](Self::with_canonical) is enabled. It may
+    /// reorder or remove entries; the map is written with the remaining ones. This buffers
+    /// every map, but doesn't apply to structs written with field names.
+    ///
+    /// [`dedup_map_keys`] is a hook that drops entries whose key was already written.
+    ///
+    /// ```rust
+    /// use serde::ser::{SerializeMap, Serializer as _};
+    ///
+    /// let mut buf = Vec::new();
+    /// let mut se = rmp_serde::Serializer::new(&mut buf).with_map_hook(rmp_serde::encode::dedup_map_keys);
+    /// let mut map = (&mut se).serialize_map(Some(3)).unwrap();
+    /// map.serialize_entry("a", &1).unwrap();
+    /// map.serialize_entry("b", &2).unwrap();
+    /// map.serialize_entry("a", &3).unwrap();
+    /// map.end().unwrap();
+    /// assert_eq!(vec![0x82, 0xa1, 0x61, 0x01, 0xa1, 0x62, 0x02], buf);
+    /// ```
+    #[inline]
+    pub fn with_map_hook(mut self, hook: fn(&mut MapEntries<'_>)) -> Serializer<W, C> {
+        self.config.map_hook = Some(MapHook(hook));
+        self
+    }
+
+    /// Writes the structs that `table` knows as maps keyed by the index of each field name in
+    /// their table, instead of by the name itself.
+    ///
+    /// This is almost as compact as writing structs as tuples, but like named fields the
+    /// indices don't depend on the order the fields are declared in. Fields missing from the
+    /// table are written with their name, and other structs as usual. The table is only asked
+    /// for structs: struct variants of enums are always written as usual. Read such data with
+    /// [`Deserializer::with_field_table`](crate::Deserializer::with_field_table) and the same
+    /// table.
+    ///
+    /// ```rust
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    /// struct Point {
+    ///     y: u8,
+    ///     x: u8,
+    /// }
+    ///
+    /// fn fields(name: &str) -> Option<&'static [&'static str]> {
+    ///     match name {
+    ///         "Point" => Some(&["x", "y"]),
+    ///         _ => None,
+    ///     }
+    /// }
+    ///
+    /// let mut buf = Vec::new();
+    /// Point { y: 2, x: 1 }.serialize(&mut rmp_serde::Serializer::new(&mut buf).with_field_table(fields)).unwrap();
+    /// // {1: 2, 0: 1}
+    /// assert_eq!(vec![0x82, 0x01, 0x02, 0x00, 0x01], buf);
+    ///
+    /// let mut de = rmp_serde::Deserializer::new(&buf[..]).with_field_table(fields);
+    /// assert_eq!(Point { y: 2, x: 1 }, Point::deserialize(&mut de).unwrap());
+    /// ```
+    #[inline]
+    pub fn with_field_table(mut self, table: fn(&str) -> Option<&'static [&'static str]>) -> Serializer<W, C> {
+        self.config.field_table = Some(FieldTable(table));
+        self
+    }
 }
 
 impl<W: Write, C> UnderlyingWrite for Serializer<W, C> {
@@ -288,17 +849,71 @@ impl<W: Write, C> UnderlyingWrite for Serializer<W, C> {
 
     #[inline(always)]
     fn get_ref(&self) -> &Self::Write {
-        &self.wr
+        &self.wr.wr
     }
 
     #[inline(always)]
     fn get_mut(&mut self) -> &mut Self::Write {
-        &mut self.wr
+        &mut self.wr.wr
     }
 
     #[inline(always)]
     fn into_inner(self) -> Self::Write {
-        self.wr
+        self.wr.wr
+    }
+}
+
+/// Counts the bytes written through it, for [`Serializer::bytes_written`] and [`encoded_len`].
+#[derive(Debug)]
+struct CountingWriter<W> {
+    wr: W,
+    written: u64,
+}
+
+impl<W> CountingWriter<W> {
+    #[inline]
+    fn new(wr: W) -> Self {
+        CountingWriter { wr, written: 0 }
+    }
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = self.wr.write(buf)?;
+        self.written += len as u64;
+        Ok(len)
+    }
+
+    #[inline]
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.wr.write_all(buf)?;
+        self.written += buf.len() as u64;
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    #[inline]
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        let len = self.wr.write_vectored(bufs)?;
+        self.written += len as u64;
+        Ok(len)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.wr.flush()
+    }
+}
+
+// With `std`, this comes from the blanket implementation for `io::Write`.
+#[cfg(not(feature = "std"))]
+impl<W: Write> encode::RmpWrite for CountingWriter<W> {
+    type Error = io::Error;
+
+    #[inline]
+    fn write_bytes(&mut self, buf: &[u8]) -> Result<(), io::Error> {
+        self.write_all(buf)
     }
 }
 
@@ -351,6 +966,25 @@ impl<'a, W: Write + 'a, C: SerializerConfig> SerializeTuple for Tuple<'a, W, C>
 #[doc(hidden)]
 pub struct Compound<'a, W, C> {
     se: &'a mut Serializer<W, C>,
+    /// Field-name table of the struct being written, if it's written with field indices.
+    fields: Option<&'static [&'static str]>,
+}
+
+impl<W: Write, C> Compound<'_, W, C> {
+    /// Writes the key of a struct field, unless structs are written as tuples.
+    #[inline]
+    fn write_field_key(&mut self, key: &'static str) -> Result<(), Error> {
+        if let Some(fields) = self.fields {
+            if let Some(idx) = fields.iter().position(|&field| field == key) {
+                encode::write_uint(&mut self.se.wr, idx as u64)?;
+                return Ok(());
+            }
+        } else if !self.se.config.is_named {
+            return Ok(());
+        }
+        encode::write_str(&mut self.se.wr, key)?;
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
@@ -359,6 +993,8 @@ pub struct ExtFieldSerializer<'a, W> {
     wr: &'a mut W,
     tag: Option<i8>,
     finish: bool,
+    /// Only canonical timestamps are allowed.
+    strict: bool,
 }
 
 /// Represents MessagePack serialization implementation for Ext.
@@ -421,9 +1057,7 @@ impl<'a, W: Write + 'a, C: SerializerConfig> SerializeStruct for Compound<'a, W,
     fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) ->
         Result<(), Self::Error>
     {
-        if self.se.config.is_named {
-            encode::write_str(self.se.get_mut(), key)?;
-        }
+        self.write_field_key(key)?;
         value.serialize(&mut *self.se)
     }
 
@@ -455,12 +1089,8 @@ impl<'a, W: Write + 'a, C: SerializerConfig> SerializeStructVariant for Compound
     fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) ->
         Result<(), Self::Error>
     {
-        if self.se.config.is_named {
-            encode::write_str(self.se.get_mut(), key)?;
-            value.serialize(&mut *self.se)
-        } else {
-            value.serialize(&mut *self.se)
-        }
+        self.write_field_key(key)?;
+        value.serialize(&mut *self.se)
     }
 
     #[inline(always)]
@@ -475,18 +1105,21 @@ impl<'a, W: Write + 'a, C: SerializerConfig> SerializeStructVariant for Compound
 struct UnknownLengthCompound {
     se: Serializer<Vec<u8>, DefaultConfig>,
     elem_count: u32,
+    // Start offsets of every encoded key and value, only tracked for canonical maps.
+    offsets: Vec<usize>,
 }
 
 impl<W, C: SerializerConfig> From<&Serializer<W, C>> for UnknownLengthCompound {
     fn from(se: &Serializer<W, C>) -> Self {
         Self {
             se: Serializer {
-                wr: Vec::with_capacity(128),
+                wr: CountingWriter::new(Vec::with_capacity(128)),
                 config: RuntimeConfig::new(se.config),
                 depth: se.depth,
                 _back_compat_config: PhantomData,
             },
-            elem_count: 0
+            elem_count: 0,
+            offsets: Vec::new(),
         }
     }
 }
@@ -543,23 +1176,181 @@ impl<'a, W: Write + 'a, C: SerializerConfig> SerializeMap for MaybeUnknownLength
     type Error = Error;
 
     fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Self::Error> {
+        if self.se.config.buffers_maps() {
+            if let Some(buf) = self.compound.as_mut() {
+                buf.offsets.push(buf.se.get_ref().len());
+            }
+        }
         <Self as SerializeSeq>::serialize_element(self, key)
     }
 
     fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        if self.se.config.buffers_maps() {
+            if let Some(buf) = self.compound.as_mut() {
+                buf.offsets.push(buf.se.get_ref().len());
+            }
+        }
         <Self as SerializeSeq>::serialize_element(self, value)
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
         if let Some(compound) = self.compound {
-            encode::write_map_len(&mut self.se.wr, compound.elem_count / 2)?;
-            self.se.wr.write_all(&compound.se.into_inner())
-                .map_err(ValueWriteError::InvalidDataWrite)?;
+            let buf = compound.se.into_inner();
+            if self.se.config.buffers_maps() {
+                let mut offsets = compound.offsets;
+                offsets.push(buf.len());
+                let mut entries = (0..offsets.len() / 2)
+                    .map(|idx| {
+                        let (key, val, end) = (offsets[2 * idx], offsets[2 * idx + 1], offsets[2 * idx + 2]);
+                        (&buf[key..val], &buf[val..end])
+                    })
+                    .collect::<Vec<_>>();
+                if self.se.config.is_canonical {
+                    entries.sort_by_key(|&(key, _)| key);
+                }
+                if let Some(MapHook(hook)) = self.se.config.map_hook {
+                    hook(&mut entries);
+                }
+                encode::write_map_len(&mut self.se.wr, entries.len() as u32)?;
+                for (key, val) in entries {
+                    self.se.wr.write_all(key)
+                        .and_then(|()| self.se.wr.write_all(val))
+                        .map_err(ValueWriteError::InvalidDataWrite)?;
+                }
+            } else {
+                encode::write_map_len(&mut self.se.wr, compound.elem_count / 2)?;
+                self.se.wr.write_all(&buf)
+                    .map_err(ValueWriteError::InvalidDataWrite)?;
+            }
         }
         Ok(())
     }
 }
 
+/// Checks whether the given ext value is a timestamp in its smallest format.
+fn is_canonical_timestamp(tag: i8, data: &[u8]) -> bool {
+    let mut buf = [0; 12];
+    tag == rmp::Timestamp::EXT_TYPE && rmp::Timestamp::from_ext_data(data).is_some_and(|ts| ts.ext_data(&mut buf) == data)
+}
+
+/// Checks that an already encoded value is written the way a canonical serializer would write
+/// it, so that raw values don't break the guarantees of a canonical byte stream.
+///
+/// Map entries must be sorted by their encoded keys. In strict mode integers and lengths must
+/// also be in their smallest representation, floats finite, and ext values timestamps in their
+/// smallest format.
+fn check_canonical(buf: &[u8], strict: bool) -> Result<(), Error> {
+    fn malformed<E>(_: E) -> Error {
+        Error::InvalidDataModel("malformed raw value")
+    }
+
+    // Every marker is visited in order, so nested values are checked without recursion.
+    let mut rd = buf;
+    while !rd.is_empty() {
+        let start = buf.len() - rd.len();
+        // The smallest encoding of the integer or header, to compare against. Writing into a
+        // `Vec` can't fail.
+        let mut canonical = Vec::new();
+        let mut data_len = 0;
+        match rmp::decode::read_marker(&mut rd).map_err(malformed)? {
+            marker @ (Marker::U8 | Marker::U16 | Marker::U32 | Marker::U64) => {
+                let val = match marker {
+                    Marker::U8 => rd.read_data_u8().map(u64::from),
+                    Marker::U16 => rd.read_data_u16().map(u64::from),
+                    Marker::U32 => rd.read_data_u32().map(u64::from),
+                    _ => rd.read_data_u64(),
+                };
+                let _ = encode::write_uint(&mut canonical, val.map_err(malformed)?);
+            }
+            marker @ (Marker::I8 | Marker::I16 | Marker::I32 | Marker::I64) => {
+                let val = match marker {
+                    Marker::I8 => rd.read_data_i8().map(i64::from),
+                    Marker::I16 => rd.read_data_i16().map(i64::from),
+                    Marker::I32 => rd.read_data_i32().map(i64::from),
+                    _ => rd.read_data_i64(),
+                };
+                let _ = encode::write_sint(&mut canonical, val.map_err(malformed)?);
+            }
+            Marker::F32 => {
+                if strict && !rd.read_data_f32().map_err(malformed)?.is_finite() {
+                    return Err(Error::NonCanonical("non-finite float"));
+                }
+            }
+            Marker::F64 => {
+                if strict && !rd.read_data_f64().map_err(malformed)?.is_finite() {
+                    return Err(Error::NonCanonical("non-finite float"));
+                }
+            }
+            Marker::FixStr(..) | Marker::Str8 | Marker::Str16 | Marker::Str32 => {
+                rd = &buf[start..];
+                data_len = rmp::decode::read_str_len(&mut rd).map_err(malformed)?;
+                let _ = encode::write_str_len(&mut canonical, data_len);
+            }
+            Marker::Bin8 | Marker::Bin16 | Marker::Bin32 => {
+                rd = &buf[start..];
+                data_len = rmp::decode::read_bin_len(&mut rd).map_err(malformed)?;
+                let _ = encode::write_bin_len(&mut canonical, data_len);
+            }
+            Marker::FixArray(..) | Marker::Array16 | Marker::Array32 => {
+                rd = &buf[start..];
+                let len = rmp::decode::read_array_len(&mut rd).map_err(malformed)?;
+                let _ = encode::write_array_len(&mut canonical, len);
+            }
+            Marker::FixMap(..) | Marker::Map16 | Marker::Map32 => {
+                rd = &buf[start..];
+                let len = rmp::decode::read_map_len(&mut rd).map_err(malformed)?;
+                let _ = encode::write_map_len(&mut canonical, len);
+
+                // Check the order of the keys, leaving the entries to be visited afterwards.
+                let mut entries = rd;
+                let mut prev: Option<&[u8]> = None;
+                for _ in 0..len {
+                    let key_len = rmp::validate(entries).map_err(malformed)?;
+                    let (key, rest) = entries.split_at(key_len);
+                    if prev.is_some_and(|prev| prev > key) {
+                        return Err(Error::NonCanonical("unsorted map keys"));
+                    }
+                    prev = Some(key);
+                    entries = &rest[rmp::validate(rest).map_err(malformed)?..];
+                }
+            }
+            Marker::FixExt1 | Marker::FixExt2 | Marker::FixExt4 | Marker::FixExt8 | Marker::FixExt16 |
+            Marker::Ext8 | Marker::Ext16 | Marker::Ext32 => {
+                rd = &buf[start..];
+                let meta = rmp::decode::read_ext_meta(&mut rd).map_err(malformed)?;
+                let _ = encode::write_ext_meta(&mut canonical, meta.size, meta.typeid);
+                let data = rd.get(..meta.size as usize).ok_or(Error::InvalidDataModel("malformed raw value"))?;
+                if strict && !is_canonical_timestamp(meta.typeid, data) {
+                    return Err(Error::NonCanonical("ext value other than a timestamp"));
+                }
+                data_len = meta.size;
+            }
+            Marker::Null | Marker::True | Marker::False | Marker::FixPos(..) | Marker::FixNeg(..) => {}
+            Marker::Reserved => return Err(Error::InvalidDataModel("malformed raw value")),
+        }
+
+        if strict && !canonical.is_empty() && buf[start..buf.len() - rd.len()] != canonical[..] {
+            return Err(Error::NonCanonical("integer or length not in its smallest representation"));
+        }
+        rd = rd.get(data_len as usize..).ok_or(Error::InvalidDataModel("malformed raw value"))?;
+    }
+    Ok(())
+}
+
+/// Serializes `value`, which must be bytes, returning the bytes without their bin header.
+fn bin_data<T: ?Sized + Serialize>(value: &T) -> Result<Vec<u8>, Error> {
+    let mut buf = Vec::new();
+    value.serialize(&mut Serializer::new(&mut buf))?;
+    let mut data = &buf[..];
+    let len = rmp::decode::read_bin_len(&mut data).map_err(|_| Error::InvalidDataModel("expected bytes"))?;
+    if data.len() != len as usize {
+        return Err(Error::InvalidDataModel("expected bytes"));
+    }
+    let header_len = buf.len() - data.len();
+    buf.drain(..header_len);
+    Ok(buf)
+}
+
 impl<'a, W, C> serde::Serializer for &'a mut Serializer<W, C>
 where
     W: Write,
@@ -587,19 +1378,35 @@ where
     }
 
     fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
+        if self.config.writes_fixed_ints() {
+            encode::write_i8(&mut self.wr, v)?;
+            return Ok(());
+        }
         self.serialize_i64(i64::from(v))
     }
 
     fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
+        if self.config.writes_fixed_ints() {
+            encode::write_i16(&mut self.wr, v)?;
+            return Ok(());
+        }
         self.serialize_i64(i64::from(v))
     }
 
     fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
+        if self.config.writes_fixed_ints() {
+            encode::write_i32(&mut self.wr, v)?;
+            return Ok(());
+        }
         self.serialize_i64(i64::from(v))
     }
 
     fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
-        encode::write_sint(&mut self.wr, v)?;
+        if self.config.writes_fixed_ints() {
+            encode::write_i64(&mut self.wr, v)?;
+        } else {
+            encode::write_sint(&mut self.wr, v)?;
+        }
         Ok(())
     }
 
@@ -608,19 +1415,35 @@ where
     }
 
     fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
+        if self.config.writes_fixed_ints() {
+            encode::write_u8(&mut self.wr, v)?;
+            return Ok(());
+        }
         self.serialize_u64(u64::from(v))
     }
 
     fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
+        if self.config.writes_fixed_ints() {
+            encode::write_u16(&mut self.wr, v)?;
+            return Ok(());
+        }
         self.serialize_u64(u64::from(v))
     }
 
     fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
+        if self.config.writes_fixed_ints() {
+            encode::write_u32(&mut self.wr, v)?;
+            return Ok(());
+        }
         self.serialize_u64(u64::from(v))
     }
 
     fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
-        encode::write_uint(&mut self.wr, v)?;
+        if self.config.writes_fixed_ints() {
+            encode::write_u64(&mut self.wr, v)?;
+        } else {
+            encode::write_uint(&mut self.wr, v)?;
+        }
         Ok(())
     }
 
@@ -629,11 +1452,23 @@ where
     }
 
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
+        if self.config.is_strict_canonical && !v.is_finite() {
+            return Err(Error::NonCanonical("non-finite float"));
+        }
         encode::write_f32(&mut self.wr, v)?;
         Ok(())
     }
 
     fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
+        if self.config.is_strict_canonical && !v.is_finite() {
+            return Err(Error::NonCanonical("non-finite float"));
+        }
+        if self.config.floats == FloatMode::Compact {
+            let narrow = v as f32;
+            if f64::from(narrow).to_bits() == v.to_bits() {
+                return self.serialize_f32(narrow);
+            }
+        }
         encode::write_f64(&mut self.wr, v)?;
         Ok(())
     }
@@ -645,12 +1480,13 @@ where
     }
 
     fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
-        encode::write_str(&mut self.wr, v)?;
+        // A single vectored write lets `to_vec` reserve the header and the data at once.
+        encode::write_str_vectored(&mut self.wr, v)?;
         Ok(())
     }
 
     fn serialize_bytes(self, value: &[u8]) -> Result<Self::Ok, Self::Error> {
-        Ok(encode::write_bin(&mut self.wr, value)?)
+        Ok(encode::write_bin_vectored(&mut self.wr, value)?)
     }
 
     fn serialize_none(self) -> Result<(), Self::Error> {
@@ -658,6 +1494,9 @@ where
     }
 
     fn serialize_some<T: ?Sized + serde::Serialize>(self, v: &T) -> Result<(), Self::Error> {
+        if self.config.options == OptionMode::Array {
+            encode::write_array_len(&mut self.wr, 1)?;
+        }
         v.serialize(self)
     }
 
@@ -671,10 +1510,15 @@ where
         Ok(())
     }
 
-    fn serialize_unit_variant(self, _name: &str, _: u32, variant: &'static str) ->
+    fn serialize_unit_variant(self, _name: &str, idx: u32, variant: &'static str) ->
         Result<Self::Ok, Self::Error>
     {
-        self.serialize_str(variant)
+        if self.config.compat == CompatMode::V08 {
+            self.serialize_variant_header(idx, variant)?;
+            encode::write_array_len(&mut self.wr, 0)?;
+            return Ok(());
+        }
+        self.serialize_variant(idx, variant)
     }
 
     fn serialize_newtype_struct<T: ?Sized + serde::Serialize>(self, name: &'static str, value: &T) -> Result<(), Self::Error> {
@@ -684,15 +1528,41 @@ where
 
             return ext_se.end();
         }
+        if name == MSGPACK_STR_STRUCT_NAME {
+            // The data is serialized as bin, then rewritten with a str header.
+            let data = bin_data(value)?;
+            encode::write_str_len(&mut self.wr, data.len() as u32)?;
+            return self.wr
+                .write_all(&data)
+                .map_err(|err| Error::InvalidValueWrite(ValueWriteError::InvalidDataWrite(err)));
+        }
+        if name == NAMED_STRUCT_NAME {
+            let is_named = core::mem::replace(&mut self.config.is_named, true);
+            let res = value.serialize(&mut *self);
+            self.config.is_named = is_named;
+            return res;
+        }
+        if name == RAW_VALUE_STRUCT_NAME {
+            // The data is already encoded MessagePack, written as is.
+            let data = bin_data(value)?;
+            if self.config.is_canonical {
+                check_canonical(&data, self.config.is_strict_canonical)?;
+            }
+            return self.wr
+                .write_all(&data)
+                .map_err(|err| Error::InvalidValueWrite(ValueWriteError::InvalidDataWrite(err)));
+        }
 
         // Encode as if it's inner type.
         value.serialize(self)
     }
 
-    fn serialize_newtype_variant<T: ?Sized + serde::Serialize>(self, _name: &'static str, _: u32, variant: &'static str, value: &T) -> Result<Self::Ok, Self::Error> {
+    fn serialize_newtype_variant<T: ?Sized + serde::Serialize>(self, _name: &'static str, idx: u32, variant: &'static str, value: &T) -> Result<Self::Ok, Self::Error> {
         // encode as a map from variant idx to its attributed data, like: {idx => value}
-        encode::write_map_len(&mut self.wr, 1)?;
-        self.serialize_str(variant)?;
+        self.serialize_variant_header(idx, variant)?;
+        if self.config.compat == CompatMode::V08 {
+            encode::write_array_len(&mut self.wr, 1)?;
+        }
         value.serialize(self)
     }
 
@@ -722,39 +1592,55 @@ where
         self.compound()
     }
 
-    fn serialize_tuple_variant(self, _name: &'static str, _: u32, variant: &'static str, len: usize) ->
+    fn serialize_tuple_variant(self, _name: &'static str, idx: u32, variant: &'static str, len: usize) ->
         Result<Self::SerializeTupleVariant, Error>
     {
         // encode as a map from variant idx to a sequence of its attributed data, like: {idx => [v1,...,vN]}
-        encode::write_map_len(&mut self.wr, 1)?;
-        self.serialize_str(variant)?;
+        self.serialize_variant_header(idx, variant)?;
         encode::write_array_len(&mut self.wr, len as u32)?;
         self.compound()
     }
 
     #[inline]
     fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Error> {
-        self.maybe_unknown_len_compound(len.map(|len| len as u32), |wr, len| encode::write_map_len(wr, len))
+        // Serde writes structs with flattened fields as maps of unknown length.
+        let is_flattened = len.is_none() && !self.config.is_named;
+        self.map_compound(len, is_flattened)
     }
 
-    fn serialize_struct(self, _name: &'static str, len: usize) ->
-        Result<Self::SerializeStruct, Self::Error>
+    fn collect_map<K, V, I>(self, iter: I) -> Result<Self::Ok, Self::Error>
+    where
+        K: Serialize,
+        V: Serialize,
+        I: IntoIterator<Item = (K, V)>,
     {
-        if self.config.is_named {
-            encode::write_map_len(self.get_mut(), len as u32)?;
-        } else {
-            encode::write_array_len(self.get_mut(), len as u32)?;
+        // Maps collected from iterators may have an unknown length too, but they aren't
+        // flattened structs.
+        let iter = iter.into_iter();
+        let len = match iter.size_hint() {
+            (lo, Some(hi)) if lo == hi => Some(lo),
+            _ => None,
+        };
+        let mut map = self.map_compound(len, false)?;
+        for (key, value) in iter {
+            map.serialize_entry(&key, &value)?;
         }
-        self.compound()
+        SerializeMap::end(map)
+    }
+
+    fn serialize_struct(self, name: &'static str, len: usize) ->
+        Result<Self::SerializeStruct, Self::Error>
+    {
+        let fields = self.config.field_table.and_then(|FieldTable(lookup)| lookup(name));
+        self.struct_compound(len, fields)
     }
 
-    fn serialize_struct_variant(self, name: &'static str, _: u32, variant: &'static str, len: usize) ->
+    fn serialize_struct_variant(self, _name: &'static str, idx: u32, variant: &'static str, len: usize) ->
         Result<Self::SerializeStructVariant, Error>
     {
         // encode as a map from variant idx to a sequence of its attributed data, like: {idx => [v1,...,vN]}
-        encode::write_map_len(&mut self.wr, 1)?;
-        self.serialize_str(variant)?;
-        self.serialize_struct(name, len)
+        self.serialize_variant_header(idx, variant)?;
+        self.struct_compound(len, None)
     }
 
     fn collect_seq<I>(self, iter: I) -> Result<Self::Ok, Self::Error> where I: IntoIterator, I::Item: Serialize {
@@ -764,14 +1650,14 @@ where
             _ => None,
         };
 
-        const MAX_ITER_SIZE: usize = std::mem::size_of::<<&[u8] as IntoIterator>::IntoIter>();
-        const ITEM_PTR_SIZE: usize = std::mem::size_of::<&u8>();
+        const MAX_ITER_SIZE: usize = core::mem::size_of::<<&[u8] as IntoIterator>::IntoIter>();
+        const ITEM_PTR_SIZE: usize = core::mem::size_of::<&u8>();
 
         // Estimate whether the input is `&[u8]` or similar (hacky, because Rust lacks proper specialization)
-        let might_be_a_bytes_iter = (std::mem::size_of::<I::Item>() == 1 || std::mem::size_of::<I::Item>() == ITEM_PTR_SIZE)
+        let might_be_a_bytes_iter = (core::mem::size_of::<I::Item>() == 1 || core::mem::size_of::<I::Item>() == ITEM_PTR_SIZE)
             // Complex types like HashSet<u8> don't support reading bytes.
             // The simplest iterator is ptr+len.
-            && std::mem::size_of::<I::IntoIter>() <= MAX_ITER_SIZE;
+            && core::mem::size_of::<I::IntoIter>() <= MAX_ITER_SIZE;
 
         let mut iter = iter.peekable();
         if might_be_a_bytes_iter && self.config.bytes != BytesMode::Normal {
@@ -793,7 +1679,7 @@ impl<W: Write, C: SerializerConfig> Serializer<W, C> {
     fn bytes_from_iter<I>(&mut self, mut iter: I, len: u32) -> Result<(), <&mut Self as serde::Serializer>::Error> where I: Iterator, I::Item: Serialize {
         encode::write_bin_len(&mut self.wr, len)?;
         iter.try_for_each(|item| {
-            self.wr.write(std::slice::from_ref(&item.serialize(OnlyBytes)
+            self.wr.write(core::slice::from_ref(&item.serialize(OnlyBytes)
                 .map_err(|_| Error::InvalidDataModel("BytesMode"))?))
                 .map_err(ValueWriteError::InvalidDataWrite)?;
              Ok(())
@@ -801,7 +1687,7 @@ impl<W: Write, C: SerializerConfig> Serializer<W, C> {
     }
 }
 
-impl<'a, W: Write + 'a> serde::Serializer for &mut ExtFieldSerializer<'a, W> {
+impl<'a, W: encode::RmpWrite<Error = io::Error> + 'a> serde::Serializer for &mut ExtFieldSerializer<'a, W> {
     type Ok = ();
     type Error = Error;
 
@@ -826,9 +1712,14 @@ impl<'a, W: Write + 'a> serde::Serializer for &mut ExtFieldSerializer<'a, W> {
     #[inline]
     fn serialize_bytes(self, val: &[u8]) -> Result<Self::Ok, Self::Error> {
         if let Some(tag) = self.tag.take() {
-            encode::write_ext_meta(self.wr, val.len() as u32, tag)?;
-            self.wr
-                .write_all(val)
+            if self.strict && !is_canonical_timestamp(tag, val) {
+                return Err(Error::NonCanonical("ext value other than a timestamp"));
+            }
+            let len = val.len() as u32;
+            let mut header = [0; 6];
+            // The buffer fits the largest ext header, so this can't fail.
+            let _ = encode::write_ext_meta(&mut &mut header[..], len, tag);
+            encode::RmpWrite::write_bytes_vectored(self.wr, &[&header[..encode::ext_header_len(len)], val])
                 .map_err(|err| Error::InvalidValueWrite(ValueWriteError::InvalidDataWrite(err)))?;
 
             self.finish = true;
@@ -975,7 +1866,7 @@ impl<'a, W: Write + 'a> serde::Serializer for &mut ExtFieldSerializer<'a, W> {
     }
 }
 
-impl<'a, W: Write + 'a> serde::ser::Serializer for &mut ExtSerializer<'a, W> {
+impl<'a, W: encode::RmpWrite<Error = io::Error> + 'a> serde::ser::Serializer for &mut ExtSerializer<'a, W> {
     type Ok = ();
     type Error = Error;
 
@@ -1136,7 +2027,7 @@ impl<'a, W: Write + 'a> serde::ser::Serializer for &mut ExtSerializer<'a, W> {
     }
 }
 
-impl<'a, W: Write + 'a> SerializeTuple for &mut ExtSerializer<'a, W> {
+impl<'a, W: encode::RmpWrite<Error = io::Error> + 'a> SerializeTuple for &mut ExtSerializer<'a, W> {
     type Ok = ();
     type Error = Error;
 
@@ -1151,7 +2042,7 @@ impl<'a, W: Write + 'a> SerializeTuple for &mut ExtSerializer<'a, W> {
     }
 }
 
-impl<'a, W: Write + 'a> ExtSerializer<'a, W> {
+impl<'a, W: Write + 'a> ExtSerializer<'a, CountingWriter<W>> {
     #[inline]
     fn new<C>(ser: &'a mut Serializer<W, C>) -> Self {
         Self {
@@ -1159,7 +2050,9 @@ impl<'a, W: Write + 'a> ExtSerializer<'a, W> {
             tuple_received: false,
         }
     }
+}
 
+impl<'a, W: encode::RmpWrite<Error = io::Error> + 'a> ExtSerializer<'a, W> {
     #[inline]
     fn end(self) -> Result<(), Error> {
         if !self.tuple_received {
@@ -1170,16 +2063,19 @@ impl<'a, W: Write + 'a> ExtSerializer<'a, W> {
     }
 }
 
-impl<'a, W: Write + 'a> ExtFieldSerializer<'a, W> {
+impl<'a, W: Write + 'a> ExtFieldSerializer<'a, CountingWriter<W>> {
     #[inline]
     fn new<C>(ser: &'a mut Serializer<W, C>) -> Self {
         Self {
-            wr: UnderlyingWrite::get_mut(ser),
+            strict: ser.config.is_strict_canonical,
+            wr: &mut ser.wr,
             tag: None,
             finish: false,
         }
     }
+}
 
+impl<'a, W: encode::RmpWrite<Error = io::Error> + 'a> ExtFieldSerializer<'a, W> {
     #[inline]
     fn end(self) -> Result<(), Error> {
         if self.finish {
@@ -1190,12 +2086,162 @@ impl<'a, W: Write + 'a> ExtFieldSerializer<'a, W> {
     }
 }
 
+/// Writes a MessagePack array element by element, without knowing its length up front.
+///
+/// The array header is written as `array 32` with a placeholder length, which is patched by
+/// seeking back once [`finish`](Self::finish) is called. This allows serializing sequences that
+/// are too large to collect in memory first, e.g. rows from a database cursor, straight into a
+/// file. The writer must be seekable and the sequence may hold at most `u32::MAX` elements.
+///
+/// If `finish` isn't called, the written array claims to be empty, so the data can't be
+/// decoded correctly.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Cursor;
+/// use rmp_serde::encode::SeqWriter;
+///
+/// let mut seq = SeqWriter::new(rmp_serde::Serializer::new(Cursor::new(Vec::new()))).unwrap();
+/// for i in 0..3u8 {
+///     seq.push(&i).unwrap();
+/// }
+/// let buf = seq.finish().unwrap().into_inner().into_inner();
+///
+/// assert_eq!(vec![0xdd, 0x00, 0x00, 0x00, 0x03, 0x00, 0x01, 0x02], buf);
+/// assert_eq!(vec![0u8, 1, 2], rmp_serde::from_slice::<Vec<u8>>(&buf).unwrap());
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct SeqWriter<W, C = DefaultConfig> {
+    se: Serializer<W, C>,
+    // Position of the array marker.
+    start: u64,
+    len: u32,
+}
+
+#[cfg(feature = "std")]
+impl<W: Write + Seek, C: SerializerConfig> SeqWriter<W, C> {
+    /// Starts a new array at the current position of the given serializer.
+    ///
+    /// Elements are written with the configuration of the serializer.
+    pub fn new(mut se: Serializer<W, C>) -> Result<Self, Error> {
+        let start = se.wr.wr.stream_position()
+            .map_err(|err| Error::InvalidValueWrite(ValueWriteError::InvalidMarkerWrite(err)))?;
+        se.wr.write_all(&[Marker::Array32.to_u8(), 0, 0, 0, 0])
+            .map_err(|err| Error::InvalidValueWrite(ValueWriteError::InvalidMarkerWrite(err)))?;
+        Ok(SeqWriter { se, start, len: 0 })
+    }
+
+    /// Serializes the next element of the array.
+    pub fn push<T: Serialize + ?Sized>(&mut self, val: &T) -> Result<(), Error> {
+        if self.len == u32::MAX {
+            return Err(Error::Syntax("sequence is too long for a MessagePack array".to_owned()));
+        }
+        val.serialize(&mut self.se)?;
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Returns the number of elements written so far.
+    #[inline]
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    /// Returns `true` if no elements were written yet.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Patches the array length and returns the serializer, positioned after the last element.
+    pub fn finish(mut self) -> Result<Serializer<W, C>, Error> {
+        // Written straight to the underlying writer, as the patch doesn't add any bytes.
+        let wr = &mut self.se.wr.wr;
+        let patch = |wr: &mut W, start: u64, len: u32| -> io::Result<()> {
+            let end = wr.stream_position()?;
+            wr.seek(SeekFrom::Start(start + 1))?;
+            wr.write_all(&len.to_be_bytes())?;
+            wr.seek(SeekFrom::Start(end))?;
+            Ok(())
+        };
+        patch(wr, self.start, self.len)
+            .map_err(|err| Error::InvalidValueWrite(ValueWriteError::InvalidDataWrite(err)))?;
+        Ok(self.se)
+    }
+}
+
+/// A map hook for [`Serializer::with_map_hook`] that drops every entry whose encoded key equals
+/// the key of an earlier entry.
+///
+/// The first entry for each key is kept, and the order of entries doesn't change.
+pub fn dedup_map_keys(entries: &mut MapEntries<'_>) {
+    let mut seen = BTreeSet::new();
+    entries.retain(|&(key, _)| seen.insert(key));
+}
+
+/// Serializes the items of the given iterator as a sequence of the given length.
+///
+/// Sequences of unknown length, e.g. collected from iterators without an exact size hint, have
+/// to be buffered before their array header can be written. When the length is known from
+/// elsewhere, this writes the header upfront and streams the items straight to the serializer.
+/// It works with any serde serializer, e.g. in a `Serialize` implementation or with
+/// `#[serde(serialize_with)]`.
+///
+/// # Errors
+///
+/// Fails if serializing an item fails, or if the iterator doesn't yield exactly `len` items.
+/// In the latter case, the part of the sequence written so far must be discarded.
+///
+/// # Examples
+///
+/// ```
+/// let mut remaining = 3;
+/// let items = std::iter::from_fn(|| {
+///     remaining -= 1;
+///     (remaining >= 0).then_some(remaining)
+/// });
+///
+/// let mut buf = Vec::new();
+/// let mut se = rmp_serde::Serializer::new(&mut buf);
+/// rmp_serde::encode::serialize_seq_exact(&mut se, items, 3).unwrap();
+/// assert_eq!(vec![0x93, 0x02, 0x01, 0x00], buf);
+///
+/// let mut se = rmp_serde::Serializer::new(Vec::new());
+/// assert!(rmp_serde::encode::serialize_seq_exact(&mut se, 0..2, 3).is_err());
+/// ```
+pub fn serialize_seq_exact<S, I>(se: S, iter: I, len: usize) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+    I: IntoIterator,
+    I::Item: Serialize,
+{
+    let mut seq = se.serialize_seq(Some(len))?;
+    let mut count = 0;
+    for item in iter {
+        if count == len {
+            return Err(serde::ser::Error::custom(format_args!(
+                "sequence has more than the declared {len} elements"
+            )));
+        }
+        seq.serialize_element(&item)?;
+        count += 1;
+    }
+    if count != len {
+        return Err(serde::ser::Error::custom(format_args!(
+            "sequence has {count} elements, but {len} were declared"
+        )));
+    }
+    seq.end()
+}
+
 /// Serialize the given data structure as MessagePack into the I/O stream.
 /// This function uses compact representation - structures as arrays
 ///
 /// Serialization can fail if `T`'s implementation of `Serialize` decides to fail.
 #[inline]
-pub fn write<W, T>(wr: &mut W, val: &T) -> Result<(), Error>
+pub fn to_writer<W, T>(wr: &mut W, val: &T) -> Result<(), Error>
 where
     W: Write + ?Sized,
     T: Serialize + ?Sized,
@@ -1207,7 +2253,7 @@ where
 /// This function serializes structures as maps
 ///
 /// Serialization can fail if `T`'s implementation of `Serialize` decides to fail.
-pub fn write_named<W, T>(wr: &mut W, val: &T) -> Result<(), Error>
+pub fn to_writer_named<W, T>(wr: &mut W, val: &T) -> Result<(), Error>
 where
     W: Write + ?Sized,
     T: Serialize + ?Sized,
@@ -1218,6 +2264,48 @@ where
     val.serialize(&mut se)
 }
 
+/// Serialize the given data structure as MessagePack into the I/O stream, using the given
+/// configuration.
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to fail.
+#[inline]
+pub fn to_writer_with<W, T, C>(wr: &mut W, val: &T, config: C) -> Result<(), Error>
+where
+    W: Write + ?Sized,
+    T: Serialize + ?Sized,
+    C: SerializerConfig,
+{
+    val.serialize(&mut Serializer::with_config(wr, config))
+}
+
+/// Serialize the given data structure as MessagePack into the I/O stream.
+/// This function uses compact representation - structures as arrays
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to fail.
+#[deprecated(note = "use `to_writer` instead")]
+#[inline]
+pub fn write<W, T>(wr: &mut W, val: &T) -> Result<(), Error>
+where
+    W: Write + ?Sized,
+    T: Serialize + ?Sized,
+{
+    to_writer(wr, val)
+}
+
+/// Serialize the given data structure as MessagePack into the I/O stream.
+/// This function serializes structures as maps
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to fail.
+#[deprecated(note = "use `to_writer_named` instead")]
+#[inline]
+pub fn write_named<W, T>(wr: &mut W, val: &T) -> Result<(), Error>
+where
+    W: Write + ?Sized,
+    T: Serialize + ?Sized,
+{
+    to_writer_named(wr, val)
+}
+
 /// Serialize the given data structure as a MessagePack byte vector.
 /// This method uses compact representation, structs are serialized as arrays
 ///
@@ -1228,7 +2316,7 @@ where
     T: Serialize + ?Sized,
 {
     let mut wr = FallibleWriter(Vec::new());
-    write(&mut wr, val)?;
+    to_writer(&mut wr, val)?;
     Ok(wr.0)
 }
 
@@ -1244,28 +2332,250 @@ where
     T: Serialize + ?Sized,
 {
     let mut wr = FallibleWriter(Vec::new());
-    write_named(&mut wr, val)?;
+    to_writer_named(&mut wr, val)?;
+    Ok(wr.0)
+}
+
+/// Serializes each of the given values as a separate top-level MessagePack value, one after
+/// another, in compact representation.
+///
+/// Unlike serializing a `Vec`, no array header is written, so the values can be read back one by
+/// one with [`from_slice_all`](crate::from_slice_all) or
+/// [`StreamDeserializer`](crate::decode::StreamDeserializer).
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to fail.
+///
+/// # Examples
+///
+/// ```
+/// let buf = rmp_serde::to_vec_all(&[(1, "one"), (2, "two")]).unwrap();
+/// assert_eq!(vec![0x92, 0x01, 0xa3, b'o', b'n', b'e', 0x92, 0x02, 0xa3, b't', b'w', b'o'], buf);
+/// ```
+pub fn to_vec_all<I>(vals: I) -> Result<Vec<u8>, Error>
+where
+    I: IntoIterator,
+    I::Item: Serialize,
+{
+    let mut wr = FallibleWriter(Vec::new());
+    {
+        let mut se = Serializer::new(&mut wr);
+        for val in vals {
+            val.serialize(&mut se)?;
+        }
+    }
+    Ok(wr.0)
+}
+
+/// Serialize the given data structure as a MessagePack byte vector, using the given
+/// configuration.
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to fail.
+///
+/// # Examples
+///
+/// ```
+/// use rmp_serde::config::{DefaultConfig, StructMapConfig};
+///
+/// let buf = rmp_serde::to_vec_with(&[1, 2], StructMapConfig::new(DefaultConfig)).unwrap();
+/// assert_eq!(rmp_serde::to_vec_named(&[1, 2]).unwrap(), buf);
+/// ```
+#[inline]
+pub fn to_vec_with<T, C>(val: &T, config: C) -> Result<Vec<u8>, Error>
+where
+    T: Serialize + ?Sized,
+    C: SerializerConfig,
+{
+    let mut wr = FallibleWriter(Vec::new());
+    val.serialize(&mut Serializer::with_config(&mut wr, config))?;
     Ok(wr.0)
 }
 
+/// Returns the number of bytes the given data structure takes when serialized as MessagePack.
+/// This method uses compact representation, structs are serialized as arrays
+///
+/// Nothing is allocated for the output, so this can be used to reserve an exact buffer or to
+/// reject oversized messages up front.
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to fail.
+///
+/// # Examples
+///
+/// ```
+/// let val = (42, "le message");
+///
+/// assert_eq!(rmp_serde::to_vec(&val).unwrap().len(), rmp_serde::encoded_len(&val).unwrap());
+/// ```
+#[inline]
+pub fn encoded_len<T>(val: &T) -> Result<usize, Error>
+where
+    T: Serialize + ?Sized,
+{
+    let mut wr = CountingWriter::new(io::sink());
+    to_writer(&mut wr, val)?;
+    Ok(wr.written as usize)
+}
+
+/// Returns the number of bytes the given data structure takes when serialized as MessagePack
+/// map, i.e. as produced by [`to_vec_named`].
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to fail.
+#[inline]
+pub fn encoded_len_named<T>(val: &T) -> Result<usize, Error>
+where
+    T: Serialize + ?Sized,
+{
+    let mut wr = CountingWriter::new(io::sink());
+    to_writer_named(&mut wr, val)?;
+    Ok(wr.written as usize)
+}
+
+/// Serializes the given data structure as MessagePack into a possibly uninitialized buffer,
+/// returning the number of bytes written.
+/// This method uses compact representation, structs are serialized as arrays
+///
+/// The encoded length is computed first, so nothing is written if the buffer is too small.
+/// This allows serializing into preallocated or arena-backed memory without initializing it
+/// beforehand. The first returned number of bytes of `buf` are initialized.
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to fail, or with an
+/// [`io::ErrorKind::WriteZero`](std::io::ErrorKind::WriteZero) write error if the value doesn't
+/// fit into `buf`.
+///
+/// # Examples
+///
+/// ```
+/// use std::mem::MaybeUninit;
+///
+/// let mut buf = [MaybeUninit::<u8>::uninit(); 64];
+/// let len = rmp_serde::encode::to_uninit_slice(&mut buf, &(42, "le message")).unwrap();
+///
+/// assert_eq!(rmp_serde::encoded_len(&(42, "le message")).unwrap(), len);
+/// assert!(rmp_serde::encode::to_uninit_slice(&mut buf[..len - 1], &(42, "le message")).is_err());
+/// ```
+pub fn to_uninit_slice<T>(buf: &mut [MaybeUninit<u8>], val: &T) -> Result<usize, Error>
+where
+    T: Serialize + ?Sized,
+{
+    let len = encoded_len(val)?;
+    if len > buf.len() {
+        return Err(write_zero());
+    }
+    write_uninit(&mut buf[..len], val)
+}
+
+/// Serializes the given data structure as MessagePack into a buffer of the exact encoded length
+/// obtained from `alloc`, e.g. from a bump allocator, returning the number of bytes written.
+///
+/// On success the whole buffer returned by `alloc` is initialized.
+/// This method uses compact representation, structs are serialized as arrays
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to fail, or with an
+/// [`io::ErrorKind::WriteZero`](std::io::ErrorKind::WriteZero) write error if `alloc` returns a
+/// buffer of a different length, or if `T` doesn't serialize to the same length twice.
+///
+/// # Examples
+///
+/// ```
+/// use std::mem::MaybeUninit;
+///
+/// let mut arena = vec![MaybeUninit::<u8>::uninit(); 1024];
+///
+/// let len = rmp_serde::encode::to_uninit_slice_with(&[1, 2, 3], |len| &mut arena[..len]).unwrap();
+/// let buf: Vec<u8> = arena[..len].iter().map(|b| unsafe { b.assume_init() }).collect();
+/// assert_eq!(vec![0x93, 0x01, 0x02, 0x03], buf);
+/// ```
+pub fn to_uninit_slice_with<'a, T, F>(val: &T, alloc: F) -> Result<usize, Error>
+where
+    T: Serialize + ?Sized,
+    F: FnOnce(usize) -> &'a mut [MaybeUninit<u8>],
+{
+    let len = encoded_len(val)?;
+    let buf = alloc(len);
+    if buf.len() != len {
+        return Err(write_zero());
+    }
+    write_uninit(buf, val)
+}
+
+/// Serializes `val` into the whole of `buf`, failing unless it fills `buf` exactly.
+fn write_uninit<T: Serialize + ?Sized>(buf: &mut [MaybeUninit<u8>], val: &T) -> Result<usize, Error> {
+    let len = buf.len();
+    let mut wr = UninitWriter { buf, pos: 0 };
+    val.serialize(&mut Serializer::new(&mut wr))?;
+    if wr.pos != len {
+        return Err(write_zero());
+    }
+    Ok(len)
+}
+
+fn write_zero() -> Error {
+    Error::InvalidValueWrite(ValueWriteError::InvalidDataWrite(io::ErrorKind::WriteZero.into()))
+}
+
+/// Writer into a possibly uninitialized buffer, which initializes it as it goes.
+struct UninitWriter<'a> {
+    buf: &'a mut [MaybeUninit<u8>],
+    pos: usize,
+}
+
+impl Write for UninitWriter<'_> {
+    #[inline]
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        let dst = &mut self.buf[self.pos..];
+        let len = data.len().min(dst.len());
+        for (dst, &byte) in dst.iter_mut().zip(&data[..len]) {
+            dst.write(byte);
+        }
+        self.pos += len;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
 #[repr(transparent)]
 struct FallibleWriter(Vec<u8>);
 
 impl Write for FallibleWriter {
     #[inline(always)]
-    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         self.write_all(buf)?;
         Ok(buf.len())
     }
 
     #[inline]
-    fn write_all(&mut self, buf: &[u8]) -> std::io::Result<()> {
-        self.0.try_reserve(buf.len()).map_err(|_| std::io::ErrorKind::OutOfMemory)?;
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.0.try_reserve(buf.len()).map_err(|_| io::ErrorKind::OutOfMemory)?;
         self.0.extend_from_slice(buf);
         Ok(())
     }
 
-    fn flush(&mut self) -> std::io::Result<()> {
+    #[cfg(feature = "std")]
+    #[inline]
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        let len = bufs.iter().map(|buf| buf.len()).sum();
+        self.0.try_reserve(len).map_err(|_| io::ErrorKind::OutOfMemory)?;
+        for buf in bufs {
+            self.0.extend_from_slice(buf);
+        }
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
         Ok(())
     }
 }
@@ -0,0 +1,116 @@
+//! Field-name tables of structs, for mapping array-encoded structs to names.
+//!
+//! Structs serialized with [`to_vec`](crate::to_vec) are written as arrays of their field
+//! values, in declaration order. Tools that translate between that layout and named maps need
+//! the names of the fields, which are available either statically through [`MsgPackFields`] or,
+//! as far as the `Deserialize` implementation of a struct reveals them, through [`fields_of`].
+
+use serde::de::{Deserialize, Deserializer, Visitor};
+use serde::forward_to_deserialize_any;
+
+use crate::decode::Error;
+
+/// Types that expose the names of their fields, in the order they are serialized.
+///
+/// The names must match those used by the `Serialize` and `Deserialize` implementations of the
+/// type, including any `#[serde(rename)]` attributes. For structs without aliases and skipped
+/// fields, the implementation can be checked against [`fields_of`].
+///
+/// # Examples
+///
+/// ```
+/// use rmp_serde::fields::{fields_of, MsgPackFields};
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Point {
+///     x: i32,
+///     #[serde(rename = "y_pos")]
+///     y: i32,
+/// }
+///
+/// impl MsgPackFields for Point {
+///     const FIELDS: &'static [&'static str] = &["x", "y_pos"];
+/// }
+///
+/// assert_eq!(Some(Point::FIELDS), fields_of::<Point>());
+/// ```
+pub trait MsgPackFields {
+    /// Names of the fields, in declaration order.
+    const FIELDS: &'static [&'static str];
+}
+
+/// Returns the field names that the `Deserialize` implementation of the struct `T` accepts.
+///
+/// The names are the `fields` that `T` passes to `deserialize_struct`, recorded without any data
+/// being deserialized. For `#[derive(Deserialize)]` that is, in declaration order, the
+/// deserialization name of every field together with its `#[serde(alias)]` names, sorted among
+/// themselves. Fields with `#[serde(skip)]` or `#[serde(skip_deserializing)]` are left out, even
+/// though they may still be serialized. The list therefore matches the array layout written by
+/// [`to_vec`](crate::to_vec) only for structs without aliases and skipped fields.
+///
+/// Returns `None` if `T` isn't deserialized as a struct with named fields, e.g. for tuple
+/// structs, enums, or structs with flattened fields.
+///
+/// # Examples
+///
+/// ```
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct User<'a> {
+///     id: u64,
+///     name: &'a str,
+/// }
+///
+/// assert_eq!(Some(&["id", "name"][..]), rmp_serde::fields::fields_of::<User<'_>>());
+/// assert_eq!(None, rmp_serde::fields::fields_of::<(u64, String)>());
+///
+/// #[derive(Deserialize)]
+/// struct Renamed {
+///     #[serde(alias = "uid")]
+///     id: u64,
+///     #[serde(skip_deserializing)]
+///     #[allow(dead_code)]
+///     cached: bool,
+/// }
+///
+/// assert_eq!(Some(&["id", "uid"][..]), rmp_serde::fields::fields_of::<Renamed>());
+/// ```
+#[must_use]
+pub fn fields_of<'de, T>() -> Option<&'static [&'static str]>
+where
+    T: Deserialize<'de>,
+{
+    let mut fields = None;
+    // The tracer always fails, after recording the fields if it's asked for a struct.
+    let _ = T::deserialize(FieldsTracer { fields: &mut fields });
+    fields
+}
+
+/// Deserializer that records the field names passed to `deserialize_struct`.
+struct FieldsTracer<'a> {
+    fields: &'a mut Option<&'static [&'static str]>,
+}
+
+impl<'de> Deserializer<'de> for FieldsTracer<'_> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value, Error>
+        where V: Visitor<'de>
+    {
+        Err(Error::Uncategorized("not a struct".into()))
+    }
+
+    fn deserialize_struct<V>(self, _name: &'static str, fields: &'static [&'static str], _visitor: V) -> Result<V::Value, Error>
+        where V: Visitor<'de>
+    {
+        *self.fields = Some(fields);
+        Err(Error::Uncategorized("field names recorded".into()))
+    }
+
+    forward_to_deserialize_any! {
+        bool u8 u16 u32 u64 i8 i16 i32 i64 f32 f64 char str string bytes byte_buf option unit
+        unit_struct newtype_struct seq tuple tuple_struct map enum identifier ignored_any
+    }
+}
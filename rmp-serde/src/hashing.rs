@@ -0,0 +1,164 @@
+//! Computing a digest of the MessagePack consumed while deserializing, e.g. to verify a signature.
+//!
+//! The deserializer reads exactly the bytes of the value it deserializes, so wrapping its reader
+//! in a [`HashingReader`] yields the digest of the encoded value without buffering it first. The
+//! digest is computed over the raw input, so it can be compared to one produced by any other
+//! MessagePack implementation from the same bytes.
+//!
+//! Keep in mind that the value is only trustworthy once the digest has been verified, so nothing
+//! should be done with it before.
+//!
+//! This module is available with the `digest` feature. It works with any hash function
+//! implementing [`digest::Digest`], and, through [`HashingReader::with_digest`] and
+//! [`from_read_with_digest`], with anything implementing [`digest::Update`], like the keyed MACs
+//! of the `hmac` crate. Those are handed back unfinalized, so that they can be verified with
+//! `Mac::verify_slice`, which compares in constant time.
+//!
+//! # Examples
+//!
+//! ```
+//! use sha2::{Digest, Sha256};
+//!
+//! let buf = rmp_serde::to_vec(&("alice", 42)).unwrap();
+//! let expected = Sha256::digest(&buf);
+//!
+//! let (val, digest) = rmp_serde::hashing::from_read_hashed::<_, Sha256, (String, u32)>(&buf[..]).unwrap();
+//! assert_eq!(expected, digest);
+//! assert_eq!(("alice".to_owned(), 42), val);
+//! ```
+
+use std::io::{self, Read};
+
+use digest::{Digest, Output, Update};
+use serde::de::DeserializeOwned;
+
+use crate::decode::{self, Error};
+
+/// A reader that feeds everything read through it into a digest.
+#[derive(Debug)]
+pub struct HashingReader<R, D> {
+    rd: R,
+    digest: D,
+    len: u64,
+}
+
+impl<R: Read, D: Digest + Update> HashingReader<R, D> {
+    /// Creates a reader hashing with a new instance of `D`.
+    #[inline]
+    #[must_use]
+    pub fn new(rd: R) -> Self {
+        Self::with_digest(rd, D::new())
+    }
+
+    /// Returns the digest of the bytes read so far, consuming this reader.
+    #[inline]
+    #[must_use]
+    pub fn finalize(self) -> Output<D> {
+        self.digest.finalize()
+    }
+}
+
+impl<R: Read, D: Update> HashingReader<R, D> {
+    /// Creates a reader hashing with the given digest, e.g. a keyed MAC or one already fed with a
+    /// prefix of the message.
+    #[inline]
+    #[must_use]
+    pub fn with_digest(rd: R, digest: D) -> Self {
+        HashingReader { rd, digest, len: 0 }
+    }
+
+    /// Returns the number of bytes read, and so hashed, so far.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Returns `true` if nothing has been read yet.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns a reference to the underlying reader.
+    #[inline]
+    pub fn get_ref(&self) -> &R {
+        &self.rd
+    }
+
+    /// Returns the underlying reader and the digest fed so far, consuming this reader.
+    ///
+    /// Reading from the underlying reader afterwards doesn't affect the digest, so anything
+    /// following the value, e.g. a signature, can be read from it.
+    #[inline]
+    pub fn into_parts(self) -> (R, D) {
+        (self.rd, self.digest)
+    }
+}
+
+impl<R: Read, D: Update> Read for HashingReader<R, D> {
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.rd.read(buf)?;
+        Update::update(&mut self.digest, &buf[..n]);
+        self.len += n as u64;
+        Ok(n)
+    }
+}
+
+/// Deserializes an instance of type `T` from an I/O stream, returning it along with the `D`
+/// digest of exactly the bytes it was read from.
+///
+/// Anything following the value is left unread in `rd`.
+///
+/// # Errors
+///
+/// Fails like [`from_read`](crate::from_read).
+pub fn from_read_hashed<R, D, T>(rd: R) -> Result<(T, Output<D>), Error>
+where
+    R: Read,
+    D: Digest + Update,
+    T: DeserializeOwned,
+{
+    let mut rd = HashingReader::<R, D>::new(rd);
+    let val = decode::from_read(&mut rd)?;
+    Ok((val, rd.finalize()))
+}
+
+/// Deserializes an instance of type `T` from an I/O stream, feeding exactly the bytes it was read
+/// from into the given digest, which is returned along with the value.
+///
+/// Unlike [`from_read_hashed`], the digest isn't finalized, so it can be any [`Update`]
+/// implementation, e.g. an HMAC to verify.
+///
+/// # Errors
+///
+/// Fails like [`from_read`](crate::from_read).
+///
+/// # Examples
+///
+/// ```
+/// use hmac::{Hmac, Mac};
+/// use sha2::Sha256;
+///
+/// let buf = rmp_serde::to_vec(&("alice", 42)).unwrap();
+/// let mut mac = Hmac::<Sha256>::new_from_slice(b"secret").unwrap();
+/// mac.update(&buf);
+/// let tag = mac.finalize().into_bytes();
+///
+/// let mac = Hmac::<Sha256>::new_from_slice(b"secret").unwrap();
+/// let (val, mac) = rmp_serde::hashing::from_read_with_digest::<_, _, (String, u32)>(&buf[..], mac).unwrap();
+/// mac.verify_slice(&tag).unwrap();
+/// assert_eq!(("alice".to_owned(), 42), val);
+/// ```
+pub fn from_read_with_digest<R, D, T>(rd: R, digest: D) -> Result<(T, D), Error>
+where
+    R: Read,
+    D: Update,
+    T: DeserializeOwned,
+{
+    let mut rd = HashingReader::with_digest(rd, digest);
+    let val = decode::from_read(&mut rd)?;
+    Ok((val, rd.into_parts().1))
+}
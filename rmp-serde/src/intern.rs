@@ -0,0 +1,328 @@
+//! Sharing allocations between repeated strings on decode.
+//!
+//! Payloads often repeat the same strings, e.g. map keys, many times. Deserializing each of them
+//! into a `String` allocates every time. An [`Interner`] set with
+//! [`Deserializer::with_interner`](crate::Deserializer::with_interner) keeps one allocation per
+//! distinct string instead, up to its limits, which is handed out to every [`Interned`] value or `Arc<str>` field
+//! annotated with `#[serde(with = "rmp_serde::intern")]`.
+//!
+//! Without an interner such values are deserialized by allocating, so the same types can be used
+//! with any deserializer.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::collections::HashMap;
+//! use std::sync::Arc;
+//!
+//! use rmp_serde::intern::{Interned, Interner};
+//! use serde::Deserialize;
+//!
+//! let rows = vec![HashMap::from([("temperature", 21)]), HashMap::from([("temperature", 23)])];
+//! let buf = rmp_serde::to_vec(&rows).unwrap();
+//!
+//! let interner = Interner::new();
+//! let mut de = rmp_serde::Deserializer::new(&buf[..]).with_interner(interner.clone());
+//! let rows: Vec<HashMap<Interned, u32>> = Deserialize::deserialize(&mut de).unwrap();
+//!
+//! let keys: Vec<_> = rows.iter().flat_map(|row| row.keys()).collect();
+//! assert!(Arc::ptr_eq(keys[0].as_arc(), keys[1].as_arc()));
+//! assert_eq!(1, interner.len());
+//! ```
+
+use std::borrow::Borrow;
+use std::collections::HashSet;
+use std::fmt::{self, Display, Formatter};
+use std::ops::Deref;
+use std::str;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use serde::de::{self, Unexpected, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::scope::{self, Entry, Scope};
+
+/// Name of the newtype struct that asks the deserializer for an interned string.
+pub(crate) const INTERNED_STRUCT_NAME: &str = "_Interned";
+
+/// A set of distinct strings shared by the values deserialized with it.
+///
+/// Cloning an interner is cheap and the clones share the same set, so an interner may be used
+/// by many deserializers, also across threads. Strings are kept until the interner is
+/// [cleared](Interner::clear) or all of its clones are dropped.
+///
+/// The set is bounded, so that untrusted input with many distinct strings can't grow it without
+/// limit. Strings longer than the length limit, and new strings once the set is full, are still
+/// deserialized, just into an allocation of their own. See [`Interner::with_limits`].
+#[derive(Clone, Debug)]
+pub struct Interner {
+    strings: Arc<Mutex<HashSet<Arc<str>>>>,
+    max_strings: usize,
+    max_len: usize,
+}
+
+impl Interner {
+    /// The number of distinct strings an interner created with [`Interner::new`] keeps.
+    pub const DEFAULT_MAX_STRINGS: usize = 1 << 16;
+    /// The length in bytes of the longest string an interner created with [`Interner::new`]
+    /// keeps.
+    pub const DEFAULT_MAX_LEN: usize = 256;
+
+    /// Creates an empty interner with the default limits.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_limits(Self::DEFAULT_MAX_STRINGS, Self::DEFAULT_MAX_LEN)
+    }
+
+    /// Creates an empty interner that keeps at most `max_strings` distinct strings, none of them
+    /// longer than `max_len` bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    ///
+    /// use rmp_serde::intern::Interner;
+    ///
+    /// let interner = Interner::with_limits(1, 8);
+    /// let key = interner.intern("key");
+    /// assert!(Arc::ptr_eq(&key, &interner.intern("key")));
+    ///
+    /// // The set is full, and the string is too long anyway.
+    /// let long = interner.intern("a rather long value");
+    /// assert!(!Arc::ptr_eq(&long, &interner.intern("a rather long value")));
+    /// assert_eq!(1, interner.len());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn with_limits(max_strings: usize, max_len: usize) -> Self {
+        Interner {
+            strings: Arc::default(),
+            max_strings,
+            max_len,
+        }
+    }
+
+    /// Returns the shared allocation of the given string, adding it to the set if it's new.
+    ///
+    /// If the string exceeds the length limit, or is new while the set is full, a new allocation
+    /// is returned that isn't shared.
+    pub fn intern(&self, s: &str) -> Arc<str> {
+        if s.len() > self.max_len {
+            return Arc::from(s);
+        }
+
+        let mut strings = self.lock();
+        if let Some(interned) = strings.get(s) {
+            return interned.clone();
+        }
+
+        let interned: Arc<str> = Arc::from(s);
+        if strings.len() < self.max_strings {
+            strings.insert(interned.clone());
+        }
+        interned
+    }
+
+    /// Returns the number of distinct strings in the set.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns `true` if the set contains no strings.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Removes all strings from the set.
+    ///
+    /// Values deserialized before keep their strings alive, but no longer share them with values
+    /// deserialized afterwards.
+    #[inline]
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    #[inline]
+    fn lock(&self) -> MutexGuard<'_, HashSet<Arc<str>>> {
+        // The set is consistent even if a thread panicked while holding the lock.
+        self.strings.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Default for Interner {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A string that shares its allocation with equal strings deserialized with the same
+/// [`Interner`].
+///
+/// It serializes as a plain MessagePack string, and can be used as a key of a `HashMap` that is
+/// looked up by `&str`.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interned(Arc<str>);
+
+impl Interned {
+    /// Returns the shared string.
+    #[inline]
+    #[must_use]
+    pub fn as_arc(&self) -> &Arc<str> {
+        &self.0
+    }
+
+    /// Consumes this value and returns the shared string.
+    #[inline]
+    #[must_use]
+    pub fn into_arc(self) -> Arc<str> {
+        self.0
+    }
+}
+
+impl Deref for Interned {
+    type Target = str;
+
+    #[inline]
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Borrow<str> for Interned {
+    #[inline]
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for Interned {
+    #[inline]
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for Interned {
+    #[inline]
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&*self.0, fmt)
+    }
+}
+
+impl From<Arc<str>> for Interned {
+    #[inline]
+    fn from(s: Arc<str>) -> Self {
+        Interned(s)
+    }
+}
+
+impl From<&str> for Interned {
+    #[inline]
+    fn from(s: &str) -> Self {
+        Interned(Arc::from(s))
+    }
+}
+
+impl Serialize for Interned {
+    #[inline]
+    fn serialize<S: Serializer>(&self, se: S) -> Result<S::Ok, S::Error> {
+        se.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Interned {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        deserialize(de).map(Interned)
+    }
+}
+
+/// Serializes a shared string as a MessagePack string.
+pub fn serialize<S>(val: &Arc<str>, se: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    se.serialize_str(val)
+}
+
+/// Deserializes a shared string, using the [`Interner`] of the deserializer if it has one.
+pub fn deserialize<'de, D>(de: D) -> Result<Arc<str>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    de.deserialize_newtype_struct(INTERNED_STRUCT_NAME, InternedVisitor)
+}
+
+/// Hands an interned string over to the visitor of [`deserialize`].
+///
+/// The visitor can only observe a `&str`, so the allocation itself is passed on the side.
+pub(crate) fn visit_interned<'de, V>(interned: Arc<str>, visitor: V) -> Result<V::Value, crate::decode::Error>
+where
+    V: Visitor<'de>,
+{
+    let _scope = Scope::enter(Entry::Interned(interned.clone()));
+    visitor.visit_str(&interned)
+}
+
+/// Visits a string and interns it, used by the deserializer to read the string to hand over.
+pub(crate) struct InternVisitor<'a>(pub &'a Interner);
+
+impl<'de> Visitor<'de> for InternVisitor<'_> {
+    type Value = Arc<str>;
+
+    fn expecting(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        fmt.write_str("a string")
+    }
+
+    #[inline]
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(self.0.intern(v))
+    }
+
+    #[inline]
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        match str::from_utf8(v) {
+            Ok(s) => Ok(self.0.intern(s)),
+            Err(..) => Err(de::Error::invalid_value(Unexpected::Bytes(v), &self)),
+        }
+    }
+}
+
+struct InternedVisitor;
+
+impl<'de> Visitor<'de> for InternedVisitor {
+    type Value = Arc<str>;
+
+    fn expecting(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        fmt.write_str("a string")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        // Only the innermost interned string is the one handed over for this visitor.
+        let interned = scope::find_map(|entry| match entry {
+            Entry::Interned(interned) => Some((**interned == *v).then(|| interned.clone())),
+            _ => None,
+        });
+        Ok(interned.flatten().unwrap_or_else(|| Arc::from(v)))
+    }
+
+    #[inline]
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        match str::from_utf8(v) {
+            Ok(s) => Ok(Arc::from(s)),
+            Err(..) => Err(de::Error::invalid_value(Unexpected::Bytes(v), &self)),
+        }
+    }
+
+    #[inline]
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, de: D) -> Result<Self::Value, D::Error> {
+        de.deserialize_str(self)
+    }
+}
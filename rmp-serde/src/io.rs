@@ -0,0 +1,216 @@
+//! I/O traits used by the serializer and the deserializer.
+//!
+//! With the `std` feature, which is on by default, these are re-exported from `std::io`.
+//! Without it, this module provides minimal replacements, implemented for byte slices and
+//! vectors, so that [`to_vec`](crate::to_vec) and [`from_slice`](crate::from_slice) work on
+//! `no_std` targets.
+//!
+//! Without `std`, a custom reader implements [`rmp::decode::RmpRead`] as well as [`Read`], with
+//! the same error type.
+
+#[cfg(feature = "std")]
+pub use std::io::{sink, Error, ErrorKind, Read, Result, Sink, Write};
+
+#[cfg(not(feature = "std"))]
+pub use self::imp::{sink, Error, ErrorKind, Read, Result, Sink, Write};
+
+#[cfg(not(feature = "std"))]
+mod imp {
+    use alloc::vec::Vec;
+    use core::fmt::{self, Display, Formatter};
+
+    use rmp::decode::RmpRead;
+
+    /// A specialized `Result` type for I/O operations.
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    /// A list specifying general categories of I/O error.
+    #[non_exhaustive]
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+    pub enum ErrorKind {
+        /// The input ended before the requested number of bytes could be read.
+        UnexpectedEof,
+        /// A write returned `Ok(0)`, so the data couldn't be written completely.
+        WriteZero,
+        /// An allocation failed, or the data didn't fit a fixed-size buffer.
+        OutOfMemory,
+        /// Any other error.
+        Other,
+    }
+
+    impl ErrorKind {
+        fn as_str(self) -> &'static str {
+            match self {
+                ErrorKind::UnexpectedEof => "unexpected end of file",
+                ErrorKind::WriteZero => "write zero",
+                ErrorKind::OutOfMemory => "out of memory",
+                ErrorKind::Other => "other error",
+            }
+        }
+    }
+
+    /// The error type for I/O operations of [`Read`] and [`Write`].
+    #[derive(Debug)]
+    pub struct Error {
+        kind: ErrorKind,
+    }
+
+    impl Error {
+        /// Returns the corresponding [`ErrorKind`] for this error.
+        #[inline]
+        #[must_use]
+        pub fn kind(&self) -> ErrorKind {
+            self.kind
+        }
+    }
+
+    impl From<ErrorKind> for Error {
+        #[inline]
+        fn from(kind: ErrorKind) -> Self {
+            Error { kind }
+        }
+    }
+
+    impl Display for Error {
+        fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+            f.write_str(self.kind.as_str())
+        }
+    }
+
+    impl serde::de::StdError for Error {}
+
+    impl rmp::encode::RmpWriteErr for Error {}
+    impl rmp::decode::RmpReadErr for Error {}
+
+    /// A sink of bytes, like `std::io::Write`.
+    pub trait Write {
+        /// Writes some of the bytes of `buf`, returning how many were written.
+        fn write(&mut self, buf: &[u8]) -> Result<usize>;
+
+        /// Flushes any buffered data.
+        fn flush(&mut self) -> Result<()>;
+
+        /// Writes the whole buffer.
+        fn write_all(&mut self, mut buf: &[u8]) -> Result<()> {
+            while !buf.is_empty() {
+                match self.write(buf)? {
+                    0 => return Err(ErrorKind::WriteZero.into()),
+                    n => buf = &buf[n..],
+                }
+            }
+            Ok(())
+        }
+    }
+
+    impl<W: Write + ?Sized> Write for &mut W {
+        #[inline]
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            (**self).write(buf)
+        }
+
+        #[inline]
+        fn flush(&mut self) -> Result<()> {
+            (**self).flush()
+        }
+
+        #[inline]
+        fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+            (**self).write_all(buf)
+        }
+    }
+
+    impl Write for Vec<u8> {
+        #[inline]
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            self.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        #[inline]
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Writes into the slice, advancing it past the written bytes.
+    impl Write for &mut [u8] {
+        #[inline]
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            let len = buf.len().min(self.len());
+            let (head, tail) = core::mem::take(self).split_at_mut(len);
+            head.copy_from_slice(&buf[..len]);
+            *self = tail;
+            Ok(len)
+        }
+
+        #[inline]
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    impl Write for rmp::encode::ByteBuf {
+        #[inline]
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            self.as_mut_vec().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        #[inline]
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    /// A writer which discards all the data, like `std::io::Sink`.
+    #[derive(Debug, Default, Clone, Copy)]
+    pub struct Sink;
+
+    /// Creates a writer which discards all the data, like `std::io::sink`.
+    #[inline]
+    #[must_use]
+    pub const fn sink() -> Sink {
+        Sink
+    }
+
+    impl Write for Sink {
+        #[inline]
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            Ok(buf.len())
+        }
+
+        #[inline]
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    /// A source of bytes, like `std::io::Read`.
+    ///
+    /// The deserializer reads MessagePack markers and numbers through [`RmpRead`], so readers
+    /// implement it too.
+    pub trait Read: RmpRead<Error = Error> {
+        /// Reads some bytes into `buf`, returning how many were read.
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize>;
+
+        /// Reads exactly enough bytes to fill `buf`.
+        fn read_exact(&mut self, mut buf: &mut [u8]) -> Result<()> {
+            while !buf.is_empty() {
+                match self.read(buf)? {
+                    0 => return Err(ErrorKind::UnexpectedEof.into()),
+                    n => buf = &mut buf[n..],
+                }
+            }
+            Ok(())
+        }
+
+        /// Borrows this reader.
+        #[inline]
+        fn by_ref(&mut self) -> &mut Self
+        where
+            Self: Sized,
+        {
+            self
+        }
+    }
+}
@@ -1,9 +1,15 @@
 #![doc = include_str!("../README.md")]
+#![cfg_attr(not(feature = "std"), no_std)]
 #![forbid(unsafe_code)]
 #![warn(missing_debug_implementations, missing_docs)]
 
-use std::fmt::{self, Display, Formatter};
-use std::str::{self, Utf8Error};
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+use core::marker::PhantomData;
+use core::str::{self, Utf8Error};
 
 use serde::de;
 use serde::{Deserialize, Serialize};
@@ -11,14 +17,67 @@ use serde::{Deserialize, Serialize};
 #[allow(deprecated)]
 pub use crate::decode::from_read_ref;
 pub use crate::decode::{from_read, Deserializer};
-pub use crate::encode::{to_vec, to_vec_named, Serializer};
+pub use crate::encode::{
+    encoded_len, to_vec, to_vec_all, to_vec_named, to_vec_with, to_writer, to_writer_named, to_writer_with, Serializer,
+};
 
-pub use crate::decode::from_slice;
+pub use crate::decode::{from_slice, from_slice_all, from_slice_exact};
+pub use crate::config::KeyPolicy;
+pub use crate::raw_value::{RawValue, RawValueRef};
+pub use crate::transcode::reencode;
 
 mod bytes;
+#[cfg(feature = "tokio-util")]
+pub mod codec;
+#[cfg(any(feature = "zstd", feature = "lz4"))]
+pub mod compress;
 pub mod config;
+#[cfg(feature = "std")]
+pub mod context;
 pub mod decode;
 pub mod encode;
+pub mod fields;
+#[cfg(feature = "digest")]
+pub mod hashing;
+#[cfg(feature = "std")]
+pub mod intern;
+pub mod io;
+pub mod partial;
+mod raw_value;
+pub mod registry;
+#[cfg(feature = "rpc")]
+pub mod rpc;
+#[cfg(feature = "std")]
+mod scope;
+#[cfg(feature = "bytes")]
+pub mod shared_bytes;
+pub mod transcode;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+/// Broad category of an [`encode::Error`] or [`decode::Error`], as returned by their `kind`
+/// methods.
+///
+/// This allows handling errors, e.g. deciding whether to retry, without matching on every
+/// variant.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// The input ended in the middle of a value. More data may make it decodable.
+    UnexpectedEof,
+    /// Any other I/O error of the underlying reader or writer.
+    Io,
+    /// The data doesn't match the structure of the target type, e.g. a string where a number
+    /// was expected, or a number that doesn't fit.
+    TypeMismatch,
+    /// A string isn't valid UTF-8.
+    Utf8,
+    /// A configured depth or length limit was exceeded.
+    LimitExceeded,
+    /// Malformed or unexpected data, or an error reported by a `Serialize` or `Deserialize`
+    /// implementation.
+    Syntax,
+}
 
 /// Hack used to serialize MessagePack Extension types.
 ///
@@ -41,12 +100,21 @@ pub mod encode;
 /// ```
 pub const MSGPACK_EXT_STRUCT_NAME: &str = "_ExtStruct";
 
+/// Name of Serde newtype struct to represent MessagePack strings that are not valid UTF-8.
+///
+/// Serde data model: `_StrStruct(binary)`
+///
+/// The bytes are written with a str header, so strings decoded with invalid UTF-8, e.g. into an
+/// `rmpv::Value`, are encoded back unchanged instead of as bin.
+pub const MSGPACK_STR_STRUCT_NAME: &str = "_StrStruct";
+
 /// Helper that allows both to encode and decode strings no matter whether they contain valid or
 /// invalid UTF-8.
 ///
 /// Regardless of validity the UTF-8 content this type will always be serialized as a string.
+/// Other serializers see invalid UTF-8 as bytes, wrapped into a newtype struct named
+/// [`MSGPACK_STR_STRUCT_NAME`].
 #[derive(Clone, Debug, PartialEq)]
-#[doc(hidden)]
 pub struct Raw {
     s: Result<String, (Vec<u8>, Utf8Error)>,
 }
@@ -59,10 +127,9 @@ impl Raw {
         Self { s: Ok(v) }
     }
 
-    /// DO NOT USE. See <https://github.com/3Hren/msgpack-rust/issues/305>
-    #[deprecated(note = "This feature has been removed")]
+    /// Constructs a new `Raw` from the bytes of a string, which may be invalid UTF-8.
     #[must_use]
-    pub fn from_utf8(v: Vec<u8>) -> Self {
+    pub fn from_bytes(v: Vec<u8>) -> Self {
         match String::from_utf8(v) {
             Ok(v) => Raw::new(v),
             Err(err) => {
@@ -74,6 +141,13 @@ impl Raw {
         }
     }
 
+    /// DO NOT USE. See <https://github.com/3Hren/msgpack-rust/issues/305>
+    #[deprecated(note = "This feature has been removed")]
+    #[must_use]
+    pub fn from_utf8(v: Vec<u8>) -> Self {
+        Self::from_bytes(v)
+    }
+
     /// Returns `true` if the raw is valid UTF-8.
     #[inline]
     #[must_use]
@@ -144,11 +218,25 @@ impl Serialize for Raw {
     {
         match self.s {
             Ok(ref s) => se.serialize_str(s),
-            Err((ref b, ..)) => se.serialize_bytes(b),
+            Err((ref b, ..)) => se.serialize_newtype_struct(MSGPACK_STR_STRUCT_NAME, &StrData(b)),
         }
     }
 }
 
+/// Bytes of a string that is not valid UTF-8, serialized inside a [`MSGPACK_STR_STRUCT_NAME`]
+/// newtype.
+struct StrData<'a>(&'a [u8]);
+
+impl Serialize for StrData<'_> {
+    #[inline]
+    fn serialize<S>(&self, se: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        se.serialize_bytes(self.0)
+    }
+}
+
 struct RawVisitor;
 
 impl<'de> de::Visitor<'de> for RawVisitor {
@@ -197,6 +285,13 @@ impl<'de> de::Visitor<'de> for RawVisitor {
 
         Ok(Raw { s })
     }
+
+    #[inline]
+    fn visit_newtype_struct<D>(self, de: D) -> Result<Self::Value, D::Error>
+        where D: de::Deserializer<'de>
+    {
+        de.deserialize_any(self)
+    }
 }
 
 impl<'de> Deserialize<'de> for Raw {
@@ -211,9 +306,9 @@ impl<'de> Deserialize<'de> for Raw {
 /// Helper that allows both to encode and decode strings no matter whether they contain valid or
 /// invalid UTF-8.
 ///
-/// Regardless of validity the UTF-8 content this type will always be serialized as a string.
+/// Regardless of validity the UTF-8 content this type will always be serialized as a string,
+/// like [`Raw`].
 #[derive(Clone, Copy, Debug, PartialEq)]
-#[doc(hidden)]
 pub struct RawRef<'a> {
     s: Result<&'a str, (&'a [u8], Utf8Error)>,
 }
@@ -226,9 +321,9 @@ impl<'a> RawRef<'a> {
         Self { s: Ok(v) }
     }
 
-    #[deprecated(note = "This feature has been removed")]
+    /// Constructs a new `RawRef` from the bytes of a string, which may be invalid UTF-8.
     #[must_use]
-    pub fn from_utf8(v: &'a [u8]) -> Self {
+    pub fn from_bytes(v: &'a [u8]) -> Self {
         match str::from_utf8(v) {
             Ok(v) => RawRef::new(v),
             Err(err) => {
@@ -239,6 +334,13 @@ impl<'a> RawRef<'a> {
         }
     }
 
+    /// DO NOT USE. See <https://github.com/3Hren/msgpack-rust/issues/305>
+    #[deprecated(note = "This feature has been removed")]
+    #[must_use]
+    pub fn from_utf8(v: &'a [u8]) -> Self {
+        Self::from_bytes(v)
+    }
+
     /// Returns `true` if the raw is valid UTF-8.
     #[inline]
     #[must_use]
@@ -292,7 +394,7 @@ impl<'a> Serialize for RawRef<'a> {
     {
         match self.s {
             Ok(s) => se.serialize_str(s),
-            Err((b, ..)) => se.serialize_bytes(b),
+            Err((b, ..)) => se.serialize_newtype_struct(MSGPACK_STR_STRUCT_NAME, &StrData(b)),
         }
     }
 }
@@ -325,6 +427,13 @@ impl<'de> de::Visitor<'de> for RawRefVisitor {
 
         Ok(RawRef { s })
     }
+
+    #[inline]
+    fn visit_newtype_struct<D>(self, de: D) -> Result<Self::Value, D::Error>
+        where D: de::Deserializer<'de>
+    {
+        de.deserialize_any(self)
+    }
 }
 
 impl<'de> Deserialize<'de> for RawRef<'de> {
@@ -335,3 +444,409 @@ impl<'de> Deserialize<'de> for RawRef<'de> {
         de.deserialize_any(RawRefVisitor)
     }
 }
+
+/// MessagePack extension value with an application-defined type and owned data.
+///
+/// Serialized and deserialized as a real MessagePack ext (`fixext*` or `ext*` depending on the
+/// data length) when used with this crate, instead of a sequence.
+///
+/// # Examples
+///
+/// ```
+/// use rmp_serde::Ext;
+///
+/// let buf = rmp_serde::to_vec(&Ext(42, vec![1, 2])).unwrap();
+/// assert_eq!(vec![0xd5, 0x2a, 0x01, 0x02], buf);
+///
+/// let ext: Ext = rmp_serde::from_slice(&buf).unwrap();
+/// assert_eq!(Ext(42, vec![1, 2]), ext);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Ext(pub i8, pub Vec<u8>);
+
+/// MessagePack extension value with an application-defined type and borrowed data.
+///
+/// Works like [`Ext`], but deserialization borrows the data, so it is only possible from
+/// a slice, e.g. using [`from_slice`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ExtRef<'a>(pub i8, pub &'a [u8]);
+
+impl Serialize for Ext {
+    #[inline]
+    fn serialize<S>(&self, se: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        ExtRef(self.0, &self.1).serialize(se)
+    }
+}
+
+impl<'a> Serialize for ExtRef<'a> {
+    fn serialize<S>(&self, se: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        se.serialize_newtype_struct(MSGPACK_EXT_STRUCT_NAME, &ExtFields(self.0, self.1))
+    }
+}
+
+/// The `(tag, data)` tuple wrapped into `_ExtStruct`.
+struct ExtFields<'a>(i8, &'a [u8]);
+
+impl<'a> Serialize for ExtFields<'a> {
+    fn serialize<S>(&self, se: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeTuple;
+
+        let mut tup = se.serialize_tuple(2)?;
+        tup.serialize_element(&self.0)?;
+        tup.serialize_element(&ExtData(self.1))?;
+        tup.end()
+    }
+}
+
+/// Extension methods for writing MessagePack specific values from custom `Serialize` impls.
+///
+/// This is implemented for every serde serializer, so it can be used without knowing the
+/// concrete serializer type. With this crate's [`Serializer`], `serialize_ext` writes a real
+/// MessagePack ext value; other serializers see the newtype struct named
+/// [`MSGPACK_EXT_STRUCT_NAME`] wrapping an `(i8, bytes)` tuple, same as for [`ExtRef`].
+///
+/// # Examples
+///
+/// ```
+/// use serde::{Serialize, Serializer};
+/// use rmp_serde::SerializerExt;
+///
+/// struct Point(u16, u16);
+///
+/// impl Serialize for Point {
+///     fn serialize<S: Serializer>(&self, se: S) -> Result<S::Ok, S::Error> {
+///         let mut data = [0; 4];
+///         data[..2].copy_from_slice(&self.0.to_be_bytes());
+///         data[2..].copy_from_slice(&self.1.to_be_bytes());
+///         se.serialize_ext(7, &data)
+///     }
+/// }
+///
+/// let buf = rmp_serde::to_vec(&Point(1, 2)).unwrap();
+/// assert_eq!(vec![0xd6, 0x07, 0x00, 0x01, 0x00, 0x02], buf);
+/// ```
+pub trait SerializerExt: serde::Serializer {
+    /// Serializes an ext value with the given application-defined type and data.
+    fn serialize_ext(self, ty: i8, data: &[u8]) -> Result<Self::Ok, Self::Error>;
+}
+
+impl<S: serde::Serializer> SerializerExt for S {
+    #[inline]
+    fn serialize_ext(self, ty: i8, data: &[u8]) -> Result<Self::Ok, Self::Error> {
+        ExtRef(ty, data).serialize(self)
+    }
+}
+
+struct ExtData<'a>(&'a [u8]);
+
+impl<'a> Serialize for ExtData<'a> {
+    #[inline]
+    fn serialize<S>(&self, se: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        se.serialize_bytes(self.0)
+    }
+}
+
+struct ExtVisitor;
+
+impl<'de> de::Visitor<'de> for ExtVisitor {
+    type Value = Ext;
+
+    #[cold]
+    fn expecting(&self, fmt: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        "MessagePack ext".fmt(fmt)
+    }
+
+    #[inline]
+    fn visit_newtype_struct<D>(self, de: D) -> Result<Self::Value, D::Error>
+        where D: de::Deserializer<'de>
+    {
+        de.deserialize_tuple(2, self)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where A: de::SeqAccess<'de>
+    {
+        let tag = seq.next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let data = seq.next_element::<ExtDataBuf>()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+
+        Ok(Ext(tag, data.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for Ext {
+    #[inline]
+    fn deserialize<D>(de: D) -> Result<Self, D::Error>
+        where D: de::Deserializer<'de>
+    {
+        de.deserialize_newtype_struct(MSGPACK_EXT_STRUCT_NAME, ExtVisitor)
+    }
+}
+
+struct ExtRefVisitor;
+
+impl<'de> de::Visitor<'de> for ExtRefVisitor {
+    type Value = ExtRef<'de>;
+
+    #[cold]
+    fn expecting(&self, fmt: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        "MessagePack ext with borrowed data".fmt(fmt)
+    }
+
+    #[inline]
+    fn visit_newtype_struct<D>(self, de: D) -> Result<Self::Value, D::Error>
+        where D: de::Deserializer<'de>
+    {
+        de.deserialize_tuple(2, self)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where A: de::SeqAccess<'de>
+    {
+        let tag = seq.next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let data = seq.next_element::<ExtDataRef<'de>>()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+
+        Ok(ExtRef(tag, data.0))
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for ExtRef<'a> {
+    #[inline]
+    fn deserialize<D>(de: D) -> Result<Self, D::Error>
+        where D: de::Deserializer<'de>
+    {
+        de.deserialize_newtype_struct(MSGPACK_EXT_STRUCT_NAME, ExtRefVisitor)
+    }
+}
+
+/// MessagePack extension value with a fixed type, wrapping a domain type that is encoded as the
+/// ext data.
+///
+/// This removes the boilerplate of implementing `Serialize` and `Deserialize` by hand for types
+/// that map to a single extension type. The wrapped value is serialized from its bytes
+/// (`AsRef<[u8]>`) and deserialized from them (`TryFrom<Vec<u8>>`), failing if the ext type
+/// doesn't match `TYPE` or if the conversion fails.
+///
+/// # Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use rmp_serde::TypedExt;
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct Record {
+///     id: TypedExt<2, [u8; 4]>,
+///     blob: TypedExt<3, Vec<u8>>,
+/// }
+///
+/// let val = Record { id: TypedExt([1, 2, 3, 4]), blob: TypedExt(vec![5]) };
+/// let buf = rmp_serde::to_vec(&val).unwrap();
+/// assert_eq!(vec![0x92, 0xd6, 0x02, 0x01, 0x02, 0x03, 0x04, 0xd4, 0x03, 0x05], buf);
+/// assert_eq!(val, rmp_serde::from_slice(&buf).unwrap());
+///
+/// // Wrong ext type.
+/// assert!(rmp_serde::from_slice::<TypedExt<3, Vec<u8>>>(&[0xd4, 0x02, 0x05]).is_err());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TypedExt<const TYPE: i8, T>(pub T);
+
+impl<const TYPE: i8, T> TypedExt<TYPE, T> {
+    /// Unwraps the value.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<const TYPE: i8, T: AsRef<[u8]>> Serialize for TypedExt<TYPE, T> {
+    #[inline]
+    fn serialize<S>(&self, se: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        ExtRef(TYPE, self.0.as_ref()).serialize(se)
+    }
+}
+
+impl<'de, const TYPE: i8, T: TryFrom<Vec<u8>>> Deserialize<'de> for TypedExt<TYPE, T> {
+    fn deserialize<D>(de: D) -> Result<Self, D::Error>
+        where D: de::Deserializer<'de>
+    {
+        let Ext(tag, data) = Ext::deserialize(de)?;
+        if tag != TYPE {
+            return Err(de::Error::custom(format_args!("expected ext type {TYPE}, got {tag}")));
+        }
+
+        let len = data.len();
+        T::try_from(data)
+            .map(TypedExt)
+            .map_err(|_| de::Error::invalid_length(len, &"ext data of the wrapped type"))
+    }
+}
+
+/// Name of the newtype struct that makes the serializer write the structs inside it as maps.
+pub(crate) const NAMED_STRUCT_NAME: &str = "_NamedStruct";
+
+/// Wrapper that serializes the structs and struct variants inside it as maps, even when the
+/// serializer otherwise writes them as arrays, e.g. with [`to_vec`].
+///
+/// Tagged enums need this: serde decodes adjacently tagged and untagged enums through a buffer
+/// that no longer knows the field names of their struct variants, so these can only be read back
+/// from maps. Internally tagged enums can be read from arrays with the tag first, but not when
+/// they are nested in an untagged or adjacently tagged one. Wrapping such enums, or the values
+/// containing them, switches them to map encoding, leaving the rest of the message compact.
+///
+/// Other serializers see a newtype struct, which most of them write as the inner value.
+///
+/// # Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use rmp_serde::Named;
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// #[serde(tag = "type", content = "payload")]
+/// enum Event {
+///     Started,
+///     Moved { x: u32 },
+/// }
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct Record {
+///     id: u32,
+///     event: Named<Event>,
+/// }
+///
+/// let val = Record { id: 1, event: Named(Event::Moved { x: 3 }) };
+/// let buf = rmp_serde::to_vec(&val).unwrap();
+/// // The record is still written as an array.
+/// assert_eq!(0x92, buf[0]);
+/// assert_eq!(val, rmp_serde::from_slice(&buf).unwrap());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Named<T>(pub T);
+
+impl<T> Named<T> {
+    /// Unwraps the value.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: Serialize> Serialize for Named<T> {
+    #[inline]
+    fn serialize<S>(&self, se: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        se.serialize_newtype_struct(NAMED_STRUCT_NAME, &self.0)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Named<T> {
+    #[inline]
+    fn deserialize<D>(de: D) -> Result<Self, D::Error>
+        where D: de::Deserializer<'de>
+    {
+        struct NamedVisitor<T>(PhantomData<T>);
+
+        impl<'de, T: Deserialize<'de>> de::Visitor<'de> for NamedVisitor<T> {
+            type Value = Named<T>;
+
+            #[cold]
+            fn expecting(&self, fmt: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+                "a value wrapped into Named".fmt(fmt)
+            }
+
+            #[inline]
+            fn visit_newtype_struct<D>(self, de: D) -> Result<Self::Value, D::Error>
+                where D: de::Deserializer<'de>
+            {
+                T::deserialize(de).map(Named)
+            }
+        }
+
+        de.deserialize_newtype_struct(NAMED_STRUCT_NAME, NamedVisitor(PhantomData))
+    }
+}
+
+struct ExtDataBuf(Vec<u8>);
+
+impl<'de> Deserialize<'de> for ExtDataBuf {
+    #[inline]
+    fn deserialize<D>(de: D) -> Result<Self, D::Error>
+        where D: de::Deserializer<'de>
+    {
+        struct ExtDataBufVisitor;
+
+        impl<'de> de::Visitor<'de> for ExtDataBufVisitor {
+            type Value = ExtDataBuf;
+
+            #[cold]
+            fn expecting(&self, fmt: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+                "ext data".fmt(fmt)
+            }
+
+            #[inline]
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+                where E: de::Error
+            {
+                Ok(ExtDataBuf(v.into()))
+            }
+
+            #[inline]
+            fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E>
+                where E: de::Error
+            {
+                Ok(ExtDataBuf(v))
+            }
+        }
+
+        de.deserialize_byte_buf(ExtDataBufVisitor)
+    }
+}
+
+struct ExtDataRef<'a>(&'a [u8]);
+
+impl<'de> Deserialize<'de> for ExtDataRef<'de> {
+    #[inline]
+    fn deserialize<D>(de: D) -> Result<Self, D::Error>
+        where D: de::Deserializer<'de>
+    {
+        struct ExtDataRefVisitor;
+
+        impl<'de> de::Visitor<'de> for ExtDataRefVisitor {
+            type Value = ExtDataRef<'de>;
+
+            #[cold]
+            fn expecting(&self, fmt: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+                "borrowed ext data".fmt(fmt)
+            }
+
+            #[inline]
+            fn visit_borrowed_bytes<E>(self, v: &'de [u8]) -> Result<Self::Value, E>
+                where E: de::Error
+            {
+                Ok(ExtDataRef(v))
+            }
+        }
+
+        de.deserialize_bytes(ExtDataRefVisitor)
+    }
+}
@@ -0,0 +1,256 @@
+//! Resumable decoding of values that arrive in pieces, e.g. from non-blocking sockets.
+//!
+//! Deserializing straight from a non-blocking reader loses whatever was read of a value when the
+//! reader reports `WouldBlock`. A [`PartialDecoder`] is fed the bytes as they arrive instead and
+//! keeps an incomplete value buffered until the rest of it is fed, so it works with any event
+//! loop, e.g. mio, without further buffering by the caller.
+//!
+//! # Examples
+//!
+//! ```
+//! use rmp_serde::partial::{PartialDecoder, Progress};
+//!
+//! let buf = rmp_serde::to_vec_all([(1, "one"), (2, "two")]).unwrap();
+//!
+//! let mut decoder = PartialDecoder::<(u8, String)>::new();
+//! let mut vals = Vec::new();
+//! // Bytes arriving 3 at a time.
+//! for mut chunk in buf.chunks(3) {
+//!     while !chunk.is_empty() {
+//!         match decoder.feed(chunk).unwrap() {
+//!             Progress::NeedMore(..) => break,
+//!             Progress::Done(val, consumed) => {
+//!                 vals.push(val);
+//!                 chunk = &chunk[consumed..];
+//!             }
+//!             Progress::Invalid(err, consumed) => {
+//!                 eprintln!("skipping a value: {err}");
+//!                 chunk = &chunk[consumed..];
+//!             }
+//!         }
+//!     }
+//! }
+//!
+//! assert_eq!(vec![(1, "one".to_owned()), (2, "two".to_owned())], vals);
+//! assert_eq!(0, decoder.buffered_len());
+//! ```
+
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Formatter};
+use core::marker::PhantomData;
+
+use rmp::{Marker, ValidationError, Validator, MAX_VALIDATION_DEPTH};
+use serde::de::DeserializeOwned;
+
+use crate::decode::Error;
+
+/// The default limit of the encoded length of a single value, 8 MiB.
+pub const DEFAULT_MAX_LEN: usize = 8 * 1024 * 1024;
+
+/// Result of feeding bytes to a [`PartialDecoder`].
+#[derive(Debug)]
+pub enum Progress<T> {
+    /// The value is incomplete and needs at least the given number of bytes more. All of the fed
+    /// bytes have been buffered.
+    NeedMore(usize),
+    /// The value is complete, and took the given number of bytes of the last fed slice. The bytes
+    /// following it weren't consumed and have to be fed again for the next value.
+    Done(T, usize),
+    /// The value is complete and well-formed, but failed to deserialize as `T`. Like with
+    /// [`Done`](Progress::Done), it took the given number of bytes of the last fed slice, and
+    /// the next value starts right after them.
+    Invalid(Error, usize),
+}
+
+/// A decoder of values of type `T` from bytes fed in arbitrary pieces.
+///
+/// Values are deserialized like with [`from_slice`](crate::from_slice). Values whose encoded
+/// length exceeds the limit set with [`PartialDecoder::with_max_len`] are rejected with
+/// [`Error::LengthLimitExceeded`] as soon as their headers are fed.
+///
+/// A value that is well-formed but doesn't match `T` is consumed and reported as
+/// [`Progress::Invalid`], so decoding can go on with the next value. Malformed data and values
+/// exceeding the limit fail with an error instead, after which the buffered bytes are dropped and
+/// it's up to the caller to find where the next value starts, if anywhere.
+///
+/// The bytes of an incomplete value are validated only once: feeding more of them resumes the
+/// validation where it stopped.
+pub struct PartialDecoder<T> {
+    /// Bytes of an incomplete value.
+    buf: Vec<u8>,
+    /// Lower bound of the length of the buffered value.
+    needed: usize,
+    max_len: usize,
+    /// Validator of the buffered value, which has seen all of the buffered bytes.
+    validator: Validator,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> PartialDecoder<T> {
+    /// Creates a new decoder with the default length limit.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        PartialDecoder {
+            buf: Vec::new(),
+            needed: 1,
+            max_len: DEFAULT_MAX_LEN,
+            validator: validator_for(DEFAULT_MAX_LEN),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Sets the maximum encoded length of a single value, in bytes.
+    ///
+    /// The default is [`DEFAULT_MAX_LEN`].
+    #[inline]
+    #[must_use]
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self.validator = validator_for(max_len);
+        self
+    }
+
+    /// Returns the number of bytes of an incomplete value buffered so far.
+    #[inline]
+    #[must_use]
+    pub fn buffered_len(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Drops the bytes of an incomplete value, so that decoding starts over with the next bytes
+    /// fed.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.buf.clear();
+        self.needed = 1;
+        self.validator.reset();
+    }
+
+    #[cold]
+    fn too_long(&mut self, len: usize) -> Error {
+        self.reset();
+        Error::LengthLimitExceeded(u32::try_from(len).unwrap_or(u32::MAX))
+    }
+}
+
+impl<T: DeserializeOwned> PartialDecoder<T> {
+    /// Feeds the next bytes of the input, returning the value once it's complete.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the input is malformed or if the value exceeds the length limit. A value that
+    /// doesn't match `T` is returned as [`Progress::Invalid`] instead.
+    pub fn feed(&mut self, data: &[u8]) -> Result<Progress<T>, Error> {
+        let prev = self.buf.len();
+        // Unless a value is already buffered, the input is only copied if it's incomplete.
+        let input = if prev == 0 {
+            data
+        } else {
+            self.buf.extend_from_slice(data);
+            &self.buf[..]
+        };
+        if input.len() < self.needed {
+            if prev == 0 {
+                self.buf.extend_from_slice(data);
+            }
+            return Ok(Progress::NeedMore(self.needed - self.buf.len()));
+        }
+
+        match self.validator.validate(input) {
+            Ok(len) if len > self.max_len => Err(self.too_long(len)),
+            Ok(len) => {
+                let res = crate::from_slice(&input[..len]);
+                self.reset();
+                Ok(match res {
+                    Ok(val) => Progress::Done(val, len - prev),
+                    Err(err) => Progress::Invalid(err, len - prev),
+                })
+            }
+            Err(ValidationError::Truncated) => {
+                let needed = frame_hint(input).max(input.len() + 1);
+                if needed > self.max_len {
+                    return Err(self.too_long(needed));
+                }
+                if prev == 0 {
+                    self.buf.extend_from_slice(data);
+                }
+                self.needed = needed;
+                Ok(Progress::NeedMore(needed - self.buf.len()))
+            }
+            Err(err) => {
+                self.reset();
+                Err(match err {
+                    ValidationError::DepthLimitExceeded { .. } => Error::DepthLimitExceeded,
+                    ValidationError::LengthLimitExceeded { len, .. } => Error::LengthLimitExceeded(len),
+                    err => Error::Syntax(err.to_string()),
+                })
+            }
+        }
+    }
+}
+
+impl<T> Default for PartialDecoder<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Debug for PartialDecoder<T> {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("PartialDecoder")
+            .field("buffered_len", &self.buf.len())
+            .field("needed", &self.needed)
+            .field("max_len", &self.max_len)
+            .finish()
+    }
+}
+
+/// Creates a validator that rejects the headers of values longer than `max_len`.
+fn validator_for(max_len: usize) -> Validator {
+    Validator::with_limits(MAX_VALIDATION_DEPTH, u32::try_from(max_len).unwrap_or(u32::MAX))
+}
+
+/// Returns a lower bound of the encoded length of the value at the start of `buf`, as declared
+/// by its header.
+pub(crate) fn frame_hint(buf: &[u8]) -> usize {
+    let marker = match buf.first() {
+        Some(&byte) => Marker::from_u8(byte),
+        None => return 1,
+    };
+    // Size of the length field, bytes between it and the payload, and the minimal number of
+    // bytes per unit of length. Each array element takes at least one byte, each map entry two.
+    let (size, extra, unit) = match marker {
+        Marker::FixArray(len) => return 1 + usize::from(len),
+        Marker::FixMap(len) => return 1 + 2 * usize::from(len),
+        Marker::FixStr(len) => return 1 + usize::from(len),
+        Marker::U8 | Marker::I8 => return 2,
+        Marker::U16 | Marker::I16 | Marker::FixExt1 => return 3,
+        Marker::FixExt2 => return 4,
+        Marker::U32 | Marker::I32 | Marker::F32 => return 5,
+        Marker::FixExt4 => return 6,
+        Marker::U64 | Marker::I64 | Marker::F64 => return 9,
+        Marker::FixExt8 => return 10,
+        Marker::FixExt16 => return 18,
+        Marker::Str8 | Marker::Bin8 => (1, 0, 1),
+        Marker::Str16 | Marker::Bin16 | Marker::Array16 => (2, 0, 1),
+        Marker::Str32 | Marker::Bin32 | Marker::Array32 => (4, 0, 1),
+        Marker::Map16 => (2, 0, 2),
+        Marker::Map32 => (4, 0, 2),
+        Marker::Ext8 => (1, 1, 1),
+        Marker::Ext16 => (2, 1, 1),
+        Marker::Ext32 => (4, 1, 1),
+        _ => return 1,
+    };
+
+    let header = 1 + size + extra;
+    match buf.get(1..=size) {
+        Some(bytes) => {
+            let len = bytes.iter().fold(0usize, |len, &byte| len << 8 | usize::from(byte));
+            header.saturating_add(len.saturating_mul(unit))
+        }
+        None => header,
+    }
+}
@@ -0,0 +1,230 @@
+//! Pre-encoded MessagePack values that are passed through serde untouched.
+
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use core::fmt::{self, Formatter};
+
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+use rmp::ValidationError;
+
+use crate::decode::Error;
+use crate::io;
+
+/// Name of the newtype struct that makes the serializer and deserializer handle raw values.
+// `rmpv::ext::DeepValue` relies on this name as well.
+pub(crate) const RAW_VALUE_STRUCT_NAME: &str = "_RawValue";
+
+/// Checks that `buf` holds exactly one complete MessagePack value.
+fn check(buf: &[u8]) -> Result<(), Error> {
+    match rmp::validate(buf) {
+        Ok(len) if len == buf.len() => Ok(()),
+        Ok(len) => Err(Error::TrailingBytes((buf.len() - len) as u64)),
+        Err(ValidationError::Truncated) => Err(Error::InvalidDataRead(io::ErrorKind::UnexpectedEof.into())),
+        Err(err) => Err(Error::Syntax(err.to_string())),
+    }
+}
+
+/// An owned, already encoded MessagePack value.
+///
+/// When serialized with this crate's `Serializer` the bytes are written verbatim, and when
+/// deserialized with this crate's `Deserializer` the exact encoding of the next value is
+/// captured, without decoding it into anything. This allows embedding or forwarding parts of a
+/// message without paying for a round trip through a typed value.
+///
+/// Other serde formats see the encoded bytes as a newtype struct of bytes.
+///
+/// # Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use rmp_serde::RawValue;
+///
+/// #[derive(Debug, PartialEq, Deserialize, Serialize)]
+/// struct Envelope {
+///     id: u32,
+///     payload: RawValue,
+/// }
+///
+/// // Encoded `[1, [true, "hi"]]`.
+/// let buf = [0x92, 0x01, 0x92, 0xc3, 0xa2, 0x68, 0x69];
+///
+/// let envelope: Envelope = rmp_serde::from_slice(&buf).unwrap();
+/// assert_eq!(&[0x92, 0xc3, 0xa2, 0x68, 0x69], envelope.payload.as_bytes());
+/// assert_eq!(&buf[..], &rmp_serde::to_vec(&envelope).unwrap()[..]);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RawValue(Vec<u8>);
+
+impl RawValue {
+    /// Wraps the given encoded value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `buf` isn't exactly one well-formed MessagePack value.
+    pub fn from_vec(buf: Vec<u8>) -> Result<Self, Error> {
+        check(&buf)?;
+        Ok(Self(buf))
+    }
+
+    /// Encodes the given value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value can't be serialized.
+    pub fn from_value<T: ?Sized + Serialize>(val: &T) -> Result<Self, crate::encode::Error> {
+        crate::to_vec(val).map(Self)
+    }
+
+    /// Returns the encoded value.
+    #[inline]
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Returns the encoded value, consuming the wrapper.
+    #[inline]
+    #[must_use]
+    pub fn into_vec(self) -> Vec<u8> {
+        self.0
+    }
+
+    /// Borrows this value as a [`RawValueRef`].
+    #[inline]
+    #[must_use]
+    pub fn as_raw_ref(&self) -> RawValueRef<'_> {
+        RawValueRef(&self.0)
+    }
+}
+
+impl<'a> From<RawValueRef<'a>> for RawValue {
+    #[inline]
+    fn from(val: RawValueRef<'a>) -> Self {
+        Self(val.0.to_vec())
+    }
+}
+
+/// A borrowed, already encoded MessagePack value.
+///
+/// This is the zero-copy counterpart of [`RawValue`]: when deserialized from a slice it points
+/// into the input. Deserializing it from a reader fails, because the bytes can't be borrowed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RawValueRef<'a>(&'a [u8]);
+
+impl<'a> RawValueRef<'a> {
+    /// Wraps the given encoded value.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `buf` isn't exactly one well-formed MessagePack value.
+    pub fn from_slice(buf: &'a [u8]) -> Result<Self, Error> {
+        check(buf)?;
+        Ok(Self(buf))
+    }
+
+    /// Returns the encoded value.
+    #[inline]
+    #[must_use]
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+}
+
+struct RawData<'a>(&'a [u8]);
+
+impl<'a> Serialize for RawData<'a> {
+    #[inline]
+    fn serialize<S>(&self, se: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        se.serialize_bytes(self.0)
+    }
+}
+
+impl Serialize for RawValue {
+    #[inline]
+    fn serialize<S>(&self, se: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        se.serialize_newtype_struct(RAW_VALUE_STRUCT_NAME, &RawData(&self.0))
+    }
+}
+
+impl<'a> Serialize for RawValueRef<'a> {
+    #[inline]
+    fn serialize<S>(&self, se: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        se.serialize_newtype_struct(RAW_VALUE_STRUCT_NAME, &RawData(self.0))
+    }
+}
+
+struct RawValueVisitor;
+
+impl<'de> Visitor<'de> for RawValueVisitor {
+    type Value = RawValue;
+
+    fn expecting(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        fmt.write_str("an encoded MessagePack value")
+    }
+
+    #[inline]
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(RawValue(v.to_vec()))
+    }
+
+    #[inline]
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(RawValue(v))
+    }
+
+    #[inline]
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, de: D) -> Result<Self::Value, D::Error> {
+        de.deserialize_bytes(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for RawValue {
+    #[inline]
+    fn deserialize<D>(de: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        de.deserialize_newtype_struct(RAW_VALUE_STRUCT_NAME, RawValueVisitor)
+    }
+}
+
+struct RawValueRefVisitor;
+
+impl<'de> Visitor<'de> for RawValueRefVisitor {
+    type Value = RawValueRef<'de>;
+
+    fn expecting(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        fmt.write_str("a borrowed encoded MessagePack value")
+    }
+
+    #[inline]
+    fn visit_borrowed_bytes<E: de::Error>(self, v: &'de [u8]) -> Result<Self::Value, E> {
+        Ok(RawValueRef(v))
+    }
+
+    #[inline]
+    fn visit_newtype_struct<D: Deserializer<'de>>(self, de: D) -> Result<Self::Value, D::Error> {
+        de.deserialize_bytes(self)
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for RawValueRef<'a> {
+    #[inline]
+    fn deserialize<D>(de: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        de.deserialize_newtype_struct(RAW_VALUE_STRUCT_NAME, RawValueRefVisitor)
+    }
+}
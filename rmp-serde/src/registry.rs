@@ -0,0 +1,599 @@
+//! Typed decoding of MessagePack ext values, see [`ExtRegistry`].
+
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Formatter};
+
+use serde::de::value::{MapAccessDeserializer, MapDeserializer, SeqDeserializer};
+use serde::de::{DeserializeOwned, IntoDeserializer, Visitor};
+use serde::ser::{
+    SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple, SerializeTupleStruct,
+    SerializeTupleVariant,
+};
+use serde::{forward_to_deserialize_any, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::decode::Error;
+use crate::encode;
+use crate::{Ext, ExtRef};
+
+/// Decodes the data of an ext value into a [`Content`] tree.
+pub(crate) type Convert = dyn Fn(&[u8]) -> Result<Content, Error> + Send + Sync;
+
+/// A mapping from ext type ids to the types their data decodes into.
+///
+/// When a [`Deserializer`](crate::Deserializer) has a registry, an ext value of a registered type
+/// is decoded as that type in place, so `deserialize_any` and target types that don't know about
+/// exts see the decoded value instead of the `(i8, bytes)` pair. Ext values of other types are
+/// handed over like without a registry, and can be captured as [`UnknownExt`].
+///
+/// # Examples
+///
+/// ```
+/// use rmp_serde::registry::{ExtRegistry, UnknownExt};
+/// use rmp_serde::{Deserializer, Ext};
+/// use serde::Deserialize;
+///
+/// #[derive(Debug, PartialEq, Deserialize)]
+/// #[serde(untagged)]
+/// enum Field {
+///     Id([u8; 4]),
+///     Other(UnknownExt),
+/// }
+///
+/// let buf = rmp_serde::to_vec(&(Ext(2, vec![1, 2, 3, 4]), Ext(9, vec![0xff]))).unwrap();
+///
+/// let mut registry = ExtRegistry::new();
+/// registry.register::<[u8; 4]>(2);
+///
+/// let mut de = Deserializer::from_read_ref(&buf).with_ext_registry(registry);
+/// let fields: (Field, Field) = Deserialize::deserialize(&mut de).unwrap();
+/// assert_eq!((Field::Id([1, 2, 3, 4]), Field::Other(UnknownExt { ty: 9, data: vec![0xff] })), fields);
+/// ```
+#[derive(Clone, Default)]
+pub struct ExtRegistry {
+    converters: BTreeMap<i8, Arc<Convert>>,
+}
+
+impl ExtRegistry {
+    /// Creates an empty registry.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `T` as the type of ext values with the given type id, replacing any previous
+    /// registration.
+    ///
+    /// The ext data is given to `T` as bytes, like with `deserialize_bytes`. Types that
+    /// deserialize from sequences, such as `[u8; N]`, get the data as a sequence of `u8`.
+    ///
+    /// The decoded value is handed to the visitor of the target type by walking its `Serialize`
+    /// implementation, which is why `T` must implement it. Structs are given as sequences of
+    /// their fields, `Some(v)` as `v` and enum variants as their names or as single-entry maps,
+    /// like the [`Serializer`](crate::Serializer) writes them by default.
+    pub fn register<T>(&mut self, ty: i8) -> &mut Self
+    where
+        T: DeserializeOwned + Serialize,
+    {
+        self.register_fn(ty, |data| T::deserialize(DataDeserializer(data)))
+    }
+
+    /// Registers a function decoding the data of ext values with the given type id, replacing
+    /// any previous registration.
+    pub fn register_fn<T, F>(&mut self, ty: i8, decode: F) -> &mut Self
+    where
+        T: Serialize,
+        F: Fn(&[u8]) -> Result<T, Error> + Send + Sync + 'static,
+    {
+        let convert = move |data: &[u8]| {
+            decode(data)?.serialize(ContentSerializer).map_err(|err| Error::Uncategorized(err.to_string()))
+        };
+        self.converters.insert(ty, Arc::new(convert));
+        self
+    }
+
+    /// Returns `true` if the given ext type id is registered.
+    #[inline]
+    #[must_use]
+    pub fn contains(&self, ty: i8) -> bool {
+        self.converters.contains_key(&ty)
+    }
+
+    #[inline]
+    pub(crate) fn get(&self, ty: i8) -> Option<Arc<Convert>> {
+        self.converters.get(&ty).cloned()
+    }
+}
+
+impl Debug for ExtRegistry {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        fmt.debug_set().entries(self.converters.keys()).finish()
+    }
+}
+
+/// An ext value whose type id isn't registered in an [`ExtRegistry`].
+///
+/// This works like [`Ext`], with named fields, and is meant as the fallback of untagged enums
+/// listing the registered types.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct UnknownExt {
+    /// The ext type id.
+    pub ty: i8,
+    /// The ext data.
+    pub data: Vec<u8>,
+}
+
+impl Serialize for UnknownExt {
+    #[inline]
+    fn serialize<S: Serializer>(&self, se: S) -> Result<S::Ok, S::Error> {
+        ExtRef(self.ty, &self.data).serialize(se)
+    }
+}
+
+impl<'de> Deserialize<'de> for UnknownExt {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        let Ext(ty, data) = Ext::deserialize(de)?;
+        Ok(UnknownExt { ty, data })
+    }
+}
+
+/// Gives ext data to the registered type.
+struct DataDeserializer<'a>(&'a [u8]);
+
+impl<'de> Deserializer<'de> for DataDeserializer<'_> {
+    type Error = Error;
+
+    #[inline]
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_bytes(self.0)
+    }
+
+    #[inline]
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_seq(SeqDeserializer::new(self.0.iter().copied()))
+    }
+
+    #[inline]
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    #[inline]
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct tuple_struct map struct enum
+        identifier ignored_any
+    }
+}
+
+/// A decoded ext value, which any visitor can be given without encoding and decoding it again.
+#[derive(Debug)]
+pub(crate) enum Content {
+    Bool(bool),
+    U64(u64),
+    I64(i64),
+    U128(u128),
+    I128(i128),
+    F32(f32),
+    F64(f64),
+    String(String),
+    Bytes(Vec<u8>),
+    None,
+    Unit,
+    Seq(Vec<Content>),
+    Map(Vec<(Content, Content)>),
+}
+
+impl<'de> Deserializer<'de> for Content {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self {
+            Content::Bool(v) => visitor.visit_bool(v),
+            Content::U64(v) => visitor.visit_u64(v),
+            Content::I64(v) => visitor.visit_i64(v),
+            Content::U128(v) => visitor.visit_u128(v),
+            Content::I128(v) => visitor.visit_i128(v),
+            Content::F32(v) => visitor.visit_f32(v),
+            Content::F64(v) => visitor.visit_f64(v),
+            Content::String(v) => visitor.visit_string(v),
+            Content::Bytes(v) => visitor.visit_byte_buf(v),
+            Content::None => visitor.visit_none(),
+            Content::Unit => visitor.visit_unit(),
+            Content::Seq(v) => SeqDeserializer::new(v.into_iter()).deserialize_any(visitor),
+            Content::Map(v) => MapDeserializer::new(v.into_iter()).deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self {
+            Content::None | Content::Unit => visitor.visit_none(),
+            v => visitor.visit_some(v),
+        }
+    }
+
+    #[inline]
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self {
+            Content::String(variant) => visitor.visit_enum(variant.into_deserializer()),
+            Content::Map(entries) => visitor.visit_enum(MapAccessDeserializer::new(MapDeserializer::new(entries.into_iter()))),
+            v => v.deserialize_any(visitor),
+        }
+    }
+
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+impl IntoDeserializer<'_, Error> for Content {
+    type Deserializer = Self;
+
+    #[inline]
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// Turns a registered type's value into a [`Content`] tree.
+struct ContentSerializer;
+
+impl Serializer for ContentSerializer {
+    type Ok = Content;
+    type Error = encode::Error;
+
+    type SerializeSeq = SeqBuilder;
+    type SerializeTuple = SeqBuilder;
+    type SerializeTupleStruct = SeqBuilder;
+    type SerializeTupleVariant = SeqBuilder;
+    type SerializeMap = MapBuilder;
+    type SerializeStruct = SeqBuilder;
+    type SerializeStructVariant = SeqBuilder;
+
+    #[inline]
+    fn serialize_bool(self, v: bool) -> Result<Content, Self::Error> {
+        Ok(Content::Bool(v))
+    }
+
+    #[inline]
+    fn serialize_i8(self, v: i8) -> Result<Content, Self::Error> {
+        Ok(Content::I64(v.into()))
+    }
+
+    #[inline]
+    fn serialize_i16(self, v: i16) -> Result<Content, Self::Error> {
+        Ok(Content::I64(v.into()))
+    }
+
+    #[inline]
+    fn serialize_i32(self, v: i32) -> Result<Content, Self::Error> {
+        Ok(Content::I64(v.into()))
+    }
+
+    #[inline]
+    fn serialize_i64(self, v: i64) -> Result<Content, Self::Error> {
+        Ok(Content::I64(v))
+    }
+
+    #[inline]
+    fn serialize_i128(self, v: i128) -> Result<Content, Self::Error> {
+        Ok(Content::I128(v))
+    }
+
+    #[inline]
+    fn serialize_u8(self, v: u8) -> Result<Content, Self::Error> {
+        Ok(Content::U64(v.into()))
+    }
+
+    #[inline]
+    fn serialize_u16(self, v: u16) -> Result<Content, Self::Error> {
+        Ok(Content::U64(v.into()))
+    }
+
+    #[inline]
+    fn serialize_u32(self, v: u32) -> Result<Content, Self::Error> {
+        Ok(Content::U64(v.into()))
+    }
+
+    #[inline]
+    fn serialize_u64(self, v: u64) -> Result<Content, Self::Error> {
+        Ok(Content::U64(v))
+    }
+
+    #[inline]
+    fn serialize_u128(self, v: u128) -> Result<Content, Self::Error> {
+        Ok(Content::U128(v))
+    }
+
+    #[inline]
+    fn serialize_f32(self, v: f32) -> Result<Content, Self::Error> {
+        Ok(Content::F32(v))
+    }
+
+    #[inline]
+    fn serialize_f64(self, v: f64) -> Result<Content, Self::Error> {
+        Ok(Content::F64(v))
+    }
+
+    #[inline]
+    fn serialize_char(self, v: char) -> Result<Content, Self::Error> {
+        Ok(Content::String(v.to_string()))
+    }
+
+    #[inline]
+    fn serialize_str(self, v: &str) -> Result<Content, Self::Error> {
+        Ok(Content::String(v.into()))
+    }
+
+    #[inline]
+    fn serialize_bytes(self, v: &[u8]) -> Result<Content, Self::Error> {
+        Ok(Content::Bytes(v.into()))
+    }
+
+    #[inline]
+    fn serialize_none(self) -> Result<Content, Self::Error> {
+        Ok(Content::None)
+    }
+
+    #[inline]
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Content, Self::Error> {
+        value.serialize(self)
+    }
+
+    #[inline]
+    fn serialize_unit(self) -> Result<Content, Self::Error> {
+        Ok(Content::Unit)
+    }
+
+    #[inline]
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Content, Self::Error> {
+        Ok(Content::Unit)
+    }
+
+    #[inline]
+    fn serialize_unit_variant(self, _name: &'static str, _idx: u32, variant: &'static str) -> Result<Content, Self::Error> {
+        Ok(Content::String(variant.into()))
+    }
+
+    #[inline]
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<Content, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _idx: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Content, Self::Error> {
+        Ok(Content::Map(vec![(Content::String(variant.into()), value.serialize(self)?)]))
+    }
+
+    #[inline]
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqBuilder, Self::Error> {
+        Ok(SeqBuilder::new(None, len.unwrap_or(0)))
+    }
+
+    #[inline]
+    fn serialize_tuple(self, len: usize) -> Result<SeqBuilder, Self::Error> {
+        Ok(SeqBuilder::new(None, len))
+    }
+
+    #[inline]
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<SeqBuilder, Self::Error> {
+        Ok(SeqBuilder::new(None, len))
+    }
+
+    #[inline]
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _idx: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SeqBuilder, Self::Error> {
+        Ok(SeqBuilder::new(Some(variant), len))
+    }
+
+    #[inline]
+    fn serialize_map(self, len: Option<usize>) -> Result<MapBuilder, Self::Error> {
+        Ok(MapBuilder { entries: Vec::with_capacity(len.unwrap_or(0)), key: None })
+    }
+
+    #[inline]
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SeqBuilder, Self::Error> {
+        Ok(SeqBuilder::new(None, len))
+    }
+
+    #[inline]
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _idx: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SeqBuilder, Self::Error> {
+        Ok(SeqBuilder::new(Some(variant), len))
+    }
+
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// Collects the elements of sequences, tuples and structs, and of the variants holding them.
+struct SeqBuilder {
+    variant: Option<&'static str>,
+    items: Vec<Content>,
+}
+
+impl SeqBuilder {
+    #[inline]
+    fn new(variant: Option<&'static str>, len: usize) -> Self {
+        SeqBuilder { variant, items: Vec::with_capacity(len) }
+    }
+
+    #[inline]
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), encode::Error> {
+        self.items.push(value.serialize(ContentSerializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Content {
+        let seq = Content::Seq(self.items);
+        match self.variant {
+            Some(variant) => Content::Map(vec![(Content::String(variant.into()), seq)]),
+            None => seq,
+        }
+    }
+}
+
+impl SerializeSeq for SeqBuilder {
+    type Ok = Content;
+    type Error = encode::Error;
+
+    #[inline]
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.push(value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Content, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl SerializeTuple for SeqBuilder {
+    type Ok = Content;
+    type Error = encode::Error;
+
+    #[inline]
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.push(value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Content, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl SerializeTupleStruct for SeqBuilder {
+    type Ok = Content;
+    type Error = encode::Error;
+
+    #[inline]
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.push(value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Content, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl SerializeTupleVariant for SeqBuilder {
+    type Ok = Content;
+    type Error = encode::Error;
+
+    #[inline]
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        self.push(value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Content, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl SerializeStruct for SeqBuilder {
+    type Ok = Content;
+    type Error = encode::Error;
+
+    #[inline]
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, _key: &'static str, value: &T) -> Result<(), Self::Error> {
+        self.push(value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Content, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+impl SerializeStructVariant for SeqBuilder {
+    type Ok = Content;
+    type Error = encode::Error;
+
+    #[inline]
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, _key: &'static str, value: &T) -> Result<(), Self::Error> {
+        self.push(value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<Content, Self::Error> {
+        Ok(self.finish())
+    }
+}
+
+/// Collects the entries of maps.
+struct MapBuilder {
+    entries: Vec<(Content, Content)>,
+    key: Option<Content>,
+}
+
+impl SerializeMap for MapBuilder {
+    type Ok = Content;
+    type Error = encode::Error;
+
+    #[inline]
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Self::Error> {
+        self.key = Some(key.serialize(ContentSerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Self::Error> {
+        let key = self.key.take().ok_or(encode::Error::InvalidDataModel("map value without a key"))?;
+        self.entries.push((key, value.serialize(ContentSerializer)?));
+        Ok(())
+    }
+
+    #[inline]
+    fn end(self) -> Result<Content, Self::Error> {
+        Ok(Content::Map(self.entries))
+    }
+}
@@ -0,0 +1,409 @@
+//! [MessagePack-RPC](https://github.com/msgpack-rpc/msgpack-rpc/blob/master/spec.md) messages and
+//! a connection exchanging them over tokio's `AsyncRead` and `AsyncWrite`.
+//!
+//! The envelopes are typed as [`Message`], with params, results and errors kept as [`RawValue`]s
+//! until the application decodes them into its own types, usually after dispatching on the method
+//! name. [`Connection`] frames messages with [`RmpCodec`] and manages message ids of outgoing
+//! requests.
+//!
+//! This module is available with the `rpc` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! use rmp_serde::rpc::{Connection, Message};
+//!
+//! let (client, server) = tokio::io::duplex(1024);
+//! let (rd, wr) = tokio::io::split(client);
+//! let mut client = Connection::new(rd, wr);
+//! let (rd, wr) = tokio::io::split(server);
+//! let mut server = Connection::new(rd, wr);
+//!
+//! let serve = async {
+//!     while let Some(msg) = server.next_message().await.unwrap() {
+//!         if let Message::Request(req) = msg {
+//!             assert_eq!("add", req.method);
+//!             let (a, b): (i32, i32) = req.params().unwrap();
+//!             server.respond::<_, ()>(req.id, Ok(&(a + b))).await.unwrap();
+//!             break;
+//!         }
+//!     }
+//! };
+//! let call = client.call::<_, i32>("add", &(1, 2));
+//!
+//! let ((), sum) = tokio::join!(serve, call);
+//! assert_eq!(Ok(3), sum.unwrap().map_err(drop));
+//! # }
+//! ```
+
+use std::collections::VecDeque;
+use std::error;
+use std::fmt::{self, Display, Formatter};
+use std::io;
+
+use ::bytes::BytesMut;
+use serde::de::{self, DeserializeOwned, SeqAccess, Unexpected, Visitor};
+use serde::ser::{self, Serialize};
+use serde::{Deserialize, Deserializer, Serializer};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::codec::RmpCodec;
+use crate::{decode, encode, RawValue};
+
+const REQUEST: u8 = 0;
+const RESPONSE: u8 = 1;
+const NOTIFICATION: u8 = 2;
+
+/// The encoding of nil, which stands for a missing error or result in responses.
+const NIL: &[u8] = &[0xc0];
+
+/// A request, `[0, msgid, method, params]` on the wire.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Request {
+    /// The id of the request, echoed back in its response.
+    pub id: u32,
+    /// The name of the method to call.
+    pub method: String,
+    /// The encoded arguments, an array according to the spec.
+    pub params: RawValue,
+}
+
+impl Request {
+    /// Deserializes the arguments, e.g. as a tuple.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the arguments can't be deserialized as `T`.
+    #[inline]
+    pub fn params<T: DeserializeOwned>(&self) -> Result<T, decode::Error> {
+        crate::from_slice(self.params.as_bytes())
+    }
+}
+
+/// A response, `[1, msgid, error, result]` on the wire.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Response {
+    /// The id of the request this response is for.
+    pub id: u32,
+    /// The encoded result, or the encoded error if it isn't nil.
+    pub result: Result<RawValue, RawValue>,
+}
+
+/// A notification, `[2, method, params]` on the wire. Notifications are never responded to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Notification {
+    /// The name of the method to call.
+    pub method: String,
+    /// The encoded arguments, an array according to the spec.
+    pub params: RawValue,
+}
+
+impl Notification {
+    /// Deserializes the arguments, e.g. as a tuple.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the arguments can't be deserialized as `T`.
+    #[inline]
+    pub fn params<T: DeserializeOwned>(&self) -> Result<T, decode::Error> {
+        crate::from_slice(self.params.as_bytes())
+    }
+}
+
+/// A MessagePack-RPC message.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    /// A request, expecting a response with the same id.
+    Request(Request),
+    /// A response to an earlier request.
+    Response(Response),
+    /// A notification.
+    Notification(Notification),
+}
+
+impl Serialize for Message {
+    fn serialize<S: Serializer>(&self, se: S) -> Result<S::Ok, S::Error> {
+        match *self {
+            Message::Request(ref req) => (REQUEST, req.id, &req.method, &req.params).serialize(se),
+            Message::Response(ref resp) => {
+                let (error, result) = match resp.result {
+                    Ok(ref result) => (None, Some(result)),
+                    Err(ref error) => (Some(error), None),
+                };
+                (RESPONSE, resp.id, error, result).serialize(se)
+            }
+            Message::Notification(ref notif) => (NOTIFICATION, &notif.method, &notif.params).serialize(se),
+        }
+    }
+}
+
+struct MessageVisitor;
+
+impl MessageVisitor {
+    fn element<'de, A, T>(&self, seq: &mut A, idx: usize) -> Result<T, A::Error>
+    where
+        A: SeqAccess<'de>,
+        T: Deserialize<'de>,
+    {
+        seq.next_element()?.ok_or_else(|| de::Error::invalid_length(idx, self))
+    }
+}
+
+impl<'de> Visitor<'de> for MessageVisitor {
+    type Value = Message;
+
+    fn expecting(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        fmt.write_str("a MessagePack-RPC message")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Message, A::Error> {
+        let msg = match self.element::<_, u8>(&mut seq, 0)? {
+            REQUEST => Message::Request(Request {
+                id: self.element(&mut seq, 1)?,
+                method: self.element(&mut seq, 2)?,
+                params: self.element(&mut seq, 3)?,
+            }),
+            RESPONSE => {
+                let id = self.element(&mut seq, 1)?;
+                let error: RawValue = self.element(&mut seq, 2)?;
+                let result: RawValue = self.element(&mut seq, 3)?;
+                let result = if error.as_bytes() == NIL { Ok(result) } else { Err(error) };
+                Message::Response(Response { id, result })
+            }
+            NOTIFICATION => Message::Notification(Notification {
+                method: self.element(&mut seq, 1)?,
+                params: self.element(&mut seq, 2)?,
+            }),
+            ty => return Err(de::Error::invalid_value(Unexpected::Unsigned(u64::from(ty)), &self)),
+        };
+        Ok(msg)
+    }
+}
+
+impl<'de> Deserialize<'de> for Message {
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        de.deserialize_seq(MessageVisitor)
+    }
+}
+
+/// An error that occurred while exchanging messages.
+#[derive(Debug)]
+pub enum Error {
+    /// Failed to encode or write a message.
+    Encode(encode::Error),
+    /// Failed to read or decode a message, or to deserialize a result.
+    Decode(decode::Error),
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Encode(ref err) => Some(err),
+            Error::Decode(ref err) => Some(err),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            Error::Encode(ref err) => write!(fmt, "failed to send message: {err}"),
+            Error::Decode(ref err) => write!(fmt, "failed to receive message: {err}"),
+        }
+    }
+}
+
+impl From<encode::Error> for Error {
+    #[cold]
+    fn from(err: encode::Error) -> Error {
+        Error::Encode(err)
+    }
+}
+
+impl From<decode::Error> for Error {
+    #[cold]
+    fn from(err: decode::Error) -> Error {
+        Error::Decode(err)
+    }
+}
+
+/// A MessagePack-RPC connection over a pair of async byte streams.
+///
+/// Both sides of the protocol are supported: the connection can call methods of the peer with
+/// [`call`](Connection::call) and [`notify`](Connection::notify), and receive its requests with
+/// [`next_message`](Connection::next_message) to answer them with
+/// [`respond`](Connection::respond).
+///
+/// The connection doesn't spawn any tasks. While [`call`](Connection::call) waits for its
+/// response, other incoming messages are queued and later returned by
+/// [`next_message`](Connection::next_message), in the order they arrived.
+#[derive(Debug)]
+pub struct Connection<R, W> {
+    rd: R,
+    wr: W,
+    codec: RmpCodec<Message>,
+    rbuf: BytesMut,
+    wbuf: BytesMut,
+    next_id: u32,
+    pending: VecDeque<Message>,
+}
+
+impl<R, W> Connection<R, W>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    /// Creates a new connection reading messages from `rd` and writing them to `wr`.
+    #[must_use]
+    pub fn new(rd: R, wr: W) -> Self {
+        Self::with_codec(rd, wr, RmpCodec::new())
+    }
+
+    /// Creates a new connection that frames messages with the given codec, e.g. to change the
+    /// maximum message length.
+    #[must_use]
+    pub fn with_codec(rd: R, wr: W, codec: RmpCodec<Message>) -> Self {
+        Connection {
+            rd,
+            wr,
+            codec,
+            rbuf: BytesMut::new(),
+            wbuf: BytesMut::new(),
+            next_id: 0,
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Returns the underlying reader and writer, consuming the connection.
+    ///
+    /// Queued messages and buffered but not yet decoded data are lost.
+    #[inline]
+    pub fn into_inner(self) -> (R, W) {
+        (self.rd, self.wr)
+    }
+
+    /// Calls the given method of the peer and waits for its response.
+    ///
+    /// The params should serialize as an array, e.g. a tuple. On success the result is
+    /// deserialized as `T`, otherwise the encoded error sent by the peer is returned in the inner
+    /// `Err`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request can't be sent, the connection is closed before the
+    /// response arrives, or the result can't be deserialized as `T`.
+    pub async fn call<P, T>(&mut self, method: &str, params: &P) -> Result<Result<T, RawValue>, Error>
+    where
+        P: ?Sized + Serialize,
+        T: DeserializeOwned,
+    {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        self.send(Message::Request(Request {
+            id,
+            method: method.into(),
+            params: RawValue::from_value(params)?,
+        }))
+        .await?;
+
+        loop {
+            match self.receive().await? {
+                Some(Message::Response(resp)) if resp.id == id => {
+                    return match resp.result {
+                        Ok(result) => Ok(Ok(crate::from_slice(result.as_bytes())?)),
+                        Err(error) => Ok(Err(error)),
+                    };
+                }
+                Some(msg) => self.pending.push_back(msg),
+                None => return Err(Error::Decode(unexpected_eof())),
+            }
+        }
+    }
+
+    /// Sends a notification to the peer.
+    ///
+    /// The params should serialize as an array, e.g. a tuple.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the notification can't be sent.
+    pub async fn notify<P: ?Sized + Serialize>(&mut self, method: &str, params: &P) -> Result<(), Error> {
+        self.send(Message::Notification(Notification {
+            method: method.into(),
+            params: RawValue::from_value(params)?,
+        }))
+        .await
+    }
+
+    /// Sends the response to the request with the given id.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the response can't be sent.
+    pub async fn respond<T, E>(&mut self, id: u32, result: Result<&T, &E>) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+        E: ?Sized + Serialize,
+    {
+        let result = match result {
+            Ok(result) => Ok(RawValue::from_value(result)?),
+            Err(error) => Err(RawValue::from_value(error)?),
+        };
+        if let Err(ref error) = result {
+            if error.as_bytes() == NIL {
+                return Err(Error::Encode(ser::Error::custom("the error of a response can't be nil")));
+            }
+        }
+        self.send(Message::Response(Response { id, result })).await
+    }
+
+    /// Returns the next incoming message, or `None` if the peer has closed the connection.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the data can't be read, or isn't a valid message. The connection is
+    /// unusable afterwards, except for messages that failed to deserialize, which are skipped.
+    pub async fn next_message(&mut self) -> Result<Option<Message>, Error> {
+        match self.pending.pop_front() {
+            Some(msg) => Ok(Some(msg)),
+            None => self.receive().await,
+        }
+    }
+
+    /// Sends the given message as is, e.g. to use message ids managed by the application.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the message can't be written.
+    pub async fn send(&mut self, msg: Message) -> Result<(), Error> {
+        self.codec.encode(msg, &mut self.wbuf)?;
+        let res = self.wr.write_all(&self.wbuf).await;
+        self.wbuf.clear();
+        res.map_err(encode::Error::from)?;
+        self.wr.flush().await.map_err(encode::Error::from)?;
+        Ok(())
+    }
+
+    async fn receive(&mut self) -> Result<Option<Message>, Error> {
+        loop {
+            if let Some(msg) = self.codec.decode(&mut self.rbuf)? {
+                return Ok(Some(msg));
+            }
+            if self.rd.read_buf(&mut self.rbuf).await.map_err(decode::Error::from)? == 0 {
+                if self.rbuf.is_empty() {
+                    return Ok(None);
+                }
+                return Err(Error::Decode(unexpected_eof()));
+            }
+        }
+    }
+}
+
+#[cold]
+fn unexpected_eof() -> decode::Error {
+    decode::Error::InvalidDataRead(io::ErrorKind::UnexpectedEof.into())
+}
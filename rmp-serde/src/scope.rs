@@ -0,0 +1,61 @@
+//! Values handed on the side to `Deserialize` implementations running on this thread.
+//!
+//! `Deserialize::deserialize` takes nothing but the deserializer, so the crate passes anything
+//! else a deserialization needs, like the [context](crate::context) of the caller, the string
+//! that was [interned](crate::intern) or the [source buffer](crate::shared_bytes), through a
+//! single thread-local stack. Entries are pushed by a [`Scope`] and popped when it's dropped, so
+//! nested deserializations shadow the entries of outer ones.
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::marker::PhantomData;
+use std::rc::Rc;
+use std::sync::Arc;
+
+#[cfg(feature = "bytes")]
+use ::bytes::Bytes;
+
+/// A value available to the deserializations in progress on this thread.
+pub(crate) enum Entry {
+    /// A context of [`DeserializerWithContext`](crate::context::DeserializerWithContext).
+    Context(Rc<dyn Any>),
+    /// The string just read by a deserializer with an [`Interner`](crate::intern::Interner).
+    Interned(Arc<str>),
+    /// The buffer passed to [`from_bytes`](crate::shared_bytes::from_bytes).
+    #[cfg(feature = "bytes")]
+    Source(Bytes),
+}
+
+thread_local! {
+    /// Entries of the scopes entered on this thread, innermost last.
+    static ENTRIES: RefCell<Vec<Entry>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Makes an entry available while it's alive.
+pub(crate) struct Scope {
+    // Scopes must be dropped on the thread that entered them.
+    _marker: PhantomData<*const ()>,
+}
+
+impl Scope {
+    #[inline]
+    pub(crate) fn enter(entry: Entry) -> Self {
+        ENTRIES.with(|entries| entries.borrow_mut().push(entry));
+        Scope { _marker: PhantomData }
+    }
+}
+
+impl Drop for Scope {
+    #[inline]
+    fn drop(&mut self) {
+        // Drop the entry outside of the borrow, as dropping a context may run arbitrary code.
+        let entry = ENTRIES.with(|entries| entries.borrow_mut().pop());
+        drop(entry);
+    }
+}
+
+/// Returns the first non-`None` result of `f` for the entries on this thread, innermost first.
+#[inline]
+pub(crate) fn find_map<T>(f: impl FnMut(&Entry) -> Option<T>) -> Option<T> {
+    ENTRIES.with(|entries| entries.borrow().iter().rev().find_map(f))
+}
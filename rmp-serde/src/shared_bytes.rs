@@ -0,0 +1,199 @@
+//! Zero-copy deserialization of binary data into [`bytes::Bytes`] handles.
+//!
+//! Deserializing from a slice can borrow binary data only for the lifetime of the slice. When the
+//! input is itself a `Bytes` buffer, [`from_bytes`] instead allows fields of type `Bytes`
+//! annotated with `#[serde(with = "rmp_serde::shared_bytes")]` to share the input buffer, which
+//! only increments a reference count instead of copying the data. [`from_arc`] does the same for
+//! an `Arc<[u8]>` buffer.
+//!
+//! Outside of [`from_bytes`] such fields are deserialized by copying, so the same types can be
+//! used with any deserializer. The handles are `Send`, so values split out of one large buffer
+//! with [`split_values`] can be decoded on many threads without copying.
+//!
+//! This module is available with the `bytes` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use bytes::Bytes;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct Frame {
+//!     id: u32,
+//!     #[serde(with = "rmp_serde::shared_bytes")]
+//!     payload: Bytes,
+//! }
+//!
+//! let frame = Frame { id: 42, payload: Bytes::from_static(b"le message") };
+//! let buf = Bytes::from(rmp_serde::to_vec(&frame).unwrap());
+//!
+//! let decoded: Frame = rmp_serde::shared_bytes::from_bytes(&buf).unwrap();
+//! assert_eq!(frame, decoded);
+//! // The payload points into `buf`.
+//! assert!(buf.as_ptr_range().contains(&decoded.payload.as_ptr()));
+//! ```
+
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+
+use ::bytes::Bytes;
+use serde::de::{self, DeserializeOwned, SeqAccess, Visitor};
+use serde::{Deserializer, Serializer};
+
+use rmp::ValidationError;
+
+use crate::decode::Error;
+use crate::scope::{self, Entry, Scope};
+
+/// Deserialize an instance of type `T` from a `Bytes` buffer.
+///
+/// This behaves like [`from_slice`](crate::from_slice), except that fields deserialized with
+/// this module share the input buffer instead of copying from it.
+///
+/// # Errors
+///
+/// Deserialization can fail if the data is not valid MessagePack or doesn't match `T`.
+pub fn from_bytes<T>(input: &Bytes) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    let _scope = Scope::enter(Entry::Source(input.clone()));
+    crate::from_slice(input)
+}
+
+/// Deserialize an instance of type `T` from a shared `Arc<[u8]>` buffer, e.g. holding a memory
+/// mapped file.
+///
+/// The buffer is wrapped into `Bytes` without copying, so fields deserialized with this module
+/// keep it alive and can be sent to other threads.
+///
+/// # Errors
+///
+/// Deserialization can fail if the data is not valid MessagePack or doesn't match `T`.
+pub fn from_arc<T>(input: &Arc<[u8]>) -> Result<T, Error>
+where
+    T: DeserializeOwned,
+{
+    from_bytes(&Bytes::from_owner(input.clone()))
+}
+
+/// Splits a buffer of MessagePack values written back to back into one handle per value,
+/// without copying.
+///
+/// The values are only checked to be well-formed, not decoded, so they can be decoded in
+/// parallel afterwards, each with [`from_bytes`] on its own thread.
+///
+/// # Errors
+///
+/// Fails if the buffer contains malformed data or ends with an incomplete value. The error
+/// carries the offset of the value at fault.
+///
+/// # Examples
+///
+/// ```
+/// use bytes::Bytes;
+///
+/// let buf = Bytes::from(rmp_serde::to_vec_all(["a", "bc", "def"]).unwrap());
+///
+/// let handles: Vec<_> = rmp_serde::shared_bytes::split_values(&buf).unwrap()
+///     .into_iter()
+///     .map(|val| std::thread::spawn(move || rmp_serde::shared_bytes::from_bytes::<String>(&val).unwrap()))
+///     .collect();
+/// let vals: Vec<String> = handles.into_iter().map(|handle| handle.join().unwrap()).collect();
+/// assert_eq!(vec!["a", "bc", "def"], vals);
+/// ```
+pub fn split_values(input: &Bytes) -> Result<Vec<Bytes>, Error> {
+    let mut vals = Vec::new();
+    let mut pos = 0;
+    while pos < input.len() {
+        let len = match rmp::validate(&input[pos..]) {
+            Ok(len) => len,
+            Err(ValidationError::Truncated) => {
+                return Err(Error::InvalidDataRead(io::ErrorKind::UnexpectedEof.into()).at(pos as u64));
+            }
+            Err(err) => return Err(Error::Syntax(err.to_string()).at(pos as u64)),
+        };
+        vals.push(input.slice(pos..pos + len));
+        pos += len;
+    }
+    Ok(vals)
+}
+
+/// Serializes `Bytes` as MessagePack binary.
+pub fn serialize<S>(val: &Bytes, se: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    se.serialize_bytes(val)
+}
+
+/// Deserializes `Bytes` from MessagePack binary, sharing the buffer passed to [`from_bytes`]
+/// whenever possible.
+pub fn deserialize<'de, D>(de: D) -> Result<Bytes, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    de.deserialize_bytes(SharedBytesVisitor)
+}
+
+fn share_or_copy(v: &[u8]) -> Bytes {
+    let source = scope::find_map(|entry| match entry {
+        Entry::Source(source) => Some(source.clone()),
+        _ => None,
+    });
+    match source {
+        Some(source) if contains(&source, v) => source.slice_ref(v),
+        _ => Bytes::copy_from_slice(v),
+    }
+}
+
+fn contains(source: &[u8], v: &[u8]) -> bool {
+    let outer = source.as_ptr_range();
+    let inner = v.as_ptr_range();
+    outer.start <= inner.start && inner.end <= outer.end
+}
+
+struct SharedBytesVisitor;
+
+impl<'de> Visitor<'de> for SharedBytesVisitor {
+    type Value = Bytes;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("a byte array")
+    }
+
+    #[inline]
+    fn visit_borrowed_bytes<E: de::Error>(self, v: &'de [u8]) -> Result<Self::Value, E> {
+        Ok(share_or_copy(v))
+    }
+
+    #[inline]
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(Bytes::copy_from_slice(v))
+    }
+
+    #[inline]
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(Bytes::from(v))
+    }
+
+    #[inline]
+    fn visit_borrowed_str<E: de::Error>(self, v: &'de str) -> Result<Self::Value, E> {
+        Ok(share_or_copy(v.as_bytes()))
+    }
+
+    #[inline]
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(Bytes::copy_from_slice(v.as_bytes()))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut vec = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(byte) = seq.next_element()? {
+            vec.push(byte);
+        }
+        Ok(Bytes::from(vec))
+    }
+}
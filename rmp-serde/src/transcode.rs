@@ -0,0 +1,294 @@
+//! Transcode MessagePack into any other serde format and back, without building an
+//! intermediate value tree.
+//!
+//! Values are streamed from a `Deserializer` straight into a `Serializer`. When the target
+//! serializer is human-readable (for example JSON), values that have no natural representation
+//! there are converted:
+//!
+//! - binary data is written as a base64 string (standard alphabet, with padding);
+//! - ext values are written as a map `{"type": <i8>, "data": <base64 string>}`.
+//!
+//! For binary targets both are passed through unchanged.
+
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::fmt::{self, Display, Formatter};
+
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{self, Serialize, SerializeMap, SerializeSeq, Serializer};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::{encode, Ext};
+
+/// Transcodes a single value from the given deserializer into the given serializer.
+///
+/// Newtype structs produced by the deserializer are assumed to be MessagePack ext values, since
+/// that's the only case this crate's `Deserializer` produces them.
+///
+/// # Errors
+///
+/// Returns an error if either reading or writing a value fails. Errors coming from the
+/// deserializer are converted into serializer errors using their messages.
+///
+/// # Examples
+///
+/// ```
+/// // Encoded `{"id": 42, "blob": b"\x00\x01"}`.
+/// let buf = [0x82, 0xa2, 0x69, 0x64, 0x2a, 0xa4, 0x62, 0x6c, 0x6f, 0x62, 0xc4, 0x02, 0x00, 0x01];
+///
+/// let mut de = rmp_serde::Deserializer::from_read_ref(&buf);
+/// let mut se = rmp_serde::Serializer::new(Vec::new());
+/// rmp_serde::transcode::transcode(&mut de, &mut se).unwrap();
+///
+/// assert_eq!(&buf[..], &se.into_inner()[..]);
+/// ```
+#[inline]
+pub fn transcode<'de, D, S>(de: D, se: S) -> Result<S::Ok, S::Error>
+where
+    D: Deserializer<'de>,
+    S: Serializer,
+{
+    Transcoder::new(de).serialize(se)
+}
+
+/// Wrapper that implements `Serialize` by transcoding the value read from the inner deserializer.
+///
+/// It can be serialized only once, since the deserializer is consumed in the process.
+pub struct Transcoder<D>(RefCell<Option<D>>);
+
+impl<D> fmt::Debug for Transcoder<D> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Transcoder").finish_non_exhaustive()
+    }
+}
+
+impl<'de, D: Deserializer<'de>> Transcoder<D> {
+    /// Constructs a new `Transcoder` from the given deserializer.
+    #[inline]
+    pub fn new(de: D) -> Self {
+        Transcoder(RefCell::new(Some(de)))
+    }
+}
+
+impl<'de, D: Deserializer<'de>> Serialize for Transcoder<D> {
+    fn serialize<S>(&self, se: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.0.borrow_mut().take() {
+            Some(de) => de.deserialize_any(TranscodeVisitor(se)).map_err(ser::Error::custom),
+            None => Err(ser::Error::custom("`Transcoder` can only be serialized once")),
+        }
+    }
+}
+
+/// How structs are laid out when re-encoding with [`reencode`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StructLayout {
+    /// Structs are arrays of their field values, as written by [`to_vec`](crate::to_vec).
+    Array,
+    /// Structs are maps from field names to values, as written by
+    /// [`to_vec_named`](crate::to_vec_named).
+    Map,
+}
+
+/// Re-encodes a value of type `T` with structs laid out as given, e.g. to migrate stored data
+/// from [`to_vec`](crate::to_vec) to [`to_vec_named`](crate::to_vec_named).
+///
+/// Since derived `Deserialize` implementations accept structs both as arrays and as maps, the
+/// input may use either layout, or a mix of both. Values of other types are re-encoded
+/// unchanged, except that integers and floats are written in their most compact form.
+///
+/// # Errors
+///
+/// Returns [`Error::Decode`](encode::Error::Decode) if `input` isn't exactly one value of type
+/// `T`.
+///
+/// # Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use rmp_serde::transcode::StructLayout;
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Point {
+///     x: u8,
+///     y: u8,
+/// }
+///
+/// let buf = rmp_serde::to_vec(&Point { x: 1, y: 2 }).unwrap();
+/// let named = rmp_serde::reencode::<Point>(&buf, StructLayout::Map).unwrap();
+/// assert_eq!(rmp_serde::to_vec_named(&Point { x: 1, y: 2 }).unwrap(), named);
+/// assert_eq!(buf, rmp_serde::reencode::<Point>(&named, StructLayout::Array).unwrap());
+/// ```
+pub fn reencode<T>(input: &[u8], layout: StructLayout) -> Result<Vec<u8>, encode::Error>
+where
+    T: Serialize + DeserializeOwned,
+{
+    let val: T = crate::from_slice_exact(input).map_err(encode::Error::Decode)?;
+    match layout {
+        StructLayout::Array => crate::to_vec(&val),
+        StructLayout::Map => crate::to_vec_named(&val),
+    }
+}
+
+struct TranscodeVisitor<S>(S);
+
+impl<'de, S: Serializer> Visitor<'de> for TranscodeVisitor<S> {
+    type Value = S::Ok;
+
+    #[cold]
+    fn expecting(&self, fmt: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        "any value".fmt(fmt)
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Self::Value, E> {
+        self.0.serialize_bool(v).map_err(E::custom)
+    }
+
+    fn visit_i8<E: de::Error>(self, v: i8) -> Result<Self::Value, E> {
+        self.0.serialize_i8(v).map_err(E::custom)
+    }
+
+    fn visit_i16<E: de::Error>(self, v: i16) -> Result<Self::Value, E> {
+        self.0.serialize_i16(v).map_err(E::custom)
+    }
+
+    fn visit_i32<E: de::Error>(self, v: i32) -> Result<Self::Value, E> {
+        self.0.serialize_i32(v).map_err(E::custom)
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        self.0.serialize_i64(v).map_err(E::custom)
+    }
+
+    fn visit_u8<E: de::Error>(self, v: u8) -> Result<Self::Value, E> {
+        self.0.serialize_u8(v).map_err(E::custom)
+    }
+
+    fn visit_u16<E: de::Error>(self, v: u16) -> Result<Self::Value, E> {
+        self.0.serialize_u16(v).map_err(E::custom)
+    }
+
+    fn visit_u32<E: de::Error>(self, v: u32) -> Result<Self::Value, E> {
+        self.0.serialize_u32(v).map_err(E::custom)
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        self.0.serialize_u64(v).map_err(E::custom)
+    }
+
+    fn visit_f32<E: de::Error>(self, v: f32) -> Result<Self::Value, E> {
+        self.0.serialize_f32(v).map_err(E::custom)
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+        self.0.serialize_f64(v).map_err(E::custom)
+    }
+
+    fn visit_char<E: de::Error>(self, v: char) -> Result<Self::Value, E> {
+        self.0.serialize_char(v).map_err(E::custom)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        self.0.serialize_str(v).map_err(E::custom)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        if self.0.is_human_readable() {
+            self.0.serialize_str(&rmp::base64::encode(v)).map_err(E::custom)
+        } else {
+            self.0.serialize_bytes(v).map_err(E::custom)
+        }
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        self.0.serialize_unit().map_err(E::custom)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        self.0.serialize_none().map_err(E::custom)
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.0.serialize_some(&Transcoder::new(deserializer)).map_err(de::Error::custom)
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let Ext(tag, data) = Ext::deserialize(deserializer)?;
+        if self.0.is_human_readable() {
+            let mut map = self.0.serialize_map(Some(2)).map_err(de::Error::custom)?;
+            map.serialize_entry("type", &tag).map_err(de::Error::custom)?;
+            map.serialize_entry("data", &rmp::base64::encode(&data)).map_err(de::Error::custom)?;
+            map.end().map_err(de::Error::custom)
+        } else {
+            Ext(tag, data).serialize(self.0).map_err(de::Error::custom)
+        }
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut se = self.0.serialize_seq(seq.size_hint()).map_err(de::Error::custom)?;
+        while let Some(()) = seq.next_element_seed(SeqSeed(&mut se))? {}
+        se.end().map_err(de::Error::custom)
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut se = self.0.serialize_map(map.size_hint()).map_err(de::Error::custom)?;
+        while let Some(()) = map.next_key_seed(KeySeed(&mut se))? {
+            map.next_value_seed(ValueSeed(&mut se))?;
+        }
+        se.end().map_err(de::Error::custom)
+    }
+}
+
+struct SeqSeed<'a, S>(&'a mut S);
+
+impl<'de, 'a, S: SerializeSeq> DeserializeSeed<'de> for SeqSeed<'a, S> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.0.serialize_element(&Transcoder::new(deserializer)).map_err(de::Error::custom)
+    }
+}
+
+struct KeySeed<'a, S>(&'a mut S);
+
+impl<'de, 'a, S: SerializeMap> DeserializeSeed<'de> for KeySeed<'a, S> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.0.serialize_key(&Transcoder::new(deserializer)).map_err(de::Error::custom)
+    }
+}
+
+struct ValueSeed<'a, S>(&'a mut S);
+
+impl<'de, 'a, S: SerializeMap> DeserializeSeed<'de> for ValueSeed<'a, S> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.0.serialize_value(&Transcoder::new(deserializer)).map_err(de::Error::custom)
+    }
+}
@@ -0,0 +1,72 @@
+//! Moving MessagePack across the JavaScript boundary, available with the `wasm` feature.
+//!
+//! Browser code usually hands MessagePack over as a `Uint8Array`. The helpers here copy it into
+//! and out of WebAssembly memory once, and otherwise behave like [`to_vec`](crate::to_vec) and
+//! [`from_slice`](crate::from_slice). Untyped payloads can be decoded into `rmpv::Value`, which
+//! implements `Deserialize` with its `with-serde` feature.
+//!
+//! The serialization and deserialization paths of this crate only need `std::io::Read` and
+//! `Write` implemented for slices and vectors, so they work on `wasm32-unknown-unknown` without
+//! any patches; the `wasm` feature only adds the `js-sys` dependency.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use js_sys::Uint8Array;
+//!
+//! let arr: Uint8Array = rmp_serde::wasm::to_uint8array(&(42, "the Answer")).unwrap();
+//! let val: (u32, String) = rmp_serde::wasm::from_uint8array(&arr).unwrap();
+//! assert_eq!((42, "the Answer".to_owned()), val);
+//! ```
+
+use js_sys::Uint8Array;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::{decode, encode};
+
+/// Serializes the given data structure into a new `Uint8Array`, structs as arrays.
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to fail.
+#[inline]
+pub fn to_uint8array<T>(val: &T) -> Result<Uint8Array, encode::Error>
+where
+    T: Serialize + ?Sized,
+{
+    let buf = encode::to_vec(val)?;
+    Ok(Uint8Array::from(&buf[..]))
+}
+
+/// Serializes the given data structure into a new `Uint8Array`, structs as maps with field names.
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to fail.
+#[inline]
+pub fn to_uint8array_named<T>(val: &T) -> Result<Uint8Array, encode::Error>
+where
+    T: Serialize + ?Sized,
+{
+    let buf = encode::to_vec_named(val)?;
+    Ok(Uint8Array::from(&buf[..]))
+}
+
+/// Deserializes an instance of type `T` from the contents of a `Uint8Array`.
+///
+/// The contents are copied out of JavaScript memory first, so `T` can't borrow from them.
+///
+/// # Errors
+///
+/// This conversion can fail if the structure of the input does not match the structure expected
+/// by `T`. It can also fail if the structure is correct but `T`'s implementation of `Deserialize`
+/// decides that something is wrong with the data.
+#[inline]
+pub fn from_uint8array<T>(arr: &Uint8Array) -> Result<T, decode::Error>
+where
+    T: DeserializeOwned,
+{
+    let buf = arr.to_vec();
+    decode::from_slice(&buf)
+}
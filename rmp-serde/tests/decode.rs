@@ -533,7 +533,7 @@ fn fail_str_invalid_utf8() {
     let err: Result<String, decode::Error> = rmp_serde::from_slice(&buf[..]);
 
     assert!(err.is_err());
-    match err.err().unwrap() {
+    match err.unwrap_err().into_inner() {
         decode::Error::Utf8Error(err) => assert_eq!(0, err.valid_up_to()),
         // decode::Error::Syntax(err) => {}
         err => panic!("unexpected error: {:?}", err),
@@ -567,3 +567,893 @@ fn fail_depth_limit() {
         other => panic!("unexpected result: {other:?}"),
     }
 }
+
+#[test]
+fn fail_depth_limit_with_max_depth() {
+    // Encoded `[[[[]]]]`.
+    let buf = [0x91, 0x91, 0x91, 0x90];
+
+    let mut de = Deserializer::new(&buf[..]).with_max_depth(5);
+    let res: Vec<Vec<Vec<Vec<u8>>>> = Deserialize::deserialize(&mut de).unwrap();
+    assert_eq!(vec![vec![vec![Vec::<u8>::new()]]], res);
+
+    let mut de = Deserializer::new(&buf[..]).with_max_depth(4);
+    let res: Result<Vec<Vec<Vec<Vec<u8>>>>, Error> = Deserialize::deserialize(&mut de);
+    match res.err().unwrap() {
+        decode::Error::DepthLimitExceeded => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn fail_depth_limit_nested_enum() {
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    enum Nested {
+        Leaf,
+        Node(Box<Nested>),
+    }
+
+    // Encoded `{"Node": {"Node": ... "Leaf"}}`.
+    let mut data = Vec::new();
+    for _ in 0..100 {
+        data.extend_from_slice(&[0x81, 0xa4, b'N', b'o', b'd', b'e']);
+    }
+    data.extend_from_slice(&[0xa4, b'L', b'e', b'a', b'f']);
+
+    let mut de = Deserializer::new(&data[..]).with_max_depth(200);
+    Nested::deserialize(&mut de).unwrap();
+
+    let mut de = Deserializer::new(&data[..]).with_max_depth(50);
+    match Nested::deserialize(&mut de).err().unwrap() {
+        decode::Error::DepthLimitExceeded => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn pass_stream_deserializer() {
+    // Encoded `1`, `128`, `65536`.
+    let buf = [0x01, 0xcc, 0x80, 0xce, 0x00, 0x01, 0x00, 0x00];
+
+    let mut stream = decode::StreamDeserializer::<_, u32>::new(&buf[..]);
+    assert_eq!(0, stream.byte_offset());
+    assert_eq!(1, stream.next().unwrap().unwrap());
+    assert_eq!(1, stream.byte_offset());
+    assert_eq!(128, stream.next().unwrap().unwrap());
+    assert_eq!(3, stream.byte_offset());
+    assert_eq!(65536, stream.next().unwrap().unwrap());
+    assert_eq!(8, stream.byte_offset());
+    assert!(stream.next().is_none());
+}
+
+#[test]
+fn fail_stream_deserializer_truncated() {
+    // Encoded `"a"` followed by a truncated `"two"`.
+    let buf = [0xa1, 0x61, 0xa3, 0x74];
+
+    let mut stream = decode::StreamDeserializer::<_, String>::new(&buf[..]);
+    assert_eq!("a", stream.next().unwrap().unwrap());
+    assert_eq!(2, stream.byte_offset());
+    assert!(stream.next().unwrap().is_err());
+    assert_eq!(2, stream.byte_offset());
+    assert!(stream.next().is_none());
+}
+
+#[test]
+fn pass_from_slice_all() {
+    let buf = rmp_serde::to_vec_all(vec![1u32, 128, 65536]).unwrap();
+    assert_eq!(vec![0x01, 0xcc, 0x80, 0xce, 0x00, 0x01, 0x00, 0x00], buf);
+    assert_eq!(vec![1, 128, 65536], rmp_serde::from_slice_all::<u32>(&buf).unwrap());
+    assert!(rmp_serde::from_slice_all::<u32>(&[]).unwrap().is_empty());
+
+    // Encoded `"a"` followed by a truncated `"two"`.
+    let buf = [0xa1, 0x61, 0xa3, 0x74];
+    let err = rmp_serde::from_slice_all::<&str>(&buf).unwrap_err();
+    assert_eq!(Some(2), err.offset());
+    assert_eq!(rmp_serde::ErrorKind::UnexpectedEof, err.kind());
+}
+
+#[test]
+fn pass_from_slice_borrows_str_and_bytes() {
+    // Encoded `["le", b"\x01\x02", b"\x03\x04\x05"]`.
+    let buf = [0x93, 0xa2, 0x6c, 0x65, 0xc4, 0x02, 0x01, 0x02, 0xc4, 0x03, 0x03, 0x04, 0x05];
+
+    let (s, b, a): (&str, &[u8], [u8; 3]) = rmp_serde::from_slice(&buf).unwrap();
+
+    assert_eq!("le", s);
+    assert_eq!(&[0x01, 0x02], b);
+    assert_eq!([0x03, 0x04, 0x05], a);
+
+    // Borrowed values must point into the input instead of a copy.
+    assert!(std::ptr::eq(&buf[2], s.as_ptr()));
+    assert!(std::ptr::eq(&buf[6], b.as_ptr()));
+}
+
+#[test]
+fn pass_ext_mode() {
+    use rmp_serde::config::ExtMode;
+    use serde_bytes::ByteBuf;
+
+    // Encoded `[1, <ext 5: [0xaa, 0xbb]>]`.
+    let buf = [0x92, 0x01, 0xd5, 0x05, 0xaa, 0xbb];
+
+    let mut de = Deserializer::new(&buf[..]).with_ext_mode(ExtMode::Tuple);
+    let val: (u8, (i8, ByteBuf)) = Deserialize::deserialize(&mut de).unwrap();
+    assert_eq!((1, (5, ByteBuf::from(vec![0xaa, 0xbb]))), val);
+
+    let mut de = Deserializer::new(&buf[..]).with_ext_mode(ExtMode::Skip);
+    let val: (u8, ()) = Deserialize::deserialize(&mut de).unwrap();
+    assert_eq!((1, ()), val);
+    assert!(de.get_ref().is_empty());
+
+    let mut de = Deserializer::new(&buf[..]).with_ext_mode(ExtMode::Error);
+    let res: Result<(u8, ()), Error> = Deserialize::deserialize(&mut de);
+    match res.err().unwrap() {
+        Error::TypeMismatch(Marker::FixExt2) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn pass_deserializer_position() {
+    // Encoded `[1, "two"]`.
+    let buf = [0x92, 0x01, 0xa3, 0x74, 0x77, 0x6f];
+
+    let mut de = Deserializer::new(&buf[..]);
+    assert_eq!(0, de.position());
+    let _: (u8, String) = Deserialize::deserialize(&mut de).unwrap();
+    assert_eq!(buf.len() as u64, de.position());
+
+    let mut de = Deserializer::from_read_ref(&buf);
+    let _: (u8, &str) = Deserialize::deserialize(&mut de).unwrap();
+    assert_eq!(buf.len() as u64, de.position());
+}
+
+#[test]
+fn fail_error_at_offset() {
+    // Encoded `[1, "two"]`.
+    let buf = [0x92, 0x01, 0xa3, 0x74, 0x77, 0x6f];
+
+    let mut de = Deserializer::new(&buf[..]);
+    let res: Result<(u8, u8), Error> = Deserialize::deserialize(&mut de);
+    let err = res.map_err(|err| err.at(de.marker_position())).unwrap_err();
+
+    // The string marker is at offset 2.
+    assert_eq!(Some(2), err.offset());
+    assert_eq!("wrong msgpack marker FixStr(3) at byte 2", err.to_string());
+    match err {
+        Error::AtOffset(2, ref inner) => match **inner {
+            Error::TypeMismatch(Marker::FixStr(3)) => (),
+            ref other => panic!("unexpected result: {other:?}"),
+        },
+        ref other => panic!("unexpected result: {other:?}"),
+    }
+
+    // Offsets aren't attached twice.
+    assert_eq!(Some(2), err.at(10).offset());
+
+    let mut de = Deserializer::from_read_ref(&buf);
+    let res: Result<(u8, u8), Error> = Deserialize::deserialize(&mut de);
+    assert_eq!(Some(2), res.map_err(|err| err.at(de.marker_position())).unwrap_err().offset());
+
+    // The entry points attach the offset themselves.
+    let err = rmp_serde::from_slice::<(u8, u8)>(&buf).unwrap_err();
+    assert_eq!(Some(2), err.offset());
+    match err.into_inner() {
+        Error::TypeMismatch(Marker::FixStr(3)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn pass_skip_value() {
+    // Encoded `[{"a": [1, "b", <bin 2>]}, <ext 5: 3 bytes>, 1.5]`, then `42`.
+    let buf = [
+        0x93,
+        0x81, 0xa1, 0x61, 0x93, 0x01, 0xa1, 0x62, 0xc4, 0x02, 0x00, 0x00,
+        0xc7, 0x03, 0x05, 0x00, 0x00, 0x00,
+        0xcb, 0x3f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x2a,
+    ];
+
+    let mut de = Deserializer::new(&buf[..]);
+    de.skip_value().unwrap();
+    assert_eq!(buf.len() as u64 - 1, de.position());
+    assert_eq!(42u8, Deserialize::deserialize(&mut de).unwrap());
+
+    // Elements can be skipped one by one.
+    let mut de = Deserializer::from_read_ref(&buf[1..]);
+    for _ in 0..3 {
+        de.skip_value().unwrap();
+    }
+    assert_eq!(42u8, Deserialize::deserialize(&mut de).unwrap());
+}
+
+#[test]
+fn fail_skip_value_truncated() {
+    // Encoded `["abc"]`, missing the last byte.
+    let buf = [0x91, 0xa3, 0x61, 0x62];
+
+    let mut de = Deserializer::new(&buf[..]);
+    match de.skip_value().err().unwrap() {
+        Error::InvalidDataRead(..) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn fail_max_len() {
+    // Encoded `[1, 2, 3]` and `"abcd"`.
+    let array = [0x93, 0x01, 0x02, 0x03];
+    let string = [0xa4, 0x61, 0x62, 0x63, 0x64];
+
+    let mut de = Deserializer::new(&array[..]).with_max_len(3);
+    assert_eq!(vec![1u8, 2, 3], Vec::<u8>::deserialize(&mut de).unwrap());
+
+    let mut de = Deserializer::new(&array[..]).with_max_len(2);
+    match Vec::<u8>::deserialize(&mut de).unwrap_err() {
+        Error::LengthLimitExceeded(3) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+
+    let mut de = Deserializer::from_read_ref(&string[..]).with_max_len(3);
+    match <&str>::deserialize(&mut de).unwrap_err() {
+        Error::LengthLimitExceeded(4) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+
+    // A huge `array 32` header fails before any element is read.
+    let buf = [0xdd, 0xff, 0xff, 0xff, 0xff];
+    let mut de = Deserializer::new(&buf[..]).with_max_len(1 << 20);
+    match Vec::<u8>::deserialize(&mut de).unwrap_err() {
+        Error::LengthLimitExceeded(0xffff_ffff) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn fail_max_bytes() {
+    // Encoded `["ab", "cd"]`, 7 bytes in total.
+    let buf = [0x92, 0xa2, 0x61, 0x62, 0xa2, 0x63, 0x64];
+
+    let mut de = Deserializer::new(&buf[..]).with_max_bytes(7);
+    assert_eq!(vec!["ab", "cd"], Vec::<String>::deserialize(&mut de).unwrap());
+
+    // The second string doesn't fit, although each one is within the length limit.
+    let mut de = Deserializer::new(&buf[..]).with_max_len(2).with_max_bytes(6);
+    match Vec::<String>::deserialize(&mut de).unwrap_err() {
+        Error::ByteLimitExceeded(6) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+
+    // Running out of budget between two values fails before the next marker is read.
+    let mut de = Deserializer::from_read_ref(&buf[..]).with_max_bytes(4);
+    match Vec::<&str>::deserialize(&mut de).unwrap_err() {
+        Error::ByteLimitExceeded(4) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+
+    // A `bin 32` header claiming more than the budget fails before anything is allocated.
+    let buf = [0xc6, 0x00, 0x10, 0x00, 0x00];
+    let mut de = Deserializer::new(&buf[..]).with_max_bytes(1 << 16);
+    match serde_bytes::ByteBuf::deserialize(&mut de).unwrap_err() {
+        Error::ByteLimitExceeded(65536) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn pass_deserializer_reset() {
+    let first = rmp_serde::to_vec(&(1u8, "abc")).unwrap();
+    let second = rmp_serde::to_vec(&Some(2u8)).unwrap();
+
+    let mut de = Deserializer::new(&first[..]).with_max_depth(8);
+    assert_eq!((1u8, "abc".to_owned()), Deserialize::deserialize(&mut de).unwrap());
+    assert_eq!(first.len() as u64, de.position());
+
+    let rest = de.reset(&second[..]);
+    assert!(rest.is_empty());
+    assert_eq!(0, de.position());
+    assert_eq!(Some(2u8), Deserialize::deserialize(&mut de).unwrap());
+    assert_eq!(second.len() as u64, de.position());
+}
+
+#[cfg(feature = "bytes")]
+#[test]
+fn pass_shared_bytes() {
+    use bytes::Bytes;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Frame {
+        #[serde(with = "rmp_serde::shared_bytes")]
+        payload: Bytes,
+    }
+
+    // [bin [1, 2, 3]]
+    let buf = Bytes::from_static(&[0x91, 0xc4, 0x03, 0x01, 0x02, 0x03]);
+
+    let frame: Frame = rmp_serde::shared_bytes::from_bytes(&buf).unwrap();
+    assert_eq!(&[1, 2, 3], &frame.payload[..]);
+    assert_eq!(buf[3..].as_ptr(), frame.payload.as_ptr());
+
+    // Outside of `from_bytes` the data is copied.
+    let frame: Frame = rmp_serde::from_slice(&buf).unwrap();
+    assert_eq!(&[1, 2, 3], &frame.payload[..]);
+    assert_ne!(buf[3..].as_ptr(), frame.payload.as_ptr());
+}
+
+#[cfg(feature = "bytes")]
+#[test]
+fn pass_shared_bytes_from_arc_and_split() {
+    use std::sync::Arc;
+
+    use bytes::Bytes;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Frame {
+        id: u8,
+        #[serde(with = "rmp_serde::shared_bytes")]
+        payload: Bytes,
+    }
+
+    // [1, bin [1, 2]], [2, bin [3]]
+    let buf: Arc<[u8]> = Arc::from(&[0x92, 0x01, 0xc4, 0x02, 0x01, 0x02, 0x92, 0x02, 0xc4, 0x01, 0x03][..]);
+
+    let frame: Frame = rmp_serde::shared_bytes::from_arc(&buf).unwrap();
+    assert_eq!(Frame { id: 1, payload: Bytes::from_static(&[1, 2]) }, frame);
+    assert_eq!(buf[4..].as_ptr(), frame.payload.as_ptr());
+
+    let shared = Bytes::from_owner(buf.clone());
+    let vals = rmp_serde::shared_bytes::split_values(&shared).unwrap();
+    assert_eq!(2, vals.len());
+    assert_eq!(buf[6..].as_ptr(), vals[1].as_ptr());
+    let frame: Frame = rmp_serde::shared_bytes::from_bytes(&vals[1]).unwrap();
+    assert_eq!(buf[10..].as_ptr(), frame.payload.as_ptr());
+
+    let err = rmp_serde::shared_bytes::split_values(&shared.slice(..9)).unwrap_err();
+    assert_eq!(Some(6), err.offset());
+}
+
+#[cfg(feature = "digest")]
+#[test]
+fn pass_hashing_reader_hashes_consumed_bytes() {
+    use rmp_serde::hashing::HashingReader;
+    use sha2::{Digest, Sha256};
+    use std::io::Read;
+
+    let mut buf = rmp_serde::to_vec(&[1u32, 300, 70000]).unwrap();
+    let len = buf.len();
+    // A trailing value, e.g. a signature, isn't hashed.
+    buf.extend_from_slice(&[0xc4, 0x02, 0xaa, 0xbb]);
+
+    let mut rd = HashingReader::<_, Sha256>::new(&buf[..]);
+    let val: Vec<u32> = rmp_serde::from_read(&mut rd).unwrap();
+    assert_eq!(vec![1, 300, 70000], val);
+    assert_eq!(len as u64, rd.len());
+
+    let (mut rest, digest) = rd.into_parts();
+    assert_eq!(Sha256::digest(&buf[..len]), digest.finalize());
+
+    let mut sig = Vec::new();
+    rest.read_to_end(&mut sig).unwrap();
+    assert_eq!(vec![0xc4, 0x02, 0xaa, 0xbb], sig);
+}
+
+#[test]
+fn pass_partial_decoder_byte_by_byte() {
+    use rmp_serde::partial::{PartialDecoder, Progress};
+
+    let mut buf = rmp_serde::to_vec(&("key", vec![1u32, 70000])).unwrap();
+    let len = buf.len();
+    buf.push(0x2a);
+
+    let mut decoder = PartialDecoder::<(String, Vec<u32>)>::new();
+    for (idx, byte) in buf[..len - 1].iter().enumerate() {
+        match decoder.feed(&[*byte]).unwrap() {
+            Progress::NeedMore(n) => assert!(n >= 1 && idx + 1 + n <= len),
+            other => panic!("value completed early: {other:?}"),
+        }
+    }
+    assert_eq!(len - 1, decoder.buffered_len());
+
+    // The last byte of the value is fed along with the next value.
+    match decoder.feed(&buf[len - 1..]).unwrap() {
+        Progress::Done(val, 1) => assert_eq!(("key".to_owned(), vec![1, 70000]), val),
+        other => panic!("unexpected progress: {other:?}"),
+    }
+    assert_eq!(0, decoder.buffered_len());
+
+    let mut decoder = PartialDecoder::<u8>::new();
+    assert!(matches!(decoder.feed(&buf[len..]).unwrap(), Progress::Done(42, 1)));
+}
+
+#[test]
+fn fail_partial_decoder() {
+    use rmp_serde::partial::{PartialDecoder, Progress};
+
+    let mut decoder = PartialDecoder::<Vec<u8>>::new().with_max_len(16);
+    // A bin of 32 bytes is rejected from its header.
+    match decoder.feed(&[0xc4, 0x20]) {
+        Err(Error::LengthLimitExceeded(..)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+    assert_eq!(0, decoder.buffered_len());
+
+    assert!(matches!(decoder.feed(&[0x92, 0x01]).unwrap(), Progress::NeedMore(1)));
+    match decoder.feed(&[0xc1]) {
+        Err(Error::Syntax(..)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+
+    // A mismatched value is consumed, and the next one starts right after it.
+    let input = [0xa1, 0x61, 0x90];
+    let consumed = match decoder.feed(&input).unwrap() {
+        Progress::Invalid(_, consumed) => consumed,
+        other => panic!("unexpected progress: {other:?}"),
+    };
+    assert_eq!(2, consumed);
+    assert!(matches!(decoder.feed(&input[consumed..]).unwrap(), Progress::Done(v, 1) if v.is_empty()));
+
+    // The same, with the mismatched value fed in pieces.
+    assert!(matches!(decoder.feed(&input[..1]).unwrap(), Progress::NeedMore(1)));
+    assert!(matches!(decoder.feed(&input[1..]).unwrap(), Progress::Invalid(_, 1)));
+}
+
+#[test]
+fn fail_trailing_bytes() {
+    let buf = [0x92, 0x01, 0x02, 0xc0, 0xc0];
+
+    assert_eq!((1, 2), rmp_serde::from_slice::<(u8, u8)>(&buf).unwrap());
+    match rmp_serde::from_slice_exact::<(u8, u8)>(&buf) {
+        Err(Error::TrailingBytes(2)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+    assert_eq!((1, 2), rmp_serde::from_slice_exact::<(u8, u8)>(&buf[..3]).unwrap());
+
+    let mut de = Deserializer::new(&buf[..]);
+    <(u8, u8)>::deserialize(&mut de).unwrap();
+    match de.end() {
+        Err(Error::TrailingBytes(2)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+    de.end().unwrap();
+}
+
+#[test]
+fn pass_str_bin_mode() {
+    use rmp_serde::config::StrBinMode;
+
+    // ["ab", <bin "cd">, <bin [0x61, 0xff]>]
+    let buf = [0x93, 0xa2, 0x61, 0x62, 0xc4, 0x02, 0x63, 0x64, 0xc4, 0x02, 0x61, 0xff];
+
+    // Str into a byte sequence fails by default.
+    let mut de = Deserializer::from_read_ref(&buf);
+    assert!(<(Vec<u8>, String, Vec<u8>)>::deserialize(&mut de).is_err());
+
+    let mut de = Deserializer::from_read_ref(&buf).with_str_bin_mode(StrBinMode::Lenient);
+    let val = <(Vec<u8>, String, Vec<u8>)>::deserialize(&mut de).unwrap();
+    assert_eq!((b"ab".to_vec(), "cd".to_owned(), vec![0x61, 0xff]), val);
+
+    // Invalid UTF-8 is rejected unless lossy.
+    let mut de = Deserializer::from_read_ref(&buf).with_str_bin_mode(StrBinMode::Lenient);
+    assert!(<(Vec<u8>, String, String)>::deserialize(&mut de).is_err());
+
+    let mut de = Deserializer::from_read_ref(&buf).with_str_bin_mode(StrBinMode::LenientLossy);
+    let val = <(&str, &str, String)>::deserialize(&mut de).unwrap();
+    assert_eq!(("ab", "cd", "a\u{fffd}".to_owned()), val);
+}
+
+#[test]
+fn fail_duplicate_keys() {
+    use std::collections::BTreeMap;
+    use rmp_serde::config::DuplicateKeyMode;
+
+    // {1: "a", 2: "b", 1 as uint16: "c"}
+    let buf = [0x83, 0x01, 0xa1, 0x61, 0x02, 0xa1, 0x62, 0xcd, 0x00, 0x01, 0xa1, 0x63];
+
+    let mut de = Deserializer::from_read_ref(&buf);
+    let val = BTreeMap::<u8, &str>::deserialize(&mut de).unwrap();
+    assert_eq!(BTreeMap::from([(1, "c"), (2, "b")]), val);
+
+    let mut de = Deserializer::from_read_ref(&buf).with_duplicate_keys(DuplicateKeyMode::Reject);
+    match BTreeMap::<u8, &str>::deserialize(&mut de) {
+        Err(Error::DuplicateKey(key)) => assert_eq!("1", key),
+        other => panic!("unexpected result: {other:?}"),
+    }
+
+    // Keys of nested maps are tracked separately.
+    let buf = [0x82, 0xa1, 0x61, 0x81, 0xa1, 0x61, 0x01, 0xa1, 0x62, 0x81, 0xa1, 0x61, 0x02];
+    let mut de = Deserializer::from_read_ref(&buf).with_duplicate_keys(DuplicateKeyMode::Reject);
+    let val = BTreeMap::<String, BTreeMap<String, u8>>::deserialize(&mut de).unwrap();
+    assert_eq!(2, val.len());
+}
+
+#[test]
+fn pass_ignored_any_skips_without_decoding() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Known {
+        a: u8,
+    }
+
+    // {"b": <bin 100000>, "c": [[[...]]] nested deeper than the depth limit, "a": 7}
+    let mut buf = vec![0x83, 0xa1, 0x62, 0xc6, 0x00, 0x01, 0x86, 0xa0];
+    buf.resize(buf.len() + 100_000, 0xff);
+    buf.extend_from_slice(&[0xa1, 0x63]);
+    buf.resize(buf.len() + 2000, 0x91);
+    buf.extend_from_slice(&[0xc0, 0xa1, 0x61, 0x07]);
+
+    let mut de = Deserializer::new(Cursor::new(&buf[..]));
+    assert_eq!(Known { a: 7 }, Known::deserialize(&mut de).unwrap());
+
+    let mut de = Deserializer::from_read_ref(&buf);
+    assert_eq!(Known { a: 7 }, Known::deserialize(&mut de).unwrap());
+    assert_eq!(buf.len() as u64, de.position());
+}
+
+#[test]
+fn pass_utf8_mode() {
+    use std::borrow::Cow;
+    use rmp_serde::config::Utf8Mode;
+
+    // "a\xff"
+    let buf = [0xa2, 0x61, 0xff];
+
+    // By default the bytes are given to types accepting them.
+    let mut de = Deserializer::from_read_ref(&buf);
+    assert_eq!(vec![0x61, 0xff], serde_bytes::ByteBuf::deserialize(&mut de).unwrap().into_vec());
+    let mut de = Deserializer::from_read_ref(&buf);
+    assert!(matches!(String::deserialize(&mut de), Err(Error::Utf8Error(..))));
+
+    let mut de = Deserializer::from_read_ref(&buf).with_utf8_mode(Utf8Mode::Strict);
+    assert!(matches!(serde_bytes::ByteBuf::deserialize(&mut de), Err(Error::Utf8Error(..))));
+
+    let mut de = Deserializer::new(&buf[..]).with_utf8_mode(Utf8Mode::Lossy);
+    assert_eq!("a\u{fffd}", String::deserialize(&mut de).unwrap());
+    let mut de = Deserializer::from_read_ref(&buf).with_utf8_mode(Utf8Mode::Lossy);
+    assert_eq!("a\u{fffd}", <Cow<'_, str>>::deserialize(&mut de).unwrap());
+}
+
+#[test]
+fn pass_key_policy() {
+    use std::collections::BTreeMap;
+    use rmp_serde::KeyPolicy;
+
+    // {1: 2, "a": 3, "4": 5}
+    let buf = [0x83, 0x01, 0x02, 0xa1, 0x61, 0x03, 0xa1, 0x34, 0x05];
+
+    let mut de = Deserializer::from_read_ref(&buf);
+    assert!(<BTreeMap<String, u8>>::deserialize(&mut de).is_err());
+
+    let mut de = Deserializer::from_read_ref(&buf).with_key_policy(KeyPolicy::Lenient);
+    let map = <BTreeMap<String, u8>>::deserialize(&mut de).unwrap();
+    assert_eq!(Some(&2), map.get("1"));
+    assert_eq!(Some(&3), map.get("a"));
+    assert_eq!(Some(&5), map.get("4"));
+
+    // Only numeric strings can be parsed.
+    let mut de = Deserializer::new(&buf[..]).with_key_policy(KeyPolicy::Lenient);
+    assert!(matches!(<BTreeMap<u64, u8>>::deserialize(&mut de), Err(Error::Syntax(..))));
+
+    // {"-1": 2, 300: 3}
+    let buf = [0x82, 0xa2, 0x2d, 0x31, 0x02, 0xcd, 0x01, 0x2c, 0x03];
+    let mut de = Deserializer::new(&buf[..]).with_key_policy(KeyPolicy::Lenient);
+    assert_eq!(vec![(-1, 2), (300, 3)], <BTreeMap<i64, u8>>::deserialize(&mut de).unwrap().into_iter().collect::<Vec<_>>());
+
+    // Maps can be read as key-value pairs, in their encoded order.
+    let mut de = Deserializer::from_read_ref(&buf).with_key_policy(KeyPolicy::Lenient);
+    assert_eq!(vec![("-1".to_owned(), 2), ("300".to_owned(), 3)], <Vec<(String, u8)>>::deserialize(&mut de).unwrap());
+    let mut de = Deserializer::from_read_ref(&buf);
+    assert!(<Vec<(String, u8)>>::deserialize(&mut de).is_err());
+}
+
+#[test]
+fn pass_error_kind() {
+    use rmp_serde::ErrorKind;
+
+    // Truncated in the middle of a string.
+    let err = rmp_serde::from_slice::<String>(&[0xa3, 0x61]).unwrap_err();
+    assert_eq!(ErrorKind::UnexpectedEof, err.kind());
+    assert_eq!(std::io::ErrorKind::UnexpectedEof, err.io_error().unwrap().kind());
+    assert_eq!(ErrorKind::UnexpectedEof, err.at(1).kind());
+
+    let err = rmp_serde::from_slice::<String>(&[0xa1, 0xff]).unwrap_err();
+    assert_eq!(ErrorKind::Utf8, err.kind());
+    assert!(err.io_error().is_none());
+
+    let mut de = Deserializer::from_read_ref(&[0x91, 0x91, 0xc0]).with_max_depth(2);
+    let err = <((),)>::deserialize(&mut de).unwrap_err();
+    assert_eq!(ErrorKind::LimitExceeded, err.kind());
+
+    let err = rmp_serde::from_slice_exact::<u8>(&[0x01, 0x02]).unwrap_err();
+    assert_eq!(ErrorKind::Syntax, err.kind());
+}
+
+#[test]
+fn pass_numeric_coercion() {
+    use rmp_serde::config::{NumericCoercion, NumericOverflow};
+
+    fn encode(vals: &[f64]) -> Vec<u8> {
+        let mut buf = Vec::new();
+        rmp::encode::write_array_len(&mut buf, vals.len() as u32).unwrap();
+        for &val in vals {
+            rmp::encode::write_f64(&mut buf, val).unwrap();
+        }
+        buf
+    }
+
+    let buf = encode(&[1.0, 2.5, -2.5, 7.9]);
+    assert!(rmp_serde::from_slice::<Vec<i32>>(&buf).is_err());
+
+    let mut de = Deserializer::from_read_ref(&buf).with_numeric_coercion(NumericCoercion::Truncate);
+    assert_eq!(vec![1, 2, -2, 7], Vec::<i32>::deserialize(&mut de).unwrap());
+
+    let mut de = Deserializer::from_read_ref(&buf).with_numeric_coercion(NumericCoercion::Round);
+    assert_eq!(vec![1, 3, -3, 8], Vec::<i32>::deserialize(&mut de).unwrap());
+
+    // Out of range floats and integers.
+    let buf = encode(&[-1.0, 1e20]);
+    let mut de = Deserializer::from_read_ref(&buf).with_numeric_coercion(NumericCoercion::Round);
+    assert!(matches!(Vec::<u64>::deserialize(&mut de), Err(Error::OutOfRange)));
+
+    let mut de = Deserializer::from_read_ref(&buf)
+        .with_numeric_coercion(NumericCoercion::Round)
+        .with_numeric_overflow(NumericOverflow::Saturate);
+    assert_eq!(vec![0, u64::MAX], Vec::<u64>::deserialize(&mut de).unwrap());
+
+    let buf = encode(&[-1.0, 1e300]);
+    let mut de = Deserializer::from_read_ref(&buf).with_numeric_overflow(NumericOverflow::Saturate);
+    assert_eq!(vec![-1.0, f32::MAX], Vec::<f32>::deserialize(&mut de).unwrap());
+
+    let buf = encode(&[f64::NAN]);
+    let mut de = Deserializer::from_read_ref(&buf)
+        .with_numeric_coercion(NumericCoercion::Truncate)
+        .with_numeric_overflow(NumericOverflow::Saturate);
+    assert!(matches!(Vec::<u8>::deserialize(&mut de), Err(Error::OutOfRange)));
+
+    // Encoded `[300, -200]`.
+    let buf = [0x92, 0xcd, 0x01, 0x2c, 0xd1, 0xff, 0x38];
+    let mut de = Deserializer::from_read_ref(&buf).with_numeric_overflow(NumericOverflow::Saturate);
+    assert_eq!(vec![127, -128], Vec::<i8>::deserialize(&mut de).unwrap());
+
+    // Integers are still read into floats.
+    let mut de = Deserializer::from_read_ref(&buf).with_numeric_coercion(NumericCoercion::Truncate);
+    assert_eq!(vec![300.0, -200.0], Vec::<f64>::deserialize(&mut de).unwrap());
+}
+
+#[test]
+fn pass_ext_registry() {
+    use rmp_serde::registry::{ExtRegistry, UnknownExt};
+    use rmp_serde::Ext;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(untagged)]
+    enum Value {
+        Int(u32),
+        Other(UnknownExt),
+    }
+
+    let buf = rmp_serde::to_vec(&(Ext(1, vec![0, 0, 1, 0x2c]), Ext(5, vec![0xff]))).unwrap();
+
+    let mut registry = ExtRegistry::new();
+    registry.register_fn(1, |data| {
+        let data: [u8; 4] = data.try_into().map_err(|_| Error::LengthMismatch(data.len() as u32))?;
+        Ok(u32::from_be_bytes(data))
+    });
+    assert!(registry.contains(1));
+    assert!(!registry.contains(5));
+
+    let mut de = Deserializer::from_read_ref(&buf).with_ext_registry(registry.clone());
+    let vals: (Value, Value) = Deserialize::deserialize(&mut de).unwrap();
+    assert_eq!((Value::Int(300), Value::Other(UnknownExt { ty: 5, data: vec![0xff] })), vals);
+
+    // Ext data the registered type can't be decoded from.
+    let buf = rmp_serde::to_vec(&Ext(1, vec![0xff])).unwrap();
+    let mut de = Deserializer::from_read_ref(&buf).with_ext_registry(registry);
+    assert!(Value::deserialize(&mut de).is_err());
+}
+
+#[test]
+fn pass_ext_registry_structured_and_ext_mode() {
+    use rmp_serde::config::ExtMode;
+    use rmp_serde::registry::ExtRegistry;
+    use rmp_serde::Ext;
+    use serde::Serialize;
+    use serde_bytes::ByteBuf;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Point {
+        x: i8,
+        y: i8,
+    }
+
+    let mut registry = ExtRegistry::new();
+    registry.register_fn(3, |data| match *data {
+        [x, y] => Ok(Point { x: x as i8, y: y as i8 }),
+        _ => Err(Error::LengthMismatch(data.len() as u32)),
+    });
+
+    let buf = rmp_serde::to_vec(&(Ext(3, vec![1, 0xff]), Some(Ext(3, vec![2, 3])), Ext(5, vec![0xaa]))).unwrap();
+    // Unregistered exts are still read according to the ext mode.
+    let mut de = Deserializer::from_read_ref(&buf).with_ext_registry(registry).with_ext_mode(ExtMode::Tuple);
+    let vals: (Point, Option<Point>, (i8, ByteBuf)) = Deserialize::deserialize(&mut de).unwrap();
+    assert_eq!((Point { x: 1, y: -1 }, Some(Point { x: 2, y: 3 }), (5, ByteBuf::from(vec![0xaa]))), vals);
+}
+
+#[test]
+fn pass_bytes_consumed() {
+    // Encoded `[1, 2]`, `"a"`, followed by `nil`.
+    let buf = [0x92, 0x01, 0x02, 0xa1, 0x61, 0xc0];
+
+    let mut de = Deserializer::new(&buf[..]);
+    assert_eq!(vec![1, 2], Vec::<u8>::deserialize(&mut de).unwrap());
+    assert_eq!(3, de.bytes_consumed());
+
+    let mut de = Deserializer::from_read_ref(&buf[3..]);
+    assert_eq!("a", <&str>::deserialize(&mut de).unwrap());
+    assert_eq!(2, de.bytes_consumed());
+    assert_eq!(None, Option::<()>::deserialize(&mut de).unwrap());
+    assert_eq!(3, de.bytes_consumed());
+    assert_eq!(de.position(), de.bytes_consumed());
+}
+
+#[test]
+fn pass_scratch_buffer() {
+    use std::io::ErrorKind;
+
+    /// A string of at most 8 bytes, stored inline like `heapless::String<8>`.
+    #[derive(Debug, PartialEq)]
+    struct InlineStr([u8; 8], usize);
+
+    impl<'de> Deserialize<'de> for InlineStr {
+        fn deserialize<D: de::Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+            struct InlineStrVisitor;
+
+            impl<'de> de::Visitor<'de> for InlineStrVisitor {
+                type Value = InlineStr;
+
+                fn expecting(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+                    fmt.write_str("a string of at most 8 bytes")
+                }
+
+                fn visit_str<E: de::Error>(self, v: &str) -> Result<InlineStr, E> {
+                    let mut buf = [0; 8];
+                    buf.get_mut(..v.len())
+                        .ok_or_else(|| E::invalid_length(v.len(), &self))?
+                        .copy_from_slice(v.as_bytes());
+                    Ok(InlineStr(buf, v.len()))
+                }
+            }
+
+            de.deserialize_str(InlineStrVisitor)
+        }
+    }
+
+    // ["temp", <bin 100>] with the binary skipped, then "humidity!".
+    let mut buf = vec![0x92, 0xa4, b't', b'e', b'm', b'p', 0xc4, 100];
+    buf.resize(buf.len() + 100, 0xff);
+    buf.extend_from_slice(&[0xa9, b'h', b'u', b'm', b'i', b'd', b'i', b't', b'y', b'!']);
+
+    let mut scratch = [0; 9];
+    let mut de = Deserializer::from_read_scratch(Cursor::new(&buf[..]), &mut scratch);
+    let (name, _): (InlineStr, de::IgnoredAny) = Deserialize::deserialize(&mut de).unwrap();
+    assert_eq!(InlineStr(*b"temp\0\0\0\0", 4), name);
+    assert_eq!(108, de.position());
+
+    // Fits the scratch buffer, but not the target type.
+    assert!(matches!(InlineStr::deserialize(&mut de), Err(Error::Syntax(..))));
+
+    let mut scratch = [0; 3];
+    let mut de = Deserializer::from_read_scratch(&buf[1..], &mut scratch);
+    match InlineStr::deserialize(&mut de) {
+        Err(Error::InvalidDataRead(err)) => assert_eq!(ErrorKind::OutOfMemory, err.kind()),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn pass_interner() {
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+
+    use rmp_serde::intern::{Interned, Interner};
+
+    #[derive(Debug, Deserialize)]
+    struct Row {
+        #[serde(with = "rmp_serde::intern")]
+        unit: Arc<str>,
+        labels: BTreeMap<Interned, String>,
+    }
+
+    // [{"unit": "celsius", "labels": {"room": "kitchen"}}, {"unit": "celsius", "labels": {"room": "hall"}}]
+    let row = |label: &str| {
+        let mut buf = vec![0x82, 0xa4, b'u', b'n', b'i', b't', 0xa7];
+        buf.extend_from_slice(b"celsius");
+        buf.extend_from_slice(&[0xa6, b'l', b'a', b'b', b'e', b'l', b's', 0x81, 0xa4, b'r', b'o', b'o', b'm']);
+        buf.push(0xa0 | label.len() as u8);
+        buf.extend_from_slice(label.as_bytes());
+        buf
+    };
+    let mut buf = vec![0x92];
+    buf.extend(row("kitchen"));
+    buf.extend(row("hall"));
+
+    let interner = Interner::new();
+    let mut de = Deserializer::new(&buf[..]).with_interner(interner.clone());
+    let rows: Vec<Row> = Deserialize::deserialize(&mut de).unwrap();
+
+    assert_eq!("celsius", &*rows[0].unit);
+    assert!(Arc::ptr_eq(&rows[0].unit, &rows[1].unit));
+    let keys: Vec<&Interned> = rows.iter().flat_map(|row| row.labels.keys()).collect();
+    assert!(Arc::ptr_eq(keys[0].as_arc(), keys[1].as_arc()));
+    assert_eq!("hall", rows[1].labels["room"]);
+    // Plain `String` values are not interned.
+    assert_eq!(2, interner.len());
+
+    // Without an interner the same types allocate every string.
+    let rows: Vec<Row> = rmp_serde::from_slice(&buf).unwrap();
+    assert_eq!(rows[0].unit, rows[1].unit);
+    assert!(!Arc::ptr_eq(&rows[0].unit, &rows[1].unit));
+}
+
+#[test]
+fn pass_interner_limits() {
+    use std::sync::Arc;
+
+    use rmp_serde::intern::{Interned, Interner};
+
+    let buf = rmp_serde::to_vec(&["a", "a", "b", "b", "long key", "long key"]).unwrap();
+
+    // Only "a" fits into the set, and "long key" is too long for it.
+    let interner = Interner::with_limits(1, 4);
+    let mut de = Deserializer::new(&buf[..]).with_interner(interner.clone());
+    let keys: Vec<Interned> = Deserialize::deserialize(&mut de).unwrap();
+
+    assert!(Arc::ptr_eq(keys[0].as_arc(), keys[1].as_arc()));
+    assert_eq!(keys[2], keys[3]);
+    assert!(!Arc::ptr_eq(keys[2].as_arc(), keys[3].as_arc()));
+    assert_eq!(keys[4], keys[5]);
+    assert!(!Arc::ptr_eq(keys[4].as_arc(), keys[5].as_arc()));
+    assert_eq!(1, interner.len());
+}
+
+#[test]
+fn pass_deserializer_with_context() {
+    use rmp_serde::context::{self, DeserializerWithContext};
+
+    struct Prefix(&'static str);
+
+    #[derive(Debug, PartialEq)]
+    struct Name(String);
+
+    impl<'de> Deserialize<'de> for Name {
+        fn deserialize<D: de::Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+            let name = String::deserialize(de)?;
+            let prefix = context::context::<Prefix>().map_or("", |prefix| prefix.0);
+            Ok(Name(format!("{prefix}{name}")))
+        }
+    }
+
+    let buf = rmp_serde::to_vec(&("alice", "bob")).unwrap();
+
+    let mut de = Deserializer::new(&buf[..]);
+    let names: (Name, Name) = DeserializerWithContext::new(&mut de, Prefix("@")).deserialize().unwrap();
+    assert_eq!((Name("@alice".into()), Name("@bob".into())), names);
+
+    // The innermost context shadows outer ones only while it's in use.
+    #[derive(Debug, PartialEq)]
+    struct Pair(Name, Name);
+
+    impl<'de> Deserialize<'de> for Pair {
+        fn deserialize<D: de::Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+            let inner: Name = context::from_slice_with_context(&[0xa3, b'b', b'o', b'b'], Prefix("#")).unwrap();
+            Ok(Pair(inner, Name::deserialize(de)?))
+        }
+    }
+
+    let pair: Pair = context::from_slice_with_context(&buf[1..], Prefix("@")).unwrap();
+    assert_eq!(Pair(Name("#bob".into()), Name("@alice".into())), pair);
+
+    assert!(context::context::<Prefix>().is_none());
+    assert_eq!(Name("alice".into()), rmp_serde::from_slice(&buf[1..]).unwrap());
+}
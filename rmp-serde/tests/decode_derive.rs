@@ -66,6 +66,43 @@ fn pass_struct() {
     assert_eq!(Decoded { id: 42, value: 100500 }, actual);
 }
 
+#[test]
+fn fail_struct_field_count_mismatch() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Decoded {
+        id: u32,
+        value: u32,
+    }
+
+    // Encoded `[42]` and `[42, 100, 7]`.
+    for (buf, len) in [(&[0x91, 0x2a][..], 1), (&[0x93, 0x2a, 0x64, 0x07][..], 3)] {
+        match rmp_serde::from_slice::<Decoded>(buf).unwrap_err().into_inner() {
+            Error::FieldCountMismatch("Decoded", 2, actual) => assert_eq!(len, actual),
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+}
+
+#[test]
+fn pass_struct_extra_fields_ignored() {
+    use rmp_serde::config::ExtraFieldsMode;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Decoded {
+        id: u32,
+        #[serde(default)]
+        value: u32,
+    }
+
+    // Encoded `[[42, 100, [1, 2]], [43]]`.
+    let buf = [0x92, 0x93, 0x2a, 0x64, 0x92, 0x01, 0x02, 0x91, 0x2b];
+
+    let mut de = Deserializer::new(&buf[..]).with_extra_fields(ExtraFieldsMode::Ignore);
+    let actual: Vec<Decoded> = Deserialize::deserialize(&mut de).unwrap();
+
+    assert_eq!(vec![Decoded { id: 42, value: 100 }, Decoded { id: 43, value: 0 }], actual);
+}
+
 #[test]
 fn pass_struct_from_map() {
     #[derive(Debug, PartialEq, Deserialize)]
@@ -165,7 +202,7 @@ fn fail_enum_map_mismatch() {
 
     let err: Result<Enum, _> = rmp_serde::from_slice(&buf);
 
-    match err.unwrap_err() {
+    match err.unwrap_err().into_inner() {
         Error::LengthMismatch(2) => (),
         other => panic!("unexpected result: {other:?}"),
     }
@@ -524,3 +561,37 @@ fn pass_from_ref() {
 
     assert_eq!(Dog { name: "Bobby", age: 8 }, rmp_serde::from_read_ref(&buf).unwrap());
 }
+
+#[test]
+fn pass_fields_of() {
+    use std::collections::BTreeMap;
+
+    use rmp_serde::fields::fields_of;
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Struct<'a> {
+        id: u32,
+        #[serde(rename = "label")]
+        name: &'a str,
+        #[serde(skip)]
+        cache: Option<u32>,
+    }
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Flattened {
+        id: u32,
+        #[serde(flatten)]
+        extra: BTreeMap<String, u32>,
+    }
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Newtype(u32);
+
+    assert_eq!(Some(&["id", "label"][..]), fields_of::<Struct<'_>>());
+    assert_eq!(None, fields_of::<Flattened>());
+    assert_eq!(None, fields_of::<Newtype>());
+    assert_eq!(None, fields_of::<BTreeMap<String, u32>>());
+}
@@ -3,7 +3,7 @@ extern crate rmp_serde as rmps;
 use std::io::Cursor;
 
 use rmps::config::BytesMode;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use rmp_serde::encode::{self, Error};
 use rmp_serde::{Raw, RawRef, Serializer};
@@ -239,6 +239,27 @@ fn pass_hash_array_bytes() {
     val.serialize(&mut Serializer::new(&mut &mut buf[..]).with_bytes(BytesMode::ForceAll)).ok().unwrap();
 }
 
+#[test]
+fn pass_byte_containers_as_bin_by_default() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Blob {
+        vec: Vec<u8>,
+        boxed: Box<[u8]>,
+        arr: [u8; 2],
+    }
+
+    let val = Blob { vec: vec![1, 2], boxed: vec![255].into_boxed_slice(), arr: [3, 4] };
+    let buf = rmps::to_vec(&val).unwrap();
+    assert_eq!(vec![0x93, 0xc4, 0x02, 0x01, 0x02, 0xc4, 0x01, 0xff, 0x92, 0x03, 0x04], buf);
+    assert_eq!(val, rmps::from_slice::<Blob>(&buf).unwrap());
+    assert_eq!(val, rmps::from_read::<_, Blob>(&buf[..]).unwrap());
+
+    let mut buf = Vec::new();
+    val.serialize(&mut Serializer::new(&mut buf).with_bytes(BytesMode::Normal)).unwrap();
+    assert_eq!(vec![0x93, 0x92, 0x01, 0x02, 0x91, 0xcc, 0xff, 0x92, 0x03, 0x04], buf);
+    assert_eq!(val, rmps::from_slice::<Blob>(&buf).unwrap());
+}
+
 #[test]
 fn pass_tuple_low_bytes() {
     let mut buf = [0x00, 0x00, 0x00, 0x00, 0x00];
@@ -367,16 +388,16 @@ fn pass_raw_valid_utf8() {
 }
 
 #[test]
-#[allow(deprecated)]
 fn pass_raw_invalid_utf8() {
     // >>> msgpack.dumps(msgpack.dumps([200, []]))
     // '\xa4\x92\xcc\xc8\x90'
-    let raw = Raw::from_utf8(vec![0x92, 0xcc, 0xc8, 0x90]);
+    let raw = Raw::from_bytes(vec![0x92, 0xcc, 0xc8, 0x90]);
 
     let mut buf = Vec::new();
     raw.serialize(&mut Serializer::new(&mut buf)).unwrap();
 
-    assert_eq!(vec![196, 4, 146, 204, 200, 144], buf);
+    assert_eq!(vec![0xa4, 0x92, 0xcc, 0xc8, 0x90], buf);
+    assert_eq!(raw, rmp_serde::from_slice::<Raw>(&buf).unwrap());
 }
 
 #[test]
@@ -390,20 +411,448 @@ fn pass_raw_ref_valid_utf8() {
 }
 
 #[test]
-#[allow(deprecated)]
 fn pass_raw_ref_invalid_utf8() {
     // >>> msgpack.dumps(msgpack.dumps([200, []]))
     // '\xa4\x92\xcc\xc8\x90'
     let b = &[0x92, 0xcc, 0xc8, 0x90];
-    let raw = RawRef::from_utf8(b);
+    let raw = RawRef::from_bytes(b);
 
     let mut buf = Vec::new();
     raw.serialize(&mut Serializer::new(&mut buf)).unwrap();
 
-    assert_eq!(vec![196, 4, 146, 204, 200, 144], buf);
+    assert_eq!(vec![0xa4, 0x92, 0xcc, 0xc8, 0x90], buf);
+    assert_eq!(raw, rmp_serde::from_slice::<RawRef<'_>>(&buf).unwrap());
 }
 
 #[test]
 fn serializer_one_type_arg() {
     let _s: rmp_serde::Serializer<&mut dyn std::io::Write>;
 }
+
+#[test]
+fn pass_map_canonical() {
+    use std::collections::HashMap;
+
+    // Keys are sorted by their encoded representation: fixint 1 is `0x01`, while uint8 200 is
+    // `0xcc 0xc8`.
+    let mut val = HashMap::new();
+    val.insert(200u32, vec![HashMap::from([("b", ()), ("a", ())])]);
+    val.insert(1u32, Vec::new());
+
+    let mut buf = Vec::new();
+    val.serialize(&mut Serializer::new(&mut buf).with_canonical()).unwrap();
+
+    assert_eq!(vec![0x82, 0x01, 0x90, 0xcc, 0xc8, 0x91, 0x82, 0xa1, 0x61, 0xc0, 0xa1, 0x62, 0xc0], buf);
+}
+
+#[test]
+fn pass_strict_canonical() {
+    use rmp_serde::ExtRef;
+
+    fn strict<T: Serialize>(val: T) -> Result<Vec<u8>, Error> {
+        let mut buf = Vec::new();
+        val.serialize(&mut Serializer::new(&mut buf).with_fixed_int_width().with_strict_canonical())?;
+        Ok(buf)
+    }
+
+    assert_eq!(vec![0x01], strict(1u32).unwrap());
+    assert_eq!(vec![0x82, 0xa1, 0x61, 0x01, 0xa1, 0x62, 0x02], strict(std::collections::HashMap::from([("b", 2), ("a", 1)])).unwrap());
+
+    assert!(matches!(strict(f64::NAN), Err(Error::NonCanonical(..))));
+    assert!(matches!(strict(f32::INFINITY), Err(Error::NonCanonical(..))));
+    assert_eq!(vec![0xcb, 0x3f, 0xf8, 0, 0, 0, 0, 0, 0], strict(1.5f64).unwrap());
+
+    // A timestamp of 1 second takes the 32-bit format.
+    assert_eq!(vec![0xd6, 0xff, 0, 0, 0, 1], strict(ExtRef(-1, &[0, 0, 0, 1])).unwrap());
+    assert!(matches!(strict(ExtRef(-1, &[0, 0, 0, 0, 0, 0, 0, 1])), Err(Error::NonCanonical(..))));
+    assert!(matches!(strict(ExtRef(5, &[0, 0, 0, 1])), Err(Error::NonCanonical(..))));
+}
+
+#[test]
+fn pass_canonical_raw_value() {
+    use rmp_serde::RawValue;
+
+    fn canonical(raw: &[u8], strict: bool) -> Result<Vec<u8>, Error> {
+        let raw = RawValue::from_vec(raw.to_vec()).unwrap();
+        let mut buf = Vec::new();
+        if strict {
+            raw.serialize(&mut Serializer::new(&mut buf).with_strict_canonical())?;
+        } else {
+            raw.serialize(&mut Serializer::new(&mut buf).with_canonical())?;
+        }
+        Ok(buf)
+    }
+
+    // Encoded `[{"a": 1, "b": [2.5, <timestamp 1>]}, 300]`, already canonical.
+    let buf = [
+        0x92, 0x82, 0xa1, 0x61, 0x01, 0xa1, 0x62, 0x92, 0xcb, 0x40, 0x04, 0, 0, 0, 0, 0, 0,
+        0xd6, 0xff, 0, 0, 0, 1, 0xcd, 0x01, 0x2c,
+    ];
+    assert_eq!(buf.to_vec(), canonical(&buf, true).unwrap());
+
+    // Encoded `[{"b": 1, "a": 2}]`, with the keys out of order.
+    let unsorted = [0x91, 0x82, 0xa1, 0x62, 0x01, 0xa1, 0x61, 0x02];
+    assert!(matches!(canonical(&unsorted, false), Err(Error::NonCanonical(..))));
+    assert!(matches!(canonical(&unsorted, true), Err(Error::NonCanonical(..))));
+
+    // `1` as a `u16`, `"a"` with a `str 8` header, a NaN, and an ext other than a timestamp.
+    for raw in [&[0xcd, 0x00, 0x01][..], &[0xd9, 0x01, 0x61], &[0xca, 0x7f, 0xc0, 0, 0], &[0xd4, 0x05, 0x00]] {
+        assert_eq!(raw.to_vec(), canonical(raw, false).unwrap());
+        assert!(matches!(canonical(raw, true), Err(Error::NonCanonical(..))));
+    }
+}
+
+#[test]
+fn pass_map_hook() {
+    use std::collections::BTreeMap;
+    fn reverse(entries: &mut Vec<(&[u8], &[u8])>) {
+        entries.reverse();
+    }
+
+    let val = BTreeMap::from([(1u8, "a"), (2, "b")]);
+
+    let mut buf = Vec::new();
+    val.serialize(&mut Serializer::new(&mut buf).with_map_hook(reverse)).unwrap();
+    assert_eq!(vec![0x82, 0x02, 0xa1, 0x62, 0x01, 0xa1, 0x61], buf);
+
+    // The hook runs after canonical sorting, and may drop entries.
+    let val = [(2u8, "b"), (1, "a"), (2, "c")];
+    let mut buf = Vec::new();
+    let mut se = Serializer::new(&mut buf).with_canonical().with_map_hook(encode::dedup_map_keys);
+    serde::Serializer::collect_map(&mut se, val.iter().map(|(k, v)| (k, v))).unwrap();
+    assert_eq!(vec![0x82, 0x01, 0xa1, 0x61, 0x02, 0xa1, 0x62], buf);
+}
+
+#[test]
+fn pass_encoded_len() {
+    use std::collections::BTreeMap;
+
+    #[derive(Serialize)]
+    struct Dog<'a> {
+        name: &'a str,
+        tags: BTreeMap<u32, Vec<u8>>,
+    }
+
+    let mut tags = BTreeMap::new();
+    tags.insert(1, vec![1, 2, 3]);
+    tags.insert(100_000, vec![0; 300]);
+    let dog = Dog { name: "Bobby", tags };
+
+    assert_eq!(rmps::to_vec(&dog).unwrap().len(), rmps::encoded_len(&dog).unwrap());
+    assert_eq!(rmps::to_vec_named(&dog).unwrap().len(), encode::encoded_len_named(&dog).unwrap());
+    assert_eq!(1, rmps::encoded_len(&()).unwrap());
+}
+
+#[test]
+fn pass_fixed_int_width() {
+    #[derive(Serialize)]
+    struct Header {
+        id: u32,
+        kind: u8,
+        delta: i16,
+        seq: u64,
+    }
+
+    let header = Header { id: 1, kind: 2, delta: -1, seq: 3 };
+
+    let mut se = Serializer::new(Vec::new()).with_fixed_int_width();
+    header.serialize(&mut se).unwrap();
+    assert_eq!(
+        vec![
+            0x94,
+            0xce, 0x00, 0x00, 0x00, 0x01,
+            0xcc, 0x02,
+            0xd1, 0xff, 0xff,
+            0xcf, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x03,
+        ],
+        se.into_inner()
+    );
+
+    // The default is the smallest representation.
+    assert_eq!(vec![0x94, 0x01, 0x02, 0xff, 0x03], rmp_serde::to_vec(&header).unwrap());
+}
+
+#[test]
+fn pass_seq_writer() {
+    use rmp_serde::encode::SeqWriter;
+    use std::io::Write;
+
+    #[derive(Serialize)]
+    struct Row {
+        id: u32,
+    }
+
+    // Some data written before the array.
+    let mut cur = Cursor::new(Vec::new());
+    cur.write_all(&[0xc0]).unwrap();
+
+    let mut seq = SeqWriter::new(Serializer::new(cur).with_struct_map()).unwrap();
+    assert!(seq.is_empty());
+    for id in 0..2 {
+        seq.push(&Row { id }).unwrap();
+    }
+    assert_eq!(2, seq.len());
+    let mut se = seq.finish().unwrap();
+
+    // The serializer can be used to write more data after the array.
+    true.serialize(&mut se).unwrap();
+
+    assert_eq!(
+        vec![
+            0xc0,
+            0xdd, 0x00, 0x00, 0x00, 0x02,
+            0x81, 0xa2, 0x69, 0x64, 0x00,
+            0x81, 0xa2, 0x69, 0x64, 0x01,
+            0xc3,
+        ],
+        se.into_inner().into_inner()
+    );
+}
+
+#[test]
+fn pass_with_buffer_coalesces_small_writes() {
+    struct CountingWriter {
+        buf: Vec<u8>,
+        writes: usize,
+    }
+
+    impl std::io::Write for CountingWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.writes += 1;
+            self.buf.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let val = vec![(1u8, "a"); 100];
+
+    // Without a buffer, every marker and value is a separate write.
+    let mut wr = CountingWriter { buf: Vec::new(), writes: 0 };
+    rmp_serde::to_writer(&mut wr, &val).unwrap();
+    assert!(wr.writes > 100);
+
+    let mut se = Serializer::new(CountingWriter { buf: Vec::new(), writes: 0 }).with_buffer(1024);
+    val.serialize(&mut se).unwrap();
+    let wr = se.into_inner().into_inner().ok().unwrap();
+    assert_eq!(1, wr.writes);
+    assert_eq!(rmp_serde::to_vec(&val).unwrap(), wr.buf);
+
+    // Dropping the serializer flushes the buffer as well.
+    let mut wr = CountingWriter { buf: Vec::new(), writes: 0 };
+    val.serialize(&mut Serializer::new(&mut wr).with_struct_map().with_buffer(16)).unwrap();
+    assert_eq!(rmp_serde::to_vec(&val).unwrap(), wr.buf);
+}
+
+#[test]
+fn pass_serializer_ext() {
+    use rmp_serde::SerializerExt;
+
+    struct Stamp(Vec<u8>);
+
+    impl Serialize for Stamp {
+        fn serialize<S: serde::Serializer>(&self, se: S) -> Result<S::Ok, S::Error> {
+            se.serialize_ext(-1, &self.0)
+        }
+    }
+
+    let buf = rmps::to_vec(&(1, Stamp(vec![0xaa; 3]))).unwrap();
+    assert_eq!(vec![0x92, 0x01, 0xc7, 0x03, 0xff, 0xaa, 0xaa, 0xaa], buf);
+
+    let val: (u8, rmp_serde::Ext) = rmps::from_slice(&buf).unwrap();
+    assert_eq!(rmp_serde::Ext(-1, vec![0xaa; 3]), val.1);
+}
+
+#[test]
+fn fail_error_kind() {
+    use rmp_serde::ErrorKind;
+
+    let mut buf = [0u8; 2];
+    let err = "hello".serialize(&mut Serializer::new(&mut buf[..])).unwrap_err();
+    assert_eq!(ErrorKind::Io, err.kind());
+    assert_eq!(std::io::ErrorKind::WriteZero, err.io_error().unwrap().kind());
+
+    struct Fails;
+
+    impl Serialize for Fails {
+        fn serialize<S: serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+            Err(serde::ser::Error::custom("nope"))
+        }
+    }
+
+    let err = rmps::to_vec(&Fails).unwrap_err();
+    assert_eq!(ErrorKind::Syntax, err.kind());
+    assert!(err.io_error().is_none());
+}
+
+#[test]
+fn pass_to_writer() {
+    use rmp_serde::config::{DefaultConfig, StructMapConfig};
+
+    #[derive(Serialize)]
+    struct Point {
+        x: u8,
+    }
+
+    let mut buf = Vec::new();
+    rmp_serde::to_writer(&mut buf, &Point { x: 1 }).unwrap();
+    assert_eq!(vec![0x91, 0x01], buf);
+
+    let mut buf = Vec::new();
+    rmp_serde::to_writer_named(&mut buf, &Point { x: 1 }).unwrap();
+    assert_eq!(vec![0x81, 0xa1, 0x78, 0x01], buf);
+
+    let mut buf = Vec::new();
+    rmp_serde::to_writer_with(&mut buf, &Point { x: 1 }, StructMapConfig::new(DefaultConfig)).unwrap();
+    assert_eq!(rmp_serde::to_vec_with(&Point { x: 1 }, StructMapConfig::new(DefaultConfig)).unwrap(), buf);
+    assert_eq!(rmp_serde::to_vec_named(&Point { x: 1 }).unwrap(), buf);
+}
+
+#[test]
+fn pass_to_uninit_slice() {
+    use std::mem::MaybeUninit;
+
+    let val = (42u8, "le message", [1u8, 2, 3]);
+    let expected = rmp_serde::to_vec(&val).unwrap();
+
+    let mut buf = [MaybeUninit::<u8>::uninit(); 32];
+    let len = encode::to_uninit_slice(&mut buf, &val).unwrap();
+    assert_eq!(expected.len(), len);
+    let written: Vec<u8> = buf[..len].iter().map(|b| unsafe { b.assume_init() }).collect();
+    assert_eq!(expected, written);
+
+    match encode::to_uninit_slice(&mut buf[..len - 1], &val) {
+        Err(Error::InvalidValueWrite(..)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let mut arena = [MaybeUninit::<u8>::uninit(); 64];
+    let len = encode::to_uninit_slice_with(&val, |len| &mut arena[8..8 + len]).unwrap();
+    assert_eq!(expected.len(), len);
+    let written: Vec<u8> = arena[8..8 + len].iter().map(|b| unsafe { b.assume_init() }).collect();
+    assert_eq!(expected, written);
+
+    let mut arena = [MaybeUninit::<u8>::uninit(); 64];
+    assert!(encode::to_uninit_slice_with(&val, |_| &mut arena[..]).is_err());
+
+    // A value that serializes shorter the second time must not leave uninitialized bytes behind.
+    struct Shrinking(std::cell::Cell<u32>);
+
+    impl Serialize for Shrinking {
+        fn serialize<S: serde::Serializer>(&self, se: S) -> Result<S::Ok, S::Error> {
+            let n = self.0.get();
+            self.0.set(0);
+            se.serialize_u32(n)
+        }
+    }
+
+    let mut arena = [MaybeUninit::<u8>::uninit(); 64];
+    match encode::to_uninit_slice_with(&Shrinking(std::cell::Cell::new(1000)), |len| &mut arena[..len]) {
+        Err(Error::InvalidValueWrite(..)) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn pass_serialize_seq_exact() {
+    // Only knows its length from elsewhere, like a record count in a header.
+    struct Records(u32);
+
+    impl Serialize for Records {
+        fn serialize<S: serde::Serializer>(&self, se: S) -> Result<S::Ok, S::Error> {
+            let mut next = 0;
+            let items = std::iter::from_fn(|| {
+                next += 1;
+                (next <= 3).then_some(next)
+            });
+            encode::serialize_seq_exact(se, items, self.0 as usize)
+        }
+    }
+
+    assert_eq!(rmp_serde::to_vec(&[1, 2, 3]).unwrap(), rmp_serde::to_vec(&Records(3)).unwrap());
+
+    match rmp_serde::to_vec(&Records(4)) {
+        Err(Error::Syntax(msg)) => assert_eq!("sequence has 3 elements, but 4 were declared", msg),
+        other => panic!("unexpected result: {:?}", other),
+    }
+    assert!(rmp_serde::to_vec(&Records(2)).is_err());
+
+    let mut buf = Vec::new();
+    encode::serialize_seq_exact(&mut Serializer::new(&mut buf), Vec::<u8>::new(), 0).unwrap();
+    assert_eq!(vec![0x90], buf);
+}
+
+#[test]
+fn pass_bytes_written() {
+    use std::collections::BTreeMap;
+
+    use rmp_serde::encode::SeqWriter;
+
+    #[derive(Serialize)]
+    struct Inner {
+        c: u8,
+    }
+
+    #[derive(Serialize)]
+    struct Outer {
+        a: rmp_serde::Ext,
+        #[serde(flatten)]
+        inner: Inner,
+    }
+
+    let mut map = BTreeMap::new();
+    map.insert("k", vec![1u8, 2, 3]);
+    let outer = Outer { a: rmp_serde::Ext(1, vec![0xaa; 2]), inner: Inner { c: 7 } };
+
+    // Counts bytes written through length headers, ext values and buffered maps alike.
+    let mut se = Serializer::new(Vec::new()).with_struct_map();
+    let mut offsets = Vec::new();
+    map.serialize(&mut se).unwrap();
+    offsets.push(se.bytes_written());
+    outer.serialize(&mut se).unwrap();
+    offsets.push(se.bytes_written());
+    assert_eq!(se.get_ref().len() as u64, offsets[1]);
+    assert_eq!(rmp_serde::to_vec(&map).unwrap().len() as u64, offsets[0]);
+
+    se.reset_bytes_written();
+    assert_eq!(0, se.bytes_written());
+    "abc".serialize(&mut se).unwrap();
+    assert_eq!(4, se.bytes_written());
+
+    // Patching the length of an array doesn't count again.
+    let mut seq = SeqWriter::new(Serializer::new(Cursor::new(Vec::new()))).unwrap();
+    seq.push(&1u8).unwrap();
+    seq.push(&300u16).unwrap();
+    let se = seq.finish().unwrap();
+    assert_eq!(9, se.bytes_written());
+    assert_eq!(9, se.into_inner().into_inner().len());
+}
+
+#[test]
+fn pass_container_headers_with_serialized_elements() {
+    #[derive(Serialize)]
+    struct Body {
+        id: u32,
+        tags: Vec<&'static str>,
+    }
+
+    // {"v" => 1, "body" => {"id" => 7, "tags" => ["a"]}}
+    let mut se = Serializer::new(Vec::new()).with_struct_map();
+    se.serialize_map_header(2).unwrap();
+    "v".serialize(&mut se).unwrap();
+    1u8.serialize(&mut se).unwrap();
+    "body".serialize(&mut se).unwrap();
+    Body { id: 7, tags: vec!["a"] }.serialize(&mut se).unwrap();
+    assert_eq!(se.get_ref().len() as u64, se.bytes_written());
+
+    let val: rmpv::Value = rmp_serde::from_slice(se.get_ref()).unwrap();
+    assert_eq!(rmpv::Value::from(1), val["v"]);
+    assert_eq!(rmpv::Value::from(7), val["body"]["id"]);
+
+    let mut se = Serializer::new(Vec::new());
+    se.serialize_array_header(0).unwrap();
+    assert_eq!(vec![0x90], se.into_inner());
+}
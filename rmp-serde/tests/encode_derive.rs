@@ -353,3 +353,56 @@ fn pass_struct_as_map_using_triple_ext_many_times() {
     assert_eq!(vec![0x82, 0xa4, 0x6e, 0x61, 0x6d, 0x65, 0xa5, 0x42, 0x6f, 0x62, 0x62, 0x79, 0xa3, 0x61, 0x67, 0x65, 0x08],
                se.into_inner());
 }
+
+#[test]
+fn pass_struct_with_flattened_field_flatten_mode() {
+    use rmp_serde::config::FlattenMode;
+    use rmp_serde::encode::Error;
+
+    #[derive(Serialize)]
+    struct Point {
+        x: u8,
+    }
+
+    #[derive(Serialize)]
+    struct Inner {
+        b: u8,
+    }
+
+    #[derive(Serialize)]
+    struct Outer {
+        a: Point,
+        #[serde(flatten)]
+        inner: Inner,
+    }
+
+    let val = Outer { a: Point { x: 1 }, inner: Inner { b: 2 } };
+
+    let mut se = Serializer::new(Vec::new());
+    val.serialize(&mut se).unwrap();
+
+    // Expect: {"a": [1], "b": 2}.
+    assert_eq!(vec![0x82, 0xa1, 0x61, 0x91, 0x01, 0xa1, 0x62, 0x02], se.into_inner());
+
+    let mut se = Serializer::new(Vec::new()).with_flatten(FlattenMode::NamedSubtree);
+    val.serialize(&mut se).unwrap();
+
+    // Expect: {"a": {"x": 1}, "b": 2}.
+    assert_eq!(vec![0x82, 0xa1, 0x61, 0x81, 0xa1, 0x78, 0x01, 0xa1, 0x62, 0x02], se.into_inner());
+
+    let mut se = Serializer::new(Vec::new()).with_flatten(FlattenMode::Error);
+    match val.serialize(&mut se) {
+        Err(Error::FlattenedStruct) => {}
+        other => panic!("unexpected result: {other:?}"),
+    }
+    assert!(se.into_inner().is_empty());
+
+    // Structs written as maps are not affected.
+    let mut se = Serializer::new(Vec::new()).with_struct_map().with_flatten(FlattenMode::Error);
+    val.serialize(&mut se).unwrap();
+
+    // Neither are maps of unknown length collected from iterators.
+    let mut se = Serializer::new(Vec::new()).with_flatten(FlattenMode::Error);
+    serde::Serializer::collect_map(&mut se, (1u8..4).filter(|k| k % 2 == 1).map(|k| (k, k))).unwrap();
+    assert_eq!(vec![0x82, 0x01, 0x01, 0x03, 0x03], se.into_inner());
+}
@@ -39,6 +39,47 @@ fn issue_250() {
     assert_eq!(Example::Unit1, ex);
 }
 
+#[test]
+fn round_tagged_enums_named() {
+    use rmp_serde::Named;
+
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    pub struct Inner {
+        a: u32,
+        b: String,
+    }
+
+    macro_rules! tagged_enum {
+        ($name:ident $(, $attr:meta)?) => {
+            #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+            $(#[$attr])?
+            enum $name {
+                Unit,
+                Struct { x: u32 },
+                Newtype(Inner),
+            }
+
+            let vals = vec![
+                $name::Unit,
+                $name::Struct { x: 3 },
+                $name::Newtype(Inner { a: 1, b: "le message".into() }),
+            ];
+            for val in vals {
+                let wrapped = (42, Named(val.clone()));
+                let buf = rmp_serde::to_vec(&wrapped).unwrap();
+                // Only the enum is encoded with field names.
+                assert_eq!(0x92, buf[0]);
+                assert_eq!(wrapped, rmp_serde::from_slice(&buf).unwrap(), "{}", stringify!($name));
+            }
+        };
+    }
+
+    tagged_enum!(External);
+    tagged_enum!(Internal, serde(tag = "type"));
+    tagged_enum!(Adjacent, serde(tag = "type", content = "payload"));
+    tagged_enum!(Untagged, serde(untagged));
+}
+
 #[test]
 fn round_trip_option() {
     #[derive(Debug, PartialEq, Serialize, Deserialize)]
@@ -563,6 +604,31 @@ fn roundtrip_ipaddr_ipv6addr() {
     assert_roundtrips(IpAddr::V6(Ipv6Addr::new(1, 2, 3, 4, 5, 6, 7, 8)));
 }
 
+#[test]
+fn roundtrip_ipv4addr_set_human_readable() {
+    let val = Ipv4Addr::new(127, 0, 0, 1);
+
+    let mut se = Serializer::new(Vec::new());
+    se.set_human_readable(true);
+    val.serialize(&mut se).unwrap();
+    let buf = se.into_inner();
+
+    // Expect: "127.0.0.1".
+    assert_eq!(&[0xa9, 0x31, 0x32, 0x37, 0x2e, 0x30, 0x2e, 0x30, 0x2e, 0x31], &buf[..]);
+
+    let mut de = Deserializer::new(&buf[..]);
+    de.set_human_readable(true);
+    assert_eq!(val, Ipv4Addr::deserialize(&mut de).unwrap());
+
+    let mut se = Serializer::new(Vec::new()).with_human_readable();
+    se.set_human_readable(false);
+    val.serialize(&mut se).unwrap();
+    let buf = se.into_inner();
+
+    // Expect: [127, 0, 0, 1].
+    assert_eq!(&[0x94, 0x7f, 0x00, 0x00, 0x01], &buf[..]);
+}
+
 #[test]
 fn roundtrip_result_ipv4addr() {
     let val: Result<Ipv4Addr, ()> = Ok(Ipv4Addr::new(127, 0, 0, 1));
@@ -659,6 +725,36 @@ fn i128_from_integers() {
     assert_eq!(arr, [0, 1i128, -12, 119]);
 }
 
+#[test]
+fn roundtrip_ext() {
+    use rmp_serde::{Ext, ExtRef};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Packet<'a> {
+        id: u32,
+        #[serde(borrow)]
+        payload: ExtRef<'a>,
+    }
+
+    // Expect: fixext4 with type 42.
+    let buf = rmp_serde::to_vec(&Ext(42, vec![1, 2, 3, 4])).unwrap();
+    assert_eq!(vec![0xd6, 0x2a, 0x01, 0x02, 0x03, 0x04], buf);
+    assert_eq!(ExtRef(42, &[1, 2, 3, 4][..]), rmp_serde::from_slice(&buf).unwrap());
+
+    // Expect: ext8 with type -2.
+    let buf = rmp_serde::to_vec(&ExtRef(-2, &[5; 3])).unwrap();
+    assert_eq!(vec![0xc7, 0x03, 0xfe, 0x05, 0x05, 0x05], buf);
+    assert_eq!(Ext(-2, vec![5; 3]), rmp_serde::from_read(&buf[..]).unwrap());
+
+    let data = vec![7; 300];
+    let packet = Packet { id: 1, payload: ExtRef(100, &data) };
+    let buf = rmp_serde::to_vec(&packet).unwrap();
+    assert_eq!(packet, rmp_serde::from_slice(&buf).unwrap());
+
+    assert_roundtrips(Ext(0, Vec::new()));
+    assert_roundtrips(vec![Ext(1, vec![1]), Ext(2, vec![0; 16]), Ext(3, vec![0; 70000])]);
+}
+
 #[ignore]
 #[test]
 fn roundtrip_some_failures() {
@@ -762,3 +858,434 @@ fn assert_roundtrips_config<T, CSF, SC, CDF, DC>(
 
     assert_eq!(val, &val2, "Config: {}", desc);
 }
+
+#[test]
+fn i128_extremes() {
+    assert_roundtrips([i128::MIN, -1, i128::MAX]);
+    assert_roundtrips([0u128, u128::MAX, u128::MAX / 2 + 1]);
+
+    let buf = rmp_serde::to_vec(&-1i8).unwrap();
+    assert!(rmp_serde::from_slice::<u128>(&buf).is_err());
+}
+
+#[test]
+fn round_floats_bit_exact() {
+    use rmp_serde::config::FloatMode;
+
+    let vals = [
+        0.0,
+        -0.0,
+        1.5,
+        0.1,
+        f64::MAX,
+        f64::INFINITY,
+        f64::NAN,
+        f64::from_bits(0x7ff8_0000_dead_beef),
+        f64::from_bits(0xfff0_0000_0000_0001),
+    ];
+
+    for mode in [FloatMode::Exact, FloatMode::Compact] {
+        for val in vals {
+            let mut buf = Vec::new();
+            val.serialize(&mut Serializer::new(&mut buf).with_floats(mode)).unwrap();
+
+            let narrows = f64::from(val as f32).to_bits() == val.to_bits();
+            let expected_len = if mode == FloatMode::Compact && narrows { 5 } else { 9 };
+            assert_eq!(expected_len, buf.len(), "{val:?} with {mode:?}");
+
+            let decoded: f64 = rmp_serde::from_slice(&buf).unwrap();
+            assert_eq!(val.to_bits(), decoded.to_bits(), "{val:?} with {mode:?}");
+        }
+    }
+
+    let mut buf = Vec::new();
+    (-0.0f64).serialize(&mut Serializer::new(&mut buf).with_floats(FloatMode::Compact)).unwrap();
+    assert_eq!(vec![0xca, 0x80, 0x00, 0x00, 0x00], buf);
+
+    let mut buf = Vec::new();
+    0.1f64.serialize(&mut Serializer::new(&mut buf).with_floats(FloatMode::Compact)).unwrap();
+    assert_eq!(9, buf.len());
+}
+
+#[test]
+fn round_nested_options() {
+    use rmp_serde::config::OptionMode;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Patch {
+        name: Option<Option<String>>,
+        tags: Option<Vec<u8>>,
+    }
+
+    let vals = [
+        Patch { name: None, tags: None },
+        Patch { name: Some(None), tags: Some(vec![1]) },
+        Patch { name: Some(Some("a".into())), tags: Some(vec![]) },
+    ];
+    for val in &vals {
+        let mut buf = Vec::new();
+        val.serialize(&mut Serializer::new(&mut buf).with_option_mode(OptionMode::Array)).unwrap();
+        let mut de = Deserializer::new(&buf[..]).with_option_mode(OptionMode::Array);
+        assert_eq!(*val, Patch::deserialize(&mut de).unwrap());
+    }
+
+    // By default `Some(None)` is lost.
+    let buf = rmp_serde::to_vec(&vals[1]).unwrap();
+    assert_eq!(Patch { name: None, tags: Some(vec![1]) }, rmp_serde::from_slice(&buf).unwrap());
+
+    // Options written in the default mode are still accepted, as long as they aren't 1-element arrays.
+    let buf = rmp_serde::to_vec(&vals[2]).unwrap();
+    let mut de = Deserializer::new(&buf[..]).with_option_mode(OptionMode::Array);
+    assert_eq!(vals[2], Patch::deserialize(&mut de).unwrap());
+}
+
+#[test]
+fn round_enum_variant_indices() {
+    use rmp_serde::config::VariantMode;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Opcode {
+        Nop,
+        Read,
+        Write(u8),
+        Seek { pos: u32 },
+    }
+
+    let vals = [Opcode::Nop, Opcode::Read, Opcode::Write(7), Opcode::Seek { pos: 300 }];
+    let mut buf = Vec::new();
+    let mut se = Serializer::new(&mut buf).with_variant_mode(VariantMode::Index);
+    for val in &vals {
+        val.serialize(&mut se).unwrap();
+    }
+    assert_eq!(vec![0x00, 0x01, 0x81, 0x02, 0x07, 0x81, 0x03, 0x91, 0xcd, 0x01, 0x2c], buf);
+
+    let decoded: Vec<Opcode> = rmp_serde::from_slice_all(&buf).unwrap();
+    assert_eq!(&vals[..], &decoded[..]);
+
+    // Plain discriminants written by other encoders.
+    assert_eq!(Opcode::Read, rmp_serde::from_slice(&[0xcc, 0x01]).unwrap());
+    assert!(rmp_serde::from_slice::<Opcode>(&[0x04]).is_err());
+}
+
+#[test]
+fn round_enum_compat_v08() {
+    use rmp_serde::config::CompatMode;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Shape {
+        Empty,
+        Circle(u32),
+        Rect(u8, u8),
+        Poly { sides: u8 },
+    }
+
+    let vals = vec![Shape::Empty, Shape::Circle(300), Shape::Rect(1, 2), Shape::Poly { sides: 5 }];
+    let mut buf = Vec::new();
+    vals.serialize(&mut Serializer::new(&mut buf).with_compat_mode(CompatMode::V08)).unwrap();
+    assert_eq!(vec![
+        0x94,
+        0x92, 0x00, 0x90,
+        0x92, 0x01, 0x91, 0xcd, 0x01, 0x2c,
+        0x92, 0x02, 0x92, 0x01, 0x02,
+        0x92, 0x03, 0x91, 0x05,
+    ], buf);
+
+    let mut de = Deserializer::new(&buf[..]).with_compat_mode(CompatMode::V08);
+    assert_eq!(vals, Vec::<Shape>::deserialize(&mut de).unwrap());
+
+    // Enums in the current format are still read.
+    let buf = rmp_serde::to_vec(&vals).unwrap();
+    let mut de = Deserializer::new(&buf[..]).with_compat_mode(CompatMode::V08);
+    assert_eq!(vals, Vec::<Shape>::deserialize(&mut de).unwrap());
+
+    // A unit variant with data.
+    let mut de = Deserializer::new(&[0x92, 0x00, 0x91, 0x01][..]).with_compat_mode(CompatMode::V08);
+    assert!(Shape::deserialize(&mut de).is_err());
+}
+
+#[test]
+fn round_typed_ext() {
+    use rmp_serde::{Ext, TypedExt};
+
+    let val: TypedExt<-5, Vec<u8>> = TypedExt(vec![1, 2, 3]);
+    let buf = rmp_serde::to_vec(&val).unwrap();
+    assert_eq!(Ext(-5, vec![1, 2, 3]), rmp_serde::from_slice(&buf).unwrap());
+    assert_eq!(val, rmp_serde::from_slice(&buf).unwrap());
+
+    let err = rmp_serde::from_slice::<TypedExt<4, Vec<u8>>>(&buf).unwrap_err();
+    assert_eq!("expected ext type 4, got -5", err.into_inner().to_string());
+
+    // The data doesn't fit the wrapped type.
+    assert!(rmp_serde::from_slice::<TypedExt<-5, [u8; 2]>>(&buf).is_err());
+}
+
+#[test]
+fn round_rmpv_value_matches_write_value() {
+    use rmpv::Value;
+
+    // A string with invalid UTF-8 is kept as a str.
+    let invalid = rmpv::decode::read_value(&mut &[0xa2, 0x61, 0xff][..]).unwrap();
+    let val = Value::Array(vec![
+        Value::Nil,
+        Value::from(true),
+        Value::from(42),
+        Value::from(-300),
+        Value::F32(1.5),
+        Value::F64(0.1),
+        Value::from("ü"),
+        Value::Binary(vec![1, 2, 3]),
+        Value::Ext(-5, vec![1, 2, 3]),
+        Value::Ext(3, vec![0; 16]),
+        Value::Map(vec![(Value::from(1), Value::from("a"))]),
+        invalid,
+    ]);
+
+    let mut expected = Vec::new();
+    rmpv::encode::write_value(&mut expected, &val).unwrap();
+    assert_eq!(expected, rmp_serde::to_vec(&val).unwrap());
+    assert_eq!(expected, rmp_serde::to_vec(&val.as_ref()).unwrap());
+
+    // Embedded in a derived struct, the value is written the same way, and read back.
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Envelope {
+        id: u32,
+        payload: Value,
+    }
+
+    let val = Envelope {
+        id: 1,
+        payload: Value::Array(vec![Value::Binary(vec![1, 2, 3]), Value::Ext(-5, vec![1, 2, 3]), Value::from("a")]),
+    };
+    let buf = rmp_serde::to_vec(&val).unwrap();
+    let mut expected = vec![0x92, 0x01];
+    rmpv::encode::write_value(&mut expected, &val.payload).unwrap();
+    assert_eq!(expected, buf);
+    assert_eq!(val, rmp_serde::from_slice(&buf).unwrap());
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn round_zstd() {
+    use rmp_serde::compress::{from_slice_zstd, to_vec_zstd};
+    use rmp_serde::decode::Error;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Blob {
+        name: String,
+        data: Vec<u32>,
+    }
+
+    let val = Blob { name: "blob".into(), data: (0..10_000).collect() };
+    let buf = to_vec_zstd(&val, 0).unwrap();
+    assert_eq!(val, from_slice_zstd::<Blob>(&buf).unwrap());
+
+    assert!(from_slice_zstd::<Blob>(&buf[..buf.len() - 1]).is_err());
+
+    // Two values, 1 and 2, in one frame.
+    let buf = zstd::encode_all(&[0x01, 0x02][..], 0).unwrap();
+    assert!(matches!(from_slice_zstd::<u8>(&buf), Err(Error::TrailingBytes(_))));
+}
+
+#[cfg(feature = "lz4")]
+#[test]
+fn round_lz4() {
+    use rmp_serde::compress::{from_slice_lz4, to_vec_lz4};
+
+    let val: Vec<String> = (0..1000).map(|idx| format!("item {idx}")).collect();
+    let buf = to_vec_lz4(&val).unwrap();
+    assert!(buf.len() < rmp_serde::to_vec(&val).unwrap().len());
+    assert_eq!(val, from_slice_lz4::<Vec<String>>(&buf).unwrap());
+
+    assert!(from_slice_lz4::<Vec<String>>(&buf[..buf.len() / 2]).is_err());
+}
+
+#[cfg(feature = "tokio-util")]
+#[test]
+fn round_codec() {
+    use bytes::BytesMut;
+    use rmp_serde::codec::RmpCodec;
+    use rmp_serde::decode::Error;
+    use tokio_util::codec::{Decoder, Encoder};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Msg {
+        id: u32,
+        data: Vec<u8>,
+    }
+
+    let msgs: Vec<Msg> = (0..3).map(|id| Msg { id, data: vec![id as u8; 300] }).collect();
+
+    let mut codec = RmpCodec::<Msg>::new();
+    let mut encoded = BytesMut::new();
+    for msg in &msgs {
+        codec.encode(Msg { id: msg.id, data: msg.data.clone() }, &mut encoded).unwrap();
+    }
+
+    // Feed the stream one byte at a time.
+    let mut buf = BytesMut::new();
+    let mut decoded = Vec::new();
+    for &byte in encoded.iter() {
+        buf.extend_from_slice(&[byte]);
+        while let Some(msg) = codec.decode(&mut buf).unwrap() {
+            decoded.push(msg);
+        }
+    }
+    assert_eq!(msgs, decoded);
+    assert!(buf.is_empty());
+
+    // The frame length is known from the bin header.
+    let mut codec = RmpCodec::<Msg>::new().with_max_frame_len(100);
+    let mut buf = BytesMut::from(&encoded[..6]);
+    assert!(matches!(codec.decode(&mut buf), Err(Error::LengthLimitExceeded(_))));
+
+    let mut codec = RmpCodec::<Msg>::new();
+    let mut buf = BytesMut::from(&[0xc1][..]);
+    assert!(codec.decode(&mut buf).is_err());
+}
+
+#[cfg(feature = "rpc")]
+#[tokio::test(flavor = "current_thread")]
+async fn round_rpc_messages() {
+    use rmp_serde::rpc::{Connection, Message, Notification, Request};
+    use rmp_serde::RawValue;
+
+    let req = Message::Request(Request {
+        id: 7,
+        method: "sum".into(),
+        params: RawValue::from_value(&(1, 2)).unwrap(),
+    });
+    assert_eq!(vec![0x94, 0x00, 0x07, 0xa3, 0x73, 0x75, 0x6d, 0x92, 0x01, 0x02], rmp_serde::to_vec(&req).unwrap());
+    assert_eq!(req, rmp_serde::from_slice(&rmp_serde::to_vec(&req).unwrap()).unwrap());
+
+    let (client, server) = tokio::io::duplex(64);
+    let (rd, wr) = tokio::io::split(client);
+    let mut client = Connection::new(rd, wr);
+    let (rd, wr) = tokio::io::split(server);
+    let mut server = Connection::new(rd, wr);
+
+    let serve = async {
+        server.notify("progress", &("started",)).await.unwrap();
+        for _ in 0..2 {
+            let req = match server.next_message().await.unwrap() {
+                Some(Message::Request(req)) => req,
+                msg => panic!("unexpected message: {msg:?}"),
+            };
+            let (a, b): (u32, u32) = req.params().unwrap();
+            match a.checked_add(b) {
+                Some(sum) => server.respond::<_, str>(req.id, Ok(&sum)).await.unwrap(),
+                None => server.respond::<u32, _>(req.id, Err("overflow")).await.unwrap(),
+            }
+        }
+    };
+    let call = async {
+        let sum = client.call::<_, u32>("sum", &(1, 2)).await.unwrap().unwrap();
+        let err = client.call::<_, u32>("sum", &(u32::MAX, 1)).await.unwrap().unwrap_err();
+        (sum, err)
+    };
+    let ((), (sum, err)) = tokio::join!(serve, call);
+    assert_eq!(3, sum);
+    assert_eq!("overflow", rmp_serde::from_slice::<String>(err.as_bytes()).unwrap());
+
+    // The notification arrived while waiting for the first response.
+    let notif = match client.next_message().await.unwrap() {
+        Some(Message::Notification(notif)) => notif,
+        msg => panic!("unexpected message: {msg:?}"),
+    };
+    assert_eq!(Notification { method: "progress".into(), params: RawValue::from_value(&("started",)).unwrap() }, notif);
+
+    drop(server);
+    assert!(client.next_message().await.unwrap().is_none());
+}
+
+#[test]
+fn round_raw_value() {
+    use rmp_serde::{RawValue, RawValueRef};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Envelope {
+        id: u32,
+        payload: RawValue,
+        extra: Option<RawValue>,
+    }
+
+    // Encoded `[7, {"a": [1, b"\x00"]}, nil]`.
+    let buf = [0x93, 0x07, 0x81, 0xa1, 0x61, 0x92, 0x01, 0xc4, 0x01, 0x00, 0xc0];
+
+    let val: Envelope = rmp_serde::from_slice(&buf).unwrap();
+    assert_eq!(&buf[2..10], val.payload.as_bytes());
+    assert_eq!(None, val.extra);
+    assert_eq!(&buf[..], &rmp_serde::to_vec(&val).unwrap()[..]);
+
+    // The same bytes are captured from a reader.
+    let mut de = Deserializer::new(&buf[..]);
+    assert_eq!(val, Envelope::deserialize(&mut de).unwrap());
+
+    // A present optional value includes its already peeked marker.
+    let val = Envelope {
+        id: 1,
+        payload: RawValue::from_value(&"hi").unwrap(),
+        extra: Some(RawValue::from_value(&[1, 2]).unwrap()),
+    };
+    let buf = rmp_serde::to_vec(&val).unwrap();
+    assert_eq!(vec![0x93, 0x01, 0xa2, 0x68, 0x69, 0x92, 0x01, 0x02], buf);
+    assert_eq!(val, rmp_serde::from_slice(&buf).unwrap());
+    assert_eq!(val, Envelope::deserialize(&mut Deserializer::new(&buf[..])).unwrap());
+
+    // Borrowed values point into the input.
+    let raw: Vec<RawValueRef<'_>> = rmp_serde::from_slice(&buf).unwrap();
+    assert_eq!(vec![&buf[1..2], &buf[2..5], &buf[5..]], raw.iter().map(RawValueRef::as_bytes).collect::<Vec<_>>());
+    assert_eq!(buf, rmp_serde::to_vec(&raw).unwrap());
+
+    assert!(RawValue::from_vec(vec![0x92, 0x01]).is_err());
+    assert!(matches!(RawValue::from_vec(vec![0x01, 0x02]), Err(rmp_serde::decode::Error::TrailingBytes(1))));
+    assert!(RawValueRef::from_slice(&[0xc1]).is_err());
+}
+
+#[test]
+fn round_field_table() {
+    use rmp_serde::decode::Error;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Inner {
+        b: u8,
+        a: u8,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Outer {
+        inner: Inner,
+        extra: u8,
+        list: Vec<Inner>,
+    }
+
+    // Both structs declare their fields in a different order than their tables, and `extra` is
+    // missing from the table of `Outer`.
+    fn fields(name: &str) -> Option<&'static [&'static str]> {
+        match name {
+            "Inner" => Some(&["a", "b"]),
+            "Outer" => Some(&["list", "inner"]),
+            _ => None,
+        }
+    }
+
+    let val = Outer { inner: Inner { b: 1, a: 2 }, extra: 3, list: vec![Inner { b: 4, a: 5 }] };
+    let mut buf = Vec::new();
+    val.serialize(&mut Serializer::new(&mut buf).with_field_table(fields)).unwrap();
+    // {1: {1: 1, 0: 2}, "extra": 3, 0: [{1: 4, 0: 5}]}
+    assert_eq!(vec![
+        0x83, 0x01, 0x82, 0x01, 0x01, 0x00, 0x02, 0xa5, b'e', b'x', b't', b'r', b'a', 0x03,
+        0x00, 0x91, 0x82, 0x01, 0x04, 0x00, 0x05,
+    ], buf);
+
+    let mut de = Deserializer::new(&buf[..]).with_field_table(fields);
+    assert_eq!(val, Outer::deserialize(&mut de).unwrap());
+
+    // Data with field names can be read as well.
+    let named = rmp_serde::to_vec_named(&val).unwrap();
+    let mut de = Deserializer::new(&named[..]).with_field_table(fields);
+    assert_eq!(val, Outer::deserialize(&mut de).unwrap());
+
+    // {5: 1}
+    let mut de = Deserializer::new(&[0x81, 0x05, 0x01][..]).with_field_table(fields);
+    assert!(matches!(Inner::deserialize(&mut de), Err(Error::Syntax(..))));
+}
@@ -0,0 +1,79 @@
+use rmp_serde::transcode::transcode;
+use rmp_serde::{Deserializer, Serializer};
+
+#[test]
+fn pass_transcode_binary_target_keeps_bin_and_ext() {
+    // Encoded `[b"\x00\xff", ext(5, b"\x01"), {1: "one"}, -1, 1.5]`.
+    let buf = [
+        0x95, 0xc4, 0x02, 0x00, 0xff, 0xd4, 0x05, 0x01, 0x81, 0x01, 0xa3, 0x6f, 0x6e, 0x65, 0xff,
+        0xcb, 0x3f, 0xf8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ];
+
+    let mut de = Deserializer::from_read_ref(&buf);
+    let mut se = Serializer::new(Vec::new());
+    transcode(&mut de, &mut se).unwrap();
+
+    assert_eq!(&buf[..], &se.into_inner()[..]);
+}
+
+#[test]
+fn pass_transcode_human_readable_target_converts_bin_and_ext() {
+    // Encoded `[b"\x00\xff", ext(5, b"\x01")]`.
+    let buf = [0x92, 0xc4, 0x02, 0x00, 0xff, 0xd4, 0x05, 0x01];
+
+    let mut de = Deserializer::new(&buf[..]);
+    let mut se = Serializer::new(Vec::new()).with_human_readable();
+    transcode(&mut de, &mut se).unwrap();
+
+    // Expect: ["AP8=", {"type": 5, "data": "AQ=="}].
+    assert_eq!(
+        vec![
+            0x92, 0xa4, 0x41, 0x50, 0x38, 0x3d, 0x82, 0xa4, 0x74, 0x79, 0x70, 0x65, 0x05, 0xa4,
+            0x64, 0x61, 0x74, 0x61, 0xa4, 0x41, 0x51, 0x3d, 0x3d,
+        ],
+        se.into_inner()
+    );
+}
+
+#[test]
+fn fail_transcode_truncated() {
+    // Encoded `[1, 2]` with the last element missing.
+    let buf = [0x92, 0x01];
+
+    let mut de = Deserializer::from_read_ref(&buf);
+    let mut se = Serializer::new(Vec::new());
+    assert!(transcode(&mut de, &mut se).is_err());
+}
+
+#[test]
+fn pass_reencode_struct_layout() {
+    use rmp_serde::transcode::StructLayout;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Inner {
+        name: String,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Outer {
+        id: u32,
+        inner: Vec<Inner>,
+    }
+
+    let val = Outer { id: 7, inner: vec![Inner { name: "a".into() }, Inner { name: "b".into() }] };
+    let positional = rmp_serde::to_vec(&val).unwrap();
+    let named = rmp_serde::to_vec_named(&val).unwrap();
+
+    assert_eq!(named, rmp_serde::reencode::<Outer>(&positional, StructLayout::Map).unwrap());
+    assert_eq!(positional, rmp_serde::reencode::<Outer>(&named, StructLayout::Array).unwrap());
+    assert_eq!(named, rmp_serde::reencode::<Outer>(&named, StructLayout::Map).unwrap());
+
+    // Trailing data is not silently dropped.
+    let mut buf = positional.clone();
+    buf.push(0xc0);
+    match rmp_serde::reencode::<Outer>(&buf, StructLayout::Map) {
+        Err(rmp_serde::encode::Error::Decode(rmp_serde::decode::Error::TrailingBytes(1))) => {}
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
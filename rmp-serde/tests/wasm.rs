@@ -0,0 +1,24 @@
+//! Run with `wasm-pack test --node rmp-serde --features wasm --test wasm`.
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+use js_sys::Uint8Array;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Reading {
+    sensor: String,
+    values: Vec<f64>,
+}
+
+#[wasm_bindgen_test]
+fn pass_uint8array_round_trip() {
+    let reading = Reading { sensor: "kitchen".into(), values: vec![21.5, 22.0] };
+
+    let arr: Uint8Array = rmp_serde::wasm::to_uint8array(&reading).unwrap();
+    assert_eq!(rmp_serde::to_vec(&reading).unwrap(), arr.to_vec());
+    assert_eq!(reading, rmp_serde::wasm::from_uint8array(&arr).unwrap());
+
+    let arr = rmp_serde::wasm::to_uint8array_named(&reading).unwrap();
+    assert_eq!(reading, rmp_serde::wasm::from_uint8array::<Reading>(&arr).unwrap());
+}
@@ -1,33 +1,30 @@
-#![feature(test)]
-
-extern crate test;
-
-use test::Bencher;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
 
 use rmp::decode::*;
 
-#[bench]
-fn from_i64_read_i64(b: &mut Bencher) {
+fn from_i64_read_i64(c: &mut Criterion) {
     let buf = [0xd3, 0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
 
-    b.iter(|| {
-        let res = read_i64(&mut &buf[..]).unwrap();
-        test::black_box(res);
+    c.bench_function("from_i64_read_i64", |b| {
+        b.iter(|| {
+            let res = read_i64(&mut &buf[..]).unwrap();
+            black_box(res);
+        });
     });
 }
 
-#[bench]
-fn from_i64_read_int(b: &mut Bencher) {
+fn from_i64_read_int(c: &mut Criterion) {
     let buf = [0xd3, 0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff];
 
-    b.iter(|| {
-        let res: i64 = read_int(&mut &buf[..]).unwrap();
-        test::black_box(res);
+    c.bench_function("from_i64_read_int", |b| {
+        b.iter(|| {
+            let res: i64 = read_int(&mut &buf[..]).unwrap();
+            black_box(res);
+        });
     });
 }
 
-#[bench]
-fn from_string_read_str(b: &mut Bencher) {
+fn from_string_read_str(c: &mut Criterion) {
     // Lorem ipsum dolor sit amet.
     let buf = [
         0xbb, 0x4c, 0x6f, 0x72, 0x65, 0x6d, 0x20, 0x69, 0x70, 0x73,
@@ -37,8 +34,13 @@ fn from_string_read_str(b: &mut Bencher) {
 
     let mut out = [0u8; 32];
 
-    b.iter(|| {
-        let res = read_str(&mut &buf[..], &mut out[..]).unwrap();
-        test::black_box(res);
+    c.bench_function("from_string_read_str", |b| {
+        b.iter(|| {
+            let res = read_str(&mut &buf[..], &mut out[..]).unwrap();
+            black_box(res);
+        });
     });
 }
+
+criterion_group!(benches, from_i64_read_i64, from_i64_read_int, from_string_read_str);
+criterion_main!(benches);
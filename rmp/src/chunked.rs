@@ -0,0 +1,183 @@
+//! Binary streams of unknown length, framed as a sequence of bin chunks.
+//!
+//! MessagePack has no indefinite-length types, so a binary payload whose length isn't known up
+//! front, e.g. sensor data that is produced as it's sent, can't be written as a single bin value.
+//! These helpers implement the following convention instead:
+//!
+//! - the stream is written as consecutive bin values (`bin 8`, `bin 16` or `bin 32`), each
+//!   holding the next chunk of the payload;
+//! - chunks can have any non-zero length, and their boundaries carry no meaning;
+//! - the stream ends with an empty bin value, `0xc4 0x00`, the terminator chunk.
+//!
+//! Any MessagePack reader can skip such a stream value by value, and anything following the
+//! terminator, e.g. more values, is left untouched by [`ChunkedBinReader`].
+//!
+//! # Examples
+//!
+//! ```
+//! use std::io::{Read, Write};
+//!
+//! use rmp::chunked::{ChunkedBinReader, ChunkedBinWriter};
+//!
+//! let mut wr = ChunkedBinWriter::new(Vec::new());
+//! wr.write_all(b"hello, ").unwrap();
+//! wr.write_all(b"world").unwrap();
+//! let buf = wr.finish().unwrap();
+//! assert_eq!([0xc4, 0x07], buf[..2]);
+//! assert_eq!([0xc4, 0x00], buf[buf.len() - 2..]);
+//!
+//! let mut data = String::new();
+//! ChunkedBinReader::new(&buf[..]).read_to_string(&mut data).unwrap();
+//! assert_eq!("hello, world", data);
+//! ```
+
+use std::io::{self, ErrorKind, Read, Write};
+
+use crate::decode::{self, ValueReadError};
+use crate::encode::{self, ValueWriteError};
+
+/// The chunk ending a stream, an empty `bin 8` value.
+pub const TERMINATOR: [u8; 2] = [0xc4, 0x00];
+
+/// A writer framing everything written to it as bin chunks.
+///
+/// Each call to `write` produces one chunk, so small writes are best batched by wrapping this
+/// writer in a [`BufWriter`](std::io::BufWriter). The stream must be ended with
+/// [`finish`](ChunkedBinWriter::finish), which isn't done on drop.
+#[derive(Debug)]
+pub struct ChunkedBinWriter<W> {
+    wr: W,
+}
+
+impl<W: Write> ChunkedBinWriter<W> {
+    /// Creates a writer starting a new stream at the current position of `wr`.
+    #[inline]
+    #[must_use]
+    pub fn new(wr: W) -> Self {
+        ChunkedBinWriter { wr }
+    }
+
+    /// Returns a reference to the underlying writer.
+    #[inline]
+    pub fn get_ref(&self) -> &W {
+        &self.wr
+    }
+
+    /// Writes the terminator chunk, ending the stream, and returns the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns any I/O error while writing or flushing.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.wr.write_all(&TERMINATOR)?;
+        self.wr.flush()?;
+        Ok(self.wr)
+    }
+}
+
+impl<W: Write> Write for ChunkedBinWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // An empty chunk would end the stream.
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let len = buf.len().min(u32::MAX as usize);
+        match encode::write_bin_len(&mut self.wr, len as u32) {
+            Ok(..) => {}
+            Err(ValueWriteError::InvalidMarkerWrite(err) | ValueWriteError::InvalidDataWrite(err)) => return Err(err),
+        }
+        self.wr.write_all(&buf[..len])?;
+        Ok(len)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.wr.flush()
+    }
+}
+
+/// A reader of the payload of a stream written by [`ChunkedBinWriter`].
+///
+/// Reading returns the data of the chunks in order, and reports the end of input once the
+/// terminator chunk is read. The underlying reader is then positioned right after it.
+///
+/// A value other than a bin chunk is reported as an `InvalidData` error, and input ending before
+/// the terminator as an `UnexpectedEof` error.
+#[derive(Debug)]
+pub struct ChunkedBinReader<R> {
+    rd: R,
+    /// Bytes left in the current chunk.
+    remaining: u32,
+    done: bool,
+}
+
+impl<R: Read> ChunkedBinReader<R> {
+    /// Creates a reader of the stream starting at the current position of `rd`.
+    #[inline]
+    #[must_use]
+    pub fn new(rd: R) -> Self {
+        ChunkedBinReader { rd, remaining: 0, done: false }
+    }
+
+    /// Returns `true` once the terminator chunk has been read.
+    #[inline]
+    #[must_use]
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Returns a reference to the underlying reader.
+    #[inline]
+    pub fn get_ref(&self) -> &R {
+        &self.rd
+    }
+
+    /// Returns the underlying reader, consuming this reader.
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.rd
+    }
+}
+
+impl<R: Read> Read for ChunkedBinReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        while self.remaining == 0 {
+            if self.done {
+                return Ok(0);
+            }
+
+            match decode::read_bin_len(&mut self.rd) {
+                Ok(0) => self.done = true,
+                Ok(len) => self.remaining = len,
+                Err(ValueReadError::TypeMismatch(marker)) => {
+                    return Err(io::Error::new(ErrorKind::InvalidData, format!("expected a bin chunk, found {marker:?}")));
+                }
+                Err(ValueReadError::InvalidMarkerRead(err) | ValueReadError::InvalidDataRead(err)) => {
+                    return Err(unexpected_eof(err));
+                }
+            }
+        }
+
+        let len = buf.len().min(self.remaining as usize);
+        let n = self.rd.read(&mut buf[..len])?;
+        if n == 0 {
+            return Err(unexpected_eof(ErrorKind::UnexpectedEof.into()));
+        }
+        self.remaining -= n as u32;
+        Ok(n)
+    }
+}
+
+/// Explains an unexpected end of input, passing other errors through.
+fn unexpected_eof(err: io::Error) -> io::Error {
+    if err.kind() == ErrorKind::UnexpectedEof {
+        io::Error::new(ErrorKind::UnexpectedEof, "stream ended before the terminator chunk")
+    } else {
+        err
+    }
+}
@@ -0,0 +1,150 @@
+//! Read functions whose errors tell what was being read.
+//!
+//! Each function behaves like the function of the same name in the [parent module](super), but
+//! its error is an [`ErrorContext`] carrying the operation that failed, e.g. `"reading map len"`,
+//! and the marker that was read, if reading it succeeded.
+//!
+//! # Examples
+//!
+//! ```
+//! use rmp::decode::{annotated, ValueReadError};
+//! use rmp::Marker;
+//!
+//! // A map 16 header that ends before its length.
+//! let buf = [0xde, 0x00];
+//!
+//! let err = annotated::read_map_len(&mut &buf[..]).unwrap_err();
+//! assert_eq!("reading map len", err.operation());
+//! assert_eq!(Some(Marker::Map16), err.marker());
+//! assert!(matches!(err.error(), ValueReadError::InvalidDataRead(..)));
+//! ```
+
+use num_traits::cast::FromPrimitive;
+
+use super::{read_marker, ExtMeta, MarkerReadError, NumValueReadError, RmpRead, ValueReadError};
+use crate::{ErrorContext, Marker};
+
+/// Reads a marker and passes it on to `read`, annotating the error of either step.
+#[inline]
+fn read_with_marker<R, T, E, F>(rd: &mut R, operation: &'static str, read: F) -> Result<T, ErrorContext<E>>
+where
+    R: RmpRead,
+    E: From<MarkerReadError<R::Error>>,
+    F: FnOnce(&mut R, Marker) -> Result<T, E>,
+{
+    let marker = read_marker(rd).map_err(|err| ErrorContext::new(E::from(err), operation))?;
+    read(rd, marker).map_err(|err| ErrorContext::new(err, operation).with_marker(marker))
+}
+
+/// Reads a nil, like [`read_nil`](super::read_nil).
+///
+/// # Errors
+///
+/// Fails like [`read_nil`](super::read_nil), annotated with `"reading nil"`.
+#[inline]
+pub fn read_nil<R: RmpRead>(rd: &mut R) -> Result<(), ErrorContext<ValueReadError<R::Error>>> {
+    read_with_marker(rd, "reading nil", |_, marker| match marker {
+        Marker::Null => Ok(()),
+        marker => Err(ValueReadError::TypeMismatch(marker)),
+    })
+}
+
+/// Reads a boolean, like [`read_bool`](super::read_bool).
+///
+/// # Errors
+///
+/// Fails like [`read_bool`](super::read_bool), annotated with `"reading bool"`.
+#[inline]
+pub fn read_bool<R: RmpRead>(rd: &mut R) -> Result<bool, ErrorContext<ValueReadError<R::Error>>> {
+    read_with_marker(rd, "reading bool", |_, marker| match marker {
+        Marker::True => Ok(true),
+        Marker::False => Ok(false),
+        marker => Err(ValueReadError::TypeMismatch(marker)),
+    })
+}
+
+/// Reads an integer of any width into `T`, like [`read_int`](super::read_int).
+///
+/// # Errors
+///
+/// Fails like [`read_int`](super::read_int), annotated with `"reading int"`.
+#[inline]
+pub fn read_int<T: FromPrimitive, R: RmpRead>(rd: &mut R) -> Result<T, ErrorContext<NumValueReadError<R::Error>>> {
+    read_with_marker(rd, "reading int", super::read_int_with_marker)
+}
+
+/// Reads a 32-bit float, like [`read_f32`](super::read_f32).
+///
+/// # Errors
+///
+/// Fails like [`read_f32`](super::read_f32), annotated with `"reading f32"`.
+#[inline]
+pub fn read_f32<R: RmpRead>(rd: &mut R) -> Result<f32, ErrorContext<ValueReadError<R::Error>>> {
+    read_with_marker(rd, "reading f32", |rd, marker| match marker {
+        Marker::F32 => Ok(rd.read_data_f32()?),
+        marker => Err(ValueReadError::TypeMismatch(marker)),
+    })
+}
+
+/// Reads a 64-bit float, like [`read_f64`](super::read_f64).
+///
+/// # Errors
+///
+/// Fails like [`read_f64`](super::read_f64), annotated with `"reading f64"`.
+#[inline]
+pub fn read_f64<R: RmpRead>(rd: &mut R) -> Result<f64, ErrorContext<ValueReadError<R::Error>>> {
+    read_with_marker(rd, "reading f64", |rd, marker| match marker {
+        Marker::F64 => Ok(rd.read_data_f64()?),
+        marker => Err(ValueReadError::TypeMismatch(marker)),
+    })
+}
+
+/// Reads a string header, like [`read_str_len`](super::read_str_len).
+///
+/// # Errors
+///
+/// Fails like [`read_str_len`](super::read_str_len), annotated with `"reading str len"`.
+#[inline]
+pub fn read_str_len<R: RmpRead>(rd: &mut R) -> Result<u32, ErrorContext<ValueReadError<R::Error>>> {
+    read_with_marker(rd, "reading str len", super::read_str_len_with_marker)
+}
+
+/// Reads a binary header, like [`read_bin_len`](super::read_bin_len).
+///
+/// # Errors
+///
+/// Fails like [`read_bin_len`](super::read_bin_len), annotated with `"reading bin len"`.
+#[inline]
+pub fn read_bin_len<R: RmpRead>(rd: &mut R) -> Result<u32, ErrorContext<ValueReadError<R::Error>>> {
+    read_with_marker(rd, "reading bin len", super::read_bin_len_with_marker)
+}
+
+/// Reads an array header, like [`read_array_len`](super::read_array_len).
+///
+/// # Errors
+///
+/// Fails like [`read_array_len`](super::read_array_len), annotated with `"reading array len"`.
+#[inline]
+pub fn read_array_len<R: RmpRead>(rd: &mut R) -> Result<u32, ErrorContext<ValueReadError<R::Error>>> {
+    read_with_marker(rd, "reading array len", super::read_array_len_with_marker)
+}
+
+/// Reads a map header, like [`read_map_len`](super::read_map_len).
+///
+/// # Errors
+///
+/// Fails like [`read_map_len`](super::read_map_len), annotated with `"reading map len"`.
+#[inline]
+pub fn read_map_len<R: RmpRead>(rd: &mut R) -> Result<u32, ErrorContext<ValueReadError<R::Error>>> {
+    read_with_marker(rd, "reading map len", super::read_map_len_with_marker)
+}
+
+/// Reads an ext header, like [`read_ext_meta`](super::read_ext_meta).
+///
+/// # Errors
+///
+/// Fails like [`read_ext_meta`](super::read_ext_meta), annotated with `"reading ext meta"`.
+#[inline]
+pub fn read_ext_meta<R: RmpRead>(rd: &mut R) -> Result<ExtMeta, ErrorContext<ValueReadError<R::Error>>> {
+    read_with_marker(rd, "reading ext meta", super::ext::read_ext_meta_with_marker)
+}
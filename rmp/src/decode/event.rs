@@ -0,0 +1,183 @@
+//! Implementation of the [EventReader] pull parser.
+
+use super::bytes::{Bytes, BytesReadError};
+use super::{read_marker, RmpRead, ValueReadError};
+use crate::Marker;
+
+/// A single MessagePack token produced by [`EventReader`].
+///
+/// Containers are not read as a whole: an array or map header is followed by its elements (or
+/// key-value pairs) as separate events. Strings, binaries and extensions are returned as borrowed
+/// slices of the input, without copying.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Event<'a> {
+    /// Nil.
+    Nil,
+    /// Boolean.
+    Boolean(bool),
+    /// Integer encoded with a positive fixint or an unsigned marker.
+    UInt(u64),
+    /// Integer encoded with a negative fixint or a signed marker.
+    Int(i64),
+    /// Single precision float.
+    F32(f32),
+    /// Double precision float.
+    F64(f64),
+    /// Raw UTF-8 string bytes, which are not validated.
+    Str(&'a [u8]),
+    /// Binary data.
+    Bin(&'a [u8]),
+    /// Array header, followed by `len` values.
+    ArrayStart(u32),
+    /// Map header, followed by `len` key-value pairs.
+    MapStart(u32),
+    /// Extension type and its data.
+    Ext(i8, &'a [u8]),
+}
+
+/// A zero-copy pull parser yielding MessagePack values as a flat sequence of [`Event`]s.
+///
+/// This allows to inspect the structure of an encoded message without building values, which is
+/// useful for validators, indexers or custom parsers.
+///
+/// The reader is an [`Iterator`], which ends when the input is exhausted between two values. It
+/// does not track the nesting of containers, so a truncated message is only reported as an
+/// error if it is cut in the middle of a value.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::{Event, EventReader};
+///
+/// let buf = [0x92, 0x2a, 0xa2, 0x68, 0x69];
+/// let events = EventReader::new(&buf).collect::<Result<Vec<_>, _>>().unwrap();
+///
+/// assert_eq!(vec![Event::ArrayStart(2), Event::UInt(42), Event::Str(b"hi")], events);
+/// ```
+#[derive(Debug, Clone)]
+pub struct EventReader<'a> {
+    rd: Bytes<'a>,
+}
+
+impl<'a> EventReader<'a> {
+    /// Constructs a new `EventReader` over the given slice.
+    #[inline]
+    #[must_use]
+    pub fn new(buf: &'a [u8]) -> Self {
+        EventReader { rd: Bytes::new(buf) }
+    }
+
+    /// Returns the number of bytes consumed so far.
+    #[inline]
+    #[must_use]
+    pub fn position(&self) -> u64 {
+        self.rd.position()
+    }
+
+    /// Returns the not yet consumed part of the input.
+    #[inline]
+    #[must_use]
+    pub fn remaining_slice(&self) -> &'a [u8] {
+        self.rd.remaining_slice()
+    }
+
+    /// Reads the next event.
+    ///
+    /// # Errors
+    ///
+    /// This function will return `ValueReadError` if the input ends in the middle of a value, and
+    /// `ValueReadError::TypeMismatch` if it meets the reserved marker.
+    pub fn read_event(&mut self) -> Result<Event<'a>, ValueReadError<BytesReadError>> {
+        let rd = &mut self.rd;
+        let event = match read_marker(rd)? {
+            Marker::Null => Event::Nil,
+            Marker::True => Event::Boolean(true),
+            Marker::False => Event::Boolean(false),
+            Marker::FixPos(val) => Event::UInt(u64::from(val)),
+            Marker::FixNeg(val) => Event::Int(i64::from(val)),
+            Marker::U8 => Event::UInt(u64::from(rd.read_data_u8()?)),
+            Marker::U16 => Event::UInt(u64::from(rd.read_data_u16()?)),
+            Marker::U32 => Event::UInt(u64::from(rd.read_data_u32()?)),
+            Marker::U64 => Event::UInt(rd.read_data_u64()?),
+            Marker::I8 => Event::Int(i64::from(rd.read_data_i8()?)),
+            Marker::I16 => Event::Int(i64::from(rd.read_data_i16()?)),
+            Marker::I32 => Event::Int(i64::from(rd.read_data_i32()?)),
+            Marker::I64 => Event::Int(rd.read_data_i64()?),
+            Marker::F32 => Event::F32(rd.read_data_f32()?),
+            Marker::F64 => Event::F64(rd.read_data_f64()?),
+            Marker::FixStr(len) => Event::Str(read_slice(rd, u32::from(len))?),
+            Marker::Str8 => {
+                let len = u32::from(rd.read_data_u8()?);
+                Event::Str(read_slice(rd, len)?)
+            }
+            Marker::Str16 => {
+                let len = u32::from(rd.read_data_u16()?);
+                Event::Str(read_slice(rd, len)?)
+            }
+            Marker::Str32 => {
+                let len = rd.read_data_u32()?;
+                Event::Str(read_slice(rd, len)?)
+            }
+            Marker::Bin8 => {
+                let len = u32::from(rd.read_data_u8()?);
+                Event::Bin(read_slice(rd, len)?)
+            }
+            Marker::Bin16 => {
+                let len = u32::from(rd.read_data_u16()?);
+                Event::Bin(read_slice(rd, len)?)
+            }
+            Marker::Bin32 => {
+                let len = rd.read_data_u32()?;
+                Event::Bin(read_slice(rd, len)?)
+            }
+            Marker::FixArray(len) => Event::ArrayStart(u32::from(len)),
+            Marker::Array16 => Event::ArrayStart(u32::from(rd.read_data_u16()?)),
+            Marker::Array32 => Event::ArrayStart(rd.read_data_u32()?),
+            Marker::FixMap(len) => Event::MapStart(u32::from(len)),
+            Marker::Map16 => Event::MapStart(u32::from(rd.read_data_u16()?)),
+            Marker::Map32 => Event::MapStart(rd.read_data_u32()?),
+            Marker::FixExt1 => read_ext(rd, 1)?,
+            Marker::FixExt2 => read_ext(rd, 2)?,
+            Marker::FixExt4 => read_ext(rd, 4)?,
+            Marker::FixExt8 => read_ext(rd, 8)?,
+            Marker::FixExt16 => read_ext(rd, 16)?,
+            Marker::Ext8 => {
+                let len = u32::from(rd.read_data_u8()?);
+                read_ext(rd, len)?
+            }
+            Marker::Ext16 => {
+                let len = u32::from(rd.read_data_u16()?);
+                read_ext(rd, len)?
+            }
+            Marker::Ext32 => {
+                let len = rd.read_data_u32()?;
+                read_ext(rd, len)?
+            }
+            Marker::Reserved => return Err(ValueReadError::TypeMismatch(Marker::Reserved)),
+        };
+
+        Ok(event)
+    }
+}
+
+impl<'a> Iterator for EventReader<'a> {
+    type Item = Result<Event<'a>, ValueReadError<BytesReadError>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rd.remaining_slice().is_empty() {
+            None
+        } else {
+            Some(self.read_event())
+        }
+    }
+}
+
+#[inline]
+fn read_slice<'a>(rd: &mut Bytes<'a>, len: u32) -> Result<&'a [u8], ValueReadError<BytesReadError>> {
+    rd.read_slice(len as usize).map_err(ValueReadError::InvalidDataRead)
+}
+
+fn read_ext<'a>(rd: &mut Bytes<'a>, len: u32) -> Result<Event<'a>, ValueReadError<BytesReadError>> {
+    let ty = rd.read_data_i8()?;
+    Ok(Event::Ext(ty, read_slice(rd, len)?))
+}
@@ -1,5 +1,5 @@
 use super::{read_marker, RmpRead, ValueReadError};
-use crate::Marker;
+use crate::{Marker, Timestamp};
 
 /// Attempts to read exactly 3 bytes from the given reader and interpret them as a fixext1 type
 /// with data attached.
@@ -149,7 +149,13 @@ pub struct ExtMeta {
 }
 
 pub fn read_ext_meta<R: RmpRead>(rd: &mut R) -> Result<ExtMeta, ValueReadError<R::Error>> {
-    let size = match read_marker(rd)? {
+    let marker = read_marker(rd)?;
+    read_ext_meta_with_marker(rd, marker)
+}
+
+/// Reads the rest of an ext header whose marker has already been read.
+pub(crate) fn read_ext_meta_with_marker<R: RmpRead>(rd: &mut R, marker: Marker) -> Result<ExtMeta, ValueReadError<R::Error>> {
+    let size = match marker {
         Marker::FixExt1 => 1,
         Marker::FixExt2 => 2,
         Marker::FixExt4 => 4,
@@ -166,3 +172,53 @@ pub fn read_ext_meta<R: RmpRead>(rd: &mut R) -> Result<ExtMeta, ValueReadError<R
 
     Ok(meta)
 }
+
+/// Attempts to read a timestamp extension value from the given reader.
+///
+/// All three encodings defined by the MessagePack specification are supported: timestamp 32
+/// (fixext4), timestamp 64 (fixext8) and timestamp 96 (ext8 with 12 bytes of data).
+///
+/// # Errors
+///
+/// This function will return `ValueReadError` on any I/O error while reading either the marker or
+/// the data.
+///
+/// It also returns `ValueReadError::TypeMismatch` with the actual marker if the value is not an
+/// extension of type `-1`, if its size doesn't match any timestamp format or if the nanoseconds
+/// part is out of range. In that case the reader is left in an unspecified position.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::Timestamp;
+///
+/// // Timestamp 64: 1 second and 500 nanoseconds.
+/// let buf = [0xd7, 0xff, 0x00, 0x00, 0x07, 0xd0, 0x00, 0x00, 0x00, 0x01];
+///
+/// let ts = rmp::decode::read_timestamp(&mut &buf[..]).unwrap();
+/// assert_eq!(Timestamp::new(1, 500), Some(ts));
+/// ```
+pub fn read_timestamp<R: RmpRead>(rd: &mut R) -> Result<Timestamp, ValueReadError<R::Error>> {
+    let marker = read_marker(rd)?;
+    let size: usize = match marker {
+        Marker::FixExt4 => 4,
+        Marker::FixExt8 => 8,
+        // Only timestamp 96 is written as ext 8.
+        Marker::Ext8 => match rd.read_data_u8()? {
+            12 => 12,
+            _ => return Err(ValueReadError::TypeMismatch(marker)),
+        },
+        marker => return Err(ValueReadError::TypeMismatch(marker)),
+    };
+
+    if rd.read_data_i8()? != Timestamp::EXT_TYPE {
+        return Err(ValueReadError::TypeMismatch(marker));
+    }
+
+    let mut buf = [0; 12];
+    let data = &mut buf[..size];
+    rd.read_exact_buf(data).map_err(ValueReadError::InvalidDataRead)?;
+    let ts = Timestamp::from_ext_data(data);
+
+    ts.ok_or(ValueReadError::TypeMismatch(marker))
+}
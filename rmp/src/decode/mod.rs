@@ -10,19 +10,24 @@
 //! to avoid data loss (using `BufRead` readers with manual consuming or some other way).
 
 mod dec;
+mod event;
 mod ext;
 mod sint;
 mod str;
 mod uint;
 
 pub use self::dec::{read_f32, read_f64};
+pub use self::event::{Event, EventReader};
 pub use self::ext::{
-    read_ext_meta, read_fixext1, read_fixext16, read_fixext2, read_fixext4, read_fixext8, ExtMeta,
+    read_ext_meta, read_fixext1, read_fixext16, read_fixext2, read_fixext4, read_fixext8,
+    read_timestamp, ExtMeta,
 };
 pub use self::sint::{read_i16, read_i32, read_i64, read_i8, read_nfix};
 #[allow(deprecated)]
 // While we re-export deprecated items, we don't want to trigger warnings while compiling this crate
-pub use self::str::{read_str, read_str_from_slice, read_str_len, read_str_ref, DecodeStringError};
+pub use self::str::{
+    read_str, read_str_from_slice, read_str_len, read_str_len_with_marker, read_str_ref, DecodeStringError,
+};
 pub use self::uint::{read_pfix, read_u16, read_u32, read_u64, read_u8};
 
 use core::fmt::{self, Debug, Display, Formatter};
@@ -33,8 +38,10 @@ use num_traits::cast::FromPrimitive;
 
 use crate::Marker;
 
+pub mod annotated;
 pub mod bytes;
 pub use bytes::Bytes;
+use bytes::BytesReadError;
 
 #[doc(inline)]
 #[allow(deprecated)]
@@ -64,24 +71,16 @@ macro_rules! read_byteorder_utils {
         )*
     };
 }
-mod sealed {
-    pub trait Sealed {}
-    #[cfg(feature = "std")]
-    impl<T: ?Sized + std::io::Read> Sealed for T {}
-    #[cfg(not(feature = "std"))]
-    impl<'a> Sealed for &'a [u8] {}
-    impl Sealed for super::Bytes<'_> {}
-}
-
 /// A type that `rmp` supports reading from.
 ///
 /// The methods of this trait should be considered an implementation detail (for now).
-/// It is currently sealed (can not be implemented by the user).
+/// Readers that implement [`std::io::Read`] get it for free; without the `std` feature it can be
+/// implemented for other sources.
 ///
 /// See also [`std::io::Read`] and [`byteorder::ReadBytesExt`]
 ///
 /// Its primary implementations are [`std::io::Read`] and [Bytes].
-pub trait RmpRead: sealed::Sealed {
+pub trait RmpRead {
     type Error: RmpReadErr;
     /// Read a single (unsigned) byte from this stream
     #[inline]
@@ -382,7 +381,33 @@ impl<E: RmpReadErr> From<ValueReadError<E>> for NumValueReadError<E> {
 /// assert_eq!(300isize, rmp::decode::read_int(&mut &buf[..]).unwrap());
 /// ```
 pub fn read_int<T: FromPrimitive, R: RmpRead>(rd: &mut R) -> Result<T, NumValueReadError<R::Error>> {
-    let val = match read_marker(rd)? {
+    let marker = read_marker(rd)?;
+    read_int_with_marker(rd, marker)
+}
+
+/// Reads the rest of an integer whose marker has already been read.
+///
+/// This is the counterpart of [`read_int`] for parsers that dispatch on [`read_marker`]
+/// themselves, e.g. to accept either an integer or some other type at the same position.
+///
+/// # Errors
+///
+/// Returns `NumValueReadError::TypeMismatch` if `marker` is not an integer marker, without
+/// reading anything, and `NumValueReadError::OutOfRange` if the value doesn't fit in `T`.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::{read_int_with_marker, read_marker};
+///
+/// let mut rd = &[0xcd, 0x1, 0x2c][..];
+///
+/// let marker = read_marker(&mut rd).unwrap();
+/// assert!(marker.is_int());
+/// assert_eq!(300u64, read_int_with_marker(&mut rd, marker).unwrap());
+/// ```
+pub fn read_int_with_marker<T: FromPrimitive, R: RmpRead>(rd: &mut R, marker: Marker) -> Result<T, NumValueReadError<R::Error>> {
+    let val = match marker {
         Marker::FixPos(val) => T::from_u8(val),
         Marker::FixNeg(val) => T::from_i8(val),
         Marker::U8 => T::from_u8(rd.read_data_u8()?),
@@ -415,7 +440,17 @@ pub fn read_array_len<R>(rd: &mut R) -> Result<u32, ValueReadError<R::Error>>
 where
     R: RmpRead,
 {
-    match read_marker(rd)? {
+    let marker = read_marker(rd)?;
+    read_array_len_with_marker(rd, marker)
+}
+
+/// Reads the rest of an array header whose marker has already been read, returning the number
+/// of elements.
+///
+/// Returns `ValueReadError::TypeMismatch` if `marker` is not an array marker, without reading
+/// anything.
+pub fn read_array_len_with_marker<R: RmpRead>(rd: &mut R, marker: Marker) -> Result<u32, ValueReadError<R::Error>> {
+    match marker {
         Marker::FixArray(size) => Ok(u32::from(size)),
         Marker::Array16 => Ok(u32::from(rd.read_data_u16()?)),
         Marker::Array32 => Ok(rd.read_data_u32()?),
@@ -436,10 +471,24 @@ where
 // TODO: Docs.
 pub fn read_map_len<R: RmpRead>(rd: &mut R) -> Result<u32, ValueReadError<R::Error>> {
     let marker = read_marker(rd)?;
-    marker_to_len(rd, marker)
+    read_map_len_with_marker(rd, marker)
 }
 
+/// Reads the rest of a map header whose marker has already been read, returning the number of
+/// entries.
+///
+/// This is the same as [`read_map_len_with_marker`].
+#[inline]
 pub fn marker_to_len<R: RmpRead>(rd: &mut R, marker: Marker) -> Result<u32, ValueReadError<R::Error>> {
+    read_map_len_with_marker(rd, marker)
+}
+
+/// Reads the rest of a map header whose marker has already been read, returning the number of
+/// entries.
+///
+/// Returns `ValueReadError::TypeMismatch` if `marker` is not a map marker, without reading
+/// anything.
+pub fn read_map_len_with_marker<R: RmpRead>(rd: &mut R, marker: Marker) -> Result<u32, ValueReadError<R::Error>> {
     match marker {
         Marker::FixMap(size) => Ok(u32::from(size)),
         Marker::Map16 => Ok(u32::from(rd.read_data_u16()?)),
@@ -456,10 +505,133 @@ pub fn marker_to_len<R: RmpRead>(rd: &mut R, marker: Marker) -> Result<u32, Valu
 /// successful read.
 // TODO: Docs.
 pub fn read_bin_len<R: RmpRead>(rd: &mut R) -> Result<u32, ValueReadError<R::Error>> {
-    match read_marker(rd)? {
+    let marker = read_marker(rd)?;
+    read_bin_len_with_marker(rd, marker)
+}
+
+/// Reads the rest of a binary header whose marker has already been read, returning the data
+/// length.
+///
+/// Returns `ValueReadError::TypeMismatch` if `marker` is not a binary marker, without reading
+/// anything.
+pub fn read_bin_len_with_marker<R: RmpRead>(rd: &mut R, marker: Marker) -> Result<u32, ValueReadError<R::Error>> {
+    match marker {
         Marker::Bin8 => Ok(u32::from(rd.read_data_u8()?)),
         Marker::Bin16 => Ok(u32::from(rd.read_data_u16()?)),
         Marker::Bin32 => Ok(rd.read_data_u32()?),
         marker => Err(ValueReadError::TypeMismatch(marker)),
     }
 }
+
+/// Runs the given read function over a slice, returning the decoded value along with the
+/// unconsumed tail of the slice.
+#[inline]
+fn read_from_slice<'a, T, E>(buf: &'a [u8], f: impl FnOnce(&mut Bytes<'a>) -> Result<T, E>) -> Result<(T, &'a [u8]), E> {
+    let mut rd = Bytes::new(buf);
+    let val = f(&mut rd)?;
+    Ok((val, rd.remaining_slice()))
+}
+
+/// Attempts to read and decode an integer value from the slice, returning it along with the
+/// rest of the slice.
+///
+/// This is the slice counterpart of [`read_int`], allowing zero-copy incremental parsing
+/// without a reader. See also [`read_str_from_slice`].
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::read_int_from_slice;
+///
+/// let buf = [0xcd, 0x1, 0x2c, 0x2a];
+///
+/// let (val, rest) = read_int_from_slice::<u16>(&buf).unwrap();
+/// assert_eq!(300, val);
+/// assert_eq!([0x2a], rest);
+/// ```
+pub fn read_int_from_slice<T: FromPrimitive>(buf: &[u8]) -> Result<(T, &[u8]), NumValueReadError<BytesReadError>> {
+    read_from_slice(buf, read_int)
+}
+
+/// Attempts to read an array length from the slice, returning it along with the rest of the
+/// slice, which starts with the array elements.
+pub fn read_array_len_from_slice(buf: &[u8]) -> Result<(u32, &[u8]), ValueReadError<BytesReadError>> {
+    read_from_slice(buf, read_array_len)
+}
+
+/// Attempts to read a map length from the slice, returning it along with the rest of the slice,
+/// which starts with the map entries.
+pub fn read_map_len_from_slice(buf: &[u8]) -> Result<(u32, &[u8]), ValueReadError<BytesReadError>> {
+    read_from_slice(buf, read_map_len)
+}
+
+/// Reads the rest of an integer whose marker has already been taken from the front of the
+/// slice, returning it along with the rest of the slice.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::read_int_with_marker_from_slice;
+/// use rmp::Marker;
+///
+/// let buf = [0xcd, 0x1, 0x2c, 0x2a];
+///
+/// let marker = Marker::from_u8(buf[0]);
+/// let (val, rest) = read_int_with_marker_from_slice::<u16>(&buf[1..], marker).unwrap();
+/// assert_eq!(300, val);
+/// assert_eq!([0x2a], rest);
+/// ```
+pub fn read_int_with_marker_from_slice<T: FromPrimitive>(buf: &[u8], marker: Marker) -> Result<(T, &[u8]), NumValueReadError<BytesReadError>> {
+    read_from_slice(buf, |rd| read_int_with_marker(rd, marker))
+}
+
+/// Reads the rest of a string header whose marker has already been taken from the front of the
+/// slice, returning the string length along with the rest of the slice.
+pub fn read_str_len_with_marker_from_slice(buf: &[u8], marker: Marker) -> Result<(u32, &[u8]), ValueReadError<BytesReadError>> {
+    read_from_slice(buf, |rd| read_str_len_with_marker(rd, marker))
+}
+
+/// Reads the rest of a binary header whose marker has already been taken from the front of the
+/// slice, returning the data length along with the rest of the slice.
+pub fn read_bin_len_with_marker_from_slice(buf: &[u8], marker: Marker) -> Result<(u32, &[u8]), ValueReadError<BytesReadError>> {
+    read_from_slice(buf, |rd| read_bin_len_with_marker(rd, marker))
+}
+
+/// Reads the rest of an array header whose marker has already been taken from the front of the
+/// slice, returning the array length along with the rest of the slice.
+pub fn read_array_len_with_marker_from_slice(buf: &[u8], marker: Marker) -> Result<(u32, &[u8]), ValueReadError<BytesReadError>> {
+    read_from_slice(buf, |rd| read_array_len_with_marker(rd, marker))
+}
+
+/// Reads the rest of a map header whose marker has already been taken from the front of the
+/// slice, returning the map length along with the rest of the slice.
+pub fn read_map_len_with_marker_from_slice(buf: &[u8], marker: Marker) -> Result<(u32, &[u8]), ValueReadError<BytesReadError>> {
+    read_from_slice(buf, |rd| read_map_len_with_marker(rd, marker))
+}
+
+/// Attempts to read a binary value from the slice, returning its data borrowed from the slice
+/// along with the rest of the slice.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::read_bin_from_slice;
+///
+/// let buf = [0xc4, 0x02, 0xaa, 0xbb, 0xc0];
+///
+/// let (data, rest) = read_bin_from_slice(&buf).unwrap();
+/// assert_eq!([0xaa, 0xbb], data);
+/// assert_eq!([0xc0], rest);
+/// ```
+pub fn read_bin_from_slice(buf: &[u8]) -> Result<(&[u8], &[u8]), ValueReadError<BytesReadError>> {
+    let (len, rest) = read_from_slice(buf, read_bin_len)?;
+    let len = len as usize;
+    if rest.len() < len {
+        return Err(ValueReadError::InvalidDataRead(BytesReadError::InsufficientBytes {
+            expected: len,
+            actual: rest.len(),
+            position: (buf.len() - rest.len()) as u64,
+        }));
+    }
+    Ok(rest.split_at(len))
+}
@@ -67,16 +67,44 @@ pub fn read_str_len<R: RmpRead>(rd: &mut R) -> Result<u32, ValueReadError<R::Err
     Ok(read_str_len_with_nread(rd)?.0)
 }
 
+/// Reads the rest of a string header whose marker has already been read, returning the string
+/// length.
+///
+/// This is useful for parsers that dispatch on [`read_marker`] themselves.
+///
+/// # Errors
+///
+/// Returns `ValueReadError::TypeMismatch` if `marker` is not a string marker, without reading
+/// anything.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::decode::{read_marker, read_str_len_with_marker};
+/// use rmp::Marker;
+///
+/// let mut rd = &[0xd9, 0x20][..];
+///
+/// let marker = read_marker(&mut rd).unwrap();
+/// assert_eq!(Marker::Str8, marker);
+/// assert_eq!(32, read_str_len_with_marker(&mut rd, marker).unwrap());
+/// ```
+pub fn read_str_len_with_marker<R: RmpRead>(rd: &mut R, marker: Marker) -> Result<u32, ValueReadError<R::Error>> {
+    match marker {
+        Marker::FixStr(size) => Ok(u32::from(size)),
+        Marker::Str8 => Ok(u32::from(rd.read_data_u8()?)),
+        Marker::Str16 => Ok(u32::from(rd.read_data_u16()?)),
+        Marker::Str32 => Ok(rd.read_data_u32()?),
+        marker => Err(ValueReadError::TypeMismatch(marker)),
+    }
+}
+
 fn read_str_len_with_nread<R>(rd: &mut R) -> Result<(u32, usize), ValueReadError<R::Error>>
     where R: RmpRead
 {
-    match read_marker(rd)? {
-        Marker::FixStr(size) => Ok((u32::from(size), 1)),
-        Marker::Str8 => Ok((u32::from(rd.read_data_u8()?), 2)),
-        Marker::Str16 => Ok((u32::from(rd.read_data_u16()?), 3)),
-        Marker::Str32 => Ok((rd.read_data_u32()?, 5)),
-        marker => Err(ValueReadError::TypeMismatch(marker)),
-    }
+    let marker = read_marker(rd)?;
+    let len = read_str_len_with_marker(rd, marker)?;
+    Ok((len, marker.header_len()))
 }
 
 /// Attempts to read a string data from the given reader and copy it to the buffer provided.
@@ -148,7 +176,15 @@ pub fn read_str_data<'r, R>(rd: &mut R,
 pub fn read_str_ref(rd: &[u8]) -> Result<&[u8], DecodeStringError<'_, super::bytes::BytesReadError>> {
     let mut cur = super::Bytes::new(rd);
     let len = read_str_len(&mut cur)?;
-    Ok(&cur.remaining_slice()[..len as usize])
+    let buf = cur.remaining_slice();
+    match buf.get(..len as usize) {
+        Some(data) => Ok(data),
+        None => Err(DecodeStringError::InvalidDataRead(super::bytes::BytesReadError::InsufficientBytes {
+            expected: len as usize,
+            actual: buf.len(),
+            position: cur.position(),
+        })),
+    }
 }
 
 /// Attempts to read and decode a string value from the reader, returning a borrowed slice from it.
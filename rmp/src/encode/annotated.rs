@@ -0,0 +1,189 @@
+//! Write functions whose errors tell what was being written.
+//!
+//! Each function behaves like the function of the same name in the [parent module](super), but
+//! its error is an [`ErrorContext`] carrying the operation that failed, e.g. `"writing map len"`,
+//! and the marker that was being written. Values written in many steps can then report which
+//! step failed without annotating every call.
+//!
+//! # Examples
+//!
+//! ```
+//! use rmp::encode::{annotated, FixedByteBuf};
+//! use rmp::Marker;
+//!
+//! let mut storage = [0u8; 2];
+//! let mut buf = FixedByteBuf::new(&mut storage);
+//!
+//! annotated::write_map_len(&mut buf, 1).unwrap();
+//! let err = annotated::write_str(&mut buf, "key").unwrap_err();
+//! assert_eq!("writing str", err.operation());
+//! assert_eq!(Some(Marker::FixStr(3)), err.marker());
+//! ```
+
+use super::{FixedByteBuf, RmpWrite, ValueWriteError};
+use crate::{ErrorContext, Marker};
+
+/// Returns the marker that `write` writes first, by writing into a scratch buffer.
+///
+/// Only used once writing to the actual writer failed, as the write functions don't return the
+/// marker along with an error.
+#[cold]
+fn marker_of<F, E>(write: F) -> Option<Marker>
+where
+    F: FnOnce(&mut FixedByteBuf<'_>) -> Result<Marker, E>,
+{
+    // The longest header, of a uint 64 or an ext 32, is 9 bytes.
+    let mut storage = [0u8; 9];
+    write(&mut FixedByteBuf::new(&mut storage)).ok()
+}
+
+#[cold]
+fn annotate<E>(err: E, operation: &'static str, marker: Option<Marker>) -> ErrorContext<E> {
+    let ctx = ErrorContext::new(err, operation);
+    match marker {
+        Some(marker) => ctx.with_marker(marker),
+        None => ctx,
+    }
+}
+
+/// Writes a nil, like [`write_nil`](super::write_nil).
+///
+/// # Errors
+///
+/// Fails with the error of the writer, annotated with `"writing nil"`.
+#[inline]
+pub fn write_nil<W: RmpWrite>(wr: &mut W) -> Result<(), ErrorContext<W::Error>> {
+    super::write_nil(wr).map_err(|err| annotate(err, "writing nil", Some(Marker::Null)))
+}
+
+/// Writes a boolean, like [`write_bool`](super::write_bool).
+///
+/// # Errors
+///
+/// Fails with the error of the writer, annotated with `"writing bool"`.
+#[inline]
+pub fn write_bool<W: RmpWrite>(wr: &mut W, val: bool) -> Result<(), ErrorContext<W::Error>> {
+    let marker = if val { Marker::True } else { Marker::False };
+    super::write_bool(wr, val).map_err(|err| annotate(err, "writing bool", Some(marker)))
+}
+
+/// Writes an unsigned integer in the most compact form, like [`write_uint`](super::write_uint).
+///
+/// # Errors
+///
+/// Fails like [`write_uint`](super::write_uint), annotated with `"writing uint"`.
+#[inline]
+pub fn write_uint<W: RmpWrite>(wr: &mut W, val: u64) -> Result<Marker, ErrorContext<ValueWriteError<W::Error>>> {
+    super::write_uint(wr, val)
+        .map_err(|err| annotate(err, "writing uint", marker_of(|buf| super::write_uint(buf, val))))
+}
+
+/// Writes a signed integer in the most compact form, like [`write_sint`](super::write_sint).
+///
+/// # Errors
+///
+/// Fails like [`write_sint`](super::write_sint), annotated with `"writing sint"`.
+#[inline]
+pub fn write_sint<W: RmpWrite>(wr: &mut W, val: i64) -> Result<Marker, ErrorContext<ValueWriteError<W::Error>>> {
+    super::write_sint(wr, val)
+        .map_err(|err| annotate(err, "writing sint", marker_of(|buf| super::write_sint(buf, val))))
+}
+
+/// Writes a 32-bit float, like [`write_f32`](super::write_f32).
+///
+/// # Errors
+///
+/// Fails like [`write_f32`](super::write_f32), annotated with `"writing f32"`.
+#[inline]
+pub fn write_f32<W: RmpWrite>(wr: &mut W, val: f32) -> Result<(), ErrorContext<ValueWriteError<W::Error>>> {
+    super::write_f32(wr, val).map_err(|err| annotate(err, "writing f32", Some(Marker::F32)))
+}
+
+/// Writes a 64-bit float, like [`write_f64`](super::write_f64).
+///
+/// # Errors
+///
+/// Fails like [`write_f64`](super::write_f64), annotated with `"writing f64"`.
+#[inline]
+pub fn write_f64<W: RmpWrite>(wr: &mut W, val: f64) -> Result<(), ErrorContext<ValueWriteError<W::Error>>> {
+    super::write_f64(wr, val).map_err(|err| annotate(err, "writing f64", Some(Marker::F64)))
+}
+
+/// Writes a string header, like [`write_str_len`](super::write_str_len).
+///
+/// # Errors
+///
+/// Fails like [`write_str_len`](super::write_str_len), annotated with `"writing str len"`.
+#[inline]
+pub fn write_str_len<W: RmpWrite>(wr: &mut W, len: u32) -> Result<Marker, ErrorContext<ValueWriteError<W::Error>>> {
+    super::write_str_len(wr, len)
+        .map_err(|err| annotate(err, "writing str len", marker_of(|buf| super::write_str_len(buf, len))))
+}
+
+/// Writes a string, like [`write_str`](super::write_str).
+///
+/// # Errors
+///
+/// Fails like [`write_str`](super::write_str), annotated with `"writing str"`.
+#[inline]
+pub fn write_str<W: RmpWrite>(wr: &mut W, data: &str) -> Result<(), ErrorContext<ValueWriteError<W::Error>>> {
+    let len = data.len() as u32;
+    super::write_str(wr, data)
+        .map_err(|err| annotate(err, "writing str", marker_of(|buf| super::write_str_len(buf, len))))
+}
+
+/// Writes a binary header, like [`write_bin_len`](super::write_bin_len).
+///
+/// # Errors
+///
+/// Fails like [`write_bin_len`](super::write_bin_len), annotated with `"writing bin len"`.
+#[inline]
+pub fn write_bin_len<W: RmpWrite>(wr: &mut W, len: u32) -> Result<Marker, ErrorContext<ValueWriteError<W::Error>>> {
+    super::write_bin_len(wr, len)
+        .map_err(|err| annotate(err, "writing bin len", marker_of(|buf| super::write_bin_len(buf, len))))
+}
+
+/// Writes binary data, like [`write_bin`](super::write_bin).
+///
+/// # Errors
+///
+/// Fails like [`write_bin`](super::write_bin), annotated with `"writing bin"`.
+#[inline]
+pub fn write_bin<W: RmpWrite>(wr: &mut W, data: &[u8]) -> Result<(), ErrorContext<ValueWriteError<W::Error>>> {
+    let len = data.len() as u32;
+    super::write_bin(wr, data)
+        .map_err(|err| annotate(err, "writing bin", marker_of(|buf| super::write_bin_len(buf, len))))
+}
+
+/// Writes an array header, like [`write_array_len`](super::write_array_len).
+///
+/// # Errors
+///
+/// Fails like [`write_array_len`](super::write_array_len), annotated with `"writing array len"`.
+#[inline]
+pub fn write_array_len<W: RmpWrite>(wr: &mut W, len: u32) -> Result<Marker, ErrorContext<ValueWriteError<W::Error>>> {
+    super::write_array_len(wr, len)
+        .map_err(|err| annotate(err, "writing array len", marker_of(|buf| super::write_array_len(buf, len))))
+}
+
+/// Writes a map header, like [`write_map_len`](super::write_map_len).
+///
+/// # Errors
+///
+/// Fails like [`write_map_len`](super::write_map_len), annotated with `"writing map len"`.
+#[inline]
+pub fn write_map_len<W: RmpWrite>(wr: &mut W, len: u32) -> Result<Marker, ErrorContext<ValueWriteError<W::Error>>> {
+    super::write_map_len(wr, len)
+        .map_err(|err| annotate(err, "writing map len", marker_of(|buf| super::write_map_len(buf, len))))
+}
+
+/// Writes an ext header, like [`write_ext_meta`](super::write_ext_meta).
+///
+/// # Errors
+///
+/// Fails like [`write_ext_meta`](super::write_ext_meta), annotated with `"writing ext meta"`.
+#[inline]
+pub fn write_ext_meta<W: RmpWrite>(wr: &mut W, len: u32, ty: i8) -> Result<Marker, ErrorContext<ValueWriteError<W::Error>>> {
+    super::write_ext_meta(wr, len, ty)
+        .map_err(|err| annotate(err, "writing ext meta", marker_of(|buf| super::write_ext_meta(buf, len, ty))))
+}
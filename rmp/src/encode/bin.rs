@@ -1,4 +1,4 @@
-use super::RmpWrite;
+use super::{FixedByteBuf, RmpWrite};
 use crate::encode::{write_marker, ValueWriteError};
 use crate::Marker;
 
@@ -43,3 +43,31 @@ pub fn write_bin<W: RmpWrite>(wr: &mut W, data: &[u8]) -> Result<(), ValueWriteE
     wr.write_bytes(data)
         .map_err(ValueWriteError::InvalidDataWrite)
 }
+
+/// Encodes and attempts to write the most efficient binary implementation to the given `Write`,
+/// passing the header and the data to the writer in a single vectored write.
+///
+/// Unlike [`write_bin`] this lets writers that support scatter/gather I/O send large payloads
+/// without an extra write call or intermediate concatenation.
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError::InvalidDataWrite` on any I/O error occurred while
+/// writing either the marker or the data.
+///
+/// # Examples
+///
+/// ```
+/// let mut buf = Vec::new();
+/// rmp::encode::write_bin_vectored(&mut buf, &[1, 2, 3]).unwrap();
+///
+/// assert_eq!(vec![0xc4, 0x03, 0x01, 0x02, 0x03], buf);
+/// ```
+pub fn write_bin_vectored<W: RmpWrite>(wr: &mut W, data: &[u8]) -> Result<(), ValueWriteError<W::Error>> {
+    let mut storage = [0u8; 5];
+    let mut header = FixedByteBuf::new(&mut storage);
+    // The header is at most 5 bytes long, so this can't fail.
+    let _ = write_bin_len(&mut header, data.len() as u32);
+    wr.write_bytes_vectored(&[header.as_slice(), data])
+        .map_err(ValueWriteError::InvalidDataWrite)
+}
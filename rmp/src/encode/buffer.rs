@@ -1,8 +1,7 @@
-//! Implementation of the [`ByteBuf`] type
+//! Implementation of the [`ByteBuf`] and [`FixedByteBuf`] types
 
 use super::RmpWrite;
 use alloc::vec::Vec;
-#[cfg(not(feature = "std"))]
 use core::fmt::{self, Display, Formatter};
 
 /// An error returned from writing to `&mut [u8]` (a byte buffer of fixed capacity) on no_std
@@ -66,6 +65,120 @@ impl<'a> RmpWrite for &'a mut [u8] {
     }
 }
 
+/// An error returned from writing to [`FixedByteBuf`] when its capacity is exceeded.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct BufferFull {
+    needed: usize,
+    remaining: usize,
+}
+
+impl BufferFull {
+    /// The number of bytes that were attempted to be written.
+    #[inline]
+    #[must_use]
+    pub fn needed(&self) -> usize {
+        self.needed
+    }
+    /// The number of bytes that were left in the buffer.
+    #[inline]
+    #[must_use]
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+}
+
+impl Display for BufferFull {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Buffer full: needed {} bytes, but only {} remaining", self.needed, self.remaining)
+    }
+}
+#[cfg(feature = "std")]
+impl std::error::Error for BufferFull {}
+impl crate::encode::RmpWriteErr for BufferFull {}
+
+/// A writer over a fixed-capacity `&mut [u8]`, for example a stack buffer.
+///
+/// Unlike [`ByteBuf`] this never allocates, and it works the same with and without the
+/// `std` feature. Writes that don't fit fail with [`BufferFull`]. Note that the encoding functions
+/// write a value in several steps, so a value that doesn't fit may still be partially written.
+///
+/// The number of bytes written so far is tracked (see [`Self::position`]), so the encoded
+/// data can be retrieved with [`Self::as_slice`]. The matching reader is [`Bytes`](crate::decode::Bytes).
+///
+/// # Examples
+///
+/// ```
+/// use rmp::encode::FixedByteBuf;
+///
+/// let mut storage = [0u8; 3];
+/// let mut buf = FixedByteBuf::new(&mut storage);
+///
+/// rmp::encode::write_u16(&mut buf, 42).unwrap();
+/// assert_eq!(&[0xcd, 0x00, 0x2a], buf.as_slice());
+///
+/// assert!(rmp::encode::write_nil(&mut buf).is_err());
+/// assert_eq!(3, buf.position());
+/// ```
+#[derive(Debug)]
+pub struct FixedByteBuf<'a> {
+    bytes: &'a mut [u8],
+    position: usize,
+}
+impl<'a> FixedByteBuf<'a> {
+    /// Wrap the specified buffer, starting at the beginning of it.
+    #[inline]
+    #[must_use]
+    pub fn new(bytes: &'a mut [u8]) -> Self {
+        FixedByteBuf { bytes, position: 0 }
+    }
+    /// Return the number of bytes written so far.
+    #[inline]
+    #[must_use]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+    /// Return the total capacity of the underlying buffer.
+    #[inline]
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.bytes.len()
+    }
+    /// Return the number of bytes that can still be written.
+    #[inline]
+    #[must_use]
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() - self.position
+    }
+    /// Get a reference to the bytes written so far.
+    #[inline]
+    #[must_use]
+    pub fn as_slice(&self) -> &[u8] {
+        &self.bytes[..self.position]
+    }
+    /// Unwrap this writer, returning the part of the buffer that was written.
+    #[inline]
+    #[must_use]
+    pub fn into_slice(self) -> &'a mut [u8] {
+        &mut self.bytes[..self.position]
+    }
+}
+
+impl RmpWrite for FixedByteBuf<'_> {
+    type Error = BufferFull;
+
+    #[inline]
+    fn write_bytes(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
+        let remaining = self.remaining();
+        if buf.len() <= remaining {
+            self.bytes[self.position..self.position + buf.len()].copy_from_slice(buf);
+            self.position += buf.len();
+            Ok(())
+        } else {
+            Err(BufferFull { needed: buf.len(), remaining })
+        }
+    }
+}
+
 /// A wrapper around `Vec<u8>` to serialize more efficiently.
 ///
 /// This has a specialized implementation of `RmpWrite`
@@ -6,6 +6,9 @@ use crate::Marker;
 ///
 /// The first byte becomes the `f32` marker and the others will represent the data itself.
 ///
+/// The IEEE 754 bits of the value are written unchanged in big-endian order, so NaN payloads and
+/// the sign of zero are preserved.
+///
 /// # Errors
 ///
 /// This function will return `ValueWriteError` on any I/O error occurred while writing either the
@@ -20,6 +23,9 @@ pub fn write_f32<W: RmpWrite>(wr: &mut W, val: f32) -> Result<(), ValueWriteErro
 ///
 /// The first byte becomes the `f64` marker and the others will represent the data itself.
 ///
+/// Like [`write_f32`], this writes the bits of the value unchanged. The value is never narrowed
+/// to `f32`, even if that would be lossless.
+///
 /// # Errors
 ///
 /// This function will return `ValueWriteError` on any I/O error occurred while writing either the
@@ -0,0 +1,140 @@
+//! Lengths of encoded values, for sizing buffers before writing.
+//!
+//! Each function returns the exact number of bytes the corresponding `write_*` function writes,
+//! so frame sizes can be computed up front without writing to a counting sink first.
+
+/// Returns the length of an unsigned integer as written by [`write_uint`](super::write_uint).
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(1, rmp::encode::uint_len(127));
+/// assert_eq!(3, rmp::encode::uint_len(256));
+/// ```
+#[inline]
+#[must_use]
+pub const fn uint_len(val: u64) -> usize {
+    if val < 128 {
+        1
+    } else if val < 256 {
+        2
+    } else if val < 65536 {
+        3
+    } else if val < 4294967296 {
+        5
+    } else {
+        9
+    }
+}
+
+/// Returns the length of a signed integer as written by [`write_sint`](super::write_sint).
+#[inline]
+#[must_use]
+pub const fn sint_len(val: i64) -> usize {
+    if val >= 0 {
+        uint_len(val as u64)
+    } else if val >= -32 {
+        1
+    } else if val >= -128 {
+        2
+    } else if val >= -32768 {
+        3
+    } else if val >= -2147483648 {
+        5
+    } else {
+        9
+    }
+}
+
+/// Returns the length of a string header as written by [`write_str_len`](super::write_str_len).
+#[inline]
+#[must_use]
+pub const fn str_header_len(len: u32) -> usize {
+    if len < 32 {
+        1
+    } else if len < 256 {
+        2
+    } else if len <= u16::MAX as u32 {
+        3
+    } else {
+        5
+    }
+}
+
+/// Returns the length of a string of `len` bytes, header included, as written by
+/// [`write_str`](super::write_str).
+#[inline]
+#[must_use]
+pub const fn str_total_len(len: u32) -> usize {
+    str_header_len(len) + len as usize
+}
+
+/// Returns the length of a binary header as written by [`write_bin_len`](super::write_bin_len).
+#[inline]
+#[must_use]
+pub const fn bin_header_len(len: u32) -> usize {
+    if len < 256 {
+        2
+    } else if len <= u16::MAX as u32 {
+        3
+    } else {
+        5
+    }
+}
+
+/// Returns the length of a binary value of `len` bytes, header included, as written by
+/// [`write_bin`](super::write_bin).
+///
+/// # Examples
+///
+/// ```
+/// let mut buf = Vec::with_capacity(rmp::encode::bin_total_len(300));
+/// rmp::encode::write_bin(&mut buf, &[0; 300]).unwrap();
+///
+/// assert_eq!(303, buf.len());
+/// ```
+#[inline]
+#[must_use]
+pub const fn bin_total_len(len: u32) -> usize {
+    bin_header_len(len) + len as usize
+}
+
+/// Returns the length of an array header as written by [`write_array_len`](super::write_array_len).
+#[inline]
+#[must_use]
+pub const fn array_header_len(len: u32) -> usize {
+    if len < 16 {
+        1
+    } else if len <= u16::MAX as u32 {
+        3
+    } else {
+        5
+    }
+}
+
+/// Returns the length of a map header as written by [`write_map_len`](super::write_map_len).
+#[inline]
+#[must_use]
+pub const fn map_header_len(len: u32) -> usize {
+    array_header_len(len)
+}
+
+/// Returns the length of an ext header, type included, as written by
+/// [`write_ext_meta`](super::write_ext_meta).
+#[inline]
+#[must_use]
+pub const fn ext_header_len(len: u32) -> usize {
+    match len {
+        1 | 2 | 4 | 8 | 16 => 2,
+        0..=255 => 3,
+        256..=65535 => 4,
+        _ => 6,
+    }
+}
+
+/// Returns the length of an ext value with `len` bytes of data, header included.
+#[inline]
+#[must_use]
+pub const fn ext_total_len(len: u32) -> usize {
+    ext_header_len(len) + len as usize
+}
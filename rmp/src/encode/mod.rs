@@ -3,26 +3,32 @@
 mod bin;
 mod dec;
 mod ext;
+mod len;
 mod map;
 mod sint;
 mod str;
 mod uint;
 mod vec;
 
-pub use self::bin::{write_bin, write_bin_len};
+pub use self::bin::{write_bin, write_bin_len, write_bin_vectored};
 pub use self::dec::{write_f32, write_f64};
+pub use self::len::{
+    array_header_len, bin_header_len, bin_total_len, ext_header_len, ext_total_len, map_header_len,
+    sint_len, str_header_len, str_total_len, uint_len,
+};
 pub use self::sint::{write_i16, write_i32, write_i64, write_i8, write_nfix, write_sint};
-pub use self::str::{write_str, write_str_len};
+pub use self::str::{write_str, write_str_len, write_str_vectored};
 pub use self::uint::{write_pfix, write_u16, write_u32, write_u64, write_u8, write_uint, write_uint8};
 
 use core::fmt::{self, Debug, Display, Formatter};
 #[cfg(feature = "std")]
 use std::error;
 
-use crate::Marker;
+use crate::{Marker, Timestamp};
 
+pub mod annotated;
 pub mod buffer;
-pub use buffer::ByteBuf;
+pub use buffer::{BufferFull, ByteBuf, FixedByteBuf};
 
 #[doc(inline)]
 #[allow(deprecated)]
@@ -102,17 +108,6 @@ pub fn write_bool<W: RmpWrite>(wr: &mut W, val: bool) -> Result<(), W::Error> {
     write_marker(wr, marker).map_err(|e| e.0)
 }
 
-mod sealed {
-    pub trait Sealed {}
-    #[cfg(feature = "std")]
-    impl<T: ?Sized + std::io::Write> Sealed for T {}
-    #[cfg(not(feature = "std"))]
-    impl Sealed for &mut [u8] {}
-    #[cfg(not(feature = "std"))]
-    impl Sealed for alloc::vec::Vec<u8> {}
-    impl Sealed for super::ByteBuf {}
-}
-
 macro_rules! write_byteorder_utils {
     ($($name:ident => $tp:ident),* $(,)?) => {
         $(
@@ -133,12 +128,13 @@ macro_rules! write_byteorder_utils {
 /// A type that `rmp` supports writing into.
 ///
 /// The methods of this trait should be considered an implementation detail (for now).
-/// It is currently sealed (can not be implemented by the user).
+/// Writers that implement [`std::io::Write`] get it for free; without the `std` feature it can be
+/// implemented for other sinks.
 ///
 /// See also [`std::io::Write`] and [`byteorder::WriteBytesExt`]
 ///
 /// Its primary implementations are [`std::io::Write`] and [`ByteBuf`].
-pub trait RmpWrite: sealed::Sealed {
+pub trait RmpWrite {
     type Error: RmpWriteErr;
 
     /// Write a single byte to this stream
@@ -154,6 +150,19 @@ pub trait RmpWrite: sealed::Sealed {
     /// See also [`std::io::Write::write_all`]
     fn write_bytes(&mut self, buf: &[u8]) -> Result<(), Self::Error>;
 
+    /// Write the given slices one after another to the underlying stream
+    ///
+    /// This will either write all the bytes or return an error. Writers that support
+    /// scatter/gather I/O write the slices without concatenating them first.
+    /// See also [`std::io::Write::write_vectored`]
+    #[inline]
+    fn write_bytes_vectored(&mut self, bufs: &[&[u8]]) -> Result<(), Self::Error> {
+        for buf in bufs {
+            self.write_bytes(buf)?;
+        }
+        Ok(())
+    }
+
     // Internal helper functions to map I/O error into the `DataWriteError` error.
 
     /// Write a single (signed) byte to this stream.
@@ -189,6 +198,55 @@ impl<T: std::io::Write> RmpWrite for T {
     fn write_bytes(&mut self, buf: &[u8]) -> Result<(), Self::Error> {
         self.write_all(buf)
     }
+
+    fn write_bytes_vectored(&mut self, mut bufs: &[&[u8]]) -> Result<(), Self::Error> {
+        use std::io::{ErrorKind, IoSlice};
+
+        // The partially written slice that goes first.
+        let mut head: &[u8] = &[];
+        loop {
+            while head.is_empty() {
+                match bufs.split_first() {
+                    Some((first, rest)) => {
+                        head = first;
+                        bufs = rest;
+                    }
+                    None => return Ok(()),
+                }
+            }
+
+            let mut slices = [IoSlice::new(&[]); 8];
+            let count = 1 + bufs.len().min(slices.len() - 1);
+            slices[0] = IoSlice::new(head);
+            for (slice, buf) in slices[1..count].iter_mut().zip(bufs) {
+                *slice = IoSlice::new(buf);
+            }
+
+            match self.write_vectored(&slices[..count]) {
+                Ok(0) => return Err(ErrorKind::WriteZero.into()),
+                Ok(mut n) => {
+                    if n < head.len() {
+                        head = &head[n..];
+                        continue;
+                    }
+                    n -= head.len();
+                    head = &[];
+                    while n > 0 {
+                        let first = bufs[0];
+                        bufs = &bufs[1..];
+                        if n < first.len() {
+                            head = &first[n..];
+                            n = 0;
+                        } else {
+                            n -= first.len();
+                        }
+                    }
+                }
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
 }
 
 /// An error that can occur when attempting to write multi-byte MessagePack value.
@@ -335,3 +393,29 @@ pub fn write_ext_meta<W: RmpWrite>(wr: &mut W, len: u32, ty: i8) -> Result<Marke
 
     Ok(marker)
 }
+
+/// Encodes and attempts to write a timestamp extension value, using the smallest of the three
+/// timestamp formats that can hold it.
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError` on any I/O error occurred while writing either the
+/// marker or the data.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::Timestamp;
+///
+/// let mut buf = Vec::new();
+/// rmp::encode::write_timestamp(&mut buf, Timestamp::new(1, 500).unwrap()).unwrap();
+///
+/// assert_eq!(vec![0xd7, 0xff, 0x00, 0x00, 0x07, 0xd0, 0x00, 0x00, 0x00, 0x01], buf);
+/// assert_eq!(Timestamp::new(1, 500), Some(rmp::decode::read_timestamp(&mut &buf[..]).unwrap()));
+/// ```
+pub fn write_timestamp<W: RmpWrite>(wr: &mut W, ts: Timestamp) -> Result<(), ValueWriteError<W::Error>> {
+    let mut buf = [0; 12];
+    let data = ts.ext_data(&mut buf);
+    write_ext_meta(wr, data.len() as u32, Timestamp::EXT_TYPE)?;
+    wr.write_bytes(data).map_err(ValueWriteError::InvalidDataWrite)
+}
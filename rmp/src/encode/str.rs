@@ -1,4 +1,4 @@
-use super::{write_marker, RmpWrite};
+use super::{write_marker, FixedByteBuf, RmpWrite};
 use crate::encode::ValueWriteError;
 use crate::Marker;
 
@@ -45,3 +45,21 @@ pub fn write_str<W: RmpWrite>(wr: &mut W, data: &str) -> Result<(), ValueWriteEr
     write_str_len(wr, data.len() as u32)?;
     wr.write_bytes(data.as_bytes()).map_err(ValueWriteError::InvalidDataWrite)
 }
+
+/// Encodes and attempts to write the most efficient string implementation to the given `Write`,
+/// passing the header and the data to the writer in a single vectored write.
+///
+/// See [`write_bin_vectored`](super::write_bin_vectored) for details.
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError::InvalidDataWrite` on any I/O error occurred while
+/// writing either the marker or the data.
+pub fn write_str_vectored<W: RmpWrite>(wr: &mut W, data: &str) -> Result<(), ValueWriteError<W::Error>> {
+    let mut storage = [0u8; 5];
+    let mut header = FixedByteBuf::new(&mut storage);
+    // The header is at most 5 bytes long, so this can't fail.
+    let _ = write_str_len(&mut header, data.len() as u32);
+    wr.write_bytes_vectored(&[header.as_slice(), data.as_bytes()])
+        .map_err(ValueWriteError::InvalidDataWrite)
+}
@@ -2,6 +2,10 @@
 //!
 //! This is used mainly for backwards compatibility and abstraction over std/no_std.
 
+use core::fmt::{self, Display, Formatter};
+
+use crate::Marker;
+
 /// An alias to the "default" error handling type.
 ///
 /// This is problematic because when working on `#[no_std]`, because there is no [`std::error::Error`] trait and also no [`std::io::Error`] type.
@@ -32,3 +36,111 @@ impl<T: ?Sized + std::error::Error> MaybeErrBound for T {}
 pub trait MaybeErrBound {}
 #[cfg(not(feature = "std"))]
 impl<T: ?Sized> MaybeErrBound for T {}
+
+/// An error annotated with the operation that failed and, if known, the marker it involved.
+///
+/// The error types of this crate only tell whether reading or writing a marker or its data
+/// failed. When a value is written or read in many steps, the functions of
+/// [`encode::annotated`](crate::encode::annotated) and [`decode::annotated`](crate::decode::annotated)
+/// tell which step failed. Errors of other functions can be annotated using [`ResultExt`].
+///
+/// # Examples
+///
+/// ```
+/// use rmp::{Marker, ResultExt};
+///
+/// let mut buf = [0u8; 1];
+/// let mut wr = &mut buf[..];
+/// rmp::encode::write_map_len(&mut wr, 1).with_operation("writing map len").unwrap();
+///
+/// let err = rmp::encode::write_str(&mut wr, "key")
+///     .with_operation_marker(Marker::FixStr(3), "writing map key")
+///     .unwrap_err();
+/// assert_eq!(Some(Marker::FixStr(3)), err.marker());
+/// assert!(err.to_string().starts_with("writing map key (FixStr(3)): "));
+/// ```
+#[derive(Debug)]
+pub struct ErrorContext<E> {
+    error: E,
+    marker: Option<Marker>,
+    operation: &'static str,
+}
+
+impl<E> ErrorContext<E> {
+    /// Annotates the error with the operation that failed, e.g. `"writing map len"`.
+    #[inline]
+    pub fn new(error: E, operation: &'static str) -> Self {
+        Self { error, marker: None, operation }
+    }
+
+    /// Adds the marker that was being read or written.
+    #[inline]
+    #[must_use]
+    pub fn with_marker(mut self, marker: Marker) -> Self {
+        self.marker = Some(marker);
+        self
+    }
+
+    /// Returns the underlying error.
+    #[inline]
+    pub fn error(&self) -> &E {
+        &self.error
+    }
+
+    /// Consumes the context and returns the underlying error.
+    #[inline]
+    pub fn into_error(self) -> E {
+        self.error
+    }
+
+    /// Returns the marker that was being read or written, if known.
+    #[inline]
+    pub fn marker(&self) -> Option<Marker> {
+        self.marker
+    }
+
+    /// Returns the operation that failed.
+    #[inline]
+    pub fn operation(&self) -> &'static str {
+        self.operation
+    }
+}
+
+impl<E: Display> Display for ErrorContext<E> {
+    #[cold]
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.marker {
+            Some(marker) => write!(f, "{} ({:?}): {}", self.operation, marker, self.error),
+            None => write!(f, "{}: {}", self.operation, self.error),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: std::error::Error + 'static> std::error::Error for ErrorContext<E> {
+    #[cold]
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Adds an [`ErrorContext`] to the error of a `Result`.
+pub trait ResultExt<T, E> {
+    /// Annotates the error, if any, with the operation that failed.
+    fn with_operation(self, operation: &'static str) -> Result<T, ErrorContext<E>>;
+
+    /// Annotates the error, if any, with the operation that failed and the marker it involved.
+    fn with_operation_marker(self, marker: Marker, operation: &'static str) -> Result<T, ErrorContext<E>>;
+}
+
+impl<T, E> ResultExt<T, E> for Result<T, E> {
+    #[inline]
+    fn with_operation(self, operation: &'static str) -> Result<T, ErrorContext<E>> {
+        self.map_err(|err| ErrorContext::new(err, operation))
+    }
+
+    #[inline]
+    fn with_operation_marker(self, marker: Marker, operation: &'static str) -> Result<T, ErrorContext<E>> {
+        self.map_err(|err| ErrorContext::new(err, operation).with_marker(marker))
+    }
+}
@@ -0,0 +1,278 @@
+//! Annotated dumps of encoded data, see [`inspect`].
+
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+use core::str;
+
+use crate::{Marker, Timestamp};
+
+/// Data bytes of strings, binaries and ext values shown at most.
+const MAX_SHOWN_LEN: usize = 32;
+/// Width of the hex column, enough for a marker followed by a 64-bit scalar.
+const HEX_WIDTH: usize = 9 * 3 - 1;
+
+/// Returns an annotated dump of all values encoded back to back in `buf`, for debugging.
+///
+/// Each value is printed on its own line with its offset, the bytes of its header and what they
+/// decode to. Elements of arrays and maps follow on their own lines, indented, with map keys and
+/// values alternating. The data of strings, binaries and ext values is shown up to 32 bytes.
+///
+/// Malformed or truncated input doesn't cause a formatting error: the dump ends with a line
+/// describing the problem instead. The format is meant to be read by humans and may change.
+///
+/// # Examples
+///
+/// ```
+/// // Encoded `{"id": 300, "tags": ["a"]}`.
+/// let buf = [0x82, 0xa2, 0x69, 0x64, 0xcd, 0x01, 0x2c, 0xa4, 0x74, 0x61, 0x67, 0x73, 0x91, 0xa1, 0x61];
+///
+/// let dump = rmp::inspect(&buf).to_string();
+/// let lines: Vec<_> = dump.lines().collect();
+/// assert_eq!("00000000  82                          fixmap(2)", lines[0]);
+/// assert_eq!("00000004  cd 01 2c                      u16 300", lines[2]);
+/// assert_eq!("0000000d  a1                              fixstr(1) \"a\"", lines[5]);
+/// ```
+#[inline]
+#[must_use]
+pub fn inspect(buf: &[u8]) -> Inspect<'_> {
+    Inspect { buf }
+}
+
+/// Annotated dump of encoded data, returned by [`inspect`].
+#[derive(Clone, Copy, Debug)]
+pub struct Inspect<'a> {
+    buf: &'a [u8],
+}
+
+impl Display for Inspect<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let buf = self.buf;
+        // Number of values still expected in each open container.
+        let mut pending: Vec<u64> = Vec::new();
+        let mut pos = 0;
+
+        while pos < buf.len() {
+            let start = pos;
+            let depth = pending.len();
+            let marker = Marker::from_u8(buf[pos]);
+            if marker == Marker::Reserved {
+                return error(f, start, depth, "reserved marker 0xc1");
+            }
+
+            let header_len = marker.header_len() + scalar_len(marker);
+            let head = match buf.get(start..start + header_len) {
+                Some(head) => head,
+                None => return error(f, start, depth, "unexpected end of input"),
+            };
+            pos += header_len;
+
+            let has_data = marker.is_str() || marker.is_bin() || marker.is_ext();
+            // The length of the data, or the number of elements of a container.
+            let data_len = match marker.fixed_len() {
+                Some(len) => len as usize,
+                None if marker.is_ext() => read_be(&head[1..header_len - 1]),
+                None if has_data || marker.is_container() => read_be(&head[1..]),
+                None => 0,
+            };
+            let data = if has_data {
+                match pos.checked_add(data_len).and_then(|end| buf.get(pos..end)) {
+                    Some(data) => data,
+                    None => return error(f, start, depth, "unexpected end of input"),
+                }
+            } else {
+                &[]
+            };
+            pos += data.len();
+
+            write!(f, "{start:08x}  ")?;
+            write_hex(f, head)?;
+            for _ in hex_len(head.len())..HEX_WIDTH {
+                f.write_str(" ")?;
+            }
+            write!(f, "  {:indent$}{}", "", name(marker), indent = 2 * depth)?;
+            describe(f, marker, head, data)?;
+            f.write_str("\n")?;
+
+            let children = match marker {
+                Marker::FixArray(..) | Marker::Array16 | Marker::Array32 => data_len as u64,
+                Marker::FixMap(..) | Marker::Map16 | Marker::Map32 => 2 * data_len as u64,
+                _ => 0,
+            };
+            if children > 0 {
+                pending.push(children);
+                continue;
+            }
+
+            // The value is complete, which may complete its enclosing containers as well.
+            while let Some(left) = pending.last_mut() {
+                *left -= 1;
+                if *left > 0 {
+                    break;
+                }
+                pending.pop();
+            }
+        }
+
+        if !pending.is_empty() {
+            return error(f, pos, pending.len(), "unexpected end of input");
+        }
+        Ok(())
+    }
+}
+
+/// Writes what the header and data of a value decode to, following the marker name.
+fn describe(f: &mut Formatter<'_>, marker: Marker, head: &[u8], data: &[u8]) -> fmt::Result {
+    match marker {
+        Marker::FixPos(val) => write!(f, " {val}"),
+        Marker::FixNeg(val) => write!(f, " {val}"),
+        Marker::U8 => write!(f, " {}", head[1]),
+        Marker::U16 => write!(f, " {}", u16::from_be_bytes(scalar(head))),
+        Marker::U32 => write!(f, " {}", u32::from_be_bytes(scalar(head))),
+        Marker::U64 => write!(f, " {}", u64::from_be_bytes(scalar(head))),
+        Marker::I8 => write!(f, " {}", head[1] as i8),
+        Marker::I16 => write!(f, " {}", i16::from_be_bytes(scalar(head))),
+        Marker::I32 => write!(f, " {}", i32::from_be_bytes(scalar(head))),
+        Marker::I64 => write!(f, " {}", i64::from_be_bytes(scalar(head))),
+        Marker::F32 => write!(f, " {:?}", f32::from_be_bytes(scalar(head))),
+        Marker::F64 => write!(f, " {:?}", f64::from_be_bytes(scalar(head))),
+        Marker::FixStr(..) | Marker::Str8 | Marker::Str16 | Marker::Str32 => {
+            write!(f, "({}) ", data.len())?;
+            let shown = &data[..data.len().min(MAX_SHOWN_LEN)];
+            match str::from_utf8(data) {
+                Ok(s) => {
+                    let mut end = shown.len();
+                    while !s.is_char_boundary(end) {
+                        end -= 1;
+                    }
+                    write!(f, "{:?}", &s[..end])?;
+                }
+                Err(..) => {
+                    write_hex(f, shown)?;
+                    f.write_str(" (invalid UTF-8)")?;
+                }
+            }
+            write_ellipsis(f, data)
+        }
+        Marker::Bin8 | Marker::Bin16 | Marker::Bin32 => {
+            write!(f, "({})", data.len())?;
+            write_data(f, data)
+        }
+        Marker::FixExt1 | Marker::FixExt2 | Marker::FixExt4 | Marker::FixExt8 | Marker::FixExt16 |
+        Marker::Ext8 | Marker::Ext16 | Marker::Ext32 => {
+            let ty = head[head.len() - 1] as i8;
+            write!(f, "(type {ty}, {})", data.len())?;
+            write_data(f, data)?;
+            match Timestamp::from_ext_data(data) {
+                Some(ts) if ty == Timestamp::EXT_TYPE => write!(f, " = timestamp {}.{:09}", ts.secs(), ts.nsecs()),
+                _ => Ok(()),
+            }
+        }
+        Marker::FixArray(len) | Marker::FixMap(len) => write!(f, "({len})"),
+        Marker::Array16 | Marker::Array32 | Marker::Map16 | Marker::Map32 => write!(f, "({})", read_be(&head[1..])),
+        Marker::Null | Marker::True | Marker::False | Marker::Reserved => Ok(()),
+    }
+}
+
+/// Writes a line reporting malformed input at the given offset.
+fn error(f: &mut Formatter<'_>, pos: usize, depth: usize, msg: &str) -> fmt::Result {
+    writeln!(f, "{pos:08x}  {:width$}  {:indent$}error: {msg}", "", "", width = HEX_WIDTH, indent = 2 * depth)
+}
+
+fn write_hex(f: &mut Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+    for (idx, byte) in bytes.iter().enumerate() {
+        if idx > 0 {
+            f.write_str(" ")?;
+        }
+        write!(f, "{byte:02x}")?;
+    }
+    Ok(())
+}
+
+/// Writes the data of a binary or ext value, up to [`MAX_SHOWN_LEN`] bytes.
+fn write_data(f: &mut Formatter<'_>, data: &[u8]) -> fmt::Result {
+    if !data.is_empty() {
+        f.write_str(" ")?;
+        write_hex(f, &data[..data.len().min(MAX_SHOWN_LEN)])?;
+    }
+    write_ellipsis(f, data)
+}
+
+fn write_ellipsis(f: &mut Formatter<'_>, data: &[u8]) -> fmt::Result {
+    if data.len() > MAX_SHOWN_LEN {
+        f.write_str(" ...")?;
+    }
+    Ok(())
+}
+
+#[inline]
+fn hex_len(len: usize) -> usize {
+    (3 * len).saturating_sub(1)
+}
+
+/// Returns the size of the data following a scalar marker.
+#[inline]
+fn scalar_len(marker: Marker) -> usize {
+    match marker {
+        Marker::U8 | Marker::I8 => 1,
+        Marker::U16 | Marker::I16 => 2,
+        Marker::U32 | Marker::I32 | Marker::F32 => 4,
+        Marker::U64 | Marker::I64 | Marker::F64 => 8,
+        _ => 0,
+    }
+}
+
+/// Returns the scalar data following the marker.
+#[inline]
+fn scalar<const N: usize>(head: &[u8]) -> [u8; N] {
+    let mut buf = [0; N];
+    buf.copy_from_slice(&head[1..=N]);
+    buf
+}
+
+/// Reads a big-endian length.
+#[inline]
+fn read_be(bytes: &[u8]) -> usize {
+    bytes.iter().fold(0, |acc, &b| (acc << 8) | usize::from(b))
+}
+
+fn name(marker: Marker) -> &'static str {
+    match marker {
+        Marker::FixPos(..) => "positive fixint",
+        Marker::FixNeg(..) => "negative fixint",
+        Marker::FixMap(..) => "fixmap",
+        Marker::FixArray(..) => "fixarray",
+        Marker::FixStr(..) => "fixstr",
+        Marker::Null => "nil",
+        Marker::Reserved => "reserved",
+        Marker::False => "false",
+        Marker::True => "true",
+        Marker::Bin8 => "bin8",
+        Marker::Bin16 => "bin16",
+        Marker::Bin32 => "bin32",
+        Marker::Ext8 => "ext8",
+        Marker::Ext16 => "ext16",
+        Marker::Ext32 => "ext32",
+        Marker::F32 => "f32",
+        Marker::F64 => "f64",
+        Marker::U8 => "u8",
+        Marker::U16 => "u16",
+        Marker::U32 => "u32",
+        Marker::U64 => "u64",
+        Marker::I8 => "i8",
+        Marker::I16 => "i16",
+        Marker::I32 => "i32",
+        Marker::I64 => "i64",
+        Marker::FixExt1 => "fixext1",
+        Marker::FixExt2 => "fixext2",
+        Marker::FixExt4 => "fixext4",
+        Marker::FixExt8 => "fixext8",
+        Marker::FixExt16 => "fixext16",
+        Marker::Str8 => "str8",
+        Marker::Str16 => "str16",
+        Marker::Str32 => "str32",
+        Marker::Array16 => "array16",
+        Marker::Array32 => "array32",
+        Marker::Map16 => "map16",
+        Marker::Map32 => "map32",
+    }
+}
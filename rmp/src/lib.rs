@@ -3,12 +3,27 @@
 
 extern crate alloc;
 
+#[doc(hidden)]
+pub mod base64;
+#[cfg(feature = "std")]
+pub mod chunked;
 pub mod decode;
 pub mod encode;
 mod errors;
+mod inspect;
+#[cfg(feature = "std")]
+pub mod log;
 mod marker;
+mod timestamp;
+#[cfg(feature = "tokio")]
+pub mod tokio;
+mod validate;
 
-pub use crate::marker::Marker;
+pub use crate::errors::{ErrorContext, ResultExt};
+pub use crate::inspect::{inspect, Inspect};
+pub use crate::marker::{Marker, MARKERS};
+pub use crate::timestamp::Timestamp;
+pub use crate::validate::{validate, validate_with_limits, ValidationError, Validator, MAX_VALIDATION_DEPTH};
 
 /// Version of the MessagePack [spec](http://github.com/msgpack/msgpack/blob/master/spec.md).
 pub const MSGPACK_VERSION: u32 = 5;
@@ -0,0 +1,199 @@
+//! Append-only logs of independent MessagePack values, e.g. stored in a single file.
+//!
+//! Records are written back to back, without any framing: each record is exactly one encoded
+//! value, and its length is known from the value itself. A crash while appending leaves at most
+//! one incomplete record at the end, which [`LogReader`] detects and skips.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::io::Cursor;
+//!
+//! use rmp::log::{self, LogReader};
+//!
+//! let mut file = Cursor::new(Vec::new());
+//! assert_eq!(0, log::append(&mut file, &[0xa2, 0x68, 0x69]).unwrap());
+//! assert_eq!(3, log::append(&mut file, &[0x92, 0x01, 0x02]).unwrap());
+//!
+//! // An interrupted write of a third record.
+//! file.get_mut().extend_from_slice(&[0x93, 0x01]);
+//!
+//! let mut rd = LogReader::new(&file.get_ref()[..]);
+//! let records: Vec<_> = rd.by_ref().map(|rec| rec.unwrap()).collect();
+//! assert_eq!(vec![(0, 3, vec![0xa2, 0x68, 0x69]), (3, 3, vec![0x92, 0x01, 0x02])], records);
+//! assert!(rd.is_truncated());
+//! assert_eq!(6, rd.valid_len());
+//! ```
+
+use std::io::{self, ErrorKind, Read, Seek, SeekFrom, Write};
+
+use crate::{validate, ValidationError, Validator};
+
+/// Number of bytes requested from the reader at a time.
+const CHUNK_LEN: usize = 8 * 1024;
+
+/// Appends the given encoded value at the end of the log, returning its offset.
+///
+/// # Errors
+///
+/// Returns an `InvalidInput` error if `value` isn't exactly one well-formed MessagePack value, so
+/// that it can be read back, and any I/O error while seeking or writing.
+pub fn append<W: Write + Seek>(wr: &mut W, value: &[u8]) -> io::Result<u64> {
+    match validate(value) {
+        Ok(len) if len == value.len() => {}
+        _ => return Err(io::Error::new(ErrorKind::InvalidInput, "not exactly one MessagePack value")),
+    }
+
+    let offset = wr.seek(SeekFrom::End(0))?;
+    wr.write_all(value)?;
+    Ok(offset)
+}
+
+/// An iterator over the records of a log, in the order they were appended.
+///
+/// Each record is returned as its offset in the log, its encoded length and its value, which is
+/// the encoded bytes themselves unless another decoder is given with
+/// [`with_decoder`](LogReader::with_decoder).
+///
+/// Iteration stops at the end of the log. If the log ends with an incomplete record, it is
+/// skipped and reported by [`is_truncated`](LogReader::is_truncated) instead, so that the log can
+/// be cut back to [`valid_len`](LogReader::valid_len) before appending to it again. Malformed
+/// data is returned as an `InvalidData` error, after which iteration stops.
+#[derive(Debug)]
+pub struct LogReader<R, F = fn(&[u8]) -> io::Result<Vec<u8>>> {
+    rd: R,
+    decode: F,
+    buf: Vec<u8>,
+    /// Position of the next record in `buf`.
+    pos: usize,
+    /// Offset of the next record in the log.
+    offset: u64,
+    /// Progress validating the next record, kept across reads.
+    validator: Validator,
+    truncated: bool,
+    done: bool,
+}
+
+impl<R: Read> LogReader<R> {
+    /// Creates a reader of the log starting at the current position of `rd`, returning the
+    /// encoded bytes of each record.
+    #[must_use]
+    pub fn new(rd: R) -> Self {
+        Self::with_decoder(rd, |data| Ok(data.to_vec()))
+    }
+}
+
+impl<R: Read, F> LogReader<R, F> {
+    /// Creates a reader of the log starting at the current position of `rd`, returning the
+    /// records as decoded by `decode` from their encoded bytes.
+    ///
+    /// An error from `decode` is returned in place of the record, and iteration goes on with the
+    /// next one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{Error, ErrorKind};
+    ///
+    /// use rmp::decode;
+    /// use rmp::log::LogReader;
+    ///
+    /// let log = [0x01, 0xcd, 0x01, 0x00];
+    /// let rd = LogReader::with_decoder(&log[..], |mut data: &[u8]| {
+    ///     decode::read_int::<u16, _>(&mut data).map_err(|err| Error::new(ErrorKind::InvalidData, err))
+    /// });
+    /// let records: Vec<_> = rd.map(Result::unwrap).collect();
+    /// assert_eq!(vec![(0, 1, 1), (1, 3, 256)], records);
+    /// ```
+    #[must_use]
+    pub fn with_decoder(rd: R, decode: F) -> Self {
+        LogReader {
+            rd,
+            decode,
+            buf: Vec::new(),
+            pos: 0,
+            offset: 0,
+            validator: Validator::new(),
+            truncated: false,
+            done: false,
+        }
+    }
+
+    /// Returns `true` if the log was found to end with an incomplete record.
+    #[inline]
+    #[must_use]
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// Returns the length of the log up to the end of the last record read.
+    #[inline]
+    #[must_use]
+    pub fn valid_len(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns the underlying reader, consuming this reader.
+    #[inline]
+    pub fn into_inner(self) -> R {
+        self.rd
+    }
+
+    /// Reads more data into the buffer, returning `false` at the end of the log.
+    fn fill(&mut self) -> io::Result<bool> {
+        self.buf.drain(..self.pos);
+        self.pos = 0;
+
+        let len = self.buf.len();
+        self.buf.resize(len + CHUNK_LEN, 0);
+        loop {
+            match self.rd.read(&mut self.buf[len..]) {
+                Ok(n) => {
+                    self.buf.truncate(len + n);
+                    return Ok(n > 0);
+                }
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => {
+                    self.buf.truncate(len);
+                    return Err(err);
+                }
+            }
+        }
+    }
+}
+
+impl<R: Read, T, F: FnMut(&[u8]) -> io::Result<T>> Iterator for LogReader<R, F> {
+    type Item = io::Result<(u64, usize, T)>;
+
+    fn next(&mut self) -> Option<io::Result<(u64, usize, T)>> {
+        while !self.done {
+            match self.validator.validate(&self.buf[self.pos..]) {
+                Ok(len) => {
+                    let offset = self.offset;
+                    let value = (self.decode)(&self.buf[self.pos..self.pos + len]);
+                    self.pos += len;
+                    self.offset += len as u64;
+                    return Some(value.map(|value| (offset, len, value)));
+                }
+                Err(ValidationError::Truncated) => {}
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(io::Error::new(ErrorKind::InvalidData, err)));
+                }
+            }
+
+            match self.fill() {
+                Ok(true) => {}
+                Ok(false) => {
+                    self.done = true;
+                    self.truncated = self.pos < self.buf.len();
+                }
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+        None
+    }
+}
@@ -46,51 +46,81 @@ pub enum Marker {
     FixNeg(i8) = 0xe0,
 }
 
+/// Markers of all 256 byte values, indexed by the byte.
+///
+/// This is what [`Marker::from_u8`] looks the marker up in. Parsers dispatching on the first byte
+/// of a value with their own jump tables may index it directly.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::{Marker, MARKERS};
+///
+/// assert_eq!(Marker::FixArray(3), MARKERS[0x93]);
+/// assert_eq!(Marker::U16, MARKERS[0xcd]);
+/// ```
+pub const MARKERS: [Marker; 256] = {
+    let mut markers = [Marker::Reserved; 256];
+    let mut n = 0;
+    while n < markers.len() {
+        markers[n] = decode(n as u8);
+        n += 1;
+    }
+    markers
+};
+
+/// Decodes a marker from a single byte, used to fill [`MARKERS`].
+const fn decode(n: u8) -> Marker {
+    match n {
+        0x00 ..= 0x7f => Marker::FixPos(n),
+        0x80 ..= 0x8f => Marker::FixMap(n & FIXMAP_SIZE),
+        0x90 ..= 0x9f => Marker::FixArray(n & FIXARRAY_SIZE),
+        0xa0 ..= 0xbf => Marker::FixStr(n & FIXSTR_SIZE),
+        0xc0 => Marker::Null,
+        // Marked in MessagePack spec as never used.
+        0xc1 => Marker::Reserved,
+        0xc2 => Marker::False,
+        0xc3 => Marker::True,
+        0xc4 => Marker::Bin8,
+        0xc5 => Marker::Bin16,
+        0xc6 => Marker::Bin32,
+        0xc7 => Marker::Ext8,
+        0xc8 => Marker::Ext16,
+        0xc9 => Marker::Ext32,
+        0xca => Marker::F32,
+        0xcb => Marker::F64,
+        0xcc => Marker::U8,
+        0xcd => Marker::U16,
+        0xce => Marker::U32,
+        0xcf => Marker::U64,
+        0xd0 => Marker::I8,
+        0xd1 => Marker::I16,
+        0xd2 => Marker::I32,
+        0xd3 => Marker::I64,
+        0xd4 => Marker::FixExt1,
+        0xd5 => Marker::FixExt2,
+        0xd6 => Marker::FixExt4,
+        0xd7 => Marker::FixExt8,
+        0xd8 => Marker::FixExt16,
+        0xd9 => Marker::Str8,
+        0xda => Marker::Str16,
+        0xdb => Marker::Str32,
+        0xdc => Marker::Array16,
+        0xdd => Marker::Array32,
+        0xde => Marker::Map16,
+        0xdf => Marker::Map32,
+        0xe0 ..= 0xff => Marker::FixNeg(n as i8),
+    }
+}
+
 impl Marker {
     /// Construct a msgpack marker from a single byte.
+    ///
+    /// This is a lookup in [`MARKERS`].
     #[must_use]
     #[inline]
-    pub fn from_u8(n: u8) -> Marker {
-        match n {
-            0x00 ..= 0x7f => Marker::FixPos(n),
-            0x80 ..= 0x8f => Marker::FixMap(n & FIXMAP_SIZE),
-            0x90 ..= 0x9f => Marker::FixArray(n & FIXARRAY_SIZE),
-            0xa0 ..= 0xbf => Marker::FixStr(n & FIXSTR_SIZE),
-            0xc0 => Marker::Null,
-            // Marked in MessagePack spec as never used.
-            0xc1 => Marker::Reserved,
-            0xc2 => Marker::False,
-            0xc3 => Marker::True,
-            0xc4 => Marker::Bin8,
-            0xc5 => Marker::Bin16,
-            0xc6 => Marker::Bin32,
-            0xc7 => Marker::Ext8,
-            0xc8 => Marker::Ext16,
-            0xc9 => Marker::Ext32,
-            0xca => Marker::F32,
-            0xcb => Marker::F64,
-            0xcc => Marker::U8,
-            0xcd => Marker::U16,
-            0xce => Marker::U32,
-            0xcf => Marker::U64,
-            0xd0 => Marker::I8,
-            0xd1 => Marker::I16,
-            0xd2 => Marker::I32,
-            0xd3 => Marker::I64,
-            0xd4 => Marker::FixExt1,
-            0xd5 => Marker::FixExt2,
-            0xd6 => Marker::FixExt4,
-            0xd7 => Marker::FixExt8,
-            0xd8 => Marker::FixExt16,
-            0xd9 => Marker::Str8,
-            0xda => Marker::Str16,
-            0xdb => Marker::Str32,
-            0xdc => Marker::Array16,
-            0xdd => Marker::Array32,
-            0xde => Marker::Map16,
-            0xdf => Marker::Map32,
-            0xe0 ..= 0xff => Marker::FixNeg(n as i8),
-        }
+    pub const fn from_u8(n: u8) -> Marker {
+        MARKERS[n as usize]
     }
 
     /// Converts a marker object into a single-byte representation.
@@ -148,6 +178,132 @@ impl Marker {
             Marker::Reserved      => 0xc1,
         }
     }
+
+    /// Returns `true` if the marker starts an integer, either a fixint or an `int`/`uint` family
+    /// marker.
+    #[must_use]
+    #[inline]
+    pub fn is_int(&self) -> bool {
+        matches!(*self,
+            Marker::FixPos(..) | Marker::FixNeg(..) |
+            Marker::U8 | Marker::U16 | Marker::U32 | Marker::U64 |
+            Marker::I8 | Marker::I16 | Marker::I32 | Marker::I64
+        )
+    }
+
+    /// Returns `true` if the marker starts a single or double precision float.
+    #[must_use]
+    #[inline]
+    pub fn is_float(&self) -> bool {
+        matches!(*self, Marker::F32 | Marker::F64)
+    }
+
+    /// Returns `true` if the marker starts a string.
+    #[must_use]
+    #[inline]
+    pub fn is_str(&self) -> bool {
+        matches!(*self, Marker::FixStr(..) | Marker::Str8 | Marker::Str16 | Marker::Str32)
+    }
+
+    /// Returns `true` if the marker starts a binary.
+    #[must_use]
+    #[inline]
+    pub fn is_bin(&self) -> bool {
+        matches!(*self, Marker::Bin8 | Marker::Bin16 | Marker::Bin32)
+    }
+
+    /// Returns `true` if the marker starts an extension.
+    #[must_use]
+    #[inline]
+    pub fn is_ext(&self) -> bool {
+        matches!(*self,
+            Marker::FixExt1 | Marker::FixExt2 | Marker::FixExt4 | Marker::FixExt8 | Marker::FixExt16 |
+            Marker::Ext8 | Marker::Ext16 | Marker::Ext32
+        )
+    }
+
+    /// Returns `true` if the marker starts an array.
+    #[must_use]
+    #[inline]
+    pub fn is_array(&self) -> bool {
+        matches!(*self, Marker::FixArray(..) | Marker::Array16 | Marker::Array32)
+    }
+
+    /// Returns `true` if the marker starts a map.
+    #[must_use]
+    #[inline]
+    pub fn is_map(&self) -> bool {
+        matches!(*self, Marker::FixMap(..) | Marker::Map16 | Marker::Map32)
+    }
+
+    /// Returns `true` if the marker starts an array or a map, i.e. a value followed by other
+    /// values rather than by raw data.
+    #[must_use]
+    #[inline]
+    pub fn is_container(&self) -> bool {
+        self.is_array() || self.is_map()
+    }
+
+    /// Returns the length stored in the marker itself, without reading any further bytes.
+    ///
+    /// This is the number of bytes for fixstr, the number of elements (or pairs) for fixarray and
+    /// fixmap, and the size of data for fixext markers. For all other markers the length, if any,
+    /// follows the marker and `None` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmp::Marker;
+    ///
+    /// assert_eq!(Some(3), Marker::from_u8(0x93).fixed_len());
+    /// assert_eq!(Some(8), Marker::FixExt8.fixed_len());
+    /// assert_eq!(None, Marker::Str8.fixed_len());
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn fixed_len(&self) -> Option<u32> {
+        match *self {
+            Marker::FixStr(len) |
+            Marker::FixArray(len) |
+            Marker::FixMap(len) => Some(u32::from(len)),
+            Marker::FixExt1 => Some(1),
+            Marker::FixExt2 => Some(2),
+            Marker::FixExt4 => Some(4),
+            Marker::FixExt8 => Some(8),
+            Marker::FixExt16 => Some(16),
+            _ => None,
+        }
+    }
+
+    /// Returns the size in bytes of the value header: the marker itself and the length and
+    /// extension type fields following it.
+    ///
+    /// In other words, this is the offset of the data (or of the first element of a container)
+    /// from the marker. Scalars have no length field, so this is `1` for them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmp::Marker;
+    ///
+    /// assert_eq!(1, Marker::U32.header_len());
+    /// assert_eq!(3, Marker::Str16.header_len());
+    /// assert_eq!(6, Marker::Ext32.header_len());
+    /// ```
+    #[must_use]
+    #[inline]
+    pub fn header_len(&self) -> usize {
+        match *self {
+            Marker::Str8 | Marker::Bin8 => 2,
+            Marker::Str16 | Marker::Bin16 | Marker::Array16 | Marker::Map16 => 3,
+            Marker::Str32 | Marker::Bin32 | Marker::Array32 | Marker::Map32 => 5,
+            Marker::FixExt1 | Marker::FixExt2 | Marker::FixExt4 | Marker::FixExt8 | Marker::FixExt16 => 2,
+            Marker::Ext8 => 3,
+            Marker::Ext16 => 4,
+            Marker::Ext32 => 6,
+            _ => 1,
+        }
+    }
 }
 
 impl From<u8> for Marker {
@@ -0,0 +1,180 @@
+#[cfg(feature = "std")]
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Timestamp extension type, which represents an instantaneous point on the time-line in the
+/// world that is independent from time zones or calendars.
+///
+/// It is stored as the number of seconds since 1970-01-01 00:00:00 UTC plus nanoseconds within
+/// that second, and uses the predefined extension type `-1`.
+///
+/// Depending on the value, it is encoded in one of three formats: 32-bit (seconds only, unsigned),
+/// 64-bit (34-bit unsigned seconds and 30-bit nanoseconds) or 96-bit (64-bit signed seconds and
+/// 32-bit nanoseconds).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Timestamp {
+    secs: i64,
+    nsecs: u32,
+}
+
+impl Timestamp {
+    /// The extension type reserved for timestamps.
+    pub const EXT_TYPE: i8 = -1;
+
+    /// Creates a new timestamp from seconds since the epoch and nanoseconds within the second.
+    ///
+    /// Returns `None` if `nsecs` is not less than 1 000 000 000.
+    #[inline]
+    #[must_use]
+    pub fn new(secs: i64, nsecs: u32) -> Option<Self> {
+        if nsecs < 1_000_000_000 {
+            Some(Self { secs, nsecs })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the number of seconds since 1970-01-01 00:00:00 UTC.
+    #[inline]
+    #[must_use]
+    pub fn secs(&self) -> i64 {
+        self.secs
+    }
+
+    /// Returns the number of nanoseconds within the second.
+    #[inline]
+    #[must_use]
+    pub fn nsecs(&self) -> u32 {
+        self.nsecs
+    }
+
+    /// Creates a timestamp from the number of nanoseconds since 1970-01-01 00:00:00 UTC.
+    ///
+    /// Returns `None` if the number of seconds doesn't fit `i64`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmp::Timestamp;
+    ///
+    /// assert_eq!(Timestamp::new(-2, 999_999_999), Timestamp::from_unix_nanos(-1_000_000_001));
+    /// assert_eq!(None, Timestamp::from_unix_nanos(i128::MAX));
+    /// ```
+    #[must_use]
+    pub fn from_unix_nanos(nanos: i128) -> Option<Self> {
+        let secs = i64::try_from(nanos.div_euclid(1_000_000_000)).ok()?;
+        Self::new(secs, nanos.rem_euclid(1_000_000_000) as u32)
+    }
+
+    /// Returns the number of nanoseconds since 1970-01-01 00:00:00 UTC, which is negative for
+    /// earlier points in time.
+    #[inline]
+    #[must_use]
+    pub fn unix_nanos(&self) -> i128 {
+        i128::from(self.secs) * 1_000_000_000 + i128::from(self.nsecs)
+    }
+
+    /// Converts a system time to a timestamp.
+    ///
+    /// Returns `None` if the number of seconds since the epoch doesn't fit `i64`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, UNIX_EPOCH};
+    ///
+    /// use rmp::Timestamp;
+    ///
+    /// let time = UNIX_EPOCH - Duration::from_millis(1500);
+    /// let ts = Timestamp::from_system_time(time).unwrap();
+    /// assert_eq!(Timestamp::new(-2, 500_000_000), Some(ts));
+    /// assert_eq!(Some(time), ts.to_system_time());
+    /// ```
+    #[cfg(feature = "std")]
+    #[must_use]
+    pub fn from_system_time(time: SystemTime) -> Option<Self> {
+        match time.duration_since(UNIX_EPOCH) {
+            Ok(since) => Self::new(i64::try_from(since.as_secs()).ok()?, since.subsec_nanos()),
+            Err(err) => {
+                let before = err.duration();
+                let secs = i64::try_from(before.as_secs()).ok()?;
+                match before.subsec_nanos() {
+                    0 => Self::new(-secs, 0),
+                    nsecs => Self::new((-secs).checked_sub(1)?, 1_000_000_000 - nsecs),
+                }
+            }
+        }
+    }
+
+    /// Converts this timestamp to a system time.
+    ///
+    /// Returns `None` if the system time can't represent it.
+    #[cfg(feature = "std")]
+    #[must_use]
+    pub fn to_system_time(&self) -> Option<SystemTime> {
+        let nsecs = Duration::from_nanos(u64::from(self.nsecs));
+        if self.secs >= 0 {
+            UNIX_EPOCH.checked_add(Duration::from_secs(self.secs as u64))?.checked_add(nsecs)
+        } else {
+            UNIX_EPOCH.checked_sub(Duration::from_secs(self.secs.unsigned_abs()))?.checked_add(nsecs)
+        }
+    }
+
+    /// Parses the data of a timestamp extension value, i.e. without the marker, length and type.
+    ///
+    /// Returns `None` if the length of `data` doesn't match any of the three formats, or if the
+    /// nanoseconds part is out of range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmp::Timestamp;
+    ///
+    /// assert_eq!(Timestamp::new(2, 0), Timestamp::from_ext_data(&[0, 0, 0, 2]));
+    /// assert_eq!(None, Timestamp::from_ext_data(&[0, 0, 2]));
+    /// ```
+    #[must_use]
+    pub fn from_ext_data(data: &[u8]) -> Option<Self> {
+        match *data {
+            [a, b, c, d] => Self::new(i64::from(u32::from_be_bytes([a, b, c, d])), 0),
+            [a, b, c, d, e, f, g, h] => {
+                let data = u64::from_be_bytes([a, b, c, d, e, f, g, h]);
+                Self::new((data & 0x0000_0003_ffff_ffff) as i64, (data >> 34) as u32)
+            }
+            [a, b, c, d, ref secs @ ..] if secs.len() == 8 => {
+                let mut buf = [0; 8];
+                buf.copy_from_slice(secs);
+                Self::new(i64::from_be_bytes(buf), u32::from_be_bytes([a, b, c, d]))
+            }
+            _ => None,
+        }
+    }
+
+    /// Encodes this timestamp as extension data into the given buffer, returning the written
+    /// part.
+    ///
+    /// The smallest of the three formats that can hold the value is used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmp::Timestamp;
+    ///
+    /// let mut buf = [0; 12];
+    /// assert_eq!(&[0, 0, 0, 2], Timestamp::new(2, 0).unwrap().ext_data(&mut buf));
+    /// assert_eq!(12, Timestamp::new(-1, 0).unwrap().ext_data(&mut buf).len());
+    /// ```
+    pub fn ext_data<'a>(&self, buf: &'a mut [u8; 12]) -> &'a [u8] {
+        if self.secs >> 34 == 0 {
+            let data = (u64::from(self.nsecs) << 34) | self.secs as u64;
+            if data >> 32 == 0 {
+                buf[..4].copy_from_slice(&(data as u32).to_be_bytes());
+                return &buf[..4];
+            }
+            buf[..8].copy_from_slice(&data.to_be_bytes());
+            return &buf[..8];
+        }
+        buf[..4].copy_from_slice(&self.nsecs.to_be_bytes());
+        buf[4..].copy_from_slice(&self.secs.to_be_bytes());
+        &buf[..]
+    }
+}
@@ -0,0 +1,206 @@
+//! Async counterparts of the functions in [`crate::decode`].
+//!
+//! On a type mismatch only the marker is consumed, like with the sync functions, so the value
+//! may still be read with another function.
+
+use std::io;
+use std::str::from_utf8;
+
+use ::tokio::io::{AsyncRead, AsyncReadExt};
+use num_traits::FromPrimitive;
+
+use crate::decode::{self, DecodeStringError, ExtMeta, MarkerReadError, NumValueReadError, ValueReadError};
+use crate::{Marker, Timestamp};
+
+/// The longest value that is decoded in one piece, fixext 16.
+const MAX_DECODED_LEN: usize = 18;
+
+/// Returns the size of the data following a scalar marker.
+#[inline]
+fn scalar_len(marker: Marker) -> usize {
+    match marker {
+        Marker::U8 | Marker::I8 => 1,
+        Marker::U16 | Marker::I16 => 2,
+        Marker::U32 | Marker::I32 | Marker::F32 => 4,
+        Marker::U64 | Marker::I64 | Marker::F64 => 8,
+        _ => 0,
+    }
+}
+
+/// Reads the marker and, if it is accepted, the rest of the header or the scalar data into `buf`,
+/// returning the number of bytes read.
+async fn read_head<R>(rd: &mut R, buf: &mut [u8; MAX_DECODED_LEN], accepts: fn(&Marker) -> bool) -> Result<usize, ValueReadError<io::Error>>
+    where R: AsyncRead + Unpin
+{
+    let marker = read_marker(rd).await?;
+    if !accepts(&marker) {
+        return Err(ValueReadError::TypeMismatch(marker));
+    }
+
+    let len = marker.header_len() + scalar_len(marker);
+    buf[0] = marker.to_u8();
+    rd.read_exact(&mut buf[1..len]).await.map_err(ValueReadError::InvalidDataRead)?;
+    Ok(len)
+}
+
+/// Async counterpart of [`read_marker`](decode::read_marker).
+///
+/// # Errors
+///
+/// Returns `MarkerReadError` on any I/O error while reading the marker.
+#[inline]
+pub async fn read_marker<R: AsyncRead + Unpin>(rd: &mut R) -> Result<Marker, MarkerReadError<io::Error>> {
+    Ok(Marker::from_u8(rd.read_u8().await?))
+}
+
+macro_rules! read_decoded_fns {
+    ($($name:ident -> $ret:ty, $accepts:expr;)*) => {
+        $(
+            #[doc = concat!("Async counterpart of [`", stringify!($name), "`](decode::", stringify!($name), ").")]
+            ///
+            /// # Errors
+            ///
+            /// This function will return `ValueReadError` on any I/O error while reading either the
+            /// marker or the data.
+            ///
+            /// It also returns `ValueReadError::TypeMismatch` if the actual type is not equal with
+            /// the expected one, indicating you with the actual type.
+            pub async fn $name<R: AsyncRead + Unpin>(rd: &mut R) -> Result<$ret, ValueReadError<io::Error>> {
+                let mut buf = [0; MAX_DECODED_LEN];
+                let len = read_head(rd, &mut buf, $accepts).await?;
+                decode::$name(&mut &buf[..len])
+            }
+        )*
+    };
+}
+
+read_decoded_fns! {
+    read_nil -> (), |marker| *marker == Marker::Null;
+    read_bool -> bool, |marker| matches!(marker, Marker::True | Marker::False);
+    read_pfix -> u8, |marker| matches!(marker, Marker::FixPos(..));
+    read_nfix -> i8, |marker| matches!(marker, Marker::FixNeg(..));
+    read_u8 -> u8, |marker| *marker == Marker::U8;
+    read_u16 -> u16, |marker| *marker == Marker::U16;
+    read_u32 -> u32, |marker| *marker == Marker::U32;
+    read_u64 -> u64, |marker| *marker == Marker::U64;
+    read_i8 -> i8, |marker| *marker == Marker::I8;
+    read_i16 -> i16, |marker| *marker == Marker::I16;
+    read_i32 -> i32, |marker| *marker == Marker::I32;
+    read_i64 -> i64, |marker| *marker == Marker::I64;
+    read_f32 -> f32, |marker| *marker == Marker::F32;
+    read_f64 -> f64, |marker| *marker == Marker::F64;
+    read_str_len -> u32, Marker::is_str;
+    read_bin_len -> u32, Marker::is_bin;
+    read_array_len -> u32, Marker::is_array;
+    read_map_len -> u32, Marker::is_map;
+    read_ext_meta -> ExtMeta, Marker::is_ext;
+}
+
+macro_rules! read_fixext_fns {
+    ($($name:ident -> $ret:ty, $marker:ident($size:expr);)*) => {
+        $(
+            #[doc = concat!("Async counterpart of [`", stringify!($name), "`](decode::", stringify!($name), ").")]
+            ///
+            /// # Errors
+            ///
+            /// This function will return `ValueReadError` on any I/O error while reading either the
+            /// marker or the data.
+            pub async fn $name<R: AsyncRead + Unpin>(rd: &mut R) -> Result<(i8, $ret), ValueReadError<io::Error>> {
+                let mut buf = [0; MAX_DECODED_LEN];
+                let len = read_head(rd, &mut buf, |marker| *marker == Marker::$marker).await?;
+                rd.read_exact(&mut buf[len..len + $size]).await.map_err(ValueReadError::InvalidDataRead)?;
+                decode::$name(&mut &buf[..len + $size])
+            }
+        )*
+    };
+}
+
+read_fixext_fns! {
+    read_fixext1 -> u8, FixExt1(1);
+    read_fixext2 -> [u8; 2], FixExt2(2);
+    read_fixext4 -> [u8; 4], FixExt4(4);
+    read_fixext8 -> [u8; 8], FixExt8(8);
+    read_fixext16 -> [u8; 16], FixExt16(16);
+}
+
+/// Async counterpart of [`read_int`](decode::read_int).
+///
+/// # Errors
+///
+/// This function will return `NumValueReadError` on any I/O error while reading either the marker
+/// or the data.
+///
+/// It also returns `NumValueReadError::OutOfRange` if the actual value does not fit in `T`, and
+/// `NumValueReadError::TypeMismatch` if the value is not an integer.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let buf = [0xcd, 0x1, 0x2c];
+///
+/// assert_eq!(300u16, rmp::tokio::decode::read_int(&mut &buf[..]).await.unwrap());
+/// assert_eq!(300i64, rmp::tokio::decode::read_int(&mut &buf[..]).await.unwrap());
+/// # }
+/// ```
+pub async fn read_int<T, R>(rd: &mut R) -> Result<T, NumValueReadError<io::Error>>
+    where T: FromPrimitive,
+          R: AsyncRead + Unpin
+{
+    let mut buf = [0; MAX_DECODED_LEN];
+    let len = read_head(rd, &mut buf, Marker::is_int).await?;
+    decode::read_int(&mut &buf[..len])
+}
+
+/// Async counterpart of [`read_timestamp`](decode::read_timestamp).
+///
+/// # Errors
+///
+/// This function will return `ValueReadError` on any I/O error while reading either the marker or
+/// the data.
+///
+/// It also returns `ValueReadError::TypeMismatch` with the actual marker if the value is not a
+/// timestamp. In that case the reader is left in an unspecified position.
+pub async fn read_timestamp<R: AsyncRead + Unpin>(rd: &mut R) -> Result<Timestamp, ValueReadError<io::Error>> {
+    let mut buf = [0; MAX_DECODED_LEN];
+    let accepts = |marker: &Marker| matches!(marker, Marker::FixExt4 | Marker::FixExt8 | Marker::Ext8);
+    let len = read_head(rd, &mut buf, accepts).await?;
+    let size = match Marker::from_u8(buf[0]).fixed_len() {
+        Some(size) => size as usize,
+        // Timestamp 96, other sizes are rejected by the sync function right after the header.
+        None if buf[1] == 12 => 12,
+        None => 0,
+    };
+
+    rd.read_exact(&mut buf[len..len + size]).await.map_err(ValueReadError::InvalidDataRead)?;
+    decode::read_timestamp(&mut &buf[..len + size])
+}
+
+/// Async counterpart of [`read_str`](decode::read_str), reading the string data into the given
+/// buffer.
+///
+/// # Errors
+///
+/// Returns `Err` in the following cases:
+///
+///  - if any IO error (including unexpected EOF) occurs while reading `rd`.
+///  - if the `buf` buffer size is not large enough to keep all the data copied.
+///  - if the data is not utf-8, with a description as to why the provided data is not utf-8.
+pub async fn read_str<'r, R>(rd: &mut R, buf: &'r mut [u8]) -> Result<&'r str, DecodeStringError<'r, io::Error>>
+    where R: AsyncRead + Unpin
+{
+    let len = read_str_len(rd).await?;
+    let ulen = len as usize;
+
+    if buf.len() < ulen {
+        return Err(DecodeStringError::BufferSizeTooSmall(len));
+    }
+
+    let buf = &mut buf[..ulen];
+    rd.read_exact(buf).await.map_err(DecodeStringError::InvalidDataRead)?;
+    match from_utf8(buf) {
+        Ok(decoded) => Ok(decoded),
+        Err(err) => Err(DecodeStringError::InvalidUtf8(buf, err)),
+    }
+}
@@ -0,0 +1,158 @@
+//! Async counterparts of the functions in [`crate::encode`].
+
+use std::io;
+
+use ::tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use crate::encode::{self, ValueWriteError};
+use crate::{Marker, Timestamp};
+
+/// The longest value that is encoded in one piece, timestamp 96.
+const MAX_ENCODED_LEN: usize = 15;
+
+/// Encodes a value with the given sync function into a stack buffer and writes it.
+///
+/// The marker is written separately from the rest, so that I/O errors are reported the same way
+/// as by the sync functions.
+async fn write_encoded<W, T, F>(wr: &mut W, encode: F) -> Result<T, ValueWriteError<io::Error>>
+where
+    W: AsyncWrite + Unpin,
+    F: FnOnce(&mut &mut [u8]) -> Result<T, ValueWriteError<io::Error>>,
+{
+    let mut buf = [0u8; MAX_ENCODED_LEN];
+    let mut rest = &mut buf[..];
+    let ret = encode(&mut rest)?;
+    let len = MAX_ENCODED_LEN - rest.len();
+
+    wr.write_all(&buf[..1]).await.map_err(ValueWriteError::InvalidMarkerWrite)?;
+    wr.write_all(&buf[1..len]).await.map_err(ValueWriteError::InvalidDataWrite)?;
+    Ok(ret)
+}
+
+/// Async counterpart of [`write_nil`](encode::write_nil).
+///
+/// # Errors
+///
+/// Returns an error on any I/O error occurred while writing the marker.
+pub async fn write_nil<W: AsyncWrite + Unpin>(wr: &mut W) -> Result<(), io::Error> {
+    wr.write_all(&[Marker::Null.to_u8()]).await
+}
+
+/// Async counterpart of [`write_bool`](encode::write_bool).
+///
+/// # Errors
+///
+/// Returns an error on any I/O error occurred while writing the marker.
+pub async fn write_bool<W: AsyncWrite + Unpin>(wr: &mut W, val: bool) -> Result<(), io::Error> {
+    let marker = if val { Marker::True } else { Marker::False };
+    wr.write_all(&[marker.to_u8()]).await
+}
+
+/// Async counterpart of [`write_pfix`](encode::write_pfix).
+///
+/// # Errors
+///
+/// Returns an error on any I/O error occurred while writing the marker.
+///
+/// # Panics
+///
+/// Panics if `val` is greater than 127.
+pub async fn write_pfix<W: AsyncWrite + Unpin>(wr: &mut W, val: u8) -> Result<(), io::Error> {
+    assert!(val < 128);
+    wr.write_all(&[Marker::FixPos(val).to_u8()]).await
+}
+
+/// Async counterpart of [`write_nfix`](encode::write_nfix).
+///
+/// # Errors
+///
+/// Returns an error on any I/O error occurred while writing the marker.
+///
+/// # Panics
+///
+/// Panics if `val` does not fit in `[-32; 0)` range.
+pub async fn write_nfix<W: AsyncWrite + Unpin>(wr: &mut W, val: i8) -> Result<(), io::Error> {
+    assert!((-32..0).contains(&val));
+    wr.write_all(&[Marker::FixNeg(val).to_u8()]).await
+}
+
+macro_rules! write_encoded_fns {
+    ($($name:ident($val:ident: $ty:ty) -> $ret:ty;)*) => {
+        $(
+            #[doc = concat!("Async counterpart of [`", stringify!($name), "`](encode::", stringify!($name), ").")]
+            ///
+            /// # Errors
+            ///
+            /// This function will return `ValueWriteError` on any I/O error occurred while writing
+            /// either the marker or the data.
+            pub async fn $name<W: AsyncWrite + Unpin>(wr: &mut W, $val: $ty) -> Result<$ret, ValueWriteError<io::Error>> {
+                write_encoded(wr, |buf| encode::$name(buf, $val)).await
+            }
+        )*
+    };
+}
+
+write_encoded_fns! {
+    write_u8(val: u8) -> ();
+    write_u16(val: u16) -> ();
+    write_u32(val: u32) -> ();
+    write_u64(val: u64) -> ();
+    write_uint8(val: u8) -> Marker;
+    write_uint(val: u64) -> Marker;
+    write_i8(val: i8) -> ();
+    write_i16(val: i16) -> ();
+    write_i32(val: i32) -> ();
+    write_i64(val: i64) -> ();
+    write_sint(val: i64) -> Marker;
+    write_f32(val: f32) -> ();
+    write_f64(val: f64) -> ();
+    write_str_len(len: u32) -> Marker;
+    write_bin_len(len: u32) -> Marker;
+    write_array_len(len: u32) -> Marker;
+    write_map_len(len: u32) -> Marker;
+    write_timestamp(ts: Timestamp) -> ();
+}
+
+/// Async counterpart of [`write_ext_meta`](encode::write_ext_meta).
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError` on any I/O error occurred while writing either the
+/// marker or the data.
+pub async fn write_ext_meta<W: AsyncWrite + Unpin>(wr: &mut W, len: u32, ty: i8) -> Result<Marker, ValueWriteError<io::Error>> {
+    write_encoded(wr, |buf| encode::write_ext_meta(buf, len, ty)).await
+}
+
+/// Async counterpart of [`write_str`](encode::write_str).
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError` on any I/O error occurred while writing either the
+/// marker or the data.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// let mut buf = Vec::new();
+/// rmp::tokio::encode::write_str(&mut buf, "hi").await.unwrap();
+///
+/// assert_eq!(vec![0xa2, 0x68, 0x69], buf);
+/// # }
+/// ```
+pub async fn write_str<W: AsyncWrite + Unpin>(wr: &mut W, data: &str) -> Result<(), ValueWriteError<io::Error>> {
+    write_str_len(wr, data.len() as u32).await?;
+    wr.write_all(data.as_bytes()).await.map_err(ValueWriteError::InvalidDataWrite)
+}
+
+/// Async counterpart of [`write_bin`](encode::write_bin).
+///
+/// # Errors
+///
+/// This function will return `ValueWriteError` on any I/O error occurred while writing either the
+/// marker or the data.
+pub async fn write_bin<W: AsyncWrite + Unpin>(wr: &mut W, data: &[u8]) -> Result<(), ValueWriteError<io::Error>> {
+    write_bin_len(wr, data.len() as u32).await?;
+    wr.write_all(data).await.map_err(ValueWriteError::InvalidDataWrite)
+}
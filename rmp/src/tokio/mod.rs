@@ -0,0 +1,31 @@
+//! Async counterparts of the [`encode`](crate::encode) and [`decode`](crate::decode) functions,
+//! working with tokio's `AsyncWrite` and `AsyncRead`.
+//!
+//! Each function here mirrors the sync function of the same name: it writes or reads exactly the
+//! same bytes and returns the same errors. Markers and headers are small, so they are encoded or
+//! decoded by the sync functions on a stack buffer, and only the I/O is done asynchronously.
+//!
+//! This module is available with the `tokio` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! use rmp::tokio::{decode, encode};
+//!
+//! let mut buf = Vec::new();
+//! encode::write_array_len(&mut buf, 2).await.unwrap();
+//! encode::write_str(&mut buf, "le message").await.unwrap();
+//! encode::write_u32(&mut buf, 42).await.unwrap();
+//!
+//! let mut rd = &buf[..];
+//! let mut out = [0u8; 16];
+//! assert_eq!(2, decode::read_array_len(&mut rd).await.unwrap());
+//! assert_eq!("le message", decode::read_str(&mut rd, &mut out).await.unwrap());
+//! assert_eq!(42, decode::read_u32(&mut rd).await.unwrap());
+//! # }
+//! ```
+
+pub mod decode;
+pub mod encode;
@@ -0,0 +1,243 @@
+//! Implementation of the constant-memory [validate] function.
+
+use core::fmt::{self, Display, Formatter};
+
+use crate::Marker;
+
+/// The maximum container nesting supported by [`validate_with_limits`].
+///
+/// The validator keeps one counter per nesting level on the stack, so this bounds its memory use
+/// to a few kilobytes regardless of the input.
+pub const MAX_VALIDATION_DEPTH: usize = 1024;
+
+/// An error returned by [`validate`] when the input is not a complete MessagePack value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ValidationError {
+    /// The input ends before the first value is complete.
+    Truncated,
+    /// The reserved marker `0xc1` was found at the given offset.
+    ReservedMarker {
+        position: usize,
+    },
+    /// Containers are nested deeper than allowed; `position` is the offset of the offending marker.
+    DepthLimitExceeded {
+        position: usize,
+    },
+    /// A str, bin, ext, array or map header declares more items than allowed.
+    LengthLimitExceeded {
+        len: u32,
+        position: usize,
+    },
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            ValidationError::Truncated => f.write_str("unexpected end of input"),
+            ValidationError::ReservedMarker { position } => write!(f, "reserved marker (pos {position})"),
+            ValidationError::DepthLimitExceeded { position } => write!(f, "depth limit exceeded (pos {position})"),
+            ValidationError::LengthLimitExceeded { len, position } => {
+                write!(f, "length {len} exceeds the limit (pos {position})")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ValidationError {}
+
+/// Checks that `buf` starts with a complete, well-formed MessagePack value, returning its
+/// encoded length in bytes.
+///
+/// Any bytes after the first value are ignored, so this can be used to find frame boundaries in
+/// a stream of concatenated values. Containers may be nested up to [`MAX_VALIDATION_DEPTH`]
+/// levels deep and have any length.
+///
+/// The contents of strings are not checked to be valid UTF-8.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::{validate, ValidationError};
+///
+/// assert_eq!(Ok(3), validate(&[0x92, 0x01, 0xc0, 0xff]));
+/// assert_eq!(Err(ValidationError::Truncated), validate(&[0x92, 0x01]));
+/// ```
+#[inline]
+pub fn validate(buf: &[u8]) -> Result<usize, ValidationError> {
+    validate_with_limits(buf, MAX_VALIDATION_DEPTH, u32::MAX)
+}
+
+/// Checks that `buf` starts with a complete, well-formed MessagePack value, returning its
+/// encoded length in bytes.
+///
+/// Unlike [`validate`], this rejects containers nested more than `max_depth` levels deep, and
+/// str, bin or ext data longer than `max_len` bytes, as well as arrays and maps with more than
+/// `max_len` elements. Values of `max_depth` above [`MAX_VALIDATION_DEPTH`] are capped to it.
+///
+/// The input is walked iteratively and nothing is allocated.
+#[inline]
+pub fn validate_with_limits(buf: &[u8], max_depth: usize, max_len: u32) -> Result<usize, ValidationError> {
+    Validator::with_limits(max_depth, max_len).validate(buf)
+}
+
+/// A resumable [`validate_with_limits`], for values whose bytes arrive in pieces.
+///
+/// When the input is [`Truncated`](ValidationError::Truncated), the validator remembers how far
+/// it got, so calling [`validate`](Validator::validate) again with the same bytes plus whatever
+/// arrived since only looks at the new part. Validating a value read in `n` pieces thus takes
+/// linear rather than quadratic time.
+///
+/// # Examples
+///
+/// ```
+/// use rmp::{ValidationError, Validator};
+///
+/// let buf = [0x93, 0x01, 0x02, 0x03, 0xc0];
+/// let mut validator = Validator::new();
+/// assert_eq!(Err(ValidationError::Truncated), validator.validate(&buf[..2]));
+/// assert_eq!(Ok(4), validator.validate(&buf));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Validator {
+    max_depth: usize,
+    max_len: u32,
+    /// Number of values still expected in each open container.
+    pending: [u64; MAX_VALIDATION_DEPTH],
+    depth: usize,
+    /// Position of the first value not validated yet.
+    pos: usize,
+}
+
+impl Validator {
+    /// Creates a validator with the limits of [`validate`].
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_limits(MAX_VALIDATION_DEPTH, u32::MAX)
+    }
+
+    /// Creates a validator with the limits of [`validate_with_limits`].
+    #[must_use]
+    pub fn with_limits(max_depth: usize, max_len: u32) -> Self {
+        Validator {
+            max_depth: max_depth.min(MAX_VALIDATION_DEPTH),
+            max_len,
+            pending: [0; MAX_VALIDATION_DEPTH],
+            depth: 0,
+            pos: 0,
+        }
+    }
+
+    /// Forgets the progress made, to validate another value from the start.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.depth = 0;
+        self.pos = 0;
+    }
+
+    /// Checks that `buf` starts with a complete, well-formed MessagePack value, returning its
+    /// encoded length in bytes.
+    ///
+    /// After a [`Truncated`](ValidationError::Truncated) error, `buf` must be passed again with
+    /// more bytes appended; the validator resumes where it stopped. Once a value is complete, the
+    /// validator is reset for the next one. After any other error, it must be
+    /// [`reset`](Validator::reset) before it's used again.
+    pub fn validate(&mut self, buf: &[u8]) -> Result<usize, ValidationError> {
+        loop {
+            // The progress is only saved between values, so that a value cut short is
+            // validated again from its marker once more input arrives.
+            let start = self.pos;
+            let mut pos = start;
+            let marker = Marker::from_u8(*buf.get(pos).ok_or(ValidationError::Truncated)?);
+            pos += 1;
+
+            let container = match marker {
+                Marker::FixArray(len) => Some((u32::from(len), 1)),
+                Marker::Array16 => Some((read_len(buf, &mut pos, 2)?, 1)),
+                Marker::Array32 => Some((read_len(buf, &mut pos, 4)?, 1)),
+                Marker::FixMap(len) => Some((u32::from(len), 2)),
+                Marker::Map16 => Some((read_len(buf, &mut pos, 2)?, 2)),
+                Marker::Map32 => Some((read_len(buf, &mut pos, 4)?, 2)),
+                _ => None,
+            };
+
+            if let Some((len, values_per_item)) = container {
+                if len > self.max_len {
+                    return Err(ValidationError::LengthLimitExceeded { len, position: start });
+                }
+                if self.depth >= self.max_depth {
+                    return Err(ValidationError::DepthLimitExceeded { position: start });
+                }
+                self.pos = pos;
+                let children = u64::from(len) * values_per_item;
+                if children > 0 {
+                    self.pending[self.depth] = children;
+                    self.depth += 1;
+                    continue;
+                }
+            } else {
+                let (len, extra) = match marker {
+                    Marker::Null | Marker::True | Marker::False | Marker::FixPos(_) | Marker::FixNeg(_) => (0, 0),
+                    Marker::U8 | Marker::I8 => (1, 0),
+                    Marker::U16 | Marker::I16 => (2, 0),
+                    Marker::U32 | Marker::I32 | Marker::F32 => (4, 0),
+                    Marker::U64 | Marker::I64 | Marker::F64 => (8, 0),
+                    Marker::FixStr(len) => (u32::from(len), 0),
+                    Marker::Str8 | Marker::Bin8 => (read_len(buf, &mut pos, 1)?, 0),
+                    Marker::Str16 | Marker::Bin16 => (read_len(buf, &mut pos, 2)?, 0),
+                    Marker::Str32 | Marker::Bin32 => (read_len(buf, &mut pos, 4)?, 0),
+                    Marker::FixExt1 => (1, 1),
+                    Marker::FixExt2 => (2, 1),
+                    Marker::FixExt4 => (4, 1),
+                    Marker::FixExt8 => (8, 1),
+                    Marker::FixExt16 => (16, 1),
+                    Marker::Ext8 => (read_len(buf, &mut pos, 1)?, 1),
+                    Marker::Ext16 => (read_len(buf, &mut pos, 2)?, 1),
+                    Marker::Ext32 => (read_len(buf, &mut pos, 4)?, 1),
+                    _ => return Err(ValidationError::ReservedMarker { position: start }),
+                };
+                if (marker.is_str() || marker.is_bin() || marker.is_ext()) && len > self.max_len {
+                    return Err(ValidationError::LengthLimitExceeded { len, position: start });
+                }
+                // Lengths near `u32::MAX` don't fit in the buffer, but may overflow `usize` on
+                // 32-bit targets, so this is checked rather than added up.
+                self.pos = usize::try_from(len)
+                    .ok()
+                    .and_then(|len| len.checked_add(extra))
+                    .and_then(|len| pos.checked_add(len))
+                    .filter(|&end| end <= buf.len())
+                    .ok_or(ValidationError::Truncated)?;
+            }
+
+            // The value is complete, which may complete its enclosing containers as well.
+            loop {
+                if self.depth == 0 {
+                    let len = self.pos;
+                    self.reset();
+                    return Ok(len);
+                }
+                self.pending[self.depth - 1] -= 1;
+                if self.pending[self.depth - 1] > 0 {
+                    break;
+                }
+                self.depth -= 1;
+            }
+        }
+    }
+}
+
+impl Default for Validator {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Reads a big-endian length of `size` bytes at `pos`, advancing it.
+fn read_len(buf: &[u8], pos: &mut usize, size: usize) -> Result<u32, ValidationError> {
+    let bytes = buf.get(*pos..*pos + size).ok_or(ValidationError::Truncated)?;
+    *pos += size;
+    Ok(bytes.iter().fold(0, |acc, &b| (acc << 8) | u32::from(b)))
+}
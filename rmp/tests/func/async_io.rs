@@ -0,0 +1,69 @@
+use rmp::decode::ValueReadError;
+use rmp::tokio::{decode, encode};
+use rmp::{Marker, Timestamp};
+
+#[tokio::test]
+async fn pass_async_matches_sync() {
+    let ts = Timestamp::new(1, 500).unwrap();
+
+    let mut sync = Vec::new();
+    rmp::encode::write_map_len(&mut sync, 1).unwrap();
+    rmp::encode::write_str(&mut sync, "le message").unwrap();
+    rmp::encode::write_array_len(&mut sync, 6).unwrap();
+    rmp::encode::write_sint(&mut sync, -300).unwrap();
+    rmp::encode::write_u64(&mut sync, 42).unwrap();
+    rmp::encode::write_f32(&mut sync, 0.5).unwrap();
+    rmp::encode::write_bin(&mut sync, &[0u8; 300]).unwrap();
+    rmp::encode::write_timestamp(&mut sync, ts).unwrap();
+    rmp::encode::write_ext_meta(&mut sync, 2, 7).unwrap();
+    sync.extend_from_slice(&[1, 2]);
+
+    let mut buf = Vec::new();
+    encode::write_map_len(&mut buf, 1).await.unwrap();
+    encode::write_str(&mut buf, "le message").await.unwrap();
+    encode::write_array_len(&mut buf, 6).await.unwrap();
+    assert_eq!(Marker::I16, encode::write_sint(&mut buf, -300).await.unwrap());
+    encode::write_u64(&mut buf, 42).await.unwrap();
+    encode::write_f32(&mut buf, 0.5).await.unwrap();
+    encode::write_bin(&mut buf, &[0u8; 300]).await.unwrap();
+    encode::write_timestamp(&mut buf, ts).await.unwrap();
+    assert_eq!(Marker::FixExt2, encode::write_ext_meta(&mut buf, 2, 7).await.unwrap());
+    buf.extend_from_slice(&[1, 2]);
+    assert_eq!(sync, buf);
+
+    let mut rd = &buf[..];
+    let mut out = [0u8; 16];
+    assert_eq!(1, decode::read_map_len(&mut rd).await.unwrap());
+    assert_eq!("le message", decode::read_str(&mut rd, &mut out).await.unwrap());
+    assert_eq!(6, decode::read_array_len(&mut rd).await.unwrap());
+    assert_eq!(-300i32, decode::read_int(&mut rd).await.unwrap());
+    assert_eq!(42, decode::read_u64(&mut rd).await.unwrap());
+    assert_eq!(0.5, decode::read_f32(&mut rd).await.unwrap());
+    assert_eq!(300, decode::read_bin_len(&mut rd).await.unwrap());
+    rd = &rd[300..];
+    assert_eq!(ts, decode::read_timestamp(&mut rd).await.unwrap());
+    assert_eq!((7, [1, 2]), decode::read_fixext2(&mut rd).await.unwrap());
+    assert!(rd.is_empty());
+}
+
+#[tokio::test]
+async fn fail_async_type_mismatch_consumes_marker_only() {
+    let buf = [0xcd, 0x01, 0x2c];
+    let mut rd = &buf[..];
+
+    match decode::read_str_len(&mut rd).await {
+        Err(ValueReadError::TypeMismatch(Marker::U16)) => {}
+        other => panic!("unexpected result: {other:?}"),
+    }
+    assert_eq!(2, rd.len());
+}
+
+#[tokio::test]
+async fn fail_async_truncated_data() {
+    let buf = [0xce, 0x00, 0x01];
+
+    match decode::read_u32(&mut &buf[..]).await {
+        Err(ValueReadError::InvalidDataRead(..)) => {}
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
@@ -0,0 +1,13 @@
+use rmp::base64;
+
+#[test]
+fn pass_encode() {
+    assert_eq!("", base64::encode(b""));
+    assert_eq!("Zg==", base64::encode(b"f"));
+    assert_eq!("Zm8=", base64::encode(b"fo"));
+    assert_eq!("Zm9v", base64::encode(b"foo"));
+    assert_eq!("Zm9vYg==", base64::encode(b"foob"));
+    assert_eq!("Zm9vYmE=", base64::encode(b"fooba"));
+    assert_eq!("Zm9vYmFy", base64::encode(b"foobar"));
+    assert_eq!("AP8=", base64::encode(&[0x00, 0xff]));
+}
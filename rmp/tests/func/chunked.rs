@@ -0,0 +1,74 @@
+use std::io::{BufWriter, ErrorKind, Read, Write};
+
+use rmp::chunked::{ChunkedBinReader, ChunkedBinWriter, TERMINATOR};
+use rmp::decode;
+
+use super::util::Trickle;
+
+#[test]
+fn pass_chunked_round_trip_leaves_following_values() {
+    let payload: Vec<u8> = (0..1000u32).map(|v| v as u8).collect();
+
+    let mut wr = ChunkedBinWriter::new(Vec::new());
+    wr.write_all(&payload[..10]).unwrap();
+    wr.write_all(&[]).unwrap();
+    wr.write_all(&payload[10..]).unwrap();
+    let mut buf = wr.finish().unwrap();
+    // A value following the stream.
+    buf.push(0x2a);
+
+    assert_eq!([0xc4, 0x0a], buf[..2]);
+    assert_eq!([0xc5, 0x03, 0xde], buf[12..15]);
+
+    let mut rd = ChunkedBinReader::new(Trickle(&buf, 3));
+    let mut data = Vec::new();
+    rd.read_to_end(&mut data).unwrap();
+    assert_eq!(payload, data);
+    assert!(rd.is_done());
+    assert_eq!(0, rd.read(&mut [0; 4]).unwrap());
+
+    let mut rest = rd.into_inner();
+    assert_eq!(42, decode::read_pfix(&mut rest).unwrap());
+}
+
+#[test]
+fn pass_chunked_empty_stream() {
+    let buf = ChunkedBinWriter::new(Vec::new()).finish().unwrap();
+    assert_eq!(TERMINATOR[..], buf[..]);
+
+    let mut data = Vec::new();
+    ChunkedBinReader::new(&buf[..]).read_to_end(&mut data).unwrap();
+    assert!(data.is_empty());
+}
+
+#[test]
+fn pass_chunked_writes_batched_by_buf_writer() {
+    let mut wr = BufWriter::new(ChunkedBinWriter::new(Vec::new()));
+    for _ in 0..100 {
+        wr.write_all(b"ab").unwrap();
+    }
+    let buf = wr.into_inner().unwrap().finish().unwrap();
+    assert_eq!(2 + 200 + 2, buf.len());
+}
+
+#[test]
+fn fail_chunked_missing_terminator() {
+    let buf = [0xc4, 0x02, 0x01, 0x02, 0xc4, 0x03, 0x01];
+
+    let mut data = Vec::new();
+    let err = ChunkedBinReader::new(&buf[..]).read_to_end(&mut data).unwrap_err();
+    assert_eq!(ErrorKind::UnexpectedEof, err.kind());
+
+    let err = ChunkedBinReader::new(&buf[..4]).read_to_end(&mut data).unwrap_err();
+    assert_eq!(ErrorKind::UnexpectedEof, err.kind());
+}
+
+#[test]
+fn fail_chunked_not_a_bin_chunk() {
+    let buf = [0xc4, 0x01, 0x01, 0xa1, 0x61];
+
+    let mut data = Vec::new();
+    let err = ChunkedBinReader::new(&buf[..]).read_to_end(&mut data).unwrap_err();
+    assert_eq!(ErrorKind::InvalidData, err.kind());
+    assert_eq!(vec![0x01], data);
+}
@@ -89,3 +89,27 @@ fn from_null_read_array_len() {
     }
     assert_eq!(1, cur.position());
 }
+
+#[test]
+fn from_array16_read_size_with_marker() {
+    let buf: &[u8] = &[0xdc, 0x01, 0x00, 0xc0];
+    let mut cur = Cursor::new(buf);
+
+    let marker = read_marker(&mut cur).unwrap();
+    assert_eq!(256, read_array_len_with_marker(&mut cur, marker).unwrap());
+    assert_eq!(3, cur.position());
+
+    assert_eq!((256, &[0xc0][..]), read_array_len_with_marker_from_slice(&buf[1..], marker).unwrap());
+}
+
+#[test]
+fn from_map_marker_read_array_len_with_marker() {
+    let buf: &[u8] = &[0x00];
+    let mut cur = Cursor::new(buf);
+
+    match read_array_len_with_marker(&mut cur, Marker::FixMap(1)) {
+        Err(ValueReadError::TypeMismatch(Marker::FixMap(1))) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+    assert_eq!(0, cur.position());
+}
@@ -59,3 +59,22 @@ fn from_bin32_max_read_len() {
     assert_eq!(4294967295, read_bin_len(&mut cur).unwrap());
     assert_eq!(5, cur.position());
 }
+
+#[test]
+fn from_bin8_decode_from_slice() {
+    let buf = [0xc4, 0x02, 0xaa, 0xbb, 0xc0];
+
+    assert_eq!((&[0xaa, 0xbb][..], &[0xc0][..]), read_bin_from_slice(&buf).unwrap());
+}
+
+#[test]
+fn from_bin8_decode_from_slice_truncated() {
+    let buf = [0xc4, 0x03, 0xaa, 0xbb];
+
+    match read_bin_from_slice(&buf) {
+        Err(ValueReadError::InvalidDataRead(bytes::BytesReadError::InsufficientBytes { expected, actual, position })) => {
+            assert_eq!((3, 2, 2), (expected, actual, position));
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
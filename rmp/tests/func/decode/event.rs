@@ -0,0 +1,64 @@
+use rmp::decode::*;
+use rmp::Marker;
+
+#[test]
+fn from_nested_read_events() {
+    // {"a": [nil, true, -1, 300], "b": <bin 1 2>, "c": <ext 5 [0xff]>}
+    let buf = [
+        0x83,
+        0xa1, 0x61, 0x94, 0xc0, 0xc3, 0xff, 0xcd, 0x01, 0x2c,
+        0xa1, 0x62, 0xc4, 0x02, 0x01, 0x02,
+        0xa1, 0x63, 0xd4, 0x05, 0xff,
+    ];
+
+    let mut rd = EventReader::new(&buf);
+    let events = rd.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+
+    assert_eq!(vec![
+        Event::MapStart(3),
+        Event::Str(b"a"),
+        Event::ArrayStart(4),
+        Event::Nil,
+        Event::Boolean(true),
+        Event::Int(-1),
+        Event::UInt(300),
+        Event::Str(b"b"),
+        Event::Bin(&[0x01, 0x02]),
+        Event::Str(b"c"),
+        Event::Ext(5, &[0xff]),
+    ], events);
+    assert_eq!(buf.len() as u64, rd.position());
+}
+
+#[test]
+fn from_signed_read_events() {
+    let buf = [0xd0, 0x7f, 0xd3, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+
+    let mut rd = EventReader::new(&buf);
+
+    assert_eq!(Event::Int(127), rd.read_event().unwrap());
+    assert_eq!(Event::Int(i64::MIN), rd.read_event().unwrap());
+    assert!(rd.next().is_none());
+}
+
+#[test]
+fn from_truncated_read_event_fails() {
+    let buf = [0xa3, 0x61, 0x62];
+
+    let mut rd = EventReader::new(&buf);
+
+    match rd.next() {
+        Some(Err(ValueReadError::InvalidDataRead(..))) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn from_reserved_read_event_fails() {
+    let buf = [0xc1];
+
+    match EventReader::new(&buf).read_event() {
+        Err(ValueReadError::TypeMismatch(Marker::Reserved)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
@@ -126,3 +126,69 @@ fn from_ext32_read_ext_meta() {
     assert_eq!(ExtMeta { typeid: 1, size: 4294967295 }, read_ext_meta(&mut cur).unwrap());
     assert_eq!(6, cur.position());
 }
+
+#[test]
+fn from_timestamp32_read_timestamp() {
+    let buf: &[u8] = &[0xd6, 0xff, 0x00, 0x00, 0x00, 0x02];
+    let mut cur = Cursor::new(buf);
+
+    assert_eq!(rmp::Timestamp::new(2, 0), Some(read_timestamp(&mut cur).unwrap()));
+    assert_eq!(6, cur.position());
+}
+
+#[test]
+fn from_timestamp64_read_timestamp() {
+    // 0x3_ffff_ffff seconds and 999_999_999 nanoseconds.
+    let buf: &[u8] = &[0xd7, 0xff, 0xee, 0x6b, 0x27, 0xff, 0xff, 0xff, 0xff, 0xff];
+    let mut cur = Cursor::new(buf);
+
+    assert_eq!(rmp::Timestamp::new(0x3_ffff_ffff, 999_999_999), Some(read_timestamp(&mut cur).unwrap()));
+    assert_eq!(10, cur.position());
+}
+
+#[test]
+fn from_timestamp96_read_timestamp() {
+    let buf: &[u8] = &[
+        0xc7, 0x0c, 0xff,
+        0x00, 0x00, 0x00, 0x01,
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+    ];
+    let mut cur = Cursor::new(buf);
+
+    assert_eq!(rmp::Timestamp::new(-2, 1), Some(read_timestamp(&mut cur).unwrap()));
+    assert_eq!(15, cur.position());
+}
+
+#[test]
+fn from_fixext4_with_wrong_type_read_timestamp() {
+    let buf: &[u8] = &[0xd6, 0x01, 0x00, 0x00, 0x00, 0x02];
+    let mut cur = Cursor::new(buf);
+
+    match read_timestamp(&mut cur) {
+        Err(ValueReadError::TypeMismatch(rmp::Marker::FixExt4)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn from_ext8_with_wrong_size_read_timestamp() {
+    let buf: &[u8] = &[0xc7, 0x08, 0xff, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02];
+    let mut cur = Cursor::new(buf);
+
+    match read_timestamp(&mut cur) {
+        Err(ValueReadError::TypeMismatch(rmp::Marker::Ext8)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn from_timestamp64_with_nsecs_out_of_range_read_timestamp() {
+    // 1_000_000_000 nanoseconds.
+    let buf: &[u8] = &[0xd7, 0xff, 0xee, 0x6b, 0x28, 0x00, 0x00, 0x00, 0x00, 0x00];
+    let mut cur = Cursor::new(buf);
+
+    match read_timestamp(&mut cur) {
+        Err(ValueReadError::TypeMismatch(rmp::Marker::FixExt8)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
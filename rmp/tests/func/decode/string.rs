@@ -240,3 +240,24 @@ fn example_process_sequence_of_strings() {
 
     assert_eq!(["Unpacking", "multiple", "strings"], chunks[..]);
 }
+
+#[test]
+fn from_str16_read_str_len_with_marker() {
+    let buf: &[u8] = &[0xda, 0x01, 0x00];
+
+    let marker = Marker::from_u8(buf[0]);
+    assert_eq!(256, read_str_len_with_marker(&mut &buf[1..], marker).unwrap());
+    assert_eq!((256, &[][..]), read_str_len_with_marker_from_slice(&buf[1..], marker).unwrap());
+    assert!(read_bin_len_with_marker_from_slice(&buf[1..], marker).is_err());
+}
+
+#[test]
+#[allow(deprecated)]
+fn from_truncated_str_read_str_ref() {
+    let buf: &[u8] = &[0xdb, 0xff, 0xff, 0xff, 0xff, 0x61];
+
+    match read_str_ref(buf) {
+        Err(DecodeStringError::InvalidDataRead(..)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
@@ -200,3 +200,20 @@ fn from_unsigned_invalid_unknown_marker_read_int() {
     }
     assert_eq!(1, cur.position());
 }
+
+#[test]
+fn from_unsigned_decode_from_slice() {
+    let buf = [0xcd, 0x01, 0x2c, 0x92, 0x2a, 0x81, 0xc0, 0xc0];
+
+    let (val, rest) = read_int_from_slice::<u16>(&buf).unwrap();
+    assert_eq!(300, val);
+    let (len, rest) = read_array_len_from_slice(rest).unwrap();
+    assert_eq!(2, len);
+    let (val, rest) = read_int_from_slice::<u8>(rest).unwrap();
+    assert_eq!(42, val);
+    let (len, rest) = read_map_len_from_slice(rest).unwrap();
+    assert_eq!(1, len);
+    assert_eq!([0xc0, 0xc0], rest);
+
+    assert!(matches!(read_int_from_slice::<u8>(&buf), Err(NumValueReadError::OutOfRange)));
+}
@@ -27,3 +27,46 @@ fn pass_pack_len_u32() {
 
     assert_eq!([0xc6, 0xff, 0xff, 0xff, 0xff], buf);
 }
+
+#[test]
+#[cfg(feature = "std")]
+fn pass_pack_bin_vectored_partial_writes() {
+    use std::io::{self, IoSlice, Write};
+
+    // Accepts at most 3 bytes per call, spread over as many slices as given.
+    struct Trickle(Vec<u8>, usize);
+
+    impl Write for Trickle {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.write_vectored(&[IoSlice::new(buf)])
+        }
+
+        fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+            self.1 += 1;
+            let mut n = 0;
+            for buf in bufs {
+                let len = buf.len().min(3 - n);
+                self.0.extend_from_slice(&buf[..len]);
+                n += len;
+            }
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let data: Vec<u8> = (0..=255).collect();
+    let mut expected = Vec::new();
+    write_bin(&mut expected, &data).unwrap();
+
+    let mut wr = Trickle(Vec::new(), 0);
+    write_bin_vectored(&mut wr, &data).unwrap();
+    assert_eq!(expected, wr.0);
+    assert_eq!(expected.len().div_ceil(3), wr.1);
+
+    let mut wr = Trickle(Vec::new(), 0);
+    write_str_vectored(&mut wr, "le message").unwrap();
+    assert_eq!(b"\xaale message", &wr.0[..]);
+}
@@ -0,0 +1,45 @@
+use rmp::decode::{self, Bytes};
+use rmp::encode::*;
+
+#[test]
+fn pass_fixed_byte_buf_roundtrip() {
+    let mut storage = [0u8; 16];
+    let mut buf = FixedByteBuf::new(&mut storage);
+
+    write_array_len(&mut buf, 2).unwrap();
+    write_u32(&mut buf, 100500).unwrap();
+    write_str(&mut buf, "le").unwrap();
+
+    assert_eq!(9, buf.position());
+    assert_eq!(7, buf.remaining());
+    assert_eq!([0x92, 0xce, 0x00, 0x01, 0x88, 0x94, 0xa2, 0x6c, 0x65], buf.as_slice());
+
+    let written = buf.into_slice();
+    let mut rd = Bytes::new(written);
+    assert_eq!(2, decode::read_array_len(&mut rd).unwrap());
+    assert_eq!(100500, decode::read_u32(&mut rd).unwrap());
+    assert_eq!(6, rd.position());
+    let mut out = [0u8; 2];
+    assert_eq!("le", decode::read_str(&mut rd, &mut out).unwrap());
+    assert_eq!(9, rd.position());
+    assert!(rd.remaining_slice().is_empty());
+}
+
+#[test]
+fn fail_fixed_byte_buf_full() {
+    let mut storage = [0u8; 2];
+    let mut buf = FixedByteBuf::new(&mut storage);
+
+    write_nil(&mut buf).unwrap();
+    match write_bin(&mut buf, &[1, 2]) {
+        Err(ValueWriteError::InvalidDataWrite(err)) => {
+            assert_eq!(1, err.needed());
+            assert_eq!(0, err.remaining());
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+
+    // The marker still fits, but the length doesn't.
+    assert_eq!([0xc0, 0xc4], buf.as_slice());
+    assert_eq!(2, buf.capacity());
+}
@@ -79,3 +79,25 @@ fn pass_pack_meta_32() {
 
     assert_eq!([0xc9, 0xff, 0xff, 0xff, 0xff, 0x10], buf);
 }
+
+#[test]
+fn pass_pack_timestamp() {
+    use rmp::Timestamp;
+
+    let cases: &[(Timestamp, &[u8])] = &[
+        (Timestamp::new(2, 0).unwrap(), &[0xd6, 0xff, 0x00, 0x00, 0x00, 0x02]),
+        (Timestamp::new(0x3_ffff_ffff, 999_999_999).unwrap(), &[0xd7, 0xff, 0xee, 0x6b, 0x27, 0xff, 0xff, 0xff, 0xff, 0xff]),
+        (Timestamp::new(-2, 1).unwrap(), &[
+            0xc7, 0x0c, 0xff,
+            0x00, 0x00, 0x00, 0x01,
+            0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+        ]),
+    ];
+
+    for &(ts, expected) in cases {
+        let mut buf = Vec::new();
+        write_timestamp(&mut buf, ts).unwrap();
+        assert_eq!(expected, &buf[..]);
+        assert_eq!(ts, rmp::decode::read_timestamp(&mut &buf[..]).unwrap());
+    }
+}
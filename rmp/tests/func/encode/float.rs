@@ -19,3 +19,27 @@ fn pass_pack_f64() {
 
     assert_eq!([0xcb, 0x7f, 0xf0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], buf);
 }
+
+#[test]
+fn pass_pack_f32_preserves_bits() {
+    let nan = f32::from_bits(0x7fa0_0001);
+    let mut buf = [0x00; 5];
+
+    write_f32(&mut &mut buf[..], nan).ok().unwrap();
+    assert_eq!([0xca, 0x7f, 0xa0, 0x00, 0x01], buf);
+
+    write_f32(&mut &mut buf[..], -0.0).ok().unwrap();
+    assert_eq!([0xca, 0x80, 0x00, 0x00, 0x00], buf);
+}
+
+#[test]
+fn pass_pack_f64_preserves_bits() {
+    let nan = f64::from_bits(0x7ff8_0000_dead_beef);
+    let mut buf = [0x00; 9];
+
+    write_f64(&mut &mut buf[..], nan).ok().unwrap();
+    assert_eq!([0xcb, 0x7f, 0xf8, 0x00, 0x00, 0xde, 0xad, 0xbe, 0xef], buf);
+
+    write_f64(&mut &mut buf[..], -0.0).ok().unwrap();
+    assert_eq!([0xcb, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], buf);
+}
@@ -0,0 +1,64 @@
+use rmp::encode::*;
+
+const UINTS: [u64; 10] = [0, 127, 128, 255, 256, 65535, 65536, 4294967295, 4294967296, u64::MAX];
+const SINTS: [i64; 13] = [
+    -1, -32, -33, -128, -129, -32768, -32769, -2147483648, -2147483649, i64::MIN, 0, 128, i64::MAX,
+];
+const LENS: [u32; 14] = [0, 1, 2, 4, 8, 15, 16, 31, 32, 255, 256, 65535, 65536, u32::MAX];
+
+#[test]
+fn pass_int_len_matches_written() {
+    for val in UINTS {
+        let mut buf = Vec::new();
+        write_uint(&mut buf, val).unwrap();
+        assert_eq!(buf.len(), uint_len(val), "{val}");
+    }
+    for val in SINTS {
+        let mut buf = Vec::new();
+        write_sint(&mut buf, val).unwrap();
+        assert_eq!(buf.len(), sint_len(val), "{val}");
+    }
+}
+
+#[test]
+fn pass_header_len_matches_written() {
+    for len in LENS {
+        let mut buf = Vec::new();
+        write_str_len(&mut buf, len).unwrap();
+        assert_eq!(buf.len(), str_header_len(len), "{len}");
+
+        buf.clear();
+        write_bin_len(&mut buf, len).unwrap();
+        assert_eq!(buf.len(), bin_header_len(len), "{len}");
+
+        buf.clear();
+        write_array_len(&mut buf, len).unwrap();
+        assert_eq!(buf.len(), array_header_len(len), "{len}");
+
+        buf.clear();
+        write_map_len(&mut buf, len).unwrap();
+        assert_eq!(buf.len(), map_header_len(len), "{len}");
+
+        buf.clear();
+        write_ext_meta(&mut buf, len, 1).unwrap();
+        assert_eq!(buf.len(), ext_header_len(len), "{len}");
+    }
+}
+
+#[test]
+fn pass_total_len_matches_written() {
+    let data = "a".repeat(70000);
+    for len in [0, 31, 32, 300, 70000] {
+        let mut buf = Vec::new();
+        write_str(&mut buf, &data[..len]).unwrap();
+        assert_eq!(buf.len(), str_total_len(len as u32));
+
+        buf.clear();
+        write_bin(&mut buf, &data.as_bytes()[..len]).unwrap();
+        assert_eq!(buf.len(), bin_total_len(len as u32));
+    }
+
+    assert_eq!(6, ext_total_len(4));
+    assert_eq!(3 + 200, ext_total_len(200));
+    assert_eq!(4 + 300, ext_total_len(300));
+}
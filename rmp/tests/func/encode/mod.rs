@@ -1,9 +1,11 @@
 mod array;
 mod bin;
 mod bool;
+mod buffer;
 mod ext;
 mod float;
 mod int;
+mod len;
 mod map;
 mod null;
 mod string;
@@ -0,0 +1,74 @@
+use std::error::Error;
+use std::io::ErrorKind;
+
+use rmp::decode::ValueReadError;
+use rmp::{Marker, ResultExt};
+
+#[test]
+fn context_of_failed_write() {
+    let mut buf = [0u8; 2];
+    let mut wr = &mut buf[..];
+    rmp::encode::write_array_len(&mut wr, 2).with_operation("writing array len").unwrap();
+
+    let err = rmp::encode::write_u16(&mut wr, 300).with_operation_marker(Marker::U16, "writing element 0").unwrap_err();
+    assert_eq!("writing element 0", err.operation());
+    assert_eq!(Some(Marker::U16), err.marker());
+    assert_eq!(format!("writing element 0 (U16): {}", err.error()), err.to_string());
+    assert!(err.source().is_some());
+}
+
+#[test]
+fn context_of_failed_read() {
+    let buf = [0x92, 0xc0];
+
+    let err = rmp::decode::read_map_len(&mut &buf[..]).with_operation("reading map len").unwrap_err();
+    assert_eq!(None, err.marker());
+    assert!(matches!(err.error(), ValueReadError::TypeMismatch(Marker::FixArray(2))));
+    assert!(err.to_string().starts_with("reading map len: "));
+
+    assert_eq!(2, rmp::decode::read_array_len(&mut &buf[..1]).with_operation("reading array len").unwrap());
+    let err = rmp::decode::read_nil(&mut &buf[2..]).with_operation("reading nil").unwrap_err();
+    match err.into_error() {
+        ValueReadError::InvalidMarkerRead(err) => assert_eq!(ErrorKind::UnexpectedEof, err.kind()),
+        other => panic!("unexpected error: {other:?}"),
+    }
+}
+
+#[test]
+fn annotated_write_carries_marker() {
+    use rmp::encode::{annotated, BufferFull, FixedByteBuf, ValueWriteError};
+
+    let mut storage = [0u8; 2];
+    let mut buf = FixedByteBuf::new(&mut storage);
+    assert_eq!(Marker::FixArray(1), annotated::write_array_len(&mut buf, 1).unwrap());
+
+    let err = annotated::write_map_len(&mut buf, 300).unwrap_err();
+    assert_eq!("writing map len", err.operation());
+    assert_eq!(Some(Marker::Map16), err.marker());
+    assert!(matches!(err.error(), ValueWriteError::InvalidDataWrite(BufferFull { .. })));
+
+    let err = annotated::write_nil(&mut buf).unwrap_err();
+    assert_eq!(Some(Marker::Null), err.marker());
+    assert!(err.to_string().starts_with("writing nil (Null): "));
+}
+
+#[test]
+fn annotated_read_carries_marker() {
+    use rmp::decode::{annotated, NumValueReadError};
+
+    let buf = [0x92, 0xcd, 0x01];
+
+    let err = annotated::read_map_len(&mut &buf[..]).unwrap_err();
+    assert_eq!(Some(Marker::FixArray(2)), err.marker());
+    assert!(matches!(err.error(), ValueReadError::TypeMismatch(Marker::FixArray(2))));
+    assert!(err.to_string().starts_with("reading map len (FixArray(2)): "));
+
+    let err = annotated::read_int::<u16, _>(&mut &buf[1..]).unwrap_err();
+    assert_eq!("reading int", err.operation());
+    assert_eq!(Some(Marker::U16), err.marker());
+    assert!(matches!(err.error(), NumValueReadError::InvalidDataRead(..)));
+
+    let err = annotated::read_nil(&mut &buf[3..]).unwrap_err();
+    assert_eq!(None, err.marker());
+    assert!(matches!(err.error(), ValueReadError::InvalidMarkerRead(..)));
+}
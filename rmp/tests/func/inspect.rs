@@ -0,0 +1,41 @@
+use rmp::inspect;
+
+#[test]
+fn inspect_nested_values() {
+    // `[<bin 1 2>, <timestamp 2 s>, -5]`, followed by a truncated `[true, ...]`.
+    let buf = [0x93, 0xc4, 0x02, 0x01, 0x02, 0xd6, 0xff, 0x00, 0x00, 0x00, 0x02, 0xfb, 0x92, 0xc3];
+
+    let expected = "\
+00000000  93                          fixarray(3)
+00000001  c4 02                         bin8(2) 01 02
+00000005  d6 ff                         fixext4(type -1, 4) 00 00 00 02 = timestamp 2.000000000
+0000000b  fb                            negative fixint -5
+0000000c  92                          fixarray(2)
+0000000d  c3                            true
+0000000e                                error: unexpected end of input
+";
+    assert_eq!(expected, inspect(&buf).to_string());
+}
+
+#[test]
+fn inspect_scalars_and_strings() {
+    // 1.5f64, "ab\xff", reserved marker.
+    let buf = [0xcb, 0x3f, 0xf8, 0, 0, 0, 0, 0, 0, 0xa3, 0x61, 0x62, 0xff, 0xc1, 0xc0];
+
+    let dump = inspect(&buf).to_string();
+    let lines: Vec<_> = dump.lines().collect();
+    assert_eq!(3, lines.len());
+    assert_eq!("00000000  cb 3f f8 00 00 00 00 00 00  f64 1.5", lines[0]);
+    assert!(lines[1].ends_with("fixstr(3) 61 62 ff (invalid UTF-8)"), "{}", lines[1]);
+    assert!(lines[2].starts_with("0000000d ") && lines[2].ends_with("error: reserved marker 0xc1"));
+}
+
+#[test]
+fn inspect_long_data() {
+    let mut buf = vec![0xd9, 40];
+    buf.extend_from_slice(&[b'x'; 40]);
+
+    let dump = inspect(&buf).to_string();
+    assert!(dump.ends_with(&format!("str8(40) {:?} ...\n", "x".repeat(32))), "{dump}");
+    assert_eq!("", inspect(&[]).to_string());
+}
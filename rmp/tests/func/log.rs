@@ -0,0 +1,57 @@
+use std::io::{Cursor, Error, ErrorKind};
+
+use rmp::decode;
+use rmp::encode;
+use rmp::log::{self, LogReader};
+
+use super::util::Trickle;
+
+#[test]
+fn pass_log_round_trip_with_truncated_tail() {
+    let mut values = Vec::new();
+    for len in [0, 3, 300] {
+        let mut buf = Vec::new();
+        encode::write_bin(&mut buf, &vec![7; len]).unwrap();
+        values.push(buf);
+    }
+
+    let mut file = Cursor::new(Vec::new());
+    let offsets: Vec<u64> = values.iter().map(|val| log::append(&mut file, val).unwrap()).collect();
+    assert_eq!(vec![0, 2, 7], offsets);
+    file.get_mut().extend_from_slice(&[0x92, 0x01]);
+
+    let mut rd = LogReader::new(Trickle(file.get_ref(), 2));
+    let records: Vec<_> = rd.by_ref().map(Result::unwrap).collect();
+    assert_eq!(3, records.len());
+    for ((rec, offset), val) in records.iter().zip(&offsets).zip(&values) {
+        assert_eq!(&(*offset, val.len(), val.clone()), rec);
+    }
+    assert!(rd.is_truncated());
+    assert_eq!(310, rd.valid_len());
+}
+
+#[test]
+fn fail_log_append_not_one_value() {
+    let mut file = Cursor::new(Vec::new());
+    assert_eq!(ErrorKind::InvalidInput, log::append(&mut file, &[0x01, 0x02]).unwrap_err().kind());
+    assert_eq!(ErrorKind::InvalidInput, log::append(&mut file, &[0x92, 0x01]).unwrap_err().kind());
+    assert!(file.get_ref().is_empty());
+}
+
+#[test]
+fn fail_log_malformed_record() {
+    let mut rd = LogReader::new(&[0x01, 0xc1, 0x02][..]);
+    assert_eq!((0, 1, vec![0x01]), rd.next().unwrap().unwrap());
+    assert_eq!(ErrorKind::InvalidData, rd.next().unwrap().unwrap_err().kind());
+    assert!(rd.next().is_none());
+    assert!(!rd.is_truncated());
+}
+
+#[test]
+fn fail_log_decoder_error_skips_record() {
+    let rd = LogReader::with_decoder(&[0x01, 0xc0, 0x02][..], |mut data: &[u8]| {
+        decode::read_int::<u8, _>(&mut data).map_err(|err| Error::new(ErrorKind::InvalidData, err))
+    });
+    let records: Vec<_> = rd.map(|rec| rec.map_err(|err| err.kind())).collect();
+    assert_eq!(vec![Ok((0, 1, 1)), Err(ErrorKind::InvalidData), Ok((2, 1, 2))], records);
+}
@@ -0,0 +1,60 @@
+use rmp::Marker;
+
+#[test]
+fn classify_all_markers() {
+    for byte in 0..=u8::MAX {
+        let marker = Marker::from_u8(byte);
+        let kinds = [
+            marker.is_int(),
+            marker.is_float(),
+            marker.is_str(),
+            marker.is_bin(),
+            marker.is_ext(),
+            marker.is_array(),
+            marker.is_map(),
+        ];
+        let count = kinds.iter().filter(|&&kind| kind).count();
+
+        match marker {
+            Marker::Null | Marker::True | Marker::False | Marker::Reserved => assert_eq!(0, count),
+            _ => assert_eq!(1, count, "{marker:?}"),
+        }
+        assert_eq!(marker.is_array() || marker.is_map(), marker.is_container());
+    }
+}
+
+#[test]
+fn fixed_len() {
+    assert_eq!(Some(31), Marker::from_u8(0xbf).fixed_len());
+    assert_eq!(Some(15), Marker::from_u8(0x8f).fixed_len());
+    assert_eq!(Some(0), Marker::from_u8(0x90).fixed_len());
+    assert_eq!(Some(16), Marker::FixExt16.fixed_len());
+    assert_eq!(None, Marker::FixPos(3).fixed_len());
+    assert_eq!(None, Marker::Array16.fixed_len());
+    assert_eq!(None, Marker::Ext8.fixed_len());
+}
+
+#[test]
+fn header_len() {
+    assert_eq!(1, Marker::FixPos(1).header_len());
+    assert_eq!(1, Marker::FixStr(3).header_len());
+    assert_eq!(1, Marker::F64.header_len());
+    assert_eq!(2, Marker::Bin8.header_len());
+    assert_eq!(5, Marker::Map32.header_len());
+    assert_eq!(2, Marker::FixExt4.header_len());
+    assert_eq!(3, Marker::Ext8.header_len());
+}
+
+#[test]
+fn marker_table_matches_encoding() {
+    for byte in 0..=u8::MAX {
+        let marker = rmp::MARKERS[byte as usize];
+        assert_eq!(byte, marker.to_u8(), "{marker:?}");
+        assert_eq!(marker, Marker::from_u8(byte));
+    }
+
+    const NIL: Marker = Marker::from_u8(0xc0);
+    assert_eq!(Marker::Null, NIL);
+    assert_eq!(Marker::FixNeg(-1), Marker::from_u8(0xff));
+    assert_eq!(Marker::FixStr(31), Marker::from_u8(0xbf));
+}
@@ -0,0 +1,48 @@
+#[cfg(feature = "std")]
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rmp::Timestamp;
+
+#[test]
+fn pass_timestamp_unix_nanos_round_trip() {
+    for nanos in [0, 1, 999_999_999, 1_000_000_000, -1, -1_000_000_000, -1_500_000_000] {
+        let ts = Timestamp::from_unix_nanos(nanos).unwrap();
+        assert!(ts.nsecs() < 1_000_000_000);
+        assert_eq!(nanos, ts.unix_nanos());
+    }
+
+    let min = i128::from(i64::MIN) * 1_000_000_000;
+    assert_eq!(Timestamp::new(i64::MIN, 0), Timestamp::from_unix_nanos(min));
+    assert_eq!(None, Timestamp::from_unix_nanos(min - 1));
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn pass_timestamp_system_time_round_trip() {
+    let times = [
+        UNIX_EPOCH,
+        UNIX_EPOCH + Duration::new(1, 500),
+        UNIX_EPOCH + Duration::from_secs(0x4_0000_0000),
+        UNIX_EPOCH - Duration::from_secs(2),
+        UNIX_EPOCH - Duration::new(2, 1),
+        SystemTime::now(),
+    ];
+    for time in times {
+        let ts = Timestamp::from_system_time(time).unwrap();
+        assert_eq!(Some(time), ts.to_system_time());
+    }
+
+    assert_eq!(Timestamp::new(-3, 999_999_999), Timestamp::from_system_time(UNIX_EPOCH - Duration::new(2, 1)));
+}
+
+#[test]
+fn pass_timestamp_ordered_by_time() {
+    let mut stamps = [
+        Timestamp::new(1, 0).unwrap(),
+        Timestamp::new(-1, 999_999_999).unwrap(),
+        Timestamp::new(0, 5).unwrap(),
+    ];
+    stamps.sort();
+    let nanos: Vec<i128> = stamps.iter().map(Timestamp::unix_nanos).collect();
+    assert_eq!(vec![-1, 5, 1_000_000_000], nanos);
+}
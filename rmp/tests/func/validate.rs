@@ -0,0 +1,62 @@
+use rmp::{validate, validate_with_limits, ValidationError, Validator};
+
+#[test]
+fn pass_validate_nested() {
+    // {"a": [1, nil, ext(1, [0xff])]}, followed by an unrelated byte.
+    let buf = [0x81, 0xa1, 0x61, 0x93, 0x01, 0xc0, 0xd4, 0x01, 0xff, 0xc3];
+    assert_eq!(Ok(9), validate(&buf));
+    assert_eq!(Ok(1), validate(&[0x90]));
+    assert_eq!(Ok(3), validate(&[0xc4, 0x01, 0x00]));
+}
+
+#[test]
+fn fail_validate_truncated() {
+    let buf = [0x81, 0xa1, 0x61, 0x93, 0x01, 0xc0, 0xd4, 0x01, 0xff];
+    for len in 0..buf.len() {
+        assert_eq!(Err(ValidationError::Truncated), validate(&buf[..len]));
+    }
+    assert_eq!(Err(ValidationError::Truncated), validate(&[0xdb, 0xff, 0xff, 0xff, 0xff]));
+}
+
+#[test]
+fn pass_validator_resumes_byte_by_byte() {
+    let buf = [0x81, 0xa1, 0x61, 0x93, 0x01, 0xc0, 0xd4, 0x01, 0xff, 0xc3];
+    let mut validator = Validator::new();
+    for len in 0..9 {
+        assert_eq!(Err(ValidationError::Truncated), validator.validate(&buf[..len]));
+    }
+    assert_eq!(Ok(9), validator.validate(&buf));
+    // The validator starts over once a value is complete.
+    assert_eq!(Ok(1), validator.validate(&buf[9..]));
+}
+
+#[test]
+fn fail_validate_reserved() {
+    assert_eq!(Err(ValidationError::ReservedMarker { position: 2 }), validate(&[0x92, 0x01, 0xc1]));
+}
+
+#[test]
+fn fail_validate_limits() {
+    let buf = [0x91, 0x91, 0x91, 0xc0];
+    assert_eq!(Ok(4), validate_with_limits(&buf, 3, 1));
+    assert_eq!(Err(ValidationError::DepthLimitExceeded { position: 2 }), validate_with_limits(&buf, 2, 1));
+
+    let buf = [0x92, 0xa2, 0x68, 0x69, 0x01];
+    assert_eq!(Ok(5), validate_with_limits(&buf, 1, 2));
+    assert_eq!(Err(ValidationError::LengthLimitExceeded { len: 2, position: 0 }), validate_with_limits(&buf, 1, 1));
+}
+
+#[test]
+fn fail_validate_deep_nesting() {
+    let buf = vec![0x91; 100_000];
+    assert_eq!(Err(ValidationError::DepthLimitExceeded { position: 1024 }), validate(&buf));
+}
+
+#[test]
+fn fail_validate_max_lengths() {
+    // Declared lengths at the limit of each header must not overflow the offset arithmetic.
+    assert_eq!(Err(ValidationError::Truncated), validate(&[0xc6, 0xff, 0xff, 0xff, 0xff]));
+    assert_eq!(Err(ValidationError::Truncated), validate(&[0xdb, 0xff, 0xff, 0xff, 0xff, 0x61]));
+    assert_eq!(Err(ValidationError::Truncated), validate(&[0xc9, 0xff, 0xff, 0xff, 0xff, 0x01]));
+    assert_eq!(Err(ValidationError::Truncated), validate(&[0xdf, 0xff, 0xff, 0xff, 0xff, 0xc0]));
+}
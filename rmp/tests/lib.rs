@@ -3,7 +3,22 @@
 extern crate quickcheck;
 
 mod func {
+    #[cfg(feature = "tokio")]
+    mod async_io;
+    mod base64;
+    #[cfg(feature = "std")]
+    mod chunked;
     mod decode;
     mod encode;
+    #[cfg(feature = "std")]
+    mod errors;
+    mod inspect;
+    #[cfg(feature = "std")]
+    mod log;
+    mod marker;
     mod mirror;
+    mod timestamp;
+    #[cfg(feature = "std")]
+    mod util;
+    mod validate;
 }
@@ -1,7 +1,4 @@
-#![feature(test)]
-extern crate test;
-
-use test::Bencher;
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
 
 use rmpv::ValueRef;
 
@@ -27,20 +24,23 @@ const COMPLEX: &[u8] = &[
     0x52,
 ];
 
-#[bench]
-fn from_complex_read_value_ref(b: &mut Bencher) {
-    b.iter(|| {
-        let res = rmpv::decode::read_value_ref(&mut &COMPLEX[..]).unwrap();
-        test::black_box(res);
+fn from_complex(c: &mut Criterion) {
+    let mut group = c.benchmark_group("from_complex");
+    group.throughput(Throughput::Bytes(COMPLEX.len() as u64));
+    group.bench_function("read_value_ref", |b| {
+        b.iter(|| {
+            let res = rmpv::decode::read_value_ref(&mut &COMPLEX[..]).unwrap();
+            black_box(res);
+        });
     });
-    b.bytes = COMPLEX.len() as u64;
-}
-
-#[bench]
-fn from_complex_zero_copy_decode(b: &mut Bencher) {
-    b.iter(|| {
-        let res: ValueRef<'_> = rmp_serde::from_slice(COMPLEX).unwrap();
-        test::black_box(res);
+    group.bench_function("zero_copy_decode", |b| {
+        b.iter(|| {
+            let res: ValueRef<'_> = rmp_serde::from_slice(COMPLEX).unwrap();
+            black_box(res);
+        });
     });
-    b.bytes = COMPLEX.len() as u64;
+    group.finish();
 }
+
+criterion_group!(benches, from_complex);
+criterion_main!(benches);
@@ -89,35 +89,35 @@ fn pass_value_map() {
 
 #[test]
 fn pass_uint_from_value() {
-    assert_eq!(i8::min_value(), deserialize_from(ValueRef::from(i8::min_value())).unwrap());
-    assert_eq!(i8::MAX, deserialize_from(ValueRef::from(i8::MAX)).unwrap());
-    assert_eq!(i16::min_value(), deserialize_from(ValueRef::from(i16::min_value())).unwrap());
-    assert_eq!(i16::MAX, deserialize_from(ValueRef::from(i16::MAX)).unwrap());
-    assert_eq!(i32::min_value(), deserialize_from(ValueRef::from(i32::min_value())).unwrap());
-    assert_eq!(i32::MAX, deserialize_from(ValueRef::from(i32::MAX)).unwrap());
-    assert_eq!(i64::min_value(), deserialize_from(ValueRef::from(i64::min_value())).unwrap());
-    assert_eq!(i64::MAX, deserialize_from(ValueRef::from(i64::MAX)).unwrap());
+    assert_eq!(i8::min_value(), deserialize_from::<i8, _>(ValueRef::from(i8::min_value())).unwrap());
+    assert_eq!(i8::MAX, deserialize_from::<i8, _>(ValueRef::from(i8::MAX)).unwrap());
+    assert_eq!(i16::min_value(), deserialize_from::<i16, _>(ValueRef::from(i16::min_value())).unwrap());
+    assert_eq!(i16::MAX, deserialize_from::<i16, _>(ValueRef::from(i16::MAX)).unwrap());
+    assert_eq!(i32::min_value(), deserialize_from::<i32, _>(ValueRef::from(i32::min_value())).unwrap());
+    assert_eq!(i32::MAX, deserialize_from::<i32, _>(ValueRef::from(i32::MAX)).unwrap());
+    assert_eq!(i64::min_value(), deserialize_from::<i64, _>(ValueRef::from(i64::min_value())).unwrap());
+    assert_eq!(i64::MAX, deserialize_from::<i64, _>(ValueRef::from(i64::MAX)).unwrap());
 }
 
 #[test]
 fn pass_sint_from_value() {
-    assert_eq!(0, deserialize_from(ValueRef::from(0)).unwrap());
-    assert_eq!(u8::MAX, deserialize_from(ValueRef::from(u8::MAX)).unwrap());
-    assert_eq!(u16::MAX, deserialize_from(ValueRef::from(u16::MAX)).unwrap());
-    assert_eq!(u32::MAX, deserialize_from(ValueRef::from(u32::MAX)).unwrap());
-    assert_eq!(u64::MAX, deserialize_from(ValueRef::from(u64::MAX)).unwrap());
+    assert_eq!(0, deserialize_from::<i32, _>(ValueRef::from(0)).unwrap());
+    assert_eq!(u8::MAX, deserialize_from::<u8, _>(ValueRef::from(u8::MAX)).unwrap());
+    assert_eq!(u16::MAX, deserialize_from::<u16, _>(ValueRef::from(u16::MAX)).unwrap());
+    assert_eq!(u32::MAX, deserialize_from::<u32, _>(ValueRef::from(u32::MAX)).unwrap());
+    assert_eq!(u64::MAX, deserialize_from::<u64, _>(ValueRef::from(u64::MAX)).unwrap());
 }
 
 #[test]
 fn pass_f32_from_value() {
-    assert_eq!(0.0f32, deserialize_from(ValueRef::from(0.0f32)).unwrap());
-    assert_eq!(std::f32::consts::PI, deserialize_from(ValueRef::from(std::f32::consts::PI)).unwrap());
+    assert_eq!(0.0f32, deserialize_from::<f32, _>(ValueRef::from(0.0f32)).unwrap());
+    assert_eq!(std::f32::consts::PI, deserialize_from::<f32, _>(ValueRef::from(std::f32::consts::PI)).unwrap());
 }
 
 #[test]
 fn pass_f64_from_value() {
-    assert_eq!(0.0, deserialize_from(ValueRef::from(0.0)).unwrap());
-    assert_eq!(std::f64::consts::PI, deserialize_from(ValueRef::from(std::f64::consts::PI)).unwrap());
+    assert_eq!(0.0, deserialize_from::<f64, _>(ValueRef::from(0.0)).unwrap());
+    assert_eq!(std::f64::consts::PI, deserialize_from::<f64, _>(ValueRef::from(std::f64::consts::PI)).unwrap());
 }
 
 #[test]
@@ -297,3 +297,39 @@ fn pass_from_ext() {
     assert_eq!(ExtRefStruct(42, &[255]),
         deserialize_from(ValueRef::Ext(42, &[255])).unwrap());
 }
+
+#[test]
+fn pass_from_value_ref() {
+    use rmpv::ext::from_value_ref;
+    use rmpv::Value;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Struct<'a> {
+        name: &'a str,
+        #[serde(with = "serde_bytes")]
+        data: &'a [u8],
+        tags: BTreeMap<&'a str, Option<u32>>,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    enum Enum<'a> {
+        Unit,
+        Newtype(&'a str),
+    }
+
+    let val = Value::Array(vec![
+        Value::from("John"),
+        Value::Binary(vec![0xcc; 64]),
+        Value::Map(vec![(Value::from("age"), Value::from(42)), (Value::from("id"), Value::Nil)]),
+    ]);
+
+    let decoded: Struct<'_> = from_value_ref(&val).unwrap();
+    assert_eq!("John", decoded.name);
+    assert_eq!(val[1].as_slice().unwrap().as_ptr(), decoded.data.as_ptr());
+    assert_eq!(Some(&Some(42)), decoded.tags.get("age"));
+    assert_eq!(Some(&None), decoded.tags.get("id"));
+
+    assert_eq!(Enum::Unit, from_value_ref(&Value::Array(vec![Value::from(0), Value::Array(vec![])])).unwrap());
+    assert_eq!(Enum::Newtype("John"),
+        from_value_ref(&Value::Array(vec![Value::from(1), Value::Array(vec![Value::from("John")])])).unwrap());
+}
@@ -105,35 +105,35 @@ fn pass_value_map() {
 
 #[test]
 fn pass_uint_from_value() {
-    assert_eq!(i8::min_value(), from_value(Value::from(i8::min_value())).unwrap());
-    assert_eq!(i8::MAX, from_value(Value::from(i8::MAX)).unwrap());
-    assert_eq!(i16::min_value(), from_value(Value::from(i16::min_value())).unwrap());
-    assert_eq!(i16::MAX, from_value(Value::from(i16::MAX)).unwrap());
-    assert_eq!(i32::min_value(), from_value(Value::from(i32::min_value())).unwrap());
-    assert_eq!(i32::MAX, from_value(Value::from(i32::MAX)).unwrap());
-    assert_eq!(i64::min_value(), from_value(Value::from(i64::min_value())).unwrap());
-    assert_eq!(i64::MAX, from_value(Value::from(i64::MAX)).unwrap());
+    assert_eq!(i8::min_value(), from_value::<i8>(Value::from(i8::min_value())).unwrap());
+    assert_eq!(i8::MAX, from_value::<i8>(Value::from(i8::MAX)).unwrap());
+    assert_eq!(i16::min_value(), from_value::<i16>(Value::from(i16::min_value())).unwrap());
+    assert_eq!(i16::MAX, from_value::<i16>(Value::from(i16::MAX)).unwrap());
+    assert_eq!(i32::min_value(), from_value::<i32>(Value::from(i32::min_value())).unwrap());
+    assert_eq!(i32::MAX, from_value::<i32>(Value::from(i32::MAX)).unwrap());
+    assert_eq!(i64::min_value(), from_value::<i64>(Value::from(i64::min_value())).unwrap());
+    assert_eq!(i64::MAX, from_value::<i64>(Value::from(i64::MAX)).unwrap());
 }
 
 #[test]
 fn pass_sint_from_value() {
-    assert_eq!(0, from_value(Value::from(0)).unwrap());
-    assert_eq!(u8::MAX, from_value(Value::from(u8::MAX)).unwrap());
-    assert_eq!(u16::MAX, from_value(Value::from(u16::MAX)).unwrap());
-    assert_eq!(u32::MAX, from_value(Value::from(u32::MAX)).unwrap());
-    assert_eq!(u64::MAX, from_value(Value::from(u64::MAX)).unwrap());
+    assert_eq!(0, from_value::<i32>(Value::from(0)).unwrap());
+    assert_eq!(u8::MAX, from_value::<u8>(Value::from(u8::MAX)).unwrap());
+    assert_eq!(u16::MAX, from_value::<u16>(Value::from(u16::MAX)).unwrap());
+    assert_eq!(u32::MAX, from_value::<u32>(Value::from(u32::MAX)).unwrap());
+    assert_eq!(u64::MAX, from_value::<u64>(Value::from(u64::MAX)).unwrap());
 }
 
 #[test]
 fn pass_f32_from_value() {
-    assert_eq!(0.0f32, from_value(Value::from(0.0f32)).unwrap());
-    assert_eq!(std::f32::consts::PI, from_value(Value::from(std::f32::consts::PI)).unwrap());
+    assert_eq!(0.0f32, from_value::<f32>(Value::from(0.0f32)).unwrap());
+    assert_eq!(std::f32::consts::PI, from_value::<f32>(Value::from(std::f32::consts::PI)).unwrap());
 }
 
 #[test]
 fn pass_f64_from_value() {
-    assert_eq!(0.0, from_value(Value::from(0.0)).unwrap());
-    assert_eq!(std::f64::consts::PI, from_value(Value::from(std::f64::consts::PI)).unwrap());
+    assert_eq!(0.0, from_value::<f64>(Value::from(0.0)).unwrap());
+    assert_eq!(std::f64::consts::PI, from_value::<f64>(Value::from(std::f64::consts::PI)).unwrap());
 }
 
 #[test]
@@ -299,3 +299,43 @@ fn pass_tuple_struct_from_ext() {
     assert_eq!(ExtStruct(42, vec![255]),
         from_value(Value::Ext(42, vec![255])).unwrap());
 }
+
+#[test]
+fn pass_deep_dynamic_value_without_recursion() {
+    use rmpv::ext::DeepValue;
+
+    #[derive(Deserialize)]
+    struct Doc {
+        a: u8,
+        payload: DeepValue,
+    }
+
+    // {"a": 1, "payload": [[[...[nil]...]]]} with 10000 nested arrays, far deeper than the depth
+    // limit of the deserializer.
+    let nesting = 10_000;
+    let mut buf = vec![0x82, 0xa1, 0x61, 0x01, 0xa7];
+    buf.extend_from_slice(b"payload");
+    buf.resize(buf.len() + nesting, 0x91);
+    buf.push(0xc0);
+
+    for doc in [rmp_serde::from_slice::<Doc>(&buf).unwrap(), rmp_serde::from_read::<_, Doc>(&buf[..]).unwrap()] {
+        assert_eq!(1, doc.a);
+        // Unwrap the arrays one by one, as dropping the whole value at once recurses.
+        let mut val = doc.payload.0;
+        let mut depth = 0;
+        while let Value::Array(mut vec) = val {
+            val = vec.pop().unwrap();
+            depth += 1;
+        }
+        assert_eq!(nesting, depth);
+        assert_eq!(Value::Nil, val);
+    }
+}
+
+#[test]
+fn pass_deep_value_from_other_deserializers() {
+    use rmpv::ext::{from_value, DeepValue};
+
+    let val = Value::Array(vec![Value::from(1), Value::from("a")]);
+    assert_eq!(DeepValue(val.clone()), from_value::<DeepValue>(val).unwrap());
+}
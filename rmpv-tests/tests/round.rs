@@ -197,3 +197,28 @@ fn pass_derive_serde_ext_struct() {
         Value::Ext(2, vec![5]),
     );
 }
+
+#[test]
+fn pass_rmp_serde_ext() {
+    test_round(rmp_serde::Ext(3, vec![1, 2, 3]), Value::Ext(3, vec![1, 2, 3]));
+}
+
+#[test]
+fn pass_value_ref_ext_through_serde() {
+    use rmpv::ValueRef;
+
+    let val = ValueRef::Array(vec![
+        ValueRef::from("le message"),
+        ValueRef::Ext(42, &[1, 2]),
+        ValueRef::Map(vec![(ValueRef::from(1), ValueRef::Ext(-2, &[3; 20]))]),
+    ]);
+
+    let mut expected = Vec::new();
+    rmpv::encode::write_value_ref(&mut expected, &val).unwrap();
+    assert_eq!(expected, rmp_serde::to_vec(&val).unwrap());
+
+    assert_eq!(val.to_owned(), rmpv::ext::to_value(&val).unwrap());
+
+    let val_from_buf: ValueRef<'_> = rmp_serde::from_slice(&expected).unwrap();
+    assert_eq!(val, val_from_buf);
+}
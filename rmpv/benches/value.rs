@@ -1,8 +1,4 @@
-#![feature(test)]
-
-extern crate test;
-
-use test::Bencher;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 
 use rmpv::decode::*;
 
@@ -28,8 +24,7 @@ const COMPLEX: [u8; 137] = [
     0x52,
 ];
 
-#[bench]
-fn from_string_read_value(b: &mut Bencher) {
+fn from_string_read_value(c: &mut Criterion) {
     // Lorem ipsum dolor sit amet.
     let buf = [
         0xbb, 0x4c, 0x6f, 0x72, 0x65, 0x6d, 0x20, 0x69, 0x70, 0x73, 0x75,
@@ -37,14 +32,15 @@ fn from_string_read_value(b: &mut Bencher) {
         0x20, 0x61, 0x6d, 0x65, 0x74, 0x2e
     ];
 
-    b.iter(|| {
-        let res = read_value(&mut &buf[..]).unwrap();
-        test::black_box(res);
+    c.bench_function("from_string_read_value", |b| {
+        b.iter(|| {
+            let res = read_value(&mut &buf[..]).unwrap();
+            black_box(res);
+        });
     });
 }
 
-#[bench]
-fn from_string_read_value_ref(b: &mut Bencher) {
+fn from_string_read_value_ref(c: &mut Criterion) {
     // Lorem ipsum dolor sit amet.
     let buf = [
         0xbb, 0x4c, 0x6f, 0x72, 0x65, 0x6d, 0x20, 0x69, 0x70, 0x73, 0x75,
@@ -52,32 +48,33 @@ fn from_string_read_value_ref(b: &mut Bencher) {
         0x20, 0x61, 0x6d, 0x65, 0x74, 0x2e
     ];
 
-    b.iter(|| {
-        let res = read_value_ref(&mut &buf[..]).unwrap();
-        test::black_box(res);
+    c.bench_function("from_string_read_value_ref", |b| {
+        b.iter(|| {
+            let res = read_value_ref(&mut &buf[..]).unwrap();
+            black_box(res);
+        });
     });
 }
 
-#[bench]
-fn from_complex_read_value(b: &mut Bencher) {
-    b.iter(|| {
-        let res = read_value(&mut &COMPLEX[..]).unwrap();
-        test::black_box(res);
+fn from_complex(c: &mut Criterion) {
+    let mut group = c.benchmark_group("from_complex");
+    group.throughput(Throughput::Bytes(COMPLEX.len() as u64));
+    group.bench_function("read_value", |b| {
+        b.iter(|| {
+            let res = read_value(&mut &COMPLEX[..]).unwrap();
+            black_box(res);
+        });
     });
-    b.bytes = COMPLEX.len() as u64;
+    group.bench_function("read_value_ref", |b| {
+        b.iter(|| {
+            let res = read_value_ref(&mut &COMPLEX[..]).unwrap();
+            black_box(res);
+        });
+    });
+    group.finish();
 }
 
- #[bench]
- fn from_complex_read_value_ref(b: &mut Bencher) {
-     b.iter(|| {
-         let res = read_value_ref(&mut &COMPLEX[..]).unwrap();
-         test::black_box(res);
-     });
-     b.bytes = COMPLEX.len() as u64;
- }
-
-#[bench]
-fn from_complex_write_value_ref(b: &mut Bencher) {
+fn from_complex_write_value_ref(c: &mut Criterion) {
     use rmpv::encode::write_value_ref;
     use rmpv::ValueRef::*;
 
@@ -93,14 +90,17 @@ fn from_complex_write_value_ref(b: &mut Bencher) {
 
     let mut buf = [0u8; 64];
 
-    b.iter(|| {
-        write_value_ref(&mut &mut buf[..], &val).unwrap();
+    let mut group = c.benchmark_group("from_complex");
+    group.throughput(Throughput::Bytes(buf.len() as u64));
+    group.bench_function("write_value_ref", |b| {
+        b.iter(|| {
+            write_value_ref(&mut &mut buf[..], &val).unwrap();
+        });
     });
-    b.bytes = buf.len() as u64;
+    group.finish();
 }
 
-#[bench]
-fn from_complex_read_value_ref_to_owned(b: &mut Bencher) {
+fn from_complex_read_value_ref_to_owned(c: &mut Criterion) {
     let buf = [
         0x95, // Fixed array with 5 len.
         0xc0, // Nil.
@@ -115,111 +115,109 @@ fn from_complex_read_value_ref_to_owned(b: &mut Bencher) {
         0xa5, 0x76, 0x61, 0x6c, 0x75, 0x65 // Value: "value".
     ];
 
-    b.iter(|| {
-        let res = read_value_ref(&mut &buf[..]).unwrap().to_owned();
-        test::black_box(res);
+    let mut group = c.benchmark_group("from_complex");
+    group.throughput(Throughput::Bytes(buf.len() as u64));
+    group.bench_function("read_value_ref_to_owned", |b| {
+        b.iter(|| {
+            let res = read_value_ref(&mut &buf[..]).unwrap().to_owned();
+            black_box(res);
+        });
     });
-    b.bytes = buf.len() as u64;
+    group.finish();
 }
 
 /// Read a single large bin32 value.
-fn read_large_bin32(b: &mut Bencher, size: u32) {
-    // Creat buffer, fill it with bytes
-    let mut buf = Vec::with_capacity(size as usize);
-    buf.resize(size as usize, 42);
-
-    // Write header (bin32 format family containing size-5 bytes)
-    let size_bytes: [u8; 4] = (size - 5).to_be_bytes();
-    buf[0] = 0xc6;
-    buf[1] = size_bytes[0];
-    buf[2] = size_bytes[1];
-    buf[3] = size_bytes[2];
-    buf[4] = size_bytes[3];
-
-    // Read value
-    b.iter(|| {
-        let res = read_value(&mut &buf[..]).unwrap();
-        test::black_box(res);
-    });
-    b.bytes = u64::from(size);
-}
-
-#[bench]
-fn read_bin32_50kib(b: &mut Bencher) {
-    read_large_bin32(b, 50 * 1024);
-}
-
-#[bench]
-fn read_bin32_100kib(b: &mut Bencher) {
-    read_large_bin32(b, 100 * 1024);
-}
-
-#[bench]
-fn read_bin32_1mib(b: &mut Bencher) {
-    read_large_bin32(b, 1024 * 1024);
-}
-
-#[bench]
-fn read_bin32_20mib(b: &mut Bencher) {
-    read_large_bin32(b, 20 * 1024 * 1024);
-}
-
-#[bench]
-fn read_bin32_100mib(b: &mut Bencher) {
-    read_large_bin32(b, 100 * 1024 * 1024);
+fn read_large_bin32(c: &mut Criterion) {
+    let mut group = c.benchmark_group("read_bin32");
+    group.sample_size(10);
+    for (name, size) in [
+        ("50kib", 50 * 1024),
+        ("100kib", 100 * 1024),
+        ("1mib", 1024 * 1024),
+        ("20mib", 20 * 1024 * 1024),
+        ("100mib", 100 * 1024 * 1024),
+    ] {
+        let size: u32 = size;
+
+        // Creat buffer, fill it with bytes
+        let mut buf = Vec::with_capacity(size as usize);
+        buf.resize(size as usize, 42);
+
+        // Write header (bin32 format family containing size-5 bytes)
+        let size_bytes: [u8; 4] = (size - 5).to_be_bytes();
+        buf[0] = 0xc6;
+        buf[1] = size_bytes[0];
+        buf[2] = size_bytes[1];
+        buf[3] = size_bytes[2];
+        buf[4] = size_bytes[3];
+
+        // Read value
+        group.throughput(Throughput::Bytes(u64::from(size)));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &buf, |b, buf| {
+            b.iter(|| {
+                let res = read_value(&mut &buf[..]).unwrap();
+                black_box(res);
+            });
+        });
+    }
+    group.finish();
 }
 
 /// Read a flat array containing positive 32-bit unsigned integers.
-fn read_large_array(b: &mut Bencher, element_count: usize) {
-    // Creat buffer, fill it with bytes
-    let size = element_count * 5 /* uint32 size */ + 5 /* array overhead */;
-    let mut buf = Vec::with_capacity(size);
-    buf.resize(size, 0);
-
-    // Write header
-    let size_bytes: [u8; 4] = (size as u32 - 5).to_be_bytes();
-    buf[0] = 0xc6;
-    buf[1] = size_bytes[0];
-    buf[2] = size_bytes[1];
-    buf[3] = size_bytes[2];
-    buf[4] = size_bytes[3];
-
-    // Write elements
-    let elements = &mut buf[5..];
-    for i in 0..element_count {
-        let offset = i * 5;
-        let value_bytes = 42u32.to_be_bytes();
-        elements[offset] = 0xce; // u32
-        elements[offset + 1] = value_bytes[0];
-        elements[offset + 2] = value_bytes[1];
-        elements[offset + 3] = value_bytes[2];
-        elements[offset + 4] = value_bytes[3];
+fn read_large_array(c: &mut Criterion) {
+    let mut group = c.benchmark_group("read_array");
+    group.sample_size(10);
+    for (name, element_count) in [
+        ("50kib", 50 * 1024),
+        ("100kib", 100 * 1024),
+        ("1mib", 1024 * 1024),
+        ("20mib", 20 * 1024 * 1024),
+    ] {
+        // Creat buffer, fill it with bytes
+        let size = element_count * 5 /* uint32 size */ + 5 /* array overhead */;
+        let mut buf = Vec::with_capacity(size);
+        buf.resize(size, 0);
+
+        // Write header
+        let size_bytes: [u8; 4] = (size as u32 - 5).to_be_bytes();
+        buf[0] = 0xc6;
+        buf[1] = size_bytes[0];
+        buf[2] = size_bytes[1];
+        buf[3] = size_bytes[2];
+        buf[4] = size_bytes[3];
+
+        // Write elements
+        let elements = &mut buf[5..];
+        for i in 0..element_count {
+            let offset = i * 5;
+            let value_bytes = 42u32.to_be_bytes();
+            elements[offset] = 0xce; // u32
+            elements[offset + 1] = value_bytes[0];
+            elements[offset + 2] = value_bytes[1];
+            elements[offset + 3] = value_bytes[2];
+            elements[offset + 4] = value_bytes[3];
+        }
+
+        // Read value
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &buf, |b, buf| {
+            b.iter(|| {
+                let res = read_value(&mut &buf[..]).unwrap();
+                black_box(res);
+            });
+        });
     }
-
-    // Read value
-    b.iter(|| {
-        let res = read_value(&mut &buf[..]).unwrap();
-        test::black_box(res);
-    });
-    b.bytes = size as u64;
-}
-
-#[bench]
-fn read_array_50kib(b: &mut Bencher) {
-    read_large_array(b, 50 * 1024);
-}
-
-#[bench]
-fn read_array_100kib(b: &mut Bencher) {
-    read_large_array(b, 100 * 1024);
-}
-
-#[bench]
-fn read_array_1mib(b: &mut Bencher) {
-    read_large_array(b, 1024 * 1024);
-}
-
-#[bench]
-fn read_array_20mib(b: &mut Bencher) {
-    read_large_array(b, 20 * 1024 * 1024);
-}
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    from_string_read_value,
+    from_string_read_value_ref,
+    from_complex,
+    from_complex_write_value_ref,
+    from_complex_read_value_ref_to_owned,
+    read_large_bin32,
+    read_large_array,
+);
+criterion_main!(benches);
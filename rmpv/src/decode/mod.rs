@@ -5,10 +5,17 @@ use std::io::{self, ErrorKind};
 use rmp::decode::{MarkerReadError, ValueReadError};
 
 pub mod value;
+#[cfg(feature = "tokio")]
+mod value_async;
 pub mod value_ref;
 
-pub use self::value::{read_value, read_value_with_max_depth};
-pub use self::value_ref::{read_value_ref, read_value_ref_with_max_depth};
+pub use self::value::{
+    read_map_of_values, read_value, read_value_lazy, read_value_with_limits, read_value_with_max_bytes, read_value_with_max_depth,
+    read_value_with_timestamps,
+};
+#[cfg(feature = "tokio")]
+pub use self::value_async::{read_value_async, read_value_async_with_max_depth};
+pub use self::value_ref::{read_value_ref, read_value_ref_from_segments, read_value_ref_with_max_depth};
 
 /// The maximum recursion depth before [`Error::DepthLimitExceeded`] is returned.
 pub const MAX_DEPTH: usize = 1024;
@@ -22,6 +29,10 @@ pub enum Error {
     InvalidDataRead(io::Error),
     /// The depth limit [`MAX_DEPTH`] was exceeded.
     DepthLimitExceeded,
+    /// A string, binary, ext, array or map header declared a length above the configured limit.
+    LengthLimitExceeded,
+    /// The value takes more than the configured total number of bytes.
+    ByteLimitExceeded,
 }
 
 #[inline]
@@ -29,6 +40,14 @@ fn decrement_depth(depth: u16) -> Result<u16, Error> {
     depth.checked_sub(1).ok_or(Error::DepthLimitExceeded)
 }
 
+#[inline]
+fn check_len(len: usize, max_len: usize) -> Result<usize, Error> {
+    if len > max_len {
+        return Err(Error::LengthLimitExceeded);
+    }
+    Ok(len)
+}
+
 impl Error {
     #[cold]
     #[must_use] pub fn kind(&self) -> ErrorKind {
@@ -36,6 +55,8 @@ impl Error {
             Error::InvalidMarkerRead(ref err) => err.kind(),
             Error::InvalidDataRead(ref err) => err.kind(),
             Error::DepthLimitExceeded => ErrorKind::Unsupported,
+            Error::LengthLimitExceeded => ErrorKind::InvalidData,
+            Error::ByteLimitExceeded => ErrorKind::InvalidData,
         }
     }
 }
@@ -47,6 +68,8 @@ impl error::Error for Error {
             Error::InvalidMarkerRead(ref err) => Some(err),
             Error::InvalidDataRead(ref err) => Some(err),
             Error::DepthLimitExceeded => None,
+            Error::LengthLimitExceeded => None,
+            Error::ByteLimitExceeded => None,
         }
     }
 }
@@ -64,6 +87,12 @@ impl Display for Error {
             Error::DepthLimitExceeded => {
                 write!(fmt, "depth limit exceeded")
             }
+            Error::LengthLimitExceeded => {
+                write!(fmt, "length limit exceeded")
+            }
+            Error::ByteLimitExceeded => {
+                write!(fmt, "byte limit exceeded")
+            }
         }
     }
 }
@@ -94,7 +123,9 @@ impl From<Error> for io::Error {
         match val {
             Error::InvalidMarkerRead(err) |
             Error::InvalidDataRead(err) => err,
-            Error::DepthLimitExceeded => io::Error::new(val.kind(), val),
+            Error::DepthLimitExceeded |
+            Error::LengthLimitExceeded |
+            Error::ByteLimitExceeded => io::Error::new(val.kind(), val),
         }
     }
 }
@@ -1,86 +1,291 @@
 use std::cmp::min;
 use std::io::{self, Read};
 
-use rmp::decode::{read_marker, RmpRead};
+use rmp::decode::{read_map_len, read_marker, read_str_len, RmpRead};
 use rmp::Marker;
 
 use super::Error;
-use crate::{Utf8String, Value};
+use crate::{Timestamp, Utf8String, Value};
 
 // See https://github.com/3Hren/msgpack-rust/issues/151
 const PREALLOC_MAX: usize = 64 * 1024; // 64 KiB
 
-fn read_array_data<R: Read>(rd: &mut R, mut len: usize, depth: u16) -> Result<Vec<Value>, Error> {
-    let depth = super::decrement_depth(depth)?;
+/// Size of the chunks in which strings are read and validated.
+const STR_CHUNK_LEN: usize = 8 * 1024; // 8 KiB
 
-    // Note: Do not preallocate a Vec of size `len`.
-    // See https://github.com/3Hren/msgpack-rust/issues/151
-    let mut vec = Vec::new();
+/// Callback of [`read_value_lazy`].
+type LargeDataHandler<'a> = dyn FnMut(Marker, &mut dyn Read) -> io::Result<Value> + 'a;
+
+struct Options<'a> {
+    max_len: usize,
+    /// Total number of bytes that may be read.
+    max_bytes: u64,
+    /// Strings and binaries longer than the given length are passed to the handler.
+    large: Option<(usize, &'a mut LargeDataHandler<'a>)>,
+    /// Whether timestamp ext values are read as `Value::Timestamp`.
+    timestamps: bool,
+}
+
+impl Options<'_> {
+    #[inline]
+    fn with_max_len(max_len: usize) -> Self {
+        Options { max_len, max_bytes: u64::MAX, large: None, timestamps: false }
+    }
+}
+
+/// Reader which stops at the byte limit, remembering whether it was hit.
+struct Budget<R> {
+    rd: R,
+    left: u64,
+    exceeded: bool,
+}
 
-    while len > 0 {
-        vec.push(read_value_inner(rd, depth)?);
-        len -= 1;
+impl<R: Read> Read for Budget<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.left == 0 && !buf.is_empty() {
+            self.exceeded = true;
+            return Ok(0);
+        }
+        let len = min(buf.len() as u64, self.left) as usize;
+        let n = self.rd.read(&mut buf[..len])?;
+        self.left -= n as u64;
+        Ok(n)
     }
+}
 
-    Ok(vec)
+fn ext_value(ty: i8, data: Vec<u8>, opts: &Options<'_>) -> Value {
+    if opts.timestamps && ty == Timestamp::EXT_TYPE {
+        if let Some(ts) = Timestamp::from_ext_data(&data) {
+            return Value::Timestamp(ts);
+        }
+    }
+    Value::Ext(ty, data)
+}
+
+/// Passes the body of a string or binary to the handler if it exceeds the inline limit, skipping
+/// whatever the handler leaves unread.
+fn read_large_data<R: Read>(rd: &mut R, marker: Marker, len: usize, opts: &mut Options<'_>) -> Result<Option<Value>, Error> {
+    match opts.large {
+        Some((max_inline_len, ref mut handler)) if len > max_inline_len => {
+            let mut body = rd.by_ref().take(len as u64);
+            let val = handler(marker, &mut body).map_err(Error::InvalidDataRead)?;
+            io::copy(&mut body, &mut io::sink()).map_err(Error::InvalidDataRead)?;
+            if body.limit() != 0 {
+                return Err(Error::InvalidDataRead(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!("Expected {len} bytes, read {} bytes", len as u64 - body.limit()),
+                )));
+            }
+            Ok(Some(val))
+        }
+        _ => Ok(None),
+    }
 }
 
-fn read_map_data<R: Read>(rd: &mut R, mut len: usize, depth: u16) -> Result<Vec<(Value, Value)>, Error> {
+/// A partially read array or map.
+///
+/// These are kept on the heap rather than on the call stack, so that the stack usage doesn't
+/// depend on the nesting depth of the value.
+struct Frame {
+    /// Remaining depth of the elements.
+    depth: u16,
+    /// Number of elements left to read, counting keys and values of maps separately.
+    left: usize,
+    data: FrameData,
+}
+
+enum FrameData {
+    Array(Vec<Value>),
+    Map(Vec<(Value, Value)>, Option<Value>),
+}
+
+impl Frame {
+    /// Adds the next element, returning `true` if the array or map is complete.
+    fn push(&mut self, val: Value) -> bool {
+        match self.data {
+            FrameData::Array(ref mut vec) => vec.push(val),
+            FrameData::Map(ref mut vec, ref mut key) => match key.take() {
+                Some(key) => vec.push((key, val)),
+                None => *key = Some(val),
+            },
+        }
+        self.left -= 1;
+        self.left == 0
+    }
+
+    fn into_value(self) -> Value {
+        match self.data {
+            FrameData::Array(vec) => Value::Array(vec),
+            FrameData::Map(vec, _) => Value::Map(vec),
+        }
+    }
+}
+
+/// Either a complete value, or an array or map whose elements follow.
+enum Item {
+    Value(Value),
+    Open(Frame),
+}
+
+impl Item {
+    fn open(frame: Frame) -> Self {
+        if frame.left == 0 {
+            Item::Value(frame.into_value())
+        } else {
+            Item::Open(frame)
+        }
+    }
+}
+
+fn read_array_data(len: usize, depth: u16, opts: &Options<'_>) -> Result<Item, Error> {
     let depth = super::decrement_depth(depth)?;
+    let len = super::check_len(len, opts.max_len)?;
 
     // Note: Do not preallocate a Vec of size `len`.
     // See https://github.com/3Hren/msgpack-rust/issues/151
-    let mut vec = Vec::new();
+    Ok(Item::open(Frame { depth, left: len, data: FrameData::Array(Vec::new()) }))
+}
 
-    while len > 0 {
-        vec.push((read_value_inner(rd, depth)?, read_value_inner(rd, depth)?));
-        len -= 1;
-    }
+fn read_map_data(len: usize, depth: u16, opts: &Options<'_>) -> Result<Item, Error> {
+    let depth = super::decrement_depth(depth)?;
+    let len = super::check_len(len, opts.max_len)?;
 
-    Ok(vec)
+    // Note: Do not preallocate a Vec of size `len`.
+    // See https://github.com/3Hren/msgpack-rust/issues/151
+    Ok(Item::open(Frame { depth, left: 2 * len, data: FrameData::Map(Vec::new(), None) }))
 }
 
-fn read_str_data<R: Read>(rd: &mut R, len: usize, depth: u16) -> Result<Utf8String, Error> {
-    let depth = super::decrement_depth(depth)?;
+/// Reads a string in chunks, validating each chunk as it's read, so that valid UTF-8 is collected
+/// into a `String` without buffering and checking the whole string again afterwards.
+///
+/// At the first invalid sequence the remaining bytes are read as they are, and the string is kept
+/// as bytes, like [`Utf8String::from_bytes`] does.
+fn read_str_data<R: Read>(rd: &mut R, len: usize, depth: u16, opts: &mut Options<'_>) -> Result<Utf8String, Error> {
+    // Strings count twice towards the depth limit, as they did when read through `read_bin_data`.
+    super::decrement_depth(depth).and_then(super::decrement_depth)?;
+    let len = super::check_len(len, opts.max_len)?;
+
+    let mut s = String::with_capacity(min(len, PREALLOC_MAX));
+    let mut chunk = [0; STR_CHUNK_LEN];
+    // Bytes of a character split between chunks, moved to the start of `chunk`.
+    let mut carry = 0;
+    let mut left = len;
+    while left > 0 {
+        let limit = carry + min(left, chunk.len() - carry);
+        let n = match rd.read(&mut chunk[carry..limit]) {
+            Ok(0) => return Err(unexpected_eof(len, len - left)),
+            Ok(n) => n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(Error::InvalidDataRead(err)),
+        };
+        left -= n;
 
-    match String::from_utf8(read_bin_data(rd, len, depth)?) {
-        Ok(s) => Ok(Utf8String::from(s)),
-        Err(err) => {
-            let e = err.utf8_error();
-            let s = Utf8String {
-                s: Err((err.into_bytes(), e)),
+        let end = carry + n;
+        let split = if left == 0 { end } else { complete_utf8_len(&chunk[..end]) };
+        match std::str::from_utf8(&chunk[..split]) {
+            Ok(valid) => s.push_str(valid),
+            Err(..) => {
+                let mut buf = s.into_bytes();
+                buf.extend_from_slice(&chunk[..end]);
+                let bytes_read = rd.take(left as u64).read_to_end(&mut buf).map_err(Error::InvalidDataRead)?;
+                if bytes_read != left {
+                    return Err(unexpected_eof(len, len - left + bytes_read));
+                }
+                return Ok(Utf8String::from_bytes(buf));
+            }
+        }
+        chunk.copy_within(split..end, 0);
+        carry = end - split;
+    }
+
+    Ok(Utf8String::from(s))
+}
+
+/// Returns the length of `buf` without a character cut off at its end.
+fn complete_utf8_len(buf: &[u8]) -> usize {
+    // The last character starts within its last 4 bytes, unless the input is invalid anyway.
+    for idx in (buf.len().saturating_sub(4)..buf.len()).rev() {
+        let byte = buf[idx];
+        if byte & 0xc0 != 0x80 {
+            let char_len = match byte {
+                0xf0..=0xff => 4,
+                0xe0..=0xef => 3,
+                0xc0..=0xdf => 2,
+                _ => 1,
             };
-            Ok(s)
+            return if idx + char_len > buf.len() { idx } else { buf.len() };
         }
     }
+    buf.len()
 }
 
-fn read_bin_data<R: Read>(rd: &mut R, len: usize, depth: u16) -> Result<Vec<u8>, Error> {
+fn read_bin_data<R: Read>(rd: &mut R, len: usize, depth: u16, opts: &mut Options<'_>) -> Result<Vec<u8>, Error> {
     let _depth = super::decrement_depth(depth)?;
+    let len = super::check_len(len, opts.max_len)?;
 
     let mut buf = Vec::with_capacity(min(len, PREALLOC_MAX));
     let bytes_read = rd.take(len as u64).read_to_end(&mut buf).map_err(Error::InvalidDataRead)?;
     if bytes_read != len {
-        return Err(Error::InvalidDataRead(io::Error::new(
-            io::ErrorKind::UnexpectedEof,
-            format!("Expected {len} bytes, read {bytes_read} bytes"),
-        )));
+        return Err(unexpected_eof(len, bytes_read));
     }
 
     Ok(buf)
 }
 
-fn read_ext_body<R: Read>(rd: &mut R, len: usize, depth: u16) -> Result<(i8, Vec<u8>), Error> {
+#[cold]
+fn unexpected_eof(len: usize, bytes_read: usize) -> Error {
+    Error::InvalidDataRead(io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        format!("Expected {len} bytes, read {bytes_read} bytes"),
+    ))
+}
+
+fn read_ext_body<R: Read>(rd: &mut R, len: usize, depth: u16, opts: &mut Options<'_>) -> Result<(i8, Vec<u8>), Error> {
     let depth = super::decrement_depth(depth)?;
 
     let ty = rd.read_data_i8()?;
-    let vec = read_bin_data(rd, len, depth)?;
+    let vec = read_bin_data(rd, len, depth, opts)?;
 
     Ok((ty, vec))
 }
 
 #[inline(never)]
-fn read_value_inner<R>(rd: &mut R, depth: u16) -> Result<Value, Error> where R: Read {
+fn read_value_inner<R>(rd: &mut R, depth: u16, opts: &mut Options<'_>) -> Result<Value, Error> where R: Read {
+    let mut rd = Budget { rd, left: opts.max_bytes, exceeded: false };
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut depth = depth;
+    'read: loop {
+        let item = match read_item(&mut rd, depth, opts) {
+            // Running out of input at the limit isn't the input's fault.
+            Err(..) if rd.exceeded => return Err(Error::ByteLimitExceeded),
+            res => res?,
+        };
+        let mut val = match item {
+            Item::Value(val) => val,
+            // Each element takes at least a byte, so fail early on lengths over the budget.
+            Item::Open(frame) if frame.left as u64 > rd.left => return Err(Error::ByteLimitExceeded),
+            Item::Open(frame) => {
+                depth = frame.depth;
+                stack.push(frame);
+                continue;
+            }
+        };
+
+        // Add the value to the innermost open array or map, closing those that are complete.
+        while let Some(mut frame) = stack.pop() {
+            if !frame.push(val) {
+                depth = frame.depth;
+                stack.push(frame);
+                continue 'read;
+            }
+            val = frame.into_value();
+        }
+        return Ok(val);
+    }
+}
+
+/// Reads a scalar value, or the header of an array or map.
+fn read_item<R>(rd: &mut R, depth: u16, opts: &mut Options<'_>) -> Result<Item, Error> where R: Read {
     let depth = super::decrement_depth(depth)?;
     let val = match read_marker(rd)? {
         Marker::Null => Value::Nil,
@@ -99,111 +304,122 @@ fn read_value_inner<R>(rd: &mut R, depth: u16) -> Result<Value, Error> where R:
         Marker::F32 => Value::F32(rd.read_data_f32()?),
         Marker::F64 => Value::F64(rd.read_data_f64()?),
         Marker::FixStr(len) => {
-            let res = read_str_data(rd, len as usize, depth)?;
+            if let Some(val) = read_large_data(rd, Marker::FixStr(len), len as usize, opts)? {
+                return Ok(Item::Value(val));
+            }
+            let res = read_str_data(rd, len as usize, depth, opts)?;
             Value::String(res)
         }
         Marker::Str8 => {
             let len = rd.read_data_u8()?;
-            let res = read_str_data(rd, len as usize, depth)?;
+            if let Some(val) = read_large_data(rd, Marker::Str8, len as usize, opts)? {
+                return Ok(Item::Value(val));
+            }
+            let res = read_str_data(rd, len as usize, depth, opts)?;
             Value::String(res)
         }
         Marker::Str16 => {
             let len = rd.read_data_u16()?;
-            let res = read_str_data(rd, len as usize, depth)?;
+            if let Some(val) = read_large_data(rd, Marker::Str16, len as usize, opts)? {
+                return Ok(Item::Value(val));
+            }
+            let res = read_str_data(rd, len as usize, depth, opts)?;
             Value::String(res)
         }
         Marker::Str32 => {
             let len = rd.read_data_u32()?;
-            let res = read_str_data(rd, len as usize, depth)?;
+            if let Some(val) = read_large_data(rd, Marker::Str32, len as usize, opts)? {
+                return Ok(Item::Value(val));
+            }
+            let res = read_str_data(rd, len as usize, depth, opts)?;
             Value::String(res)
         }
-        Marker::FixArray(len) => {
-            let vec = read_array_data(rd, len as usize, depth)?;
-            Value::Array(vec)
-        }
+        Marker::FixArray(len) => return read_array_data(len as usize, depth, opts),
         Marker::Array16 => {
             let len = rd.read_data_u16()?;
-            let vec = read_array_data(rd, len as usize, depth)?;
-            Value::Array(vec)
+            return read_array_data(len as usize, depth, opts);
         }
         Marker::Array32 => {
             let len = rd.read_data_u32()?;
-            let vec = read_array_data(rd, len as usize, depth)?;
-            Value::Array(vec)
-        }
-        Marker::FixMap(len) => {
-            let map = read_map_data(rd, len as usize, depth)?;
-            Value::Map(map)
+            return read_array_data(len as usize, depth, opts);
         }
+        Marker::FixMap(len) => return read_map_data(len as usize, depth, opts),
         Marker::Map16 => {
             let len = rd.read_data_u16()?;
-            let map = read_map_data(rd, len as usize, depth)?;
-            Value::Map(map)
+            return read_map_data(len as usize, depth, opts);
         }
         Marker::Map32 => {
             let len = rd.read_data_u32()?;
-            let map = read_map_data(rd, len as usize, depth)?;
-            Value::Map(map)
+            return read_map_data(len as usize, depth, opts);
         }
         Marker::Bin8 => {
             let len = rd.read_data_u8()?;
-            let vec = read_bin_data(rd, len as usize, depth)?;
+            if let Some(val) = read_large_data(rd, Marker::Bin8, len as usize, opts)? {
+                return Ok(Item::Value(val));
+            }
+            let vec = read_bin_data(rd, len as usize, depth, opts)?;
             Value::Binary(vec)
         }
         Marker::Bin16 => {
             let len = rd.read_data_u16()?;
-            let vec = read_bin_data(rd, len as usize, depth)?;
+            if let Some(val) = read_large_data(rd, Marker::Bin16, len as usize, opts)? {
+                return Ok(Item::Value(val));
+            }
+            let vec = read_bin_data(rd, len as usize, depth, opts)?;
             Value::Binary(vec)
         }
         Marker::Bin32 => {
             let len = rd.read_data_u32()?;
-            let vec = read_bin_data(rd, len as usize, depth)?;
+            if let Some(val) = read_large_data(rd, Marker::Bin32, len as usize, opts)? {
+                return Ok(Item::Value(val));
+            }
+            let vec = read_bin_data(rd, len as usize, depth, opts)?;
             Value::Binary(vec)
         }
         Marker::FixExt1 => {
             let len = 1_usize;
-            let (ty, vec) = read_ext_body(rd, len, depth)?;
-            Value::Ext(ty, vec)
+            let (ty, vec) = read_ext_body(rd, len, depth, opts)?;
+            ext_value(ty, vec, opts)
         }
         Marker::FixExt2 => {
             let len = 2_usize;
-            let (ty, vec) = read_ext_body(rd, len, depth)?;
-            Value::Ext(ty, vec)
+            let (ty, vec) = read_ext_body(rd, len, depth, opts)?;
+            ext_value(ty, vec, opts)
         }
         Marker::FixExt4 => {
             let len = 4_usize;
-            let (ty, vec) = read_ext_body(rd, len, depth)?;
-            Value::Ext(ty, vec)
+            let (ty, vec) = read_ext_body(rd, len, depth, opts)?;
+            ext_value(ty, vec, opts)
         }
         Marker::FixExt8 => {
             let len = 8_usize;
-            let (ty, vec) = read_ext_body(rd, len, depth)?;
-            Value::Ext(ty, vec)
+            let (ty, vec) = read_ext_body(rd, len, depth, opts)?;
+            ext_value(ty, vec, opts)
         }
         Marker::FixExt16 => {
             let len = 16_usize;
-            let (ty, vec) = read_ext_body(rd, len, depth)?;
-            Value::Ext(ty, vec)
+            let (ty, vec) = read_ext_body(rd, len, depth, opts)?;
+            ext_value(ty, vec, opts)
         }
         Marker::Ext8 => {
             let len = rd.read_data_u8()? as usize;
-            let (ty, vec) = read_ext_body(rd, len, depth)?;
-            Value::Ext(ty, vec)
+            let (ty, vec) = read_ext_body(rd, len, depth, opts)?;
+            ext_value(ty, vec, opts)
         }
         Marker::Ext16 => {
             let len = rd.read_data_u16()? as usize;
-            let (ty, vec) = read_ext_body(rd, len, depth)?;
-            Value::Ext(ty, vec)
+            let (ty, vec) = read_ext_body(rd, len, depth, opts)?;
+            ext_value(ty, vec, opts)
         }
         Marker::Ext32 => {
             let len = rd.read_data_u32()? as usize;
-            let (ty, vec) = read_ext_body(rd, len, depth)?;
-            Value::Ext(ty, vec)
+            let (ty, vec) = read_ext_body(rd, len, depth, opts)?;
+            ext_value(ty, vec, opts)
         }
         Marker::Reserved => Value::Nil,
     };
 
-    Ok(val)
+    Ok(Item::Value(val))
 }
 
 /// Attempts to read bytes from the given reader and interpret them as a [`Value`].
@@ -217,11 +433,15 @@ fn read_value_inner<R>(rd: &mut R, depth: u16) -> Result<Value, Error> where R:
 /// [`Error::DepthLimitExceeded`] is returned if this function recurses
 /// [`MAX_DEPTH`](super::MAX_DEPTH) times. To configure the maximum recursion depth, use
 /// [`read_value_with_max_depth`] instead.
+///
+/// Arrays and maps are read with an explicit stack on the heap rather than by recursion, so the
+/// call stack doesn't grow with the nesting depth of the input, however high the depth limit is
+/// set. The same goes for the other `read_value` functions of this module.
 #[inline]
 pub fn read_value<R>(rd: &mut R) -> Result<Value, Error>
     where R: Read
 {
-    read_value_inner(rd, super::MAX_DEPTH as _)
+    read_value_inner(rd, super::MAX_DEPTH as _, &mut Options::with_max_len(usize::MAX))
 }
 
 /// Attempts to read bytes from the given reader and interpret them as a [`Value`].
@@ -239,5 +459,210 @@ pub fn read_value<R>(rd: &mut R) -> Result<Value, Error>
 pub fn read_value_with_max_depth<R>(rd: &mut R, max_depth: usize) -> Result<Value, Error>
     where R: Read
 {
-    read_value_inner(rd, max_depth.min(u16::MAX as usize) as u16)
+    read_value_inner(rd, max_depth.min(u16::MAX as usize) as u16, &mut Options::with_max_len(usize::MAX))
+}
+
+/// Attempts to read bytes from the given reader and interpret them as a [`Value`], limiting both
+/// the recursion depth and the length of every string, binary, ext, array and map.
+///
+/// Values are never preallocated beyond a small fixed size, so a hostile length header can't
+/// exhaust memory on its own. This additionally rejects such headers up front instead of
+/// reading as much data as the reader provides. To bound the total size of the input as well,
+/// use [`read_value_with_max_bytes`].
+///
+/// # Errors
+///
+/// This function will return [`Error`] on any I/O error while either reading or decoding a [`Value`].
+///
+/// [`Error::DepthLimitExceeded`] is returned if this function recurses `max_depth` times, and
+/// [`Error::LengthLimitExceeded`] if any length exceeds `max_len`.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::decode::{read_value_with_limits, Error};
+///
+/// // A `str 32` header claiming 4 GiB of data.
+/// let buf = [0xdb, 0xff, 0xff, 0xff, 0xff];
+///
+/// let res = read_value_with_limits(&mut &buf[..], 64, 1024);
+/// assert!(matches!(res, Err(Error::LengthLimitExceeded)));
+/// ```
+#[inline]
+pub fn read_value_with_limits<R>(rd: &mut R, max_depth: usize, max_len: usize) -> Result<Value, Error>
+    where R: Read
+{
+    read_value_inner(rd, max_depth.min(u16::MAX as usize) as u16, &mut Options::with_max_len(max_len))
+}
+
+/// Attempts to read bytes from the given reader and interpret them as a [`Value`], limiting the
+/// recursion depth, the length of every string, binary, ext, array and map, and the total number
+/// of bytes read.
+///
+/// Unlike the length limit, the byte limit also bounds input made of many values which are each
+/// short enough. Nothing is read past the limit.
+///
+/// # Errors
+///
+/// This function will return [`Error`] on any I/O error while either reading or decoding a [`Value`].
+///
+/// [`Error::DepthLimitExceeded`] is returned if this function recurses `max_depth` times,
+/// [`Error::LengthLimitExceeded`] if any length exceeds `max_len`, and
+/// [`Error::ByteLimitExceeded`] if the value takes more than `max_bytes` bytes.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::decode::{read_value_with_max_bytes, Error};
+///
+/// // [1, 2, 3, 4]
+/// let buf = [0x94, 0x01, 0x02, 0x03, 0x04];
+///
+/// assert!(read_value_with_max_bytes(&mut &buf[..], 64, 1024, 5).is_ok());
+///
+/// let res = read_value_with_max_bytes(&mut &buf[..], 64, 1024, 4);
+/// assert!(matches!(res, Err(Error::ByteLimitExceeded)));
+/// ```
+#[inline]
+pub fn read_value_with_max_bytes<R>(rd: &mut R, max_depth: usize, max_len: usize, max_bytes: u64) -> Result<Value, Error>
+    where R: Read
+{
+    let mut opts = Options::with_max_len(max_len);
+    opts.max_bytes = max_bytes;
+    read_value_inner(rd, max_depth.min(u16::MAX as usize) as u16, &mut opts)
+}
+
+/// Attempts to read bytes from the given reader and interpret them as a [`Value`], reading
+/// timestamp ext values as [`Value::Timestamp`].
+///
+/// Ext values of the timestamp type `-1` whose data doesn't match any timestamp format are
+/// kept as [`Value::Ext`]. Other readers return all ext values as `Value::Ext`, for
+/// compatibility; use [`Value::as_timestamp`] or [`Value::convert_timestamps`] with them.
+///
+/// # Errors
+///
+/// This function will return [`Error`] on any I/O error while either reading or decoding a [`Value`].
+///
+/// [`Error::DepthLimitExceeded`] is returned if this function recurses
+/// [`MAX_DEPTH`](super::MAX_DEPTH) times.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::{Timestamp, Value};
+/// use rmpv::decode::read_value_with_timestamps;
+///
+/// // [timestamp 32 of 2 seconds, ext -1 with bogus data]
+/// let buf = [0x92, 0xd6, 0xff, 0x00, 0x00, 0x00, 0x02, 0xd4, 0xff, 0x00];
+///
+/// let val = read_value_with_timestamps(&mut &buf[..]).unwrap();
+/// assert_eq!(Value::Array(vec![Value::from(Timestamp::new(2, 0).unwrap()), Value::Ext(-1, vec![0])]), val);
+/// ```
+#[inline]
+pub fn read_value_with_timestamps<R>(rd: &mut R) -> Result<Value, Error>
+    where R: Read
+{
+    let mut opts = Options::with_max_len(usize::MAX);
+    opts.timestamps = true;
+    read_value_inner(rd, super::MAX_DEPTH as _, &mut opts)
+}
+
+/// Attempts to read bytes from the given reader and interpret them as a [`Value`], without
+/// materializing the bodies of strings and binaries longer than `max_inline_len` bytes.
+///
+/// For each such string or binary the handler is called with its marker and a reader limited
+/// to its body. The handler may stream the body elsewhere, e.g. into a file, or ignore it, and
+/// returns the placeholder value to put in the tree instead. Any part of the body left unread by
+/// the handler is skipped. Shorter strings and binaries are read as usual.
+///
+/// # Errors
+///
+/// This function will return [`Error`] on any I/O error while either reading or decoding a
+/// [`Value`], including errors returned by the handler.
+///
+/// [`Error::DepthLimitExceeded`] is returned if this function recurses
+/// [`MAX_DEPTH`](super::MAX_DEPTH) times.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::Value;
+/// use rmpv::decode::read_value_lazy;
+///
+/// // {"name": "photo", "data": <bin of 1000 bytes>}
+/// let mut buf = vec![0x82, 0xa4, b'n', b'a', b'm', b'e', 0xa5, b'p', b'h', b'o', b't', b'o'];
+/// buf.extend_from_slice(&[0xa4, b'd', b'a', b't', b'a', 0xc5, 0x03, 0xe8]);
+/// buf.extend_from_slice(&[0xff; 1000]);
+///
+/// let mut data = Vec::new();
+/// let val = read_value_lazy(&mut &buf[..], 64, |_marker, body| {
+///     let len = std::io::copy(body, &mut data)?;
+///     Ok(Value::from(format!("<{len} bytes>")))
+/// }).unwrap();
+///
+/// assert_eq!(Value::from("photo"), val["name"]);
+/// assert_eq!(Value::from("<1000 bytes>"), val["data"]);
+/// assert_eq!(1000, data.len());
+/// ```
+pub fn read_value_lazy<R, F>(rd: &mut R, max_inline_len: usize, mut handler: F) -> Result<Value, Error>
+    where R: Read,
+          F: FnMut(Marker, &mut dyn Read) -> io::Result<Value>
+{
+    let mut opts = Options {
+        max_len: usize::MAX,
+        max_bytes: u64::MAX,
+        large: Some((max_inline_len, &mut handler)),
+        timestamps: false,
+    };
+    read_value_inner(rd, super::MAX_DEPTH as _, &mut opts)
+}
+
+/// Attempts to read a map with string keys, the common shape of top-level messages, into `M`.
+///
+/// Keys are read directly as `String`s and values as [`Value`]s. This is faster than reading the
+/// whole map as a `Value` and converting it, or deserializing it through `serde`, and `M` may be
+/// any map type, e.g. `HashMap<String, Value>` or `BTreeMap<String, Value>`. Entries are added
+/// in the order they are read, so later duplicate keys win for these map types.
+///
+/// # Errors
+///
+/// This function will return [`Error`] on any I/O error while either reading or decoding the
+/// map, or if the input isn't a map with keys that are valid UTF-8 strings.
+///
+/// [`Error::DepthLimitExceeded`] is returned if this function recurses
+/// [`MAX_DEPTH`](super::MAX_DEPTH) times, counting the map itself.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use rmpv::Value;
+/// use rmpv::decode::read_map_of_values;
+///
+/// // {"id": 42, "tags": ["a"]}
+/// let buf = [0x82, 0xa2, b'i', b'd', 0x2a, 0xa4, b't', b'a', b'g', b's', 0x91, 0xa1, b'a'];
+///
+/// let map: HashMap<String, Value> = read_map_of_values(&mut &buf[..]).unwrap();
+/// assert_eq!(Value::from(42), map["id"]);
+/// assert_eq!(Value::Array(vec![Value::from("a")]), map["tags"]);
+/// ```
+pub fn read_map_of_values<R, M>(rd: &mut R) -> Result<M, Error>
+    where R: Read,
+          M: Default + Extend<(String, Value)>
+{
+    let opts = &mut Options::with_max_len(usize::MAX);
+    let depth = super::decrement_depth(super::MAX_DEPTH as _)?;
+    let len = read_map_len(rd)?;
+
+    let mut map = M::default();
+    for _ in 0..len {
+        let key_len = read_str_len(rd)?;
+        let key = read_bin_data(rd, key_len as usize, depth, opts)?;
+        let key = String::from_utf8(key)
+            .map_err(|err| Error::InvalidDataRead(io::Error::new(io::ErrorKind::InvalidData, err)))?;
+        let val = read_value_inner(rd, depth, opts)?;
+        map.extend(Some((key, val)));
+    }
+    Ok(map)
 }
@@ -0,0 +1,243 @@
+use std::cmp::min;
+use std::io;
+
+use rmp::Marker;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use super::Error;
+use crate::{Utf8String, Value};
+
+// See https://github.com/3Hren/msgpack-rust/issues/151
+const PREALLOC_MAX: usize = 64 * 1024; // 64 KiB
+
+/// A container whose elements are being read.
+enum Frame {
+    Array(Vec<Value>, u32),
+    /// Collected pairs, the key waiting for its value and the number of remaining pairs.
+    Map(Vec<(Value, Value)>, Option<Value>, u32),
+}
+
+/// A single value, or the header of a container followed by its elements.
+enum Item {
+    Value(Value),
+    Array(u32),
+    Map(u32),
+}
+
+async fn read_data<R: AsyncRead + Unpin>(rd: &mut R, len: u32) -> Result<Vec<u8>, Error> {
+    let len = len as usize;
+    let mut buf = Vec::with_capacity(min(len, PREALLOC_MAX));
+    let bytes_read = rd.take(len as u64).read_to_end(&mut buf).await.map_err(Error::InvalidDataRead)?;
+    if bytes_read != len {
+        return Err(Error::InvalidDataRead(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("Expected {len} bytes, read {bytes_read} bytes"),
+        )));
+    }
+
+    Ok(buf)
+}
+
+async fn read_str<R: AsyncRead + Unpin>(rd: &mut R, len: u32) -> Result<Value, Error> {
+    let s = match String::from_utf8(read_data(rd, len).await?) {
+        Ok(s) => Utf8String::from(s),
+        Err(err) => {
+            let e = err.utf8_error();
+            Utf8String {
+                s: Err((err.into_bytes(), e)),
+            }
+        }
+    };
+
+    Ok(Value::String(s))
+}
+
+async fn read_ext<R: AsyncRead + Unpin>(rd: &mut R, len: u32) -> Result<Value, Error> {
+    let ty = rd.read_i8().await.map_err(Error::InvalidDataRead)?;
+    Ok(Value::Ext(ty, read_data(rd, len).await?))
+}
+
+async fn read_item<R: AsyncRead + Unpin>(rd: &mut R) -> Result<Item, Error> {
+    macro_rules! data {
+        ($read:ident) => {
+            rd.$read().await.map_err(Error::InvalidDataRead)?
+        };
+    }
+
+    let marker = Marker::from_u8(rd.read_u8().await.map_err(Error::InvalidMarkerRead)?);
+    let val = match marker {
+        Marker::Null | Marker::Reserved => Value::Nil,
+        Marker::True => Value::Boolean(true),
+        Marker::False => Value::Boolean(false),
+        Marker::FixPos(val) => Value::from(val),
+        Marker::FixNeg(val) => Value::from(val),
+        Marker::U8 => Value::from(data!(read_u8)),
+        Marker::U16 => Value::from(data!(read_u16)),
+        Marker::U32 => Value::from(data!(read_u32)),
+        Marker::U64 => Value::from(data!(read_u64)),
+        Marker::I8 => Value::from(data!(read_i8)),
+        Marker::I16 => Value::from(data!(read_i16)),
+        Marker::I32 => Value::from(data!(read_i32)),
+        Marker::I64 => Value::from(data!(read_i64)),
+        Marker::F32 => Value::F32(data!(read_f32)),
+        Marker::F64 => Value::F64(data!(read_f64)),
+        Marker::FixStr(len) => read_str(rd, u32::from(len)).await?,
+        Marker::Str8 => {
+            let len = u32::from(data!(read_u8));
+            read_str(rd, len).await?
+        }
+        Marker::Str16 => {
+            let len = u32::from(data!(read_u16));
+            read_str(rd, len).await?
+        }
+        Marker::Str32 => {
+            let len = data!(read_u32);
+            read_str(rd, len).await?
+        }
+        Marker::Bin8 => {
+            let len = u32::from(data!(read_u8));
+            Value::Binary(read_data(rd, len).await?)
+        }
+        Marker::Bin16 => {
+            let len = u32::from(data!(read_u16));
+            Value::Binary(read_data(rd, len).await?)
+        }
+        Marker::Bin32 => {
+            let len = data!(read_u32);
+            Value::Binary(read_data(rd, len).await?)
+        }
+        Marker::FixExt1 => read_ext(rd, 1).await?,
+        Marker::FixExt2 => read_ext(rd, 2).await?,
+        Marker::FixExt4 => read_ext(rd, 4).await?,
+        Marker::FixExt8 => read_ext(rd, 8).await?,
+        Marker::FixExt16 => read_ext(rd, 16).await?,
+        Marker::Ext8 => {
+            let len = u32::from(data!(read_u8));
+            read_ext(rd, len).await?
+        }
+        Marker::Ext16 => {
+            let len = u32::from(data!(read_u16));
+            read_ext(rd, len).await?
+        }
+        Marker::Ext32 => {
+            let len = data!(read_u32);
+            read_ext(rd, len).await?
+        }
+        Marker::FixArray(len) => return Ok(Item::Array(u32::from(len))),
+        Marker::Array16 => return Ok(Item::Array(u32::from(data!(read_u16)))),
+        Marker::Array32 => return Ok(Item::Array(data!(read_u32))),
+        Marker::FixMap(len) => return Ok(Item::Map(u32::from(len))),
+        Marker::Map16 => return Ok(Item::Map(u32::from(data!(read_u16)))),
+        Marker::Map32 => return Ok(Item::Map(data!(read_u32))),
+    };
+
+    Ok(Item::Value(val))
+}
+
+async fn read_value_async_inner<R: AsyncRead + Unpin>(rd: &mut R, max_depth: usize) -> Result<Value, Error> {
+    // Containers are tracked on an explicit stack, since async functions can't recurse without
+    // boxing every level.
+    let mut stack = Vec::new();
+
+    loop {
+        let mut val = match read_item(rd).await? {
+            Item::Value(val) => val,
+            Item::Array(0) => Value::Array(Vec::new()),
+            Item::Map(0) => Value::Map(Vec::new()),
+            Item::Array(..) | Item::Map(..) if stack.len() >= max_depth => {
+                return Err(Error::DepthLimitExceeded);
+            }
+            // Note: Do not preallocate a Vec of size `len`.
+            // See https://github.com/3Hren/msgpack-rust/issues/151
+            Item::Array(len) => {
+                stack.push(Frame::Array(Vec::new(), len));
+                continue;
+            }
+            Item::Map(len) => {
+                stack.push(Frame::Map(Vec::new(), None, len));
+                continue;
+            }
+        };
+
+        // Put the value into its container, completing as many containers as it fills up.
+        loop {
+            match stack.last_mut() {
+                None => return Ok(val),
+                Some(Frame::Array(vec, remaining)) => {
+                    vec.push(val);
+                    *remaining -= 1;
+                    if *remaining > 0 {
+                        break;
+                    }
+                    val = Value::Array(std::mem::take(vec));
+                }
+                Some(Frame::Map(vec, key, remaining)) => match key.take() {
+                    None => {
+                        *key = Some(val);
+                        break;
+                    }
+                    Some(key) => {
+                        vec.push((key, val));
+                        *remaining -= 1;
+                        if *remaining > 0 {
+                            break;
+                        }
+                        val = Value::Map(std::mem::take(vec));
+                    }
+                },
+            }
+            stack.pop();
+        }
+    }
+}
+
+/// Asynchronously reads a [`Value`] from the given reader, without blocking the thread while
+/// waiting for data.
+///
+/// This is the async counterpart of [`read_value`](super::read_value), available with the
+/// `tokio` feature. The reader is read in small pieces, so wrap unbuffered readers, such as a
+/// `TcpStream`, with a `BufReader`.
+///
+/// # Errors
+///
+/// This function will return [`Error`] on any I/O error while either reading or decoding a
+/// [`Value`].
+///
+/// [`Error::DepthLimitExceeded`] is returned if containers are nested deeper than
+/// [`MAX_DEPTH`](super::MAX_DEPTH). To configure the limit, use
+/// [`read_value_async_with_max_depth`] instead.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// use rmpv::Value;
+///
+/// let buf = [0x92, 0x01, 0xa2, 0x68, 0x69];
+/// let val = rmpv::decode::read_value_async(&mut &buf[..]).await.unwrap();
+///
+/// assert_eq!(Value::Array(vec![Value::from(1), Value::from("hi")]), val);
+/// # }
+/// ```
+#[inline]
+pub async fn read_value_async<R>(rd: &mut R) -> Result<Value, Error>
+    where R: AsyncRead + Unpin
+{
+    read_value_async_inner(rd, super::MAX_DEPTH).await
+}
+
+/// Asynchronously reads a [`Value`] from the given reader, allowing at most `max_depth` nested
+/// arrays and maps.
+///
+/// # Errors
+///
+/// This function will return [`Error`] on any I/O error while either reading or decoding a
+/// [`Value`], and [`Error::DepthLimitExceeded`] if containers are nested deeper than
+/// `max_depth`.
+#[inline]
+pub async fn read_value_async_with_max_depth<R>(rd: &mut R, max_depth: usize) -> Result<Value, Error>
+    where R: AsyncRead + Unpin
+{
+    read_value_async_inner(rd, max_depth).await
+}
@@ -1,8 +1,9 @@
 use std;
 use std::io::{self, Cursor, ErrorKind, Read};
-use std::str;
+use std::ops::Deref;
+use std::{mem, str};
 
-use rmp::decode::{read_marker, RmpRead};
+use rmp::decode::{read_marker, RmpRead, ValueReadError};
 use rmp::Marker;
 
 use super::Error;
@@ -28,17 +29,7 @@ fn read_bin_data<'a, R>(rd: &mut R, len: usize, depth: u16) -> Result<&'a [u8],
     where R: BorrowRead<'a>
 {
     let _depth = super::decrement_depth(depth)?;
-    let buf = rd.fill_buf();
-
-    if len > buf.len() {
-        return Err(Error::InvalidDataRead(io::Error::new(ErrorKind::UnexpectedEof, "unexpected EOF")));
-    }
-
-    // Take a slice.
-    let buf = &buf[..len];
-    rd.consume(len);
-
-    Ok(buf)
+    rd.read_borrowed(len).map_err(Error::InvalidDataRead)
 }
 
 fn read_ext_body<'a, R>(rd: &mut R, len: usize, depth: u16) -> Result<(i8, &'a [u8]), Error>
@@ -51,36 +42,73 @@ fn read_ext_body<'a, R>(rd: &mut R, len: usize, depth: u16) -> Result<(i8, &'a [
     Ok((ty, buf))
 }
 
-fn read_array_data<'a, R>(rd: &mut R, mut len: usize, depth: u16) -> Result<Vec<ValueRef<'a>>, Error>
-    where R: BorrowRead<'a>
-{
-    let depth = super::decrement_depth(depth)?;
-    // Note: Do not preallocate a Vec of size `len`.
-    // See https://github.com/3Hren/msgpack-rust/issues/151
-    let mut vec = Vec::new();
+/// A partially read array or map.
+///
+/// These are kept on the heap rather than on the call stack, so that the stack usage doesn't
+/// depend on the nesting depth of the value.
+struct Frame<'a> {
+    /// Remaining depth of the elements.
+    depth: u16,
+    /// Number of elements left to read, counting keys and values of maps separately.
+    left: usize,
+    data: FrameData<'a>,
+}
 
-    while len > 0 {
-        vec.push(read_value_ref_inner(rd, depth)?);
-        len -= 1;
+enum FrameData<'a> {
+    Array(Vec<ValueRef<'a>>),
+    Map(Vec<(ValueRef<'a>, ValueRef<'a>)>, Option<ValueRef<'a>>),
+}
+
+impl<'a> Frame<'a> {
+    /// Adds the next element, returning `true` if the array or map is complete.
+    fn push(&mut self, val: ValueRef<'a>) -> bool {
+        match self.data {
+            FrameData::Array(ref mut vec) => vec.push(val),
+            FrameData::Map(ref mut vec, ref mut key) => match key.take() {
+                Some(key) => vec.push((key, val)),
+                None => *key = Some(val),
+            },
+        }
+        self.left -= 1;
+        self.left == 0
     }
 
-    Ok(vec)
+    fn into_value(self) -> ValueRef<'a> {
+        match self.data {
+            FrameData::Array(vec) => ValueRef::Array(vec),
+            FrameData::Map(vec, _) => ValueRef::Map(vec),
+        }
+    }
 }
 
-fn read_map_data<'a, R>(rd: &mut R, mut len: usize, depth: u16) -> Result<Vec<(ValueRef<'a>, ValueRef<'a>)>, Error>
-    where R: BorrowRead<'a>
-{
+/// Either a complete value, or an array or map whose elements follow.
+enum Item<'a> {
+    Value(ValueRef<'a>),
+    Open(Frame<'a>),
+}
+
+impl<'a> Item<'a> {
+    fn open(frame: Frame<'a>) -> Self {
+        if frame.left == 0 {
+            Item::Value(frame.into_value())
+        } else {
+            Item::Open(frame)
+        }
+    }
+}
+
+fn read_array_data<'a>(len: usize, depth: u16) -> Result<Item<'a>, Error> {
     let depth = super::decrement_depth(depth)?;
     // Note: Do not preallocate a Vec of size `len`.
     // See https://github.com/3Hren/msgpack-rust/issues/151
-    let mut vec = Vec::new();
-
-    while len > 0 {
-        vec.push((read_value_ref_inner(rd, depth)?, read_value_ref_inner(rd, depth)?));
-        len -= 1;
-    }
+    Ok(Item::open(Frame { depth, left: len, data: FrameData::Array(Vec::new()) }))
+}
 
-    Ok(vec)
+fn read_map_data<'a>(len: usize, depth: u16) -> Result<Item<'a>, Error> {
+    let depth = super::decrement_depth(depth)?;
+    // Note: Do not preallocate a Vec of size `len`.
+    // See https://github.com/3Hren/msgpack-rust/issues/151
+    Ok(Item::open(Frame { depth, left: 2 * len, data: FrameData::Map(Vec::new(), None) }))
 }
 
 /// A `BorrowRead` is a type of Reader which has an internal buffer.
@@ -102,6 +130,23 @@ pub trait BorrowRead<'a>: Read {
     /// Tells this buffer that len bytes have been consumed from the buffer, so they should no
     /// longer be returned in calls to read.
     fn consume(&mut self, len: usize);
+
+    /// Consumes the next `len` bytes and returns them.
+    ///
+    /// The default implementation takes them from the buffer returned by `fill_buf`, and fails
+    /// with `ErrorKind::UnexpectedEof` if it's shorter.
+    fn read_borrowed(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        let buf = self.fill_buf();
+        if len > buf.len() {
+            return Err(io::Error::new(ErrorKind::UnexpectedEof, "unexpected EOF"));
+        }
+
+        // Take a slice.
+        let buf = &buf[..len];
+        self.consume(len);
+
+        Ok(buf)
+    }
 }
 
 impl<'a> BorrowRead<'a> for &'a [u8] {
@@ -129,6 +174,35 @@ impl<'a> BorrowRead<'a> for Cursor<&'a [u8]> {
 
 fn read_value_ref_inner<'a, R>(rd: &mut R, depth: u16) -> Result<ValueRef<'a>, Error>
     where R: BorrowRead<'a>
+{
+    let mut stack: Vec<Frame<'a>> = Vec::new();
+    let mut depth = depth;
+    'read: loop {
+        let mut val = match read_item(rd, depth)? {
+            Item::Value(val) => val,
+            Item::Open(frame) => {
+                depth = frame.depth;
+                stack.push(frame);
+                continue;
+            }
+        };
+
+        // Add the value to the innermost open array or map, closing those that are complete.
+        while let Some(mut frame) = stack.pop() {
+            if !frame.push(val) {
+                depth = frame.depth;
+                stack.push(frame);
+                continue 'read;
+            }
+            val = frame.into_value();
+        }
+        return Ok(val);
+    }
+}
+
+/// Reads a scalar value, or the header of an array or map.
+fn read_item<'a, R>(rd: &mut R, depth: u16) -> Result<Item<'a>, Error>
+    where R: BorrowRead<'a>
 {
     let depth = super::decrement_depth(depth)?;
 
@@ -184,33 +258,23 @@ fn read_value_ref_inner<'a, R>(rd: &mut R, depth: u16) -> Result<ValueRef<'a>, E
             let res = read_bin_data(rd, len as usize, depth)?;
             ValueRef::Binary(res)
         }
-        Marker::FixArray(len) => {
-            let vec = read_array_data(rd, len as usize, depth)?;
-            ValueRef::Array(vec)
-        }
+        Marker::FixArray(len) => return read_array_data(len as usize, depth),
         Marker::Array16 => {
             let len = rd.read_data_u16()?;
-            let vec = read_array_data(rd, len as usize, depth)?;
-            ValueRef::Array(vec)
+            return read_array_data(len as usize, depth);
         }
         Marker::Array32 => {
             let len = rd.read_data_u32()?;
-            let vec = read_array_data(rd, len as usize, depth)?;
-            ValueRef::Array(vec)
-        }
-        Marker::FixMap(len) => {
-            let map = read_map_data(rd, len as usize, depth)?;
-            ValueRef::Map(map)
+            return read_array_data(len as usize, depth);
         }
+        Marker::FixMap(len) => return read_map_data(len as usize, depth),
         Marker::Map16 => {
             let len = rd.read_data_u16()?;
-            let map = read_map_data(rd, len as usize, depth)?;
-            ValueRef::Map(map)
+            return read_map_data(len as usize, depth);
         }
         Marker::Map32 => {
             let len = rd.read_data_u32()?;
-            let map = read_map_data(rd, len as usize, depth)?;
-            ValueRef::Map(map)
+            return read_map_data(len as usize, depth);
         }
         Marker::FixExt1 => {
             let len = 1;
@@ -255,7 +319,7 @@ fn read_value_ref_inner<'a, R>(rd: &mut R, depth: u16) -> Result<ValueRef<'a>, E
         Marker::Reserved => ValueRef::Nil,
     };
 
-    Ok(val)
+    Ok(Item::Value(val))
 }
 
 /// Attempts to read the data from the given reader until either a complete MessagePack value
@@ -318,3 +382,219 @@ pub fn read_value_ref_with_max_depth<'a, R>(rd: &mut R, max_depth: usize) -> Res
 {
     read_value_ref_inner(rd, max_depth.min(u16::MAX as _) as u16)
 }
+
+/// Position in a sequence of byte segments.
+struct SegmentCursor<'a, S> {
+    rest: &'a [S],
+    current: &'a [u8],
+    consumed: usize,
+}
+
+impl<S> Clone for SegmentCursor<'_, S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S> Copy for SegmentCursor<'_, S> {}
+
+impl<'a, S: Deref<Target = [u8]>> SegmentCursor<'a, S> {
+    fn new(segments: &'a [S]) -> Self {
+        let mut cursor = SegmentCursor { rest: segments, current: &[], consumed: 0 };
+        cursor.advance(0);
+        cursor
+    }
+
+    /// Consumes `len` bytes of the current segment, moving to the next non-empty one after it.
+    fn advance(&mut self, len: usize) {
+        self.current = &self.current[len..];
+        self.consumed += len;
+        while self.current.is_empty() {
+            match self.rest.split_first() {
+                Some((first, rest)) => {
+                    self.current = first;
+                    self.rest = rest;
+                }
+                None => break,
+            }
+        }
+    }
+
+    fn skip(&mut self, mut len: usize) -> io::Result<()> {
+        while len > 0 {
+            if self.current.is_empty() {
+                return Err(io::Error::new(ErrorKind::UnexpectedEof, "unexpected EOF"));
+            }
+            let n = len.min(self.current.len());
+            self.advance(n);
+            len -= n;
+        }
+        Ok(())
+    }
+}
+
+impl<S: Deref<Target = [u8]>> Read for SegmentCursor<'_, S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = buf.len().min(self.current.len());
+        buf[..n].copy_from_slice(&self.current[..n]);
+        self.advance(n);
+        Ok(n)
+    }
+}
+
+/// Reads from segments, copying data that crosses a segment boundary into `scratch`.
+struct SegmentReader<'a, S> {
+    cursor: SegmentCursor<'a, S>,
+    scratch: &'a mut [u8],
+}
+
+impl<S: Deref<Target = [u8]>> Read for SegmentReader<'_, S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.cursor.read(buf)
+    }
+}
+
+impl<'a, S: Deref<Target = [u8]>> BorrowRead<'a> for SegmentReader<'a, S> {
+    fn fill_buf(&self) -> &'a [u8] {
+        self.cursor.current
+    }
+
+    fn consume(&mut self, len: usize) {
+        self.cursor.advance(len);
+    }
+
+    fn read_borrowed(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        if len <= self.cursor.current.len() {
+            let buf = &self.cursor.current[..len];
+            self.cursor.advance(len);
+            return Ok(buf);
+        }
+        if len > self.scratch.len() {
+            return Err(io::Error::new(ErrorKind::UnexpectedEof, "unexpected EOF"));
+        }
+
+        let (buf, rest) = mem::take(&mut self.scratch).split_at_mut(len);
+        self.scratch = rest;
+        self.cursor.read_exact(buf)?;
+        Ok(buf)
+    }
+}
+
+/// Returns the number of data bytes of the next value that cross a segment boundary.
+///
+/// Stops early on malformed input, leaving the error to be reported when decoding it.
+fn split_data_len<S: Deref<Target = [u8]>>(mut rd: SegmentCursor<'_, S>) -> usize {
+    fn data_len<R: Read>(rd: &mut R, marker: Marker, remaining: &mut u64) -> Result<usize, ValueReadError<io::Error>> {
+        let len = match marker {
+            Marker::FixStr(len) => len.into(),
+            Marker::Str8 | Marker::Bin8 => rd.read_data_u8()?.into(),
+            Marker::Str16 | Marker::Bin16 => rd.read_data_u16()?.into(),
+            Marker::Str32 | Marker::Bin32 => rd.read_data_u32()?,
+            Marker::FixExt1 => 1,
+            Marker::FixExt2 => 2,
+            Marker::FixExt4 => 4,
+            Marker::FixExt8 => 8,
+            Marker::FixExt16 => 16,
+            Marker::Ext8 => rd.read_data_u8()?.into(),
+            Marker::Ext16 => rd.read_data_u16()?.into(),
+            Marker::Ext32 => rd.read_data_u32()?,
+            Marker::FixArray(len) => {
+                *remaining = remaining.saturating_add(u64::from(len));
+                0
+            }
+            Marker::Array16 => {
+                *remaining = remaining.saturating_add(u64::from(rd.read_data_u16()?));
+                0
+            }
+            Marker::Array32 => {
+                *remaining = remaining.saturating_add(u64::from(rd.read_data_u32()?));
+                0
+            }
+            Marker::FixMap(len) => {
+                *remaining = remaining.saturating_add(2 * u64::from(len));
+                0
+            }
+            Marker::Map16 => {
+                *remaining = remaining.saturating_add(2 * u64::from(rd.read_data_u16()?));
+                0
+            }
+            Marker::Map32 => {
+                *remaining = remaining.saturating_add(2 * u64::from(rd.read_data_u32()?));
+                0
+            }
+            // Scalars are always copied out of the reader.
+            Marker::U8 | Marker::I8 => return rd.read_data_u8().map(|_| 0),
+            Marker::U16 | Marker::I16 => return rd.read_data_u16().map(|_| 0),
+            Marker::U32 | Marker::I32 | Marker::F32 => return rd.read_data_u32().map(|_| 0),
+            Marker::U64 | Marker::I64 | Marker::F64 => return rd.read_data_u64().map(|_| 0),
+            _ => return Ok(0),
+        };
+        if marker.is_ext() {
+            rd.read_data_i8()?;
+        }
+        Ok(len as usize)
+    }
+
+    let mut needed = 0;
+    let mut remaining: u64 = 1;
+    while remaining > 0 {
+        remaining -= 1;
+        let Ok(marker) = read_marker(&mut rd) else { break };
+        let Ok(len) = data_len(&mut rd, marker, &mut remaining) else { break };
+        let crosses = len > rd.current.len();
+        // Only data that is actually present is counted, so a bogus length can't make the
+        // scratch buffer larger than the input.
+        if rd.skip(len).is_err() {
+            break;
+        }
+        if crosses {
+            needed += len;
+        }
+    }
+    needed
+}
+
+/// Attempts to read a complete MessagePack value from a sequence of byte segments, such as the
+/// `IoSlice`s of a scattered network buffer.
+///
+/// Strings, binaries and ext data that lie within a single segment are borrowed from it. Those
+/// crossing a segment boundary are copied into `scratch`, which is cleared and resized to fit
+/// them exactly, so input that isn't split in the middle of such data is decoded without copying.
+///
+/// Returns the value and the number of bytes it occupies, which can be used to advance the
+/// underlying buffer, e.g. with `bytes::Buf::advance`.
+///
+/// # Errors
+///
+/// Same as [`read_value_ref`]. Like it, this enforces a maximum recursion depth of
+/// [`MAX_DEPTH`](super::MAX_DEPTH).
+///
+/// # Examples
+///
+/// ```
+/// use std::io::IoSlice;
+/// use rmpv::ValueRef;
+/// use rmpv::decode::read_value_ref_from_segments;
+///
+/// // `["le", "message"]`, split in the middle of the second string.
+/// let head = [0x92, 0xa2, 0x6c, 0x65, 0xa7, 0x6d, 0x65, 0x73];
+/// let tail = [0x73, 0x61, 0x67, 0x65];
+/// let segments = [IoSlice::new(&head), IoSlice::new(&tail)];
+///
+/// let mut scratch = Vec::new();
+/// let (val, len) = read_value_ref_from_segments(&segments, &mut scratch).unwrap();
+///
+/// assert_eq!(ValueRef::Array(vec![ValueRef::from("le"), ValueRef::from("message")]), val);
+/// assert_eq!(12, len);
+/// ```
+pub fn read_value_ref_from_segments<'a, S>(segments: &'a [S], scratch: &'a mut Vec<u8>) -> Result<(ValueRef<'a>, usize), Error>
+    where S: Deref<Target = [u8]>
+{
+    let cursor = SegmentCursor::new(segments);
+    scratch.clear();
+    scratch.resize(split_data_len(cursor), 0);
+
+    let mut rd = SegmentReader { cursor, scratch: &mut scratch[..] };
+    let val = read_value_ref_inner(&mut rd, super::MAX_DEPTH as _)?;
+    Ok((val, rd.cursor.consumed))
+}
@@ -0,0 +1,273 @@
+//! Structural comparison of values, e.g. for golden-file tests.
+
+use std::fmt::{self, Display, Formatter};
+
+use crate::{Integer, Value};
+
+/// A step on the path from the root value to a nested one.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PathSegment {
+    /// An array element with the given index.
+    Index(usize),
+    /// The value of the map entry with the given key.
+    Key(Value),
+}
+
+impl Display for PathSegment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            PathSegment::Index(idx) => write!(f, "[{idx}]"),
+            PathSegment::Key(ref key) => write!(f, "[{key}]"),
+        }
+    }
+}
+
+/// The way two values at the same path differ.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DifferenceKind {
+    /// The values are of different types, e.g. a string and an integer.
+    Type,
+    /// The values are of the same type, but not equal.
+    Value,
+    /// The path only exists in the right value, i.e. an array element or map entry was added.
+    Added,
+    /// The path only exists in the left value, i.e. an array element or map entry was removed.
+    Removed,
+}
+
+/// A single difference between two values, as returned by [`diff`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Difference {
+    /// Array indices and map keys leading from the root to the differing values.
+    pub path: Vec<PathSegment>,
+    /// The way the values differ.
+    pub kind: DifferenceKind,
+    /// The value in the left tree, `None` for [`DifferenceKind::Added`].
+    pub left: Option<Value>,
+    /// The value in the right tree, `None` for [`DifferenceKind::Removed`].
+    pub right: Option<Value>,
+}
+
+impl Display for Difference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("$")?;
+        for segment in &self.path {
+            Display::fmt(segment, f)?;
+        }
+        match (&self.left, &self.right) {
+            (Some(left), Some(right)) => write!(f, ": {left} != {right}"),
+            (Some(left), None) => write!(f, ": {left} was removed"),
+            (None, Some(right)) => write!(f, ": {right} was added"),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+/// Options of [`diff_with`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct DiffOptions {
+    numbers_by_value: bool,
+}
+
+impl DiffOptions {
+    /// Returns the default options, which compare values exactly.
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether integers and floats are compared by their numeric value, so that e.g.
+    /// `Value::from(1)`, `Value::F32(1.0)` and `Value::F64(1.0)` are considered equal.
+    ///
+    /// Integers are always compared by value, regardless of whether they were encoded signed or
+    /// unsigned, or how many bytes they took.
+    #[inline]
+    #[must_use]
+    pub fn numbers_by_value(mut self, enabled: bool) -> Self {
+        self.numbers_by_value = enabled;
+        self
+    }
+}
+
+/// Returns the differences between two values, in the order they appear.
+///
+/// Arrays are compared element by element, with extra elements reported as added or removed.
+/// Maps are compared by key regardless of the order of their entries. An empty result means the
+/// values are equal.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::diff::DifferenceKind;
+/// use rmpv::Value;
+///
+/// let left = Value::Map(vec![("id".into(), 1.into()), ("tags".into(), Value::Array(vec!["a".into()]))]);
+/// let right = Value::Map(vec![("tags".into(), Value::Array(vec!["b".into()])), ("id".into(), 1.into())]);
+///
+/// let diffs = rmpv::diff(&left, &right);
+/// assert_eq!(1, diffs.len());
+/// assert_eq!(DifferenceKind::Value, diffs[0].kind);
+/// assert_eq!(r#"$["tags"][0]: "a" != "b""#, diffs[0].to_string());
+/// ```
+#[must_use]
+pub fn diff(left: &Value, right: &Value) -> Vec<Difference> {
+    diff_with(left, right, DiffOptions::new())
+}
+
+/// Returns the differences between two values, like [`diff`], using the given options.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::diff::{diff_with, DiffOptions};
+/// use rmpv::Value;
+///
+/// let left = Value::Array(vec![Value::from(1), Value::F32(0.5)]);
+/// let right = Value::Array(vec![Value::F64(1.0), Value::F64(0.5)]);
+///
+/// assert_eq!(2, diff_with(&left, &right, DiffOptions::new()).len());
+/// assert!(diff_with(&left, &right, DiffOptions::new().numbers_by_value(true)).is_empty());
+/// ```
+#[must_use]
+pub fn diff_with(left: &Value, right: &Value, opts: DiffOptions) -> Vec<Difference> {
+    let mut diffs = Vec::new();
+    let mut path = Vec::new();
+    let mut steps = vec![Step::Compare(left, right)];
+    while let Some(step) = steps.pop() {
+        match step {
+            Step::Compare(left, right) => diff_step(left, right, &path, opts, &mut steps, &mut diffs),
+            Step::Enter(segment) => path.push(segment),
+            Step::Leave => {
+                path.pop();
+            }
+            Step::OneSided(segment, left, right) => push_one_sided(&mut diffs, &path, segment, left, right),
+        }
+    }
+    diffs
+}
+
+/// Work left to do by [`diff_with`].
+///
+/// These are kept on the heap rather than on the call stack, so that the stack usage doesn't
+/// depend on the nesting depth of the values.
+enum Step<'a> {
+    /// Compares two values at the current path.
+    Compare(&'a Value, &'a Value),
+    /// Descends into an array element or map entry.
+    Enter(PathSegment),
+    /// Returns from the innermost array element or map entry.
+    Leave,
+    /// Reports an array element or map entry found on one side only.
+    OneSided(PathSegment, Option<&'a Value>, Option<&'a Value>),
+}
+
+/// Compares two values, adding the steps to compare their elements, if any, to `steps`.
+fn diff_step<'a>(
+    left: &'a Value,
+    right: &'a Value,
+    path: &[PathSegment],
+    opts: DiffOptions,
+    steps: &mut Vec<Step<'a>>,
+    diffs: &mut Vec<Difference>,
+) {
+    // Steps are taken from the end, so the ones added here are reversed afterwards.
+    let start = steps.len();
+    let kind = match (left, right) {
+        (Value::Array(l), Value::Array(r)) => {
+            for (idx, (l, r)) in l.iter().zip(r).enumerate() {
+                steps.extend([Step::Enter(PathSegment::Index(idx)), Step::Compare(l, r), Step::Leave]);
+            }
+            for (idx, l) in l.iter().enumerate().skip(r.len()) {
+                steps.push(Step::OneSided(PathSegment::Index(idx), Some(l), None));
+            }
+            for (idx, r) in r.iter().enumerate().skip(l.len()) {
+                steps.push(Step::OneSided(PathSegment::Index(idx), None, Some(r)));
+            }
+            steps[start..].reverse();
+            return;
+        }
+        (Value::Map(l), Value::Map(r)) => {
+            // Entries with duplicate keys are paired up in order.
+            let mut matched = vec![false; r.len()];
+            for (key, l) in l {
+                match (0..r.len()).find(|&idx| !matched[idx] && r[idx].0 == *key) {
+                    Some(idx) => {
+                        matched[idx] = true;
+                        steps.extend([Step::Enter(PathSegment::Key(key.clone())), Step::Compare(l, &r[idx].1), Step::Leave]);
+                    }
+                    None => steps.push(Step::OneSided(PathSegment::Key(key.clone()), Some(l), None)),
+                }
+            }
+            for ((key, r), _) in r.iter().zip(matched).filter(|&(_, matched)| !matched) {
+                steps.push(Step::OneSided(PathSegment::Key(key.clone()), None, Some(r)));
+            }
+            steps[start..].reverse();
+            return;
+        }
+        _ if opts.numbers_by_value && is_number(left) && is_number(right) => {
+            if numbers_equal(left, right) {
+                return;
+            }
+            DifferenceKind::Value
+        }
+        _ if left == right => return,
+        _ if std::mem::discriminant(left) == std::mem::discriminant(right) => DifferenceKind::Value,
+        _ => DifferenceKind::Type,
+    };
+
+    diffs.push(Difference {
+        path: path.to_vec(),
+        kind,
+        left: Some(left.clone()),
+        right: Some(right.clone()),
+    });
+}
+
+fn push_one_sided(diffs: &mut Vec<Difference>, path: &[PathSegment], segment: PathSegment, left: Option<&Value>, right: Option<&Value>) {
+    let mut path = path.to_vec();
+    path.push(segment);
+    diffs.push(Difference {
+        path,
+        kind: if left.is_some() { DifferenceKind::Removed } else { DifferenceKind::Added },
+        left: left.cloned(),
+        right: right.cloned(),
+    });
+}
+
+#[inline]
+fn is_number(val: &Value) -> bool {
+    matches!(val, Value::Integer(..) | Value::F32(..) | Value::F64(..))
+}
+
+/// Compares two numbers by value, without rounding integers that don't fit into `f64` exactly.
+fn numbers_equal(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Integer(l), Value::Integer(r)) => l == r,
+        (&Value::Integer(int), &Value::F32(float)) | (&Value::F32(float), &Value::Integer(int)) => {
+            integer_equals_float(int, f64::from(float))
+        }
+        (&Value::Integer(int), &Value::F64(float)) | (&Value::F64(float), &Value::Integer(int)) => {
+            integer_equals_float(int, float)
+        }
+        _ => float_value(left) == float_value(right),
+    }
+}
+
+fn integer_equals_float(int: Integer, float: f64) -> bool {
+    // Whole floats in this range convert to `i128` exactly, and so do all integers.
+    if float.fract() != 0.0 || float.abs() >= 2f64.powi(64) {
+        return false;
+    }
+    let int = int.as_i64().map(i128::from).or_else(|| int.as_u64().map(i128::from));
+    int == Some(float as i128)
+}
+
+#[inline]
+fn float_value(val: &Value) -> Option<f64> {
+    match *val {
+        Value::F32(val) => Some(f64::from(val)),
+        Value::F64(val) => Some(val),
+        _ => None,
+    }
+}
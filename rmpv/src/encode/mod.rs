@@ -1,7 +1,11 @@
 pub use rmp::encode::ValueWriteError as Error;
 
 mod value;
+#[cfg(feature = "tokio")]
+mod value_async;
 mod value_ref;
 
-pub use self::value::write_value;
+pub use self::value::{write_value, write_value_canonical};
+#[cfg(feature = "tokio")]
+pub use self::value_async::write_value_async;
 pub use self::value_ref::write_value_ref;
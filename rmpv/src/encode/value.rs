@@ -2,7 +2,7 @@ use std::io::Write;
 
 use rmp::encode::{
     write_array_len, write_bin, write_bool, write_ext_meta, write_f32, write_f64, write_map_len,
-    write_nil, write_sint, write_str, write_uint,
+    write_nil, write_sint, write_str, write_str_len, write_timestamp, write_uint,
 };
 
 use super::Error;
@@ -42,7 +42,11 @@ pub fn write_value<W>(wr: &mut W, val: &Value) -> Result<(), Error>
         }
         Value::String(Utf8String { ref s }) => match *s {
             Ok(ref val) => write_str(wr, val)?,
-            Err(ref err) => write_bin(wr, &err.0)?,
+            // Invalid UTF-8 is kept with a str header, so that it reads back as a string.
+            Err(ref err) => {
+                write_str_len(wr, err.0.len() as u32)?;
+                wr.write_all(&err.0).map_err(Error::InvalidDataWrite)?;
+            }
         },
         Value::Binary(ref val) => {
             write_bin(wr, val)?;
@@ -64,6 +68,66 @@ pub fn write_value<W>(wr: &mut W, val: &Value) -> Result<(), Error>
             write_ext_meta(wr, data.len() as u32, ty)?;
             wr.write_all(data).map_err(Error::InvalidDataWrite)?;
         }
+        Value::Timestamp(ts) => {
+            write_timestamp(wr, ts)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Encodes and attempts to write the given Value in canonical form.
+///
+/// The output is the same as produced by [`write_value`], except that entries of every map
+/// (including nested ones) are written in ascending order of their encoded key bytes. As a result
+/// logically equal values are always encoded to byte-identical output, which makes it suitable
+/// for hashing or signing.
+///
+/// Entries with equal encoded keys keep their relative order.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::Value;
+/// use rmpv::encode::write_value_canonical;
+///
+/// let lhs = Value::Map(vec![(Value::from("b"), Value::from(2)), (Value::from("a"), Value::from(1))]);
+/// let rhs = Value::Map(vec![(Value::from("a"), Value::from(1)), (Value::from("b"), Value::from(2))]);
+///
+/// let mut lhs_buf = Vec::new();
+/// let mut rhs_buf = Vec::new();
+/// write_value_canonical(&mut lhs_buf, &lhs).unwrap();
+/// write_value_canonical(&mut rhs_buf, &rhs).unwrap();
+///
+/// assert_eq!(vec![0x82, 0xa1, 0x61, 0x01, 0xa1, 0x62, 0x02], lhs_buf);
+/// assert_eq!(lhs_buf, rhs_buf);
+/// ```
+pub fn write_value_canonical<W>(wr: &mut W, val: &Value) -> Result<(), Error>
+    where W: Write
+{
+    match *val {
+        Value::Array(ref vec) => {
+            write_array_len(wr, vec.len() as u32)?;
+            for v in vec {
+                write_value_canonical(wr, v)?;
+            }
+        }
+        Value::Map(ref map) => {
+            let mut entries = Vec::with_capacity(map.len());
+            for (key, val) in map {
+                let mut buf = Vec::new();
+                write_value_canonical(&mut buf, key)?;
+                entries.push((buf, val));
+            }
+            entries.sort_by(|(lhs, _), (rhs, _)| lhs.cmp(rhs));
+
+            write_map_len(wr, entries.len() as u32)?;
+            for (key, val) in entries {
+                wr.write_all(&key).map_err(Error::InvalidDataWrite)?;
+                write_value_canonical(wr, val)?;
+            }
+        }
+        ref val => write_value(wr, val)?,
     }
 
     Ok(())
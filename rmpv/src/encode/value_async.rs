@@ -0,0 +1,36 @@
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use super::{write_value, Error};
+use crate::Value;
+
+/// Asynchronously encodes and writes the given Value, without blocking the thread while the
+/// writer is not ready.
+///
+/// This is the async counterpart of [`write_value`], available with the `tokio` feature. The
+/// value is encoded into a buffer first and then written with a single `write_all` call.
+///
+/// # Errors
+///
+/// This function returns `Error::InvalidDataWrite` on any I/O error while writing the encoded
+/// value.
+///
+/// # Examples
+///
+/// ```
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() {
+/// use rmpv::Value;
+///
+/// let mut buf = Vec::new();
+/// rmpv::encode::write_value_async(&mut buf, &Value::from("hi")).await.unwrap();
+///
+/// assert_eq!(vec![0xa2, 0x68, 0x69], buf);
+/// # }
+/// ```
+pub async fn write_value_async<W>(wr: &mut W, val: &Value) -> Result<(), Error>
+    where W: AsyncWrite + Unpin
+{
+    let mut buf = Vec::new();
+    write_value(&mut buf, val)?;
+    wr.write_all(&buf).await.map_err(Error::InvalidDataWrite)
+}
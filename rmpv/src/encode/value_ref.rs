@@ -2,7 +2,7 @@ use std::io::Write;
 
 use rmp::encode::{
     write_array_len, write_bin, write_bool, write_ext_meta, write_f32, write_f64, write_map_len,
-    write_nil, write_sint, write_str, write_uint,
+    write_nil, write_sint, write_str, write_str_len, write_timestamp, write_uint,
 };
 
 use super::Error;
@@ -54,7 +54,11 @@ pub fn write_value_ref<W>(wr: &mut W, val: &ValueRef<'_>) -> Result<(), Error>
         }
         ValueRef::String(Utf8StringRef { s }) => match s {
             Ok(val) => write_str(wr, val)?,
-            Err(err) => write_bin(wr, err.0)?,
+            // Invalid UTF-8 is kept with a str header, so that it reads back as a string.
+            Err(err) => {
+                write_str_len(wr, err.0.len() as u32)?;
+                wr.write_all(err.0).map_err(Error::InvalidDataWrite)?;
+            }
         },
         ValueRef::Binary(val) => {
             write_bin(wr, val)?;
@@ -76,6 +80,9 @@ pub fn write_value_ref<W>(wr: &mut W, val: &ValueRef<'_>) -> Result<(), Error>
             write_ext_meta(wr, data.len() as u32, ty)?;
             wr.write_all(data).map_err(Error::InvalidDataWrite)?;
         }
+        ValueRef::Timestamp(ts) => {
+            write_timestamp(wr, ts)?;
+        }
     }
 
     Ok(())
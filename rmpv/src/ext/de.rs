@@ -1,6 +1,6 @@
 use std::borrow::Cow;
 use std::fmt::{self, Display, Formatter};
-use std::iter::ExactSizeIterator;
+use std::iter::{self, ExactSizeIterator};
 use std::slice::Iter;
 use std::vec::IntoIter;
 
@@ -8,7 +8,7 @@ use serde::de::{self, DeserializeSeed, IntoDeserializer, SeqAccess, Unexpected,
 use serde::forward_to_deserialize_any;
 use serde::{self, Deserialize, Deserializer};
 
-use crate::{IntPriv, Integer, Utf8String, Utf8StringRef, Value, ValueRef};
+use crate::{IntPriv, Integer, Timestamp, Utf8String, Utf8StringRef, Value, ValueRef};
 
 use super::{Error, ValueExt};
 use crate::MSGPACK_EXT_STRUCT_NAME;
@@ -20,6 +20,31 @@ pub fn from_value<T>(val: Value) -> Result<T, Error>
     deserialize_from(val)
 }
 
+/// Deserializes an instance of type `T` from a borrowed [`Value`].
+///
+/// Unlike [`from_value`], the tree is neither consumed nor cloned: strings, binaries and ext
+/// data may be borrowed by `T` for as long as the tree lives, e.g. into `&str` or `&[u8]`. A
+/// [`ValueRef`] can be deserialized the same way with `deserialize_from(&val)`.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::Value;
+///
+/// let val = Value::Array(vec![Value::from("le message"), Value::from(vec![0xcc; 1024])]);
+///
+/// let (name, data): (&str, &[u8]) = rmpv::ext::from_value_ref(&val).unwrap();
+/// assert_eq!("le message", name);
+/// // The data points into the tree.
+/// assert_eq!(val[1].as_slice().unwrap().as_ptr(), data.as_ptr());
+/// ```
+#[inline]
+pub fn from_value_ref<'de, T>(val: &'de Value) -> Result<T, Error>
+    where T: Deserialize<'de>
+{
+    deserialize_from(val)
+}
+
 #[inline]
 pub fn deserialize_from<'de, T, D>(val: D) -> Result<T, Error>
     where T: Deserialize<'de>,
@@ -176,6 +201,69 @@ impl<'de> Deserialize<'de> for Value {
     }
 }
 
+/// Name of the newtype struct under which `rmp_serde` hands over the encoded bytes of a value,
+/// as it does for `rmp_serde::RawValue`.
+const RAW_VALUE_STRUCT_NAME: &str = "_RawValue";
+
+/// A [`Value`] that `rmp_serde` deserializes without recursion.
+///
+/// Deserializing a `Value` takes a nested call per level of nesting, so a deeply nested document
+/// uses stack in proportion to its depth, whatever the depth limit. From `rmp_serde`, this
+/// wrapper instead gets the encoded value, which `rmp_serde` skips over iteratively, and decodes
+/// it with [`read_value_with_max_depth`](crate::decode::read_value_with_max_depth), which keeps
+/// partially read arrays and maps on the heap. Values can thus be nested up to `u16::MAX` levels
+/// deep, regardless of the depth limit of the deserializer, while the stack usage stays constant.
+///
+/// Other deserializers read it like a plain `Value`. It serializes like the value it holds. Note
+/// that dropping a deeply nested `Value` still recurses.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeepValue(pub Value);
+
+impl<'de> Deserialize<'de> for DeepValue {
+    #[inline]
+    fn deserialize<D>(de: D) -> Result<Self, D::Error>
+        where D: Deserializer<'de>
+    {
+        struct DeepValueVisitor;
+
+        impl<'de> Visitor<'de> for DeepValueVisitor {
+            type Value = DeepValue;
+
+            #[cold]
+            fn expecting(&self, fmt: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+                "any valid MessagePack value".fmt(fmt)
+            }
+
+            fn visit_bytes<E: de::Error>(self, mut buf: &[u8]) -> Result<DeepValue, E> {
+                crate::decode::read_value_with_max_depth(&mut buf, usize::MAX)
+                    .map(DeepValue)
+                    .map_err(E::custom)
+            }
+
+            #[inline]
+            fn visit_newtype_struct<D: Deserializer<'de>>(self, de: D) -> Result<DeepValue, D::Error> {
+                Value::deserialize(de).map(DeepValue)
+            }
+        }
+
+        de.deserialize_newtype_struct(RAW_VALUE_STRUCT_NAME, DeepValueVisitor)
+    }
+}
+
+impl From<Value> for DeepValue {
+    #[inline]
+    fn from(val: Value) -> Self {
+        DeepValue(val)
+    }
+}
+
+impl From<DeepValue> for Value {
+    #[inline]
+    fn from(val: DeepValue) -> Self {
+        val.0
+    }
+}
+
 impl<'de> Deserialize<'de> for ValueRef<'de> {
     #[inline]
     fn deserialize<D>(de: D) -> Result<Self, D::Error>
@@ -350,6 +438,7 @@ impl<'de> Deserializer<'de> for Value {
                 let de = ExtDeserializer::new_owned(tag, data);
                 visitor.visit_newtype_struct(de)
             }
+            Value::Timestamp(ts) => visitor.visit_newtype_struct(ExtDeserializer::new_timestamp(ts)),
         }
     }
 
@@ -377,6 +466,9 @@ impl<'de> Deserializer<'de> for Value {
                     let ext_de = ExtDeserializer::new_owned(tag, data);
                     return visitor.visit_newtype_struct(ext_de);
                 }
+                Value::Timestamp(ts) => {
+                    return visitor.visit_newtype_struct(ExtDeserializer::new_timestamp(ts));
+                }
                 other => return Err(de::Error::invalid_type(other.unexpected(), &"expected Ext")),
             }
         }
@@ -443,6 +535,7 @@ impl<'de> Deserializer<'de> for ValueRef<'de> {
                 let de = ExtDeserializer::new_ref(tag, data);
                 visitor.visit_newtype_struct(de)
             }
+            ValueRef::Timestamp(ts) => visitor.visit_newtype_struct(ExtDeserializer::new_timestamp(ts)),
         }
     }
 
@@ -470,6 +563,9 @@ impl<'de> Deserializer<'de> for ValueRef<'de> {
                     let ext_de = ExtDeserializer::new_ref(tag, data);
                     return visitor.visit_newtype_struct(ext_de);
                 }
+                ValueRef::Timestamp(ts) => {
+                    return visitor.visit_newtype_struct(ExtDeserializer::new_timestamp(ts));
+                }
                 other => return Err(de::Error::invalid_type(other.unexpected(), &"expected Ext")),
             }
         }
@@ -536,6 +632,7 @@ impl<'de> Deserializer<'de> for &'de ValueRef<'de> {
                 let de = ExtDeserializer::new_ref(tag, data);
                 visitor.visit_newtype_struct(de)
             }
+            ValueRef::Timestamp(ts) => visitor.visit_newtype_struct(ExtDeserializer::new_timestamp(ts)),
         }
     }
 
@@ -585,6 +682,9 @@ impl<'de> Deserializer<'de> for &'de ValueRef<'de> {
                     let ext_de = ExtDeserializer::new_ref(*tag, data);
                     return visitor.visit_newtype_struct(ext_de);
                 }
+                ValueRef::Timestamp(ts) => {
+                    return visitor.visit_newtype_struct(ExtDeserializer::new_timestamp(*ts));
+                }
                 other => return Err(de::Error::invalid_type(other.unexpected(), &"expected Ext")),
             }
         }
@@ -615,6 +715,107 @@ impl<'de> Deserializer<'de> for &'de ValueRef<'de> {
     }
 }
 
+impl<'de> Deserializer<'de> for &'de Value {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        match *self {
+            Value::Nil => visitor.visit_unit(),
+            Value::Boolean(v) => visitor.visit_bool(v),
+            Value::Integer(Integer { n }) => match n {
+                IntPriv::PosInt(v) => visitor.visit_u64(v),
+                IntPriv::NegInt(v) => visitor.visit_i64(v),
+            },
+            Value::F32(v) => visitor.visit_f32(v),
+            Value::F64(v) => visitor.visit_f64(v),
+            Value::String(ref v) => match v.s {
+                Ok(ref v) => visitor.visit_borrowed_str(v),
+                Err((ref v, _)) => visitor.visit_borrowed_bytes(v),
+            },
+            Value::Binary(ref v) => visitor.visit_borrowed_bytes(v),
+            Value::Array(ref v) => {
+                let len = v.len();
+                let mut de = SeqDeserializer::new(v.iter());
+                let seq = visitor.visit_seq(&mut de)?;
+                if de.iter.len() == 0 {
+                    Ok(seq)
+                } else {
+                    Err(de::Error::invalid_length(len, &"fewer elements in array"))
+                }
+            }
+            Value::Map(ref v) => {
+                let len = v.len();
+                let mut de = MapDeserializer::new(v.iter().map(split_ref as fn(_) -> _));
+                let map = visitor.visit_map(&mut de)?;
+                if de.iter.len() == 0 {
+                    Ok(map)
+                } else {
+                    Err(de::Error::invalid_length(len, &"fewer elements in map"))
+                }
+            }
+            Value::Ext(tag, ref data) => {
+                let de = ExtDeserializer::new_ref(tag, data);
+                visitor.visit_newtype_struct(de)
+            }
+            Value::Timestamp(ts) => visitor.visit_newtype_struct(ExtDeserializer::new_timestamp(ts)),
+        }
+    }
+
+    #[inline]
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        ValueBase::deserialize_option(self, visitor)
+    }
+
+    #[inline]
+    fn deserialize_enum<V>(self, _name: &str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        ValueBase::deserialize_enum(self, visitor)
+    }
+
+    #[inline]
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        if name == MSGPACK_EXT_STRUCT_NAME {
+            match *self {
+                Value::Ext(tag, ref data) => {
+                    let ext_de = ExtDeserializer::new_ref(tag, data);
+                    return visitor.visit_newtype_struct(ext_de);
+                }
+                Value::Timestamp(ts) => {
+                    return visitor.visit_newtype_struct(ExtDeserializer::new_timestamp(ts));
+                }
+                ref other => return Err(de::Error::invalid_type(other.unexpected(), &"expected Ext")),
+            }
+        }
+
+        visitor.visit_newtype_struct(self)
+    }
+
+    #[inline]
+    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value, Self::Error>
+        where V: Visitor<'de>
+    {
+        ValueBase::deserialize_unit_struct(self, visitor)
+    }
+
+    forward_to_deserialize_any! {
+        bool u8 u16 u32 u64 i8 i16 i32 i64 f32 f64 char str string unit seq
+        bytes byte_buf map tuple_struct struct
+        identifier tuple ignored_any
+    }
+}
+
+#[inline]
+fn split_ref((key, val): &(Value, Value)) -> (&Value, &Value) {
+    (key, val)
+}
+
 struct ExtDeserializer<'de> {
     tag: Option<i8>,
     data: Option<Cow<'de, [u8]>>,
@@ -634,6 +835,10 @@ impl<'de> ExtDeserializer<'de> {
             data: Some(Cow::Borrowed(data)),
         }
     }
+
+    fn new_timestamp(ts: Timestamp) -> Self {
+        Self::new_owned(Timestamp::EXT_TYPE, ts.ext_data(&mut [0; 12]).to_vec())
+    }
 }
 
 impl<'de> SeqAccess<'de> for ExtDeserializer<'de> {
@@ -1216,3 +1421,31 @@ impl<'de> ValueBase<'de> for ValueRef<'de> {
         }
     }
 }
+
+impl<'de> ValueBase<'de> for &'de Value {
+    type Item = &'de Value;
+    type Iter = Iter<'de, Value>;
+    type MapIter = iter::Map<Iter<'de, (Value, Value)>, fn(&'de (Value, Value)) -> (&'de Value, &'de Value)>;
+    type MapDeserializer = MapDeserializer<Self::MapIter, Self::Item>;
+
+    #[inline]
+    fn is_nil(&self) -> bool {
+        Value::is_nil(self)
+    }
+
+    #[inline]
+    fn into_iter(self) -> Result<Self::Iter, Self::Item> {
+        match *self {
+            Value::Array(ref v) => Ok(v.iter()),
+            ref other => Err(other),
+        }
+    }
+
+    #[inline]
+    fn into_map_iter(self) -> Result<Self::MapIter, Self::Item> {
+        match *self {
+            Value::Map(ref v) => Ok(v.iter().map(split_ref as fn(_) -> _)),
+            ref other => Err(other),
+        }
+    }
+}
@@ -5,7 +5,7 @@ use serde::de::Unexpected;
 
 use crate::{IntPriv, Integer, Value, ValueRef};
 
-pub use self::de::{deserialize_from, from_value, EnumRefDeserializer};
+pub use self::de::{deserialize_from, from_value, from_value_ref, DeepValue, EnumRefDeserializer};
 pub use self::se::to_value;
 
 mod de;
@@ -50,11 +50,18 @@ impl ValueExt for Value {
             Value::Binary(ref v) => Unexpected::Bytes(v),
             Value::Array(..) => Unexpected::Seq,
             Value::Map(..) => Unexpected::Map,
-            Value::Ext(..) => Unexpected::Seq,
+            Value::Ext(..) | Value::Timestamp(..) => Unexpected::Seq,
         }
     }
 }
 
+impl ValueExt for &Value {
+    #[inline]
+    fn unexpected(&self) -> Unexpected<'_> {
+        (**self).unexpected()
+    }
+}
+
 impl<'a> ValueExt for ValueRef<'a> {
     #[cold]
     fn unexpected(&self) -> Unexpected<'_> {
@@ -74,7 +81,7 @@ impl<'a> ValueExt for ValueRef<'a> {
             ValueRef::Binary(v) => Unexpected::Bytes(v),
             ValueRef::Array(..) => Unexpected::Seq,
             ValueRef::Map(..) => Unexpected::Map,
-            ValueRef::Ext(..) => Unexpected::Seq,
+            ValueRef::Ext(..) | ValueRef::Timestamp(..) => Unexpected::Seq,
         }
     }
 }
@@ -6,10 +6,10 @@ use serde::ser::{
 use serde::Serialize;
 use serde_bytes::Bytes;
 
-use crate::{IntPriv, Integer, Value};
+use crate::{IntPriv, Integer, Timestamp, Utf8String, Value, ValueRef};
 
-use super::Error;
-use crate::MSGPACK_EXT_STRUCT_NAME;
+use super::{DeepValue, Error};
+use crate::{MSGPACK_EXT_STRUCT_NAME, MSGPACK_STR_STRUCT_NAME};
 
 impl Serialize for Value {
     fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
@@ -26,7 +26,7 @@ impl Serialize for Value {
             Value::F64(v) => s.serialize_f64(v),
             Value::String(ref v) => match v.s {
                 Ok(ref v) => s.serialize_str(v),
-                Err(ref v) => Bytes::new(&v.0[..]).serialize(s),
+                Err(ref v) => s.serialize_newtype_struct(MSGPACK_STR_STRUCT_NAME, Bytes::new(&v.0[..])),
             },
             Value::Binary(ref v) => Bytes::new(&v[..]).serialize(s),
             Value::Array(ref array) => {
@@ -47,6 +47,65 @@ impl Serialize for Value {
                 let value = (ty, Bytes::new(&buf[..]));
                 s.serialize_newtype_struct(MSGPACK_EXT_STRUCT_NAME, &value)
             }
+            Value::Timestamp(ts) => {
+                let mut buf = [0; 12];
+                let value = (Timestamp::EXT_TYPE, Bytes::new(ts.ext_data(&mut buf)));
+                s.serialize_newtype_struct(MSGPACK_EXT_STRUCT_NAME, &value)
+            }
+        }
+    }
+}
+
+impl Serialize for DeepValue {
+    #[inline]
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+        where S: ser::Serializer
+    {
+        self.0.serialize(s)
+    }
+}
+
+impl Serialize for ValueRef<'_> {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+        where S: ser::Serializer
+    {
+        match *self {
+            ValueRef::Nil => s.serialize_unit(),
+            ValueRef::Boolean(v) => s.serialize_bool(v),
+            ValueRef::Integer(Integer { n }) => match n {
+                IntPriv::PosInt(n) => s.serialize_u64(n),
+                IntPriv::NegInt(n) => s.serialize_i64(n),
+            },
+            ValueRef::F32(v) => s.serialize_f32(v),
+            ValueRef::F64(v) => s.serialize_f64(v),
+            ValueRef::String(ref v) => match v.s {
+                Ok(v) => s.serialize_str(v),
+                Err(ref v) => s.serialize_newtype_struct(MSGPACK_STR_STRUCT_NAME, Bytes::new(v.0)),
+            },
+            ValueRef::Binary(v) => Bytes::new(v).serialize(s),
+            ValueRef::Array(ref array) => {
+                let mut state = s.serialize_seq(Some(array.len()))?;
+                for item in array {
+                    state.serialize_element(item)?;
+                }
+                state.end()
+            }
+            ValueRef::Map(ref map) => {
+                let mut state = s.serialize_map(Some(map.len()))?;
+                for (key, val) in map {
+                    state.serialize_entry(key, val)?;
+                }
+                state.end()
+            }
+            ValueRef::Ext(ty, buf) => {
+                let value = (ty, Bytes::new(buf));
+                s.serialize_newtype_struct(MSGPACK_EXT_STRUCT_NAME, &value)
+            }
+            ValueRef::Timestamp(ts) => {
+                let mut buf = [0; 12];
+                let value = (Timestamp::EXT_TYPE, Bytes::new(ts.ext_data(&mut buf)));
+                s.serialize_newtype_struct(MSGPACK_EXT_STRUCT_NAME, &value)
+            }
         }
     }
 }
@@ -189,6 +248,12 @@ impl ser::Serializer for Serializer {
 
             return ext_se.value();
         }
+        if name == MSGPACK_STR_STRUCT_NAME {
+            if let Value::Binary(buf) = to_value(value)? {
+                return Ok(Value::String(Utf8String::from_bytes(buf)));
+            }
+            return Err(<Error as ser::Error>::custom("expected bytes"));
+        }
 
         to_value(value)
     }
@@ -0,0 +1,231 @@
+//! Conversions between [`Value`] and [`serde_json::Value`].
+//!
+//! Every JSON value has a MessagePack counterpart, so converting from JSON never fails. The
+//! opposite direction has to decide what to do with binaries, non-string map keys and integers
+//! above `i64::MAX`, which JSON either lacks or many JSON consumers can't handle. Use
+//! [`to_json`] with explicit [`JsonOptions`] to choose, or `TryFrom` for the defaults.
+//!
+//! This module is available with the `json` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use rmpv::Value;
+//! use rmpv::json::{to_json, BinaryPolicy, JsonOptions, KeyPolicy};
+//!
+//! let val = Value::Map(vec![
+//!     (Value::from(1), Value::Binary(vec![0xde, 0xad, 0xbe, 0xef])),
+//! ]);
+//!
+//! // Non-string keys are rejected by default.
+//! assert!(serde_json::Value::try_from(val.clone()).is_err());
+//!
+//! let opts = JsonOptions {
+//!     binary: BinaryPolicy::Base64,
+//!     keys: KeyPolicy::Stringify,
+//!     ..JsonOptions::default()
+//! };
+//! assert_eq!(serde_json::json!({"1": "3q2+7w=="}), to_json(&val, opts).unwrap());
+//! ```
+
+use std::convert::TryFrom;
+use std::error;
+use std::fmt::{self, Display, Formatter};
+
+use serde_json::{Map, Number};
+
+use crate::{Timestamp, Utf8String, Value};
+
+/// How binaries are converted to JSON.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum BinaryPolicy {
+    /// Convert to an array of numbers.
+    #[default]
+    Array,
+    /// Convert to a string with the standard, padded base64 encoding.
+    Base64,
+    /// Fail with [`JsonError::Binary`].
+    Error,
+}
+
+/// How map keys other than strings are converted to JSON.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum KeyPolicy {
+    /// Fail with [`JsonError::Key`].
+    #[default]
+    Error,
+    /// Use the [`Display`] representation of the key, e.g. `1` or `nil`.
+    Stringify,
+}
+
+/// How integers above `i64::MAX` are converted to JSON.
+///
+/// JSON itself has no limits, but many consumers parse numbers as `i64` or even as `f64`.
+#[non_exhaustive]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum LargeUintPolicy {
+    /// Convert to a number.
+    #[default]
+    Number,
+    /// Convert to a string with the decimal representation.
+    String,
+    /// Fail with [`JsonError::LargeUint`].
+    Error,
+}
+
+/// Options for [`to_json`].
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct JsonOptions {
+    /// How binaries are converted.
+    pub binary: BinaryPolicy,
+    /// How non-string map keys are converted.
+    pub keys: KeyPolicy,
+    /// How integers above `i64::MAX` are converted.
+    pub large_uint: LargeUintPolicy,
+}
+
+/// An error converting a [`Value`] to JSON.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum JsonError {
+    /// A binary was found with [`BinaryPolicy::Error`].
+    Binary,
+    /// The enclosed non-string map key was found with [`KeyPolicy::Error`].
+    Key(Value),
+    /// The enclosed integer was found with [`LargeUintPolicy::Error`].
+    LargeUint(u64),
+    /// A NaN or infinite float was found, which JSON can't represent.
+    NonFiniteFloat,
+    /// A string with invalid UTF-8 was found.
+    InvalidUtf8,
+    /// An extension with the enclosed type was found, which JSON can't represent.
+    Ext(i8),
+}
+
+impl Display for JsonError {
+    #[cold]
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), fmt::Error> {
+        match *self {
+            JsonError::Binary => fmt.write_str("binary can't be converted to JSON"),
+            JsonError::Key(ref key) => write!(fmt, "map key {key} can't be converted to JSON"),
+            JsonError::LargeUint(val) => write!(fmt, "integer {val} can't be converted to JSON"),
+            JsonError::NonFiniteFloat => fmt.write_str("NaN or infinite float can't be converted to JSON"),
+            JsonError::InvalidUtf8 => fmt.write_str("string with invalid UTF-8 can't be converted to JSON"),
+            JsonError::Ext(ty) => write!(fmt, "ext of type {ty} can't be converted to JSON"),
+        }
+    }
+}
+
+impl error::Error for JsonError {}
+
+/// Converts a [`Value`] to JSON using the given options.
+///
+/// # Errors
+///
+/// Returns [`JsonError`] if the value contains something JSON can't represent under the given
+/// options: NaN or infinite floats, strings with invalid UTF-8, extensions, and depending on the
+/// options binaries, non-string keys and integers above `i64::MAX`.
+pub fn to_json(val: &Value, opts: JsonOptions) -> Result<serde_json::Value, JsonError> {
+    let json = match *val {
+        Value::Nil => serde_json::Value::Null,
+        Value::Boolean(val) => serde_json::Value::Bool(val),
+        Value::Integer(val) => {
+            if let Some(val) = val.as_i64() {
+                serde_json::Value::from(val)
+            } else {
+                // Doesn't fit `i64`, so it's a large positive integer.
+                let val = val.as_u64().unwrap_or_default();
+                match opts.large_uint {
+                    LargeUintPolicy::Number => serde_json::Value::from(val),
+                    LargeUintPolicy::String => serde_json::Value::String(val.to_string()),
+                    LargeUintPolicy::Error => return Err(JsonError::LargeUint(val)),
+                }
+            }
+        }
+        Value::F32(val) => float_to_json(f64::from(val))?,
+        Value::F64(val) => float_to_json(val)?,
+        Value::String(ref val) => serde_json::Value::String(str_to_json(val)?),
+        Value::Binary(ref val) => match opts.binary {
+            BinaryPolicy::Array => serde_json::Value::Array(val.iter().map(|&b| serde_json::Value::from(b)).collect()),
+            BinaryPolicy::Base64 => serde_json::Value::String(rmp::base64::encode(val)),
+            BinaryPolicy::Error => return Err(JsonError::Binary),
+        },
+        Value::Array(ref vec) => {
+            serde_json::Value::Array(vec.iter().map(|val| to_json(val, opts)).collect::<Result<_, _>>()?)
+        }
+        Value::Map(ref map) => {
+            let mut obj = Map::new();
+            for (key, val) in map {
+                let key = match *key {
+                    Value::String(ref key) => str_to_json(key)?,
+                    ref key => match opts.keys {
+                        KeyPolicy::Stringify => key.to_string(),
+                        KeyPolicy::Error => return Err(JsonError::Key(key.clone())),
+                    },
+                };
+                obj.insert(key, to_json(val, opts)?);
+            }
+            serde_json::Value::Object(obj)
+        }
+        Value::Ext(ty, _) => return Err(JsonError::Ext(ty)),
+        Value::Timestamp(..) => return Err(JsonError::Ext(Timestamp::EXT_TYPE)),
+    };
+
+    Ok(json)
+}
+
+/// Converts a JSON value to a [`Value`].
+///
+/// Numbers become integers if they are integral and fit `i64` or `u64`, and `F64` otherwise.
+/// Objects become maps with string keys, in the iteration order of the object.
+#[must_use]
+pub fn from_json(json: &serde_json::Value) -> Value {
+    match *json {
+        serde_json::Value::Null => Value::Nil,
+        serde_json::Value::Bool(val) => Value::Boolean(val),
+        serde_json::Value::Number(ref val) => number_from_json(val),
+        serde_json::Value::String(ref val) => Value::from(val.as_str()),
+        serde_json::Value::Array(ref vec) => Value::Array(vec.iter().map(from_json).collect()),
+        serde_json::Value::Object(ref obj) => {
+            Value::Map(obj.iter().map(|(key, val)| (Value::from(key.as_str()), from_json(val))).collect())
+        }
+    }
+}
+
+fn number_from_json(val: &Number) -> Value {
+    if let Some(val) = val.as_u64() {
+        Value::from(val)
+    } else if let Some(val) = val.as_i64() {
+        Value::from(val)
+    } else {
+        Value::F64(val.as_f64().unwrap_or(f64::NAN))
+    }
+}
+
+fn float_to_json(val: f64) -> Result<serde_json::Value, JsonError> {
+    Number::from_f64(val).map(serde_json::Value::Number).ok_or(JsonError::NonFiniteFloat)
+}
+
+fn str_to_json(val: &Utf8String) -> Result<String, JsonError> {
+    val.as_str().map(str::to_owned).ok_or(JsonError::InvalidUtf8)
+}
+
+impl From<serde_json::Value> for Value {
+    /// Converts a JSON value, see [`from_json`].
+    #[inline]
+    fn from(json: serde_json::Value) -> Self {
+        from_json(&json)
+    }
+}
+
+impl TryFrom<Value> for serde_json::Value {
+    type Error = JsonError;
+
+    /// Converts a value to JSON with the default [`JsonOptions`], see [`to_json`].
+    #[inline]
+    fn try_from(val: Value) -> Result<Self, Self::Error> {
+        to_json(&val, JsonOptions::default())
+    }
+}
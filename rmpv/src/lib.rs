@@ -2,21 +2,34 @@
 #![forbid(unsafe_code)]
 
 use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
 use std::convert::TryFrom;
 use std::fmt::{self, Debug, Display};
+use std::hash::{Hash, Hasher};
 use std::iter::FromIterator;
-use std::ops::Index;
+use std::ops::{Index, IndexMut};
 use std::str::Utf8Error;
 
 use num_traits::NumCast;
 
+pub use rmp::Timestamp;
+
+pub use crate::diff::diff;
+
+mod macros;
+
 pub mod decode;
+pub mod diff;
 pub mod encode;
 
 #[cfg(feature = "with-serde")]
 pub mod ext;
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg(feature = "json")]
+pub mod json;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 enum IntPriv {
     /// Always non-less than zero.
     PosInt(u64),
@@ -39,10 +52,35 @@ enum IntPriv {
 /// ```
 pub const MSGPACK_EXT_STRUCT_NAME: &str = "_ExtStruct";
 
+/// Name of Serde newtype struct to represent MessagePack strings that are not valid UTF-8.
+///
+/// Serde data model: `_StrStruct(binary)`
+///
+/// `Value::String` and `ValueRef::String` holding invalid UTF-8 serialize their bytes wrapped
+/// into this newtype, so that `rmp_serde` writes them with a str header, same as
+/// [`encode::write_value`]. Other serializers see plain bytes.
+pub const MSGPACK_STR_STRUCT_NAME: &str = "_StrStruct";
+
 /// Represents a MessagePack integer, whether signed or unsigned.
 ///
 /// A `Value` or `ValueRef` that contains integer can be constructed using `From` trait.
-#[derive(Copy, Clone, PartialEq)]
+///
+/// Integers convert to every primitive integer type they fit with `TryFrom`, and compare with
+/// them by numeric value.
+///
+/// # Examples
+///
+/// ```
+/// use std::convert::TryFrom;
+///
+/// use rmpv::Integer;
+///
+/// let n = Integer::from(-300);
+/// assert_eq!(Ok(-300), i32::try_from(n));
+/// assert_eq!(Err(n), u16::try_from(n));
+/// assert!(n == -300i64 && n < 0u8);
+/// ```
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
 pub struct Integer {
     n: IntPriv,
 }
@@ -88,6 +126,15 @@ impl Integer {
         }
     }
 
+    /// Returns the integer represented as any primitive integer type if it fits, or else `None`.
+    #[inline]
+    fn as_num<T: NumCast>(&self) -> Option<T> {
+        match self.n {
+            IntPriv::PosInt(n) => NumCast::from(n),
+            IntPriv::NegInt(n) => NumCast::from(n),
+        }
+    }
+
     /// Returns the integer represented as `f64` if possible, or else `None`.
     #[inline]
     #[must_use]
@@ -99,6 +146,25 @@ impl Integer {
     }
 }
 
+/// Integers are ordered by their numeric value.
+impl Ord for Integer {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.n, other.n) {
+            (IntPriv::PosInt(a), IntPriv::PosInt(b)) => a.cmp(&b),
+            (IntPriv::NegInt(a), IntPriv::NegInt(b)) => a.cmp(&b),
+            (IntPriv::NegInt(..), IntPriv::PosInt(..)) => Ordering::Less,
+            (IntPriv::PosInt(..), IntPriv::NegInt(..)) => Ordering::Greater,
+        }
+    }
+}
+
+impl PartialOrd for Integer {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl Debug for Integer {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         Debug::fmt(&self.n, fmt)
@@ -204,6 +270,51 @@ impl From<isize> for Integer {
     }
 }
 
+impl From<Integer> for i128 {
+    /// Converts the integer losslessly, as every MessagePack integer fits.
+    #[inline]
+    fn from(n: Integer) -> Self {
+        match n.n {
+            IntPriv::PosInt(n) => n.into(),
+            IntPriv::NegInt(n) => n.into(),
+        }
+    }
+}
+
+macro_rules! impl_integer_conversions {
+    ($($t: ty),*) => {
+        $(
+            impl TryFrom<Integer> for $t {
+                type Error = Integer;
+
+                /// Converts the integer if it fits, or else returns it back.
+                #[inline]
+                fn try_from(n: Integer) -> Result<$t, Self::Error> {
+                    n.as_num().ok_or(n)
+                }
+            }
+
+            impl PartialEq<$t> for Integer {
+                #[inline]
+                fn eq(&self, other: &$t) -> bool {
+                    *self == Integer::from(*other)
+                }
+            }
+
+            impl PartialOrd<$t> for Integer {
+                #[inline]
+                fn partial_cmp(&self, other: &$t) -> Option<Ordering> {
+                    Some(self.cmp(&Integer::from(*other)))
+                }
+            }
+        )*
+    };
+}
+
+// Comparisons only go one way, as impls on the primitive types would break type inference of
+// comparisons between them in dependent crates, e.g. `42u64 == Deserialize::deserialize(de)?`.
+impl_integer_conversions!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
 /// Represents an UTF-8 MessagePack string type.
 ///
 /// According to the MessagePack spec, string objects may contain invalid byte sequence and the
@@ -216,7 +327,7 @@ impl From<isize> for Integer {
 /// it is possible to obtain an underlying bytes that were attempted to convert to a `String`. This
 /// may happen when trying to unpack strings that were decoded using older MessagePack spec with
 /// raw types instead of string/binary.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Utf8String {
     s: Result<String, (Vec<u8>, Utf8Error)>,
 }
@@ -292,6 +403,40 @@ impl Utf8String {
             Err((ref buf, err)) => Utf8StringRef { s: Err((&buf[..], err)) },
         }
     }
+
+    /// Converts bytes decoded from a MessagePack string, keeping them if they are not valid
+    /// UTF-8.
+    pub(crate) fn from_bytes(buf: Vec<u8>) -> Self {
+        match String::from_utf8(buf) {
+            Ok(s) => Utf8String { s: Ok(s) },
+            Err(err) => {
+                let e = err.utf8_error();
+                Utf8String { s: Err((err.into_bytes(), e)) }
+            }
+        }
+    }
+}
+
+/// Strings are ordered by their bytes, whether they are valid UTF-8 or not.
+impl Ord for Utf8String {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_bytes().cmp(other.as_bytes())
+    }
+}
+
+impl PartialOrd for Utf8String {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Hash for Utf8String {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_bytes().hash(state);
+    }
 }
 
 impl Display for Utf8String {
@@ -431,7 +576,44 @@ impl<'a> From<Utf8StringRef<'a>> for Utf8String {
 }
 
 /// Represents any valid MessagePack value.
-#[derive(Clone, Debug, PartialEq)]
+///
+/// # Ordering
+///
+/// Values implement `Eq`, `Ord` and `Hash`, so they can be used as keys of `BTreeMap` and
+/// `HashMap` and be sorted or deduplicated deterministically. The order is total and stable
+/// across versions:
+///
+/// - Values of different variants are ordered by variant, in the order they are declared:
+///   `Nil < Boolean < Integer < F32 < F64 < String < Binary < Array < Map < Ext < Timestamp`.
+///   Values are never equal across variants, e.g. `Integer(1)`, `F32(1.0)` and `F64(1.0)` all
+///   differ.
+/// - Integers are ordered by their numeric value, strings and binaries by their bytes.
+/// - Floats are ordered by [`f64::total_cmp`], which follows their bit patterns: `-NaN` comes
+///   first and `NaN` last, and `-0.0 < 0.0`. Accordingly, floats are equal if and only if their
+///   bits are, so a `NaN` is equal to itself and `-0.0` isn't equal to `0.0`. This
+///   differs from earlier versions, which compared floats like `f64` does; compare the results
+///   of [`as_f64`](Value::as_f64) to get that behavior.
+/// - Arrays and maps are compared element by element, maps by their entries in encoded order,
+///   like the tuples in their `Vec`s.
+/// - Ext values are ordered by type and then by data, timestamps chronologically.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::BTreeSet;
+/// use rmpv::Value;
+///
+/// let set: BTreeSet<_> = [Value::from(f64::NAN), Value::from("a"), Value::from(-1), Value::Nil, Value::from(f64::NAN)]
+///     .into_iter()
+///     .collect();
+///
+/// let sorted: Vec<_> = set.into_iter().collect();
+/// assert_eq!(4, sorted.len());
+/// assert_eq!(Value::Nil, sorted[0]);
+/// assert_eq!(Value::from(-1), sorted[1]);
+/// assert!(sorted[2].as_f64().unwrap().is_nan());
+/// ```
+#[derive(Clone, Debug)]
 pub enum Value {
     /// Nil represents nil.
     Nil,
@@ -467,13 +649,42 @@ pub enum Value {
     /// Array represents a sequence of objects.
     Array(Vec<Value>),
     /// Map represents key-value pairs of objects.
+    ///
+    /// Entries are kept in the order they were decoded or inserted, and are encoded in that
+    /// order, also when going through serde. Keys aren't deduplicated on decode. Converting to a
+    /// `HashMap` or `BTreeMap` loses the order; with the `indexmap` feature, maps can be converted
+    /// to and from an `IndexMap` instead. JSON objects keep their order when converted to and from
+    /// `serde_json::Value` with the `json-preserve-order` feature.
     Map(Vec<(Value, Value)>),
     /// Extended implements Extension interface: represents a tuple of type information and a byte
     /// array where type information is an integer whose meaning is defined by applications.
     Ext(i8, Vec<u8>),
+    /// Timestamp represents the predefined timestamp extension type `-1`.
+    ///
+    /// It is written as an ext value of that type. Decoders produce it instead of `Ext` only
+    /// when asked to, e.g. by [`decode::read_value_with_timestamps`].
+    Timestamp(Timestamp),
 }
 
 impl Value {
+    /// Returns the position of the variant in the order of values.
+    #[inline]
+    fn rank(&self) -> u8 {
+        match *self {
+            Value::Nil => 0,
+            Value::Boolean(..) => 1,
+            Value::Integer(..) => 2,
+            Value::F32(..) => 3,
+            Value::F64(..) => 4,
+            Value::String(..) => 5,
+            Value::Binary(..) => 6,
+            Value::Array(..) => 7,
+            Value::Map(..) => 8,
+            Value::Ext(..) => 9,
+            Value::Timestamp(..) => 10,
+        }
+    }
+
     /// Converts the current owned Value to a `ValueRef`.
     ///
     /// # Panics
@@ -519,6 +730,7 @@ impl Value {
                 ValueRef::Map(val.iter().map(|(k, v)| (k.as_ref(), v.as_ref())).collect())
             }
             Value::Ext(ty, ref buf) => ValueRef::Ext(ty, buf.as_slice()),
+            Value::Timestamp(ts) => ValueRef::Timestamp(ts),
         }
     }
 
@@ -920,11 +1132,490 @@ impl Value {
             None
         }
     }
+
+    /// If the `Value` is a timestamp, or an ext value of the timestamp type holding valid
+    /// timestamp data, returns the timestamp. Returns None otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::{Timestamp, Value};
+    ///
+    /// let ts = Timestamp::new(2, 0).unwrap();
+    ///
+    /// assert_eq!(Some(ts), Value::Timestamp(ts).as_timestamp());
+    /// assert_eq!(Some(ts), Value::Ext(-1, vec![0, 0, 0, 2]).as_timestamp());
+    /// assert_eq!(None, Value::Ext(42, vec![0, 0, 0, 2]).as_timestamp());
+    /// ```
+    #[must_use]
+    pub fn as_timestamp(&self) -> Option<Timestamp> {
+        match *self {
+            Value::Timestamp(ts) => Some(ts),
+            Value::Ext(Timestamp::EXT_TYPE, ref buf) => Timestamp::from_ext_data(buf),
+            _ => None,
+        }
+    }
+
+    /// Converts ext values of the timestamp type holding valid timestamp data, here and in every
+    /// nested value, into [`Value::Timestamp`].
+    ///
+    /// This is what [`decode::read_value_with_timestamps`] does while reading.
+    pub fn convert_timestamps(&mut self) {
+        self.walk_mut(|val| {
+            if let Some(ts) = val.as_timestamp() {
+                *val = Value::Timestamp(ts);
+            }
+        });
+    }
+
+    /// If the `Value` is an Array, returns a mutable reference to the associated vector.
+    /// Returns None otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let mut val = Value::Array(vec![Value::Nil]);
+    /// val.as_array_mut().unwrap().push(Value::from(42));
+    ///
+    /// assert_eq!(Value::Array(vec![Value::Nil, Value::from(42)]), val);
+    /// ```
+    #[inline]
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<Value>> {
+        if let Value::Array(ref mut array) = *self {
+            Some(array)
+        } else {
+            None
+        }
+    }
+
+    /// If the `Value` is a Map, returns a mutable reference to the associated vector of key-value
+    /// tuples. Returns None otherwise.
+    #[inline]
+    pub fn as_map_mut(&mut self) -> Option<&mut Vec<(Value, Value)>> {
+        if let Value::Map(ref mut map) = *self {
+            Some(map)
+        } else {
+            None
+        }
+    }
+
+    /// If the `Value` is a Map, returns the value of the first entry with the given key.
+    /// Returns None otherwise.
+    ///
+    /// Lookups scan the entries, so they take linear time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Map(vec![(Value::from(1), Value::from("one"))]);
+    ///
+    /// assert_eq!(Some(&Value::from("one")), val.get(&Value::from(1)));
+    /// assert_eq!(None, val.get(&Value::from(2)));
+    /// ```
+    #[must_use]
+    pub fn get(&self, key: &Value) -> Option<&Value> {
+        self.as_map()?.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    /// If the `Value` is a Map, returns a mutable reference to the value of the first entry with
+    /// the given key. Returns None otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let mut val = Value::Map(vec![(Value::from("a"), Value::from(1))]);
+    /// *val.get_mut(&Value::from("a")).unwrap() = Value::from(2);
+    ///
+    /// assert_eq!(Value::from(2), val["a"]);
+    /// ```
+    pub fn get_mut(&mut self, key: &Value) -> Option<&mut Value> {
+        self.as_map_mut()?.iter_mut().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    /// Inserts an entry into a Map, replacing the value of the first entry with an equal key.
+    ///
+    /// Returns the replaced value, or None if the key wasn't present, in which case the entry is
+    /// appended, keeping the order of existing entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `Value` is not a Map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let mut val = Value::Map(Vec::new());
+    ///
+    /// assert_eq!(None, val.insert(Value::from("a"), Value::from(1)));
+    /// assert_eq!(Some(Value::from(1)), val.insert(Value::from("a"), Value::from(2)));
+    /// assert_eq!(Value::Map(vec![(Value::from("a"), Value::from(2))]), val);
+    /// ```
+    pub fn insert(&mut self, key: Value, value: Value) -> Option<Value> {
+        match self.get_mut(&key) {
+            Some(old) => Some(std::mem::replace(old, value)),
+            None => {
+                self.map_entries_mut().push((key, value));
+                None
+            }
+        }
+    }
+
+    /// If the `Value` is a Map, removes the first entry with the given key and returns its value.
+    /// Returns None otherwise.
+    ///
+    /// The order of the remaining entries is preserved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let mut val = Value::Map(vec![(Value::from("a"), Value::from(1))]);
+    ///
+    /// assert_eq!(Some(Value::from(1)), val.remove(&Value::from("a")));
+    /// assert_eq!(None, val.remove(&Value::from("a")));
+    /// ```
+    pub fn remove(&mut self, key: &Value) -> Option<Value> {
+        let map = self.as_map_mut()?;
+        let idx = map.iter().position(|(k, _)| k == key)?;
+        Some(map.remove(idx).1)
+    }
+
+    /// Returns a mutable reference to the value of the first Map entry with the given key,
+    /// appending a new entry with `Value::Nil` if the key isn't present.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `Value` is not a Map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let mut val = Value::Map(Vec::new());
+    /// *val.entry(Value::from("hits")) = Value::from(1);
+    ///
+    /// let hits = val.entry(Value::from("hits"));
+    /// *hits = Value::from(hits.as_u64().unwrap() + 1);
+    ///
+    /// assert_eq!(Value::from(2), val["hits"]);
+    /// ```
+    pub fn entry(&mut self, key: Value) -> &mut Value {
+        let map = self.map_entries_mut();
+        let idx = match map.iter().position(|(k, _)| *k == key) {
+            Some(idx) => idx,
+            None => {
+                map.push((key, Value::Nil));
+                map.len() - 1
+            }
+        };
+        &mut map[idx].1
+    }
+
+    /// Calls the given function for this value and, depth-first, for every value nested in it,
+    /// including map keys.
+    ///
+    /// Containers are visited before their elements, and map keys before their values. This
+    /// doesn't recurse, so it's safe to call on arbitrarily nested values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Map(vec![(Value::from("a"), Value::Array(vec![Value::from(1)]))]);
+    ///
+    /// let mut count = 0;
+    /// val.walk(|_| count += 1);
+    ///
+    /// assert_eq!(4, count);
+    /// ```
+    pub fn walk<F: FnMut(&Value)>(&self, mut f: F) {
+        self.walk_inner(true, &mut f);
+    }
+
+    /// Like [`walk`](Self::walk), but skips map keys.
+    pub fn walk_values<F: FnMut(&Value)>(&self, mut f: F) {
+        self.walk_inner(false, &mut f);
+    }
+
+    /// Calls the given function for this value and, depth-first, for every value nested in it,
+    /// including map keys, allowing to modify them in place.
+    ///
+    /// Containers are visited before their elements, so if the function replaces a container,
+    /// the elements of the new value are visited.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let mut val = Value::Map(vec![
+    ///     (Value::from("password"), Value::from("hunter2")),
+    ///     (Value::from("tags"), Value::Array(vec![Value::from("secret")])),
+    /// ]);
+    ///
+    /// val.walk_values_mut(|v| {
+    ///     if v.is_str() {
+    ///         *v = Value::from("<redacted>");
+    ///     }
+    /// });
+    ///
+    /// assert_eq!(Value::from("<redacted>"), val["password"]);
+    /// assert_eq!(Value::from("<redacted>"), val["tags"][0]);
+    /// ```
+    pub fn walk_mut<F: FnMut(&mut Value)>(&mut self, mut f: F) {
+        self.walk_mut_inner(true, &mut f);
+    }
+
+    /// Like [`walk_mut`](Self::walk_mut), but skips map keys.
+    pub fn walk_values_mut<F: FnMut(&mut Value)>(&mut self, mut f: F) {
+        self.walk_mut_inner(false, &mut f);
+    }
+
+    fn walk_inner<F: FnMut(&Value)>(&self, keys: bool, f: &mut F) {
+        // Children are pushed in reverse, so that they are popped in order.
+        let mut stack = vec![self];
+        while let Some(val) = stack.pop() {
+            f(val);
+            match *val {
+                Value::Array(ref vec) => stack.extend(vec.iter().rev()),
+                Value::Map(ref map) => {
+                    for (key, val) in map.iter().rev() {
+                        stack.push(val);
+                        if keys {
+                            stack.push(key);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn walk_mut_inner<F: FnMut(&mut Value)>(&mut self, keys: bool, f: &mut F) {
+        let mut stack = vec![self];
+        while let Some(val) = stack.pop() {
+            f(val);
+            match val {
+                Value::Array(vec) => stack.extend(vec.iter_mut().rev()),
+                Value::Map(map) => {
+                    for (key, val) in map.iter_mut().rev() {
+                        stack.push(val);
+                        if keys {
+                            stack.push(key);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn map_entries_mut(&mut self) -> &mut Vec<(Value, Value)> {
+        match *self {
+            Value::Map(ref mut map) => map,
+            ref other => panic!("expected a map, found {other:?}"),
+        }
+    }
+
+    /// Returns the number of bytes `encode::write_value` would write for this value, without
+    /// encoding it.
+    ///
+    /// This is the same as [`encoded_size`](Self::encoded_size).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Array(vec![Value::from(42), Value::from("le message")]);
+    ///
+    /// let mut buf = Vec::new();
+    /// rmpv::encode::write_value(&mut buf, &val).unwrap();
+    ///
+    /// assert_eq!(buf.len(), val.msgpack_len());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn msgpack_len(&self) -> usize {
+        self.encoded_size()
+    }
+
+    /// Returns the number of bytes `encode::write_value` would write for this value, without
+    /// encoding it.
+    ///
+    /// Like [`depth`](Self::depth) and [`count_nodes`](Self::count_nodes), this doesn't recurse,
+    /// so it's safe to call on arbitrarily nested values, e.g. to enforce quotas on untrusted
+    /// documents before processing them further.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Map(vec![(Value::from("id"), Value::from(300))]);
+    ///
+    /// let mut buf = Vec::new();
+    /// rmpv::encode::write_value(&mut buf, &val).unwrap();
+    ///
+    /// assert_eq!(buf.len(), val.encoded_size());
+    /// ```
+    #[must_use]
+    pub fn encoded_size(&self) -> usize {
+        let mut size = 0usize;
+        self.visit_nodes(|val, _| size = size.saturating_add(val.own_len()));
+        size
+    }
+
+    /// Returns the number of nesting levels in this value.
+    ///
+    /// Values that aren't arrays or maps, as well as empty arrays and maps, have a depth of 1;
+    /// each level of non-empty containers adds one. Map keys are accounted as well.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// assert_eq!(1, Value::from(42).depth());
+    /// assert_eq!(1, Value::Array(vec![]).depth());
+    /// assert_eq!(3, Value::Array(vec![Value::from(1), Value::Array(vec![Value::from(2)])]).depth());
+    /// ```
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        let mut max = 0;
+        self.visit_nodes(|_, depth| max = max.max(depth));
+        max
+    }
+
+    /// Returns the number of values in this value, counting itself, every nested value and map
+    /// keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rmpv::Value;
+    ///
+    /// let val = Value::Map(vec![(Value::from("a"), Value::Array(vec![Value::from(1)]))]);
+    ///
+    /// assert_eq!(4, val.count_nodes());
+    /// ```
+    #[must_use]
+    pub fn count_nodes(&self) -> usize {
+        let mut count = 0;
+        self.visit_nodes(|_, _| count += 1);
+        count
+    }
+
+    /// Calls the given function for this value and every nested one, including map keys, along
+    /// with their depth, starting at 1.
+    ///
+    /// Unlike `walk`, this doesn't visit values in order.
+    fn visit_nodes<F: FnMut(&Value, usize)>(&self, mut f: F) {
+        let mut stack = vec![(self, 1)];
+        while let Some((val, depth)) = stack.pop() {
+            f(val, depth);
+            match *val {
+                Value::Array(ref vec) => stack.extend(vec.iter().map(|val| (val, depth + 1))),
+                Value::Map(ref map) => {
+                    for (key, val) in map {
+                        stack.push((key, depth + 1));
+                        stack.push((val, depth + 1));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns the encoded length of this value alone, i.e. only the header of arrays and maps.
+    fn own_len(&self) -> usize {
+        // Lengths are truncated to `u32` like `encode::write_value` does.
+        match *self {
+            Value::Nil | Value::Boolean(..) => 1,
+            Value::Integer(Integer { n }) => match n {
+                IntPriv::PosInt(n) => rmp::encode::uint_len(n),
+                IntPriv::NegInt(n) => rmp::encode::sint_len(n),
+            },
+            Value::F32(..) => 5,
+            Value::F64(..) => 9,
+            Value::String(ref s) => rmp::encode::str_header_len(s.as_bytes().len() as u32) + s.as_bytes().len(),
+            Value::Binary(ref buf) => rmp::encode::bin_header_len(buf.len() as u32) + buf.len(),
+            Value::Array(ref vec) => rmp::encode::array_header_len(vec.len() as u32),
+            Value::Map(ref map) => rmp::encode::map_header_len(map.len() as u32),
+            Value::Timestamp(ts) => rmp::encode::ext_total_len(ts.ext_data(&mut [0; 12]).len() as u32),
+            Value::Ext(.., ref buf) => rmp::encode::ext_header_len(buf.len() as u32) + buf.len(),
+        }
+    }
 }
 
 static NIL: Value = Value::Nil;
 static NIL_REF: ValueRef<'static> = ValueRef::Nil;
 
+impl Ord for Value {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Value::Nil, Value::Nil) => Ordering::Equal,
+            (Value::Boolean(a), Value::Boolean(b)) => a.cmp(b),
+            (Value::Integer(a), Value::Integer(b)) => a.cmp(b),
+            (Value::F32(a), Value::F32(b)) => a.total_cmp(b),
+            (Value::F64(a), Value::F64(b)) => a.total_cmp(b),
+            (Value::String(a), Value::String(b)) => a.cmp(b),
+            (Value::Binary(a), Value::Binary(b)) => a.cmp(b),
+            (Value::Array(a), Value::Array(b)) => a.cmp(b),
+            (Value::Map(a), Value::Map(b)) => a.cmp(b),
+            (Value::Ext(ty_a, a), Value::Ext(ty_b, b)) => (ty_a, a).cmp(&(ty_b, b)),
+            (Value::Timestamp(a), Value::Timestamp(b)) => a.cmp(b),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+impl PartialOrd for Value {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Value {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Value {}
+
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.rank().hash(state);
+        match *self {
+            Value::Nil => {}
+            Value::Boolean(val) => val.hash(state),
+            Value::Integer(val) => val.hash(state),
+            Value::F32(val) => val.to_bits().hash(state),
+            Value::F64(val) => val.to_bits().hash(state),
+            Value::String(ref val) => val.hash(state),
+            Value::Binary(ref val) => val.hash(state),
+            Value::Array(ref val) => val.hash(state),
+            Value::Map(ref val) => val.hash(state),
+            Value::Ext(ty, ref data) => (ty, data).hash(state),
+            Value::Timestamp(val) => val.hash(state),
+        }
+    }
+}
+
 impl Index<usize> for Value {
     type Output = Value;
 
@@ -933,6 +1624,20 @@ impl Index<usize> for Value {
     }
 }
 
+impl IndexMut<usize> for Value {
+    /// Returns a mutable reference to an Array element.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `Value` is not an Array or the index is out of bounds.
+    fn index_mut(&mut self, index: usize) -> &mut Value {
+        match *self {
+            Value::Array(ref mut array) => &mut array[index],
+            ref other => panic!("cannot index into {other:?} with {index}"),
+        }
+    }
+}
+
 impl Index<&str> for Value {
     type Output = Value;
     fn index(&self, index: &str) -> &Value {
@@ -1080,6 +1785,13 @@ impl<'a> From<&'a [u8]> for Value {
     }
 }
 
+impl<const N: usize> From<&[u8; N]> for Value {
+    #[inline]
+    fn from(v: &[u8; N]) -> Self {
+        Value::Binary(v.to_vec())
+    }
+}
+
 impl<'a> From<Cow<'a, [u8]>> for Value {
     #[inline]
     fn from(v: Cow<'a, [u8]>) -> Self {
@@ -1094,6 +1806,13 @@ impl From<Vec<Value>> for Value {
     }
 }
 
+impl From<Timestamp> for Value {
+    #[inline]
+    fn from(v: Timestamp) -> Self {
+        Value::Timestamp(v)
+    }
+}
+
 impl From<Vec<(Value, Value)>> for Value {
     #[inline]
     fn from(v: Vec<(Value, Value)>) -> Self {
@@ -1211,6 +1930,84 @@ impl_try_from!(Vec<u8>, Binary);
 impl_try_from!(f32, F32);
 impl_try_from!(Utf8String, String);
 
+macro_rules! impl_try_from_int {
+    ($($t: ty),*) => {
+        $(
+            impl TryFrom<Value> for $t {
+                type Error = Value;
+
+                fn try_from(val: Value) -> Result<$t, Self::Error> {
+                    match val {
+                        Value::Integer(n) => match n.as_num() {
+                            Some(i) => Ok(i),
+                            None => Err(val),
+                        },
+                        v => Err(v),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_try_from_int!(u8, u16, u32, usize, i8, i16, i32, isize);
+
+impl TryFrom<Value> for BTreeMap<String, Value> {
+    type Error = Value;
+
+    /// Converts a map whose keys are all distinct valid UTF-8 strings.
+    ///
+    /// A map with duplicate keys is given back rather than losing all but one of their values.
+    fn try_from(val: Value) -> Result<Self, Self::Error> {
+        match val {
+            Value::Map(map) => {
+                let unique = {
+                    let mut keys = BTreeSet::new();
+                    map.iter().all(|(k, _)| k.as_str().is_some_and(|k| keys.insert(k)))
+                };
+                if unique {
+                    Ok(map.into_iter().filter_map(|(k, v)| Some((String::try_from(k).ok()?, v))).collect())
+                } else {
+                    Err(Value::Map(map))
+                }
+            }
+            v => Err(v),
+        }
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl TryFrom<Value> for indexmap::IndexMap<String, Value> {
+    type Error = Value;
+
+    /// Converts a map whose keys are all valid UTF-8 strings, keeping the order of its entries.
+    fn try_from(val: Value) -> Result<Self, Self::Error> {
+        match val {
+            Value::Map(map) if map.iter().all(|(k, _)| k.as_str().is_some()) => {
+                Ok(map.into_iter().filter_map(|(k, v)| Some((String::try_from(k).ok()?, v))).collect())
+            }
+            v => Err(v),
+        }
+    }
+}
+
+#[cfg(feature = "indexmap")]
+impl<K, V> From<indexmap::IndexMap<K, V>> for Value
+where
+    K: Into<Value>,
+    V: Into<Value>,
+{
+    /// Converts the map keeping the order of its entries.
+    #[inline]
+    fn from(map: indexmap::IndexMap<K, V>) -> Self {
+        Value::Map(map.into_iter().map(|(k, v)| (k.into(), v.into())).collect())
+    }
+}
+
+fn display_timestamp(f: &mut fmt::Formatter<'_>, ts: Timestamp) -> Result<(), fmt::Error> {
+    write!(f, "Timestamp({}, {})", ts.secs(), ts.nsecs())
+}
+
 impl Display for Value {
     #[cold]
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
@@ -1253,6 +2050,7 @@ impl Display for Value {
             Value::Ext(ty, ref data) => {
                 write!(f, "[{ty}, {data:?}]")
             }
+            Value::Timestamp(ts) => display_timestamp(f, ts),
         }
     }
 }
@@ -1282,6 +2080,8 @@ pub enum ValueRef<'a> {
     /// Extended implements Extension interface: represents a tuple of type information and a byte
     /// array where type information is an integer whose meaning is defined by applications.
     Ext(i8, &'a [u8]),
+    /// Timestamp represents the predefined timestamp extension type `-1`.
+    Timestamp(Timestamp),
 }
 
 impl<'a> ValueRef<'a> {
@@ -1332,6 +2132,7 @@ impl<'a> ValueRef<'a> {
                 Value::Map(val.iter().map(|(k, v)| (k.to_owned(), v.to_owned())).collect())
             }
             ValueRef::Ext(ty, buf) => Value::Ext(ty, buf.to_vec()),
+            ValueRef::Timestamp(ts) => Value::Timestamp(ts),
         }
     }
 
@@ -1564,6 +2365,80 @@ impl_try_from_ref!(&'a [u8], Binary);
 impl_try_from_ref!(f32, F32);
 impl_try_from_ref!(Utf8StringRef<'a>, String);
 
+macro_rules! impl_try_from_int_ref {
+    ($($t: ty),*) => {
+        $(
+            impl<'a> TryFrom<ValueRef<'a>> for $t {
+                type Error = ValueRef<'a>;
+
+                fn try_from(val: ValueRef<'a>) -> Result<$t, Self::Error> {
+                    match val {
+                        ValueRef::Integer(n) => match n.as_num() {
+                            Some(i) => Ok(i),
+                            None => Err(val),
+                        },
+                        v => Err(v),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_try_from_int_ref!(i64, u8, u16, u32, usize, i8, i16, i32, isize);
+
+impl<'a> TryFrom<ValueRef<'a>> for f64 {
+    type Error = ValueRef<'a>;
+
+    fn try_from(val: ValueRef<'a>) -> Result<Self, Self::Error> {
+        match val {
+            ValueRef::Integer(n) => match n.as_f64() {
+                Some(i) => Ok(i),
+                None => Err(val),
+            },
+            ValueRef::F32(n) => Ok(From::from(n)),
+            ValueRef::F64(n) => Ok(n),
+            v => Err(v),
+        }
+    }
+}
+
+impl<'a> TryFrom<ValueRef<'a>> for &'a str {
+    type Error = ValueRef<'a>;
+
+    fn try_from(val: ValueRef<'a>) -> Result<Self, Self::Error> {
+        match val {
+            ValueRef::String(Utf8StringRef { s: Ok(s) }) => Ok(s),
+            v => Err(v),
+        }
+    }
+}
+
+impl<'a> TryFrom<ValueRef<'a>> for BTreeMap<&'a str, ValueRef<'a>> {
+    type Error = ValueRef<'a>;
+
+    /// Converts a map whose keys are all distinct valid UTF-8 strings.
+    ///
+    /// A map with duplicate keys is given back rather than losing all but one of their values.
+    fn try_from(val: ValueRef<'a>) -> Result<Self, Self::Error> {
+        match val {
+            ValueRef::Map(map) => {
+                let mut keys = BTreeSet::new();
+                let unique = map.iter().all(|(k, _)| match *k {
+                    ValueRef::String(Utf8StringRef { s: Ok(s) }) => keys.insert(s),
+                    _ => false,
+                });
+                if unique {
+                    Ok(map.into_iter().filter_map(|(k, v)| Some((<&str>::try_from(k).ok()?, v))).collect())
+                } else {
+                    Err(ValueRef::Map(map))
+                }
+            }
+            v => Err(v),
+        }
+    }
+}
+
 impl<'a> Display for ValueRef<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         match *self {
@@ -1603,6 +2478,7 @@ impl<'a> Display for ValueRef<'a> {
             ValueRef::Ext(ty, data) => {
                 write!(f, "[{ty}, {data:?}]")
             }
+            ValueRef::Timestamp(ts) => display_timestamp(f, ts),
         }
     }
 }
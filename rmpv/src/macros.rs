@@ -0,0 +1,131 @@
+/// Constructs a [`Value`](crate::Value) from a JSON-like literal.
+///
+/// Maps are written as `{key: value, ...}` where keys may be any value, arrays as
+/// `[value, ...]`, nil as `nil` and extensions as `ext(type, data)`. Any other expression is
+/// converted using [`Value::from`](crate::Value), so byte string literals become binaries.
+///
+/// # Examples
+///
+/// ```
+/// use rmpv::{msgpack, Value};
+///
+/// let val = msgpack!({
+///     "name": "John",
+///     "tags": ["a", -1, nil],
+///     "photo": b"\x89PNG",
+///     1: ext(42, b"\x01\x02"),
+/// });
+///
+/// assert_eq!(Value::from("John"), val["name"]);
+/// assert_eq!(Value::from(-1), val["tags"][1]);
+/// assert_eq!(Value::Binary(b"\x89PNG".to_vec()), val["photo"]);
+/// assert_eq!(Some(&Value::Ext(42, vec![1, 2])), val.get(&Value::from(1)));
+/// ```
+#[macro_export]
+macro_rules! msgpack {
+    // Done with array elements, with or without a trailing comma.
+    (@array [$($elems:expr,)*]) => {
+        ::std::vec![$($elems,)*]
+    };
+    (@array [$($elems:expr),*]) => {
+        ::std::vec![$($elems),*]
+    };
+
+    // Next element is `nil`, an extension, an array or a map.
+    (@array [$($elems:expr,)*] nil $($rest:tt)*) => {
+        $crate::msgpack!(@array [$($elems,)* $crate::msgpack!(nil)] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] ext($($ext:tt)*) $($rest:tt)*) => {
+        $crate::msgpack!(@array [$($elems,)* $crate::msgpack!(ext($($ext)*))] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] [$($array:tt)*] $($rest:tt)*) => {
+        $crate::msgpack!(@array [$($elems,)* $crate::msgpack!([$($array)*])] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] {$($map:tt)*} $($rest:tt)*) => {
+        $crate::msgpack!(@array [$($elems,)* $crate::msgpack!({$($map)*})] $($rest)*)
+    };
+
+    // Next element is an expression, followed by a comma or being the last one.
+    (@array [$($elems:expr,)*] $next:expr, $($rest:tt)*) => {
+        $crate::msgpack!(@array [$($elems,)* $crate::msgpack!($next),] $($rest)*)
+    };
+    (@array [$($elems:expr,)*] $last:expr) => {
+        $crate::msgpack!(@array [$($elems,)* $crate::msgpack!($last)])
+    };
+
+    // Comma after the most recent element.
+    (@array [$($elems:expr),*] , $($rest:tt)*) => {
+        $crate::msgpack!(@array [$($elems,)*] $($rest)*)
+    };
+
+    // Done with map entries.
+    (@map $map:ident () () ()) => {};
+
+    // Push the current entry, followed by a comma or being the last one.
+    (@map $map:ident [$($key:tt)+] ($value:expr) , $($rest:tt)*) => {
+        $map.push(($crate::msgpack!($($key)+), $value));
+        $crate::msgpack!(@map $map () ($($rest)*) ($($rest)*));
+    };
+    (@map $map:ident [$($key:tt)+] ($value:expr)) => {
+        $map.push(($crate::msgpack!($($key)+), $value));
+    };
+
+    // Next value is `nil`, an extension, an array or a map.
+    (@map $map:ident ($($key:tt)+) (: nil $($rest:tt)*) $copy:tt) => {
+        $crate::msgpack!(@map $map [$($key)+] ($crate::msgpack!(nil)) $($rest)*);
+    };
+    (@map $map:ident ($($key:tt)+) (: ext($($ext:tt)*) $($rest:tt)*) $copy:tt) => {
+        $crate::msgpack!(@map $map [$($key)+] ($crate::msgpack!(ext($($ext)*))) $($rest)*);
+    };
+    (@map $map:ident ($($key:tt)+) (: [$($array:tt)*] $($rest:tt)*) $copy:tt) => {
+        $crate::msgpack!(@map $map [$($key)+] ($crate::msgpack!([$($array)*])) $($rest)*);
+    };
+    (@map $map:ident ($($key:tt)+) (: {$($inner:tt)*} $($rest:tt)*) $copy:tt) => {
+        $crate::msgpack!(@map $map [$($key)+] ($crate::msgpack!({$($inner)*})) $($rest)*);
+    };
+
+    // Next value is an expression, followed by a comma or being the last one.
+    (@map $map:ident ($($key:tt)+) (: $value:expr , $($rest:tt)*) $copy:tt) => {
+        $crate::msgpack!(@map $map [$($key)+] ($crate::msgpack!($value)) , $($rest)*);
+    };
+    (@map $map:ident ($($key:tt)+) (: $value:expr) $copy:tt) => {
+        $crate::msgpack!(@map $map [$($key)+] ($crate::msgpack!($value)));
+    };
+
+    // Munch a token into the current key.
+    (@map $map:ident ($($key:tt)*) ($tt:tt $($rest:tt)*) $copy:tt) => {
+        $crate::msgpack!(@map $map ($($key)* $tt) ($($rest)*) ($($rest)*));
+    };
+
+    (nil) => {
+        $crate::Value::Nil
+    };
+
+    (ext($ty:expr, $data:expr $(,)?)) => {
+        $crate::Value::Ext($ty, ::std::convert::AsRef::<[u8]>::as_ref(&$data).to_vec())
+    };
+
+    ([]) => {
+        $crate::Value::Array(::std::vec![])
+    };
+
+    ([ $($tt:tt)+ ]) => {
+        $crate::Value::Array($crate::msgpack!(@array [] $($tt)+))
+    };
+
+    ({}) => {
+        $crate::Value::Map(::std::vec![])
+    };
+
+    ({ $($tt:tt)+ }) => {
+        $crate::Value::Map({
+            let mut map = ::std::vec::Vec::new();
+            $crate::msgpack!(@map map () ($($tt)+) ($($tt)+));
+            map
+        })
+    };
+
+    ($other:expr) => {
+        $crate::Value::from($other)
+    };
+}
@@ -1,4 +1,4 @@
-use rmpv::decode::{read_value, Error};
+use rmpv::decode::{read_value, read_value_lazy, read_value_with_limits, read_value_with_max_bytes, Error};
 use rmpv::Value;
 
 #[test]
@@ -354,6 +354,52 @@ fn from_str32_invalid_utf8() {
     }
 }
 
+#[test]
+fn from_long_str_split_across_chunks() {
+    // Multi-byte characters straddle the boundaries of the chunks strings are validated in.
+    let val = "aé€😀".repeat(5000);
+    let mut buf = Vec::new();
+    rmpv::encode::write_value(&mut buf, &Value::from(val.as_str())).unwrap();
+
+    assert_eq!(Value::from(val.as_str()), read_value(&mut &buf[..]).unwrap());
+
+    // A reader returning a single byte per call.
+    struct OneByte<'a>(&'a [u8]);
+
+    impl std::io::Read for OneByte<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let len = buf.len().min(self.0.len()).min(1);
+            buf[..len].copy_from_slice(&self.0[..len]);
+            self.0 = &self.0[len..];
+            Ok(len)
+        }
+    }
+
+    assert_eq!(Value::from(val.as_str()), read_value(&mut OneByte(&buf)).unwrap());
+}
+
+#[test]
+fn from_long_str_invalid_utf8_after_first_chunk() {
+    let mut data = "€".repeat(10000).into_bytes();
+    data[19998] = 0xff;
+    let mut buf = vec![0xdb];
+    buf.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    buf.extend_from_slice(&data);
+
+    match read_value(&mut &buf[..]).unwrap() {
+        Value::String(s) => {
+            assert_eq!(Some(19998), s.as_err().map(|err| err.valid_up_to()));
+            assert_eq!(data, s.into_bytes());
+        }
+        _ => panic!("wrong type"),
+    }
+
+    match read_value(&mut &buf[..buf.len() - 1]) {
+        Err(Error::InvalidDataRead(err)) => assert_eq!(std::io::ErrorKind::UnexpectedEof, err.kind()),
+        res => panic!("Unexpected result: {res:?}"),
+    }
+}
+
 #[test]
 fn from_array_of_two_integers() {
     let buf: &[u8] = &[0x92, 0x04, 0x2a];
@@ -397,3 +443,204 @@ fn invalid_buf_size_arr() {
         Err(e) => panic!("Unexpected error: {e}"),
     }
 }
+
+#[test]
+fn fail_length_limit() {
+    // Encoded `[[1, 2], "abc"]`.
+    let buf = [0x92, 0x92, 0x01, 0x02, 0xa3, 0x61, 0x62, 0x63];
+
+    let val = read_value_with_limits(&mut &buf[..], 16, 3).unwrap();
+    assert_eq!(Value::Array(vec![Value::Array(vec![Value::from(1), Value::from(2)]), Value::from("abc")]), val);
+
+    match read_value_with_limits(&mut &buf[..], 16, 2) {
+        Err(Error::LengthLimitExceeded) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+
+    // A huge `bin 32` header is rejected without reading the data.
+    let buf = [0xc6, 0xff, 0xff, 0xff, 0xff];
+    match read_value_with_limits(&mut &buf[..], 16, 1 << 20) {
+        Err(Error::LengthLimitExceeded) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn from_bytes_over_limit() {
+    // Encoded `["ab", "cd"]`, 7 bytes in total.
+    let buf = [0x92, 0xa2, 0x61, 0x62, 0xa2, 0x63, 0x64];
+
+    let val = read_value_with_max_bytes(&mut &buf[..], 16, 2, 7).unwrap();
+    assert_eq!(Value::Array(vec![Value::from("ab"), Value::from("cd")]), val);
+
+    // Each string is within the length limit, but not both of them within the byte limit.
+    let mut rd = &buf[..];
+    match read_value_with_max_bytes(&mut rd, 16, 2, 6) {
+        Err(Error::ByteLimitExceeded) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+    // Nothing was read past the limit.
+    assert_eq!(1, rd.len());
+
+    // An `array 32` header claiming more elements than there are bytes left.
+    let buf = [0xdd, 0x00, 0x01, 0x00, 0x00];
+    match read_value_with_max_bytes(&mut &buf[..], 16, usize::MAX, 1 << 10) {
+        Err(Error::ByteLimitExceeded) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn from_deeply_nested_without_recursion() {
+    use rmpv::decode::read_value_with_max_depth;
+
+    // `[[[...[nil]...]]]`, nested deep enough to overflow the stack if read recursively.
+    let nesting = 20_000;
+    let mut buf = vec![0x91; nesting];
+    buf.push(0xc0);
+
+    let mut val = read_value_with_max_depth(&mut &buf[..], usize::MAX).unwrap();
+    // Unwrap the arrays one by one, as dropping the whole value at once recurses as well.
+    let mut depth = 0;
+    while let Value::Array(mut vec) = val {
+        assert_eq!(1, vec.len());
+        val = vec.pop().unwrap();
+        depth += 1;
+    }
+    assert_eq!(nesting, depth);
+    assert_eq!(Value::Nil, val);
+
+    match read_value_with_max_depth(&mut &buf[..], 1024) {
+        Err(Error::DepthLimitExceeded) => (),
+        other => panic!("unexpected result: {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn from_lazy_skips_large_data() {
+    // ["ab", <bin 1 2 3 4>, "abcdef", nil]
+    let buf = [
+        0x94, 0xa2, 0x61, 0x62, 0xc4, 0x04, 0x01, 0x02, 0x03, 0x04,
+        0xa6, 0x61, 0x62, 0x63, 0x64, 0x65, 0x66, 0xc0,
+    ];
+
+    let mut seen = Vec::new();
+    let val = read_value_lazy(&mut &buf[..], 3, |marker, body| {
+        // Only read a part of the body, the rest must be skipped.
+        let mut head = [0; 1];
+        body.read_exact(&mut head)?;
+        seen.push((marker, head[0]));
+        Ok(Value::Nil)
+    }).unwrap();
+
+    assert_eq!(Value::Array(vec![Value::from("ab"), Value::Nil, Value::Nil, Value::Nil]), val);
+    assert_eq!(vec![(rmp::Marker::Bin8, 0x01), (rmp::Marker::FixStr(6), 0x61)], seen);
+}
+
+#[test]
+fn fail_lazy_truncated_large_data() {
+    let buf = [0xc4, 0x04, 0x01, 0x02];
+
+    match read_value_lazy(&mut &buf[..], 0, |_, _| Ok(Value::Nil)) {
+        Err(Error::InvalidDataRead(..)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn from_async_round_trip() {
+    use rmpv::decode::read_value_async;
+    use rmpv::encode::write_value_async;
+
+    let val = Value::Map(vec![
+        (Value::from("a"), Value::Array(vec![Value::from(1), Value::Array(vec![]), Value::from(-1.5)])),
+        (Value::from(2), Value::Map(vec![(Value::Nil, Value::Binary(vec![0; 300]))])),
+        (Value::Ext(5, vec![1, 2]), Value::Boolean(true)),
+    ]);
+
+    let mut buf = Vec::new();
+    write_value_async(&mut buf, &val).await.unwrap();
+    assert_eq!(read_value(&mut &buf[..]).unwrap(), val);
+
+    let mut rd = &buf[..];
+    assert_eq!(val, read_value_async(&mut rd).await.unwrap());
+    assert!(rd.is_empty());
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn fail_async_depth_limit() {
+    use rmpv::decode::read_value_async_with_max_depth;
+
+    // [[[]]]
+    let buf = [0x91, 0x91, 0x90];
+
+    read_value_async_with_max_depth(&mut &buf[..], 2).await.unwrap();
+    match read_value_async_with_max_depth(&mut &buf[..], 1).await {
+        Err(Error::DepthLimitExceeded) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+    match read_value_async_with_max_depth(&mut &buf[..2], 2).await {
+        Err(Error::InvalidMarkerRead(..)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn from_timestamps_decode_value_with_timestamps() {
+    use rmpv::decode::read_value_with_timestamps;
+    use rmpv::Timestamp;
+
+    let vals = [
+        Timestamp::new(2, 0).unwrap(),
+        Timestamp::new(0x3_ffff_ffff, 999_999_999).unwrap(),
+        Timestamp::new(-2, 1).unwrap(),
+    ];
+    let val = Value::Array(vals.iter().map(|&ts| Value::Timestamp(ts)).collect());
+
+    let mut buf = Vec::new();
+    rmpv::encode::write_value(&mut buf, &val).unwrap();
+    assert_eq!(buf.len(), val.encoded_size());
+
+    assert_eq!(val, read_value_with_timestamps(&mut &buf[..]).unwrap());
+
+    // Other readers keep returning ext values, which can be converted afterwards.
+    let mut ext = read_value(&mut &buf[..]).unwrap();
+    assert_eq!(Some(vals[2]), ext[2].as_timestamp());
+    assert_eq!(Some((-1, &[0, 0, 0, 2][..])), ext[0].as_ext());
+    ext.convert_timestamps();
+    assert_eq!(val, ext);
+}
+
+#[test]
+fn from_map_of_values() {
+    use std::collections::BTreeMap;
+
+    use rmpv::decode::read_map_of_values;
+
+    let val = Value::Map(vec![
+        (Value::from("id"), Value::from(42)),
+        (Value::from("nested"), Value::Map(vec![(Value::from(1), Value::Nil)])),
+        (Value::from("id"), Value::from(43)),
+    ]);
+    let mut buf = Vec::new();
+    rmpv::encode::write_value(&mut buf, &val).unwrap();
+
+    let map: BTreeMap<String, Value> = read_map_of_values(&mut &buf[..]).unwrap();
+    assert_eq!(2, map.len());
+    assert_eq!(Value::from(43), map["id"]);
+    assert_eq!(val["nested"], map["nested"]);
+
+    let map: Vec<(String, Value)> = read_map_of_values(&mut &buf[..]).unwrap();
+    assert_eq!(3, map.len());
+
+    // Not a map, and a map with a non-string key.
+    assert!(read_map_of_values::<_, Vec<(String, Value)>>(&mut &[0x90][..]).is_err());
+    assert!(read_map_of_values::<_, Vec<(String, Value)>>(&mut &[0x81, 0x01, 0xc0][..]).is_err());
+    // A key that isn't valid UTF-8.
+    match read_map_of_values::<_, Vec<(String, Value)>>(&mut &[0x81, 0xa1, 0xff, 0xc0][..]) {
+        Err(Error::InvalidDataRead(err)) => assert_eq!(std::io::ErrorKind::InvalidData, err.kind()),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
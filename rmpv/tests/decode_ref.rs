@@ -1,4 +1,4 @@
-use rmpv::decode::{read_value_ref, Error};
+use rmpv::decode::{read_value_ref, read_value_ref_with_max_depth, Error};
 use rmpv::ValueRef;
 
 #[test]
@@ -687,3 +687,76 @@ fn into_owned() {
     assert_eq!(expected, val.to_owned());
     assert_eq!(expected.as_ref(), val);
 }
+
+#[test]
+fn from_segments_at_every_split() {
+    use rmpv::decode::read_value_ref_from_segments;
+
+    // ["abc", 300, <bin [1, 2, 3, 4]>, {"k": <ext 5 [0xff, 0xee]>}, nil]
+    let buf = [
+        0x95, 0xa3, 0x61, 0x62, 0x63, 0xcd, 0x01, 0x2c, 0xc4, 0x04, 0x01, 0x02, 0x03, 0x04,
+        0x81, 0xa1, 0x6b, 0xd5, 0x05, 0xff, 0xee, 0xc0,
+    ];
+    let expected = read_value_ref(&mut &buf[..]).unwrap();
+
+    for mid in 0..=buf.len() {
+        let segments = [&buf[..mid], &[][..], &buf[mid..]];
+        let mut scratch = Vec::new();
+        let (val, len) = read_value_ref_from_segments(&segments, &mut scratch).unwrap();
+        assert_eq!(expected, val, "split at {mid}");
+        assert_eq!(buf.len(), len);
+    }
+
+    // Splitting between values needs no copies.
+    let segments = [&buf[..5], &buf[5..14], &buf[14..]];
+    let mut scratch = Vec::new();
+    assert_eq!(expected, read_value_ref_from_segments(&segments, &mut scratch).unwrap().0);
+    assert!(scratch.is_empty());
+}
+
+#[test]
+fn from_segments_truncated() {
+    use rmpv::decode::read_value_ref_from_segments;
+
+    let segments = [&[0x92, 0xa3, 0x61][..], &[0x62][..]];
+    let mut scratch = Vec::new();
+    match read_value_ref_from_segments(&segments, &mut scratch) {
+        Err(Error::InvalidDataRead(..)) => (),
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn from_segments_bogus_len_does_not_allocate() {
+    use rmpv::decode::read_value_ref_from_segments;
+
+    // A bin 32 claiming 4 GiB, split right after its header.
+    let segments = [&[0xc6, 0xff, 0xff, 0xff, 0xff][..], &[0x00][..]];
+    let mut scratch = Vec::new();
+    assert!(read_value_ref_from_segments(&segments, &mut scratch).is_err());
+    assert!(scratch.capacity() <= 1);
+}
+
+#[test]
+fn from_deeply_nested_without_recursion() {
+    // `[[[...[nil]...]]]`, nested deep enough to overflow the stack if read recursively.
+    let nesting = 20_000;
+    let mut buf = vec![0x91; nesting];
+    buf.push(0xc0);
+
+    let mut val = read_value_ref_with_max_depth(&mut &buf[..], usize::MAX).unwrap();
+    // Unwrap the arrays one by one, as dropping the whole value at once recurses as well.
+    let mut depth = 0;
+    while let ValueRef::Array(mut vec) = val {
+        assert_eq!(1, vec.len());
+        val = vec.pop().unwrap();
+        depth += 1;
+    }
+    assert_eq!(nesting, depth);
+    assert_eq!(ValueRef::Nil, val);
+
+    match read_value_ref_with_max_depth(&mut &buf[..], 1024) {
+        Err(Error::DepthLimitExceeded) => (),
+        other => panic!("unexpected result: {:?}", other.map(|_| ())),
+    }
+}
@@ -0,0 +1,82 @@
+use rmpv::encode::{write_value, write_value_canonical};
+use rmpv::Value;
+
+#[test]
+fn pass_pack_map_canonical_sorts_by_encoded_key() {
+    // Keys are sorted by their encoded representation, not by their logical value: fixint 1 is
+    // `0x01`, while uint8 200 is `0xcc 0xc8` and fixstr "a" is `0xa1 0x61`.
+    let val = Value::Map(vec![
+        (Value::from(200), Value::from(3)),
+        (Value::from("a"), Value::from(2)),
+        (Value::from(1), Value::from(1)),
+    ]);
+
+    let mut buf = Vec::new();
+    write_value_canonical(&mut buf, &val).unwrap();
+
+    assert_eq!(vec![0x83, 0x01, 0x01, 0xa1, 0x61, 0x02, 0xcc, 0xc8, 0x03], buf);
+}
+
+#[test]
+fn pass_pack_map_canonical_nested() {
+    let inner1 = Value::Map(vec![(Value::from("y"), Value::Nil), (Value::from("x"), Value::Nil)]);
+    let inner2 = Value::Map(vec![(Value::from("x"), Value::Nil), (Value::from("y"), Value::Nil)]);
+
+    let lhs = Value::Array(vec![Value::Map(vec![(Value::from(0), inner1)])]);
+    let rhs = Value::Array(vec![Value::Map(vec![(Value::from(0), inner2)])]);
+
+    let mut lhs_buf = Vec::new();
+    let mut rhs_buf = Vec::new();
+    write_value_canonical(&mut lhs_buf, &lhs).unwrap();
+    write_value_canonical(&mut rhs_buf, &rhs).unwrap();
+
+    assert_eq!(lhs_buf, rhs_buf);
+
+    // Already sorted values are encoded the same way as by `write_value`.
+    let mut buf = Vec::new();
+    write_value(&mut buf, &rhs).unwrap();
+
+    assert_eq!(buf, rhs_buf);
+}
+
+#[test]
+fn pass_msgpack_len_matches_write_value() {
+    let values = vec![
+        Value::Nil,
+        Value::Boolean(true),
+        Value::from(0),
+        Value::from(127),
+        Value::from(128),
+        Value::from(65535),
+        Value::from(65536),
+        Value::from(u64::MAX),
+        Value::from(-1),
+        Value::from(-33),
+        Value::from(-129),
+        Value::from(-32769),
+        Value::from(i64::MIN),
+        Value::F32(1.5),
+        Value::F64(1.5),
+        Value::from("a".repeat(31)),
+        Value::from("a".repeat(32)),
+        Value::from("a".repeat(256)),
+        Value::from("a".repeat(65536)),
+        Value::Binary(vec![0; 255]),
+        Value::Binary(vec![0; 256]),
+        Value::Binary(vec![0; 65536]),
+        Value::Array(vec![Value::Nil; 16]),
+        Value::Array(vec![Value::Nil; 65536]),
+        Value::Map(vec![(Value::from(1), Value::from("one")); 15]),
+        Value::Ext(1, vec![0; 4]),
+        Value::Ext(1, vec![0; 3]),
+        Value::Ext(1, vec![0; 256]),
+        Value::Ext(1, vec![0; 65536]),
+    ];
+
+    for val in values {
+        let mut buf = Vec::new();
+        write_value(&mut buf, &val).unwrap();
+
+        assert_eq!(buf.len(), val.msgpack_len());
+    }
+}
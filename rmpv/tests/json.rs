@@ -0,0 +1,90 @@
+#![cfg(feature = "json")]
+
+use std::convert::TryFrom;
+
+use rmpv::json::{to_json, BinaryPolicy, JsonError, JsonOptions, KeyPolicy, LargeUintPolicy};
+use rmpv::Value;
+use serde_json::json;
+
+#[test]
+fn from_json() {
+    let json = json!({"a": [null, true, -1, 18446744073709551615u64, 1.5, "s"], "b": {}});
+
+    assert_eq!(Value::Map(vec![
+        (Value::from("a"), Value::Array(vec![
+            Value::Nil,
+            Value::Boolean(true),
+            Value::from(-1),
+            Value::from(u64::MAX),
+            Value::F64(1.5),
+            Value::from("s"),
+        ])),
+        (Value::from("b"), Value::Map(vec![])),
+    ]), Value::from(json));
+}
+
+#[test]
+fn to_json_round_trip() {
+    let json = json!({"a": [null, true, -1, 1.5, "s"], "b": {"c": 42}});
+
+    assert_eq!(json, serde_json::Value::try_from(Value::from(json.clone())).unwrap());
+}
+
+#[cfg(feature = "json-preserve-order")]
+#[test]
+fn json_round_trip_keeps_key_order() {
+    let json = json!({"z": 1, "a": {"y": 2, "b": 3}});
+
+    let val = Value::from(json.clone());
+    assert_eq!(Some(&Value::from("z")), val.as_map().map(|map| &map[0].0));
+
+    let json = serde_json::Value::try_from(val).unwrap();
+    assert_eq!(r#"{"z":1,"a":{"y":2,"b":3}}"#, json.to_string());
+}
+
+#[test]
+fn to_json_binary() {
+    let val = Value::Binary(vec![0x66, 0x6f, 0x6f, 0x62]);
+
+    assert_eq!(json!([0x66, 0x6f, 0x6f, 0x62]), to_json(&val, JsonOptions::default()).unwrap());
+
+    let opts = JsonOptions { binary: BinaryPolicy::Base64, ..JsonOptions::default() };
+    assert_eq!(json!("Zm9vYg=="), to_json(&val, opts).unwrap());
+
+    let opts = JsonOptions { binary: BinaryPolicy::Error, ..JsonOptions::default() };
+    assert_eq!(Err(JsonError::Binary), to_json(&val, opts));
+}
+
+#[test]
+fn to_json_keys() {
+    let val = Value::Map(vec![(Value::from(1), Value::Nil), (Value::Nil, Value::Nil)]);
+
+    assert_eq!(Err(JsonError::Key(Value::from(1))), to_json(&val, JsonOptions::default()));
+
+    let opts = JsonOptions { keys: KeyPolicy::Stringify, ..JsonOptions::default() };
+    assert_eq!(json!({"1": null, "nil": null}), to_json(&val, opts).unwrap());
+}
+
+#[test]
+fn to_json_large_uint() {
+    let val = Value::from(u64::MAX);
+
+    assert_eq!(json!(u64::MAX), to_json(&val, JsonOptions::default()).unwrap());
+
+    let opts = JsonOptions { large_uint: LargeUintPolicy::String, ..JsonOptions::default() };
+    assert_eq!(json!("18446744073709551615"), to_json(&val, opts).unwrap());
+
+    let opts = JsonOptions { large_uint: LargeUintPolicy::Error, ..JsonOptions::default() };
+    assert_eq!(Err(JsonError::LargeUint(u64::MAX)), to_json(&val, opts));
+
+    // Values that fit `i64` are never affected.
+    assert_eq!(json!(i64::MAX), to_json(&Value::from(i64::MAX), opts).unwrap());
+}
+
+#[test]
+fn to_json_unrepresentable() {
+    let opts = JsonOptions::default();
+
+    assert_eq!(Err(JsonError::NonFiniteFloat), to_json(&Value::F64(f64::NAN), opts));
+    assert_eq!(Err(JsonError::Ext(5)), to_json(&Value::Ext(5, vec![]), opts));
+}
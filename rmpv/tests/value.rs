@@ -1,4 +1,4 @@
-use rmpv::Value;
+use rmpv::{msgpack, Value};
 
 #[test]
 fn display_nil() {
@@ -191,8 +191,410 @@ fn try_from_val() {
     use rmpv::Utf8String;
     use std::convert::TryInto;
 
-  assert_eq!(false, Value::Boolean(false).try_into().unwrap());
+  assert_eq!(false, TryInto::<bool>::try_into(Value::Boolean(false)).unwrap());
   assert_eq!(Utf8String::from("spook"), Value::from("spook").try_into().unwrap());
   assert_eq!(String::from("spook"), TryInto::<String>::try_into(Value::from("spook")).unwrap());
   assert_eq!(vec![0], TryInto::<Vec<u8>>::try_into(Value::Binary(vec![0u8])).unwrap());
 }
+
+#[test]
+fn try_from_val_integers() {
+    use std::convert::TryFrom;
+
+    assert_eq!(Ok(42u8), u8::try_from(Value::from(42)));
+    assert_eq!(Ok(-42i32), i32::try_from(Value::from(-42)));
+    assert_eq!(Ok(65535usize), usize::try_from(Value::from(65535)));
+
+    // Out of range or mismatched values are given back.
+    assert_eq!(Err(Value::from(256)), u8::try_from(Value::from(256)));
+    assert_eq!(Err(Value::from(-1)), u16::try_from(Value::from(-1)));
+    assert_eq!(Err(Value::from("42")), i8::try_from(Value::from("42")));
+}
+
+#[test]
+fn try_from_integer() {
+    use rmpv::Integer;
+    use std::convert::TryFrom;
+
+    assert_eq!(Ok(200u8), u8::try_from(Integer::from(200u64)));
+    assert_eq!(Ok(-1isize), isize::try_from(Integer::from(-1)));
+    assert_eq!(Err(Integer::from(-1)), u64::try_from(Integer::from(-1)));
+    assert_eq!(Err(Integer::from(u64::MAX)), i64::try_from(Integer::from(u64::MAX)));
+    assert_eq!(u64::MAX as i128, i128::from(Integer::from(u64::MAX)));
+    assert_eq!(i64::MIN as i128, i128::from(Integer::from(i64::MIN)));
+}
+
+#[test]
+fn cmp_integer_with_primitives() {
+    use rmpv::Integer;
+
+    let n = Integer::from(300u16);
+    assert!(n == 300i32 && n == 300u64);
+    assert!(n != 44u8);
+    assert!(n > -1i8 && n < u64::MAX);
+    assert!(Integer::from(-1) < 0u8 && Integer::from(-1) > i64::MIN);
+}
+
+#[test]
+fn try_from_val_map() {
+    use std::collections::BTreeMap;
+    use std::convert::TryFrom;
+
+    let val = Value::Map(vec![(Value::from("a"), Value::from(1)), (Value::from("b"), Value::Nil)]);
+    let map = BTreeMap::<String, Value>::try_from(val).unwrap();
+    assert_eq!(2, map.len());
+    assert_eq!(Value::from(1), map["a"]);
+    assert_eq!(Value::Nil, map["b"]);
+
+    let val = Value::Map(vec![(Value::from(1), Value::from(1))]);
+    assert_eq!(Err(val.clone()), BTreeMap::<String, Value>::try_from(val));
+
+    // Duplicate keys would lose values, so the map is given back.
+    let val = Value::Map(vec![(Value::from("a"), Value::from(1)), (Value::from("a"), Value::from(2))]);
+    assert_eq!(Err(val.clone()), BTreeMap::<String, Value>::try_from(val));
+}
+
+#[cfg(feature = "indexmap")]
+#[test]
+fn try_from_val_index_map_keeps_order() {
+    use indexmap::IndexMap;
+    use std::convert::TryFrom;
+
+    let val = Value::Map(vec![(Value::from("z"), Value::from(1)), (Value::from("a"), Value::Nil)]);
+    let map = IndexMap::<String, Value>::try_from(val.clone()).unwrap();
+    assert_eq!(vec!["z", "a"], map.keys().map(String::as_str).collect::<Vec<_>>());
+    assert_eq!(val, Value::from(map));
+
+    let val = Value::Map(vec![(Value::from(1), Value::from(1))]);
+    assert_eq!(Err(val.clone()), IndexMap::<String, Value>::try_from(val));
+}
+
+#[test]
+fn try_from_val_ref() {
+    use rmpv::ValueRef;
+    use std::collections::BTreeMap;
+    use std::convert::TryFrom;
+
+    assert_eq!(Ok(42u16), u16::try_from(ValueRef::from(42)));
+    assert_eq!(Ok(-42i64), i64::try_from(ValueRef::from(-42)));
+    assert_eq!(Ok(1.5f64), f64::try_from(ValueRef::F32(1.5)));
+    assert_eq!(Ok("spook"), <&str>::try_from(ValueRef::from("spook")));
+    assert_eq!(Err(ValueRef::from(300)), u8::try_from(ValueRef::from(300)));
+
+    let val = ValueRef::Map(vec![(ValueRef::from("a"), ValueRef::from(1))]);
+    let map = BTreeMap::<&str, ValueRef<'_>>::try_from(val).unwrap();
+    assert_eq!(ValueRef::from(1), map["a"]);
+
+    let val = ValueRef::Map(vec![(ValueRef::from("a"), ValueRef::from(1)), (ValueRef::from("a"), ValueRef::Nil)]);
+    assert_eq!(Err(val.clone()), BTreeMap::<&str, ValueRef<'_>>::try_from(val));
+}
+
+#[test]
+fn map_mutation() {
+    let mut val = Value::Map(vec![
+        (Value::from("a"), Value::from(1)),
+        (Value::from(2), Value::Nil),
+    ]);
+
+    *val.get_mut(&Value::from(2)).unwrap() = Value::from("two");
+    assert_eq!(Some(&Value::from("two")), val.get(&Value::from(2)));
+
+    assert_eq!(Some(Value::from(1)), val.insert(Value::from("a"), Value::from(10)));
+    assert_eq!(None, val.insert(Value::from("b"), Value::Nil));
+    *val.entry(Value::from("c")) = Value::from(3);
+    *val.entry(Value::from("b")) = Value::from(20);
+
+    assert_eq!(Some(Value::from("two")), val.remove(&Value::from(2)));
+    assert_eq!(None, val.remove(&Value::from(2)));
+
+    assert_eq!(
+        Value::Map(vec![
+            (Value::from("a"), Value::from(10)),
+            (Value::from("b"), Value::from(20)),
+            (Value::from("c"), Value::from(3)),
+        ]),
+        val
+    );
+
+    assert_eq!(None, Value::Nil.get_mut(&Value::from("a")));
+}
+
+#[test]
+#[should_panic]
+fn map_insert_into_non_map() {
+    Value::Nil.insert(Value::from("a"), Value::Nil);
+}
+
+#[test]
+fn array_index_mut() {
+    let mut val = Value::Array(vec![Value::Nil, Value::Array(vec![Value::Nil])]);
+    val[0] = Value::from(1);
+    val[1][0] = Value::from(2);
+
+    assert_eq!(Value::Array(vec![Value::from(1), Value::Array(vec![Value::from(2)])]), val);
+}
+
+#[test]
+fn walk_order() {
+    let val = Value::Map(vec![
+        (Value::from("a"), Value::Array(vec![Value::from(1), Value::from(2)])),
+        (Value::from("b"), Value::Nil),
+    ]);
+
+    let mut visited = Vec::new();
+    val.walk(|v| visited.push(v.to_string()));
+    assert_eq!(
+        vec![val.to_string(), "\"a\"".into(), "[1, 2]".into(), "1".into(), "2".into(), "\"b\"".into(), "nil".into()],
+        visited
+    );
+
+    let mut visited = Vec::new();
+    val.walk_values(|v| visited.push(v.to_string()));
+    assert_eq!(vec![val.to_string(), "[1, 2]".into(), "1".into(), "2".into(), "nil".into()], visited);
+}
+
+#[test]
+fn walk_mut_keys() {
+    let mut val = Value::Map(vec![(Value::from("a"), Value::from("b"))]);
+
+    val.walk_mut(|v| {
+        if let Some(s) = v.as_str() {
+            *v = Value::from(s.to_uppercase());
+        }
+    });
+    assert_eq!(Value::Map(vec![(Value::from("A"), Value::from("B"))]), val);
+
+    val.walk_values_mut(|v| {
+        if v.is_str() {
+            *v = Value::Nil;
+        }
+    });
+    assert_eq!(Value::Map(vec![(Value::from("A"), Value::Nil)]), val);
+
+    // Deeply nested values don't overflow the stack.
+    let mut val = Value::Nil;
+    for _ in 0..5000 {
+        val = Value::Array(vec![val]);
+    }
+    let mut count = 0;
+    val.walk_mut(|v| {
+        if v.is_nil() {
+            *v = Value::from(true);
+        }
+        count += 1;
+    });
+    assert_eq!(5001, count);
+}
+
+#[test]
+fn msgpack_macro_scalars() {
+    assert_eq!(Value::Nil, msgpack!(nil));
+    assert_eq!(Value::Boolean(true), msgpack!(true));
+    assert_eq!(Value::from(-42), msgpack!(-42));
+    assert_eq!(Value::from(1.5), msgpack!(1.5));
+    assert_eq!(Value::from("le message"), msgpack!("le message"));
+    assert_eq!(Value::Binary(vec![1, 2]), msgpack!(b"\x01\x02"));
+    assert_eq!(Value::Ext(-1, vec![0]), msgpack!(ext(-1, [0u8])));
+    assert_eq!(Value::Array(vec![]), msgpack!([]));
+    assert_eq!(Value::Map(vec![]), msgpack!({}));
+}
+
+#[test]
+fn msgpack_macro_nested() {
+    let id = 42;
+    let val = msgpack!({
+        "id": id,
+        "tags": [nil, "a", [1, 2], {}, ext(1, b"\x00")],
+        nil: {-1: b"\xff"},
+    });
+
+    assert_eq!(Value::Map(vec![
+        (Value::from("id"), Value::from(42)),
+        (Value::from("tags"), Value::Array(vec![
+            Value::Nil,
+            Value::from("a"),
+            Value::Array(vec![Value::from(1), Value::from(2)]),
+            Value::Map(vec![]),
+            Value::Ext(1, vec![0]),
+        ])),
+        (Value::Nil, Value::Map(vec![(Value::from(-1), Value::Binary(vec![0xff]))])),
+    ]), val);
+}
+
+#[test]
+fn depth_count_nodes_encoded_size() {
+    let val = Value::Map(vec![
+        (Value::from("a"), Value::Array(vec![Value::from(1), Value::Array(vec![])])),
+        (Value::Array(vec![Value::Nil]), Value::from(300)),
+    ]);
+    assert_eq!(3, val.depth());
+    assert_eq!(8, val.count_nodes());
+
+    let mut buf = Vec::new();
+    rmpv::encode::write_value(&mut buf, &val).unwrap();
+    assert_eq!(buf.len(), val.encoded_size());
+
+    assert_eq!(1, Value::Nil.depth());
+    assert_eq!(1, Value::Nil.count_nodes());
+
+    // Deeply nested values don't overflow the stack.
+    let mut val = Value::Nil;
+    for _ in 0..5000 {
+        val = Value::Array(vec![val]);
+    }
+    assert_eq!(5001, val.depth());
+    assert_eq!(5001, val.count_nodes());
+    assert_eq!(5001, val.encoded_size());
+}
+
+#[test]
+fn diff_paths_and_kinds() {
+    use rmpv::diff::{diff_with, DiffOptions, Difference, DifferenceKind, PathSegment};
+
+    let left = Value::Map(vec![
+        (Value::from("name"), Value::from("left")),
+        (Value::from("items"), Value::Array(vec![Value::from(1), Value::from(2), Value::from(3)])),
+        (Value::from("gone"), Value::Nil),
+        (Value::from("id"), Value::from(7u64)),
+    ]);
+    let right = Value::Map(vec![
+        (Value::from("id"), Value::from(7i64)),
+        (Value::from("items"), Value::Array(vec![Value::from(1), Value::F64(2.0)])),
+        (Value::from("name"), Value::from(true)),
+        (Value::from("new"), Value::Nil),
+    ]);
+
+    let diffs = rmpv::diff(&left, &right);
+    assert_eq!(
+        vec![
+            Difference {
+                path: vec![PathSegment::Key(Value::from("name"))],
+                kind: DifferenceKind::Type,
+                left: Some(Value::from("left")),
+                right: Some(Value::from(true)),
+            },
+            Difference {
+                path: vec![PathSegment::Key(Value::from("items")), PathSegment::Index(1)],
+                kind: DifferenceKind::Type,
+                left: Some(Value::from(2)),
+                right: Some(Value::F64(2.0)),
+            },
+            Difference {
+                path: vec![PathSegment::Key(Value::from("items")), PathSegment::Index(2)],
+                kind: DifferenceKind::Removed,
+                left: Some(Value::from(3)),
+                right: None,
+            },
+            Difference {
+                path: vec![PathSegment::Key(Value::from("gone"))],
+                kind: DifferenceKind::Removed,
+                left: Some(Value::Nil),
+                right: None,
+            },
+            Difference {
+                path: vec![PathSegment::Key(Value::from("new"))],
+                kind: DifferenceKind::Added,
+                left: None,
+                right: Some(Value::Nil),
+            },
+        ],
+        diffs
+    );
+    assert_eq!(r#"$["items"][2]: 3 was removed"#, diffs[2].to_string());
+
+    let diffs = diff_with(&left, &right, DiffOptions::new().numbers_by_value(true));
+    assert_eq!(4, diffs.len());
+    assert!(!diffs.iter().any(|diff| diff.path.get(1) == Some(&PathSegment::Index(1))));
+
+    assert!(rmpv::diff(&left, &left).is_empty());
+    assert!(!diff_with(&Value::from(u64::MAX), &Value::F64(u64::MAX as f64), DiffOptions::new().numbers_by_value(true)).is_empty());
+}
+
+#[test]
+fn diff_deeply_nested_without_recursion() {
+    use rmpv::diff::{DifferenceKind, PathSegment};
+
+    // `[[[...[1]...]]]` and `[[[...[2]...]]]`, nested deep enough to overflow the stack if
+    // compared recursively.
+    let nesting = 20_000;
+    let (mut left, mut right) = (Value::from(1), Value::from(2));
+    for _ in 0..nesting {
+        left = Value::Array(vec![left]);
+        right = Value::Array(vec![right]);
+    }
+
+    let diffs = rmpv::diff(&left, &right);
+    assert_eq!(1, diffs.len());
+    assert_eq!(DifferenceKind::Value, diffs[0].kind);
+    assert_eq!(nesting, diffs[0].path.len());
+    assert!(diffs[0].path.iter().all(|segment| *segment == PathSegment::Index(0)));
+
+    // Unwrap the arrays one by one, as dropping the whole value at once recurses as well.
+    for mut val in [left, right] {
+        while let Value::Array(mut vec) = val {
+            val = vec.pop().unwrap();
+        }
+    }
+}
+
+#[test]
+fn total_order() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::{BTreeMap, HashSet};
+    use std::hash::{Hash, Hasher};
+
+    use rmpv::Timestamp;
+
+    let sorted = vec![
+        Value::Nil,
+        Value::from(false),
+        Value::from(true),
+        Value::from(i64::MIN),
+        Value::from(-1),
+        Value::from(0),
+        Value::from(u64::MAX),
+        Value::F32(f32::NEG_INFINITY),
+        Value::F32(1.0),
+        Value::F64(-f64::NAN),
+        Value::F64(-0.0),
+        Value::F64(0.0),
+        Value::F64(f64::NAN),
+        Value::from(""),
+        Value::from("a"),
+        Value::from("b"),
+        Value::Binary(vec![0]),
+        Value::Array(vec![]),
+        Value::Array(vec![Value::from(1)]),
+        Value::Array(vec![Value::from(1), Value::Nil]),
+        Value::Map(vec![(Value::from("a"), Value::from(2))]),
+        Value::Ext(-1, vec![0xff]),
+        Value::Ext(2, vec![0x00]),
+        Value::from(Timestamp::new(-1, 0).unwrap()),
+        Value::from(Timestamp::new(1, 0).unwrap()),
+    ];
+    for (idx, val) in sorted.iter().enumerate() {
+        for (other_idx, other) in sorted.iter().enumerate() {
+            assert_eq!(idx.cmp(&other_idx), val.cmp(other), "{val} vs {other}");
+        }
+    }
+
+    fn hash(val: &Value) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        val.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    // Equal floats have equal bits.
+    assert_eq!(Value::F64(f64::NAN), Value::F64(f64::NAN));
+    assert_eq!(hash(&Value::F64(f64::NAN)), hash(&Value::F64(f64::NAN)));
+    assert_ne!(Value::F64(0.0), Value::F64(-0.0));
+    assert_ne!(Value::from(1), Value::F64(1.0));
+    assert_ne!(Value::F32(1.0), Value::F64(1.0));
+
+    let set: HashSet<_> = sorted.iter().chain(sorted.iter()).cloned().collect();
+    assert_eq!(sorted.len(), set.len());
+
+    let mut cache = BTreeMap::new();
+    cache.insert(Value::Array(vec![Value::from("key"), Value::F32(0.5)]), 1);
+    assert_eq!(Some(&1), cache.get(&Value::Array(vec![Value::from("key"), Value::F32(0.5)])));
+}